@@ -0,0 +1,7 @@
+#!/usr/bin/env lua
+-- bin/sublua-cli.lua
+-- Shell access to key derivation, signing, transfers, storage queries and decoding
+-- Usage: lua bin/sublua-cli.lua help
+
+require("sublua")
+os.exit(require("sublua.cli").run(arg))
@@ -0,0 +1,26 @@
+#!/usr/bin/env lua
+-- bin/sublua-codegen.lua
+-- Generate Lua binding modules from chain metadata (see sublua.codegen)
+-- Usage: lua bin/sublua-codegen.lua <node_url|metadata_file> <out_dir> [Pallet ...]
+
+require("sublua")
+local Codegen = require("sublua.codegen")
+
+local source, out_dir = arg[1], arg[2]
+if not source or not out_dir then
+    io.stderr:write("Usage: sublua-codegen <node_url|metadata_file> <out_dir> [Pallet ...]\n")
+    os.exit(2)
+end
+
+local pallets
+if #arg > 2 then
+    pallets = {}
+    for i = 3, #arg do table.insert(pallets, arg[i]) end
+end
+
+local ok, files = pcall(Codegen.generate, source, out_dir, { pallets = pallets })
+if not ok then
+    io.stderr:write("sublua-codegen: " .. tostring(files) .. "\n")
+    os.exit(1)
+end
+for _, path in ipairs(files) do print(path) end
@@ -0,0 +1,168 @@
+/*
+  ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381) on TweetNaCl's curve arithmetic
+  Keys are ed25519 keys: the secret scalar and nonce prefix come from SHA512(seed)
+*/
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "ecvrf.h"
+#include "tweetnacl_ext.h"
+#include "vendor/tweetnacl.h"
+
+#define FOR(i,n) for (i = 0;i < n;++i)
+
+typedef unsigned char u8;
+typedef unsigned long long u64;
+typedef long long i64;
+typedef tweetnacl_gf gf;
+
+/* Group order L, little endian */
+static const u8 L[32] = {0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+                         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10};
+
+static void mul_cofactor(gf p[4])
+{
+  tweetnacl_point_add(p,p);
+  tweetnacl_point_add(p,p);
+  tweetnacl_point_add(p,p);
+}
+
+/* Try-and-increment: first SHA512(suite || 0x01 || pk || alpha || ctr || 0x00) that decodes, times 8 */
+static int hash_to_curve(gf h[4],const u8 *pk,const u8 *alpha,u64 alen)
+{
+  u8 d[64];
+  int ctr;
+  u8 *buf = malloc(alen + 36);
+  if (!buf) return -1;
+  buf[0] = 0x03;
+  buf[1] = 0x01;
+  memcpy(buf + 2, pk, 32);
+  if (alen) memcpy(buf + 34, alpha, alen);
+  buf[35 + alen] = 0x00;
+  for (ctr = 0; ctr < 256; ctr++) {
+    buf[34 + alen] = (u8)ctr;
+    crypto_hash(d, buf, alen + 36);
+    if (tweetnacl_point_unpack(h, d) == 0) {
+      free(buf);
+      mul_cofactor(h);
+      return 0;
+    }
+  }
+  free(buf);
+  return -1;
+}
+
+static void challenge(u8 c[16],const u8 *y,const u8 *h,const u8 *gamma,const u8 *u,const u8 *v)
+{
+  u8 buf[163],d[64];
+  int i;
+  buf[0] = 0x03;
+  buf[1] = 0x02;
+  FOR(i,32) {
+    buf[2 + i] = y[i];
+    buf[34 + i] = h[i];
+    buf[66 + i] = gamma[i];
+    buf[98 + i] = u[i];
+    buf[130 + i] = v[i];
+  }
+  buf[162] = 0x00;
+  crypto_hash(d, buf, 163);
+  FOR(i,16) c[i] = d[i];
+}
+
+int ecvrf_proof_to_hash(u8 beta[64],const u8 pi[80])
+{
+  u8 buf[35];
+  gf g[4];
+  if (tweetnacl_point_unpack(g, pi)) return -1;
+  mul_cofactor(g);
+  buf[0] = 0x03;
+  buf[1] = 0x03;
+  tweetnacl_point_pack(buf + 2, g);
+  buf[34] = 0x00;
+  crypto_hash(beta, buf, 35);
+  return 0;
+}
+
+int ecvrf_prove(u8 pi[80],const u8 seed[32],const u8 *alpha,u64 alen)
+{
+  u8 d[64],pk[32],hs[32],u[32],v[32],kb[64],k[64],c[16];
+  i64 i,j,x[64];
+  gf y[4],h[4],p[4];
+
+  crypto_hash(d, seed, 32);
+  d[0] &= 248;
+  d[31] &= 127;
+  d[31] |= 64;
+  tweetnacl_point_base(y, d);
+  tweetnacl_point_pack(pk, y);
+
+  if (hash_to_curve(h, pk, alpha, alen)) return -1;
+  tweetnacl_point_pack(hs, h);
+  tweetnacl_point_mult(p, h, d);
+  tweetnacl_point_pack(pi, p);
+
+  /* Nonce as in RFC 8032: SHA512(prefix || h_string) mod L */
+  FOR(i,32) {
+    kb[i] = d[32 + i];
+    kb[32 + i] = hs[i];
+  }
+  crypto_hash(k, kb, 64);
+  tweetnacl_scalar_reduce(k);
+  tweetnacl_point_base(p, k);
+  tweetnacl_point_pack(u, p);
+  tweetnacl_point_mult(p, h, k);
+  tweetnacl_point_pack(v, p);
+
+  challenge(c, pk, hs, pi, u, v);
+  FOR(i,16) pi[32 + i] = c[i];
+
+  FOR(i,64) x[i] = 0;
+  FOR(i,32) x[i] = (u64) k[i];
+  FOR(i,16) FOR(j,32) x[i+j] += c[i] * (u64) d[j];
+  tweetnacl_scalar_modl(pi + 48, x);
+  return 0;
+}
+
+int ecvrf_verify(u8 beta[64],const u8 pk[32],const u8 pi[80],const u8 *alpha,u64 alen)
+{
+  static const u8 identity[32] = {1};
+  u8 hs[32],u[32],v[32],c[16],c32[32],t8[32];
+  int i;
+  gf y[4],g[4],h[4],t[4],p[4],q[4];
+
+  if (tweetnacl_point_unpack(y, pk)) return -1;
+  memcpy(t, y, sizeof t);
+  mul_cofactor(t);
+  tweetnacl_point_pack(t8, t);
+  if (crypto_verify_32(t8, identity) == 0) return -1;
+  if (tweetnacl_point_unpack(g, pi)) return -1;
+  /* s must be below L */
+  for (i = 31; i >= 0; i--) {
+    if (pi[48 + i] < L[i]) break;
+    if (pi[48 + i] > L[i] || i == 0) return -1;
+  }
+
+  if (hash_to_curve(h, pk, alpha, alen)) return -1;
+  tweetnacl_point_pack(hs, h);
+  FOR(i,32) c32[i] = i < 16 ? pi[32 + i] : 0;
+
+  /* U = s*B - c*Y */
+  tweetnacl_point_base(p, pi + 48);
+  tweetnacl_point_mult(q, y, c32);
+  tweetnacl_point_neg(q);
+  tweetnacl_point_add(p, q);
+  tweetnacl_point_pack(u, p);
+
+  /* V = s*H - c*Gamma */
+  tweetnacl_point_mult(p, h, pi + 48);
+  tweetnacl_point_mult(q, g, c32);
+  tweetnacl_point_neg(q);
+  tweetnacl_point_add(p, q);
+  tweetnacl_point_pack(v, p);
+
+  challenge(c, pk, hs, pi, u, v);
+  if (crypto_verify_16(c, pi + 32)) return -1;
+  return ecvrf_proof_to_hash(beta, pi);
+}
@@ -0,0 +1,16 @@
+/*
+  ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381) over ed25519 keys
+*/
+
+#ifndef ECVRF_H
+#define ECVRF_H
+
+/* pi = Gamma(32) || c(16) || s(32) for the ed25519 key of a 32-byte seed; 0 on success */
+int ecvrf_prove(unsigned char pi[80], const unsigned char seed[32], const unsigned char *alpha, unsigned long long alen);
+/* 0 and beta when pi proves alpha under pk, -1 otherwise */
+int ecvrf_verify(unsigned char beta[64], const unsigned char pk[32], const unsigned char pi[80],
+                 const unsigned char *alpha, unsigned long long alen);
+/* beta = SHA512(suite || 0x03 || 8 * Gamma || 0x00); -1 when Gamma does not decode */
+int ecvrf_proof_to_hash(unsigned char beta[64], const unsigned char pi[80]);
+
+#endif
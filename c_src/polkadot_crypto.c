@@ -10,6 +10,8 @@
 #include <string.h>
 #include <stdlib.h>
 
+#include "polkadot_crypto.h"
+#include "ecvrf.h"
 #include "vendor/monocypher.h"
 #include "vendor/tweetnacl.h"
 #define XXH_INLINE_ALL
@@ -73,6 +75,234 @@ static int l_twox64(lua_State *L) {
     return 1;
 }
 
+static int l_twox256(lua_State *L) {
+    size_t len;
+    const char *data = luaL_checklstring(L, 1, &len);
+    
+    // Substrate twox_256: XXH64 with seeds 0..3, each little endian
+    uint8_t out[32];
+    for (int s = 0; s < 4; s++) {
+        uint64_t h = XXH64(data, len, s);
+        for (int i = 0; i < 8; i++) out[s*8 + i] = (h >> (i*8)) & 0xFF;
+    }
+    
+    lua_pushlstring(L, (const char*)out, 32);
+    return 1;
+}
+
+/* --- SHA-256 (FIPS 180-4, minimal C implementation) --- */
+
+static const uint32_t SHA256_K[64] = {
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+};
+
+#define ROTR32(x, n) (((x) >> (n)) | ((x) << (32 - (n))))
+
+static void sha256_block(uint32_t st[8], const uint8_t *p) {
+    uint32_t w[64];
+    for (int i = 0; i < 16; i++) {
+        w[i] = ((uint32_t)p[i*4] << 24) | ((uint32_t)p[i*4+1] << 16) | ((uint32_t)p[i*4+2] << 8) | p[i*4+3];
+    }
+    for (int i = 16; i < 64; i++) {
+        uint32_t s0 = ROTR32(w[i-15], 7) ^ ROTR32(w[i-15], 18) ^ (w[i-15] >> 3);
+        uint32_t s1 = ROTR32(w[i-2], 17) ^ ROTR32(w[i-2], 19) ^ (w[i-2] >> 10);
+        w[i] = w[i-16] + s0 + w[i-7] + s1;
+    }
+    uint32_t a = st[0], b = st[1], c = st[2], d = st[3], e = st[4], f = st[5], g = st[6], h = st[7];
+    for (int i = 0; i < 64; i++) {
+        uint32_t S1 = ROTR32(e, 6) ^ ROTR32(e, 11) ^ ROTR32(e, 25);
+        uint32_t ch = (e & f) ^ (~e & g);
+        uint32_t t1 = h + S1 + ch + SHA256_K[i] + w[i];
+        uint32_t S0 = ROTR32(a, 2) ^ ROTR32(a, 13) ^ ROTR32(a, 22);
+        uint32_t maj = (a & b) ^ (a & c) ^ (b & c);
+        uint32_t t2 = S0 + maj;
+        h = g; g = f; f = e; e = d + t1; d = c; c = b; b = a; a = t1 + t2;
+    }
+    st[0] += a; st[1] += b; st[2] += c; st[3] += d; st[4] += e; st[5] += f; st[6] += g; st[7] += h;
+}
+
+static void sha256(uint8_t out[32], const uint8_t *data, size_t len) {
+    uint32_t st[8] = {
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19
+    };
+    size_t full = len / 64;
+    for (size_t i = 0; i < full; i++) sha256_block(st, data + i*64);
+    
+    // Padding: 0x80, zeros, 64-bit big-endian bit length
+    uint8_t tail[128];
+    size_t rem = len - full*64;
+    memset(tail, 0, sizeof(tail));
+    memcpy(tail, data + full*64, rem);
+    tail[rem] = 0x80;
+    size_t tail_len = (rem < 56) ? 64 : 128;
+    uint64_t bits = (uint64_t)len * 8;
+    for (int i = 0; i < 8; i++) tail[tail_len - 1 - i] = (bits >> (i*8)) & 0xFF;
+    for (size_t i = 0; i < tail_len; i += 64) sha256_block(st, tail + i);
+    
+    for (int i = 0; i < 8; i++) {
+        out[i*4] = st[i] >> 24; out[i*4+1] = st[i] >> 16; out[i*4+2] = st[i] >> 8; out[i*4+3] = st[i];
+    }
+}
+
+static int l_sha256(lua_State *L) {
+    size_t len;
+    const char *data = luaL_checklstring(L, 1, &len);
+    uint8_t out[32];
+    sha256(out, (const uint8_t*)data, len);
+    lua_pushlstring(L, (const char*)out, 32);
+    return 1;
+}
+
+/* --- SHA-512 (TweetNaCl) --- */
+
+static int l_sha512(lua_State *L) {
+    size_t len;
+    const char *data = luaL_checklstring(L, 1, &len);
+    uint8_t out[64];
+    crypto_hash_sha512(out, (const unsigned char*)data, len);
+    lua_pushlstring(L, (const char*)out, 64);
+    return 1;
+}
+
+/* --- Keccak-256 (original Keccak padding, as used by Ethereum) --- */
+
+static const uint64_t KECCAK_RC[24] = {
+    0x0000000000000001ULL, 0x0000000000008082ULL, 0x800000000000808aULL, 0x8000000080008000ULL,
+    0x000000000000808bULL, 0x0000000080000001ULL, 0x8000000080008081ULL, 0x8000000000008009ULL,
+    0x000000000000008aULL, 0x0000000000000088ULL, 0x0000000080008009ULL, 0x000000008000000aULL,
+    0x000000008000808bULL, 0x800000000000008bULL, 0x8000000000008089ULL, 0x8000000000008003ULL,
+    0x8000000000008002ULL, 0x8000000000000080ULL, 0x000000000000800aULL, 0x800000008000000aULL,
+    0x8000000080008081ULL, 0x8000000000008080ULL, 0x0000000080000001ULL, 0x8000000080008008ULL
+};
+
+static const int KECCAK_ROT[25] = {
+     0,  1, 62, 28, 27,
+    36, 44,  6, 55, 20,
+     3, 10, 43, 25, 39,
+    41, 45, 15, 21,  8,
+    18,  2, 61, 56, 14
+};
+
+#define ROTL64(x, n) ((n) == 0 ? (x) : (((x) << (n)) | ((x) >> (64 - (n)))))
+
+static void keccak_f1600(uint64_t a[25]) {
+    for (int round = 0; round < 24; round++) {
+        uint64_t c[5], d[5], b[25];
+        // Theta
+        for (int x = 0; x < 5; x++) c[x] = a[x] ^ a[x+5] ^ a[x+10] ^ a[x+15] ^ a[x+20];
+        for (int x = 0; x < 5; x++) d[x] = c[(x+4) % 5] ^ ROTL64(c[(x+1) % 5], 1);
+        for (int i = 0; i < 25; i++) a[i] ^= d[i % 5];
+        // Rho + Pi
+        for (int x = 0; x < 5; x++) {
+            for (int y = 0; y < 5; y++) {
+                b[y + 5*((2*x + 3*y) % 5)] = ROTL64(a[x + 5*y], KECCAK_ROT[x + 5*y]);
+            }
+        }
+        // Chi
+        for (int y = 0; y < 5; y++) {
+            for (int x = 0; x < 5; x++) {
+                a[x + 5*y] = b[x + 5*y] ^ (~b[(x+1) % 5 + 5*y] & b[(x+2) % 5 + 5*y]);
+            }
+        }
+        // Iota
+        a[0] ^= KECCAK_RC[round];
+    }
+}
+
+static void keccak256(uint8_t out[32], const uint8_t *data, size_t len) {
+    const size_t rate = 136;
+    uint64_t st[25];
+    memset(st, 0, sizeof(st));
+    
+    uint8_t block[136];
+    while (1) {
+        size_t take = len < rate ? len : rate;
+        memset(block, 0, rate);
+        memcpy(block, data, take);
+        if (take < rate) {
+            // Final block: Keccak pad10*1 with domain byte 0x01
+            block[take] ^= 0x01;
+            block[rate - 1] ^= 0x80;
+        }
+        for (size_t i = 0; i < rate / 8; i++) {
+            uint64_t lane = 0;
+            for (int j = 0; j < 8; j++) lane |= (uint64_t)block[i*8 + j] << (8*j);
+            st[i] ^= lane;
+        }
+        keccak_f1600(st);
+        if (take < rate) break;
+        data += take;
+        len -= take;
+    }
+    
+    for (int i = 0; i < 32; i++) out[i] = (st[i / 8] >> (8 * (i % 8))) & 0xFF;
+}
+
+static int l_keccak256(lua_State *L) {
+    size_t len;
+    const char *data = luaL_checklstring(L, 1, &len);
+    uint8_t out[32];
+    keccak256(out, (const uint8_t*)data, len);
+    lua_pushlstring(L, (const char*)out, 32);
+    return 1;
+}
+
+/* --- Unified hash(algorithm, data) --- */
+/* Algorithms: blake2_128/256/512, twox64/128/256, keccak256, sha256, identity,
+   and the storage "concat" hashers blake2_128_concat / twox64_concat */
+
+static int l_hash(lua_State *L) {
+    const char *alg = luaL_checkstring(L, 1);
+    size_t len;
+    const char *data = luaL_checklstring(L, 2, &len);
+    const uint8_t *in = (const uint8_t*)data;
+    
+    uint8_t out[64];
+    size_t out_len;
+    int concat = 0;
+    
+    if (strcmp(alg, "blake2_128") == 0 || strcmp(alg, "blake2_128_concat") == 0) {
+        crypto_blake2b(out, 16, in, len); out_len = 16;
+        concat = alg[10] != 0;
+    } else if (strcmp(alg, "blake2_256") == 0) {
+        crypto_blake2b(out, 32, in, len); out_len = 32;
+    } else if (strcmp(alg, "blake2_512") == 0) {
+        crypto_blake2b(out, 64, in, len); out_len = 64;
+    } else if (strcmp(alg, "twox64") == 0 || strcmp(alg, "twox64_concat") == 0 ||
+               strcmp(alg, "twox128") == 0 || strcmp(alg, "twox256") == 0) {
+        int seeds = (alg[4] == '6') ? 1 : (alg[4] == '1') ? 2 : 4;
+        for (int s = 0; s < seeds; s++) {
+            uint64_t h = XXH64(data, len, s);
+            for (int i = 0; i < 8; i++) out[s*8 + i] = (h >> (i*8)) & 0xFF;
+        }
+        out_len = seeds * 8;
+        concat = strcmp(alg, "twox64_concat") == 0;
+    } else if (strcmp(alg, "keccak256") == 0) {
+        keccak256(out, in, len); out_len = 32;
+    } else if (strcmp(alg, "sha256") == 0) {
+        sha256(out, in, len); out_len = 32;
+    } else if (strcmp(alg, "identity") == 0) {
+        lua_pushlstring(L, data, len);
+        return 1;
+    } else {
+        return luaL_error(L, "Unknown hash algorithm: %s", alg);
+    }
+    
+    lua_pushlstring(L, (const char*)out, out_len);
+    if (concat) {
+        lua_pushlstring(L, data, len);
+        lua_concat(L, 2);
+    }
+    return 1;
+}
+
 /* --- Ed25519 (TweetNaCl — standard SHA-512, RFC 8032) --- */
 
 static int l_ed25519_keypair_from_seed(lua_State *L) {
@@ -87,7 +317,7 @@ static int l_ed25519_keypair_from_seed(lua_State *L) {
     unsigned char sk[64];
     tweetnacl_keypair_from_seed(pub, sk, (const unsigned char*)seed);
     
-    memset(sk, 0, 64);
+    crypto_wipe(sk, 64);
     lua_pushlstring(L, (const char*)pub, 32);
     return 1;
 }
@@ -115,7 +345,7 @@ static int l_ed25519_sign(lua_State *L) {
     lua_pushlstring(L, (const char*)sm, 64);
     
     free(sm);
-    memset(sk, 0, 64);
+    crypto_wipe(sk, 64);
     return 1;
 }
 
@@ -146,6 +376,82 @@ static int l_ed25519_verify(lua_State *L) {
     return 1;
 }
 
+/* --- Key handles: secret key kept in C memory, wiped on destroy/GC --- */
+
+#define KEYPAIR_MT "polkadot_crypto.keypair"
+
+typedef sublua_keypair_t keypair_t;
+
+static keypair_t *check_keypair(lua_State *L, int idx) {
+    keypair_t *kp = (keypair_t*)luaL_checkudata(L, idx, KEYPAIR_MT);
+    if (kp->abi_version != SUBLUA_ABI_VERSION) luaL_error(L, "Keypair handle has an incompatible layout");
+    if (kp->destroyed) luaL_error(L, "Keypair handle has been destroyed");
+    return kp;
+}
+
+static int l_keypair_create(lua_State *L) {
+    size_t seed_len;
+    const char *seed = luaL_checklstring(L, 1, &seed_len);
+    if (seed_len != 32) return luaL_error(L, "Seed must be 32 bytes");
+    
+    keypair_t *kp = (keypair_t*)lua_newuserdata(L, sizeof(keypair_t));
+    kp->abi_version = SUBLUA_ABI_VERSION;
+    kp->destroyed = 0;
+    tweetnacl_keypair_from_seed(kp->pub, kp->sk, (const unsigned char*)seed);
+    luaL_getmetatable(L, KEYPAIR_MT);
+    lua_setmetatable(L, -2);
+    return 1;
+}
+
+static int l_keypair_public(lua_State *L) {
+    keypair_t *kp = check_keypair(L, 1);
+    lua_pushlstring(L, (const char*)kp->pub, 32);
+    return 1;
+}
+
+static int l_keypair_sign(lua_State *L) {
+    keypair_t *kp = check_keypair(L, 1);
+    size_t msg_len;
+    const char *msg = luaL_checklstring(L, 2, &msg_len);
+    
+    unsigned long long smlen;
+    unsigned char *sm = (unsigned char*)malloc(msg_len + 64);
+    if (!sm) return luaL_error(L, "out of memory");
+    
+    crypto_sign(sm, &smlen, (const unsigned char*)msg, msg_len, kp->sk);
+    lua_pushlstring(L, (const char*)sm, 64);
+    free(sm);
+    return 1;
+}
+
+static int l_keypair_destroy(lua_State *L) {
+    keypair_t *kp = (keypair_t*)luaL_checkudata(L, 1, KEYPAIR_MT);
+    crypto_wipe(kp->sk, 64);
+    kp->destroyed = 1;
+    return 0;
+}
+
+static int l_keypair_tostring(lua_State *L) {
+    keypair_t *kp = (keypair_t*)luaL_checkudata(L, 1, KEYPAIR_MT);
+    lua_pushstring(L, kp->destroyed ? "keypair (destroyed)" : "keypair");
+    return 1;
+}
+
+static void register_keypair_mt(lua_State *L) {
+    luaL_newmetatable(L, KEYPAIR_MT);
+    
+    lua_newtable(L);
+    lua_pushcfunction(L, l_keypair_sign);    lua_setfield(L, -2, "sign");
+    lua_pushcfunction(L, l_keypair_public);  lua_setfield(L, -2, "public_key");
+    lua_pushcfunction(L, l_keypair_destroy); lua_setfield(L, -2, "destroy");
+    lua_setfield(L, -2, "__index");
+    
+    lua_pushcfunction(L, l_keypair_destroy);  lua_setfield(L, -2, "__gc");
+    /* Lua 5.4: local key <close> = crypto.keypair_create(seed) wipes at scope exit */
+    lua_pushcfunction(L, l_keypair_destroy);  lua_setfield(L, -2, "__close");
+    lua_pushcfunction(L, l_keypair_tostring); lua_setfield(L, -2, "__tostring");
+    lua_pop(L, 1);
+}
 
 /* --- SS58 Encode/Decode (Minimal C Implementation) --- */
 
@@ -314,16 +620,168 @@ static int l_ss58_decode(lua_State *L) {
     }
 }
 
+/* --- VRF: ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381) over ed25519 keys --- */
+
+/* vrf_prove(seed32, alpha) -> proof(80), output(64) */
+static int l_vrf_prove(lua_State *L) {
+    size_t seed_len, alpha_len;
+    const char *seed = luaL_checklstring(L, 1, &seed_len);
+    const char *alpha = luaL_checklstring(L, 2, &alpha_len);
+
+    if (seed_len != 32) {
+        return luaL_error(L, "Seed must be 32 bytes");
+    }
+    uint8_t pi[80], beta[64];
+    if (ecvrf_prove(pi, (const unsigned char*)seed, (const unsigned char*)alpha, alpha_len) != 0 ||
+        ecvrf_proof_to_hash(beta, pi) != 0) {
+        return luaL_error(L, "VRF proof generation failed");
+    }
+    lua_pushlstring(L, (const char*)pi, 80);
+    lua_pushlstring(L, (const char*)beta, 64);
+    return 2;
+}
+
+/* vrf_verify(pubkey32, alpha, proof80) -> output(64), or nil if the proof is invalid */
+static int l_vrf_verify(lua_State *L) {
+    size_t pk_len, alpha_len, pi_len;
+    const char *pk = luaL_checklstring(L, 1, &pk_len);
+    const char *alpha = luaL_checklstring(L, 2, &alpha_len);
+    const char *pi = luaL_checklstring(L, 3, &pi_len);
+
+    if (pk_len != 32 || pi_len != 80) {
+        lua_pushnil(L);
+        return 1;
+    }
+    uint8_t beta[64];
+    if (ecvrf_verify(beta, (const unsigned char*)pk, (const unsigned char*)pi,
+                     (const unsigned char*)alpha, alpha_len) != 0) {
+        lua_pushnil(L);
+        return 1;
+    }
+    lua_pushlstring(L, (const char*)beta, 64);
+    return 1;
+}
+
+/* --- Password hashing and authenticated encryption (Monocypher) --- */
+
+/* argon2id(password, salt, [kib = 65536], [passes = 3]) -> 32-byte key */
+static int l_argon2id(lua_State *L) {
+    size_t pass_len, salt_len;
+    const char *pass = luaL_checklstring(L, 1, &pass_len);
+    const char *salt = luaL_checklstring(L, 2, &salt_len);
+    lua_Integer kib = luaL_optinteger(L, 3, 65536);
+    lua_Integer passes = luaL_optinteger(L, 4, 3);
+
+    if (salt_len < 8) {
+        return luaL_error(L, "Salt must be at least 8 bytes");
+    }
+    if (kib < 8 || kib > 4194304 || passes < 1 || passes > 64) {
+        return luaL_error(L, "Argon2 cost out of range");
+    }
+    void *work_area = malloc((size_t)kib * 1024);
+    if (!work_area) {
+        return luaL_error(L, "Out of memory for Argon2 work area");
+    }
+    crypto_argon2_config config = { CRYPTO_ARGON2_ID, (uint32_t)kib, (uint32_t)passes, 1 };
+    crypto_argon2_inputs inputs = { (const uint8_t*)pass, (const uint8_t*)salt,
+                                    (uint32_t)pass_len, (uint32_t)salt_len };
+    uint8_t key[32];
+    crypto_argon2(key, 32, work_area, config, inputs, crypto_argon2_no_extras);
+    free(work_area);
+    lua_pushlstring(L, (const char*)key, 32);
+    crypto_wipe(key, 32);
+    return 1;
+}
+
+/* aead_lock(key32, nonce24, plaintext, [ad]) -> mac(16) || ciphertext (XChaCha20-Poly1305) */
+static int l_aead_lock(lua_State *L) {
+    size_t key_len, nonce_len, text_len, ad_len = 0;
+    const char *key = luaL_checklstring(L, 1, &key_len);
+    const char *nonce = luaL_checklstring(L, 2, &nonce_len);
+    const char *text = luaL_checklstring(L, 3, &text_len);
+    const char *ad = luaL_optlstring(L, 4, "", &ad_len);
+
+    if (key_len != 32 || nonce_len != 24) {
+        return luaL_error(L, "Key must be 32 bytes and nonce 24 bytes");
+    }
+    uint8_t *out = malloc(16 + text_len);
+    if (!out) {
+        return luaL_error(L, "Out of memory");
+    }
+    crypto_aead_lock(out + 16, out, (const uint8_t*)key, (const uint8_t*)nonce,
+                     (const uint8_t*)ad, ad_len, (const uint8_t*)text, text_len);
+    lua_pushlstring(L, (const char*)out, 16 + text_len);
+    free(out);
+    return 1;
+}
+
+/* aead_unlock(key32, nonce24, mac || ciphertext, [ad]) -> plaintext, or nil if forged/wrong key */
+static int l_aead_unlock(lua_State *L) {
+    size_t key_len, nonce_len, box_len, ad_len = 0;
+    const char *key = luaL_checklstring(L, 1, &key_len);
+    const char *nonce = luaL_checklstring(L, 2, &nonce_len);
+    const char *box = luaL_checklstring(L, 3, &box_len);
+    const char *ad = luaL_optlstring(L, 4, "", &ad_len);
+
+    if (key_len != 32 || nonce_len != 24) {
+        return luaL_error(L, "Key must be 32 bytes and nonce 24 bytes");
+    }
+    if (box_len < 16) {
+        lua_pushnil(L);
+        return 1;
+    }
+    size_t text_len = box_len - 16;
+    uint8_t *out = malloc(text_len + 1);
+    if (!out) {
+        return luaL_error(L, "Out of memory");
+    }
+    int rc = crypto_aead_unlock(out, (const uint8_t*)box, (const uint8_t*)key, (const uint8_t*)nonce,
+                                (const uint8_t*)ad, ad_len, (const uint8_t*)box + 16, text_len);
+    if (rc != 0) {
+        lua_pushnil(L);
+    } else {
+        lua_pushlstring(L, (const char*)out, text_len);
+    }
+    crypto_wipe(out, text_len);
+    free(out);
+    return 1;
+}
+
+/* --- ABI version --- */
+
+unsigned int sublua_abi_version(void) {
+    return SUBLUA_ABI_VERSION;
+}
+
+static int l_abi_version(lua_State *L) {
+    lua_pushinteger(L, (lua_Integer)sublua_abi_version());
+    return 1;
+}
 
 static const struct luaL_Reg polkadot_crypto [] = {
     {"blake2b", l_blake2b},
     {"twox128", l_twox128},
     {"twox64", l_twox64},
+    {"twox256", l_twox256},
+    {"sha256", l_sha256},
+    {"sha512", l_sha512},
+    {"keccak256", l_keccak256},
+    {"hash", l_hash},
     {"ed25519_keypair_from_seed", l_ed25519_keypair_from_seed},
     {"ed25519_sign", l_ed25519_sign},
     {"ed25519_verify", l_ed25519_verify},
+    {"keypair_create", l_keypair_create},
+    {"keypair_sign", l_keypair_sign},
+    {"keypair_public", l_keypair_public},
+    {"keypair_destroy", l_keypair_destroy},
     {"ss58_encode", l_ss58_encode},
     {"ss58_decode", l_ss58_decode},
+    {"argon2id", l_argon2id},
+    {"aead_lock", l_aead_lock},
+    {"aead_unlock", l_aead_unlock},
+    {"vrf_prove", l_vrf_prove},
+    {"vrf_verify", l_vrf_verify},
+    {"abi_version", l_abi_version},
     {NULL, NULL}
 };
 
@@ -332,11 +790,15 @@ static int polkadot_crypto_init(lua_State *L) {
     lua_pushstring(L, "Pure C (Monocypher + TweetNaCl + xxHash)");
     lua_setglobal(L, "_POLKADOT_CRYPTO_IMPL");
     
+    register_keypair_mt(L);
+    
     #if LUA_VERSION_NUM >= 502
         luaL_newlib(L, polkadot_crypto);
     #else
         luaL_register(L, "polkadot_crypto", polkadot_crypto);
     #endif
+    lua_pushinteger(L, SUBLUA_ABI_VERSION);
+    lua_setfield(L, -2, "ABI_VERSION");
     return 1;
 }
 
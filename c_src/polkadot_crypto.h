@@ -0,0 +1,25 @@
+/*
+  Public interface of the polkadot_crypto C module
+  SUBLUA_ABI_VERSION changes whenever an exported function, its Lua signature or a
+  shared struct layout changes; sublua/init.lua refuses a module built for another ABI
+  instead of failing later in a confusing way. Everything between the FFI markers is
+  plain C that LuaJIT's ffi.cdef accepts as is (see sublua/abi.lua)
+*/
+
+#ifndef POLKADOT_CRYPTO_H
+#define POLKADOT_CRYPTO_H
+
+#define SUBLUA_ABI_VERSION 3
+
+/* FFI BEGIN */
+typedef struct {
+    unsigned int abi_version;   /* SUBLUA_ABI_VERSION the handle was created with */
+    unsigned char sk[64];       /* TweetNaCl secret key: seed(32) || pubkey(32) */
+    unsigned char pub[32];
+    int destroyed;
+} sublua_keypair_t;
+
+unsigned int sublua_abi_version(void);
+/* FFI END */
+
+#endif
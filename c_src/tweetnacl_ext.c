@@ -0,0 +1,32 @@
+/*
+  TweetNaCl with its point and scalar primitives exported for ecvrf.c
+  vendor/tweetnacl.c is compiled through this file, not on its own, so its static
+  functions are reachable without patching the vendored source
+*/
+
+#include "vendor/tweetnacl.c"
+#include "tweetnacl_ext.h"
+
+int tweetnacl_point_unpack(gf p[4],const u8 s[32])
+{
+  if (unpackneg(p,s)) return -1;
+  Z(p[0],gf0,p[0]);
+  Z(p[3],gf0,p[3]);
+  return 0;
+}
+
+void tweetnacl_point_pack(u8 r[32],gf p[4]) { pack(r,p); }
+void tweetnacl_point_add(gf p[4],gf q[4]) { add(p,q); }
+void tweetnacl_point_neg(gf p[4]) { Z(p[0],gf0,p[0]); Z(p[3],gf0,p[3]); }
+void tweetnacl_point_base(gf p[4],const u8 s[32]) { scalarbase(p,s); }
+
+void tweetnacl_point_mult(gf p[4],gf q[4],const u8 s[32])
+{
+  gf t[4];
+  int i;
+  FOR(i,4) set25519(t[i],q[i]);
+  scalarmult(p,t,s);
+}
+
+void tweetnacl_scalar_reduce(u8 r[64]) { reduce(r); }
+void tweetnacl_scalar_modl(u8 r[32],i64 x[64]) { modL(r,x); }
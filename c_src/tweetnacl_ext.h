@@ -0,0 +1,27 @@
+/*
+  Edwards25519 point and scalar primitives of vendor/tweetnacl.c, exported by
+  tweetnacl_ext.c for ecvrf.c
+  A point is extended coordinates (X, Y, Z, T), each a 16-limb field element
+*/
+
+#ifndef TWEETNACL_EXT_H
+#define TWEETNACL_EXT_H
+
+typedef long long tweetnacl_gf[16];
+
+/* Decode a compressed point; -1 when it is not on the curve */
+int tweetnacl_point_unpack(tweetnacl_gf p[4], const unsigned char s[32]);
+void tweetnacl_point_pack(unsigned char r[32], tweetnacl_gf p[4]);
+/* p += q */
+void tweetnacl_point_add(tweetnacl_gf p[4], tweetnacl_gf q[4]);
+void tweetnacl_point_neg(tweetnacl_gf p[4]);
+/* p = s * B */
+void tweetnacl_point_base(tweetnacl_gf p[4], const unsigned char s[32]);
+/* p = s * q (q is left as it is) */
+void tweetnacl_point_mult(tweetnacl_gf p[4], tweetnacl_gf q[4], const unsigned char s[32]);
+/* r[0..31] = r (64 bytes, little endian) mod L */
+void tweetnacl_scalar_reduce(unsigned char r[64]);
+/* r = x mod L, x as 64 wide limbs */
+void tweetnacl_scalar_modl(unsigned char r[32], long long x[64]);
+
+#endif
@@ -0,0 +1,41 @@
+-- sublua/abi.lua
+-- ABI compatibility between the Lua modules and the polkadot_crypto C module
+-- VERSION must match SUBLUA_ABI_VERSION in c_src/polkadot_crypto.h; a stale build left
+-- over from an older release is rejected at load time with a rebuild hint
+
+local Abi = {}
+
+Abi.VERSION = 3
+
+-- Check a loaded C module
+-- Returns true, or false, err
+function Abi.check(crypto)
+    local version = type(crypto) == "table" and crypto.abi_version and crypto.abi_version()
+    if not version then
+        return false, "polkadot_crypto C module predates ABI versioning; rebuild it (make build or luarocks make)"
+    end
+    if version ~= Abi.VERSION then
+        return false, "polkadot_crypto C module has ABI v" .. version .. ", SubLua needs v" .. Abi.VERSION
+            .. "; rebuild it (make build or luarocks make)"
+    end
+    return true
+end
+
+-- Declarations for LuaJIT's ffi.cdef, taken from the FFI section of the C header
+-- header_path: default c_src/polkadot_crypto.h next to this source tree
+-- Returns the declarations and the header's ABI version
+function Abi.cdef(header_path)
+    if not header_path then
+        local dir = (debug.getinfo(1, "S").source:match("^@(.*[/\\])") or "./")
+        header_path = dir .. "../c_src/polkadot_crypto.h"
+    end
+    local f, err = io.open(header_path, "rb")
+    if not f then error("Cannot read C header: " .. tostring(err)) end
+    local text = f:read("*a")
+    f:close()
+    local decls = text:match("/%* FFI BEGIN %*/\n(.-)/%* FFI END %*/")
+    if not decls then error("No FFI section in " .. header_path) end
+    return decls, tonumber(text:match("#define SUBLUA_ABI_VERSION (%d+)"))
+end
+
+return Abi
@@ -0,0 +1,268 @@
+-- sublua/address.lua
+-- SS58 address inspection, validation and conversion between network prefixes and EVM (H160) accounts
+-- Covers the full prefix range (0-16383, two-byte prefixes from 64) for 32-byte account ids
+-- Format: base58(prefix ++ account_id ++ blake2_512("SS58PRE" ++ prefix ++ account_id)[1..2])
+
+local crypto = require("polkadot_crypto")
+
+local Address = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Well-known network prefixes
+Address.PREFIXES = {
+    polkadot = 0,
+    kusama = 2,
+    substrate = 42
+}
+
+local MAX_PREFIX = 16383
+
+-- ============================================================
+-- Base58
+-- ============================================================
+
+local ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+local DIGITS = {}
+for i = 1, #ALPHABET do DIGITS[ALPHABET:sub(i, i)] = i - 1 end
+
+-- Convert between bases on little-endian digit lists
+local function rebase(digits, from, to)
+    local out = {}
+    for i = #digits, 1, -1 do
+        local carry = digits[i]
+        for j = 1, #out do
+            local x = out[j] * from + carry
+            out[j] = x % to
+            carry = math.floor(x / to)
+        end
+        while carry > 0 do
+            table.insert(out, carry % to)
+            carry = math.floor(carry / to)
+        end
+    end
+    return out
+end
+
+local function base58_encode(bytes)
+    local digits = {}
+    for i = 1, #bytes do digits[#bytes - i + 1] = string.byte(bytes, i) end
+    local out = {}
+    for _, d in ipairs(rebase(digits, 256, 58)) do table.insert(out, 1, ALPHABET:sub(d + 1, d + 1)) end
+    local zeros = 0
+    while string.byte(bytes, zeros + 1) == 0 do zeros = zeros + 1 end
+    return string.rep("1", zeros) .. table.concat(out)
+end
+
+local function base58_decode(text)
+    local digits = {}
+    for i = #text, 1, -1 do
+        local d = DIGITS[text:sub(i, i)]
+        if not d then return nil, "Invalid base58 character '" .. text:sub(i, i) .. "'" end
+        table.insert(digits, d)
+    end
+    local out = {}
+    for _, b in ipairs(rebase(digits, 58, 256)) do table.insert(out, 1, string.char(b)) end
+    local ones = text:match("^1*")
+    return string.rep("\0", #ones) .. table.concat(out)
+end
+
+-- ============================================================
+-- SS58
+-- ============================================================
+
+local function checksum(payload)
+    return crypto.blake2b("SS58PRE" .. payload, 64):sub(1, 2)
+end
+
+local function encode_prefix(prefix)
+    if prefix < 64 then return string.char(prefix) end
+    local first = math.floor((prefix % 256) / 4) + 64
+    local second = math.floor(prefix / 256) + (prefix % 4) * 64
+    return string.char(first, second)
+end
+
+-- Public key as raw bytes from raw bytes or "0x" hex
+local function raw_key(pubkey)
+    if #pubkey ~= 32 and pubkey:match("^0x%x+$") then pubkey = from_hex(pubkey) end
+    assert(#pubkey == 32, "Public key must be 32 bytes")
+    return pubkey
+end
+
+-- Encode a 32-byte public key (raw or "0x" hex) as an SS58 address
+function Address.encode(pubkey, prefix)
+    prefix = Address.PREFIXES[prefix] or prefix
+    assert(type(prefix) == "number" and prefix >= 0 and prefix <= MAX_PREFIX and prefix % 1 == 0,
+        "SS58 prefix must be an integer from 0 to " .. MAX_PREFIX)
+    local payload = encode_prefix(prefix) .. raw_key(pubkey)
+    return base58_encode(payload .. checksum(payload))
+end
+
+-- Inspect an address without failing
+-- address: SS58 address, "0x" hex public key, or raw 32-byte public key
+-- Returns { valid, format = "ss58"|"hex"|"raw", prefix, network, pubkey = "0x..",
+--   checksum_valid, error }
+--   prefix/checksum_valid are nil for hex and raw keys; network names well-known prefixes
+function Address.inspect(address)
+    local info = { valid = false }
+    if type(address) ~= "string" then
+        info.error = "Address must be a string"
+        return info
+    end
+    if #address == 32 and not address:match("^[%w]+$") then
+        info.format, info.valid, info.pubkey = "raw", true, "0x" .. to_hex(address)
+        return info
+    end
+    if address:match("^0x") then
+        info.format = "hex"
+        if not address:match("^0x" .. string.rep("%x", 64) .. "$") then
+            info.error = "Hex public key must be 32 bytes"
+            return info
+        end
+        info.valid, info.pubkey = true, address:lower()
+        return info
+    end
+
+    info.format = "ss58"
+    local data, err = base58_decode(address)
+    if not data then
+        info.error = err
+        return info
+    end
+    local first = string.byte(data, 1)
+    if not first or first >= 128 then
+        info.error = "Invalid SS58 prefix byte"
+        return info
+    end
+    local prefix_len = first < 64 and 1 or 2
+    if #data ~= prefix_len + 34 then
+        info.error = "Unsupported SS58 length: " .. #data .. " bytes (expected a 32-byte account id)"
+        return info
+    end
+    if prefix_len == 1 then
+        info.prefix = first
+    else
+        local second = string.byte(data, 2)
+        info.prefix = (first - 64) * 4 + math.floor(second / 64) + (second % 64) * 256
+    end
+    for name, prefix in pairs(Address.PREFIXES) do
+        if prefix == info.prefix then info.network = name end
+    end
+    local payload = data:sub(1, prefix_len + 32)
+    info.pubkey = "0x" .. to_hex(payload:sub(prefix_len + 1))
+    info.checksum_valid = checksum(payload) == data:sub(-2)
+    if not info.checksum_valid then
+        info.error = "Invalid SS58 checksum"
+        return info
+    end
+    info.valid = true
+    return info
+end
+
+-- Decode an SS58 address; returns raw public key, prefix
+function Address.decode(address)
+    local info = Address.inspect(address)
+    if info.format ~= "ss58" then error("Not an SS58 address") end
+    if not info.valid then error(info.error) end
+    return from_hex(info.pubkey), info.prefix
+end
+
+-- Check an SS58 address, optionally for a specific network
+-- expected_prefix: number or well-known name ("polkadot", "kusama", ...)
+-- Returns true, or false, err
+function Address.is_valid(address, expected_prefix)
+    local info = Address.inspect(address)
+    if info.format ~= "ss58" then return false, "Not an SS58 address" end
+    if not info.valid then return false, info.error end
+    expected_prefix = Address.PREFIXES[expected_prefix] or expected_prefix
+    if expected_prefix and info.prefix ~= expected_prefix then
+        return false, "Address is for network prefix " .. info.prefix .. ", expected " .. expected_prefix
+    end
+    return true
+end
+
+-- Re-encode an address for another network
+-- address: SS58 address (any prefix), "0x" hex public key, or raw 32-byte public key
+-- target: SS58 prefix (number or well-known name), or "hex" for the "0x" public key
+function Address.convert(address, target)
+    local info = Address.inspect(address)
+    if not info.valid then error("Invalid address: " .. tostring(info.error)) end
+    if target == "hex" then return info.pubkey end
+    return Address.encode(info.pubkey, target)
+end
+
+-- ============================================================
+-- EVM (Frontier) Mapping
+--   hashed:    AccountId32 = blake2_256("evm:" ++ h160)   (HashedAddressMapping, one-way)
+--   truncated: AccountId32 = h160 ++ 12 zero bytes        (TruncatedAddressMapping)
+--              h160 = first 20 bytes of the AccountId32    (EnsureAddressTruncated)
+-- ============================================================
+
+-- EIP-55 mixed-case checksum form of a 20-byte address (raw or "0x" hex)
+function Address.to_checksum_h160(h160)
+    if #h160 ~= 20 then
+        assert(h160:match("^0x" .. string.rep("%x", 40) .. "$"), "H160 must be 20 bytes")
+        h160 = from_hex(h160)
+    end
+    local lower = to_hex(h160)
+    local hash = to_hex(crypto.keccak256(lower))
+    local out = {}
+    for i = 1, 40 do
+        local c = lower:sub(i, i)
+        if tonumber(hash:sub(i, i), 16) >= 8 then c = c:upper() end
+        out[i] = c
+    end
+    return "0x" .. table.concat(out)
+end
+
+-- Raw bytes of an H160; mixed-case input must carry a valid EIP-55 checksum
+local function raw_h160(h160)
+    assert(type(h160) == "string" and h160:match("^0x" .. string.rep("%x", 40) .. "$"),
+        "H160 must be a 0x-prefixed 20-byte hex string")
+    local body = h160:sub(3)
+    if body ~= body:lower() and body ~= body:upper() and Address.to_checksum_h160(h160) ~= h160 then
+        error("Invalid EIP-55 checksum for " .. h160)
+    end
+    return from_hex(h160)
+end
+
+-- Substrate account of an EVM address
+-- scheme: "hashed" (default, Frontier chains without unified accounts) or "truncated"
+-- prefix: SS58 prefix of the result (default 42)
+-- Returns SS58 address, "0x" public key
+function Address.evm_to_substrate(h160, scheme, prefix)
+    scheme = scheme or "hashed"
+    local bytes = raw_h160(h160)
+    local pubkey
+    if scheme == "hashed" then
+        pubkey = crypto.blake2b("evm:" .. bytes, 32)
+    elseif scheme == "truncated" then
+        pubkey = bytes .. string.rep("\0", 12)
+    else
+        error("Unknown EVM mapping scheme: " .. tostring(scheme))
+    end
+    return Address.encode(pubkey, prefix or Address.PREFIXES.substrate), "0x" .. to_hex(pubkey)
+end
+
+-- EVM address that a Substrate account controls, EIP-55 checksummed
+-- address: SS58 address, "0x" hex public key, or raw 32-byte public key
+-- scheme: only "truncated" can be reversed; the hashed mapping is one-way
+function Address.substrate_to_evm(address, scheme)
+    scheme = scheme or "truncated"
+    if scheme == "hashed" then
+        error("The hashed mapping is one-way: an EVM address cannot be derived from its Substrate account")
+    end
+    assert(scheme == "truncated", "Unknown EVM mapping scheme: " .. tostring(scheme))
+    local info = Address.inspect(address)
+    if not info.valid then error("Invalid address: " .. tostring(info.error)) end
+    return Address.to_checksum_h160(from_hex(info.pubkey):sub(1, 20))
+end
+
+return Address
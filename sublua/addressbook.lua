@@ -0,0 +1,173 @@
+-- sublua/addressbook.lua
+-- Address book for UIs: local labels plus on-chain identity names, cached
+-- resolve_display_name gives "Alice (verified)" or "Parent/Sub" instead of an SS58 string;
+-- resolve_address_by_identity searches every registered identity and sub-identity by name
+-- Identity reads go through sublua.identity; entries expire after opts.ttl seconds
+
+local Address = require("sublua.address")
+local Storage = require("sublua.storage")
+local Identity = require("sublua.identity")
+
+local AddressBook = {}
+AddressBook.__index = AddressBook
+
+-- api: RPC client; identity reads follow Identity.chain (the People chain on relays
+--   that moved identities there)
+-- opts: { ttl = 300, prefix = nil, now = os.time }
+--   prefix: SS58 prefix of returned addresses (default: the client's ss58_format, else
+--     the chain's)
+--   now: clock in seconds, for callers with their own time source
+function AddressBook.new(api, opts)
+    opts = opts or {}
+    return setmetatable({
+        api = api,
+        ttl = opts.ttl or 300,
+        prefix = opts.prefix,
+        now = opts.now or os.time,
+        labels = {},
+        cache = {},
+        index = nil
+    }, AddressBook)
+end
+
+local function account_hex(who)
+    local info = Address.inspect(who)
+    if not info.valid then error("Invalid address " .. tostring(who) .. ": " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+function AddressBook:ss58(pubkey_hex)
+    local prefix = self.prefix or self.api.ss58_format
+    if not prefix then
+        local ok, props = pcall(self.api.get_chain_properties, self.api)
+        prefix = ok and props and props.ss58_prefix or 42
+    end
+    return Address.encode(pubkey_hex, prefix)
+end
+
+-- ============================================================
+-- Labels
+-- ============================================================
+
+-- Name an address locally; shown when it has no on-chain identity (nil removes it)
+function AddressBook:set_label(address, label)
+    self.labels[account_hex(address)] = label
+end
+
+function AddressBook:label(address)
+    return self.labels[account_hex(address)]
+end
+
+-- Drop cached identity data (everything, or one address)
+function AddressBook:clear(address)
+    if address then
+        self.cache[account_hex(address)] = nil
+    else
+        self.cache = {}
+    end
+    self.index = nil
+end
+
+-- ============================================================
+-- Lookups
+-- ============================================================
+
+-- Identity details of an address (cached)
+-- Returns { address, display, verified, label, parent, sub_name, identity }
+--   display: own display name, or "Parent/Sub" for sub-accounts; nil without either
+function AddressBook:lookup(address)
+    local key = account_hex(address)
+    local hit = self.cache[key]
+    local entry = hit and self.now() - hit.at < self.ttl and hit.entry
+    if not entry then
+        entry = self:fetch(key)
+        self.cache[key] = { at = self.now(), entry = entry }
+    end
+    local result = {}
+    for k, v in pairs(entry) do result[k] = v end
+    result.label = self.labels[key]
+    return result
+end
+
+-- Uncached identity read behind lookup
+function AddressBook:fetch(key)
+    local entry = { address = self:ss58(key), verified = false }
+    local own = Identity.query_identity(self.api, key)
+    if own then
+        entry.identity, entry.display, entry.verified = own, own.display, own.verified
+    end
+    if not entry.display then
+        local super = Identity.query_super(self.api, key)
+        if super then
+            local parent = self:lookup(super.parent)
+            entry.parent, entry.sub_name = parent.address, super.name
+            entry.display = (parent.display or parent.address) .. "/" .. (super.name or "?")
+            entry.verified = parent.verified
+        end
+    end
+    return entry
+end
+
+-- Name for showing an address
+-- Returns "Name (verified)", "Name", the local label, or the SS58 address; then the
+--   lookup table
+function AddressBook:resolve_display_name(address)
+    local entry = self:lookup(address)
+    if entry.display then
+        return entry.verified and (entry.display .. " (verified)") or entry.display, entry
+    end
+    return entry.label or entry.address, entry
+end
+
+-- Every identity and sub-identity name on chain (cached; one paged scan of IdentityOf
+-- and SuperOf)
+function AddressBook:identities()
+    if self.index and self.now() - self.index.at < self.ttl then return self.index.list end
+    local list, names = {}, {}
+    for _, item in ipairs(Storage.entries(Identity.chain(self.api), "Identity", "IdentityOf")) do
+        local key = item.keys[1]
+        local entry = self:lookup(key)
+        if entry.display then
+            names[key] = entry.display
+            table.insert(list, { address = entry.address, display = entry.display, verified = entry.verified })
+        end
+    end
+    for _, item in ipairs(Storage.entries(Identity.chain(self.api), "Identity", "SuperOf")) do
+        local key = item.keys[1]
+        local sub_name = Identity.data_text(item.value[2])
+        local parent = item.value[1]
+        local parent_entry = self:lookup(parent)
+        local display = (names[parent] or parent_entry.address) .. "/" .. (type(sub_name) == "string" and sub_name or "?")
+        table.insert(list, {
+            address = self:ss58(key), display = display, verified = parent_entry.verified,
+            parent = parent_entry.address, sub_name = sub_name
+        })
+    end
+    table.sort(list, function(a, b) return a.display < b.display end)
+    self.index = { at = self.now(), list = list }
+    return list
+end
+
+-- Find addresses whose identity name contains a substring (case-insensitive)
+-- Matches own display names, "Parent/Sub" names of sub-identities and local labels
+-- Returns a list of { address, display, verified, parent, sub_name }, sorted by name
+function AddressBook:resolve_address_by_identity(substring)
+    local needle = substring:lower()
+    local out, seen = {}, {}
+    for _, item in ipairs(self:identities()) do
+        if item.display:lower():find(needle, 1, true) then
+            table.insert(out, item)
+            seen[item.address] = true
+        end
+    end
+    for key, label in pairs(self.labels) do
+        local address = self:ss58(key)
+        if not seen[address] and label:lower():find(needle, 1, true) then
+            table.insert(out, { address = address, display = label, verified = false })
+        end
+    end
+    table.sort(out, function(a, b) return a.display < b.display end)
+    return out
+end
+
+return AddressBook
@@ -0,0 +1,244 @@
+-- sublua/assets.lua
+-- Fungible assets on Asset Hub style chains: the Assets pallet (u32 ids) and the
+-- ForeignAssets pallet (XCM Location ids), which share one storage layout
+-- query_all_balances reads an account's native balance and every asset it holds at one
+-- block, with names, symbols and decimals from asset metadata
+-- Approvals give allowance-style flows: the owner approves a delegate for an amount,
+-- the delegate moves up to that amount with transfer_approved
+
+local RPC = require("sublua.rpc")
+local Address = require("sublua.address")
+local Storage = require("sublua.storage")
+local Bytes = require("sublua.bytes")
+local Call = require("sublua.call")
+
+local Assets = {}
+
+-- Asset pallets read by query_all_balances, in result order
+Assets.PALLETS = { "Assets", "ForeignAssets" }
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function account_hex(who)
+    local info = Address.inspect(who)
+    if not info.valid then error("Invalid account " .. tostring(who) .. ": " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+local function format(amount, decimals, symbol)
+    if (decimals or 0) == 0 then return tostring(amount) .. (symbol and " " .. symbol or "") end
+    return Bytes.format_balance(Bytes.decimal_to_bytes(tostring(amount)), decimals or 0, symbol)
+end
+
+local function text(value)
+    if type(value) ~= "string" or value == "" or value == "0x" then return nil end
+    return from_hex(value)
+end
+
+local function is_zero(amount)
+    return tostring(amount or "0"):match("^0*$") ~= nil
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- Every asset registered in an asset pallet
+-- pallet: "Assets" (default) or "ForeignAssets"; at: block hash (default: best block)
+-- Returns { { id, details }, ... }; details is the decoded Asset entry (owner, supply,
+--   min_balance, is_sufficient, status, ...); u32 ids are sorted numerically
+function Assets.list(api, pallet, at)
+    pallet = pallet or "Assets"
+    local out = {}
+    for _, item in ipairs(Storage.entries(api, pallet, "Asset", nil, { at = at })) do
+        table.insert(out, { id = item.keys[1], details = item.value })
+    end
+    if out[1] and type(out[1].id) == "number" then
+        table.sort(out, function(a, b) return a.id < b.id end)
+    end
+    return out
+end
+
+-- Name, symbol and decimals of assets
+-- ids: list of asset ids of one pallet
+-- Returns { [i] = { name, symbol, decimals, is_frozen } } (nil for assets without metadata)
+function Assets.query_metadata(api, pallet, ids, at)
+    local out = {}
+    local keys = {}
+    for i, id in ipairs(ids) do keys[i] = { id } end
+    Storage.batch_reader(api, pallet, "Metadata"):read(keys, out, at)
+    for i = 1, #ids do
+        local m = out[i]
+        if m and (text(m.symbol) or text(m.name)) then
+            out[i] = { name = text(m.name), symbol = text(m.symbol), decimals = m.decimals or 0, is_frozen = m.is_frozen }
+        else
+            out[i] = nil
+        end
+    end
+    return out
+end
+
+-- Balance of one asset
+-- Returns { balance, status } or nil when the account holds none; status is "Liquid",
+--   "Frozen" or "Blocked" (older runtimes: from is_frozen)
+function Assets.query_balance(api, pallet, id, who, at)
+    local value = Storage.fetch(api, pallet or "Assets", "Account", { id, account_hex(who) }, at)
+    if not value then return nil end
+    return { balance = tostring(value.balance), status = Assets.account_status(value) }
+end
+
+function Assets.account_status(account)
+    if type(account.status) == "table" then return account.status.variant end
+    if type(account.status) == "string" then return account.status end
+    return account.is_frozen and "Frozen" or "Liquid"
+end
+
+-- Native balance and every non-zero asset balance of an account, at one block
+-- api: RPC client or node URL; who: SS58 address, "0x" hex or raw public key
+-- opts: { at = nil, pallets = Assets.PALLETS }
+--   at: block hash (default: the finalized head)
+--   pallets: asset pallets to read; ones the runtime lacks are skipped
+-- Returns { at, native = { free, reserved, frozen, decimals, symbol, formatted },
+--   assets = { { pallet, id, balance, status, name, symbol, decimals, formatted,
+--     min_balance, is_sufficient }, ... } }
+--   amounts are decimal strings in the asset's smallest unit; formatted applies decimals
+--   and symbol (assets without metadata get 0 decimals and no symbol)
+function Assets.query_all_balances(api, who, opts)
+    opts = opts or {}
+    if type(api) == "string" then api = RPC.new(api) end
+    local meta = api:get_metadata()
+    local key = account_hex(who)
+    local at = opts.at or api:chain_getFinalizedHead()
+
+    local props = api:get_chain_properties()
+    local account = Storage.fetch(api, "System", "Account", { key }, at)
+    local data = account and account.data or {}
+    local result = {
+        at = at,
+        native = {
+            free = tostring(data.free or "0"),
+            reserved = tostring(data.reserved or "0"),
+            frozen = tostring(data.frozen or data.misc_frozen or "0"),
+            decimals = props.decimals,
+            symbol = props.symbol
+        },
+        assets = {}
+    }
+    result.native.formatted = format(result.native.free, props.decimals, props.symbol)
+
+    for _, pallet in ipairs(opts.pallets or Assets.PALLETS) do
+        if meta.pallets[pallet] and meta.pallets[pallet].storage then
+            local listed = Assets.list(api, pallet, at)
+            local keys = {}
+            for i, asset in ipairs(listed) do keys[i] = { asset.id, key } end
+            local balances = {}
+            Storage.batch_reader(api, pallet, "Account"):read(keys, balances, at)
+
+            local held, ids = {}, {}
+            for i, asset in ipairs(listed) do
+                local b = balances[i]
+                if b and not is_zero(b.balance) then
+                    table.insert(held, { asset = asset, account = b })
+                    table.insert(ids, asset.id)
+                end
+            end
+            local metadata = Assets.query_metadata(api, pallet, ids, at)
+            for i, h in ipairs(held) do
+                local m = metadata[i] or {}
+                local details = h.asset.details or {}
+                local entry = {
+                    pallet = pallet,
+                    id = h.asset.id,
+                    balance = tostring(h.account.balance),
+                    status = Assets.account_status(h.account),
+                    name = m.name,
+                    symbol = m.symbol,
+                    decimals = m.decimals or 0,
+                    min_balance = details.min_balance and tostring(details.min_balance),
+                    is_sufficient = details.is_sufficient
+                }
+                entry.formatted = format(entry.balance, entry.decimals, entry.symbol)
+                table.insert(result.assets, entry)
+            end
+        end
+    end
+    return result
+end
+
+-- ============================================================
+-- Approvals
+-- pallet: "Assets" (default) or "ForeignAssets"; id: u32 asset id, or a Location table
+-- for ForeignAssets; amounts in the asset's smallest unit
+-- Functions that sign return the extrinsic hex and build details, as
+-- Transaction.create_signed_from_api; opts go to it
+-- ============================================================
+
+local function multi_address(who)
+    return { variant = "Id", value = account_hex(who) }
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Let delegate transfer up to amount of the signer's asset (adds to an existing approval;
+-- reserves the approval deposit)
+function Assets.approve_transfer(api, signer, pallet, id, delegate, amount, opts)
+    local call_bytes = Call.encode_call(api, pallet or "Assets", "approve_transfer",
+        { id = id, delegate = multi_address(delegate), amount = amount })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Remove the signer's approval for delegate (returns the deposit)
+function Assets.cancel_approval(api, signer, pallet, id, delegate, opts)
+    local call_bytes = Call.encode_call(api, pallet or "Assets", "cancel_approval",
+        { id = id, delegate = multi_address(delegate) })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Transfer amount of owner's asset to destination, signed by the approved delegate
+function Assets.transfer_approved(api, signer, pallet, id, owner, destination, amount, opts)
+    local call_bytes = Call.encode_call(api, pallet or "Assets", "transfer_approved",
+        { id = id, owner = multi_address(owner), destination = multi_address(destination), amount = amount })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Approval of one asset
+-- Returns { amount, deposit } or nil without one
+function Assets.query_approval(api, pallet, id, owner, delegate, at)
+    local value = Storage.fetch(api, pallet or "Assets", "Approvals",
+        { id, account_hex(owner), account_hex(delegate) }, at)
+    if not value then return nil end
+    return { amount = tostring(value.amount), deposit = tostring(value.deposit) }
+end
+
+-- Every approval owner has given delegate, across assets
+-- opts: { at = nil, pallets = Assets.PALLETS }
+-- Returns { { pallet, id, amount, deposit }, ... }; one batched read per pallet
+function Assets.query_approvals(api, owner, delegate, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local owner_key, delegate_key = account_hex(owner), account_hex(delegate)
+    local out = {}
+    for _, pallet in ipairs(opts.pallets or Assets.PALLETS) do
+        if meta.pallets[pallet] and meta.pallets[pallet].storage then
+            local listed = Assets.list(api, pallet, opts.at)
+            local keys, values = {}, {}
+            for i, asset in ipairs(listed) do keys[i] = { asset.id, owner_key, delegate_key } end
+            Storage.batch_reader(api, pallet, "Approvals"):read(keys, values, opts.at)
+            for i, asset in ipairs(listed) do
+                local v = values[i]
+                if v then
+                    table.insert(out, { pallet = pallet, id = asset.id, amount = tostring(v.amount), deposit = tostring(v.deposit) })
+                end
+            end
+        end
+    end
+    return out
+end
+
+return Assets
@@ -0,0 +1,158 @@
+-- sublua/beefy.lua
+-- BEEFY and MMR commitments for bridge and light-client services (Snowbridge, ...)
+-- Reads the MMR root and validator sets at the BEEFY-finalized block, and fetches
+-- MMR leaf proofs through mmr_generateProof with the leaves and proof decoded
+-- Proofs are returned for relaying; they are not verified here
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Storage = require("sublua.storage")
+
+local Beefy = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function read(data, offset, len, what)
+    local bytes = data:sub(offset, offset + len - 1)
+    assert(#bytes == len, "Truncated " .. what)
+    return bytes, offset + len
+end
+
+local function hash32(data, offset, what)
+    local bytes
+    bytes, offset = read(data, offset, 32, what)
+    return "0x" .. to_hex(bytes), offset
+end
+
+-- ============================================================
+-- Commitments
+-- Default block: the latest BEEFY-finalized head (beefy_getFinalizedHead)
+-- ============================================================
+
+function Beefy.finalized_head(api)
+    return api:beefy_getFinalizedHead()
+end
+
+-- MMR root hash at a block ("0x" hex)
+function Beefy.query_mmr_root(api, at)
+    return Storage.fetch(api, "Mmr", "RootHash", nil, at or Beefy.finalized_head(api))
+end
+
+-- Number of MMR leaves (one per block since the MMR started)
+function Beefy.query_mmr_leaf_count(api, at)
+    return tonumber(Storage.fetch(api, "Mmr", "NumberOfLeaves", nil, at or Beefy.finalized_head(api)))
+end
+
+-- Current BEEFY validator set id
+function Beefy.query_validator_set_id(api, at)
+    return tonumber(Storage.fetch(api, "Beefy", "ValidatorSetId", nil, at or Beefy.finalized_head(api)))
+end
+
+-- Current BEEFY authorities: list of 33-byte compressed ECDSA keys ("0x" hex)
+function Beefy.query_authorities(api, at)
+    return Storage.fetch(api, "Beefy", "Authorities", nil, at or Beefy.finalized_head(api)) or {}
+end
+
+-- Everything a bridge anchors on, read at one block
+-- Returns { block_hash, mmr_root, leaf_count, validator_set_id, authorities,
+--   authority_set, next_authority_set }
+--   the authority sets ({ id, len, keyset_commitment }) come from BeefyMmrLeaf and are
+--   nil on chains without it
+function Beefy.query_commitment(api, at)
+    at = at or Beefy.finalized_head(api)
+    local meta = api:get_metadata()
+    local result = {
+        block_hash = at,
+        mmr_root = Beefy.query_mmr_root(api, at),
+        leaf_count = Beefy.query_mmr_leaf_count(api, at),
+        validator_set_id = Beefy.query_validator_set_id(api, at),
+        authorities = Beefy.query_authorities(api, at)
+    }
+    if meta.pallets["BeefyMmrLeaf"] then
+        result.authority_set = Storage.fetch(api, "BeefyMmrLeaf", "BeefyAuthorities", nil, at)
+        result.next_authority_set = Storage.fetch(api, "BeefyMmrLeaf", "BeefyNextAuthorities", nil, at)
+    end
+    return result
+end
+
+-- ============================================================
+-- MMR Proofs
+-- ============================================================
+
+-- Decode a SCALE LeafProof { leaf_indices: Vec<u64>, leaf_count: u64, items: Vec<H256> }
+function Beefy.decode_leaf_proof(proof)
+    local data = proof:match("^0x") and from_hex(proof) or proof
+    local result = { leaf_indices = {}, items = {} }
+    local count, offset = Scale.decode_compact(data, 1)
+    for i = 1, count do
+        result.leaf_indices[i], offset = Scale.decode_u64(data, offset)
+    end
+    result.leaf_count, offset = Scale.decode_u64(data, offset)
+    count, offset = Scale.decode_compact(data, offset)
+    for i = 1, count do
+        result.items[i], offset = hash32(data, offset, "proof item")
+    end
+    assert(offset == #data + 1, "Trailing bytes after leaf proof")
+    return result
+end
+
+-- Decode a BEEFY MMR leaf
+-- MmrLeaf { version: u8, parent_number_and_hash: (u32, H256),
+--   beefy_next_authority_set: { id: u64, len: u32, keyset_commitment: H256 }, leaf_extra: H256 }
+-- Returns the fields above plus hash (keccak256 of the leaf, as the MMR stores it)
+function Beefy.decode_leaf(leaf)
+    local data = leaf:match("^0x") and from_hex(leaf) or leaf
+    local result = { hash = "0x" .. to_hex(crypto.hash("keccak256", data)) }
+    local offset
+    result.version, offset = Scale.decode_u8(data, 1)
+    result.parent_number, offset = Scale.decode_u32(data, offset)
+    result.parent_hash, offset = hash32(data, offset, "parent hash")
+    local set = {}
+    set.id, offset = Scale.decode_u64(data, offset)
+    set.len, offset = Scale.decode_u32(data, offset)
+    set.keyset_commitment, offset = hash32(data, offset, "keyset commitment")
+    result.beefy_next_authority_set = set
+    result.leaf_extra, offset = hash32(data, offset, "leaf extra")
+    assert(offset == #data + 1, "Trailing bytes after MMR leaf")
+    return result
+end
+
+-- Proof for the MMR leaves of block_numbers
+-- best_known_block_number: the MMR size to prove against (default: the node's best;
+--   use the block of a BEEFY commitment the verifier already has)
+-- at: block hash the proof is generated at
+-- Returns { block_hash, leaves = { decoded leaf, ... }, proof = decoded LeafProof,
+--   raw = { leaves, proof } (hex as returned by the node) }
+function Beefy.generate_proof(api, block_numbers, best_known_block_number, at)
+    if type(block_numbers) == "number" then block_numbers = { block_numbers } end
+    assert(type(block_numbers) == "table" and #block_numbers > 0, "block_numbers must be a non-empty list")
+    local response = api:mmr_generateProof(block_numbers, best_known_block_number, at)
+    assert(type(response) == "table" and response.leaves and response.proof, "mmr_generateProof returned no proof")
+
+    -- leaves: SCALE Vec<EncodableOpaqueLeaf>, each a Vec<u8>
+    local data = from_hex(response.leaves)
+    local count, offset = Scale.decode_compact(data, 1)
+    local leaves = {}
+    for i = 1, count do
+        local len
+        len, offset = Scale.decode_compact(data, offset)
+        local bytes
+        bytes, offset = read(data, offset, len, "leaf")
+        leaves[i] = Beefy.decode_leaf(bytes)
+    end
+    return {
+        block_hash = response.blockHash,
+        leaves = leaves,
+        proof = Beefy.decode_leaf_proof(response.proof),
+        raw = { leaves = response.leaves, proof = response.proof }
+    }
+end
+
+return Beefy
@@ -3,6 +3,8 @@
 
 local Scale = require("sublua.scale")
 local crypto = require("polkadot_crypto")
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
 
 local Call = {}
 
@@ -45,6 +47,74 @@ function Call.encode_transfer(module_index, call_index, dest_pubkey, amount)
     return call_idx .. dest .. value
 end
 
+-- Encode any call from metadata
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- args: table keyed by argument name (values follow sublua.codec conventions)
+-- Returns raw call bytes
+function Call.encode_call(meta_source, pallet_name, call_name, args)
+    local meta = Metadata.from_source(meta_source)
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then error("Pallet not found: " .. tostring(pallet_name)) end
+    if not pallet.calls_type_id then error("Pallet has no calls: " .. pallet_name) end
+
+    local def = Codec.resolve(meta, pallet.calls_type_id).type_def
+    for _, variant in ipairs(def.variants) do
+        if variant.name == call_name then
+            local encoded = Codec.encode(meta, pallet.calls_type_id, { variant = call_name, value = args or {} })
+            return Call.encode_index(pallet.index, variant.index) .. encoded:sub(2)
+        end
+    end
+    error("Call not found: " .. pallet_name .. "." .. tostring(call_name))
+end
+
+-- Encode a call from JSON arguments, coerced by the call's argument types
+-- (see Codec.from_json: addresses, numbers or decimal strings, hex, nested objects and enums)
+-- args: JSON object text, or the decoded table
+-- Returns call bytes; errors name the offending argument, e.g. "transfer_keep_alive.dest: ..."
+function Call.encode_call_json(meta_source, pallet_name, call_name, args)
+    local meta = Metadata.from_source(meta_source)
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then error("Pallet not found: " .. tostring(pallet_name)) end
+    if not pallet.calls_type_id then error("Pallet has no calls: " .. pallet_name) end
+    if type(args) == "string" then
+        local ok, decoded = pcall(require("cjson").decode, args)
+        if not ok or type(decoded) ~= "table" then error("Call arguments must be a JSON object") end
+        args = decoded
+    end
+    local value = Codec.from_json(meta, pallet.calls_type_id, { variant = call_name, value = args or {} })
+    return Call.encode_call(meta, pallet_name, call_name, value.value)
+end
+
+-- Account argument of a call in the shape its type expects
+-- who: SS58 address, "0x" hex public key, or raw 32-byte public key
+-- Returns { variant = "Id", value = hex } for lookup sources (MultiAddress), else the hex
+function Call.account_arg(meta_source, pallet_name, call_name, field_name, who)
+    local meta = Metadata.from_source(meta_source)
+    local info = require("sublua.address").inspect(who)
+    if not info.valid then error("Invalid account for " .. field_name .. ": " .. tostring(info.error)) end
+    local pallet = meta.pallets[pallet_name]
+    if not pallet or not pallet.calls_type_id then error("Pallet has no calls: " .. tostring(pallet_name)) end
+    for _, variant in ipairs(Codec.resolve(meta, pallet.calls_type_id).type_def.variants) do
+        if variant.name == call_name then
+            for _, field in ipairs(variant.fields) do
+                if field.name == field_name then
+                    local def = Codec.resolve(meta, field.type_id).type_def
+                    if def.type == "Variant" then return { variant = "Id", value = info.pubkey } end
+                    return info.pubkey
+                end
+            end
+            error(pallet_name .. "." .. call_name .. " has no argument " .. tostring(field_name))
+        end
+    end
+    error("Call not found: " .. pallet_name .. "." .. tostring(call_name))
+end
+
+-- Blake2-256 hash of call bytes, as used by Multisig and Proxy announcements
+-- Returns "0x"-prefixed hex
+function Call.call_hash(call_bytes)
+    return "0x" .. to_hex(crypto.blake2b(call_bytes, 32))
+end
+
 -- Helper to convert call bytes to hex
 function Call.to_hex(call_bytes)
     return (call_bytes:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
@@ -0,0 +1,70 @@
+-- sublua/cancel.lua
+-- Cancellation tokens for long-running work: block scans, inclusion and finality waits,
+-- vanity search and storage iteration take one as opts.cancel and stop at their next
+-- check once token:cancel() is called (from a UI handler, a pump callback, another coroutine)
+-- A loop that never yields cannot see a Cancel button pressed meanwhile: give the token a
+-- poll function that asks the host (e.g. pumps the UI's events) and it is called at each check
+-- Stopped operations report errors starting with "Cancelled:" (see Cancel.is_cancelled)
+
+local Cancel = {}
+
+local Token = {}
+Token.__index = Token
+
+-- New token
+-- poll(): optional; called at each check, returning true (or a reason string) cancels
+function Cancel.token(poll)
+    assert(poll == nil or type(poll) == "function", "poll must be a function")
+    return setmetatable({ cancelled = false, reason = nil, poll = poll, cleanups = {} }, Token)
+end
+
+-- Cancel the operations holding this token; cleanup functions registered with on_cancel run now
+-- Later calls do nothing
+function Token:cancel(reason)
+    if self.cancelled then return end
+    self.cancelled = true
+    self.reason = reason or "cancelled by the caller"
+    local cleanups = self.cleanups
+    self.cleanups = {}
+    for i = #cleanups, 1, -1 do cleanups[i](self.reason) end
+end
+
+-- True once cancelled (asks the poll function first)
+function Token:is_cancelled()
+    if not self.cancelled and self.poll then
+        local stop = self.poll()
+        if stop then self:cancel(type(stop) == "string" and stop or nil) end
+    end
+    return self.cancelled
+end
+
+-- Run fn(reason) when the token is cancelled (straight away if it already is),
+-- e.g. to close a file or a socket the operation holds
+function Token:on_cancel(fn)
+    if self.cancelled then
+        fn(self.reason)
+    else
+        table.insert(self.cleanups, fn)
+    end
+end
+
+-- Error message for a cancelled operation, or nil while it may go on
+-- token: a token or nil (no cancellation); what: the operation, for the message
+function Cancel.check(token, what)
+    if token and token:is_cancelled() then
+        return "Cancelled: " .. what .. " (" .. token.reason .. ")"
+    end
+    return nil
+end
+
+-- Raise the Cancel.check error when the token is cancelled
+function Cancel.raise(token, what)
+    local err = Cancel.check(token, what)
+    if err then error(err, 0) end
+end
+
+function Cancel.is_cancelled(err)
+    return tostring(err):find("Cancelled:", 1, true) ~= nil
+end
+
+return Cancel
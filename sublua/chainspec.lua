@@ -0,0 +1,169 @@
+-- sublua/chainspec.lua
+-- Offline chain-spec parsing and genesis hash computation
+-- Genesis hash = blake2_256(SCALE(genesis header)); the header's state root is
+-- the trie root of the raw genesis storage (see sublua.trie)
+
+local json = require("cjson")
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Trie = require("sublua.trie")
+
+local ChainSpec = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Well-known keys
+local CODE_KEY = "0x3a636f6465"  -- ":code"
+local CHILD_STORAGE_DEFAULT = ":child_storage:default:"
+
+-- zstd-compressed runtime blobs (sp-maybe-compressed-blob)
+local ZSTD_PREFIX = "\82\188\83\118\70\219\142\5"
+
+-- ============================================================
+-- Runtime Version (from the wasm "runtime_version" custom section)
+-- ============================================================
+
+local function read_leb128(data, offset)
+    local value, shift = 0, 1
+    while true do
+        local b = string.byte(data, offset)
+        assert(b, "truncated LEB128")
+        offset = offset + 1
+        value = value + (b % 128) * shift
+        if b < 128 then return value, offset end
+        shift = shift * 128
+    end
+end
+
+-- Find a custom section in an uncompressed wasm module, or nil
+local function wasm_custom_section(code, wanted)
+    local offset = 9
+    while offset <= #code do
+        local id = string.byte(code, offset)
+        local size
+        size, offset = read_leb128(code, offset + 1)
+        if id == 0 then
+            local name_len, name_off = read_leb128(code, offset)
+            if code:sub(name_off, name_off + name_len - 1) == wanted then
+                return code:sub(name_off + name_len, offset + size - 1)
+            end
+        end
+        offset = offset + size
+    end
+    return nil
+end
+
+-- State version declared by the runtime, or nil when it cannot be read
+-- (zstd-compressed code). Uncompressed runtimes without a runtime_version
+-- section predate state version 1 and use 0.
+function ChainSpec.runtime_state_version(code)
+    if code:sub(1, 8) == ZSTD_PREFIX or code:sub(1, 4) ~= "\0asm" then return nil end
+    local section = wasm_custom_section(code, "runtime_version")
+    if not section then return 0 end
+
+    -- spec_name, impl_name, authoring/spec/impl version, apis, transaction_version, [state_version]
+    local offset = 1
+    for _ = 1, 2 do
+        local len
+        len, offset = Scale.decode_compact(section, offset)
+        offset = offset + len
+    end
+    offset = offset + 12
+    local apis
+    apis, offset = Scale.decode_compact(section, offset)
+    offset = offset + apis * 12 + 4
+    if offset > #section then return 0 end
+    return string.byte(section, offset)
+end
+
+-- ============================================================
+-- Genesis
+-- ============================================================
+
+-- Genesis block hash from raw genesis storage
+-- top: { ["0x.."] = "0x.." }; children_default: { ["0x<child key>"] = { ["0x.."] = "0x.." } }
+-- state_version: 0 or 1
+-- Returns genesis hash hex, state root hex
+function ChainSpec.genesis_hash(top, children_default, state_version)
+    local storage = {}
+    for k, v in pairs(top) do storage[k] = v end
+    for child_key, child in pairs(children_default or {}) do
+        if next(child) then
+            local root = Trie.root(child, state_version, { hex = true })
+            storage["0x" .. to_hex(CHILD_STORAGE_DEFAULT) .. child_key:gsub("^0x", "")] = "0x" .. to_hex(root)
+        end
+    end
+    local state_root = "0x" .. to_hex(Trie.root(storage, state_version, { hex = true }))
+    return ChainSpec.genesis_header_hash(state_root), state_root
+end
+
+-- Hash of the genesis header for a given state root
+-- Header: parent_hash (zero), number (compact 0), state_root, extrinsics_root (empty trie), digest (empty)
+function ChainSpec.genesis_header_hash(state_root)
+    local header = string.rep("\0", 32) .. Scale.encode_compact(0) .. from_hex(state_root)
+        .. Trie.EMPTY_ROOT .. Scale.encode_compact(0)
+    return "0x" .. to_hex(crypto.blake2b(header, 32))
+end
+
+-- ============================================================
+-- Parsing
+-- ============================================================
+
+-- Parse a chain spec
+-- source: file path, JSON text, or an already decoded table
+-- opts: { state_version = nil } overrides the state version read from the runtime
+--   (needed for zstd-compressed runtimes, which default to 1)
+-- Returns { name, id, chain_type, protocol_id, boot_nodes, properties, ss58_prefix,
+--   token_symbol, token_decimals, relay_chain, para_id, raw,
+--   genesis_hash, state_root, state_version, state_version_detected }
+--   genesis_hash is nil for non-raw specs (build them with --raw)
+function ChainSpec.parse(source, opts)
+    opts = opts or {}
+    local spec = source
+    if type(source) == "string" then
+        local text = source
+        if not source:match("^%s*{") then
+            local f = assert(io.open(source, "rb"))
+            text = f:read("*a")
+            f:close()
+        end
+        spec = json.decode(text)
+    end
+    assert(type(spec) == "table" and spec.genesis, "Not a chain spec: missing genesis")
+
+    local props = type(spec.properties) == "table" and spec.properties or {}
+    local function first(v) return type(v) == "table" and v[1] or v end
+
+    local result = {
+        name = spec.name,
+        id = spec.id,
+        chain_type = spec.chainType,
+        protocol_id = spec.protocolId,
+        boot_nodes = spec.bootNodes or {},
+        properties = props,
+        ss58_prefix = tonumber(props.ss58Format) or 42,
+        token_symbol = first(props.tokenSymbol),
+        token_decimals = tonumber(first(props.tokenDecimals)),
+        relay_chain = spec.relay_chain or spec.relayChain,
+        para_id = spec.para_id or spec.paraId,
+        raw = spec.genesis.raw ~= nil
+    }
+
+    local raw = spec.genesis.raw
+    if raw then
+        local detected = raw.top[CODE_KEY] and ChainSpec.runtime_state_version(from_hex(raw.top[CODE_KEY]))
+        result.state_version_detected = detected ~= nil
+        result.state_version = opts.state_version or detected or 1
+        result.genesis_hash, result.state_root = ChainSpec.genesis_hash(raw.top, raw.childrenDefault, result.state_version)
+    end
+    return result
+end
+
+return ChainSpec
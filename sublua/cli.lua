@@ -0,0 +1,230 @@
+-- sublua/cli.lua
+-- Command-line front end over the SDK (bin/sublua-cli.lua) for shell scripts and CI
+-- Subcommands: derive, sign, verify, transfer, query, decode
+-- Structured results are printed as JSON; failures go to stderr with a non-zero exit code
+
+local json = require("cjson")
+local crypto = require("polkadot_crypto")
+local RPC = require("sublua.rpc")
+local Keyring = require("sublua.keyring")
+local Address = require("sublua.address")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Transaction = require("sublua.transaction")
+
+local Cli = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+Cli.USAGE = [[
+Usage: sublua-cli <command> [args]
+
+  derive <key> [--prefix N] [--password P]      address and public key of a key
+  sign <key> <message|0xhex> [--password P]     ed25519 signature (hex)
+  verify <address|0xpubkey> <message|0xhex> <0xsig>
+  transfer <url> <key> <dest> <amount> [--keep-alive] [--wait inclusion|finalized] [--password P]
+  query <url> balance <address>
+  query <url> storage <Pallet.Entry> [key ...]  keys: address, 0xhex, number or JSON
+  decode address <address>
+  decode extrinsic <url|metadata_file> <0xhex>
+
+<key>: //Alice, //Bob, //Charlie, a 0x 32-byte seed, a BIP-39 phrase, or env:VAR to
+read any of them from the environment (keeps secrets out of the process list and
+shell history)
+--password: BIP-39 passphrase of a phrase key; env:VAR works here too
+]]
+
+-- Value of an argument, or of the environment variable it names as env:VAR
+local function from_env(arg)
+    local var = arg:match("^env:(.+)$")
+    if not var then return arg end
+    local value = os.getenv(var)
+    if not value or value == "" then error("Environment variable " .. var .. " is not set") end
+    return value
+end
+
+-- Signer from a key argument
+-- password: BIP-39 passphrase (or env:VAR) for phrase keys
+function Cli.signer(key, password)
+    key = from_env(key)
+    if password then password = from_env(password) end
+    if password and not key:find("%s") then error("--password only applies to BIP-39 phrase keys") end
+    if key:match("^//") then return Keyring.from_uri(key, { secure = true }) end
+    if key:match("^0x" .. string.rep("%x", 64) .. "$") then return Keyring.from_seed(key, { secure = true }) end
+    if key:find("%s") then return Keyring.from_mnemonic(key, { secure = true, password = password }) end
+    error("Key must be //Name, a 0x 32-byte seed, a BIP-39 phrase or env:VAR")
+end
+
+-- Message bytes: "0x" hex is decoded, anything else is taken literally
+local function message(arg)
+    if arg:match("^0x%x*$") and #arg % 2 == 0 then return from_hex(arg) end
+    return arg
+end
+
+-- Storage key argument: address -> public key hex, JSON values decoded, otherwise as given
+local function key_value(arg)
+    local info = Address.inspect(arg)
+    if info.valid and info.format == "ss58" then return info.pubkey end
+    if arg:match("^[%[{\"]") or arg:match("^%-?%d+$") or arg == "true" or arg == "false" then
+        local ok, value = pcall(json.decode, arg)
+        if ok then return value end
+    end
+    return arg
+end
+
+-- Split "--flag value" options from positional arguments
+local function parse(args, flags)
+    local positional, options = {}, {}
+    local i = 1
+    while i <= #args do
+        local name = args[i]:match("^%-%-([%w-]+)$")
+        if name then
+            if flags[name] == nil then error("Unknown option --" .. name) end
+            if flags[name] then
+                options[name] = args[i + 1] or error("--" .. name .. " needs a value")
+                i = i + 1
+            else
+                options[name] = true
+            end
+        else
+            table.insert(positional, args[i])
+        end
+        i = i + 1
+    end
+    return positional, options
+end
+
+-- ============================================================
+-- Commands
+-- ============================================================
+
+local commands = {}
+
+function commands.derive(ctx, args)
+    local pos, opts = parse(args, { prefix = true, password = true })
+    local pair = Cli.signer(assert(pos[1], "derive needs a key"), opts.password)
+    local prefix = tonumber(opts.prefix) or Address.PREFIXES[opts.prefix] or 42
+    ctx.print_json({
+        address = Address.encode(pair.pubkey, prefix),
+        public_key = "0x" .. to_hex(pair.pubkey),
+        scheme = pair.scheme
+    })
+end
+
+function commands.sign(ctx, args)
+    local pos, opts = parse(args, { password = true })
+    local pair = Cli.signer(assert(pos[1], "sign needs a key"), opts.password)
+    local msg = message(assert(pos[2], "sign needs a message"))
+    ctx.print("0x" .. to_hex(pair:sign(msg)))
+end
+
+function commands.verify(ctx, args)
+    assert(#args == 3, "verify needs <address|0xpubkey> <message> <0xsig>")
+    local info = Address.inspect(args[1])
+    if not info.valid then error("Invalid address: " .. tostring(info.error)) end
+    local sig = from_hex(args[3])
+    if #sig ~= 64 then error("Signature must be 64 bytes") end
+    local valid = crypto.ed25519_verify(from_hex(info.pubkey), message(args[2]), sig)
+    ctx.print(valid and "valid" or "invalid")
+    return valid and 0 or 1
+end
+
+function commands.transfer(ctx, args)
+    local pos, opts = parse(args, { ["keep-alive"] = false, wait = true, password = true })
+    assert(#pos == 4, "transfer needs <url> <key> <dest> <amount>")
+    local api = ctx.connect(pos[1])
+    local signer = Cli.signer(pos[2], opts.password)
+    local dest = Address.inspect(pos[3])
+    if not dest.valid then error("Invalid destination: " .. tostring(dest.error)) end
+    assert(pos[4]:match("^%d+$"), "Amount must be an integer in plancks")
+    if opts.wait and opts.wait ~= "inclusion" and opts.wait ~= "finalized" then
+        error("--wait must be inclusion or finalized")
+    end
+    local call = Call.encode_call(api, "Balances", opts["keep-alive"] and "transfer_keep_alive" or "transfer_allow_death",
+        { dest = { variant = "Id", value = dest.pubkey }, value = pos[4] })
+    local hash, report = Transaction.submit(api, signer, call, { wait = opts.wait })
+    ctx.print_json({ hash = hash, result = report.result, error = report.error })
+    return (hash and not report.error) and 0 or 1
+end
+
+function commands.query(ctx, args)
+    local url, what = args[1], args[2]
+    assert(url and what, "query needs <url> balance|storage ...")
+    local api = ctx.connect(url)
+    if what == "balance" then
+        local info = Address.inspect(assert(args[3], "query balance needs an address"))
+        if not info.valid then error("Invalid address: " .. tostring(info.error)) end
+        local account = Storage.fetch(api, "System", "Account", { info.pubkey })
+        ctx.print_json(account and account.data or json.null)
+    elseif what == "storage" then
+        local pallet, entry = (args[3] or ""):match("^([%w_]+)%.([%w_]+)$")
+        assert(pallet, "query storage needs Pallet.Entry")
+        local keys = {}
+        for i = 4, #args do keys[#keys + 1] = key_value(args[i]) end
+        local value = Storage.fetch(api, pallet, entry, keys)
+        if value == nil then value = json.null end
+        ctx.print_json(value)
+    else
+        error("Unknown query '" .. tostring(what) .. "': expected balance or storage")
+    end
+end
+
+function commands.decode(ctx, args)
+    local what = args[1]
+    if what == "address" then
+        local info = Address.inspect(assert(args[2], "decode address needs an address"))
+        ctx.print_json(info)
+        return info.valid and 0 or 1
+    elseif what == "extrinsic" then
+        assert(args[2] and args[3], "decode extrinsic needs <url|metadata_file> <0xhex>")
+        local source = args[2]
+        if source:match("^https?://") or source:match("^wss?://") then source = ctx.connect(source) end
+        ctx.print_json(Transaction.decode(require("sublua.metadata_json").load(source), args[3]))
+    else
+        error("Unknown decode target '" .. tostring(what) .. "': expected address or extrinsic")
+    end
+end
+
+-- ============================================================
+-- Entry
+-- ============================================================
+
+-- Run a command line; returns the process exit code
+-- opts: { print = print, error = stderr writer, connect = function(url) -> client }
+function Cli.run(args, opts)
+    opts = opts or {}
+    local ctx = {
+        print = opts.print or print,
+        connect = opts.connect or function(url) return RPC.new(url) end
+    }
+    ctx.print_json = function(value) ctx.print(json.encode(value)) end
+    local report = opts.error or function(msg) io.stderr:write(msg, "\n") end
+
+    local name = args[1]
+    if not name or name == "help" or name == "--help" or name == "-h" then
+        ctx.print(Cli.USAGE)
+        return name and 0 or 2
+    end
+    local command = commands[name]
+    if not command then
+        report("sublua-cli: unknown command '" .. name .. "'\n" .. Cli.USAGE)
+        return 2
+    end
+    local rest = {}
+    for i = 2, #args do rest[#rest + 1] = args[i] end
+    local ok, code = pcall(command, ctx, rest)
+    if not ok then
+        report("sublua-cli " .. name .. ": " .. tostring(code))
+        return 1
+    end
+    return code or 0
+end
+
+return Cli
@@ -0,0 +1,795 @@
+-- sublua/codec.lua
+-- Metadata-driven SCALE codec
+-- Walks the V14 portable type registry (meta.types) to convert between raw bytes
+-- and plain Lua values. Encoding accepts the same shapes decoding produces.
+--
+-- Value conventions:
+--   bool                        -> boolean
+--   u8/u16/u32, i8/i16/i32      -> number
+--   u64/u128/u256, i64..i256    -> decimal string (no precision loss)
+--   str                         -> string
+--   Vec<u8>, [u8; N]            -> "0x..." hex string
+--   Sequence / Array / Tuple    -> array table
+--   Composite (named fields)    -> table keyed by field name
+--   Composite (one unnamed)     -> the inner value (newtype unwrapping)
+--   Composite (many unnamed)    -> array table
+--   Variant                     -> { variant = "Name", value = <fields as above> }
+
+local Scale = require("sublua.scale")
+
+local Codec = {}
+
+-- Si1TypeDefPrimitive indices
+local PRIMITIVES = {
+    [0] = "bool", [1] = "char", [2] = "str",
+    [3] = "u8", [4] = "u16", [5] = "u32", [6] = "u64", [7] = "u128", [8] = "u256",
+    [9] = "i8", [10] = "i16", [11] = "i32", [12] = "i64", [13] = "i128", [14] = "i256"
+}
+
+local INT_SIZES = {
+    u8 = 1, u16 = 2, u32 = 4, u64 = 8, u128 = 16, u256 = 32,
+    i8 = 1, i16 = 2, i32 = 4, i64 = 8, i128 = 16, i256 = 32
+}
+
+Codec.PRIMITIVES = PRIMITIVES
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- Little-endian bytes of any length -> decimal string
+local function le_to_decimal(bytes)
+    local digits = {}
+    for i = 1, #bytes do digits[i] = string.byte(bytes, i) end
+
+    local out = {}
+    local nonzero = true
+    while nonzero do
+        local remainder = 0
+        nonzero = false
+        for i = #digits, 1, -1 do
+            local value = remainder * 256 + digits[i]
+            digits[i] = math.floor(value / 10)
+            remainder = value % 10
+            if digits[i] ~= 0 then nonzero = true end
+        end
+        if nonzero or remainder ~= 0 then
+            table.insert(out, 1, tostring(remainder))
+        end
+    end
+    if #out == 0 then return "0" end
+    return table.concat(out)
+end
+
+-- Two's complement negation of little-endian bytes
+local function negate_le(bytes)
+    local out = {}
+    local carry = 1
+    for i = 1, #bytes do
+        local v = (255 - string.byte(bytes, i)) + carry
+        out[i] = string.char(v % 256)
+        carry = math.floor(v / 256)
+    end
+    return table.concat(out)
+end
+
+Codec.le_to_decimal = le_to_decimal
+
+-- Resolve a type entry from the registry
+function Codec.resolve(meta, type_id)
+    local t = meta.types[type_id]
+    if not t then error("Unknown type id: " .. tostring(type_id)) end
+    return t
+end
+
+-- Primitive name for a type id, or nil
+function Codec.primitive_name(meta, type_id)
+    local def = Codec.resolve(meta, type_id).type_def
+    if def.type == "Primitive" then return PRIMITIVES[def.value] end
+    return nil
+end
+
+-- Last path segment of a type (e.g. "AccountId32"), or nil for anonymous types
+function Codec.type_name(meta, type_id)
+    local path = Codec.resolve(meta, type_id).path
+    return path and path[#path] or nil
+end
+
+-- Find a type id by its full path (e.g. "sp_runtime::DispatchError"), or nil
+function Codec.find_type(meta, path)
+    for id, t in pairs(meta.types) do
+        if t.path and #t.path > 0 and table.concat(t.path, "::") == path then
+            return id
+        end
+    end
+    return nil
+end
+
+-- Follow single-field composites down to the type actually carrying the data
+-- (used for Compact<T> where T may be a newtype such as Perbill)
+local function unwrap_newtype(meta, type_id)
+    local def = Codec.resolve(meta, type_id).type_def
+    while def.type == "Composite" and #def.fields == 1 do
+        type_id = def.fields[1].type_id
+        def = Codec.resolve(meta, type_id).type_def
+    end
+    return type_id, def
+end
+
+local decode_value
+
+local function decode_int(name, data, offset)
+    local size = INT_SIZES[name]
+    assert(offset + size - 1 <= #data, "insufficient data for " .. name)
+    local bytes = data:sub(offset, offset + size - 1)
+    local signed = name:sub(1, 1) == "i"
+    local negative = signed and string.byte(bytes, size) >= 128
+
+    if size <= 4 then
+        local v = 0
+        for i = size, 1, -1 do v = v * 256 + string.byte(bytes, i) end
+        if negative then v = v - 2 ^ (size * 8) end
+        return v, offset + size
+    end
+
+    if negative then
+        return "-" .. le_to_decimal(negate_le(bytes)), offset + size
+    end
+    return le_to_decimal(bytes), offset + size
+end
+
+local function decode_primitive(name, data, offset)
+    if name == "bool" then
+        assert(offset <= #data, "insufficient data for bool")
+        return string.byte(data, offset) ~= 0, offset + 1
+    elseif name == "str" then
+        local len, off = Scale.decode_compact(data, offset)
+        assert(off + len - 1 <= #data, "insufficient data for str")
+        return data:sub(off, off + len - 1), off + len
+    elseif name == "char" then
+        return Scale.decode_u32(data, offset)
+    end
+    return decode_int(name, data, offset)
+end
+
+-- Compact<T>: returns a number for small targets, decimal string for u64 and wider
+local function decode_compact(meta, type_id, data, offset)
+    local _, inner_def = unwrap_newtype(meta, type_id)
+    if inner_def.type == "Tuple" and #inner_def.types == 0 then
+        -- Compact<()> carries no data
+        return {}, offset
+    end
+    assert(offset <= #data, "insufficient data for compact")
+    local wide = false
+    if inner_def.type == "Primitive" then
+        local size = INT_SIZES[PRIMITIVES[inner_def.value]]
+        wide = size ~= nil and size >= 8
+    end
+
+    local b1 = string.byte(data, offset)
+    if b1 % 4 == 3 then
+        local len = math.floor(b1 / 4) + 4
+        assert(offset + len <= #data, "insufficient data for bigint compact")
+        local value = le_to_decimal(data:sub(offset + 1, offset + len))
+        if not wide then value = tonumber(value) end
+        return value, offset + 1 + len
+    end
+
+    local value, off = Scale.decode_compact(data, offset)
+    if wide then value = string.format("%.0f", value) end
+    return value, off
+end
+
+-- Decode a list of fields following the composite conventions above
+local function decode_fields(meta, fields, data, offset)
+    if #fields == 0 then
+        return nil, offset
+    end
+    if #fields == 1 and not fields[1].name then
+        return decode_value(meta, fields[1].type_id, data, offset)
+    end
+
+    local out = {}
+    local named = fields[1].name ~= nil
+    for i, field in ipairs(fields) do
+        local v
+        v, offset = decode_value(meta, field.type_id, data, offset)
+        if named then out[field.name] = v else out[i] = v end
+    end
+    return out, offset
+end
+
+local function decode_items(meta, elem_id, count, data, offset)
+    if Codec.primitive_name(meta, elem_id) == "u8" then
+        assert(offset + count - 1 <= #data, "insufficient data for byte array")
+        return "0x" .. to_hex(data:sub(offset, offset + count - 1)), offset + count
+    end
+    local out = {}
+    for i = 1, count do
+        out[i], offset = decode_value(meta, elem_id, data, offset)
+    end
+    return out, offset
+end
+
+decode_value = function(meta, type_id, data, offset)
+    local def = Codec.resolve(meta, type_id).type_def
+    local kind = def.type
+
+    if kind == "Primitive" then
+        return decode_primitive(PRIMITIVES[def.value], data, offset)
+    elseif kind == "Compact" then
+        return decode_compact(meta, def.type_id, data, offset)
+    elseif kind == "Sequence" then
+        local len, off = Scale.decode_compact(data, offset)
+        return decode_items(meta, def.type_id, len, data, off)
+    elseif kind == "Array" then
+        return decode_items(meta, def.type_id, def.len, data, offset)
+    elseif kind == "Tuple" then
+        local out = {}
+        for i, id in ipairs(def.types) do
+            out[i], offset = decode_value(meta, id, data, offset)
+        end
+        return out, offset
+    elseif kind == "Composite" then
+        local v, off = decode_fields(meta, def.fields, data, offset)
+        if v == nil then v = {} end
+        return v, off
+    elseif kind == "Variant" then
+        assert(offset <= #data, "insufficient data for enum")
+        local index = string.byte(data, offset)
+        for _, variant in ipairs(def.variants) do
+            if variant.index == index then
+                local v, off = decode_fields(meta, variant.fields, data, offset + 1)
+                return { variant = variant.name, value = v }, off
+            end
+        end
+        error("Unknown variant index " .. index .. " for type " .. tostring(type_id))
+    elseif kind == "BitSequence" then
+        local bits, off = Scale.decode_compact(data, offset)
+        local len = math.ceil(bits / 8)
+        return "0x" .. to_hex(data:sub(off, off + len - 1)), off + len
+    end
+    error("Unsupported type definition: " .. tostring(kind))
+end
+
+-- ============================================================
+-- Encoding
+-- ============================================================
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    assert(#hex % 2 == 0 and not hex:find("[^%x]"), "invalid hex string")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Decimal string -> little-endian bytes of the given size (two's complement if negative)
+local function decimal_to_le(str, size)
+    local negative = str:sub(1, 1) == "-"
+    if negative then str = str:sub(2) end
+    assert(str:match("^%d+$"), "invalid integer: " .. str)
+
+    local bytes = {}
+    for i = 1, size do bytes[i] = 0 end
+    for digit in str:gmatch("%d") do
+        local carry = tonumber(digit)
+        for i = 1, size do
+            local v = bytes[i] * 10 + carry
+            bytes[i] = v % 256
+            carry = math.floor(v / 256)
+        end
+        assert(carry == 0, "integer exceeds " .. (size * 8) .. "-bit range: " .. str)
+    end
+
+    local chars = {}
+    for i = 1, size do chars[i] = string.char(bytes[i]) end
+    local out = table.concat(chars)
+    if negative then out = negate_le(out) end
+    return out
+end
+
+-- Normalise a number or decimal string to a decimal string
+local function int_string(value)
+    if type(value) == "number" then
+        assert(value == math.floor(value), "integer expected, got " .. tostring(value))
+        return string.format("%.0f", value)
+    end
+    assert(type(value) == "string", "integer expected, got " .. type(value))
+    return value
+end
+
+local function encode_int(name, value)
+    local size = INT_SIZES[name]
+    local signed = name:sub(1, 1) == "i"
+    local str = int_string(value)
+    if not signed then
+        assert(str:sub(1, 1) ~= "-", name .. " cannot be negative")
+    end
+    local bytes = decimal_to_le(str, size)
+    if signed then
+        -- Sign bit must agree with the requested sign, otherwise the value overflowed
+        local top = string.byte(bytes, size) >= 128
+        assert(top == (str:sub(1, 1) == "-") or str:match("^%-?0+$"), "integer exceeds " .. name .. " range: " .. str)
+    end
+    return bytes
+end
+
+-- Compact<T> for values of any size (big-int mode beyond 2^30)
+local function encode_compact_any(value)
+    local str = int_string(value)
+    local n = tonumber(str)
+    if n and n < 1073741824 then
+        return Scale.encode_compact(n)
+    end
+    local bytes = decimal_to_le(str, 32)
+    local len = 32
+    while len > 4 and string.byte(bytes, len) == 0 do len = len - 1 end
+    return string.char((len - 4) * 4 + 3) .. bytes:sub(1, len)
+end
+
+Codec.encode_compact = encode_compact_any
+
+local encode_value
+
+local function encode_primitive(name, value)
+    if name == "bool" then
+        assert(type(value) == "boolean", "bool expected")
+        return value and "\1" or "\0"
+    elseif name == "str" then
+        assert(type(value) == "string", "string expected")
+        return Scale.encode_compact(#value) .. value
+    elseif name == "char" then
+        return Scale.encode_u32(value)
+    end
+    return encode_int(name, value)
+end
+
+local function encode_fields(meta, fields, value)
+    if #fields == 0 then
+        return ""
+    end
+    if #fields == 1 and not fields[1].name then
+        return encode_value(meta, fields[1].type_id, value)
+    end
+    assert(type(value) == "table", "table expected for composite fields")
+    local out = {}
+    for i, field in ipairs(fields) do
+        local v
+        if field.name then v = value[field.name] else v = value[i] end
+        if v == nil then
+            error("missing field: " .. tostring(field.name or i))
+        end
+        out[i] = encode_value(meta, field.type_id, v)
+    end
+    return table.concat(out)
+end
+
+local function encode_items(meta, elem_id, value, expected_len)
+    if Codec.primitive_name(meta, elem_id) == "u8" and type(value) == "string" then
+        local bytes = from_hex(value)
+        if expected_len then
+            assert(#bytes == expected_len, "expected " .. expected_len .. " bytes, got " .. #bytes)
+            return bytes
+        end
+        return Scale.encode_compact(#bytes) .. bytes
+    end
+    assert(type(value) == "table", "array table expected")
+    if expected_len then
+        assert(#value == expected_len, "expected " .. expected_len .. " items, got " .. #value)
+    end
+    local out = {}
+    if not expected_len then out[1] = Scale.encode_compact(#value) end
+    for _, item in ipairs(value) do
+        table.insert(out, encode_value(meta, elem_id, item))
+    end
+    return table.concat(out)
+end
+
+encode_value = function(meta, type_id, value)
+    local def = Codec.resolve(meta, type_id).type_def
+    local kind = def.type
+
+    if kind == "Primitive" then
+        return encode_primitive(PRIMITIVES[def.value], value)
+    elseif kind == "Compact" then
+        local _, inner_def = unwrap_newtype(meta, def.type_id)
+        if inner_def.type == "Tuple" and #inner_def.types == 0 then
+            return ""
+        end
+        return encode_compact_any(value)
+    elseif kind == "Sequence" then
+        return encode_items(meta, def.type_id, value, nil)
+    elseif kind == "Array" then
+        return encode_items(meta, def.type_id, value, def.len)
+    elseif kind == "Tuple" then
+        if #def.types == 0 then return "" end
+        assert(type(value) == "table", "array table expected for tuple")
+        local out = {}
+        for i, id in ipairs(def.types) do
+            out[i] = encode_value(meta, id, value[i])
+        end
+        return table.concat(out)
+    elseif kind == "Composite" then
+        return encode_fields(meta, def.fields, value)
+    elseif kind == "Variant" then
+        assert(type(value) == "table" and value.variant, "{ variant = ... } expected for enum")
+        for _, variant in ipairs(def.variants) do
+            if variant.name == value.variant then
+                return string.char(variant.index) .. encode_fields(meta, variant.fields, value.value)
+            end
+        end
+        error("Unknown variant " .. tostring(value.variant) .. " for type " .. tostring(type_id))
+    elseif kind == "BitSequence" then
+        local bytes = from_hex(value)
+        return Scale.encode_compact(#bytes * 8) .. bytes
+    end
+    error("Unsupported type definition: " .. tostring(kind))
+end
+
+-- Encode a Lua value as type_id, returning raw bytes
+function Codec.encode(meta, type_id, value)
+    return encode_value(meta, type_id, value)
+end
+
+-- Decode a value of type_id from raw bytes starting at offset (1-based)
+-- Returns value, next_offset
+function Codec.decode(meta, type_id, data, offset)
+    return decode_value(meta, type_id, data, offset or 1)
+end
+
+-- Decode a complete hex-encoded value (e.g. a storage query result)
+function Codec.decode_hex(meta, type_id, hex)
+    hex = hex:gsub("^0x", "")
+    local data = (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+    local value, offset = decode_value(meta, type_id, data, 1)
+    if offset ~= #data + 1 then
+        error("Trailing bytes after decoding type " .. tostring(type_id) .. ": " .. (#data + 1 - offset))
+    end
+    return value
+end
+
+-- ============================================================
+-- Account Formatting
+-- ============================================================
+
+local format_accounts
+
+local function format_fields(meta, fields, value, prefix)
+    if #fields == 0 then return value end
+    if #fields == 1 and not fields[1].name then
+        return format_accounts(meta, fields[1].type_id, value, prefix)
+    end
+    if type(value) ~= "table" then return value end
+    local out = {}
+    for k, v in pairs(value) do out[k] = v end
+    for i, field in ipairs(fields) do
+        local key = field.name or i
+        if out[key] ~= nil then out[key] = format_accounts(meta, field.type_id, out[key], prefix) end
+    end
+    return out
+end
+
+format_accounts = function(meta, type_id, value, prefix)
+    if value == nil then return nil end
+    local def = Codec.resolve(meta, type_id).type_def
+    local kind = def.type
+    if Codec.type_name(meta, type_id) == "AccountId32" and type(value) == "string" then
+        if not value:match("^0x" .. string.rep("%x", 64) .. "$") then return value end
+        return require("sublua.address").encode(value, prefix)
+    elseif kind == "Composite" then
+        return format_fields(meta, def.fields, value, prefix)
+    elseif kind == "Variant" then
+        if type(value) ~= "table" or value.value == nil then return value end
+        for _, variant in ipairs(def.variants) do
+            if variant.name == value.variant then
+                return { variant = value.variant, value = format_fields(meta, variant.fields, value.value, prefix) }
+            end
+        end
+    elseif (kind == "Sequence" or kind == "Array") and type(value) == "table" then
+        local out = {}
+        for i, v in ipairs(value) do out[i] = format_accounts(meta, def.type_id, v, prefix) end
+        return out
+    elseif kind == "Tuple" and type(value) == "table" then
+        local out = {}
+        for i, id in ipairs(def.types) do out[i] = format_accounts(meta, id, value[i], prefix) end
+        return out
+    end
+    return value
+end
+
+-- Copy of a decoded value with every AccountId32 in it as an SS58 address
+-- prefix: SS58 prefix (number or network name, see Address.PREFIXES)
+function Codec.format_accounts(meta, type_id, value, prefix)
+    return format_accounts(meta, type_id, value, prefix)
+end
+
+-- ============================================================
+-- JSON Values
+-- ============================================================
+
+local coerce
+local json_null  -- cjson.null, looked up on first use
+
+local function is_null(value)
+    if json_null == nil then
+        local ok, cjson = pcall(require, "cjson")
+        json_null = ok and cjson.null or false
+    end
+    return value == nil or (json_null and value == json_null)
+end
+
+local function fail(path, msg)
+    error((path ~= "" and path .. ": " or "") .. msg, 0)
+end
+
+local function join(path, key)
+    return path == "" and tostring(key) or path .. "." .. tostring(key)
+end
+
+local function is_empty(def)
+    return (def.type == "Tuple" and #def.types == 0) or (def.type == "Composite" and #def.fields == 0)
+end
+
+-- Integers from JSON numbers or decimal strings; u64 and wider come back as strings
+local function int_value(value, name, path)
+    local str
+    if type(value) == "number" then
+        if value % 1 ~= 0 then fail(path, name .. " expects an integer, got " .. tostring(value)) end
+        if math.abs(value) >= 2^53 then
+            fail(path, string.format("%.0f", value) .. " is beyond double precision; pass it as a decimal string")
+        end
+        str = string.format("%.0f", value)
+    elseif type(value) == "string" and value:match("^%-?%d+$") then
+        str = value
+    else
+        fail(path, name .. " expects a number or decimal string, got " .. (type(value) == "string" and ("'" .. value .. "'") or type(value)))
+    end
+    if (INT_SIZES[name] or 16) <= 4 then return tonumber(str) end
+    return str
+end
+
+-- Bytes from 0x hex, text (taken as its UTF-8 bytes) or an array of byte numbers
+local function bytes_value(value, path)
+    if type(value) == "string" then
+        if value:sub(1, 2) == "0x" then
+            if not value:match("^0x%x*$") or #value % 2 ~= 0 then
+                fail(path, "invalid hex '" .. value .. "': bytes expect an even number of hex digits after 0x")
+            end
+            return value:lower()
+        end
+        return "0x" .. to_hex(value)
+    elseif type(value) == "table" then
+        local out = {}
+        for i, b in ipairs(value) do
+            if type(b) ~= "number" or b < 0 or b > 255 or b % 1 ~= 0 then fail(join(path, i), "byte expected") end
+            out[i] = string.char(b)
+        end
+        return "0x" .. to_hex(table.concat(out))
+    end
+    fail(path, "bytes expect 0x hex, text or an array of byte values, got " .. type(value))
+end
+
+local function account_value(value, path)
+    if type(value) ~= "string" then fail(path, "account expects an SS58 address or 0x hex, got " .. type(value)) end
+    local info = require("sublua.address").inspect(value)
+    if not info.valid then fail(path, "invalid account '" .. value .. "': " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+local function array_of(value, path)
+    if type(value) ~= "table" then fail(path, "array expected, got " .. type(value)) end
+    return value
+end
+
+local function fields_value(meta, fields, value, path)
+    if #fields == 0 then return nil end
+    if #fields == 1 and not fields[1].name then
+        return coerce(meta, fields[1].type_id, value, path)
+    end
+    local out = {}
+    if fields[1].name then
+        if type(value) ~= "table" then fail(path, "object expected, got " .. type(value)) end
+        local known = {}
+        for _, field in ipairs(fields) do
+            known[field.name] = true
+            out[field.name] = coerce(meta, field.type_id, value[field.name], join(path, field.name))
+        end
+        for key in pairs(value) do
+            if not known[key] then fail(join(path, key), "unknown field") end
+        end
+    else
+        array_of(value, path)
+        for i, field in ipairs(fields) do out[i] = coerce(meta, field.type_id, value[i], join(path, i)) end
+    end
+    return out
+end
+
+-- Enums: { variant = "Name", value = ... }, { Name = value }, "Name" for unit variants,
+-- null / any other value for Option (None / Some), an address for MultiAddress (Id)
+local function variant_value(meta, type_id, def, value, path)
+    local by_name, names = {}, {}
+    for i, v in ipairs(def.variants) do
+        by_name[v.name] = v
+        names[i] = v.name
+    end
+    local is_option = Codec.type_name(meta, type_id) == "Option" and by_name.Some
+    local name, inner
+    if is_null(value) then
+        if not is_option then fail(path, "missing value") end
+        name = "None"
+    elseif type(value) == "string" and by_name[value] then
+        name = value
+    elseif type(value) == "table" and value.variant ~= nil then
+        name, inner = value.variant, value.value
+    elseif type(value) == "table" and next(value) ~= nil and next(value, (next(value))) == nil
+            and by_name[(next(value))] then
+        name, inner = next(value)
+    elseif is_option then
+        name, inner = "Some", value
+    elseif by_name.Id and type(value) == "string" then
+        name, inner = "Id", value
+    else
+        fail(path, "enum expects { variant = name, value = ... }, { Name: value } or a variant name ("
+            .. table.concat(names, ", ") .. ")")
+    end
+    local variant = by_name[name]
+    if not variant then
+        fail(path, "unknown variant " .. tostring(name) .. " (" .. table.concat(names, ", ") .. ")")
+    end
+    return { variant = name, value = fields_value(meta, variant.fields, inner, join(path, name)) }
+end
+
+coerce = function(meta, type_id, value, path)
+    local def = Codec.resolve(meta, type_id).type_def
+    local kind = def.type
+    if kind == "Variant" then return variant_value(meta, type_id, def, value, path) end
+    if is_null(value) then
+        if is_empty(def) or (kind == "Compact" and is_empty(select(2, unwrap_newtype(meta, def.type_id)))) then
+            return {}
+        end
+        fail(path, "missing value")
+    end
+    if Codec.type_name(meta, type_id) == "AccountId32" then return account_value(value, path) end
+
+    if kind == "Primitive" then
+        local name = PRIMITIVES[def.value]
+        if name == "bool" then
+            if value == "true" or value == "false" then return value == "true" end
+            if type(value) ~= "boolean" then fail(path, "bool expects true or false, got " .. type(value)) end
+            return value
+        elseif name == "str" then
+            if type(value) ~= "string" then fail(path, "str expects a string, got " .. type(value)) end
+            return value
+        end
+        return int_value(value, name == "char" and "u32" or name, path)
+    elseif kind == "Compact" then
+        local inner_id, inner = unwrap_newtype(meta, def.type_id)
+        if is_empty(inner) then return {} end
+        return int_value(value, Codec.primitive_name(meta, inner_id) or "u128", path)
+    elseif kind == "Sequence" or kind == "Array" then
+        if Codec.primitive_name(meta, def.type_id) == "u8" then return bytes_value(value, path) end
+        local out = {}
+        for i, item in ipairs(array_of(value, path)) do out[i] = coerce(meta, def.type_id, item, join(path, i)) end
+        return out
+    elseif kind == "Tuple" then
+        array_of(value, path)
+        local out = {}
+        for i, id in ipairs(def.types) do out[i] = coerce(meta, id, value[i], join(path, i)) end
+        return out
+    elseif kind == "Composite" then
+        return fields_value(meta, def.fields, value, path)
+    elseif kind == "BitSequence" then
+        return bytes_value(value, path)
+    end
+    return value
+end
+
+-- Value of type_id from loosely shaped JSON input, coerced by the type rather than
+-- requiring the codec's own shapes: addresses (SS58 or hex) for AccountId32 and MultiAddress,
+-- numbers or decimal strings for integers and Compacts, 0x hex / text / byte arrays for
+-- bytes (a value starting with 0x must be even-length hex), objects for named composites, { variant = ..., value = ... }, { Name: value } or
+-- "Name" for enums, null or the bare value for Option
+-- value: decoded JSON (cjson.null counts as null) or plain Lua values
+-- Returns the value in codec shapes; errors name the path of the offending value
+function Codec.from_json(meta, type_id, value)
+    return coerce(meta, type_id, value, "")
+end
+
+-- Canonical JSON of a decoded value, by type:
+--   bool, str                   -> as is
+--   u8..u32, i8..i32, char      -> number
+--   u64..u256, i64..i256        -> decimal string (Compact<T> as T)
+--   Vec<u8>, [u8; N], bits      -> "0x..." hex (AccountId32 included)
+--   Sequence / Array / Tuple    -> array; () and empty composites -> null
+--   Composite (named fields)    -> object; one unnamed field -> the inner value;
+--                                  many unnamed -> array
+--   Variant                     -> "Name" without fields, else { "Name": fields as above }
+--   Option<T>                   -> null for None, the value for Some; { "Some": value }
+--                                  when the value would be ambiguous (null or another Option)
+local canonical
+
+local function json_lib()
+    local cjson = require("cjson")
+    return cjson, cjson.null
+end
+
+local function json_array(t)
+    local cjson = json_lib()
+    if cjson.array_mt then setmetatable(t, cjson.array_mt) end
+    return t
+end
+
+local function canonical_fields(meta, fields, value)
+    local _, null = json_lib()
+    if #fields == 0 then return null end
+    if #fields == 1 and not fields[1].name then return canonical(meta, fields[1].type_id, value) end
+    local out = {}
+    if fields[1].name then
+        for _, field in ipairs(fields) do out[field.name] = canonical(meta, field.type_id, value[field.name]) end
+        return out
+    end
+    for i, field in ipairs(fields) do out[i] = canonical(meta, field.type_id, value[i]) end
+    return json_array(out)
+end
+
+canonical = function(meta, type_id, value)
+    local _, null = json_lib()
+    local def = Codec.resolve(meta, type_id).type_def
+    local kind = def.type
+    if kind == "Variant" then
+        for _, variant in ipairs(def.variants) do
+            if variant.name == value.variant then
+                if Codec.type_name(meta, type_id) == "Option" and #def.variants == 2 then
+                    if variant.name == "None" then return null end
+                    local inner_id = variant.fields[1].type_id
+                    local inner = canonical(meta, inner_id, value.value)
+                    if inner == null or Codec.type_name(meta, inner_id) == "Option" then return { Some = inner } end
+                    return inner
+                end
+                if #variant.fields == 0 then return variant.name end
+                return { [variant.name] = canonical_fields(meta, variant.fields, value.value) }
+            end
+        end
+        error("Unknown variant " .. tostring(value.variant) .. " for type " .. tostring(type_id))
+    elseif kind == "Composite" then
+        return canonical_fields(meta, def.fields, value)
+    elseif kind == "Sequence" or kind == "Array" then
+        if Codec.primitive_name(meta, def.type_id) == "u8" then return value end
+        local out = {}
+        for i, item in ipairs(value) do out[i] = canonical(meta, def.type_id, item) end
+        return json_array(out)
+    elseif kind == "Tuple" then
+        if #def.types == 0 then return null end
+        local out = {}
+        for i, id in ipairs(def.types) do out[i] = canonical(meta, id, value[i]) end
+        return json_array(out)
+    elseif kind == "Compact" then
+        local _, inner = unwrap_newtype(meta, def.type_id)
+        if is_empty(inner) then return null end
+    end
+    return value
+end
+
+-- Canonical JSON (see above) of a decoded value, as a table ready for cjson.encode
+function Codec.to_json(meta, type_id, value)
+    return canonical(meta, type_id, value)
+end
+
+-- SCALE hex -> canonical JSON text
+function Codec.value_to_json(meta, type_id, scale_hex)
+    local cjson = json_lib()
+    return cjson.encode(canonical(meta, type_id, Codec.decode_hex(meta, type_id, scale_hex)))
+end
+
+-- JSON -> SCALE "0x" hex; json: JSON text or the decoded value, in the canonical
+-- encoding or any shape Codec.from_json accepts
+function Codec.json_to_scale(meta, type_id, json)
+    if type(json) == "string" then
+        local cjson = json_lib()
+        local ok, decoded = pcall(cjson.decode, json)
+        if not ok then error("Invalid JSON: " .. tostring(decoded)) end
+        json = decoded
+    end
+    return "0x" .. to_hex(Codec.encode(meta, type_id, coerce(meta, type_id, json, "")))
+end
+
+return Codec
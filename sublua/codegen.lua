@@ -0,0 +1,234 @@
+-- sublua/codegen.lua
+-- Lua binding stubs generated from runtime metadata, in the spirit of subxt codegen
+-- Emits one module per pallet with a function per call (named arguments), per storage
+-- entry (map keys as arguments) and per constant, plus an init.lua loading them all
+-- The generated code calls into sublua.call / sublua.storage, so it stays small and
+-- keeps working against the metadata the client fetches at run time
+
+local MetadataJson = require("sublua.metadata_json")
+
+local Codegen = {}
+
+local KEYWORDS = {}
+for word in ([[and break do else elseif end false for function goto if in local nil not
+    or repeat return then true until while]]):gmatch("%a+") do KEYWORDS[word] = true end
+
+-- "TotalIssuance" -> "total_issuance", "SS58Prefix" -> "ss58_prefix"
+function Codegen.snake_case(name)
+    local s = name:gsub("(%u)(%u%l)", "%1_%2"):gsub("([%l%d])(%u)", "%1_%2")
+    return s:lower()
+end
+
+-- Valid Lua identifier for a metadata name
+local function ident(name)
+    local s = tostring(name):gsub("[^%w_]", "_")
+    if s:match("^%d") then s = "_" .. s end
+    if KEYWORDS[s] then s = s .. "_" end
+    return s
+end
+
+local function type_label(meta, field)
+    return field.type_name or MetadataJson.type_name(meta, field.type_id)
+end
+
+local function comment_docs(out, docs, indent)
+    for _, line in ipairs(docs or {}) do
+        line = line:gsub("%s+$", "")
+        table.insert(out, indent .. "--" .. (line ~= "" and (" " .. line:gsub("^ ", "")) or ""))
+    end
+end
+
+-- ============================================================
+-- Rendering
+-- ============================================================
+
+local function render_calls(meta, pallet, var, out)
+    local variants = meta.types[pallet.calls_type_id].type_def.variants or {}
+    if #variants == 0 then return end
+    table.insert(out, "-- Calls: return raw call bytes for sublua.transaction / api:create_signed")
+    table.insert(out, "-- api: RPC client or any metadata source")
+    table.insert(out, "")
+    for _, v in ipairs(variants) do
+        local params, sig, named = {}, {}, true
+        for i, f in ipairs(v.fields) do
+            if not f.name then named = false end
+            params[i] = ident(f.name or ("arg" .. i))
+            sig[i] = (f.name or params[i]) .. ": " .. type_label(meta, f)
+        end
+        local args
+        if #params == 0 then
+            args = "{}"
+        elseif named then
+            local items = {}
+            for i, f in ipairs(v.fields) do
+                items[i] = (ident(f.name) == f.name and f.name or string.format("[%q]", f.name)) .. " = " .. params[i]
+            end
+            args = "{ " .. table.concat(items, ", ") .. " }"
+        elseif #params == 1 then
+            args = params[1]
+        else
+            args = "{ " .. table.concat(params, ", ") .. " }"
+        end
+        table.insert(out, "-- " .. v.name .. "(" .. table.concat(sig, ", ") .. ")")
+        comment_docs(out, v.docs, "")
+        table.insert(out, string.format("function %s.calls.%s(%s)", var, ident(v.name),
+            table.concat({ "api", table.concat(params, ", ") }, #params > 0 and ", " or "")))
+        table.insert(out, string.format("    return Call.encode_call(api, %q, %q, %s)", pallet.name, v.name, args))
+        table.insert(out, "end")
+        table.insert(out, "")
+    end
+end
+
+local function render_storage(meta, pallet, var, out)
+    local names = {}
+    for name in pairs(pallet.storage.entries) do table.insert(names, name) end
+    if #names == 0 then return end
+    table.sort(names)
+    table.insert(out, "-- Storage: decoded values; at = block hash (default: best block)")
+    table.insert(out, "")
+    for _, name in ipairs(names) do
+        local entry = pallet.storage.entries[name]
+        local st = entry.storage_type
+        local keys, labels = {}, {}
+        if st.type == "Map" then
+            local key_ids = { st.key }
+            if #st.hashers > 1 then key_ids = meta.types[st.key].type_def.types end
+            for i, id in ipairs(key_ids) do
+                keys[i] = #key_ids == 1 and "key" or ("key" .. i)
+                labels[i] = type_label(meta, { type_id = id })
+            end
+        end
+        local value = type_label(meta, { type_id = st.value })
+        table.insert(out, "-- " .. name .. "(" .. table.concat(labels, ", ") .. ") -> " .. value
+            .. (entry.modifier == 0 and " | nil" or ""))
+        comment_docs(out, entry.docs, "")
+        local params = { "api" }
+        for _, k in ipairs(keys) do table.insert(params, k) end
+        table.insert(params, "at")
+        table.insert(out, string.format("function %s.storage.%s(%s)", var, ident(Codegen.snake_case(name)),
+            table.concat(params, ", ")))
+        table.insert(out, string.format("    return Storage.fetch(api, %q, %q, %s, at)", pallet.name, name,
+            #keys > 0 and ("{ " .. table.concat(keys, ", ") .. " }") or "{}"))
+        table.insert(out, "end")
+        table.insert(out, "")
+    end
+end
+
+local function render_constants(meta, pallet, var, out)
+    local names = {}
+    for name in pairs(pallet.constants or {}) do table.insert(names, name) end
+    if #names == 0 then return end
+    table.sort(names)
+    table.insert(out, "-- Constants: decoded from the metadata of api")
+    table.insert(out, "")
+    for _, name in ipairs(names) do
+        local c = pallet.constants[name]
+        table.insert(out, "-- " .. name .. ": " .. type_label(meta, { type_id = c.type_id }))
+        table.insert(out, string.format("function %s.constants.%s(api)", var, ident(Codegen.snake_case(name))))
+        table.insert(out, string.format("    return constant(api, %q, %q)", pallet.name, name))
+        table.insert(out, "end")
+        table.insert(out, "")
+    end
+end
+
+-- Source of the binding module for one pallet
+function Codegen.render_pallet(meta_source, pallet_name)
+    local meta = MetadataJson.load(meta_source)
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then error("Pallet not found: " .. tostring(pallet_name)) end
+    local var = ident(pallet.name)
+    local has_constants = next(pallet.constants or {}) ~= nil
+
+    local out = {
+        "-- Generated by sublua.codegen from runtime metadata; do not edit",
+        "-- Pallet " .. pallet.name .. " (index " .. pallet.index .. ")",
+        ""
+    }
+    if pallet.calls_type_id then table.insert(out, 'local Call = require("sublua.call")') end
+    if pallet.storage then table.insert(out, 'local Storage = require("sublua.storage")') end
+    if has_constants then
+        table.insert(out, 'local Metadata = require("sublua.metadata")')
+        table.insert(out, 'local Codec = require("sublua.codec")')
+    end
+    table.insert(out, "")
+    table.insert(out, "local " .. var .. " = { calls = {}, storage = {}, constants = {} }")
+    table.insert(out, "")
+    if has_constants then
+        table.insert(out, "local function constant(api, pallet, name)")
+        table.insert(out, "    local meta = Metadata.from_source(api)")
+        table.insert(out, "    local c, err = Metadata.get_constant(meta, pallet, name)")
+        table.insert(out, '    if not c then error(pallet .. "." .. name .. ": " .. err) end')
+        table.insert(out, "    return (Codec.decode(meta, c.type_id, c.value, 1))")
+        table.insert(out, "end")
+        table.insert(out, "")
+    end
+    if pallet.calls_type_id then render_calls(meta, pallet, var, out) end
+    if pallet.storage then render_storage(meta, pallet, var, out) end
+    if has_constants then render_constants(meta, pallet, var, out) end
+    table.insert(out, "return " .. var)
+    return table.concat(out, "\n") .. "\n"
+end
+
+-- Source of init.lua, loading every generated pallet module relative to its own module name
+function Codegen.render_index(pallet_names)
+    local out = {
+        "-- Generated by sublua.codegen from runtime metadata; do not edit",
+        "-- Load with require(\"<dir name>\"); pallet modules are required relative to it",
+        "",
+        "local base = ...",
+        "local Runtime = {}",
+        ""
+    }
+    for _, name in ipairs(pallet_names) do
+        table.insert(out, string.format('Runtime[%q] = require(base .. %q)', name, "." .. Codegen.snake_case(name)))
+    end
+    table.insert(out, "")
+    table.insert(out, "return Runtime")
+    return table.concat(out, "\n") .. "\n"
+end
+
+-- ============================================================
+-- Output
+-- ============================================================
+
+local function write_file(path, content)
+    local f, err = io.open(path, "wb")
+    if not f then error("Cannot write " .. path .. ": " .. tostring(err) .. " (does the directory exist?)") end
+    f:write(content)
+    f:close()
+end
+
+-- Write binding modules for a runtime
+-- metadata_source: node URL, metadata file, RPC client, parsed metadata or hex (see sublua.metadata_json)
+-- out_dir: existing directory; receives <pallet>.lua per pallet and init.lua
+-- opts: { pallets = nil } list of pallet names to generate (default: all)
+-- Returns the list of written file paths
+function Codegen.generate(metadata_source, out_dir, opts)
+    opts = opts or {}
+    local meta = MetadataJson.load(metadata_source)
+    out_dir = out_dir:gsub("[/\\]$", "")
+
+    local names = {}
+    if opts.pallets then
+        for _, name in ipairs(opts.pallets) do
+            if not meta.pallets[name] then error("Pallet not found: " .. tostring(name)) end
+            table.insert(names, name)
+        end
+    else
+        for name in pairs(meta.pallets) do table.insert(names, name) end
+    end
+    table.sort(names, function(a, b) return meta.pallets[a].index < meta.pallets[b].index end)
+
+    local files = {}
+    for _, name in ipairs(names) do
+        local path = out_dir .. "/" .. Codegen.snake_case(name) .. ".lua"
+        write_file(path, Codegen.render_pallet(meta, name))
+        table.insert(files, path)
+    end
+    local index = out_dir .. "/init.lua"
+    write_file(index, Codegen.render_index(names))
+    table.insert(files, index)
+    return files
+end
+
+return Codegen
@@ -0,0 +1,162 @@
+-- sublua/collective.lua
+-- Collective pallet instances (Council, TechnicalCommittee, AllianceMotion, ...):
+-- motions proposed, voted and closed by members, and queries of open proposals
+-- instance: the pallet name of the instance in metadata (default "Council")
+
+local Scale = require("sublua.scale")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+
+local Collective = {}
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+local function bytes(call)
+    assert(type(call) == "string", "proposal must be call bytes or 0x hex")
+    if call:match("^0x%x*$") then return from_hex(call) end
+    return call
+end
+
+-- ============================================================
+-- Call Encoding
+-- Collective.propose(threshold: Compact<u32>, proposal: Box<RuntimeCall>,
+--                    length_bound: Compact<u32>)
+-- The proposal is spliced in as raw call bytes, like Utility.as_derivative
+-- ============================================================
+
+-- Build propose call bytes
+-- proposal: raw call bytes or 0x hex; length_bound: default the proposal's length
+function Collective.encode_propose(meta_source, instance, threshold, proposal, length_bound)
+    local meta = Metadata.from_source(meta_source)
+    instance = instance or "Council"
+    local pallet = meta.pallets[instance]
+    assert(pallet, instance .. " pallet not found in metadata")
+    local call_index = pallet.calls and pallet.calls["propose"]
+    assert(call_index, "propose not found in " .. instance)
+    proposal = bytes(proposal)
+    assert(#proposal >= 2, "proposal must be raw call bytes")
+    return Call.encode_index(pallet.index, call_index) .. Scale.encode_compact(threshold)
+        .. proposal .. Scale.encode_compact(length_bound or #proposal)
+end
+
+-- ============================================================
+-- Member Actions
+-- Functions that sign return the extrinsic hex and build details, as
+-- Transaction.create_signed_from_api; opts go to it (tip, nonce, mortality, ...)
+-- ============================================================
+
+-- Propose a motion; with threshold 1 (or below) it executes right away
+-- Returns extrinsic hex, details; details.proposal_hash is the motion's hash
+function Collective.propose(api, signer, instance, threshold, proposal, opts)
+    proposal = bytes(proposal)
+    local signed, details = sign(api, signer,
+        Collective.encode_propose(api:get_metadata(), instance, threshold, proposal), opts)
+    details.proposal_hash = Call.call_hash(proposal)
+    return signed, details
+end
+
+-- Vote aye (approve = true) or nay on an open motion
+-- proposal_hash: "0x" hash; index: the motion's proposal index
+function Collective.vote(api, signer, instance, proposal_hash, index, approve, opts)
+    local call_bytes = Call.encode_call(api, instance or "Council", "vote",
+        { proposal = proposal_hash, index = index, approve = approve == true })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Close a motion once it has enough votes or its voting period ended
+-- opts: { weight = nil, length_bound = nil, ... }
+--   weight: { ref_time, proof_size } bound for executing the proposal; by default the
+--     proposal is read from storage and weighed with sublua.weights
+--   length_bound: default the stored proposal's length
+-- Other opts go to Transaction.create_signed_from_api
+function Collective.close(api, signer, instance, proposal_hash, index, opts)
+    opts = opts or {}
+    instance = instance or "Council"
+    local weight, length_bound = opts.weight, opts.length_bound
+    if not weight or not length_bound then
+        local raw = api:state_getStorage(Storage.build_key(api, instance, "ProposalOf", { proposal_hash }))
+        if type(raw) ~= "string" or raw == "0x" then
+            error("Proposal " .. proposal_hash .. " not found in " .. instance .. ".ProposalOf")
+        end
+        local proposal = from_hex(raw)
+        length_bound = length_bound or #proposal
+        if not weight then
+            local w = require("sublua.weights").query_call(api, proposal)
+            weight = { ref_time = w.ref_time, proof_size = w.proof_size }
+        end
+    end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.weight, o.length_bound = nil, nil
+    local call_bytes = Call.encode_call(api, instance, "close", {
+        proposal_hash = proposal_hash, index = index,
+        proposal_weight_bound = weight, length_bound = length_bound
+    })
+    return sign(api, signer, call_bytes, o)
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- Open motion by hash
+-- Returns { hash, index, threshold, ayes, nays, ["end"], call = { pallet, name, args },
+--   call_hex, length } or nil when no motion has that hash
+function Collective.query_proposal(api, instance, proposal_hash)
+    instance = instance or "Council"
+    local meta = api:get_metadata()
+    local voting = Storage.fetch(api, instance, "Voting", { proposal_hash })
+    if not voting then return nil end
+    local proposal = {
+        hash = proposal_hash,
+        index = voting.index,
+        threshold = voting.threshold,
+        ayes = voting.ayes,
+        nays = voting.nays,
+        ["end"] = voting["end"]
+    }
+    local raw = api:state_getStorage(Storage.build_key(meta, instance, "ProposalOf", { proposal_hash }))
+    if type(raw) == "string" and raw ~= "0x" then
+        local data = from_hex(raw)
+        proposal.call_hex, proposal.length = raw, #data
+        local ok, call = pcall(require("sublua.transaction").decode_call, meta, data)
+        if ok then proposal.call = call end
+    end
+    return proposal
+end
+
+-- All open motions, in proposal order (see query_proposal)
+function Collective.query_proposals(api, instance)
+    local list = {}
+    for _, hash in ipairs(Storage.fetch(api, instance or "Council", "Proposals") or {}) do
+        local proposal = Collective.query_proposal(api, instance, hash)
+        if proposal then table.insert(list, proposal) end
+    end
+    return list
+end
+
+-- Members ("0x" public keys) and the prime member (nil when unset)
+function Collective.query_members(api, instance)
+    return Storage.fetch(api, instance or "Council", "Members") or {}
+end
+
+function Collective.query_prime(api, instance)
+    return Storage.fetch(api, instance or "Council", "Prime")
+end
+
+-- Number of motions ever proposed (the next proposal index)
+function Collective.query_proposal_count(api, instance)
+    return Storage.fetch(api, instance or "Council", "ProposalCount") or 0
+end
+
+return Collective
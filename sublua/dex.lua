@@ -0,0 +1,156 @@
+-- sublua/dex.lua
+-- Asset Hub DEX (AssetConversion pallet): price quotes, swaps and liquidity
+-- Assets are XCM Locations as the runtime's asset kind: Dex.NATIVE for the relay token,
+-- Dex.local_asset for Assets pallet ids, or any Location table (e.g. a ForeignAssets id)
+-- Amounts are in each asset's smallest unit (numbers or decimal strings)
+
+local Call = require("sublua.call")
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+
+local Dex = {}
+
+-- Native token of the chain (DOT / KSM on Asset Hub)
+Dex.NATIVE = { parents = 1, interior = { variant = "Here" } }
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Argument types of an AssetConversion call, by name
+local function call_fields(meta, call_name)
+    local pallet = assert(meta.pallets["AssetConversion"], "AssetConversion pallet not found in metadata")
+    for _, variant in ipairs(Codec.resolve(meta, pallet.calls_type_id).type_def.variants) do
+        if variant.name == call_name then
+            local fields = {}
+            for _, field in ipairs(variant.fields) do fields[field.name] = field.type_id end
+            return fields
+        end
+    end
+    error("Call not found: AssetConversion." .. call_name)
+end
+
+-- Location of an Assets pallet asset: { parents = 0, X2(PalletInstance, GeneralIndex) }
+-- meta_source: parsed metadata, RPC client, or metadata hex (for the Assets pallet index)
+function Dex.local_asset(meta_source, id)
+    local meta = Metadata.from_source(meta_source)
+    local pallet = assert(meta.pallets["Assets"], "Assets pallet not found in metadata")
+    return { parents = 0, interior = { variant = "X2", value = {
+        { variant = "PalletInstance", value = pallet.index },
+        { variant = "GeneralIndex", value = id }
+    } } }
+end
+
+-- ============================================================
+-- Quotes (AssetConversionApi)
+-- ============================================================
+
+local function quote(api, method, asset1, asset2, amount, include_fee, at)
+    local meta = api:get_metadata()
+    local fields = call_fields(meta, "swap_exact_tokens_for_tokens")
+    local kind = call_fields(meta, "create_pool").asset1
+    local input = Codec.encode(meta, kind, asset1) .. Codec.encode(meta, kind, asset2)
+        .. Codec.encode(meta, fields.amount_in, amount) .. string.char(include_fee == false and 0 or 1)
+    local data = from_hex(api:state_call("AssetConversionApi_" .. method, "0x" .. to_hex(input), at) or "0x")
+    if #data < 17 or string.byte(data, 1) ~= 1 then return nil end
+    return Codec.le_to_decimal(data:sub(2, 17))
+end
+
+-- Amount of asset2 received for exactly amount of asset1
+-- include_fee: take the pool fee into account (default true); at: block hash
+-- Returns a decimal string, or nil when there is no pool or liquidity
+function Dex.quote_price_exact_tokens_for_tokens(api, asset1, asset2, amount, include_fee, at)
+    return quote(api, "quote_price_exact_tokens_for_tokens", asset1, asset2, amount, include_fee, at)
+end
+
+-- Amount of asset1 needed to receive exactly amount of asset2
+-- Returns a decimal string, or nil when there is no pool or liquidity
+function Dex.quote_price_tokens_for_exact_tokens(api, asset1, asset2, amount, include_fee, at)
+    return quote(api, "quote_price_tokens_for_exact_tokens", asset1, asset2, amount, include_fee, at)
+end
+
+-- ============================================================
+-- Swaps and Liquidity
+-- Functions that sign return the extrinsic hex and build details, as
+-- Transaction.create_signed_from_api; opts not listed go to it
+-- ============================================================
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+local function signer_of(api, signer)
+    return require("sublua.transaction").signer_of(api, signer)
+end
+
+local function account(api, call_name, field, who)
+    return Call.account_arg(api, "AssetConversion", call_name, field, who)
+end
+
+-- Create the pool of two assets (the native token is one of them on Asset Hub)
+function Dex.create_pool(api, signer, asset1, asset2, opts)
+    return sign(api, signer, Call.encode_call(api, "AssetConversion", "create_pool",
+        { asset1 = asset1, asset2 = asset2 }), opts)
+end
+
+-- Swap exactly amount_in of path[1] for at least amount_out_min of path[#path]
+-- path: list of asset Locations, e.g. { Dex.local_asset(api, 1984), Dex.NATIVE }
+-- opts: { send_to = signer, keep_alive = true, ... }
+function Dex.swap_exact_tokens_for_tokens(api, signer, path, amount_in, amount_out_min, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local call_bytes = Call.encode_call(api, "AssetConversion", "swap_exact_tokens_for_tokens", {
+        path = path, amount_in = amount_in, amount_out_min = amount_out_min,
+        send_to = account(api, "swap_exact_tokens_for_tokens", "send_to", opts.send_to or signer.pubkey),
+        keep_alive = opts.keep_alive ~= false
+    })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Swap at most amount_in_max of path[1] for exactly amount_out of path[#path]
+-- opts: { send_to = signer, keep_alive = true, ... }
+function Dex.swap_tokens_for_exact_tokens(api, signer, path, amount_out, amount_in_max, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local call_bytes = Call.encode_call(api, "AssetConversion", "swap_tokens_for_exact_tokens", {
+        path = path, amount_out = amount_out, amount_in_max = amount_in_max,
+        send_to = account(api, "swap_tokens_for_exact_tokens", "send_to", opts.send_to or signer.pubkey),
+        keep_alive = opts.keep_alive ~= false
+    })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Deposit both assets of a pool for LP tokens
+-- opts: { amount1_min = 0, amount2_min = 0, mint_to = signer, ... }
+function Dex.add_liquidity(api, signer, asset1, asset2, amount1_desired, amount2_desired, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local call_bytes = Call.encode_call(api, "AssetConversion", "add_liquidity", {
+        asset1 = asset1, asset2 = asset2,
+        amount1_desired = amount1_desired, amount2_desired = amount2_desired,
+        amount1_min = opts.amount1_min or 0, amount2_min = opts.amount2_min or 0,
+        mint_to = account(api, "add_liquidity", "mint_to", opts.mint_to or signer.pubkey)
+    })
+    return sign(api, signer, call_bytes, opts)
+end
+
+-- Burn LP tokens for the pool's assets
+-- opts: { amount1_min_receive = 0, amount2_min_receive = 0, withdraw_to = signer, ... }
+function Dex.remove_liquidity(api, signer, asset1, asset2, lp_token_burn, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local call_bytes = Call.encode_call(api, "AssetConversion", "remove_liquidity", {
+        asset1 = asset1, asset2 = asset2, lp_token_burn = lp_token_burn,
+        amount1_min_receive = opts.amount1_min_receive or 0, amount2_min_receive = opts.amount2_min_receive or 0,
+        withdraw_to = account(api, "remove_liquidity", "withdraw_to", opts.withdraw_to or signer.pubkey)
+    })
+    return sign(api, signer, call_bytes, opts)
+end
+
+return Dex
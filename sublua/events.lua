@@ -0,0 +1,97 @@
+-- sublua/events.lua
+-- System.Events decoding and filtering
+-- Events are decoded through the metadata type registry (see sublua.codec)
+
+local crypto = require("polkadot_crypto")
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+
+local Events = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- Filter presets: lists of { pallet = ..., name = ... } (name optional = any event)
+Events.PRESETS = {
+    -- Validator monitoring: slashes and offline reports
+    slashing = {
+        { pallet = "Staking", name = "Slashed" },
+        { pallet = "ImOnline", name = "SomeOffline" }
+    }
+}
+
+-- Storage key for System.Events: Twox128("System") + Twox128("Events")
+function Events.storage_key()
+    return "0x" .. to_hex(crypto.twox128("System")) .. to_hex(crypto.twox128("Events"))
+end
+
+-- Decode the raw Vec<EventRecord> returned by state_getStorage
+-- ss58_format: render accounts in event fields as SS58 with this prefix (default: "0x" hex)
+-- Returns a list of { index, pallet, name, fields, phase, extrinsic_index, topics }
+--   index: position in the block's events (0-based)
+--   fields: keyed by the metadata field names (a list for events with unnamed fields)
+--   phase: { variant = "ApplyExtrinsic", value = n } | { variant = "Finalization" }
+--     | { variant = "Initialization" }; extrinsic_index is n for ApplyExtrinsic, else nil
+--   topics: "0x" hashes the event was indexed under
+function Events.decode(meta, hex, ss58_format)
+    local entry, _, err = Metadata.get_storage_entry(meta, "System", "Events")
+    if err then error("System.Events: " .. err) end
+
+    local records = Codec.decode_hex(meta, entry.storage_type.value, hex)
+    if ss58_format ~= nil then
+        records = Codec.format_accounts(meta, entry.storage_type.value, records, ss58_format)
+    end
+    local events = {}
+    for i, record in ipairs(records) do
+        local outer = record.event
+        local phase = record.phase
+        events[i] = {
+            index = i - 1,
+            pallet = outer.variant,
+            name = outer.value and outer.value.variant,
+            fields = outer.value and outer.value.value,
+            phase = phase,
+            extrinsic_index = type(phase) == "table" and phase.variant == "ApplyExtrinsic" and phase.value or nil,
+            topics = record.topics or {}
+        }
+    end
+    return events
+end
+
+-- Fetch and decode all events at a block (latest if block_hash is nil)
+-- Accounts are SS58 in the client's format when one was set with api:set_ss58_format
+function Events.fetch(api, block_hash)
+    local meta = api:get_metadata()
+    local raw = api:state_getStorage(Events.storage_key(), block_hash)
+    if type(raw) ~= "string" or raw == "0x" then
+        return {}
+    end
+    return Events.decode(meta, raw, api.ss58_format)
+end
+
+-- Check an event against a filter list
+function Events.matches(filter, event)
+    for _, f in ipairs(filter) do
+        if f.pallet == event.pallet and (f.name == nil or f.name == event.name) then
+            return true
+        end
+    end
+    return false
+end
+
+-- Return the events that match a filter list (or preset name)
+function Events.filter(events, filter)
+    if type(filter) == "string" then
+        filter = assert(Events.PRESETS[filter], "Unknown filter preset: " .. filter)
+    end
+    local out = {}
+    for _, event in ipairs(events) do
+        if Events.matches(filter, event) then
+            table.insert(out, event)
+        end
+    end
+    return out
+end
+
+return Events
@@ -0,0 +1,84 @@
+-- sublua/fast_unstake.lua
+-- FastUnstake pallet: stashes not exposed in any recent era unbond at once instead of
+-- waiting out the unbonding period
+-- Registering puts the stash in a queue with a deposit; the pallet then checks past eras a
+-- few per block (the stash is at the head of the queue while that runs) and unstakes it,
+-- or keeps the deposit if the stash turns out to have been exposed
+-- Functions that sign return the extrinsic hex, like Session.set_keys
+
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Validate = require("sublua.validate")
+
+local FastUnstake = {}
+
+local function sign(api, signer, call_name, opts)
+    local call_bytes = Call.encode_call(api, "FastUnstake", call_name, {})
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- ============================================================
+-- Calls
+-- Signed by the stash's controller (the stash itself on current runtimes)
+-- ============================================================
+
+-- Queue the signer's stash for fast unstaking; the deposit (FastUnstake.Deposit) is
+-- reserved until the check finishes
+-- The stash must be bonded, nominating or idle (not validating) and have nothing unlocking
+-- opts: passed to Transaction.create_signed_from_api (tip, nonce, mortality, ...)
+function FastUnstake.register_fast_unstake(api, signer, opts)
+    return sign(api, signer, "register_fast_unstake", opts)
+end
+
+-- Leave the queue and get the deposit back; not possible once the stash is at the head
+function FastUnstake.deregister(api, signer, opts)
+    return sign(api, signer, "deregister", opts)
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- Stashes being checked now: { stashes = { { who, deposit }, ... }, checked = { era, ... } },
+-- or nil when no check is running
+-- who is the stash's "0x" public key; deposit a decimal string
+function FastUnstake.query_head(api, at)
+    local head = Storage.fetch(api, "FastUnstake", "Head", {}, at)
+    if not head then return nil end
+    local stashes = {}
+    for i, pair in ipairs(head.stashes) do stashes[i] = { who = pair[1], deposit = pair[2] } end
+    return { stashes = stashes, checked = head.checked }
+end
+
+-- Stashes waiting for their check: { { who, deposit }, ... } in storage order
+-- opts: { at = best block, page_size = 1000, cancel = nil } as for Storage.entries
+function FastUnstake.query_queue(api, opts)
+    local out = {}
+    for _, e in ipairs(Storage.entries(api, "FastUnstake", "Queue", nil, opts)) do
+        table.insert(out, { who = e.keys[1], deposit = e.value })
+    end
+    return out
+end
+
+-- Where a stash is in fast unstaking
+-- who: SS58 address or 0x public key of the stash
+-- Returns nil when it is not registered, else { state, deposit, checked }
+--   state: "queued" (waiting) or "checking" (at the head; checked lists the eras done)
+function FastUnstake.status(api, who, at)
+    local stash = Validate.account(who, "who")
+    local head = FastUnstake.query_head(api, at)
+    for _, s in ipairs(head and head.stashes or {}) do
+        if s.who == stash then return { state = "checking", deposit = s.deposit, checked = head.checked } end
+    end
+    local deposit = Storage.fetch(api, "FastUnstake", "Queue", { stash }, at)
+    if deposit == nil then return nil end
+    return { state = "queued", deposit = deposit }
+end
+
+-- Number of stashes waiting (FastUnstake.CounterForQueue)
+function FastUnstake.queue_length(api, at)
+    return Storage.fetch(api, "FastUnstake", "CounterForQueue", {}, at) or 0
+end
+
+return FastUnstake
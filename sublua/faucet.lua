@@ -0,0 +1,66 @@
+-- sublua/faucet.lua
+-- Testnet faucet client (HTTP drip API of paritytech/polkadot-testnet-faucet)
+-- POST <faucet_url> { address, parachain_id, recaptcha } -> { hash } or { error }
+
+local http = require("socket.http")
+local ltn12 = require("ltn12")
+local json = require("cjson")
+local Address = require("sublua.address")
+
+local Faucet = {}
+
+-- Request testnet funds for an address
+-- faucet_url: full drip endpoint, e.g. "http://localhost:5555/drip/web"
+-- address: SS58 address to fund
+-- opts: { parachain_id = nil, captcha = nil, timeout_ms = nil }
+--   parachain_id: fund the account on a parachain (e.g. "1000" for AssetHub)
+--   captcha: token for faucets that require one (public deployments do)
+--   timeout_ms: give up when the faucet does not answer in time
+-- Returns the transaction hash, or nil, err
+function Faucet.request_testnet_funds(faucet_url, address, opts)
+    opts = opts or {}
+    local ok, err = Address.is_valid(address)
+    if not ok then
+        return nil, "Invalid SS58 address: " .. tostring(address) .. " (" .. err .. ")"
+    end
+
+    local body = json.encode({
+        address = address,
+        parachain_id = opts.parachain_id and tostring(opts.parachain_id) or "",
+        recaptcha = opts.captcha
+    })
+    local response_body = {}
+    local saved_timeout = http.TIMEOUT
+    if opts.timeout_ms then http.TIMEOUT = opts.timeout_ms / 1000 end
+    local success, status_code = http.request{
+        url = faucet_url,
+        method = "POST",
+        headers = {
+            ["Content-Type"] = "application/json",
+            ["Content-Length"] = tostring(#body)
+        },
+        source = ltn12.source.string(body),
+        sink = ltn12.sink.table(response_body)
+    }
+    http.TIMEOUT = saved_timeout
+    if not success then
+        if status_code == "timeout" then
+            return nil, "Timeout: faucet did not answer within " .. tostring(opts.timeout_ms or (saved_timeout or 60) * 1000) .. " ms"
+        end
+        return nil, "Faucet request failed: " .. tostring(status_code)
+    end
+
+    local decoded_ok, response = pcall(json.decode, table.concat(response_body))
+    if not decoded_ok or type(response) ~= "table" then
+        return nil, "Faucet returned HTTP " .. tostring(status_code) .. " with an unreadable body"
+    end
+    if response.error then
+        return nil, "Faucet error: " .. tostring(response.error)
+    end
+    if status_code ~= 200 or not response.hash then
+        return nil, "Faucet returned HTTP " .. tostring(status_code)
+    end
+    return response.hash
+end
+
+return Faucet
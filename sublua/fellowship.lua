@@ -0,0 +1,235 @@
+-- sublua/fellowship.lua
+-- Fellowship governance: FellowshipReferenda submissions, FellowshipCollective votes
+-- and rank changes, and the Whitelist pallet the Fellowship feeds
+-- A whitelisting runs in two steps: the Fellowship votes a referendum whose proposal
+-- is encode_whitelist_call(hash); the public referendum on the Whitelisted track then
+-- carries encode_dispatch_whitelisted(call). Both are call bytes, not signed here
+-- Instances: opts.referenda (default "FellowshipReferenda") and opts.collective
+-- (default "FellowshipCollective") select other ranked collectives
+
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+local Address = require("sublua.address")
+local Preimage = require("sublua.preimage")
+
+local Fellowship = {}
+
+-- Largest proposal a referendum takes inline (BoundedInline); longer ones are noted first
+Fellowship.MAX_INLINE = 128
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function bytes(call)
+    assert(type(call) == "string", "call must be call bytes or 0x hex")
+    if call:match("^0x%x*$") then return from_hex(call) end
+    return call
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Split signing opts from this module's instance options
+local function split(opts)
+    opts = opts or {}
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.referenda, o.collective, o.origin, o.enactment = nil, nil, nil, nil
+    return opts.referenda or "FellowshipReferenda", opts.collective or "FellowshipCollective", o
+end
+
+-- ============================================================
+-- Referenda
+-- ============================================================
+
+-- Bounded<Call> for a proposal: Inline up to MAX_INLINE bytes, else a Lookup of a
+-- preimage that must already be noted (see sublua.preimage)
+function Fellowship.bounded(call)
+    call = bytes(call)
+    if #call <= Fellowship.MAX_INLINE then
+        return { variant = "Inline", value = "0x" .. to_hex(call) }
+    end
+    return Preimage.lookup_of(call)
+end
+
+-- Submit a referendum
+-- proposal: call bytes or 0x hex
+-- opts: {
+--   origin = required,     -- OriginCaller value, e.g. on the Collectives chain
+--                          -- { variant = "FellowshipOrigins", value = { variant = "Fellows" } }
+--   enactment = { variant = "After", value = 10 },  -- DispatchTime
+--   referenda = "FellowshipReferenda", ...          -- other opts go to signing
+-- }
+function Fellowship.submit_referendum(api, signer, proposal, opts)
+    assert(opts and opts.origin, "opts.origin (the proposal's OriginCaller) is required")
+    local referenda, _, o = split(opts)
+    local call_bytes = Call.encode_call(api, referenda, "submit", {
+        proposal_origin = opts.origin,
+        proposal = Fellowship.bounded(proposal),
+        enactment_moment = opts.enactment or { variant = "After", value = 10 }
+    })
+    return sign(api, signer, call_bytes, o)
+end
+
+-- Place the decision deposit so a referendum can start deciding
+function Fellowship.place_decision_deposit(api, signer, index, opts)
+    local referenda, _, o = split(opts)
+    return sign(api, signer, Call.encode_call(api, referenda, "place_decision_deposit", { index = index }), o)
+end
+
+-- Refund the decision deposit of a concluded referendum
+function Fellowship.refund_decision_deposit(api, signer, index, opts)
+    local referenda, _, o = split(opts)
+    return sign(api, signer, Call.encode_call(api, referenda, "refund_decision_deposit", { index = index }), o)
+end
+
+-- Referendum by index
+-- Returns { index, status = "Ongoing" | "Approved" | "Rejected" | ..., ongoing, since }
+--   or nil when unknown; ongoing: the ReferendumStatus (track, tally, deciding, ...)
+--   while Ongoing; since: block the referendum concluded at otherwise
+function Fellowship.query_referendum(api, index, opts)
+    local referenda = split(opts)
+    local info = Storage.fetch(api, referenda, "ReferendumInfoFor", { index })
+    if not info then return nil end
+    local result = { index = index, status = info.variant }
+    if info.variant == "Ongoing" then
+        result.ongoing = info.value
+    elseif type(info.value) == "table" then
+        result.since = info.value[1]
+    else
+        result.since = info.value
+    end
+    return result
+end
+
+-- Number of referenda ever submitted (the next index)
+function Fellowship.query_referendum_count(api, opts)
+    return Storage.fetch(api, (split(opts)), "ReferendumCount") or 0
+end
+
+-- ============================================================
+-- Ranked Collective
+-- ============================================================
+
+-- Vote aye or nay on a poll (a referendum index); vote weight follows the voter's rank
+function Fellowship.vote(api, signer, poll, aye, opts)
+    local _, collective, o = split(opts)
+    return sign(api, signer, Call.encode_call(api, collective, "vote", { poll = poll, aye = aye == true }), o)
+end
+
+local function member_call(api, signer, call_name, who, extra, opts)
+    local _, collective, o = split(opts)
+    local args = { who = Call.account_arg(api, collective, call_name, "who", who) }
+    for k, v in pairs(extra or {}) do args[k] = v end
+    return sign(api, signer, Call.encode_call(api, collective, call_name, args), o)
+end
+
+-- Rank operations; each needs the origin the runtime configures (usually a
+-- higher-ranked member or a referendum)
+function Fellowship.add_member(api, signer, who, opts)
+    return member_call(api, signer, "add_member", who, nil, opts)
+end
+
+function Fellowship.promote_member(api, signer, who, opts)
+    return member_call(api, signer, "promote_member", who, nil, opts)
+end
+
+function Fellowship.demote_member(api, signer, who, opts)
+    return member_call(api, signer, "demote_member", who, nil, opts)
+end
+
+-- min_rank: the member's current rank (witness)
+function Fellowship.remove_member(api, signer, who, min_rank, opts)
+    return member_call(api, signer, "remove_member", who, { min_rank = min_rank }, opts)
+end
+
+-- Rank of who, or nil for non-members
+function Fellowship.query_rank(api, who, opts)
+    local _, collective = split(opts)
+    local record = Storage.fetch(api, collective, "Members", { Address.convert(who, "hex") })
+    return record and record.rank
+end
+
+-- All members: { { account = "0x..", rank }, ... }, highest rank first
+function Fellowship.query_members(api, opts)
+    local _, collective = split(opts)
+    local out = {}
+    for _, item in ipairs(Storage.entries(api, collective, "Members")) do
+        table.insert(out, { account = item.keys[1], rank = item.value.rank })
+    end
+    table.sort(out, function(a, b)
+        if a.rank ~= b.rank then return a.rank > b.rank end
+        return a.account < b.account
+    end)
+    return out
+end
+
+-- Vote of who on a poll: { aye = bool, votes }, or nil when they have not voted
+function Fellowship.query_vote(api, poll, who, opts)
+    local _, collective = split(opts)
+    local record = Storage.fetch(api, collective, "Voting", { poll, Address.convert(who, "hex") })
+    if not record then return nil end
+    return { aye = record.variant == "Aye", votes = record.value }
+end
+
+-- ============================================================
+-- Whitelist
+-- ============================================================
+
+-- Whitelist.whitelist_call(call_hash) bytes, for a Fellowship referendum's proposal
+-- call: the call to whitelist (bytes or 0x hex), or its "0x" hash when hash = true
+function Fellowship.encode_whitelist_call(meta_source, call, hash)
+    local call_hash = hash and call or Call.call_hash(bytes(call))
+    return Call.encode_call(meta_source, "Whitelist", "whitelist_call", { call_hash = call_hash })
+end
+
+function Fellowship.encode_remove_whitelisted_call(meta_source, call_hash)
+    return Call.encode_call(meta_source, "Whitelist", "remove_whitelisted_call", { call_hash = call_hash })
+end
+
+-- Call bytes dispatching a whitelisted call, for the Whitelisted track's referendum
+-- opts: { inline = false, weight = nil }
+--   inline: dispatch_whitelisted_call_with_preimage carrying the call itself; by default
+--     dispatch_whitelisted_call references the noted preimage by hash and length
+--   weight: { ref_time, proof_size } witness; measured with sublua.weights by default
+function Fellowship.encode_dispatch_whitelisted(api, call, opts)
+    opts = opts or {}
+    call = bytes(call)
+    local meta = api:get_metadata()
+    if opts.inline then
+        local whitelist = assert(meta.pallets["Whitelist"], "Whitelist pallet not found in metadata")
+        local call_index = whitelist.calls and whitelist.calls["dispatch_whitelisted_call_with_preimage"]
+        assert(call_index, "dispatch_whitelisted_call_with_preimage not found in Whitelist")
+        return Call.encode_index(whitelist.index, call_index) .. call
+    end
+    local weight = opts.weight
+    if not weight then
+        local w = require("sublua.weights").query_call(api, call)
+        weight = { ref_time = w.ref_time, proof_size = w.proof_size }
+    end
+    return Call.encode_call(meta, "Whitelist", "dispatch_whitelisted_call", {
+        call_hash = Call.call_hash(call), call_encoded_len = #call, call_weight_witness = weight
+    })
+end
+
+-- Whether a call hash is currently whitelisted
+function Fellowship.is_whitelisted(api, call_hash)
+    local meta = api:get_metadata()
+    if not Metadata.get_storage_entry(meta, "Whitelist", "WhitelistedCall") then
+        error("Whitelist.WhitelistedCall not found in metadata")
+    end
+    local raw = api:state_getStorage(Storage.build_key(meta, "Whitelist", "WhitelistedCall", { call_hash }))
+    -- The value is (), so a whitelisted hash reads as "0x" and an absent one as null
+    return type(raw) == "string"
+end
+
+return Fellowship
@@ -0,0 +1,226 @@
+-- sublua/grandpa.lua
+-- GRANDPA finality justification decoding and verification
+-- A justification proves finality when precommits signed by a supermajority (by weight)
+-- of the current authority set all vote for the target block or its descendants
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Header = require("sublua.header")
+
+local Grandpa = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Message::Precommit variant index in the signed payload
+local PRECOMMIT = "\1"
+
+-- ============================================================
+-- Decoding
+-- ============================================================
+
+local function read_bytes(data, offset, len, what)
+    local bytes = data:sub(offset, offset + len - 1)
+    assert(#bytes == len, "Truncated justification: " .. what)
+    return bytes, offset + len
+end
+
+-- Decode a GrandpaJustification { round: u64, commit, votes_ancestries: Vec<Header> }
+-- data: raw bytes or "0x" hex
+-- Returns { round, round_bytes, target_hash, target_number,
+--   precommits = { { target_hash, target_number, signature, id } }, ancestries = { header } }
+--   hashes and ids are "0x" hex, signature is raw bytes
+function Grandpa.decode_justification(data)
+    if data:match("^0x%x*$") then data = from_hex(data) end
+    local j = { precommits = {}, ancestries = {} }
+    local offset = 1
+    local bytes
+    j.round_bytes = read_bytes(data, offset, 8, "round")
+    j.round, offset = Scale.decode_u64(data, offset)
+    bytes, offset = read_bytes(data, offset, 32, "target_hash")
+    j.target_hash = "0x" .. to_hex(bytes)
+    j.target_number, offset = Scale.decode_u32(data, offset)
+
+    local count
+    count, offset = Scale.decode_compact(data, offset)
+    for _ = 1, count do
+        local p = {}
+        bytes, offset = read_bytes(data, offset, 32, "precommit target")
+        p.target_hash = "0x" .. to_hex(bytes)
+        p.target_number, offset = Scale.decode_u32(data, offset)
+        p.signature, offset = read_bytes(data, offset, 64, "signature")
+        bytes, offset = read_bytes(data, offset, 32, "authority id")
+        p.id = "0x" .. to_hex(bytes)
+        table.insert(j.precommits, p)
+    end
+
+    count, offset = Scale.decode_compact(data, offset)
+    for _ = 1, count do
+        local header
+        header, offset = Header.decode(data, offset)
+        table.insert(j.ancestries, header)
+    end
+    assert(offset == #data + 1, "Trailing bytes after justification")
+    return j
+end
+
+-- ============================================================
+-- Verification
+-- ============================================================
+
+-- Authority public key as "0x" hex from hex, SS58, or raw bytes
+local function authority_key(id)
+    if #id == 32 then return "0x" .. to_hex(id) end
+    if id:match("^0x%x+$") then return id:lower() end
+    return "0x" .. to_hex(crypto.ss58_decode(id))
+end
+
+-- Bytes signed by a voter: SCALE((Message::Precommit(target), round, set_id))
+local function precommit_payload(p, round_bytes, set_id)
+    return PRECOMMIT .. from_hex(p.target_hash) .. Scale.encode_u32(p.target_number)
+        .. round_bytes .. Scale.encode_u64(set_id)
+end
+
+-- Verify a GRANDPA justification for a header
+-- justification: raw bytes or "0x" hex (as in a block's "FRNK" justification)
+-- authority_set: { set_id = n, authorities = { id | { id, weight } } }
+--   ids are public keys (hex or SS58); weight defaults to 1
+-- header: the finalized header (decoded, chain_getHeader JSON, or encoded hex),
+--   or just its "0x" block hash
+-- A voter may appear twice when it equivocated (precommits for two blocks); its weight
+-- counts once
+-- Returns true, or false, err
+function Grandpa.verify_justification(justification, authority_set, header)
+    local ok, j = pcall(Grandpa.decode_justification, justification)
+    if not ok then return false, "Invalid justification: " .. tostring(j) end
+
+    local hash, number
+    if type(header) == "string" and #header == 66 then
+        hash = header:lower()
+    else
+        if type(header) == "string" then header = Header.decode(header) end
+        if header.parentHash then header = Header.from_rpc(header) end
+        hash, number = Header.hash(header), header.number
+    end
+    if j.target_hash ~= hash or (number and j.target_number ~= number) then
+        return false, "Justification targets block #" .. j.target_number .. " " .. j.target_hash
+            .. ", not " .. (number and ("#" .. number .. " ") or "") .. hash
+    end
+
+    local weights, total = {}, 0
+    for _, authority in ipairs(authority_set.authorities) do
+        local id, weight = authority, 1
+        if type(authority) == "table" then
+            id, weight = authority.id or authority[1], authority.weight or authority[2] or 1
+        end
+        weights[authority_key(id)] = weight
+        total = total + weight
+    end
+    -- Supermajority: more than 2/3 of the weight, i.e. total - floor((total - 1) / 3)
+    local threshold = total - math.floor((total - 1) / 3)
+
+    local ancestry = {}
+    for _, h in ipairs(j.ancestries) do ancestry[Header.hash(h)] = h end
+    local used = {}
+
+    -- An equivocating voter's precommits are each checked, but its weight counts once,
+    -- as finality-grandpa does
+    local signed, seen = 0, {}
+    for _, p in ipairs(j.precommits) do
+        local weight = weights[p.id]
+        if not weight then return false, "Precommit from non-authority " .. p.id end
+
+        local payload = precommit_payload(p, j.round_bytes, authority_set.set_id)
+        if not crypto.ed25519_verify(from_hex(p.id), payload, p.signature) then
+            return false, "Bad precommit signature from " .. p.id
+        end
+
+        -- The precommit must vote for the target or one of its descendants
+        local cursor = p.target_hash
+        while cursor ~= j.target_hash do
+            local h = ancestry[cursor]
+            if not h or h.number <= j.target_number then
+                return false, "Precommit target " .. p.target_hash .. " does not descend from the justified block"
+            end
+            used[cursor] = true
+            cursor = h.parent_hash
+        end
+        if not seen[p.id] then
+            seen[p.id] = true
+            signed = signed + weight
+        end
+    end
+
+    for h in pairs(ancestry) do
+        if not used[h] then return false, "Unused header " .. h .. " in votes ancestries" end
+    end
+    if signed < threshold then
+        return false, "Not enough precommits: weight " .. signed .. " of " .. total .. ", need " .. threshold
+    end
+    return true
+end
+
+-- verify_justification with the authority set as JSON
+-- justification_hex: "0x" hex; header_hex: SCALE-encoded header or block hash, "0x" hex
+-- authorities_json: { "set_id": n, "authorities": [ id | [id, weight] | { "id", "weight" }, ... ] }
+-- Returns true, or false, err
+function Grandpa.verify_grandpa_justification(justification_hex, authorities_json, header_hex)
+    local ok, set = pcall(require("cjson").decode, authorities_json)
+    if not ok or type(set) ~= "table" then return false, "Invalid authorities JSON: " .. tostring(set) end
+    if type(set.set_id) ~= "number" or type(set.authorities) ~= "table" then
+        return false, "Invalid authorities JSON: expected { set_id, authorities }"
+    end
+    for i, authority in ipairs(set.authorities) do
+        if type(authority) ~= "string" and type(authority) ~= "table" then
+            return false, "Invalid authorities JSON: authority " .. i .. " is a " .. type(authority)
+        end
+    end
+    return Grandpa.verify_justification(justification_hex, set, header_hex)
+end
+
+-- ============================================================
+-- Fetching
+-- ============================================================
+
+-- Current authority set from a node, ready for verify_justification
+-- Uses the GrandpaApi runtime API and the Grandpa.CurrentSetId storage value
+-- Returns { set_id, authorities = { { id, weight } } }
+function Grandpa.get_authority_set(api, at)
+    local data = from_hex(api:state_call("GrandpaApi_grandpa_authorities", "0x", at))
+    local count, offset = Scale.decode_compact(data, 1)
+    local authorities = {}
+    for _ = 1, count do
+        local id = data:sub(offset, offset + 31)
+        local weight
+        weight, offset = Scale.decode_u64(data, offset + 32)
+        table.insert(authorities, { id = "0x" .. to_hex(id), weight = weight })
+    end
+
+    local key = "0x" .. to_hex(crypto.twox128("Grandpa") .. crypto.twox128("CurrentSetId"))
+    local set_id = api:state_getStorage(key, at)
+    return {
+        set_id = type(set_id) == "string" and Scale.decode_u64(from_hex(set_id)) or 0,
+        authorities = authorities
+    }
+end
+
+-- Justification proving finality of a block, via grandpa_proveFinality
+-- The node answers with the justification of the last block of that block's authority set,
+-- which finalizes the requested block too; verify it against that block's header
+-- Returns justification hex, justified block hash; or nil when the node has no proof
+function Grandpa.get_justification(api, block_number)
+    local proof = api:grandpa_proveFinality(block_number)
+    if type(proof) ~= "string" then return nil end
+    local data = from_hex(proof)
+    -- FinalityProof { block: H256, justification: Vec<u8>, unknown_headers: Vec<Header> }
+    local len, offset = Scale.decode_compact(data, 33)
+    return "0x" .. to_hex(data:sub(offset, offset + len - 1)), "0x" .. to_hex(data:sub(1, 32))
+end
+
+return Grandpa
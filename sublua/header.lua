@@ -0,0 +1,124 @@
+-- sublua/header.lua
+-- Block header SCALE decoding, encoding and hashing
+-- Header { parent_hash, number: Compact<u32>, state_root, extrinsics_root, digest: Vec<DigestItem> }
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+
+local Header = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- DigestItem variants; engine-tagged items carry a 4-byte ConsensusEngineId
+Header.DIGEST_ITEMS = {
+    [0] = { name = "Other" },
+    [4] = { name = "Consensus", engine = true },
+    [5] = { name = "Seal", engine = true },
+    [6] = { name = "PreRuntime", engine = true },
+    [8] = { name = "RuntimeEnvironmentUpdated", empty = true }
+}
+
+local DIGEST_INDEX = {}
+for index, item in pairs(Header.DIGEST_ITEMS) do DIGEST_INDEX[item.name] = index end
+
+local function read_bytes(data, offset, len, what)
+    local bytes = data:sub(offset, offset + len - 1)
+    assert(#bytes == len, "Truncated header: " .. what)
+    return bytes, offset + len
+end
+
+-- Decode a SCALE-encoded header (raw bytes or "0x" hex)
+-- Returns { parent_hash, number, state_root, extrinsics_root,
+--   digest = { { type, engine = "BABE"|nil, data = "0x.." } } }, next offset
+function Header.decode(data, offset)
+    if data:match("^0x%x*$") then data = from_hex(data) end
+    offset = offset or 1
+    local header = { digest = {} }
+    local bytes
+    bytes, offset = read_bytes(data, offset, 32, "parent_hash")
+    header.parent_hash = "0x" .. to_hex(bytes)
+    header.number, offset = Scale.decode_compact(data, offset)
+    bytes, offset = read_bytes(data, offset, 32, "state_root")
+    header.state_root = "0x" .. to_hex(bytes)
+    bytes, offset = read_bytes(data, offset, 32, "extrinsics_root")
+    header.extrinsics_root = "0x" .. to_hex(bytes)
+
+    local count
+    count, offset = Scale.decode_compact(data, offset)
+    for _ = 1, count do
+        local index = string.byte(data, offset)
+        local kind = Header.DIGEST_ITEMS[index]
+        assert(kind, "Unknown digest item " .. tostring(index))
+        offset = offset + 1
+        local item = { type = kind.name }
+        if kind.engine then
+            item.engine, offset = read_bytes(data, offset, 4, "engine id")
+        end
+        if not kind.empty then
+            local len
+            len, offset = Scale.decode_compact(data, offset)
+            bytes, offset = read_bytes(data, offset, len, "digest item")
+            item.data = "0x" .. to_hex(bytes)
+        end
+        table.insert(header.digest, item)
+    end
+    return header, offset
+end
+
+-- Convert a header from chain_getHeader JSON into the decoded form
+function Header.from_rpc(json_header)
+    local logs = json_header.digest and json_header.digest.logs or {}
+    local digest_bytes = { Scale.encode_compact(#logs) }
+    for _, log in ipairs(logs) do table.insert(digest_bytes, from_hex(log)) end
+    local number = json_header.number
+    if type(number) == "string" then number = tonumber((number:gsub("^0x", "")), 16) end
+
+    local encoded = from_hex(json_header.parentHash) .. Scale.encode_compact(number)
+        .. from_hex(json_header.stateRoot) .. from_hex(json_header.extrinsicsRoot)
+        .. table.concat(digest_bytes)
+    return (Header.decode(encoded))
+end
+
+-- SCALE-encode a header (decoded form or chain_getHeader JSON); returns raw bytes
+function Header.encode(header)
+    if header.parentHash then header = Header.from_rpc(header) end
+    local out = {
+        from_hex(header.parent_hash), Scale.encode_compact(header.number),
+        from_hex(header.state_root), from_hex(header.extrinsics_root),
+        Scale.encode_compact(#header.digest)
+    }
+    for _, item in ipairs(header.digest) do
+        local index = DIGEST_INDEX[item.type]
+        assert(index, "Unknown digest item " .. tostring(item.type))
+        table.insert(out, string.char(index))
+        if Header.DIGEST_ITEMS[index].engine then
+            assert(#item.engine == 4, "Digest engine id must be 4 bytes")
+            table.insert(out, item.engine)
+        end
+        if not Header.DIGEST_ITEMS[index].empty then
+            local bytes = from_hex(item.data)
+            table.insert(out, Scale.encode_compact(#bytes) .. bytes)
+        end
+    end
+    return table.concat(out)
+end
+
+-- Block hash: blake2_256 of the encoded header
+-- header: decoded form, chain_getHeader JSON, raw bytes, or "0x" hex
+-- Returns "0x" hash hex
+function Header.hash(header)
+    if type(header) == "string" then
+        if header:match("^0x%x*$") then header = from_hex(header) end
+        return "0x" .. to_hex(crypto.blake2b(header, 32))
+    end
+    return "0x" .. to_hex(crypto.blake2b(Header.encode(header), 32))
+end
+
+return Header
@@ -0,0 +1,316 @@
+-- sublua/identity.lua
+-- Identity pallet: registrations with their judgements, sub-identities, display names,
+-- and set_identity / clear_identity
+-- Data fields come back as text for Raw values and as { hash_type = hash } for hashed
+-- ones; IdentityOf values from before usernames (a bare Registration) and after
+-- ((Registration, Option<Username>)) both decode
+-- On Polkadot, Kusama and Westend identities live on the People parachain: every function
+-- here takes the relay client and routes to the people chain (see Identity.chain); block
+-- hashes passed as at are then people-chain blocks
+
+local Storage = require("sublua.storage")
+local Address = require("sublua.address")
+local Call = require("sublua.call")
+local Codec = require("sublua.codec")
+local Validate = require("sublua.validate")
+
+local Identity = {}
+
+-- People chain endpoints by relay spec name, used when the relay has no Identity pallet
+Identity.PEOPLE_CHAINS = {
+    polkadot = "https://polkadot-people-rpc.polkadot.io",
+    kusama = "https://kusama-people-rpc.polkadot.io",
+    westend = "https://westend-people-rpc.polkadot.io"
+}
+
+-- Judgements a registrar gives after checking the identity
+Identity.VERIFIED = { Reasonable = true, KnownGood = true }
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function account_hex(who)
+    local info = Address.inspect(who)
+    if not info.valid then error("Invalid account " .. tostring(who) .. ": " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+-- ============================================================
+-- Data
+-- ============================================================
+
+-- Text of an identity Data value
+-- Returns the text for Raw values, nil for None, and { [hash type] = "0x.." } for hashes
+function Identity.data_text(data)
+    if type(data) ~= "table" or not data.variant or data.variant == "None" then return nil end
+    if data.variant:match("^Raw%d+$") then
+        if data.variant == "Raw0" then return "" end
+        return from_hex(type(data.value) == "string" and data.value or "")
+    end
+    return { [data.variant] = data.value }
+end
+
+-- ============================================================
+-- Identity Chain
+-- ============================================================
+
+-- Use another client (or URL) for identity calls made with api
+-- people: RPC client, URL, or nil to go back to automatic routing
+function Identity.set_people_chain(api, people)
+    if type(people) == "string" then people = require("sublua.rpc").new(people) end
+    api.identity_chain = people
+end
+
+-- Client holding the Identity pallet for api: the one set with set_people_chain, api
+-- itself when its runtime has the pallet, or the People chain of a known relay
+-- (Identity.PEOPLE_CHAINS, by spec name; the client is created once and kept)
+function Identity.chain(api)
+    if api.identity_chain then return api.identity_chain end
+    if api:get_metadata().pallets["Identity"] then return api end
+    local version = api:state_getRuntimeVersion() or {}
+    local url = Identity.PEOPLE_CHAINS[version.specName or ""]
+    if not url then
+        error("No Identity pallet on this chain; pass its people chain with Identity.set_people_chain")
+    end
+    Identity.set_people_chain(api, url)
+    return api.identity_chain
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- On-chain identity of an account
+-- at: block hash (default: best block)
+-- Returns { display, legal, web, email, twitter, matrix, github, discord, image,
+--   judgements = { { registrar, judgement }, ... }, verified, deposit, username, fields }
+--   or nil without one; fields holds every IdentityInfo field as Identity.data_text gives it;
+--   verified: some registrar judged Reasonable or KnownGood
+function Identity.query_identity(api, who, at)
+    local value = Storage.fetch(Identity.chain(api), "Identity", "IdentityOf", { account_hex(who) }, at)
+    if not value then return nil end
+    local registration, username = value, nil
+    if value.info == nil and type(value[1]) == "table" then
+        registration = value[1]
+        local name = value[2]
+        if type(name) == "table" and name.variant == "Some" then name = name.value end
+        if type(name) == "string" then username = from_hex(name) end
+    end
+
+    local fields = {}
+    for k, v in pairs(registration.info or {}) do
+        if type(v) == "table" and v.variant then fields[k] = Identity.data_text(v) end
+    end
+    local result = {
+        display = fields.display, legal = fields.legal, web = fields.web, email = fields.email,
+        twitter = fields.twitter, matrix = fields.matrix or fields.riot, github = fields.github,
+        discord = fields.discord, image = fields.image,
+        judgements = {}, verified = false,
+        deposit = registration.deposit, username = username, fields = fields
+    }
+    for _, j in ipairs(registration.judgements or {}) do
+        local judgement = type(j[2]) == "table" and j[2].variant or j[2]
+        table.insert(result.judgements, { registrar = j[1], judgement = judgement })
+        if Identity.VERIFIED[judgement] then result.verified = true end
+    end
+    return result
+end
+
+-- Parent of a sub-identity
+-- Returns { parent = "0x" account, name } or nil when who is not a sub-account
+function Identity.query_super(api, who, at)
+    local value = Storage.fetch(Identity.chain(api), "Identity", "SuperOf", { account_hex(who) }, at)
+    if not value then return nil end
+    local name = Identity.data_text(value[2])
+    return { parent = value[1], name = type(name) == "string" and name or nil }
+end
+
+-- Sub-accounts of an identity
+-- Returns { deposit, subs = { "0x" account, ... } }
+function Identity.query_subs(api, who, at)
+    local value = Storage.fetch(Identity.chain(api), "Identity", "SubsOf", { account_hex(who) }, at)
+    if not value then return { deposit = "0", subs = {} } end
+    return { deposit = value[1], subs = value[2] or {} }
+end
+
+-- Name to show for an account: its own display name, or "Parent/Sub" for sub-accounts
+-- Returns name (or nil), verified (of the account, or of the parent for subs)
+function Identity.display_name(api, who, at)
+    local own = Identity.query_identity(api, who, at)
+    if own and own.display then return own.display, own.verified end
+    local super = Identity.query_super(api, who, at)
+    if super then
+        local parent = Identity.query_identity(api, super.parent, at)
+        local parent_name = parent and parent.display or super.parent
+        return parent_name .. "/" .. (super.name or "?"), parent ~= nil and parent.verified
+    end
+    return nil, false
+end
+
+-- ============================================================
+-- Setting an Identity
+-- Signed on the identity chain; functions that sign return the extrinsic hex and build
+-- details, as Transaction.create_signed_from_api; opts go to it
+-- ============================================================
+
+-- Largest RawN variant of a Data type
+local function max_bytes(meta, data_type)
+    local max = 0
+    for _, variant in ipairs(Codec.resolve(meta, data_type).type_def.variants or {}) do
+        local n = tonumber(variant.name:match("^Raw(%d+)$"))
+        if n and n > max then max = n end
+    end
+    return max > 0 and max or 32
+end
+
+local function data_of(text, path, limit)
+    text = Validate.text(text, path, { max_bytes = limit, optional = true })
+    if text == nil or text == "" then return { variant = "None" } end
+    return { variant = "Raw" .. #text, value = "0x" .. to_hex(text) }
+end
+
+-- IdentityInfo fields of the chain's runtime, by name, with the Data type holding them
+local function info_fields(meta)
+    local pallet = assert(meta.pallets["Identity"], "Identity pallet not found in metadata")
+    local info_type
+    for _, variant in ipairs(Codec.resolve(meta, pallet.calls_type_id).type_def.variants) do
+        if variant.name == "set_identity" then info_type = variant.fields[1].type_id end
+    end
+    assert(info_type, "set_identity not found in Identity")
+    return Codec.resolve(meta, info_type).type_def.fields
+end
+
+-- Longest text an identity field holds on this chain (the largest RawN of its Data type;
+-- 32 on current runtimes)
+function Identity.max_field_bytes(meta_source)
+    local meta = require("sublua.metadata").from_source(meta_source)
+    for _, field in ipairs(info_fields(meta)) do
+        if field.name == "display" then return max_bytes(meta, field.type_id) end
+    end
+    return 32
+end
+
+-- IdentityInfo value for the chain's runtime
+-- info: { display, legal, web, email, twitter, matrix, github, discord, image } as text,
+--   or that object as JSON (matrix is written to riot on older runtimes); fields the runtime
+--   has but info lacks are left empty, and so are additional fields (MaxAdditionalFields
+--   cannot be exceeded)
+-- A field the chain does not have, or a value that is not text within the chain's length
+-- (Identity.max_field_bytes), fails naming it ("info.web: expected text of at most 32 bytes, got number 7")
+function Identity.encode_info(meta_source, info)
+    local meta = require("sublua.metadata").from_source(meta_source)
+    local def_fields = info_fields(meta)
+    local fields = {}
+    for _, field in ipairs(def_fields) do
+        if field.name ~= "additional" and field.name ~= "pgp_fingerprint" then
+            table.insert(fields, field.name)
+            if field.name == "riot" then table.insert(fields, "matrix") end
+        end
+    end
+    info = Validate.decode(info, "info", "a JSON object of identity fields")
+    Validate.object(info, "info", fields, "an object of identity fields")
+    local value = {}
+    for _, field in ipairs(def_fields) do
+        local name = field.name
+        if name == "additional" then
+            value[name] = {}
+        elseif name == "pgp_fingerprint" then
+            value[name] = { variant = "None" }
+        elseif name == "riot" and info.matrix ~= nil then
+            value[name] = data_of(info.matrix, "info.matrix", max_bytes(meta, field.type_id))
+        else
+            value[name] = data_of(info[name], "info." .. name, max_bytes(meta, field.type_id))
+        end
+    end
+    return value
+end
+
+local PLAIN_TEXT = { "text without control characters or surrounding spaces", function(v)
+    return not v:find("%c") and not v:match("^%s") and not v:match("%s$")
+end }
+
+-- Format rules for Identity.check_info: field -> { expectation, test(text) }
+-- Registrars judge identities whose fields do not look like what they claim to be Erroneous
+Identity.FORMATS = {
+    display = PLAIN_TEXT,
+    legal = PLAIN_TEXT,
+    email = { "an address like name@example.org", function(v)
+        return v:match("^[^%s@]+@[^%s@]+%.[^%s@%.]+$") ~= nil
+    end },
+    web = { "a domain or http(s) URL without spaces", function(v)
+        return v:match("^https?://[^%s/]+%.[^%s]+$") ~= nil or v:match("^[%w%-]+%.[^%s:]+$") ~= nil
+    end },
+    twitter = { "a handle like @name (up to 15 letters, digits or _)", function(v)
+        local handle = v:match("^@?([%w_]+)$")
+        return handle ~= nil and #handle <= 15
+    end },
+    matrix = { "a Matrix ID like @name:example.org", function(v)
+        return v:match("^@[^%s:]+:[%w%-%.]+%.%a+[:%d]*$") ~= nil
+    end },
+    github = { "a GitHub username (up to 39 letters, digits or -)", function(v)
+        local name = v:match("^@?([%w%-]+)$")
+        return name ~= nil and #name <= 39 and not name:match("^%-")
+    end },
+    discord = { "a Discord username (2 to 32 lowercase letters, digits, _ or .) or name#1234", function(v)
+        return (#v >= 2 and #v <= 32 and v:match("^[%l%d_%.]+$") ~= nil) or v:match("^[^#%c]+#%d%d%d%d$") ~= nil
+    end }
+}
+
+-- Problems set_identity would refuse to submit, in field order: values the chain cannot
+-- hold and values that do not look like their field (Identity.FORMATS)
+-- Returns a list of "info.<field>: expected ..., got ..." messages (empty when fine)
+function Identity.check_info(meta_source, info)
+    local meta = require("sublua.metadata").from_source(meta_source)
+    local ok, err = pcall(Identity.encode_info, meta, info)
+    if not ok then return { err } end
+    info = Validate.decode(info, "info")
+    local problems = {}
+    for _, field in ipairs(info_fields(meta)) do
+        local name = field.name
+        if name == "riot" then name = "matrix" end
+        local text = info[name]
+        if name == "matrix" and text == nil then text = info.riot end
+        local rule = Identity.FORMATS[name]
+        if rule and type(text) == "string" and text ~= "" and not rule[2](text) then
+            table.insert(problems, "info." .. name .. ": expected " .. rule[1] .. ", got " .. Validate.describe(text))
+        end
+    end
+    return problems
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Set the signer's identity (reserves the identity deposit)
+-- info is checked first (Identity.check_info) so a bad field fails here, not on chain after fees
+-- opts: { allow_invalid = false, ... }; others go to Transaction.create_signed_from_api
+--   allow_invalid: skip the format checks (lengths still apply: longer text cannot be encoded)
+function Identity.set_identity(api, signer, info, opts)
+    opts = opts or {}
+    local chain = Identity.chain(api)
+    if not opts.allow_invalid then
+        local problem = Identity.check_info(chain, info)[1]
+        if problem then error(problem, 0) end
+    end
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.allow_invalid = nil
+    local call_bytes = Call.encode_call(chain, "Identity", "set_identity", { info = Identity.encode_info(chain, info) })
+    return sign(chain, signer, call_bytes, o)
+end
+
+-- Clear the signer's identity and sub-identities (returns the deposits)
+function Identity.clear_identity(api, signer, opts)
+    local chain = Identity.chain(api)
+    return sign(chain, signer, Call.encode_call(chain, "Identity", "clear_identity", {}), opts)
+end
+
+return Identity
@@ -0,0 +1,308 @@
+-- sublua/indexer.lua
+-- Embedded indexer: follows finalized blocks and keeps the transfers and events of a set
+-- of watched accounts in a local store, so an application can show transaction history
+-- without running an external indexing service
+-- The default store is a SQLite file through lsqlite3 (luarocks install lsqlite3), which
+-- is only loaded when an indexer is opened on a path; Indexer.memory_store() needs nothing
+
+local json = require("cjson")
+local Address = require("sublua.address")
+local Scanner = require("sublua.scanner")
+
+local Indexer = {}
+Indexer.__index = Indexer
+
+-- Normalise an account (SS58 address or 0x public key) to lowercase 0x hex
+local function account_hex(address)
+    local info = Address.inspect(address)
+    if not info.valid then error("Invalid address " .. tostring(address) .. ": " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+-- Every watched account found anywhere in a decoded value (AccountId32 decodes to 0x hex,
+-- or to SS58 in events of a client with an ss58_format)
+local function collect_accounts(value, watched, found)
+    if type(value) == "string" then
+        local key = value:lower()
+        if not key:match("^0x") and #value >= 46 and #value <= 50 then
+            local info = Address.inspect(value)
+            if info.valid and info.format == "ss58" then key = info.pubkey:lower() end
+        end
+        if watched[key] then found[key] = true end
+    elseif type(value) == "table" then
+        for _, v in pairs(value) do collect_accounts(v, watched, found) end
+    end
+    return found
+end
+
+local function in_range(row, range)
+    return (not range.from_block or row.block_number >= range.from_block)
+        and (not range.to_block or row.block_number <= range.to_block)
+end
+
+-- ============================================================
+-- Stores
+-- ============================================================
+-- A store keeps three things: transfer rows, event rows (one per watched account the
+-- event mentions) and a small key/value state holding the next block to index.
+-- Interface: begin(), commit(), rollback(), get_state(key), set_state(key, value),
+--   add_transfer(row), add_event(row), transfers(account, range), events(account, range)
+-- Rows are returned in chain order: block_number, then event_index
+
+-- In-memory store (lost when the process exits; for tests and short-lived tools)
+function Indexer.memory_store()
+    local store = { state = {}, transfer_rows = {}, event_rows = {} }
+    local pending
+
+    function store:begin() pending = { state = {}, transfers = {}, events = {} } end
+    function store:commit()
+        for k, v in pairs(pending.state) do self.state[k] = v end
+        for _, row in ipairs(pending.transfers) do table.insert(self.transfer_rows, row) end
+        for _, row in ipairs(pending.events) do table.insert(self.event_rows, row) end
+        pending = nil
+    end
+    function store:rollback() pending = nil end
+    function store:get_state(key) return self.state[key] end
+    function store:set_state(key, value) pending.state[key] = value end
+    function store:add_transfer(row) table.insert(pending.transfers, row) end
+    function store:add_event(row) table.insert(pending.events, row) end
+    function store:transfers(account, range)
+        local out = {}
+        for _, row in ipairs(self.transfer_rows) do
+            if (row.from == account or row.to == account) and in_range(row, range) then
+                table.insert(out, row)
+            end
+        end
+        return out
+    end
+    function store:events(account, range)
+        local out = {}
+        for _, row in ipairs(self.event_rows) do
+            if row.account == account and in_range(row, range) then table.insert(out, row) end
+        end
+        return out
+    end
+    return store
+end
+
+local SCHEMA = [[
+CREATE TABLE IF NOT EXISTS transfers (
+    block_number INTEGER NOT NULL, block_hash TEXT NOT NULL, event_index INTEGER NOT NULL,
+    extrinsic_index INTEGER, sender TEXT NOT NULL, recipient TEXT NOT NULL, amount TEXT NOT NULL,
+    PRIMARY KEY (block_number, event_index));
+CREATE INDEX IF NOT EXISTS transfers_sender ON transfers (sender, block_number);
+CREATE INDEX IF NOT EXISTS transfers_recipient ON transfers (recipient, block_number);
+CREATE TABLE IF NOT EXISTS events (
+    block_number INTEGER NOT NULL, block_hash TEXT NOT NULL, event_index INTEGER NOT NULL,
+    extrinsic_index INTEGER, account TEXT NOT NULL, pallet TEXT NOT NULL, name TEXT,
+    fields TEXT NOT NULL, PRIMARY KEY (account, block_number, event_index));
+CREATE TABLE IF NOT EXISTS state (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+]]
+
+local TRANSFER_COLUMNS = "block_number, block_hash, event_index, extrinsic_index, sender, recipient, amount"
+local EVENT_COLUMNS = "block_number, block_hash, event_index, extrinsic_index, account, pallet, name, fields"
+
+-- SQLite store
+-- db: a path (opened with lsqlite3) or an already open lsqlite3 database
+function Indexer.sqlite_store(db)
+    if type(db) == "string" then
+        local ok, sqlite3 = pcall(require, "lsqlite3")
+        if not ok then error("SQLite indexer store needs lsqlite3 (luarocks install lsqlite3)") end
+        local path = db
+        db = sqlite3.open(path)
+        if not db then error("Cannot open SQLite database " .. path) end
+    end
+
+    local function exec(sql)
+        if db:exec(sql) ~= 0 then error("SQLite: " .. db:errmsg()) end
+    end
+
+    local function run(sql, ...)
+        local stmt = db:prepare(sql)
+        if not stmt then error("SQLite: " .. db:errmsg()) end
+        stmt:bind_values(...)
+        local rows = {}
+        for row in stmt:nrows() do table.insert(rows, row) end
+        stmt:finalize()
+        return rows
+    end
+
+    -- Block numbers are u32
+    local function bounds(range)
+        return range.from_block or 0, range.to_block or 4294967295
+    end
+
+    exec(SCHEMA)
+
+    local store = { db = db }
+    function store:begin() exec("BEGIN") end
+    function store:commit() exec("COMMIT") end
+    function store:rollback() exec("ROLLBACK") end
+    function store:get_state(key)
+        local row = run("SELECT value FROM state WHERE key = ?", key)[1]
+        return row and row.value
+    end
+    function store:set_state(key, value)
+        run("INSERT OR REPLACE INTO state (key, value) VALUES (?, ?)", key, tostring(value))
+    end
+    function store:add_transfer(r)
+        run("INSERT OR REPLACE INTO transfers (" .. TRANSFER_COLUMNS .. ") VALUES (?, ?, ?, ?, ?, ?, ?)",
+            r.block_number, r.block_hash, r.event_index, r.extrinsic_index, r.from, r.to, r.amount)
+    end
+    function store:add_event(r)
+        run("INSERT OR REPLACE INTO events (" .. EVENT_COLUMNS .. ") VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            r.block_number, r.block_hash, r.event_index, r.extrinsic_index, r.account, r.pallet, r.name,
+            json.encode(r.fields))
+    end
+    function store:transfers(account, range)
+        local rows = run("SELECT " .. TRANSFER_COLUMNS .. " FROM transfers"
+            .. " WHERE (sender = ? OR recipient = ?) AND block_number >= ? AND block_number <= ?"
+            .. " ORDER BY block_number, event_index", account, account, bounds(range))
+        for _, row in ipairs(rows) do
+            row.from, row.to, row.sender, row.recipient = row.sender, row.recipient, nil, nil
+        end
+        return rows
+    end
+    function store:events(account, range)
+        local rows = run("SELECT " .. EVENT_COLUMNS .. " FROM events"
+            .. " WHERE account = ? AND block_number >= ? AND block_number <= ?"
+            .. " ORDER BY block_number, event_index", account, bounds(range))
+        for _, row in ipairs(rows) do row.fields = json.decode(row.fields) end
+        return rows
+    end
+    return store
+end
+
+-- ============================================================
+-- Indexer
+-- ============================================================
+
+-- Open an indexer
+-- config: {
+--   addresses = { ... },      -- SS58 addresses or 0x public keys to watch
+--   db = "history.sqlite",    -- SQLite path, or store = a store table (see above)
+--   start_block = nil,        -- first block on a fresh store (default: the finalized head)
+--   batch_blocks = 500        -- blocks indexed per sync() call at most
+-- }
+function Indexer.new(api, config)
+    assert(config and config.addresses and #config.addresses > 0, "Indexer needs at least one address")
+    local watched = {}
+    for _, address in ipairs(config.addresses) do watched[account_hex(address)] = true end
+    local store = config.store or (config.db and Indexer.sqlite_store(config.db))
+    assert(store, "Indexer needs db (a SQLite path) or store")
+    return setmetatable({
+        api = api,
+        store = store,
+        watched = watched,
+        start_block = config.start_block,
+        batch_blocks = config.batch_blocks or 500
+    }, Indexer)
+end
+
+-- Next block to index, as recorded in the store
+function Indexer:next_block()
+    return tonumber(self.store:get_state("next_block"))
+end
+
+local function finalized_number(api)
+    local header = api:chain_getHeader(api:chain_getFinalizedHead())
+    return tonumber((header.number:gsub("^0x", "")), 16)
+end
+
+-- Index one block inside a store transaction; the next_block state moves with the rows,
+-- so a crash never leaves a block half indexed
+function Indexer:index_block(number)
+    local hash = self.api:chain_getBlockHash(number)
+    local items = Scanner.scan_block(self.api, number, { events = true }, hash)
+    local store = self.store
+    store:begin()
+    local ok, err = pcall(function()
+        for _, item in ipairs(items) do
+            local event = item.event
+            local found = collect_accounts(event.fields, self.watched, {})
+            local row_base = {
+                block_number = number,
+                block_hash = hash,
+                event_index = item.event_index,
+                extrinsic_index = item.extrinsic_index
+            }
+            if event.pallet == "Balances" and event.name == "Transfer" and next(found) then
+                local row = {}
+                for k, v in pairs(row_base) do row[k] = v end
+                row.from, row.to, row.amount = account_hex(event.fields.from), account_hex(event.fields.to), tostring(event.fields.amount)
+                store:add_transfer(row)
+            end
+            for account in pairs(found) do
+                local row = { account = account, pallet = event.pallet, name = event.name, fields = event.fields }
+                for k, v in pairs(row_base) do row[k] = v end
+                store:add_event(row)
+            end
+        end
+        store:set_state("next_block", number + 1)
+    end)
+    if not ok then
+        store:rollback()
+        error(err, 0)
+    end
+    store:commit()
+end
+
+-- Index finalized blocks that are not in the store yet, up to batch_blocks of them
+-- Returns the number of blocks indexed, or nil, err (blocks before the failing one stay indexed)
+function Indexer:sync()
+    local head = finalized_number(self.api)
+    local next_block = self:next_block() or self.start_block or head
+    local last = math.min(head, next_block + self.batch_blocks - 1)
+    local count = 0
+    for n = next_block, last do
+        local ok, err = pcall(self.index_block, self, n)
+        if not ok then return nil, "Block " .. n .. ": " .. tostring(err) end
+        count = count + 1
+    end
+    return count
+end
+
+-- Keep indexing as blocks are finalized
+-- opts: { poll_interval = 6, sleep = nil, stop = nil, on_error = nil }
+--   sleep: function(seconds), defaults to socket.sleep
+--   stop(): checked after every sync; return true to leave the loop
+--   on_error(err): called when a sync fails; the loop retries after poll_interval
+function Indexer:follow(opts)
+    opts = opts or {}
+    local sleep = opts.sleep or require("socket").sleep
+    local interval = opts.poll_interval or 6
+    while true do
+        local count, err = self:sync()
+        if not count and opts.on_error then opts.on_error(err) end
+        if opts.stop and opts.stop() then return end
+        -- Still catching up: go again straight away
+        if count ~= self.batch_blocks then sleep(interval) end
+    end
+end
+
+-- Open an indexer and follow the chain (blocks until opts.stop returns true)
+function Indexer.start(api, config, opts)
+    local indexer = Indexer.new(api, config)
+    indexer:follow(opts)
+    return indexer
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- Transfers to or from an address
+-- range: { from_block, to_block } (both optional, inclusive)
+-- Returns a list of { block_number, block_hash, event_index, extrinsic_index, from, to, amount }
+--   from/to are 0x public keys; amount is a decimal string
+function Indexer:query_transfers(address, range)
+    return self.store:transfers(account_hex(address), range or {})
+end
+
+-- Events that mention an address
+-- Returns a list of { block_number, block_hash, event_index, extrinsic_index, account, pallet, name, fields }
+function Indexer:query_events(address, range)
+    return self.store:events(account_hex(address), range or {})
+end
+
+return Indexer
@@ -18,6 +18,11 @@ if not status then
     end
 end
 
+local abi_ok, abi_err = require("sublua.abi").check(crypto)
+if not abi_ok then
+    error(abi_err)
+end
+
 -- Preload under both names so submodules find it regardless of install method
 package.preload["polkadot_crypto"] = package.preload["polkadot_crypto"] or function() return crypto end
 package.preload["sublua.polkadot_crypto"] = package.preload["sublua.polkadot_crypto"] or function() return crypto end
@@ -32,10 +37,73 @@ SubLua.call = require("sublua.call")
 SubLua.rpc = require("sublua.rpc")
 SubLua.metadata = require("sublua.metadata")
 SubLua.xcm = require("sublua.xcm")
+SubLua.session = require("sublua.session")
+SubLua.codec = require("sublua.codec")
+SubLua.events = require("sublua.events")
+SubLua.staking = require("sublua.staking")
+SubLua.storage = require("sublua.storage")
+SubLua.utility = require("sublua.utility")
+SubLua.uos = require("sublua.uos")
+SubLua.faucet = require("sublua.faucet")
+SubLua.trie = require("sublua.trie")
+SubLua.chainspec = require("sublua.chainspec")
+SubLua.header = require("sublua.header")
+SubLua.grandpa = require("sublua.grandpa")
+SubLua.registry = require("sublua.registry")
+SubLua.address = require("sublua.address")
+SubLua.portfolio = require("sublua.portfolio")
+SubLua.prices = require("sublua.prices")
+SubLua.types = require("sublua.types")
+SubLua.metadata_json = require("sublua.metadata_json")
+SubLua.codegen = require("sublua.codegen")
+SubLua.cli = require("sublua.cli")
+SubLua.abi = require("sublua.abi")
+SubLua.scanner = require("sublua.scanner")
+SubLua.indexer = require("sublua.indexer")
+SubLua.webhooks = require("sublua.webhooks")
+SubLua.merchant = require("sublua.merchant")
+SubLua.weights = require("sublua.weights")
+SubLua.bip39 = require("sublua.bip39")
+SubLua.keystore = require("sublua.keystore")
+SubLua.keychain = require("sublua.keychain")
+SubLua.ledger = require("sublua.ledger")
+SubLua.vrf = require("sublua.vrf")
+SubLua.random = require("sublua.random")
+SubLua.society = require("sublua.society")
+SubLua.collective = require("sublua.collective")
+SubLua.preimage = require("sublua.preimage")
+SubLua.fellowship = require("sublua.fellowship")
+SubLua.parachains = require("sublua.parachains")
+SubLua.beefy = require("sublua.beefy")
+SubLua.snapshot = require("sublua.snapshot")
+SubLua.runtime = require("sublua.runtime")
+SubLua.offchain = require("sublua.offchain")
+SubLua.multisig = require("sublua.multisig")
+SubLua.identity = require("sublua.identity")
+SubLua.addressbook = require("sublua.addressbook")
+SubLua.assets = require("sublua.assets")
+SubLua.dex = require("sublua.dex")
+SubLua.txbuilder = require("sublua.txbuilder")
+SubLua.subscription = require("sublua.subscription")
+SubLua.pump = require("sublua.pump")
+SubLua.cancel = require("sublua.cancel")
+SubLua.introspect = require("sublua.introspect")
+SubLua.validate = require("sublua.validate")
+SubLua.safety = require("sublua.safety")
+SubLua.simulate = require("sublua.simulate")
+SubLua.fast_unstake = require("sublua.fast_unstake")
+SubLua.locks = require("sublua.locks")
+SubLua.testchain = require("sublua.testchain")
+SubLua.selfcheck = require("sublua.selfcheck")
 
 -- Convenience
-function SubLua.connect(url)
-    return SubLua.rpc.new(url)
+function SubLua.connect(url, opts)
+    return SubLua.rpc.new(url, opts)
+end
+
+-- Live SDK objects and what they hold, for leak checks (see sublua.introspect)
+function SubLua.debug_stats(opts)
+    return SubLua.introspect.debug_stats(opts)
 end
 
 return SubLua
@@ -0,0 +1,53 @@
+-- sublua/introspect.lua
+-- Live object accounting for leak hunting: constructors register what they create (clients,
+-- subscriptions, event queues, pumps, metrics servers, storage readers) in weak tables, so
+-- debug_stats counts the objects the host still references and what they hold
+-- Nothing here keeps an object alive; Lua frees what is unreachable on its own, and only
+-- open metrics servers hold an OS resource that needs an explicit close()
+
+local Introspect = {}
+
+local live = {}      -- kind -> weak set of objects
+local measures = {}  -- kind -> function(object, stats) adding what the object holds
+
+-- Register an object under kind (a stats field name, e.g. "clients")
+-- measure(object, stats): optional, adds the object's holdings to stats fields
+function Introspect.track(kind, object, measure)
+    local set = live[kind]
+    if not set then
+        set = setmetatable({}, { __mode = "k" })
+        live[kind] = set
+    end
+    set[object] = true
+    if measure then measures[kind] = measure end
+    return object
+end
+
+-- Counts of live SDK objects and what they hold
+-- opts: { collect = false }: run a full garbage collection first, so objects the host no
+--   longer references are not counted
+-- Returns { lua_memory_kb, clients, subscriptions, event_queues, queued_events, pumps,
+--   pump_tasks, pump_callbacks, metrics_servers, open_metrics_servers, storage_readers,
+--   cached_storage_keys } (kinds nothing was created for count 0)
+function Introspect.debug_stats(opts)
+    if opts and opts.collect then collectgarbage("collect") end
+    local stats = {
+        clients = 0, subscriptions = 0, event_queues = 0, queued_events = 0,
+        pumps = 0, pump_tasks = 0, pump_callbacks = 0,
+        metrics_servers = 0, open_metrics_servers = 0,
+        storage_readers = 0, cached_storage_keys = 0
+    }
+    for kind, set in pairs(live) do
+        local measure = measures[kind]
+        local count = 0
+        for object in pairs(set) do
+            count = count + 1
+            if measure then measure(object, stats) end
+        end
+        stats[kind] = (stats[kind] or 0) + count
+    end
+    stats.lua_memory_kb = collectgarbage("count")
+    return stats
+end
+
+return Introspect
@@ -0,0 +1,124 @@
+-- sublua/keychain.lua
+-- OS credential stores through their command-line front ends: macOS Keychain
+-- (security), libsecret / GNOME Keyring / KWallet (secret-tool) and the Windows
+-- Credential Manager's PasswordVault (PowerShell)
+-- Optional: keystores use it only when opened with { keychain = true }
+-- Values are hex strings, passed on stdin so they never show up in a process list
+
+local Keychain = {}
+Keychain.__index = Keychain
+
+Keychain.PLATFORMS = { macos = true, libsecret = true, windows = true }
+
+local VAULT = "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime];"
+    .. "$v=New-Object Windows.Security.Credentials.PasswordVault;"
+
+-- Run a shell command, writing input to its stdin when given
+-- Returns stdout ("" when input was written), or nil, err when the command failed
+-- The exit status goes through a file: close() does not return it on Lua 5.1, and in
+-- write mode there is no output to judge by
+function Keychain.exec(cmd, input)
+    local status = os.tmpname()
+    local wrapped
+    if package.config:sub(1, 1) == "\\" then
+        wrapped = cmd .. ' && echo 0 > "' .. status .. '"'
+    else
+        wrapped = "(" .. cmd .. "); echo $? > '" .. status .. "'"
+    end
+    local f, err = io.popen(wrapped, input and "w" or "r")
+    if not f then
+        os.remove(status)
+        return nil, tostring(err)
+    end
+    local out = ""
+    if input then f:write(input) else out = f:read("*a") or "" end
+    f:close()
+    local sf = io.open(status, "r")
+    local code = sf and sf:read("*a"):match("%d+")
+    if sf then sf:close() end
+    os.remove(status)
+    if code ~= "0" then return nil, cmd:match("^%S+") .. " exited with " .. (code or "an error") end
+    return out
+end
+
+-- The credential store of this machine: "macos", "libsecret", "windows", or nil
+function Keychain.detect()
+    if package.config:sub(1, 1) == "\\" then return "windows" end
+    local uname = Keychain.exec("uname -s 2>/dev/null")
+    if uname and uname:match("^Darwin") then return "macos" end
+    local found = Keychain.exec("command -v secret-tool 2>/dev/null")
+    if found and found:match("%S") then return "libsecret" end
+    return nil
+end
+
+-- opts: {
+--   platform = Keychain.detect(),
+--   service = "sublua",   -- service / label the entries are filed under
+--   exec = Keychain.exec  -- function(cmd, input) -> stdout | nil, err
+-- }
+function Keychain.new(opts)
+    opts = opts or {}
+    local platform = opts.platform or Keychain.detect()
+    if not platform then error("No OS credential store found (needs macOS security, secret-tool or Windows)") end
+    assert(Keychain.PLATFORMS[platform], "Unknown keychain platform: " .. tostring(platform))
+    local service = opts.service or "sublua"
+    assert(service:match("^[%w_%-%.:/]+$"), "Keychain service must be letters, digits or _-.:/")
+    return setmetatable({ platform = platform, service = service, exec = opts.exec or Keychain.exec }, Keychain)
+end
+
+local function check_account(account)
+    assert(type(account) == "string" and account:match("^[%w_%-%.:/]+$"), "Keychain account must be letters, digits or _-.:/")
+end
+
+-- Store a value (hex string), replacing any previous one
+function Keychain:set(account, value)
+    check_account(account)
+    assert(type(value) == "string" and value:match("^%x+$"), "Keychain values must be hex strings")
+    local s, a = self.service, account
+    local ok, err
+    if self.platform == "macos" then
+        ok, err = self.exec("security -i", "add-generic-password -U -s " .. s .. " -a " .. a .. " -w " .. value .. "\n")
+    elseif self.platform == "libsecret" then
+        ok, err = self.exec("secret-tool store --label='" .. s .. " " .. a .. "' service '" .. s .. "' account '" .. a .. "'", value)
+    else
+        ok, err = self.exec('powershell -NoProfile -NonInteractive -Command "' .. VAULT
+            .. "$v.Add((New-Object Windows.Security.Credentials.PasswordCredential('" .. s .. "','" .. a
+            .. "',[Console]::In.ReadLine())))\"", value .. "\n")
+    end
+    if not ok then error("Cannot store " .. a .. " in the " .. self.platform .. " keychain: " .. tostring(err)) end
+    return true
+end
+
+-- Returns the stored value, or nil when there is none
+function Keychain:get(account)
+    check_account(account)
+    local s, a = self.service, account
+    local out
+    if self.platform == "macos" then
+        out = self.exec("security find-generic-password -s '" .. s .. "' -a '" .. a .. "' -w 2>/dev/null")
+    elseif self.platform == "libsecret" then
+        out = self.exec("secret-tool lookup service '" .. s .. "' account '" .. a .. "' 2>/dev/null")
+    else
+        out = self.exec('powershell -NoProfile -NonInteractive -Command "' .. VAULT
+            .. "try{$c=$v.Retrieve('" .. s .. "','" .. a .. "');$c.RetrievePassword();$c.Password}catch{}\"")
+    end
+    local value = out and out:match("^%s*(%x+)%s*$")
+    return value
+end
+
+-- Remove a value; removing a missing one is not an error
+function Keychain:delete(account)
+    check_account(account)
+    local s, a = self.service, account
+    if self.platform == "macos" then
+        self.exec("security delete-generic-password -s '" .. s .. "' -a '" .. a .. "' >/dev/null 2>&1")
+    elseif self.platform == "libsecret" then
+        self.exec("secret-tool clear service '" .. s .. "' account '" .. a .. "' 2>/dev/null")
+    else
+        self.exec('powershell -NoProfile -NonInteractive -Command "' .. VAULT
+            .. "try{$v.Remove($v.Retrieve('" .. s .. "','" .. a .. "'))}catch{}\"")
+    end
+    return true
+end
+
+return Keychain
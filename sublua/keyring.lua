@@ -6,7 +6,13 @@ local crypto = require("polkadot_crypto")
 
 local Keyring = {}
 
-function Keyring.from_seed(seed_hex)
+-- opts: { secure = false }
+--   secure: keep the secret key in a C-side handle (wiped by pair:destroy() or
+--     on garbage collection) instead of exposing pair.seed. The seed argument
+--     itself is a Lua string and cannot be wiped; drop references to it.
+-- pair.seed is deprecated: prefer secure pairs and pair:sign()
+function Keyring.from_seed(seed_hex, opts)
+    opts = opts or {}
     local seed
     if seed_hex:match("^0x") then
         seed = (seed_hex:gsub("^0x", ""):gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
@@ -18,11 +24,28 @@ function Keyring.from_seed(seed_hex)
         error("Seed must be 32 bytes")
     end
     
+    if opts.secure then
+        local handle = crypto.keypair_create(seed)
+        local pubkey = handle:public_key()
+        return {
+            pubkey = pubkey,
+            scheme = "ed25519",
+            address = crypto.ss58_encode(pubkey, 42),
+            sign = function(self, msg)
+                return handle:sign(msg)
+            end,
+            destroy = function(self)
+                handle:destroy()
+            end
+        }
+    end
+    
     local pubkey = crypto.ed25519_keypair_from_seed(seed)
     
     return {
         seed = seed,
         pubkey = pubkey,
+        scheme = "ed25519",
         address = crypto.ss58_encode(pubkey, 42), -- Default Substrate 42
         sign = function(self, msg)
             return crypto.ed25519_sign(self.seed, msg)
@@ -30,13 +53,108 @@ function Keyring.from_seed(seed_hex)
     }
 end
 
-function Keyring.from_uri(uri)
+-- Ed25519 pair from a BIP-39 phrase, matching polkadot.js / subkey for the bare phrase
+-- (derivation paths like "//Alice" after the phrase are not supported)
+-- opts: as for from_seed, plus password = BIP-39 passphrase (subkey's --password)
+function Keyring.from_mnemonic(phrase, opts)
+    local seed, err = require("sublua.bip39").to_seed(phrase, opts and opts.password)
+    if not seed then
+        error(err)
+    end
+    return Keyring.from_seed(seed:sub(1, 32), opts)
+end
+
+-- New random phrase and its pair
+-- opts: as for from_mnemonic, plus words = 12 and random = nil
+--   random: a Random.deterministic source for reproducible test output (see sublua.random)
+-- Returns pair, phrase
+function Keyring.generate(opts)
+    opts = opts or {}
+    local phrase = require("sublua.bip39").generate(opts.words, opts.random)
+    return Keyring.from_mnemonic(phrase, opts), phrase
+end
+
+-- Search random seeds for an SS58 address containing pattern (case-sensitive)
+-- opts: { prefix = 42, max_tries = 1000000, at_start = false, secure = false, cancel = nil, random = nil }
+--   at_start: the pattern must follow the address's first (network) character
+--   cancel: token (see sublua.cancel), checked every 256 tries
+--   random: a Random.deterministic source for reproducible test output (see sublua.random)
+-- Returns pair, seed hex, tries; or nil, err after max_tries or when cancelled
+function Keyring.vanity(pattern, opts)
+    opts = opts or {}
+    assert(type(pattern) == "string" and pattern ~= "", "Vanity pattern must be a non-empty string")
+    assert(not pattern:find("[^1-9A-HJ-NP-Za-km-z]"), "Vanity pattern must use base58 characters only")
+    local random = require("sublua.random").source(opts.random)
+    local Cancel = require("sublua.cancel")
+    local prefix = opts.prefix or 42
+    for tries = 1, opts.max_tries or 1000000 do
+        if tries % 256 == 1 then
+            local cancelled = Cancel.check(opts.cancel, "vanity search after " .. (tries - 1) .. " tries")
+            if cancelled then return nil, cancelled end
+        end
+        local seed = random(32)
+        local address = crypto.ss58_encode(crypto.ed25519_keypair_from_seed(seed), prefix)
+        local at = address:find(pattern, 1, true)
+        if at and (not opts.at_start or at == 2) then
+            local pair = Keyring.from_seed(seed, { secure = opts.secure })
+            pair.address = address
+            return pair, "0x" .. (seed:gsub(".", function(c) return string.format("%02x", string.byte(c)) end)), tries
+        end
+    end
+    return nil, "No address containing '" .. pattern .. "' in " .. (opts.max_tries or 1000000) .. " tries"
+end
+
+-- Signer whose key lives elsewhere (Ledger, HSM, KMS): the seed never enters Lua
+-- public_key: 32-byte raw key, "0x" hex, or SS58 address
+-- callback(payload, user_data) -> 64-byte signature (raw or "0x" hex)
+--   payload is exactly what must be signed (already hashed if over 256 bytes)
+-- opts: { scheme = "ed25519" | "sr25519" } (selects the MultiSignature variant)
+function Keyring.from_external(public_key, callback, user_data, opts)
+    opts = opts or {}
+    assert(type(callback) == "function", "callback must be a function")
+    local scheme = opts.scheme or "ed25519"
+    assert(scheme == "ed25519" or scheme == "sr25519", "Unsupported signature scheme: " .. tostring(scheme))
+    
+    local pubkey
+    if #public_key == 32 then
+        pubkey = public_key
+    elseif public_key:match("^0x%x+$") then
+        pubkey = (public_key:gsub("^0x", ""):gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+    else
+        pubkey = crypto.ss58_decode(public_key)
+    end
+    if #pubkey ~= 32 then
+        error("Public key must be 32 bytes")
+    end
+    
+    return {
+        pubkey = pubkey,
+        scheme = scheme,
+        external = true,
+        address = crypto.ss58_encode(pubkey, 42),
+        sign = function(self, msg)
+            local sig = callback(msg, user_data)
+            if type(sig) ~= "string" then
+                error("External signer returned no signature")
+            end
+            if sig:match("^0x") then
+                sig = (sig:gsub("^0x", ""):gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+            end
+            if #sig ~= 64 then
+                error("External signer returned " .. #sig .. " bytes, expected a 64-byte signature")
+            end
+            return sig
+        end
+    }
+end
+
+function Keyring.from_uri(uri, opts)
     if uri == "//Alice" then
-        return Keyring.from_seed(string.rep("a", 32)) -- Mock Ed25519 "Alice"
+        return Keyring.from_seed(string.rep("a", 32), opts) -- Mock Ed25519 "Alice"
     elseif uri == "//Bob" then
-        return Keyring.from_seed(string.rep("b", 32)) -- Mock Ed25519 "Bob"
+        return Keyring.from_seed(string.rep("b", 32), opts) -- Mock Ed25519 "Bob"
     elseif uri == "//Charlie" then
-        return Keyring.from_seed(string.rep("c", 32)) -- Mock Ed25519 "Charlie"
+        return Keyring.from_seed(string.rep("c", 32), opts) -- Mock Ed25519 "Charlie"
     end
     error("Keyring URI parsing not fully implemented in Pure Lua (requires BIP39/PBKDF2)")
 end
@@ -0,0 +1,240 @@
+-- sublua/keystore.lua
+-- Password-protected keystore of named accounts, so apps keep a keystore file and a
+-- master password instead of plaintext mnemonics in their config
+-- The master password goes through Argon2id; every secret is sealed with
+-- XChaCha20-Poly1305 under its own random nonce, bound to the account name
+-- The file is JSON: names, addresses and KDF parameters are readable, secrets are not
+-- With { keychain = ... } the sealed secrets go to the OS credential store instead of
+-- the file (see sublua.keychain); the master password is still needed to use them
+
+local json = require("cjson")
+local crypto = require("polkadot_crypto")
+local Keyring = require("sublua.keyring")
+local Transaction = require("sublua.transaction")
+local Random = require("sublua.random")
+
+local Keystore = {}
+Keystore.__index = Keystore
+
+Keystore.FORMAT = 1
+
+-- Marker sealed with the derived key; unsealing it checks the master password
+local CHECK = "sublua-keystore"
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function seal(key, plaintext, ad)
+    local nonce = Random.bytes(24)
+    return to_hex(nonce .. crypto.aead_lock(key, nonce, plaintext, ad))
+end
+
+local function unseal(key, sealed, ad)
+    local data = from_hex(sealed)
+    return crypto.aead_unlock(key, data:sub(1, 24), data:sub(25), ad)
+end
+
+-- Keypair for a stored secret: 0x seed hex, raw 32-byte seed, or BIP-39 phrase
+-- A phrase with a passphrase is stored as phrase .. "\0" .. passphrase
+-- Any 32-byte secret is a raw seed: the shortest phrase is 12 words, far longer
+local function pair_of(secret, opts)
+    if secret:match("^0x%x+$") or #secret == 32 then
+        return Keyring.from_seed(secret, opts)
+    end
+    local phrase, password = secret:match("^([^%z]*)%z(.*)$")
+    local o = {}
+    for k, v in pairs(opts or {}) do o[k] = v end
+    o.password = password
+    return Keyring.from_mnemonic(phrase or secret, o)
+end
+
+-- ============================================================
+-- Setup
+-- ============================================================
+
+-- Open a keystore file, creating it when it does not exist
+-- master_password: unlocks every account; a wrong one is an error
+-- opts: {
+--   kib = 65536, passes = 3,  -- Argon2id cost for a new keystore (existing files keep
+--                             -- the cost they were created with)
+--   keychain = nil            -- true, Keychain.new opts, or a Keychain: accounts added
+--                             -- from now on keep their sealed secret in the OS store
+-- }
+function Keystore.init(path, master_password, opts)
+    opts = opts or {}
+    assert(type(path) == "string" and path ~= "", "Keystore needs a file path")
+    assert(type(master_password) == "string" and master_password ~= "", "Master password must be a non-empty string")
+    local self = setmetatable({ path = path, keychain_opts = opts.keychain }, Keystore)
+
+    local f = io.open(path, "rb")
+    if f then
+        local text = f:read("*a")
+        f:close()
+        local ok, state = pcall(json.decode, text)
+        if not ok or type(state) ~= "table" or type(state.kdf) ~= "table" then
+            error("Unreadable keystore file " .. path)
+        end
+        if state.format ~= Keystore.FORMAT then
+            error("Unsupported keystore format " .. tostring(state.format))
+        end
+        self.state = state
+        self.key = crypto.argon2id(master_password, from_hex(state.kdf.salt), state.kdf.kib, state.kdf.passes)
+        if unseal(self.key, state.check, CHECK) ~= CHECK then
+            self.key = nil
+            error("Wrong master password for keystore " .. path)
+        end
+        return self
+    end
+
+    local kdf = { name = "argon2id", kib = opts.kib or 65536, passes = opts.passes or 3, salt = to_hex(Random.bytes(16)) }
+    self.key = crypto.argon2id(master_password, from_hex(kdf.salt), kdf.kib, kdf.passes)
+    self.state = {
+        format = Keystore.FORMAT,
+        id = to_hex(Random.bytes(8)),
+        kdf = kdf,
+        check = seal(self.key, CHECK, CHECK),
+        accounts = {}
+    }
+    self:save()
+    return self
+end
+
+function Keystore:save()
+    local tmp = self.path .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write keystore: " .. tostring(err)) end
+    f:write(json.encode(self.state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, self.path)
+    if not ok then error("Cannot write keystore: " .. tostring(rename_err)) end
+end
+
+-- OS credential store, created on first use
+-- Entries are named "<keystore id>/<account name>" so keystores sharing a store do not clash
+function Keystore:keychain()
+    if not self.keychain_store then
+        local o = self.keychain_opts
+        if type(o) == "table" and getmetatable(o) then
+            self.keychain_store = o
+        else
+            self.keychain_store = require("sublua.keychain").new(type(o) == "table" and o or nil)
+        end
+    end
+    return self.keychain_store
+end
+
+local function keychain_account(self, name)
+    -- Files written before keychain support have no id; derive one from the salt
+    self.state.id = self.state.id or self.state.kdf.salt:sub(1, 16)
+    return self.state.id .. "/" .. name
+end
+
+-- ============================================================
+-- Accounts
+-- ============================================================
+
+-- Store a secret under a name
+-- secret: BIP-39 phrase, or a 32-byte seed (raw or 0x hex)
+-- password: BIP-39 passphrase of the phrase, sealed along with it (optional)
+-- Returns the account's SS58 address
+function Keystore:add(name, secret, password)
+    assert(type(name) == "string" and name:match("^[%w_%-%.]+$"), "Account name must be letters, digits, '_', '-' or '.'")
+    assert(not self.state.accounts[name], "Account '" .. name .. "' already exists")
+    assert(type(secret) == "string", "Secret must be a string")
+    -- Raw seeds are kept as 0x hex so they never pass for a phrase (a seed may hold 0x20)
+    if #secret == 32 and not secret:match("^0x%x+$") then secret = "0x" .. to_hex(secret) end
+    if password and password ~= "" then
+        assert(type(password) == "string" and not password:find("%z"), "Passphrase must be a string without NUL bytes")
+        assert(secret:find(" "), "A passphrase only applies to mnemonic phrases")
+        secret = secret .. "\0" .. password
+    end
+    local pair = pair_of(secret)
+    local account = { address = pair.address, scheme = pair.scheme }
+    if self.keychain_opts then
+        local keychain, entry, sealed = self:keychain(), keychain_account(self, name), seal(self.key, secret, name)
+        keychain:set(entry, sealed)
+        -- Read it back: some stores accept a write and keep nothing (no session bus, a locked keyring)
+        if keychain:get(entry) ~= sealed then
+            error("The " .. keychain.platform .. " keychain did not keep account '" .. name .. "'; nothing was saved")
+        end
+        account.keychain = true
+    else
+        account.secret = seal(self.key, secret, name)
+    end
+    self.state.accounts[name] = account
+    self:save()
+    return pair.address
+end
+
+-- Returns true if the account existed
+function Keystore:remove(name)
+    local account = self.state.accounts[name]
+    if not account then return false end
+    if account.keychain then self:keychain():delete(keychain_account(self, name)) end
+    self.state.accounts[name] = nil
+    self:save()
+    return true
+end
+
+-- Returns { { name, address, scheme, keychain }, ... } sorted by name
+function Keystore:list()
+    local list = {}
+    for name, account in pairs(self.state.accounts) do
+        table.insert(list, { name = name, address = account.address, scheme = account.scheme, keychain = account.keychain })
+    end
+    table.sort(list, function(a, b) return a.name < b.name end)
+    return list
+end
+
+-- Keypair of a named account
+-- opts: passed to Keyring.from_seed (default { secure = true }: the seed stays in a C handle)
+function Keystore:keypair(name, opts)
+    local account = self.state.accounts[name]
+    if not account then error("No account '" .. tostring(name) .. "' in keystore") end
+    local sealed = account.secret
+    if account.keychain then
+        sealed = self:keychain():get(keychain_account(self, name))
+        if not sealed then error("Account '" .. name .. "' is missing from the " .. self:keychain().platform .. " keychain") end
+    end
+    local secret = unseal(self.key, sealed, name)
+    if not secret then error("Account '" .. name .. "' does not decrypt; the keystore file was modified") end
+    return pair_of(secret, opts or { secure = true })
+end
+
+-- Sign a payload (raw bytes or 0x hex) with a named account
+-- Returns the 64-byte signature
+function Keystore:sign(name, payload)
+    if payload:match("^0x%x*$") then payload = from_hex(payload) end
+    local pair = self:keypair(name)
+    local signature = pair:sign(payload)
+    pair:destroy()
+    return signature
+end
+
+-- ============================================================
+-- Submission by account name
+-- ============================================================
+
+-- Transaction.submit signed by a named account
+function Keystore:submit(api, name, call_bytes, opts)
+    local pair = self:keypair(name)
+    local hash, report = Transaction.submit(api, pair, call_bytes, opts)
+    pair:destroy()
+    return hash, report
+end
+
+-- Utility.submit_batch signed by a named account
+function Keystore:submit_batch(api, name, calls, opts)
+    local pair = self:keypair(name)
+    local hashes, reports = require("sublua.utility").submit_batch(api, pair, calls, opts)
+    pair:destroy()
+    return hashes, reports
+end
+
+return Keystore
@@ -0,0 +1,162 @@
+-- sublua/ledger.lua
+-- Ledger-compatible account derivation (m/44'/<slip44>'/<account>'/0'/<index>')
+-- Reproduces the Polkadot Ledger app's ed25519 keys from the device's recovery phrase,
+-- so addresses shown on the device can be tracked watch-only (or recovered) with the SDK
+-- The derivation is BIP32-Ed25519 over the standard BIP-39 seed, as in polkadot.js hdLedger;
+-- sr25519 Ledger accounts (generic app) are not covered
+
+local crypto = require("polkadot_crypto")
+local Bip39 = require("sublua.bip39")
+local Keyring = require("sublua.keyring")
+
+local Ledger = {}
+
+-- SLIP-44 coin types used by the Ledger apps, with the chain's SS58 prefix
+Ledger.CHAINS = {
+    polkadot = { slip44 = 354, prefix = 0 },
+    kusama = { slip44 = 434, prefix = 2 },
+    westend = { slip44 = 354, prefix = 42 },
+    statemint = { slip44 = 354, prefix = 0 },
+    statemine = { slip44 = 434, prefix = 2 }
+}
+
+local HARDENED = 2147483648
+
+-- XOR two equal-length strings (no bit library on Lua 5.1)
+local function xor_strings(a, b)
+    local out = {}
+    for i = 1, #a do
+        local x, y, r, bit = a:byte(i), b:byte(i), 0, 1
+        while x > 0 or y > 0 do
+            if x % 2 ~= y % 2 then r = r + bit end
+            x, y, bit = math.floor(x / 2), math.floor(y / 2), bit * 2
+        end
+        out[i] = string.char(r)
+    end
+    return table.concat(out)
+end
+
+local function hmac(hash, block, key, message)
+    if #key > block then key = hash(key) end
+    key = key .. string.rep("\0", block - #key)
+    local inner = hash(xor_strings(key, string.rep("\54", block)) .. message)
+    return hash(xor_strings(key, string.rep("\92", block)) .. inner)
+end
+
+local function hmac_sha512(key, message) return hmac(crypto.sha512, 128, key, message) end
+local function hmac_sha256(key, message) return hmac(crypto.sha256, 64, key, message) end
+
+-- (a + b * factor) mod 2^256, little-endian byte strings (b may be shorter)
+local function add_le(a, b, factor)
+    local out, carry = {}, 0
+    for i = 1, 32 do
+        local sum = a:byte(i) + (b:byte(i) or 0) * factor + carry
+        out[i] = string.char(sum % 256)
+        carry = math.floor(sum / 256)
+    end
+    return table.concat(out)
+end
+
+local function u32_le(n)
+    return string.char(n % 256, math.floor(n / 256) % 256, math.floor(n / 65536) % 256, math.floor(n / 16777216) % 256)
+end
+
+-- Set and clear the bits BIP32-Ed25519 requires of kL (byte arithmetic, no bit library)
+local function clamp(k)
+    local first, last = k:byte(1), k:byte(32)
+    first = first - first % 8
+    last = last % 128
+    if last < 64 then last = last + 64 end
+    return string.char(first) .. k:sub(2, 31) .. string.char(last)
+end
+
+-- Extended master key { kl, kr, chain_code } from a BIP-39 seed
+local function master(seed)
+    local chain_code = hmac_sha256("ed25519 seed", "\1" .. seed)
+    local priv = hmac_sha512("ed25519 seed", seed)
+    -- Retry until the third highest bit of kL is clear
+    while math.floor(priv:byte(32) / 32) % 2 == 1 do
+        priv = hmac_sha512("ed25519 seed", priv)
+    end
+    priv = clamp(priv:sub(1, 32)) .. priv:sub(33)
+    return { kl = priv:sub(1, 32), kr = priv:sub(33, 64), chain_code = chain_code }
+end
+
+-- Hardened child of an extended key
+local function child(key, index)
+    local data = key.kl .. key.kr .. u32_le(index)
+    local z = hmac_sha512(key.chain_code, "\0" .. data)
+    return {
+        kl = add_le(key.kl, z:sub(1, 28), 8),
+        kr = add_le(key.kr, z:sub(33, 64), 1),
+        chain_code = hmac_sha512(key.chain_code, "\1" .. data):sub(33, 64)
+    }
+end
+
+-- Path of a Ledger account: m/44'/<slip44>'/<account>'/0'/<address_index>'
+function Ledger.path(account, address_index, slip44)
+    return string.format("m/44'/%d'/%d'/0'/%d'", slip44 or 354, account or 0, address_index or 0)
+end
+
+-- 32-byte ed25519 seed at a path
+-- phrase: 12 or 24 words, or 25 where the last word is the passphrase (as on the device)
+-- path: "m/44'/354'/0'/0'/0'"; every level must be hardened (BIP32-Ed25519 has no
+--   private derivation of normal children)
+-- password: BIP-39 passphrase (instead of a 25th word)
+function Ledger.derive_seed(phrase, path, password)
+    local words = {}
+    for word in tostring(phrase):gmatch("%S+") do table.insert(words, word) end
+    if #words == 25 then
+        password = table.remove(words)
+        phrase = table.concat(words, " ")
+    end
+    local seed, err = Bip39.to_bip39_seed(phrase, password)
+    if not seed then error(err) end
+
+    assert(type(path) == "string" and path:match("^m/"), "Derivation path must start with m/")
+    local key = master(seed)
+    for level in path:sub(3):gmatch("[^/]+") do
+        local n = tonumber(level:match("^(%d+)'$"))
+        if not n or n >= HARDENED then error("Invalid path level '" .. level .. "' (must be hardened, like 0')") end
+        key = child(key, n + HARDENED)
+    end
+    return key.kl
+end
+
+-- Keypair of a Ledger account
+-- opts: {
+--   chain = "polkadot",    -- key of Ledger.CHAINS (SLIP-44 coin type and address prefix)
+--   account = 0, address_index = 0,
+--   path = nil,            -- full path instead of chain/account/address_index
+--   password = nil,        -- BIP-39 passphrase
+--   secure = false         -- as for Keyring.from_seed
+-- }
+-- The pair's address uses the chain's SS58 prefix
+function Ledger.from_mnemonic(phrase, opts)
+    opts = opts or {}
+    local chain = Ledger.CHAINS[opts.chain or "polkadot"]
+    if not chain then error("Unknown Ledger chain '" .. tostring(opts.chain) .. "'") end
+    local path = opts.path or Ledger.path(opts.account, opts.address_index, chain.slip44)
+    local pair = Keyring.from_seed(Ledger.derive_seed(phrase, path, opts.password), { secure = opts.secure })
+    pair.address = crypto.ss58_encode(pair.pubkey, chain.prefix)
+    pair.path = path
+    return pair
+end
+
+-- Watch-only list of the first count addresses of an account (address_index 0 .. count - 1)
+-- opts: as for from_mnemonic
+-- Returns { { path, address, pubkey }, ... }
+function Ledger.addresses(phrase, count, opts)
+    local list = {}
+    for i = 0, (count or 1) - 1 do
+        local o = {}
+        for k, v in pairs(opts or {}) do o[k] = v end
+        o.address_index, o.secure = i, true
+        local pair = Ledger.from_mnemonic(phrase, o)
+        table.insert(list, { path = pair.path, address = pair.address, pubkey = pair.pubkey })
+        pair:destroy()
+    end
+    return list
+end
+
+return Ledger
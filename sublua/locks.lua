@@ -0,0 +1,221 @@
+-- sublua/locks.lua
+-- What holds an account's balance: Balances.Locks and Balances.Freezes with readable
+-- identifiers, and for conviction votes the block each lock can be lifted at
+-- A conviction lock lasts until the referendum ends plus 2^(conviction-1) vote locking
+-- periods when the vote was on the winning side; losing, split and abstain votes lock
+-- nothing past the end. Lifting it still takes remove_vote and unlock calls
+
+local Bytes = require("sublua.bytes")
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+local Storage = require("sublua.storage")
+local Validate = require("sublua.validate")
+
+local Locks = {}
+
+-- Readable names of well-known lock identifiers (8 bytes, space padded)
+Locks.LOCK_NAMES = {
+    ["staking "] = "Staking",
+    ["vesting "] = "Vesting",
+    ["pyconvot"] = "Conviction voting",
+    ["democrac"] = "Democracy",
+    ["phrelect"] = "Elections"
+}
+
+-- Convictions by their index in a vote byte (the low 7 bits; the top bit is aye)
+local CONVICTIONS = { [0] = "None", "Locked1x", "Locked2x", "Locked3x", "Locked4x", "Locked5x", "Locked6x" }
+
+-- Lock periods per conviction: None locks nothing past the end, Locked<N>x 2^(N-1) periods
+local CONVICTION_PERIODS = {
+    None = 0, Locked1x = 1, Locked2x = 2, Locked3x = 4, Locked4x = 8, Locked5x = 16, Locked6x = 32
+}
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function client(node)
+    if type(node) == "string" then return require("sublua.rpc").new(node) end
+    return node
+end
+
+local function has_entry(meta, pallet, name)
+    local _, _, err = Metadata.get_storage_entry(meta, pallet, name)
+    return err == nil
+end
+
+local function add(a, b)
+    return Bytes.bytes_to_decimal(Bytes.add(Bytes.decimal_to_bytes(tostring(a)), Bytes.decimal_to_bytes(tostring(b))))
+end
+
+local function later(a, b)
+    if a == nil or b == nil then return nil end
+    return math.max(a, b)
+end
+
+-- ============================================================
+-- Conviction Voting
+-- ============================================================
+
+-- Block a referendum finished at and whether it was approved; nil while it is ongoing
+local function referendum_end(api, poll, at)
+    local info = Storage.fetch(api, "Referenda", "ReferendumInfoFor", { poll }, at)
+    if info == nil then return false end
+    if info.variant == "Ongoing" then return nil end
+    local block = type(info.value) == "table" and info.value[1] or info.value
+    return block, info.variant == "Approved"
+end
+
+-- One vote of a Casting record: { poll, amount, aye, conviction, ongoing, unlock_at }
+local function vote_unlock(api, poll, account_vote, period, at)
+    local vote = { poll = poll }
+    local v = account_vote.value
+    if account_vote.variant == "Standard" then
+        vote.amount = v.balance
+        vote.aye = v.vote >= 128
+        vote.conviction = CONVICTIONS[v.vote % 128]
+    else
+        vote.amount = add(add(v.aye, v.nay), v.abstain or "0")
+    end
+
+    local ended, approved = referendum_end(api, poll, at)
+    if ended == nil then
+        vote.ongoing = true
+        return vote
+    end
+    if ended == false then
+        vote.unlock_at = 0  -- the referendum is gone from storage: nothing holds the vote
+        return vote
+    end
+    vote.unlock_at = ended
+    if vote.conviction and vote.aye == approved then
+        vote.unlock_at = ended + CONVICTION_PERIODS[vote.conviction] * period
+    end
+    return vote
+end
+
+-- Conviction voting locks of an account per voting class (track)
+-- api: RPC client; who: SS58 address or 0x public key
+-- at: block hash (default: best block)
+-- Returns { { class, locked, unlock_at, votes, delegating, prior }, ... } by class, or nil
+-- when the runtime has no ConvictionVoting pallet
+--   locked: amount ClassLocksFor holds for the class (decimal string)
+--   unlock_at: block the class lock can be lifted from; nil while a vote is on an ongoing
+--     referendum or the account delegates (the lock then runs from undelegating)
+--   votes: { { poll, amount, aye, conviction, ongoing, unlock_at }, ... } (Casting)
+--   delegating: { target, conviction, balance } when the class is delegated
+--   prior: { block, amount } left from earlier votes or delegations, or nil
+function Locks.conviction_unlocks(api, who, at)
+    local meta = api:get_metadata()
+    if not has_entry(meta, "ConvictionVoting", "VotingFor") then return nil end
+    local account = Validate.account(who, "who")
+    local period = 0
+    local c = Metadata.get_constant(meta, "ConvictionVoting", "VoteLockingPeriod")
+    if c then period = Codec.decode(meta, c.type_id, c.value) end
+
+    local classes, by_class = {}, {}
+    for _, pair in ipairs(Storage.fetch(api, "ConvictionVoting", "ClassLocksFor", { account }, at) or {}) do
+        local class = { class = pair[1], locked = pair[2], unlock_at = 0, votes = {} }
+        table.insert(classes, class)
+        by_class[pair[1]] = class
+    end
+    for _, e in ipairs(Storage.entries(api, "ConvictionVoting", "VotingFor", { account }, { at = at })) do
+        local class = by_class[e.keys[2]]
+        if not class then
+            class = { class = e.keys[2], locked = "0", unlock_at = 0, votes = {} }
+            table.insert(classes, class)
+            by_class[class.class] = class
+        end
+        local voting = e.value.value
+        local prior = voting.prior
+        if prior and prior[2] ~= "0" then
+            class.prior = { block = prior[1], amount = prior[2] }
+            class.unlock_at = later(class.unlock_at, prior[1])
+        end
+        if e.value.variant == "Delegating" then
+            class.delegating = { target = voting.target, conviction = voting.conviction.variant, balance = voting.balance }
+            class.unlock_at = nil
+        else
+            for _, item in ipairs(voting.votes) do
+                local vote = vote_unlock(api, item[1], item[2], period, at)
+                table.insert(class.votes, vote)
+                class.unlock_at = later(class.unlock_at, vote.unlock_at)
+            end
+        end
+    end
+    table.sort(classes, function(a, b) return a.class < b.class end)
+    return classes
+end
+
+-- ============================================================
+-- Locks and Freezes
+-- ============================================================
+
+local function lock_id(hex)
+    local raw = from_hex(hex)
+    if raw:match("^[%w%p ]+$") then return raw end
+    return hex
+end
+
+local function freeze_id(id)
+    if type(id) ~= "table" then return tostring(id) end
+    local parts, v = {}, id
+    while type(v) == "table" and v.variant do
+        table.insert(parts, v.variant)
+        v = v.value
+    end
+    return table.concat(parts, ".")
+end
+
+-- Everything locking or freezing an account's balance
+-- node: RPC client or URL; who: SS58 address or 0x public key
+-- opts: { at = nil } block hash (default: best block)
+-- Returns { block_number, locks, freezes, conviction }
+--   locks: { { id, name, amount, reasons, unlock_at, unlockable }, ... } from Balances.Locks;
+--     id is the 8-byte identifier as text ("staking ", "pyconvot"), name its readable
+--     name; unlock_at/unlockable are set for the conviction voting lock
+--   freezes: { { id, amount }, ... } from Balances.Freezes, id as "Pallet.Reason"
+--   conviction: see Locks.conviction_unlocks, each class with unlockable added
+--   amounts are decimal strings in plancks
+function Locks.query_locks(node, who, opts)
+    opts = opts or {}
+    local api = client(node)
+    local meta = api:get_metadata()
+    local account = Validate.account(who, "who")
+    local header = api:chain_getHeader(opts.at)
+    local now = tonumber(header.number:gsub("^0x", ""), 16)
+
+    local result = { block_number = now, locks = {}, freezes = {} }
+    result.conviction = Locks.conviction_unlocks(api, account, opts.at)
+    local conviction_unlock = 0
+    for _, class in ipairs(result.conviction or {}) do
+        class.unlockable = class.unlock_at ~= nil and class.unlock_at <= now
+        conviction_unlock = later(conviction_unlock, class.unlock_at)
+    end
+
+    if has_entry(meta, "Balances", "Locks") then
+        for _, lock in ipairs(Storage.fetch(api, "Balances", "Locks", { account }, opts.at) or {}) do
+            local id = lock_id(lock.id)
+            local entry = {
+                id = id,
+                name = Locks.LOCK_NAMES[id] or (id:gsub("%s+$", "")),
+                amount = lock.amount,
+                reasons = type(lock.reasons) == "table" and lock.reasons.variant or lock.reasons
+            }
+            if id == "pyconvot" and result.conviction then
+                entry.unlock_at = conviction_unlock
+                entry.unlockable = conviction_unlock ~= nil and conviction_unlock <= now
+            end
+            table.insert(result.locks, entry)
+        end
+    end
+    if has_entry(meta, "Balances", "Freezes") then
+        for _, freeze in ipairs(Storage.fetch(api, "Balances", "Freezes", { account }, opts.at) or {}) do
+            table.insert(result.freezes, { id = freeze_id(freeze.id), amount = freeze.amount })
+        end
+    end
+    return result
+end
+
+return Locks
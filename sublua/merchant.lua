@@ -0,0 +1,225 @@
+-- sublua/merchant.lua
+-- Payment tracking for merchants: one deposit address per order, confirmation
+-- tracking on finalized blocks, and a batched sweep of paid orders to a cold wallet
+-- Deposit addresses are Utility derivative sub-accounts of the merchant wallet
+-- (see sublua.utility), so no extra keys are created and the wallet alone can move
+-- the funds with Utility.as_derivative
+
+local json = require("cjson")
+local Address = require("sublua.address")
+local Bytes = require("sublua.bytes")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Transaction = require("sublua.transaction")
+local Utility = require("sublua.utility")
+
+local Merchant = {}
+Merchant.__index = Merchant
+
+-- Derivative indices are u16
+local MAX_INDEX = 65535
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function amount_bytes(value)
+    return Bytes.decimal_to_bytes(tostring(value))
+end
+
+local function block_number(header)
+    return tonumber((header.number:gsub("^0x", "")), 16)
+end
+
+-- ============================================================
+-- Setup and State
+-- ============================================================
+
+-- Open a merchant
+-- opts: {
+--   wallet = SS58 address of the merchant (hot) wallet that owns the deposit sub-accounts,
+--   cold_address = SS58 address swept funds go to,
+--   confirmations = 2,   -- finalized blocks an order's payment must be buried under
+--   first_index = 1,     -- first derivative index handed out
+--   state_file = nil,    -- JSON file for orders (nil keeps them in memory)
+--   clock = os.time      -- seconds, for order expiry
+-- }
+function Merchant.new(api, opts)
+    opts = opts or {}
+    assert(opts.wallet and Address.is_valid(opts.wallet), "Merchant needs a valid wallet address")
+    assert(opts.cold_address and Address.is_valid(opts.cold_address), "Merchant needs a valid cold_address")
+    local self = setmetatable({
+        api = api,
+        wallet = opts.wallet,
+        cold_address = opts.cold_address,
+        confirmations = opts.confirmations or 2,
+        state_file = opts.state_file,
+        clock = opts.clock or os.time,
+        state = { next_index = opts.first_index or 1, orders = {} }
+    }, Merchant)
+    if self.state_file then
+        local f = io.open(self.state_file, "rb")
+        if f then
+            local text = f:read("*a")
+            f:close()
+            local ok, state = pcall(json.decode, text)
+            if not ok or type(state) ~= "table" then error("Unreadable merchant state file " .. self.state_file) end
+            self.state = state
+        end
+    end
+    return self
+end
+
+function Merchant:save()
+    if not self.state_file then return end
+    local tmp = self.state_file .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write merchant state: " .. tostring(err)) end
+    f:write(json.encode(self.state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, self.state_file)
+    if not ok then error("Cannot write merchant state: " .. tostring(rename_err)) end
+end
+
+-- ============================================================
+-- Orders
+-- Order: { id, index, address, amount, status, created, expires, received,
+--          paid_block, confirmed_block, sweep_hash }
+-- status: "pending" -> "partial" (less than amount received) -> "paid" (amount received,
+--   waiting for confirmations) -> "confirmed" -> "swept"; "expired" when nothing
+--   sufficient arrived before expires
+-- ============================================================
+
+-- Create an order with its own deposit address
+-- order_id: merchant reference (string), unique
+-- amount: planck as a decimal string or integer
+-- opts: { expires_in = nil } seconds until an unpaid order expires
+-- Returns the order
+function Merchant:create_order(order_id, amount, opts)
+    opts = opts or {}
+    assert(type(order_id) == "string" and order_id ~= "", "Order id must be a non-empty string")
+    assert(not self.state.orders[order_id], "Order '" .. order_id .. "' already exists")
+    amount = tostring(amount)
+    amount_bytes(amount)
+    local index = self.state.next_index
+    if index > MAX_INDEX then error("No deposit indices left (derivative indices are u16)") end
+    local address = Utility.derive_sub_account(self.wallet, index)
+    local now = self.clock()
+    local order = {
+        id = order_id,
+        index = index,
+        address = address,
+        amount = amount,
+        received = "0",
+        status = "pending",
+        created = now,
+        expires = opts.expires_in and now + opts.expires_in or nil
+    }
+    self.state.next_index = index + 1
+    self.state.orders[order_id] = order
+    self:save()
+    return order
+end
+
+function Merchant:get_order(order_id)
+    return self.state.orders[order_id]
+end
+
+-- Check an order's deposit address at the finalized head and advance its status
+-- Returns the order, or nil, err
+function Merchant:poll_order(order_id)
+    local order = self.state.orders[order_id]
+    if not order then return nil, "Unknown order '" .. tostring(order_id) .. "'" end
+    if order.status == "confirmed" or order.status == "swept" or order.status == "expired" then
+        return order
+    end
+
+    local ok, err = pcall(function()
+        local hash = self.api:chain_getFinalizedHead()
+        local head = block_number(self.api:chain_getHeader(hash))
+        local pubkey = "0x" .. to_hex((Address.decode(order.address)))
+        local info = Storage.fetch(self.api, "System", "Account", { pubkey }, hash)
+        order.received = info and info.data and tostring(info.data.free) or "0"
+
+        local enough = Bytes.compare(amount_bytes(order.received), amount_bytes(order.amount)) >= 0
+        if enough then
+            if order.status ~= "paid" then
+                order.status, order.paid_block = "paid", head
+            end
+            if head - order.paid_block + 1 >= self.confirmations then
+                order.status, order.confirmed_block = "confirmed", head
+            end
+        elseif order.expires and self.clock() >= order.expires then
+            order.status, order.paid_block = "expired", nil
+        else
+            order.status = order.received == "0" and "pending" or "partial"
+            order.paid_block = nil
+        end
+    end)
+    self:save()
+    if not ok then return nil, tostring(err) end
+    return order
+end
+
+-- Poll every open order; returns the list of orders that changed status
+function Merchant:poll_open()
+    local changed = {}
+    for id, order in pairs(self.state.orders) do
+        local before = order.status
+        if self:poll_order(id) and order.status ~= before then table.insert(changed, order) end
+    end
+    table.sort(changed, function(a, b) return a.index < b.index end)
+    return changed
+end
+
+-- ============================================================
+-- Sweep
+-- ============================================================
+
+-- Call bytes moving everything out of one deposit sub-account:
+-- Utility.as_derivative(index, Balances.transfer_all(cold, keep_alive = false))
+function Merchant:sweep_call(order)
+    local meta = self.api:get_metadata()
+    local utility = assert(meta.pallets["Utility"], "Utility pallet not found in metadata")
+    local call_index = assert(utility.calls and utility.calls["as_derivative"], "as_derivative not found in Utility")
+    local cold = "0x" .. to_hex((Address.decode(self.cold_address)))
+    local inner = Call.encode_call(self.api, "Balances", "transfer_all",
+        { dest = { variant = "Id", value = cold }, keep_alive = false })
+    return Utility.encode_as_derivative(utility.index, call_index, order.index, inner)
+end
+
+-- Sweep all confirmed orders to the cold address in one Utility.batch_all extrinsic
+-- signer: the merchant wallet's keypair (pays the fee)
+-- opts: passed to Transaction.create_signed_from_api (tip, nonce, mortality, ...)
+-- Returns { hash, extrinsic, orders = { order_id, ... } }, nil when nothing is confirmed,
+--   or nil, err when the submission failed (orders stay "confirmed")
+function Merchant:sweep(signer, opts)
+    signer = Transaction.signer_of(self.api, signer)
+    assert(signer.pubkey == Address.decode(self.wallet), "Sweep must be signed by the merchant wallet")
+    local ready = {}
+    for _, order in pairs(self.state.orders) do
+        if order.status == "confirmed" then table.insert(ready, order) end
+    end
+    if #ready == 0 then return nil end
+    table.sort(ready, function(a, b) return a.index < b.index end)
+
+    local calls, ids = {}, {}
+    for i, order in ipairs(ready) do
+        calls[i] = self:sweep_call(order)
+        ids[i] = order.id
+    end
+
+    local batch = Utility.encode_batch(self.api:get_metadata(), calls, "batch_all")
+    local extrinsic = Transaction.create_signed_from_api(self.api, signer, batch, opts)
+    local ok, hash = pcall(self.api.author_submitExtrinsic, self.api, extrinsic)
+    if not ok or type(hash) ~= "string" then
+        return nil, "Sweep submission failed: " .. tostring(hash)
+    end
+    for _, order in ipairs(ready) do
+        order.status, order.sweep_hash = "swept", hash
+    end
+    self:save()
+    return { hash = hash, extrinsic = extrinsic, orders = ids }
+end
+
+return Merchant
@@ -70,6 +70,13 @@ local function decode_vec(data, offset, inner_decoder)
     return items, off
 end
 
+-- Pack a list of byte values (as returned by decode_vec(decode_u8)) into a string
+local function bytes_to_string(list)
+    local chars = {}
+    for i, b in ipairs(list) do chars[i] = string.char(b) end
+    return table.concat(chars)
+end
+
 -- StorageEntryType decoder
 local function decode_storage_entry_type(data, offset)
     local variant = string.byte(data, offset)
@@ -247,13 +254,34 @@ function Metadata.parse(hex_data)
     
     local pallets, pallets_offset = decode_vec(data, offset, decode_pallet)
     offset = pallets_offset
+    meta.pallets_by_index = {}
     for _, pallet in ipairs(pallets) do
-        meta.pallets[pallet.name] = {
+        local info = {
+            name = pallet.name,
             index = pallet.index,
             calls_type_id = pallet.calls_type_id,
             events_type_id = pallet.events_type_id,
-            errors_type_id = pallet.errors_type_id
+            errors_type_id = pallet.errors_type_id,
+            constants = {}
         }
+        -- Storage entries keyed by name; defaults kept as raw bytes
+        if pallet.storage then
+            local entries = {}
+            for _, entry in ipairs(pallet.storage.entries) do
+                entry.default = bytes_to_string(entry.default)
+                entries[entry.name] = entry
+            end
+            info.storage = { prefix = pallet.storage.prefix, entries = entries }
+        end
+        -- Constants keyed by name; values kept as raw SCALE bytes
+        for _, constant in ipairs(pallet.constants) do
+            info.constants[constant.name] = {
+                type_id = constant.type_id,
+                value = bytes_to_string(constant.value)
+            }
+        end
+        meta.pallets[pallet.name] = info
+        meta.pallets_by_index[pallet.index] = info
     end
     
     local extrinsic, ext_offset = decode_extrinsic_metadata(data, offset)
@@ -281,6 +309,56 @@ function Metadata.parse(hex_data)
     return meta
 end
 
+-- Accept any metadata source: a parsed table, an RPC client, or metadata hex
+function Metadata.from_source(source)
+    if type(source) == "string" then
+        return Metadata.parse(source)
+    end
+    assert(type(source) == "table", "metadata source must be a table or hex string")
+    if source.pallets then
+        return source
+    end
+    if source.get_metadata then
+        return source:get_metadata()
+    end
+    error("Unrecognised metadata source")
+end
+
+-- Look up a storage entry: returns entry, prefix (or nil, nil, err)
+function Metadata.get_storage_entry(meta, pallet_name, entry_name)
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then return nil, nil, "Pallet not found" end
+    if not pallet.storage then return nil, nil, "Pallet has no storage" end
+    local entry = pallet.storage.entries[entry_name]
+    if not entry then return nil, nil, "Storage entry not found" end
+    return entry, pallet.storage.prefix
+end
+
+-- Look up a constant: returns { type_id, value } (or nil, err)
+function Metadata.get_constant(meta, pallet_name, constant_name)
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then return nil, "Pallet not found" end
+    local constant = pallet.constants and pallet.constants[constant_name]
+    if not constant then return nil, "Constant not found" end
+    return constant
+end
+
+-- Look up a pallet error by indices (as found in DispatchError::Module)
+-- error_index: first byte of ModuleError.error
+-- Returns { pallet, name, docs } (or nil, err)
+function Metadata.get_error(meta, pallet_index, error_index)
+    local pallet = meta.pallets_by_index[pallet_index]
+    if not pallet then return nil, "Pallet not found" end
+    if not pallet.errors_type_id then return nil, "Pallet has no errors" end
+    local t = meta.types[pallet.errors_type_id]
+    for _, variant in ipairs(t.type_def.variants or {}) do
+        if variant.index == error_index then
+            return { pallet = pallet.name, name = variant.name, docs = table.concat(variant.docs or {}, " ") }
+        end
+    end
+    return nil, "Error not found"
+end
+
 function Metadata.get_call_index(meta, pallet_name, call_name)
     local pallet = meta.pallets[pallet_name]
     if not pallet then return nil, nil, "Pallet not found" end
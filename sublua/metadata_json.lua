@@ -0,0 +1,310 @@
+-- sublua/metadata_json.lua
+-- Runtime metadata rendered as a JSON document for host-side tooling (code generators, docs)
+-- Covers pallets (calls, events, errors, storage, constants), the type registry and the
+-- extrinsic format; a pallet filter keeps only those pallets and the types they reach
+
+local json = require("cjson")
+local Metadata = require("sublua.metadata")
+local Codec = require("sublua.codec")
+local Storage = require("sublua.storage")
+
+local MetadataJson = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- Keep empty lists as [] with lua-cjson versions that support it
+local function list(t)
+    if json.array_mt then setmetatable(t, json.array_mt) end
+    return t
+end
+
+local function copy(items)
+    local out = {}
+    for i, v in ipairs(items or {}) do out[i] = v end
+    return list(out)
+end
+
+-- ============================================================
+-- Loading
+-- ============================================================
+
+-- Parsed metadata from a node URL, file, RPC client, parsed table or hex
+-- Files may hold metadata hex, a JSON-RPC response ({ "result": "0x..." }) or raw SCALE bytes
+function MetadataJson.load(source)
+    if type(source) == "table" then return Metadata.from_source(source) end
+    assert(type(source) == "string", "source must be a node URL, file path, metadata hex or client")
+    if source:match("^https?://") or source:match("^wss?://") then
+        return require("sublua.rpc").new(source):get_metadata()
+    end
+    if source:match("^0x%x+$") then return Metadata.parse(source) end
+
+    local f, err = io.open(source, "rb")
+    if not f then error("Cannot open metadata file: " .. tostring(err)) end
+    local text = f:read("*a")
+    f:close()
+    if text:sub(1, 4) == "meta" then return Metadata.parse(to_hex(text)) end
+    text = text:gsub("^%s+", ""):gsub("%s+$", "")
+    if text:match("^{") then
+        local ok, decoded = pcall(json.decode, text)
+        text = ok and type(decoded) == "table" and decoded.result
+        if type(text) ~= "string" then error("JSON metadata file must be a JSON-RPC response with a hex result") end
+    end
+    if not text:match("^0?x?%x+$") then error("Unrecognised metadata file format: " .. source) end
+    return Metadata.parse(text)
+end
+
+-- ============================================================
+-- Types
+-- ============================================================
+
+-- Readable name of a type, e.g. "Vec<u8>", "[u8; 32]", "Option<AccountId32>"
+local function type_name(meta, id, depth)
+    depth = (depth or 0) + 1
+    local t = meta.types[id]
+    if not t then return "?" end
+    local def = t.type_def
+    if depth > 8 then return t.path and t.path[#t.path] or ("#" .. id) end
+    if t.path and #t.path > 0 then
+        local name = t.path[#t.path]
+        local params = {}
+        for _, p in ipairs(t.type_params or {}) do
+            if p.type_id then table.insert(params, type_name(meta, p.type_id, depth)) end
+        end
+        if #params > 0 then name = name .. "<" .. table.concat(params, ", ") .. ">" end
+        return name
+    end
+    if def.type == "Primitive" then return Codec.PRIMITIVES[def.value] end
+    if def.type == "Sequence" then return "Vec<" .. type_name(meta, def.type_id, depth) .. ">" end
+    if def.type == "Compact" then return "Compact<" .. type_name(meta, def.type_id, depth) .. ">" end
+    if def.type == "Array" then return "[" .. type_name(meta, def.type_id, depth) .. "; " .. def.len .. "]" end
+    if def.type == "Tuple" then
+        local parts = {}
+        for i, tid in ipairs(def.types) do parts[i] = type_name(meta, tid, depth) end
+        return "(" .. table.concat(parts, ", ") .. ")"
+    end
+    if def.type == "BitSequence" then return "BitVec" end
+    return "#" .. id
+end
+
+function MetadataJson.type_name(meta, id)
+    return type_name(meta, id)
+end
+
+local function fields_json(meta, fields)
+    local out = {}
+    for i, f in ipairs(fields or {}) do
+        out[i] = { name = f.name, type = f.type_id, type_name = f.type_name or type_name(meta, f.type_id), docs = copy(f.docs) }
+    end
+    return list(out)
+end
+
+local function variants_json(meta, variants)
+    local out = {}
+    for i, v in ipairs(variants or {}) do
+        out[i] = { name = v.name, index = v.index, fields = fields_json(meta, v.fields), docs = copy(v.docs) }
+    end
+    return list(out)
+end
+
+local function def_json(meta, def)
+    if def.type == "Composite" then return { composite = { fields = fields_json(meta, def.fields) } } end
+    if def.type == "Variant" then return { variant = { variants = variants_json(meta, def.variants) } } end
+    if def.type == "Sequence" then return { sequence = { type = def.type_id } } end
+    if def.type == "Array" then return { array = { len = def.len, type = def.type_id } } end
+    if def.type == "Tuple" then return { tuple = copy(def.types) } end
+    if def.type == "Primitive" then return { primitive = Codec.PRIMITIVES[def.value] } end
+    if def.type == "Compact" then return { compact = { type = def.type_id } } end
+    return { bit_sequence = { bit_store_type = def.bit_store_type, bit_order_type = def.bit_order_type } }
+end
+
+-- Type ids a definition refers to
+local function referenced(t)
+    local def, ids = t.type_def, {}
+    for _, p in ipairs(t.type_params or {}) do
+        if p.type_id then table.insert(ids, p.type_id) end
+    end
+    for _, f in ipairs(def.fields or {}) do table.insert(ids, f.type_id) end
+    for _, v in ipairs(def.variants or {}) do
+        for _, f in ipairs(v.fields or {}) do table.insert(ids, f.type_id) end
+    end
+    for _, tid in ipairs(def.types or {}) do table.insert(ids, tid) end
+    if def.type_id then table.insert(ids, def.type_id) end
+    if def.bit_store_type then table.insert(ids, def.bit_store_type) end
+    if def.bit_order_type then table.insert(ids, def.bit_order_type) end
+    return ids
+end
+
+-- ============================================================
+-- Document
+-- ============================================================
+
+local function pallet_json(meta, pallet, roots)
+    local function root(id)
+        if id then table.insert(roots, id) end
+        return id
+    end
+    local out = { name = pallet.name, index = pallet.index }
+
+    if pallet.calls_type_id then
+        local calls = {}
+        for i, v in ipairs(meta.types[root(pallet.calls_type_id)].type_def.variants or {}) do
+            calls[i] = { name = v.name, index = v.index, args = fields_json(meta, v.fields), docs = copy(v.docs) }
+        end
+        out.calls = { type = pallet.calls_type_id, items = list(calls) }
+    end
+    if pallet.events_type_id then
+        local def = meta.types[root(pallet.events_type_id)].type_def
+        out.events = { type = pallet.events_type_id, items = variants_json(meta, def.variants) }
+    end
+    if pallet.errors_type_id then
+        local errors = {}
+        for i, v in ipairs(meta.types[root(pallet.errors_type_id)].type_def.variants or {}) do
+            errors[i] = { name = v.name, index = v.index, docs = copy(v.docs) }
+        end
+        out.errors = { type = pallet.errors_type_id, items = list(errors) }
+    end
+
+    if pallet.storage then
+        local names, entries = {}, {}
+        for name in pairs(pallet.storage.entries) do table.insert(names, name) end
+        table.sort(names)
+        for i, name in ipairs(names) do
+            local entry = pallet.storage.entries[name]
+            local st = entry.storage_type
+            local item = {
+                name = name,
+                modifier = entry.modifier == 0 and "Optional" or "Default",
+                kind = st.type,
+                value = root(st.value),
+                value_name = type_name(meta, st.value),
+                default = "0x" .. to_hex(entry.default or ""),
+                docs = copy(entry.docs)
+            }
+            if st.type == "Map" then
+                item.key = root(st.key)
+                item.key_name = type_name(meta, st.key)
+                local hashers = {}
+                for j, h in ipairs(st.hashers) do
+                    hashers[j] = Storage.HASHERS[h] and Storage.HASHERS[h].name or h
+                end
+                item.hashers = list(hashers)
+            end
+            entries[i] = item
+        end
+        out.storage = { prefix = pallet.storage.prefix, entries = list(entries) }
+    end
+
+    local names, constants = {}, {}
+    for name in pairs(pallet.constants or {}) do table.insert(names, name) end
+    table.sort(names)
+    for i, name in ipairs(names) do
+        local c = pallet.constants[name]
+        local ok, decoded = pcall(Codec.decode, meta, root(c.type_id), c.value, 1)
+        constants[i] = {
+            name = name,
+            type = c.type_id,
+            type_name = type_name(meta, c.type_id),
+            value = "0x" .. to_hex(c.value),
+            decoded = ok and decoded or nil
+        }
+    end
+    out.constants = list(constants)
+    return out
+end
+
+-- Metadata as a plain table ready for JSON encoding
+-- source: see MetadataJson.load
+-- filter: pallet name or list of pallet names (default: all pallets, all types)
+-- Returns { version, pallets = { ... by index }, types = { { id, path, name, params, def, docs } },
+--   extrinsic = { type, version, signed_extensions }, runtime_type }
+--   type references are ids into `types`; *_name fields give readable names
+function MetadataJson.document(source, filter)
+    local meta = MetadataJson.load(source)
+    local selected
+    if filter then
+        selected = {}
+        for _, name in ipairs(type(filter) == "table" and filter or { filter }) do
+            if not meta.pallets[name] then error("Pallet not found: " .. tostring(name)) end
+            selected[name] = true
+        end
+    end
+
+    local pallets, roots = {}, {}
+    for _, pallet in pairs(meta.pallets) do
+        if not selected or selected[pallet.name] then
+            table.insert(pallets, pallet_json(meta, pallet, roots))
+        end
+    end
+    table.sort(pallets, function(a, b) return a.index < b.index end)
+
+    local extensions = {}
+    for i, ext in ipairs(meta.extrinsic and meta.extrinsic.signed_extensions or {}) do
+        extensions[i] = { identifier = ext.identifier, type = ext.type_id, additional_signed = ext.additional_signed }
+        if not selected then
+            table.insert(roots, ext.type_id)
+            table.insert(roots, ext.additional_signed)
+        end
+    end
+
+    -- Every type, or the closure of those the selected pallets reach
+    local keep = {}
+    if selected then
+        local queue = roots
+        while #queue > 0 do
+            local id = table.remove(queue)
+            if not keep[id] and meta.types[id] then
+                keep[id] = true
+                for _, ref in ipairs(referenced(meta.types[id])) do table.insert(queue, ref) end
+            end
+        end
+    else
+        for id in pairs(meta.types) do keep[id] = true end
+    end
+    local ids = {}
+    for id in pairs(keep) do table.insert(ids, id) end
+    table.sort(ids)
+    local types = {}
+    for i, id in ipairs(ids) do
+        local t = meta.types[id]
+        local params = {}
+        for j, p in ipairs(t.type_params or {}) do params[j] = { name = p.name, type = p.type_id } end
+        types[i] = {
+            id = id,
+            path = t.path and #t.path > 0 and table.concat(t.path, "::") or nil,
+            name = type_name(meta, id),
+            params = list(params),
+            def = def_json(meta, t.type_def),
+            docs = copy(t.docs)
+        }
+    end
+
+    return {
+        version = meta.version,
+        pallets = list(pallets),
+        types = list(types),
+        extrinsic = meta.extrinsic and {
+            type = meta.extrinsic.type_id,
+            version = meta.extrinsic.version,
+            signed_extensions = list(extensions)
+        } or nil,
+        runtime_type = meta.runtime_type
+    }
+end
+
+-- Metadata as JSON text (see MetadataJson.document)
+-- opts: { file = nil } also writes the JSON to this path
+function MetadataJson.export(source, filter, opts)
+    opts = opts or {}
+    local text = json.encode(MetadataJson.document(source, filter))
+    if opts.file then
+        local f, err = io.open(opts.file, "wb")
+        if not f then error("Cannot write " .. opts.file .. ": " .. tostring(err)) end
+        f:write(text)
+        f:close()
+    end
+    return text
+end
+
+return MetadataJson
@@ -0,0 +1,405 @@
+-- sublua/multisig.lua
+-- Multisig pallet: multi-account derivation, approve/execute/cancel calls, and a session
+-- object that walks a pending multisig call through its signatories
+-- A session is created once (by whoever proposes the call), shared as a JSON file, and
+-- refreshed from chain with session:status(api); session:next_action(address) then tells
+-- each signatory what to do and session:act(api, signer) builds that extrinsic
+-- The first approval opens the multisig (as_multi without a timepoint), later ones pass
+-- its timepoint, and the threshold-th signatory executes the call with its weight
+-- 1-of-N multisigs skip all of that: Multisig.as_multi_threshold_1 dispatches at once
+
+local crypto = require("polkadot_crypto")
+local json = require("cjson")
+local Scale = require("sublua.scale")
+local Call = require("sublua.call")
+local Address = require("sublua.address")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+local Validate = require("sublua.validate")
+
+local Multisig = {}
+
+local Session = {}
+Session.__index = Session
+
+-- Domain separator used by pallet_multisig::multi_account_id
+local MULTI_ACCOUNT_PREFIX = "modlpy/utilisuba"
+
+Multisig.SESSION_VERSION = 1
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function bytes(call)
+    assert(type(call) == "string", "call must be call bytes or 0x hex")
+    if call:match("^0x%x*$") then return from_hex(call) end
+    return call
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+local function signer_of(api, signer)
+    return require("sublua.transaction").signer_of(api, signer)
+end
+
+local function pubkey_hex(who)
+    local info = Address.inspect(who)
+    if not info.valid then error("Invalid signatory " .. tostring(who) .. ": " .. tostring(info.error)) end
+    return info.pubkey:lower()
+end
+
+-- ============================================================
+-- Multi-Accounts
+-- multi_account_id(who, threshold) =
+--     blake2_256(SCALE("modlpy/utilisuba" as [u8; 16], sorted who: Vec<AccountId>, threshold: u16))
+-- ============================================================
+
+-- Signatories as sorted, de-duplicated "0x" public keys (the order the pallet requires)
+-- signatories: SS58 addresses or 0x public keys; a bad entry fails naming its position
+--   ("signatories.2: expected an SS58 address or 0x public key, got ...")
+function Multisig.sort_signatories(signatories)
+    Validate.list(signatories, "signatories", { min = 1, what = "an array of signatories" })
+    local seen, sorted = {}, {}
+    for i, who in ipairs(signatories) do
+        local key = Validate.account(who, "signatories." .. i)
+        if not seen[key] then
+            seen[key] = true
+            table.insert(sorted, key)
+        end
+    end
+    table.sort(sorted)
+    return sorted
+end
+
+-- Address of the multisig account
+-- prefix: SS58 prefix of the result (default 42)
+-- Returns address, pubkey
+function Multisig.account_id(signatories, threshold, prefix)
+    local sorted = Multisig.sort_signatories(signatories)
+    threshold = Validate.integer(threshold, "threshold", { min = 1, max = #sorted })
+    local parts = { MULTI_ACCOUNT_PREFIX, Scale.encode_compact(#sorted) }
+    for _, key in ipairs(sorted) do table.insert(parts, from_hex(key)) end
+    table.insert(parts, Scale.encode_u16(threshold))
+    local pubkey = crypto.blake2b(table.concat(parts), 32)
+    return Address.encode(pubkey, prefix or 42), pubkey
+end
+
+-- Every signatory except who, sorted
+local function others(signatories, who)
+    local me, out = pubkey_hex(who), {}
+    for _, key in ipairs(Multisig.sort_signatories(signatories)) do
+        if key ~= me then table.insert(out, key) end
+    end
+    return out
+end
+
+-- ============================================================
+-- Calls
+-- timepoint: { height, index } of the extrinsic that opened the multisig (nil for the first)
+-- ============================================================
+
+-- Build Multisig.as_multi call bytes (the call is carried in full)
+-- other_signatories: the sorted signatories without the sender
+-- max_weight: { ref_time, proof_size } the call may use when this approval executes it
+function Multisig.encode_as_multi(meta_source, threshold, other_signatories, timepoint, call, max_weight)
+    local meta = Metadata.from_source(meta_source)
+    local pallet = assert(meta.pallets["Multisig"], "Multisig pallet not found in metadata")
+    local call_index = pallet.calls and pallet.calls["as_multi"]
+    assert(call_index, "as_multi not found in Multisig")
+    local parts = { Call.encode_index(pallet.index, call_index), Scale.encode_u16(threshold),
+        Scale.encode_compact(#other_signatories) }
+    for _, key in ipairs(other_signatories) do table.insert(parts, from_hex(key)) end
+    if timepoint then
+        table.insert(parts, "\1" .. Scale.encode_u32(timepoint.height) .. Scale.encode_u32(timepoint.index))
+    else
+        table.insert(parts, "\0")
+    end
+    max_weight = max_weight or { ref_time = 0, proof_size = 0 }
+    table.insert(parts, bytes(call))
+    table.insert(parts, Scale.encode_compact(max_weight.ref_time) .. Scale.encode_compact(max_weight.proof_size))
+    return table.concat(parts)
+end
+
+-- Build Multisig.approve_as_multi call bytes (only the call hash goes on chain)
+function Multisig.encode_approve_as_multi(meta_source, threshold, other_signatories, timepoint, call_hash)
+    return Call.encode_call(meta_source, "Multisig", "approve_as_multi", {
+        threshold = threshold,
+        other_signatories = other_signatories,
+        maybe_timepoint = timepoint and { variant = "Some", value = timepoint } or { variant = "None" },
+        call_hash = call_hash,
+        max_weight = { ref_time = 0, proof_size = 0 }
+    })
+end
+
+-- Build Multisig.cancel_as_multi call bytes (depositor only; returns the deposit)
+function Multisig.encode_cancel_as_multi(meta_source, threshold, other_signatories, timepoint, call_hash)
+    return Call.encode_call(meta_source, "Multisig", "cancel_as_multi", {
+        threshold = threshold, other_signatories = other_signatories,
+        timepoint = timepoint, call_hash = call_hash
+    })
+end
+
+-- Build Multisig.as_multi_threshold_1 call bytes (dispatches at once, no storage)
+function Multisig.encode_as_multi_threshold_1(meta_source, other_signatories, call)
+    local meta = Metadata.from_source(meta_source)
+    local pallet = assert(meta.pallets["Multisig"], "Multisig pallet not found in metadata")
+    local call_index = pallet.calls and pallet.calls["as_multi_threshold_1"]
+    assert(call_index, "as_multi_threshold_1 not found in Multisig")
+    local parts = { Call.encode_index(pallet.index, call_index), Scale.encode_compact(#other_signatories) }
+    for _, key in ipairs(other_signatories) do table.insert(parts, from_hex(key)) end
+    table.insert(parts, bytes(call))
+    return table.concat(parts)
+end
+
+-- ============================================================
+-- Queries
+-- ============================================================
+
+-- Accounts in the client's SS58 format when one is set (api:set_ss58_format), else as given
+local function render(api, who)
+    if who == nil or api.ss58_format == nil then return who end
+    return Address.convert(who, api.ss58_format)
+end
+
+local function render_all(api, list)
+    local out = {}
+    for i, who in ipairs(list) do out[i] = render(api, who) end
+    return out
+end
+
+local function query(api, multisig_address, call_hash, at)
+    local entry = Storage.fetch(api, "Multisig", "Multisigs", { pubkey_hex(multisig_address), call_hash }, at)
+    if not entry then return nil end
+    return {
+        timepoint = { height = entry.when.height, index = entry.when.index },
+        deposit = entry.deposit,
+        depositor = entry.depositor,
+        approvals = entry.approvals or {}
+    }
+end
+
+-- Open multisig operation for a call
+-- Returns { timepoint = { height, index }, deposit, depositor, approvals = { "0x" key, ... } }
+--   or nil when none is open (never opened, executed, or cancelled); accounts are SS58
+--   instead when the client has an ss58_format
+function Multisig.query(api, multisig_address, call_hash, at)
+    local open = query(api, multisig_address, call_hash, at)
+    if open then
+        open.depositor, open.approvals = render(api, open.depositor), render_all(api, open.approvals)
+    end
+    return open
+end
+
+-- ============================================================
+-- 1-of-N Multisigs
+-- Any signatory dispatches directly with as_multi_threshold_1: no approvals, deposit or
+-- timepoint; several calls go in one Utility batch
+-- ============================================================
+
+-- Build as_multi_threshold_1 call bytes for one call or several
+-- sender: the signatory that signs; signatories: all of them, sender included
+-- calls: call bytes or 0x hex, or a list of them (wrapped with Utility.encode_batch)
+-- kind: batch kind for lists (default "batch_all")
+function Multisig.encode_threshold_1(meta_source, sender, signatories, calls, kind)
+    local me = pubkey_hex(sender)
+    local member = false
+    for _, key in ipairs(Multisig.sort_signatories(signatories)) do
+        if key == me then member = true end
+    end
+    assert(member, "Sender is not a signatory of the multisig")
+    local call = calls
+    if type(calls) == "table" then
+        Validate.list(calls, "calls", { min = 1, what = "an array of calls" })
+        local list = {}
+        for i, c in ipairs(calls) do list[i] = Validate.within("calls." .. i, bytes, c) end
+        call = #list == 1 and list[1] or require("sublua.utility").encode_batch(meta_source, list, kind)
+    end
+    return Multisig.encode_as_multi_threshold_1(meta_source, others(signatories, sender), call)
+end
+
+-- Dispatch calls from a 1-of-N multisig account, signed by one signatory
+-- opts: { kind = "batch_all", prefix = nil, ... }; others go to Transaction.create_signed_from_api
+--   prefix: SS58 prefix of details.multisig (default: the client's ss58_format, else 42)
+-- Returns extrinsic hex, details (details.multisig is the dispatching account's address)
+function Multisig.as_multi_threshold_1(api, signer, signatories, calls, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.kind, o.prefix = nil, nil
+    local call_bytes = Multisig.encode_threshold_1(api, signer.address, signatories, calls, opts.kind)
+    local signed, details = sign(api, signer, call_bytes, o)
+    details.multisig = Multisig.account_id(signatories, 1, opts.prefix or api.ss58_format)
+    return signed, details
+end
+
+-- ============================================================
+-- Sessions
+-- state: "new" (not on chain yet), "collecting" (open, approvals below threshold - 1),
+-- "ready" (the next approval executes), "closed" (gone from storage after being open:
+-- executed or cancelled)
+-- ============================================================
+
+-- Start tracking a multisig call
+-- call: call bytes or 0x hex; signatories: SS58 addresses or 0x public keys
+-- opts: { path = nil, prefix = 42, max_weight = nil }
+--   path: JSON file the session is saved to on every change
+--   max_weight: weight the executing approval passes (default: queried when executing)
+function Multisig.session_create(call, signatories, threshold, opts)
+    opts = opts or {}
+    call = bytes(call)
+    local sorted = Multisig.sort_signatories(signatories)
+    threshold = Validate.integer(threshold, "threshold", { min = 1, max = #sorted })
+    local address = Multisig.account_id(sorted, threshold, opts.prefix)
+    local session = setmetatable({ path = opts.path, state = {
+        version = Multisig.SESSION_VERSION,
+        call = "0x" .. to_hex(call),
+        call_hash = Call.call_hash(call),
+        signatories = sorted,
+        threshold = threshold,
+        multisig = address,
+        max_weight = opts.max_weight,
+        status = threshold == 1 and "ready" or "new",
+        timepoint = nil,
+        deposit = nil,
+        depositor = nil,
+        approvals = {}
+    } }, Session)
+    if session.path then session:save() end
+    return session
+end
+
+-- Open a session saved by session_create
+function Multisig.session_load(path)
+    local f, err = io.open(path, "rb")
+    if not f then error("Cannot open multisig session: " .. tostring(err)) end
+    local text = f:read("*a")
+    f:close()
+    local ok, state = pcall(json.decode, text)
+    if not ok or type(state) ~= "table" or state.version ~= Multisig.SESSION_VERSION then
+        error("Unreadable multisig session " .. path)
+    end
+    if state.timepoint == json.null then state.timepoint = nil end
+    return setmetatable({ path = path, state = state }, Session)
+end
+
+function Session:save(path)
+    path = path or self.path
+    assert(path, "Session has no file path")
+    local tmp = path .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write multisig session: " .. tostring(err)) end
+    f:write(json.encode(self.state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, path)
+    if not ok then error("Cannot write multisig session: " .. tostring(rename_err)) end
+end
+
+-- Refresh from chain
+-- Returns { status, multisig, call_hash, threshold, approvals, remaining, timepoint,
+--   deposit, depositor }; accounts follow the client's ss58_format when one is set
+function Session:status(api)
+    local s = self.state
+    local changed = false
+    if s.threshold > 1 and s.status ~= "closed" then
+        local open = query(api, s.multisig, s.call_hash)
+        if open then
+            s.timepoint, s.deposit, s.depositor, s.approvals = open.timepoint, open.deposit, open.depositor, open.approvals
+            s.status = #open.approvals >= s.threshold - 1 and "ready" or "collecting"
+            changed = true
+        elseif s.timepoint then
+            s.status = "closed"
+            changed = true
+        end
+    end
+    if changed and self.path then self:save() end
+    return {
+        status = s.status,
+        multisig = render(api, s.multisig),
+        call_hash = s.call_hash,
+        threshold = s.threshold,
+        approvals = render_all(api, s.approvals),
+        remaining = math.max(s.threshold - #s.approvals, 0),
+        timepoint = s.timepoint,
+        deposit = s.deposit,
+        depositor = render(api, s.depositor)
+    }
+end
+
+-- What a signatory should do next, from the last refreshed status
+-- Returns { action = "approve" | "execute" | "wait" | "done" | "none", reason }
+--   approve: add an approval (opens the multisig when status is "new")
+--   execute: this approval reaches the threshold and dispatches the call
+--   wait: already approved; waiting for others
+--   none: not a signatory
+function Session:next_action(address)
+    local s = self.state
+    local me = pubkey_hex(address)
+    local member = false
+    for _, key in ipairs(s.signatories) do
+        if key == me then member = true end
+    end
+    if not member then return { action = "none", reason = "not a signatory" } end
+    if s.status == "closed" then return { action = "done", reason = "executed or cancelled" } end
+    for _, key in ipairs(s.approvals) do
+        if key:lower() == me then
+            return { action = "wait", reason = (s.threshold - #s.approvals) .. " more approvals needed" }
+        end
+    end
+    if s.status == "ready" then return { action = "execute", reason = "this approval reaches the threshold" } end
+    return { action = "approve", reason = s.status == "new" and "opens the multisig" or "adds an approval" }
+end
+
+-- Sign the extrinsic for the signer's next action
+-- opts go to Transaction.create_signed_from_api, plus max_weight to override the session's
+-- Returns extrinsic hex, details (details.action is the action taken)
+function Session:act(api, signer, opts)
+    opts = opts or {}
+    signer = signer_of(api, signer)
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.max_weight = nil
+    local s = self.state
+    local next = self:next_action(signer.address)
+    local other = others(s.signatories, signer.address)
+    local call_bytes
+    if next.action == "execute" then
+        if s.threshold == 1 then
+            call_bytes = Multisig.encode_as_multi_threshold_1(api, other, s.call)
+        else
+            local weight = opts.max_weight or s.max_weight
+            if not weight then
+                local w = require("sublua.weights").query_call(api, from_hex(s.call))
+                weight = { ref_time = w.ref_time, proof_size = w.proof_size }
+            end
+            call_bytes = Multisig.encode_as_multi(api, s.threshold, other, s.timepoint, s.call, weight)
+        end
+    elseif next.action == "approve" then
+        call_bytes = Multisig.encode_approve_as_multi(api, s.threshold, other, s.timepoint, s.call_hash)
+    else
+        error("Nothing to sign: " .. next.action .. " (" .. next.reason .. ")")
+    end
+    local signed, details = sign(api, signer, call_bytes, o)
+    details.action = next.action
+    return signed, details
+end
+
+-- Cancel the open multisig (depositor only); the deposit is returned
+function Session:cancel(api, signer, opts)
+    signer = signer_of(api, signer)
+    local s = self.state
+    assert(s.timepoint, "The multisig is not open on chain")
+    return sign(api, signer, Multisig.encode_cancel_as_multi(api, s.threshold,
+        others(s.signatories, signer.address), s.timepoint, s.call_hash), opts)
+end
+
+return Multisig
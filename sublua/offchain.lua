@@ -0,0 +1,193 @@
+-- sublua/offchain.lua
+-- Off-chain worker integration: the node's offchain local storage and the statement store
+-- Local storage needs a client created with { allow_unsafe = true } against the node's
+-- own RPC; the statement store needs a node started with --enable-statement-store
+-- Statements follow sp-statement-store: a Vec<Field> with fields in index order
+--   0 AuthenticityProof, 1 DecryptionKey, 2 Priority (u32), 3 Channel,
+--   4..7 Topic1..Topic4, 8 Data; each 32-byte field as "0x" hex
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+
+local Offchain = {}
+
+-- Storage kinds: PERSISTENT survives restarts and forks, LOCAL is fork-aware
+Offchain.PERSISTENT = "PERSISTENT"
+Offchain.LOCAL = "LOCAL"
+
+Offchain.MAX_TOPICS = 4
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Bytes or 0x hex -> 0x hex
+local function hex_of(data)
+    assert(type(data) == "string", "expected bytes or 0x hex")
+    if data:match("^0x%x*$") then return data:lower() end
+    return "0x" .. to_hex(data)
+end
+
+local function bytes32(value, what)
+    local raw = value:match("^0x%x+$") and from_hex(value) or value
+    assert(#raw == 32, what .. " must be 32 bytes")
+    return raw
+end
+
+local function read(data, offset, len, what)
+    local b = data:sub(offset, offset + len - 1)
+    assert(#b == len, "Truncated " .. what)
+    return b, offset + len
+end
+
+-- ============================================================
+-- Local Storage
+-- Keys and values are bytes or 0x hex; an off-chain worker's keys are usually
+-- ASCII prefixes such as "my-pallet::last-run"
+-- ============================================================
+
+-- Read a value; kind defaults to PERSISTENT
+-- Returns "0x" hex, or nil when the key is not set
+function Offchain.get(api, key, kind)
+    local value = api:offchain_localStorageGet(kind or Offchain.PERSISTENT, hex_of(key))
+    if type(value) ~= "string" then return nil end
+    return value
+end
+
+-- Write a value; kind defaults to PERSISTENT
+function Offchain.set(api, key, value, kind)
+    return api:offchain_localStorageSet(kind or Offchain.PERSISTENT, hex_of(key), hex_of(value))
+end
+
+-- ============================================================
+-- Statements
+-- statement: { data, topics = { topic, ... }, channel, priority, decryption_key }
+-- ============================================================
+
+-- Encode the fields after the proof; these are also what the proof signs
+local function encode_fields(statement)
+    local parts, count = {}, 0
+    local function field(index, payload)
+        table.insert(parts, string.char(index) .. payload)
+        count = count + 1
+    end
+    if statement.decryption_key then field(1, bytes32(statement.decryption_key, "decryption_key")) end
+    if statement.priority then field(2, Scale.encode_u32(statement.priority)) end
+    if statement.channel then field(3, bytes32(statement.channel, "channel")) end
+    local topics = statement.topics or {}
+    assert(#topics <= Offchain.MAX_TOPICS, "A statement takes at most " .. Offchain.MAX_TOPICS .. " topics")
+    for i, topic in ipairs(topics) do field(3 + i, bytes32(topic, "topic")) end
+    if statement.data then
+        local data = from_hex(hex_of(statement.data))
+        field(8, Scale.encode_compact(#data) .. data)
+    end
+    return table.concat(parts), count
+end
+
+-- Encode a statement, signed by an ed25519 pair (from sublua.keyring) when given
+-- Returns "0x" hex, as statement_submit takes it
+function Offchain.encode_statement(statement, signer)
+    local fields, count = encode_fields(statement)
+    if not signer then
+        return "0x" .. to_hex(Scale.encode_compact(count) .. fields)
+    end
+    assert(signer.scheme == nil or signer.scheme == "ed25519", "Statements are signed with ed25519 pairs")
+    local signature = signer:sign(fields)
+    local proof = "\0" .. "\1" .. signature .. signer.pubkey
+    return "0x" .. to_hex(Scale.encode_compact(count + 1) .. proof .. fields)
+end
+
+-- Decode a SCALE statement
+-- Returns the statement table plus hash (blake2_256 of the encoding) and proof
+--   ({ type = "Sr25519" | "Ed25519" | "Secp256k1Ecdsa" | "OnChain", signer, signature, ... })
+function Offchain.decode_statement(encoded)
+    local data = from_hex(hex_of(encoded))
+    local statement = { hash = "0x" .. to_hex(crypto.blake2b(data, 32)), topics = {} }
+    local count, offset = Scale.decode_compact(data, 1)
+    for _ = 1, count do
+        local index = string.byte(data, offset)
+        assert(index, "Truncated statement")
+        offset = offset + 1
+        local b
+        if index == 0 then
+            local kind = string.byte(data, offset)
+            offset = offset + 1
+            local proof = {}
+            if kind == 0 or kind == 1 then
+                proof.type = kind == 0 and "Sr25519" or "Ed25519"
+                b, offset = read(data, offset, 64, "signature")
+                proof.signature = "0x" .. to_hex(b)
+                b, offset = read(data, offset, 32, "signer")
+                proof.signer = "0x" .. to_hex(b)
+            elseif kind == 2 then
+                proof.type = "Secp256k1Ecdsa"
+                b, offset = read(data, offset, 65, "signature")
+                proof.signature = "0x" .. to_hex(b)
+                b, offset = read(data, offset, 33, "signer")
+                proof.signer = "0x" .. to_hex(b)
+            elseif kind == 3 then
+                proof.type = "OnChain"
+                b, offset = read(data, offset, 32, "who")
+                proof.who = "0x" .. to_hex(b)
+                b, offset = read(data, offset, 32, "block hash")
+                proof.block_hash = "0x" .. to_hex(b)
+                proof.event_index, offset = Scale.decode_u64(data, offset)
+            else
+                error("Unknown statement proof " .. tostring(kind))
+            end
+            statement.proof = proof
+        elseif index == 2 then
+            statement.priority, offset = Scale.decode_u32(data, offset)
+        elseif index == 8 then
+            local len
+            len, offset = Scale.decode_compact(data, offset)
+            b, offset = read(data, offset, len, "data")
+            statement.data = "0x" .. to_hex(b)
+        elseif index == 1 or index == 3 or (index >= 4 and index <= 7) then
+            b, offset = read(data, offset, 32, "field")
+            if index == 1 then
+                statement.decryption_key = "0x" .. to_hex(b)
+            elseif index == 3 then
+                statement.channel = "0x" .. to_hex(b)
+            else
+                statement.topics[index - 3] = "0x" .. to_hex(b)
+            end
+        else
+            error("Unknown statement field " .. tostring(index))
+        end
+    end
+    assert(offset == #data + 1, "Trailing bytes after statement")
+    return statement
+end
+
+-- Check a statement's ed25519 proof; statements with other proofs return false
+function Offchain.verify_statement(encoded)
+    local statement = Offchain.decode_statement(encoded)
+    local proof = statement.proof
+    if not proof or proof.type ~= "Ed25519" then return false end
+    local fields = encode_fields(statement)
+    return crypto.ed25519_verify(from_hex(proof.signer), fields, from_hex(proof.signature)) == true
+end
+
+-- Sign and submit a statement
+-- Returns the node's submission result and the statement hash
+function Offchain.submit_statement(api, statement, signer)
+    local encoded = Offchain.encode_statement(statement, signer)
+    return api:statement_submit(encoded), Offchain.decode_statement(encoded).hash
+end
+
+-- Statements in the node's store carrying all of the given topics (decoded)
+function Offchain.query_statements(api, topics)
+    local out = {}
+    for _, data in ipairs(api:statement_broadcasts(topics or {}) or {}) do
+        table.insert(out, Offchain.decode_statement(data))
+    end
+    return out
+end
+
+return Offchain
@@ -0,0 +1,106 @@
+-- sublua/parachains.lua
+-- Relay-chain views of parachains: HRMP channels, head data and lifecycles (Paras and
+-- Hrmp pallets), plus the parachain's own id from ParachainInfo
+-- Para ids are plain numbers; heads are decoded as Substrate headers when they are one
+
+local crypto = require("polkadot_crypto")
+local Storage = require("sublua.storage")
+local Header = require("sublua.header")
+
+local Parachains = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- ============================================================
+-- HRMP Channels
+-- ============================================================
+
+-- Open HRMP channels
+-- opts: { para_id = nil, at = nil }
+--   para_id: only channels this para sends or receives on
+-- Returns a list of { sender, recipient, max_capacity, max_total_size, max_message_size,
+--   msg_count, total_size, ... } (the HrmpChannel fields), ordered by sender then recipient
+function Parachains.query_hrmp_channels(api, opts)
+    opts = opts or {}
+    local channels = {}
+    for _, item in ipairs(Storage.entries(api, "Hrmp", "HrmpChannels", nil, { at = opts.at })) do
+        local id = item.keys[1]
+        if not opts.para_id or id.sender == opts.para_id or id.recipient == opts.para_id then
+            local channel = { sender = id.sender, recipient = id.recipient }
+            for k, v in pairs(item.value) do channel[k] = v end
+            table.insert(channels, channel)
+        end
+    end
+    table.sort(channels, function(a, b)
+        if a.sender ~= b.sender then return a.sender < b.sender end
+        return a.recipient < b.recipient
+    end)
+    return channels
+end
+
+-- Channel requests waiting for the recipient to accept
+-- Returns a list of { sender, recipient, confirmed, max_capacity, max_message_size, ... }
+function Parachains.query_hrmp_requests(api, opts)
+    opts = opts or {}
+    local requests = {}
+    for _, item in ipairs(Storage.entries(api, "Hrmp", "HrmpOpenChannelRequests", nil, { at = opts.at })) do
+        local id = item.keys[1]
+        if not opts.para_id or id.sender == opts.para_id or id.recipient == opts.para_id then
+            local request = { sender = id.sender, recipient = id.recipient }
+            for k, v in pairs(item.value) do request[k] = v end
+            table.insert(requests, request)
+        end
+    end
+    return requests
+end
+
+-- ============================================================
+-- Paras
+-- ============================================================
+
+-- Latest head data the relay chain holds for a para
+-- at: block hash (default: best block)
+-- Returns { para_id, data = "0x..", hash, number, parent_hash, state_root } or nil
+--   hash is blake2_256 of the head (the para block hash for Substrate heads);
+--   number/parent_hash/state_root are nil when the head is not a Substrate header
+function Parachains.query_parachain_head(api, para_id, at)
+    local data = Storage.fetch(api, "Paras", "Heads", { para_id }, at)
+    if not data then return nil end
+    local raw = from_hex(data)
+    local head = { para_id = para_id, data = data, hash = "0x" .. to_hex(crypto.blake2b(raw, 32)) }
+    local ok, header, offset = pcall(Header.decode, raw)
+    if ok and offset == #raw + 1 then
+        head.number, head.parent_hash, head.state_root = header.number, header.parent_hash, header.state_root
+    end
+    return head
+end
+
+-- Lifecycle of a para: "Onboarding", "Parathread", "Parachain", "UpgradingParathread",
+-- "DowngradingParachain", "OffboardingParathread", "OffboardingParachain", or nil
+function Parachains.query_parachain_lifecycle(api, para_id, at)
+    local lifecycle = Storage.fetch(api, "Paras", "ParaLifecycles", { para_id }, at)
+    return lifecycle and lifecycle.variant
+end
+
+-- Para ids currently producing blocks as parachains
+function Parachains.query_parachains(api, at)
+    return Storage.fetch(api, "Paras", "Parachains", nil, at) or {}
+end
+
+-- ============================================================
+-- Parachain Side
+-- ============================================================
+
+-- The para id a parachain runs as (ParachainInfo.ParachainId on the parachain itself)
+function Parachains.query_para_id(api, at)
+    return Storage.fetch(api, "ParachainInfo", "ParachainId", nil, at)
+end
+
+return Parachains
@@ -0,0 +1,183 @@
+-- sublua/portfolio.lua
+-- Watch-only account registry with an aggregate portfolio snapshot
+-- A snapshot reads every account's balance, staking ledger, nominations and pool
+-- membership in two batched state_queryStorageAt calls at one block, instead of
+-- one round trip per account and item
+
+local RPC = require("sublua.rpc")
+local Address = require("sublua.address")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Bytes = require("sublua.bytes")
+
+local Portfolio = {}
+Portfolio.__index = Portfolio
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+function Portfolio.new()
+    return setmetatable({ accounts = {}, order = {} }, Portfolio)
+end
+
+-- Watch an account under a label
+-- address: SS58 address (any network), "0x" hex or raw 32-byte public key
+function Portfolio:add_account(label, address)
+    assert(type(label) == "string" and label ~= "", "Label must be a non-empty string")
+    assert(not self.accounts[label], "Account '" .. label .. "' is already in the portfolio")
+    local info = Address.inspect(address)
+    if not info.valid then error("Invalid address for '" .. label .. "': " .. tostring(info.error)) end
+    self.accounts[label] = { label = label, address = address, pubkey = info.pubkey }
+    table.insert(self.order, label)
+end
+
+function Portfolio:remove(label)
+    if not self.accounts[label] then error("Unknown account '" .. tostring(label) .. "'") end
+    self.accounts[label] = nil
+    for i, l in ipairs(self.order) do
+        if l == label then table.remove(self.order, i) break end
+    end
+end
+
+-- Watched labels, in the order they were added
+function Portfolio:labels()
+    local labels = {}
+    for i, l in ipairs(self.order) do labels[i] = l end
+    return labels
+end
+
+-- ============================================================
+-- Snapshot
+-- ============================================================
+
+local function add(a, b)
+    return Bytes.bytes_to_decimal(Bytes.add(Bytes.decimal_to_bytes(tostring(a)), Bytes.decimal_to_bytes(tostring(b))))
+end
+
+-- Storage entry value type, or nil when the runtime lacks the entry
+local function entry_type(meta, pallet, name)
+    local entry, _, err = Metadata.get_storage_entry(meta, pallet, name)
+    if err then return nil end
+    return entry.storage_type.value
+end
+
+-- Read a batch of { pallet, entry, key } at one block; returns decoded values by index
+local function query(api, meta, items, at)
+    if #items == 0 then return {} end
+    local keys, index = {}, {}
+    for i, item in ipairs(items) do
+        keys[i] = Storage.build_key(meta, item[1], item[2], { item[3] })
+        index[keys[i]] = i
+    end
+    local values = {}
+    local sets = api:state_queryStorageAt(keys, at) or {}
+    for _, set in ipairs(sets) do
+        for _, change in ipairs(set.changes or {}) do
+            local i, raw = index[change[1]], change[2]
+            if i and type(raw) == "string" and raw ~= "0x" then
+                values[i] = Codec.decode_hex(meta, entry_type(meta, items[i][1], items[i][2]), raw)
+            end
+        end
+    end
+    return values
+end
+
+-- Balances, staking and pool state of every watched account at one block
+-- api: RPC client or node URL
+-- opts: { at = nil, pending_rewards = true }
+--   at: block hash (default: the finalized head)
+--   pending_rewards: ask NominationPoolsApi_pending_rewards for pool members (one call each)
+-- Returns { at, accounts = { { label, address, free, reserved, frozen, nonce,
+--     staking = nil | { controller, total, active, unlocking = { { value, era } }, nominations },
+--     pool = nil | { pool_id, points, pending_rewards } } },
+--   totals = { free, reserved, bonded, pending_rewards } }
+--   amounts are decimal strings in plancks
+function Portfolio:snapshot(api, opts)
+    opts = opts or {}
+    if type(api) == "string" then api = RPC.new(api) end
+    local meta = api:get_metadata()
+    local at = opts.at or api:chain_getFinalizedHead()
+    local has_staking = entry_type(meta, "Staking", "Bonded") ~= nil
+    local has_pools = entry_type(meta, "NominationPools", "PoolMembers") ~= nil
+
+    -- Round 1: accounts, bonds, nominations, pool membership
+    local items, slots = {}, {}
+    for i, label in ipairs(self.order) do
+        local who = self.accounts[label].pubkey
+        slots[i] = {}
+        local function want(field, pallet, entry)
+            table.insert(items, { pallet, entry, who })
+            slots[i][field] = #items
+        end
+        want("account", "System", "Account")
+        if has_staking then
+            want("bonded", "Staking", "Bonded")
+            if entry_type(meta, "Staking", "Nominators") then want("nominations", "Staking", "Nominators") end
+        end
+        if has_pools then want("pool", "NominationPools", "PoolMembers") end
+    end
+    local values = query(api, meta, items, at)
+
+    -- Round 2: ledgers of bonded stashes (keyed by controller)
+    local ledger_items, ledger_slots = {}, {}
+    for i in ipairs(self.order) do
+        local controller = slots[i].bonded and values[slots[i].bonded]
+        if controller then
+            table.insert(ledger_items, { "Staking", "Ledger", controller })
+            ledger_slots[i] = #ledger_items
+        end
+    end
+    local ledgers = query(api, meta, ledger_items, at)
+
+    local result = {
+        at = at,
+        accounts = {},
+        totals = { free = "0", reserved = "0", bonded = "0", pending_rewards = "0" }
+    }
+    for i, label in ipairs(self.order) do
+        local watched = self.accounts[label]
+        local info = values[slots[i].account]
+        local data = info and info.data or {}
+        local entry = {
+            label = label,
+            address = watched.address,
+            nonce = info and info.nonce or 0,
+            free = tostring(data.free or "0"),
+            reserved = tostring(data.reserved or "0"),
+            frozen = tostring(data.frozen or "0")
+        }
+        local ledger = ledger_slots[i] and ledgers[ledger_slots[i]]
+        if ledger then
+            local nominations = slots[i].nominations and values[slots[i].nominations]
+            entry.staking = {
+                controller = values[slots[i].bonded],
+                total = tostring(ledger.total),
+                active = tostring(ledger.active),
+                unlocking = ledger.unlocking or {},
+                nominations = nominations and nominations.targets or nil
+            }
+            result.totals.bonded = add(result.totals.bonded, entry.staking.total)
+        end
+        local member = slots[i].pool and values[slots[i].pool]
+        if member then
+            entry.pool = { pool_id = member.pool_id, points = tostring(member.points) }
+            if opts.pending_rewards ~= false then
+                local raw = api:state_call("NominationPoolsApi_pending_rewards", watched.pubkey, at)
+                if type(raw) == "string" and #raw >= 34 then
+                    entry.pool.pending_rewards = Bytes.bytes_to_decimal((Scale.decode_u128(from_hex(raw), 1)))
+                    result.totals.pending_rewards = add(result.totals.pending_rewards, entry.pool.pending_rewards)
+                end
+            end
+        end
+        result.totals.free = add(result.totals.free, entry.free)
+        result.totals.reserved = add(result.totals.reserved, entry.reserved)
+        table.insert(result.accounts, entry)
+    end
+    return result
+end
+
+return Portfolio
@@ -0,0 +1,125 @@
+-- sublua/preimage.lua
+-- Preimage pallet: note and unnote call data, and look up stored preimages
+-- Referenda and scheduler take proposals as Bounded<Call>: calls too large to inline
+-- are noted here first and referenced by hash and length (see Preimage.lookup_of)
+
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+
+local Preimage = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function bytes(data)
+    assert(type(data) == "string", "preimage must be bytes or 0x hex")
+    if data:match("^0x%x*$") then return from_hex(data) end
+    return data
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Option<T> as decoded by sublua.codec -> value or nil
+local function some(value)
+    if type(value) == "table" and value.variant then
+        if value.variant ~= "Some" then return nil end
+        return value.value
+    end
+    return value
+end
+
+-- ============================================================
+-- Hashes and Lookups
+-- ============================================================
+
+-- Blake2-256 hash a preimage is stored under ("0x" hex)
+-- data: raw bytes or 0x hex
+function Preimage.hash(data)
+    return Call.call_hash(bytes(data))
+end
+
+-- Bounded<Call> value referencing a noted preimage, for Call.encode_call arguments
+-- data: call bytes or 0x hex
+-- Returns { variant = "Lookup", value = { hash, len } }
+function Preimage.lookup_of(data)
+    data = bytes(data)
+    return { variant = "Lookup", value = { hash = Preimage.hash(data), len = #data } }
+end
+
+-- ============================================================
+-- Calls
+-- Functions that sign return the extrinsic hex and build details, as
+-- Transaction.create_signed_from_api; opts go to it (tip, nonce, mortality, ...)
+-- ============================================================
+
+-- Build Preimage.note_preimage call bytes
+function Preimage.encode_note(meta_source, data)
+    return Call.encode_call(meta_source, "Preimage", "note_preimage", { bytes = "0x" .. to_hex(bytes(data)) })
+end
+
+-- Note call data (reserves a deposit unless the preimage was requested)
+-- call_data: call bytes or 0x hex
+-- Returns extrinsic hex, details; details.hash and details.len identify the preimage
+function Preimage.note(api, signer, call_data, opts)
+    local data = bytes(call_data)
+    local signed, details = sign(api, signer, Preimage.encode_note(api, data), opts)
+    details.hash, details.len = Preimage.hash(data), #data
+    return signed, details
+end
+
+-- Remove a preimage the signer noted and release the deposit
+function Preimage.unnote(api, signer, hash, opts)
+    return sign(api, signer, Call.encode_call(api, "Preimage", "unnote_preimage", { hash = hash }), opts)
+end
+
+-- ============================================================
+-- Queries
+-- RequestStatusFor on current runtimes, StatusFor on older ones
+-- ============================================================
+
+-- Status of a preimage
+-- opts: { data = false } also read the preimage bytes (needs the length to be known)
+-- Returns { hash, status = "Unrequested" | "Requested", len, depositor, count, data }
+--   or nil when the hash is unknown
+--   depositor: "0x" account holding the deposit (nil when none); count: open requests;
+--   data: "0x" hex when opts.data is set and the bytes are stored
+function Preimage.query(api, hash, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local entry_name = "RequestStatusFor"
+    if not Metadata.get_storage_entry(meta, "Preimage", entry_name) then entry_name = "StatusFor" end
+    local status = Storage.fetch(api, "Preimage", entry_name, { hash })
+    if not status then return nil end
+
+    local fields = status.value or {}
+    local ticket = some(fields.ticket or fields.maybe_ticket or fields.deposit)
+    local result = {
+        hash = hash,
+        status = status.variant,
+        len = some(fields.len or fields.maybe_len),
+        depositor = ticket and ticket[1],
+        count = fields.count or 0
+    }
+    if opts.data and result.len then
+        result.data = Storage.fetch(api, "Preimage", "PreimageFor", { { hash, result.len } })
+    end
+    return result
+end
+
+-- Whether a preimage is stored (noted, not merely requested)
+function Preimage.is_noted(api, hash)
+    local status = Preimage.query(api, hash)
+    return status ~= nil and status.len ~= nil
+end
+
+return Preimage
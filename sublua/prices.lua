@@ -0,0 +1,117 @@
+-- sublua/prices.lua
+-- Optional fiat price lookups for wallet UIs, cached with a TTL
+-- Default provider: CoinGecko-compatible GET <base_url>/simple/price?ids=<id>&vs_currencies=<vs>
+-- Nothing is fetched unless a price is asked for; plug in your own provider to use another source
+
+local http = require("socket.http")
+local ltn12 = require("ltn12")
+local socket = require("socket")
+local json = require("cjson")
+
+local Prices = {}
+Prices.__index = Prices
+
+Prices.DEFAULT_URL = "https://api.coingecko.com/api/v3"
+
+-- Token symbol -> CoinGecko coin id
+Prices.COIN_IDS = {
+    DOT = "polkadot",
+    KSM = "kusama",
+    ASTR = "astar",
+    GLMR = "moonbeam",
+    MOVR = "moonriver",
+    ACA = "acala",
+    HDX = "hydradx",
+    USDT = "tether",
+    USDC = "usd-coin"
+}
+
+-- Create a price client
+-- opts: { base_url, api_key, headers, ttl = 60, timeout_ms = nil, coin_ids = {}, provider = nil, clock = nil }
+--   api_key: sent as x-cg-demo-api-key (use headers for other services)
+--   ttl: seconds a price stays cached
+--   coin_ids: extra or overriding symbol -> coin id entries
+--   provider: function(symbol, vs_currency) -> price | nil, err; replaces the HTTP lookup
+--   clock: function() -> seconds, for tests (default socket.gettime)
+function Prices.new(opts)
+    opts = opts or {}
+    local ids = {}
+    for k, v in pairs(Prices.COIN_IDS) do ids[k] = v end
+    for k, v in pairs(opts.coin_ids or {}) do ids[k:upper()] = v end
+    local headers = { Accept = "application/json" }
+    for k, v in pairs(opts.headers or {}) do headers[k] = v end
+    if opts.api_key then headers["x-cg-demo-api-key"] = opts.api_key end
+    return setmetatable({
+        base_url = (opts.base_url or Prices.DEFAULT_URL):gsub("/$", ""),
+        headers = headers,
+        ttl = opts.ttl or 60,
+        timeout_ms = opts.timeout_ms,
+        coin_ids = ids,
+        provider = opts.provider,
+        clock = opts.clock or socket.gettime,
+        cache = {}
+    }, Prices)
+end
+
+-- HTTP lookup against a CoinGecko-compatible API
+function Prices:fetch(symbol, vs_currency)
+    local id = self.coin_ids[symbol]
+    if not id then return nil, "No coin id known for " .. symbol .. "; pass coin_ids = { " .. symbol .. " = \"...\" }" end
+    local url = self.base_url .. "/simple/price?ids=" .. id .. "&vs_currencies=" .. vs_currency
+    local body = {}
+    local saved_timeout = http.TIMEOUT
+    if self.timeout_ms then http.TIMEOUT = self.timeout_ms / 1000 end
+    local ok, status = http.request{
+        url = url,
+        method = "GET",
+        headers = self.headers,
+        sink = ltn12.sink.table(body)
+    }
+    http.TIMEOUT = saved_timeout
+    if not ok then
+        if status == "timeout" then return nil, "Timeout: price provider did not answer" end
+        return nil, "Price request failed: " .. tostring(status)
+    end
+    if status ~= 200 then return nil, "Price provider returned HTTP " .. tostring(status) end
+    local decoded, response = pcall(json.decode, table.concat(body))
+    local price = decoded and type(response) == "table" and type(response[id]) == "table" and response[id][vs_currency]
+    if type(price) ~= "number" then
+        return nil, "No " .. vs_currency .. " price for " .. symbol .. " in provider response"
+    end
+    return price
+end
+
+-- Price of one token in a fiat (or other) currency
+-- symbol: token symbol, e.g. "DOT" (case-insensitive); vs_currency: default "usd"
+-- Returns price (number), or nil, err
+function Prices:get_token_price(symbol, vs_currency)
+    assert(type(symbol) == "string" and symbol ~= "", "symbol must be a non-empty string")
+    symbol, vs_currency = symbol:upper(), (vs_currency or "usd"):lower()
+    local key = symbol .. "/" .. vs_currency
+    local now = self.clock()
+    local hit = self.cache[key]
+    if hit and now - hit.at < self.ttl then return hit.price end
+
+    local price, err
+    if self.provider then
+        price, err = self.provider(symbol, vs_currency)
+    else
+        price, err = self:fetch(symbol, vs_currency)
+    end
+    if type(price) ~= "number" then return nil, err or ("No price for " .. key) end
+    self.cache[key] = { price = price, at = now }
+    return price
+end
+
+function Prices:clear_cache()
+    self.cache = {}
+end
+
+-- Shared client with default options (CoinGecko, 60 s cache)
+local shared
+function Prices.default()
+    shared = shared or Prices.new()
+    return shared
+end
+
+return Prices
@@ -0,0 +1,191 @@
+-- sublua/prometheus.lua
+-- Prometheus exporter for RPC client health counters (see RPC:metrics)
+-- Opt-in: nothing listens until start_metrics_server is called. The listener is a
+-- non-blocking LuaSocket server: bots with their own loop call server:step() between
+-- jobs, scripts with nothing else to do call server:run()
+
+local socket = require("socket")
+local Introspect = require("sublua.introspect")
+
+local Prometheus = {}
+
+local Server = {}
+Server.__index = Server
+
+-- ============================================================
+-- Text Format
+-- ============================================================
+
+local function label_value(v)
+    return (tostring(v):gsub("\\", "\\\\"):gsub("\"", "\\\""):gsub("\n", "\\n"))
+end
+
+local function labels(t)
+    local keys = {}
+    for k in pairs(t) do table.insert(keys, k) end
+    table.sort(keys)
+    local out = {}
+    for i, k in ipairs(keys) do out[i] = k .. "=\"" .. label_value(t[k]) .. "\"" end
+    return "{" .. table.concat(out, ",") .. "}"
+end
+
+local function number(v)
+    if v % 1 == 0 and math.abs(v) < 2^53 then return string.format("%d", v) end
+    return string.format("%.6g", v)
+end
+
+-- Families in output order: name, type, help, value(metrics) or samples(metrics)
+local FAMILIES = {
+    { "sublua_rpc_requests_total", "counter", "JSON-RPC requests sent",
+        function(m) return m.requests end },
+    { "sublua_rpc_errors_total", "counter", "Failed JSON-RPC requests by kind",
+        samples = function(m)
+            return { { { kind = "timeout" }, m.timeouts }, { { kind = "rpc" }, m.rpc_errors },
+                { { kind = "transport" }, m.transport_errors } }
+        end },
+    { "sublua_rpc_latency_avg_seconds", "gauge", "Average request latency",
+        function(m) return m.avg_latency_ms / 1000 end },
+    { "sublua_rpc_latency_max_seconds", "gauge", "Slowest request latency",
+        function(m) return m.max_latency_ms / 1000 end },
+    { "sublua_rpc_sent_bytes_total", "counter", "Request bytes sent",
+        function(m) return m.bytes_out end },
+    { "sublua_rpc_received_bytes_total", "counter", "Response bytes received",
+        function(m) return m.bytes_in end },
+    { "sublua_rpc_reconnects_total", "counter", "Recoveries after transport errors",
+        function(m) return m.reconnects end },
+    { "sublua_rpc_up", "gauge", "1 unless the last request failed at the transport level",
+        function(m) return m.connected and 1 or 0 end },
+    { "sublua_rpc_method_requests_total", "counter", "JSON-RPC requests by method",
+        samples = function(m)
+            local out = {}
+            for method, count in pairs(m.methods) do table.insert(out, { { method = method }, count }) end
+            table.sort(out, function(a, b) return a[1].method < b[1].method end)
+            return out
+        end }
+}
+
+-- Clients as a sorted list of { name, client }
+-- clients: one RPC client (named "default"), or { [name] = client }
+local function named(clients)
+    if type(clients) == "table" and clients.metrics then return { { "default", clients } } end
+    local out = {}
+    for name, client in pairs(clients or {}) do table.insert(out, { tostring(name), client }) end
+    table.sort(out, function(a, b) return a[1] < b[1] end)
+    return out
+end
+
+-- Metrics of RPC clients in the Prometheus text exposition format (version 0.0.4)
+-- clients: one RPC client, or { [name] = client } (the name goes in the client label)
+function Prometheus.render(clients)
+    local list = named(clients)
+    local snapshots = {}
+    for i, entry in ipairs(list) do snapshots[i] = entry[2]:metrics() end
+    local lines = {}
+    for _, family in ipairs(FAMILIES) do
+        local name = family[1]
+        table.insert(lines, "# HELP " .. name .. " " .. family[3])
+        table.insert(lines, "# TYPE " .. name .. " " .. family[2])
+        for i, entry in ipairs(list) do
+            local m = snapshots[i]
+            if family.samples then
+                for _, sample in ipairs(family.samples(m)) do
+                    sample[1].client = entry[1]
+                    table.insert(lines, name .. labels(sample[1]) .. " " .. number(sample[2]))
+                end
+            else
+                table.insert(lines, name .. labels({ client = entry[1] }) .. " " .. number(family[4](m)))
+            end
+        end
+    end
+    return table.concat(lines, "\n") .. "\n"
+end
+
+-- ============================================================
+-- Server
+-- ============================================================
+
+Prometheus.CONTENT_TYPE = "text/plain; version=0.0.4; charset=utf-8"
+
+-- Listen for scrapes of GET /metrics
+-- bind_addr: "host:port" (":9100" or "9100" bind 127.0.0.1; port 0 picks a free one)
+-- clients: as Prometheus.render
+-- opts: { read_timeout = 2 } seconds to wait for a scraper's request
+-- Returns the server: { host, port, step, run, close }
+function Prometheus.start_metrics_server(bind_addr, clients, opts)
+    opts = opts or {}
+    local host, port = tostring(bind_addr):match("^(.-):?(%d+)$")
+    if not port then error("bind_addr must be host:port, got " .. tostring(bind_addr)) end
+    if host == "" then host = "127.0.0.1" end
+    local listener, err = socket.bind(host, tonumber(port))
+    if not listener then error("Cannot listen on " .. host .. ":" .. port .. ": " .. tostring(err)) end
+    listener:settimeout(0)
+    local _, bound_port = listener:getsockname()
+    return Introspect.track("metrics_servers", setmetatable({
+        listener = listener,
+        clients = clients,
+        host = host,
+        port = tonumber(bound_port),
+        read_timeout = opts.read_timeout or 2,
+        served = 0
+    }, Server), function(server, stats)
+        if server.listener then stats.open_metrics_servers = stats.open_metrics_servers + 1 end
+    end)
+end
+
+local function respond(conn, status, content_type, body)
+    conn:send("HTTP/1.1 " .. status .. "\r\nContent-Type: " .. content_type
+        .. "\r\nContent-Length: " .. #body .. "\r\nConnection: close\r\n\r\n" .. body)
+end
+
+local function serve(self, conn)
+    conn:settimeout(self.read_timeout)
+    local line = conn:receive("*l")
+    if not line then return end
+    repeat
+        local header = conn:receive("*l")
+    until not header or header == ""
+    local method, path = line:match("^(%u+)%s+(%S+)")
+    if method ~= "GET" then
+        respond(conn, "405 Method Not Allowed", "text/plain", "GET only\n")
+    elseif path:match("^/metrics") then
+        local ok, body = pcall(Prometheus.render, self.clients)
+        if ok then
+            respond(conn, "200 OK", Prometheus.CONTENT_TYPE, body)
+        else
+            respond(conn, "500 Internal Server Error", "text/plain", tostring(body) .. "\n")
+        end
+    else
+        respond(conn, "404 Not Found", "text/plain", "Metrics are at /metrics\n")
+    end
+    self.served = self.served + 1
+end
+
+-- Answer pending scrapes, waiting up to timeout seconds (default 0) for the first
+-- Returns the number of requests served
+function Server:step(timeout)
+    if not self.listener then return 0 end
+    local before = self.served
+    self.listener:settimeout(timeout or 0)
+    local conn = self.listener:accept()
+    self.listener:settimeout(0)
+    while conn do
+        pcall(serve, self, conn)
+        conn:close()
+        conn = self.listener:accept()
+    end
+    return self.served - before
+end
+
+-- Serve until close() is called (e.g. from another coroutine) or forever
+function Server:run()
+    while self.listener do self:step(1) end
+end
+
+function Server:close()
+    if self.listener then
+        self.listener:close()
+        self.listener = nil
+    end
+end
+
+return Prometheus
@@ -0,0 +1,114 @@
+-- sublua/pump.lua
+-- Main-loop dispatch for hosts that must get callbacks on their own loop and cannot block
+-- a frame (LÖVE, Defold, Solar2D, ...)
+-- Work started with pump:spawn runs as a coroutine whose waits (poll intervals, retry
+-- backoff: pass pump.wait as the sleep option) yield instead of sleeping; its result, and
+-- events sent through pump:deliver callbacks, are queued and only run when the host calls
+-- pump:pump() from its loop
+-- A single RPC call still blocks while it runs: LuaSocket HTTP is synchronous
+
+local socket = require("socket")
+local Introspect = require("sublua.introspect")
+
+local Pump = {}
+Pump.__index = Pump
+
+local function pack(...) return { n = select("#", ...), ... } end
+local unpack = table.unpack or unpack
+
+-- opts: { clock = socket.gettime }
+function Pump.new(opts)
+    opts = opts or {}
+    local self = setmetatable({
+        clock = opts.clock or socket.gettime,
+        tasks = {},
+        queue = {},
+        current = nil  -- Task being resumed, so wait knows it may yield
+    }, Pump)
+    -- Sleep function for the sleep option of SDK loops (Subscription:run, Transaction.submit
+    -- retries, wait_for_inclusion, ...): yields inside spawned work, blocks elsewhere
+    self.wait = function(seconds) return self:sleep(seconds) end
+    return Introspect.track("pumps", self, function(pump, stats)
+        stats.pump_tasks = stats.pump_tasks + #pump.tasks
+        stats.pump_callbacks = stats.pump_callbacks + #pump.queue
+    end)
+end
+
+-- Wait seconds; inside spawned work the task yields and pump() resumes it once the time is up
+-- Yielding across pcall needs Lua 5.2+ or LuaJIT; where it is not possible this blocks
+function Pump:sleep(seconds)
+    local yieldable = self.current ~= nil
+    if yieldable and coroutine.isyieldable then yieldable = coroutine.isyieldable() end
+    if not yieldable then return socket.sleep(seconds) end
+    coroutine.yield(self.clock() + (seconds or 0))
+end
+
+-- Wrap a callback so its calls are queued and run by pump() on the host's loop
+-- e.g. Subscription.new(api, { on_block = pump:deliver(function(block) ... end) })
+function Pump:deliver(fn)
+    return function(...)
+        table.insert(self.queue, { fn, pack(...) })
+    end
+end
+
+-- Start work on the pump: fn() runs as a coroutine, resumed by pump() between waits
+-- on_done(ok, result...): queued once fn returns (ok = true) or raises (ok = false, err);
+--   without it, an error is raised from pump()
+-- Returns the task { done, ok, results }
+function Pump:spawn(fn, on_done)
+    local task = { co = coroutine.create(fn), wake = 0, done = false, on_done = on_done }
+    table.insert(self.tasks, task)
+    return task
+end
+
+local function finish(self, task, results)
+    task.done, task.ok, task.results = true, results[1], pack(unpack(results, 2, results.n))
+    if task.on_done then
+        table.insert(self.queue, { task.on_done, results })
+    elseif not task.ok then
+        local err = results[2]
+        table.insert(self.queue, { function() error(err, 0) end, pack() })
+    end
+end
+
+-- Call from the host's loop (e.g. love.update): resume spawned work whose wait is over,
+-- then run queued callbacks, all on the calling thread
+-- budget_ms: stop running queued callbacks after this long; the rest wait for the next pump
+-- Returns the number of callbacks run
+function Pump:pump(budget_ms)
+    local now = self.clock()
+    local running = self.tasks
+    self.tasks = {}
+    for _, task in ipairs(running) do
+        if task.wake <= now then
+            self.current = task
+            local results = pack(coroutine.resume(task.co))
+            self.current = nil
+            if coroutine.status(task.co) == "dead" then
+                finish(self, task, results)
+            else
+                task.wake = results[1] and tonumber(results[2]) or 0
+                table.insert(self.tasks, task)
+            end
+        else
+            table.insert(self.tasks, task)
+        end
+    end
+
+    local deadline = budget_ms and now + budget_ms / 1000
+    local count = 0
+    while #self.queue > 0 do
+        if deadline and count > 0 and self.clock() >= deadline then break end
+        local item = table.remove(self.queue, 1)
+        count = count + 1
+        item[1](unpack(item[2], 1, item[2].n))
+    end
+    return count
+end
+
+-- Spawned tasks still running and callbacks waiting for pump()
+function Pump:pending()
+    return #self.tasks + #self.queue
+end
+
+return Pump
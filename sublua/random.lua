@@ -0,0 +1,57 @@
+-- sublua/random.lua
+-- Random bytes for key generation and sealing, always from the OS
+-- Deterministic sources (tests, fixtures, golden files): Random.deterministic(seed) returns
+-- a BLAKE2b stream of the seed that mnemonic generation and vanity search take as an
+-- explicit opts.random argument, so generated phrases and vanity keys come out the same
+-- on every run. Nothing else uses it: keystore salts and nonces always come from the OS.
+-- Never use it for real keys
+
+local crypto = require("polkadot_crypto")
+
+local Random = {}
+
+local function u32_le(n)
+    return string.char(n % 256, math.floor(n / 256) % 256, math.floor(n / 65536) % 256, math.floor(n / 16777216) % 256)
+end
+
+local function check_count(n)
+    assert(type(n) == "number" and n >= 0 and n % 1 == 0, "Byte count must be a non-negative integer")
+end
+
+-- n random bytes from /dev/urandom
+function Random.bytes(n)
+    check_count(n)
+    local f = io.open("/dev/urandom", "rb")
+    if not f then error("No /dev/urandom to draw random bytes from") end
+    local bytes = f:read(n)
+    f:close()
+    assert(n == 0 or (bytes and #bytes == n), "Short read from /dev/urandom")
+    return bytes or ""
+end
+
+-- Deterministic source derived from seed (any non-empty string)
+-- Returns function(n) -> n bytes; each source continues its own stream, and two sources
+-- of the same seed give the same bytes
+function Random.deterministic(seed)
+    assert(type(seed) == "string" and seed ~= "", "Random seed must be a non-empty string")
+    local key, counter, buffer = crypto.blake2b("sublua-random" .. seed, 32), 0, ""
+    return function(n)
+        check_count(n)
+        while #buffer < n do
+            buffer = buffer .. crypto.blake2b(key .. u32_le(counter), 64)
+            counter = counter + 1
+        end
+        local out = buffer:sub(1, n)
+        buffer = buffer:sub(n + 1)
+        return out
+    end
+end
+
+-- The source to draw from: a caller's deterministic source, else the OS
+function Random.source(random)
+    if random == nil then return Random.bytes end
+    assert(type(random) == "function", "opts.random must be a source from Random.deterministic")
+    return random
+end
+
+return Random
@@ -0,0 +1,146 @@
+-- sublua/registry.lua
+-- Multi-chain registry: one RPC client, metadata cache, SS58 prefix and nonce tracker per named chain
+-- Lets relay chain + parachain applications address chains by name instead of juggling URLs
+
+local Address = require("sublua.address")
+local RPC = require("sublua.rpc")
+local Transaction = require("sublua.transaction")
+
+local Registry = {}
+Registry.__index = Registry
+
+function Registry.new()
+    return setmetatable({ chains = {} }, Registry)
+end
+
+-- Register a chain under a name
+-- config: URL string, or { url, ss58_prefix = nil, para_id = nil, relay = nil, people = nil,
+--   rpc = nil, api = nil }
+--   ss58_prefix: address format; read from system_properties when nil
+--   para_id / relay: parachain id and the name of its relay chain, for XCM helpers
+--   people: name of the chain holding this chain's identities (see Registry:identity_api)
+--   rpc: options for rpc.new (proxy, tls, ...); api: an already built client to use instead
+-- Returns the chain context
+function Registry:register(name, config)
+    assert(type(name) == "string" and name ~= "", "Chain name must be a non-empty string")
+    assert(not self.chains[name], "Chain '" .. name .. "' is already registered")
+    if type(config) == "string" then config = { url = config } end
+    assert(config.url or config.api, "Chain '" .. name .. "' needs a url")
+    if config.relay then self:get(config.relay) end
+
+    local chain = {
+        name = name,
+        url = config.url,
+        api = config.api or RPC.new(config.url, config.rpc),
+        para_id = config.para_id,
+        relay = config.relay,
+        people = config.people,
+        prefix = config.ss58_prefix,
+        nonces = {}
+    }
+    self.chains[name] = chain
+    return chain
+end
+
+function Registry:remove(name)
+    self:get(name)
+    for other, chain in pairs(self.chains) do
+        assert(chain.relay ~= name, "Chain '" .. other .. "' still uses '" .. name .. "' as its relay chain")
+    end
+    self.chains[name] = nil
+end
+
+-- Chain context by name; errors for unknown chains
+function Registry:get(name)
+    local chain = self.chains[name]
+    if not chain then error("Unknown chain '" .. tostring(name) .. "'") end
+    return chain
+end
+
+-- Registered chain names, sorted
+function Registry:names()
+    local names = {}
+    for name in pairs(self.chains) do table.insert(names, name) end
+    table.sort(names)
+    return names
+end
+
+-- ============================================================
+-- Per-chain Context
+-- ============================================================
+
+function Registry:api(name)
+    return self:get(name).api
+end
+
+-- Client for identity calls on a chain: its people chain when one is registered (directly
+-- or through its relay chain), else the chain's own client
+-- The link is also set on the chain's client, so sublua.identity calls with either one reach it
+function Registry:identity_api(name)
+    local chain = self:get(name)
+    local people = chain.people or (chain.relay and self:get(chain.relay).people)
+    if not people then return chain.api end
+    local api = self:api(people)
+    chain.api.identity_chain = api
+    return api
+end
+
+-- Parsed metadata (cached by the chain's client)
+function Registry:metadata(name)
+    return self:get(name).api:get_metadata()
+end
+
+function Registry:ss58_prefix(name)
+    local chain = self:get(name)
+    if not chain.prefix then
+        chain.prefix = chain.api:get_chain_properties().ss58_prefix or 42
+    end
+    return chain.prefix
+end
+
+-- Address of a key on a chain
+-- who: keyring pair, 32-byte public key (raw or "0x" hex), or an SS58 address in any format
+function Registry:address(name, who)
+    if type(who) == "table" then who = who.pubkey end
+    return Address.convert(who, self:ss58_prefix(name))
+end
+
+-- ============================================================
+-- Nonces
+-- ============================================================
+
+-- Next nonce for an account, counting transactions already built through the registry
+-- The first call asks the node (system_accountNextIndex, which includes pooled transactions)
+function Registry:next_nonce(name, address)
+    local chain = self:get(name)
+    local key = Address.decode(address)
+    local nonce = chain.nonces[key]
+    if nonce == nil then
+        nonce = tonumber(chain.api:system_accountNextIndex(address))
+    end
+    chain.nonces[key] = nonce + 1
+    return nonce
+end
+
+-- Forget the tracked nonce (e.g. after a rejected submission) so the next one is re-read
+function Registry:reset_nonce(name, address)
+    self:get(name).nonces[(Address.decode(address))] = nil
+end
+
+-- Build a signed extrinsic on a chain with a managed nonce
+-- Same opts as transaction.create_signed_from_api; returns extrinsic hex, info
+function Registry:create_signed(name, signer, call_bytes, opts)
+    local chain = self:get(name)
+    signer = Transaction.signer_of(chain.api, signer)
+    local o = {}
+    for k, v in pairs(opts or {}) do o[k] = v end
+    o.nonce = o.nonce or self:next_nonce(name, signer.address)
+    local ok, signed, info = pcall(Transaction.create_signed_from_api, chain.api, signer, call_bytes, o)
+    if not ok then
+        self:reset_nonce(name, signer.address)
+        error(signed, 0)
+    end
+    return signed, info
+end
+
+return Registry
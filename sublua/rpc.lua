@@ -1,8 +1,12 @@
 local http = require("socket.http")
 local ltn12 = require("ltn12")
 local json = require("cjson")
+local socket = require("socket")
 local crypto = require("polkadot_crypto") -- Found in package.cpath
 local Metadata = require("sublua.metadata")
+local Types = require("sublua.types")
+local Address = require("sublua.address")
+local Introspect = require("sublua.introspect")
 
 local RPC = {}
 RPC.__index = RPC
@@ -18,34 +22,182 @@ local function from_hex(hex)
     return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
 end
 
-function RPC.new(url)
+-- Subscription APIs need a persistent connection (WebSocket); patterns cover the
+-- legacy *_subscribe*/*AndWatch* methods and the new chainHead/transactionWatch groups
+local SUBSCRIPTION_PATTERNS = {
+    "_subscribe", "_unsubscribe", "AndWatch", "^chainHead_", "^transactionWatch_"
+}
+
+function RPC.is_subscription(method)
+    for _, pattern in ipairs(SUBSCRIPTION_PATTERNS) do
+        if method:match(pattern) then return true end
+    end
+    return false
+end
+
+-- Timeouts raise errors starting with "Timeout:"; check with RPC.is_timeout
+function RPC.is_timeout(err)
+    return tostring(err):find("Timeout:", 1, true) ~= nil
+end
+
+local function new_stats()
+    return {
+        since = socket.gettime(),
+        requests = 0,
+        errors = 0,
+        timeouts = 0,
+        rpc_errors = 0,
+        transport_errors = 0,
+        latency_total_ms = 0,
+        latency_max_ms = 0,
+        bytes_out = 0,
+        bytes_in = 0,
+        reconnects = 0,
+        down = false,  -- last request failed at the transport level
+        methods = {}
+    }
+end
+
+-- opts: { allow_unsafe = false, transport = "http", proxy = nil, tls = nil, timeout_ms = nil,
+--         runtime_check_interval = 60, on_runtime_upgrade = nil, types = nil, ss58_format = nil,
+--         raw_methods = nil }
+--   allow_unsafe: permit node-local RPCs such as author_rotateKeys and offchain local storage
+--   transport: "http" (JSON-RPC over HTTP POST, the only transport available);
+--     ws:// and wss:// URLs are rewritten to their HTTP equivalents
+--   proxy: "http://host:port" HTTP proxy (SOCKS is not supported by LuaSocket)
+--   tls: { cafile, capath, verify } for https:// (handled by LuaSec)
+--     cafile/capath: custom CA certificates; enables peer verification
+--     verify = false: skip certificate checks (self-signed dev nodes only)
+--   timeout_ms: per-call limit for connecting and reading a response (LuaSocket default: 60 s)
+--   runtime_check_interval / on_runtime_upgrade: see RPC:check_runtime_upgrade
+--   types: custom type bundle (table or JSON text, see sublua.types), applied to every
+--     metadata fetch so it survives runtime upgrades
+--   ss58_format: address format of results (see RPC:set_ss58_format)
+--   raw_methods: methods RPC:rpc_raw may call, as names or "prefix*" groups
+--     (e.g. { "babe_*", "system_health" }); nil allows any method
+function RPC.new(url, opts)
+    opts = opts or {}
+    local transport = opts.transport or "http"
+    if transport ~= "http" then
+        error("Unsupported RPC transport '" .. tostring(transport) .. "': only \"http\" is available")
+    end
+    if opts.proxy ~= nil then
+        if type(opts.proxy) ~= "string" or not opts.proxy:match("^%a[%w+.-]*://") then
+            error("Invalid proxy '" .. tostring(opts.proxy) .. "': expected a URL such as http://host:port")
+        end
+        if opts.proxy:match("^socks") then
+            error("SOCKS proxies are not supported by the LuaSocket HTTP transport; use an HTTP proxy")
+        end
+    end
     if url:match("^wss://") then
         url = url:gsub("^wss://", "https://")
     elseif url:match("^ws://") then
         url = url:gsub("^ws://", "http://")
     end
     
-    return setmetatable({
+    local client = setmetatable({
         url = url,
         id = 1,
         chain_properties = nil,
-        metadata = nil  -- Cached metadata
+        metadata = nil,  -- Cached metadata
+        allow_unsafe = opts.allow_unsafe or false,
+        transport = transport,
+        proxy = opts.proxy,
+        tls = opts.tls,
+        timeout_ms = opts.timeout_ms,
+        deadline = nil,  -- Absolute socket.gettime() limit set by with_deadline
+        runtime_version = nil,  -- Version the cached metadata belongs to
+        runtime_check_interval = opts.runtime_check_interval == nil and 60 or opts.runtime_check_interval,
+        on_runtime_upgrade = opts.on_runtime_upgrade,
+        types = opts.types,
+        ss58_format = nil,  -- Address format of results, set with set_ss58_format
+        signer = nil,  -- Default signer of transactions, set with set_signer
+        stats = new_stats(),  -- Counters behind RPC:metrics
+        raw_methods = opts.raw_methods
     }, RPC)
+    if opts.ss58_format ~= nil then client:set_ss58_format(opts.ss58_format) end
+    return Introspect.track("clients", client)
 end
 
-function RPC:request(method, params)
-    local request_data = {
-        jsonrpc = "2.0",
-        method = method,
-        params = params or {},
-        id = self.id
-    }
+-- Raw text of a member of the top-level JSON object in text (nil when absent)
+local function json_member(text, key)
+    local function string_end(i)  -- i at the opening quote; returns the closing quote
+        repeat
+            i = text:find('["\\]', i + 1)
+            if not i then error("Unterminated string in JSON response") end
+            if text:sub(i, i) == "\\" then i = i + 1 else return i end
+        until false
+    end
+    local function value_end(i)
+        local c = text:sub(i, i)
+        if c == '"' then return string_end(i) end
+        if c ~= "{" and c ~= "[" then return (text:find("[%s,}%]]", i) or #text + 1) - 1 end
+        local depth = 0
+        repeat
+            i = text:find('["{}%[%]]', i)
+            if not i then error("Unterminated value in JSON response") end
+            c = text:sub(i, i)
+            if c == '"' then
+                i = string_end(i)
+            elseif c == "{" or c == "[" then
+                depth = depth + 1
+            else
+                depth = depth - 1
+                if depth == 0 then return i end
+            end
+            i = i + 1
+        until false
+    end
+    local pos = text:find("%S")
+    if not pos or text:sub(pos, pos) ~= "{" then return nil end
+    pos = text:find("%S", pos + 1)
+    while pos and text:sub(pos, pos) == '"' do
+        local name_end = string_end(pos)
+        local name = text:sub(pos + 1, name_end - 1)
+        local start = text:find("%S", (text:find(":", name_end + 1, true) or #text) + 1)
+        if not start then return nil end
+        local stop = value_end(start)
+        if name == key then return text:sub(start, stop) end
+        pos = text:find("%S", stop + 1)
+        if not pos or text:sub(pos, pos) ~= "," then return nil end
+        pos = text:find("%S", pos + 1)
+    end
+    return nil
+end
+
+-- JSON text of one request object, taking the next id
+-- params: Lua table, or JSON text sent as is
+local function request_body(self, method, params)
+    local body
+    if type(params) == "string" then
+        body = '{"jsonrpc":"2.0","method":' .. json.encode(method) .. ',"params":' .. params
+            .. ',"id":' .. self.id .. '}'
+    else
+        body = json.encode({
+            jsonrpc = "2.0",
+            method = method,
+            params = params or {},
+            id = self.id
+        })
+    end
     self.id = self.id + 1
-    
-    local body = json.encode(request_data)
+    return body
+end
+
+-- Message of a JSON-RPC error object
+local function rpc_error(err)
+    -- Pool rejections carry the reason in data, e.g. "Transaction is outdated"
+    local detail = type(err.data) == "string" and (": " .. err.data) or ""
+    return "RPC error: " .. tostring(err.message) .. detail
+end
+
+-- One HTTP POST of body; counts bytes into stats and returns the response text
+-- label: what is being sent, for timeout messages
+local function post(self, label, body)
     local response_body = {}
+    self.stats.bytes_out = self.stats.bytes_out + #body
     
-    local success, status_code, headers = http.request{
+    local req = {
         url = self.url,
         method = "POST",
         headers = {
@@ -54,18 +206,228 @@ function RPC:request(method, params)
         },
         source = ltn12.source.string(body),
         sink = ltn12.sink.table(response_body),
-        redirect = true
+        redirect = true,
+        proxy = self.proxy
     }
+    if self.tls and self.url:match("^https://") then
+        req.cafile = self.tls.cafile
+        req.capath = self.tls.capath
+        if self.tls.verify == false then
+            req.verify = "none"
+        elseif self.tls.cafile or self.tls.capath then
+            req.verify = "peer"
+        end
+    end
     
-    if not success then error("HTTP request failed: " .. tostring(status_code)) end
+    local timeout = self.timeout_ms and self.timeout_ms / 1000
+    if self.deadline then
+        local remaining = self.deadline - socket.gettime()
+        if remaining <= 0 then
+            error("Timeout: deadline passed before " .. label .. " was sent")
+        end
+        timeout = math.min(timeout or remaining, remaining)
+    end
+    local saved_timeout = http.TIMEOUT
+    if timeout then http.TIMEOUT = timeout end
+    local success, status_code, headers = http.request(req)
+    http.TIMEOUT = saved_timeout
+    
+    if not success then
+        local via = self.proxy and (" via proxy " .. self.proxy) or ""
+        if status_code == "timeout" then
+            error("Timeout: " .. label .. " to " .. self.url .. via .. " got no response within "
+                .. math.floor(timeout and timeout * 1000 or (saved_timeout or 60) * 1000) .. " ms")
+        end
+        error("HTTP request to " .. self.url .. via .. " failed: " .. tostring(status_code))
+    end
     if status_code ~= 200 then error("HTTP error code " .. status_code) end
     
-    local response = json.decode(table.concat(response_body))
-    if response.error then error("RPC error: " .. response.error.message) end
+    local text = table.concat(response_body)
+    self.stats.bytes_in = self.stats.bytes_in + #text
+    return text
+end
+
+-- One JSON-RPC call; raw: return the result as JSON text
+local function send(self, method, params, raw)
+    local text = post(self, method, request_body(self, method, params))
+    local response = json.decode(text)
+    if response.error then error(rpc_error(response.error)) end
     
+    if raw then return json_member(text, "result") or "null" end
     return response.result
 end
 
+-- exchange(self, ...) with the subscription check and the counters behind RPC:metrics
+-- methods: the JSON-RPC methods it carries (one exchange may carry a batch)
+local function counted(self, methods, exchange, ...)
+    for _, method in ipairs(methods) do
+        if RPC.is_subscription(method) then
+            error(method .. " is a subscription API and needs a WebSocket connection; "
+                .. "this client uses the HTTP transport, poll instead (e.g. chain_getFinalizedHead)")
+        end
+    end
+
+    local stats = self.stats
+    stats.requests = stats.requests + 1
+    for _, method in ipairs(methods) do
+        stats.methods[method] = (stats.methods[method] or 0) + 1
+    end
+    local started = socket.gettime()
+    local ok, result, more = pcall(exchange, self, ...)
+    local elapsed = (socket.gettime() - started) * 1000
+    stats.latency_total_ms = stats.latency_total_ms + elapsed
+    if elapsed > stats.latency_max_ms then stats.latency_max_ms = elapsed end
+    if not ok then
+        stats.errors = stats.errors + 1
+        local message = tostring(result)
+        if RPC.is_timeout(message) then
+            stats.timeouts = stats.timeouts + 1
+        elseif message:find("RPC error:", 1, true) then
+            stats.rpc_errors = stats.rpc_errors + 1
+        else
+            stats.transport_errors = stats.transport_errors + 1
+            stats.down = true
+        end
+        error(result, 0)
+    end
+    if stats.down then
+        stats.reconnects = stats.reconnects + 1
+        stats.down = false
+    end
+    return result, more
+end
+
+function RPC:request(method, params)
+    return (counted(self, { method }, send, method, params))
+end
+
+local function raw_allowed(list, method)
+    for _, entry in ipairs(list) do
+        if entry == method or (entry:sub(-1) == "*" and method:sub(1, #entry - 1) == entry:sub(1, -2)) then
+            return true
+        end
+    end
+    return false
+end
+
+-- Call a method the client has no wrapper for (e.g. babe_epochAuthorship or a
+-- node-specific RPC)
+-- params_json: JSON array or object text, sent unchanged (default "[]")
+-- Returns the node's result as JSON text, byte for byte (large numbers keep their digits)
+function RPC:rpc_raw(method, params_json)
+    if type(method) ~= "string" or method == "" then error("rpc_raw needs a method name") end
+    if self.raw_methods and not raw_allowed(self.raw_methods, method) then
+        error(method .. " is not in this client's raw_methods")
+    end
+    params_json = params_json or "[]"
+    local ok, params = pcall(json.decode, type(params_json) == "string" and params_json or "")
+    if not ok or type(params) ~= "table" then
+        error("params_json must be a JSON array or object, got " .. tostring(params_json))
+    end
+    return (counted(self, { method }, send, method, params_json, true))
+end
+
+local function send_batch(self, calls)
+    local first = self.id
+    local bodies = {}
+    for i, c in ipairs(calls) do bodies[i] = request_body(self, c[1], c[2]) end
+    local response = json.decode(post(self, "batch of " .. #calls, "[" .. table.concat(bodies, ",") .. "]"))
+    if type(response) ~= "table" then error("Invalid batch response") end
+    if response.error then error(rpc_error(response.error)) end
+    local results, errors = { n = #calls }, {}
+    local answered = {}
+    for _, entry in ipairs(response) do
+        local i = type(entry.id) == "number" and entry.id - first + 1
+        if i and i >= 1 and i <= #calls then
+            answered[i] = true
+            if entry.error then errors[i] = rpc_error(entry.error) else results[i] = entry.result end
+        end
+    end
+    for i = 1, #calls do
+        if not answered[i] then errors[i] = "RPC error: no response to " .. calls[i][1] .. " in the batch" end
+    end
+    return results, errors
+end
+
+-- Several calls in one HTTP exchange (a JSON-RPC batch), e.g. a dashboard's values per refresh
+-- calls: list of { method, params } (params: Lua table or JSON text), or JSON text of an
+--   array of { "method": ..., "params": ... } objects
+-- Returns results, errors: both by position in calls; results[i] is the decoded result
+--   (results.n = #calls), errors[i] the "RPC error: ..." message of a call that failed
+-- Transport failures and a rejected batch raise as RPC:request does
+function RPC:rpc_batch(calls)
+    if type(calls) == "string" then
+        local ok, decoded = pcall(json.decode, calls)
+        if not ok or type(decoded) ~= "table" then error("Batch JSON must be an array of requests, got " .. calls) end
+        calls = {}
+        for i, c in ipairs(decoded) do calls[i] = { c.method, c.params } end
+    end
+    local methods = {}
+    for i, c in ipairs(calls) do
+        if type(c[1]) ~= "string" then error("Batch call " .. i .. " has no method name") end
+        methods[i] = c[1]
+    end
+    if #calls == 0 then return { n = 0 }, {} end
+    return counted(self, methods, send_batch, calls)
+end
+
+-- Health counters of this client since it was created (or reset_metrics), for export
+-- to a monitoring stack
+-- Returns { since, uptime_s, requests, errors, timeouts, rpc_errors, transport_errors,
+--   avg_latency_ms, max_latency_ms, bytes_out, bytes_in, reconnects, connected,
+--   subscription_lag_ms, methods = { [method] = count } }
+--   errors: every failed request; timeouts / rpc_errors (node replied with an error) /
+--     transport_errors (no usable HTTP response) split them
+--   reconnects: times a request went through again after transport errors
+--   subscription_lag_ms: always nil, as the HTTP transport has no subscriptions
+function RPC:metrics()
+    local stats = self.stats
+    local methods = {}
+    for k, v in pairs(stats.methods) do methods[k] = v end
+    return {
+        since = stats.since,
+        uptime_s = socket.gettime() - stats.since,
+        requests = stats.requests,
+        errors = stats.errors,
+        timeouts = stats.timeouts,
+        rpc_errors = stats.rpc_errors,
+        transport_errors = stats.transport_errors,
+        avg_latency_ms = stats.requests > 0 and stats.latency_total_ms / stats.requests or 0,
+        max_latency_ms = stats.latency_max_ms,
+        bytes_out = stats.bytes_out,
+        bytes_in = stats.bytes_in,
+        reconnects = stats.reconnects,
+        connected = not stats.down,
+        subscription_lag_ms = nil,
+        methods = methods
+    }
+end
+
+function RPC:reset_metrics()
+    self.stats = new_stats()
+end
+
+local unpack = table.unpack or unpack
+local function pack(...) return { n = select("#", ...), ... } end
+
+-- Run fn(self) with every request sharing one deadline, timeout_ms from now
+-- Nested calls keep the earlier deadline; returns fn's results
+function RPC:with_deadline(timeout_ms, fn)
+    local outer = self.deadline
+    local deadline = socket.gettime() + timeout_ms / 1000
+    if outer and outer < deadline then deadline = outer end
+    self.deadline = deadline
+    local results = pack(pcall(fn, self))
+    self.deadline = outer
+    if not results[1] then error(results[2], 0) end
+    return unpack(results, 2, results.n)
+end
+
+-- Seconds left before the current deadline, or nil without one
+function RPC:time_left()
+    return self.deadline and math.max(self.deadline - socket.gettime(), 0)
+end
+
 -- Chain Properties
 function RPC:get_chain_properties()
     if self.chain_properties then return self.chain_properties end
@@ -77,17 +439,68 @@ function RPC:get_chain_properties()
         self.chain_properties = {
             decimals = decimals,
             symbol = symbol,
-            divisor = 10 ^ decimals
+            divisor = 10 ^ decimals,
+            ss58_prefix = tonumber(props.ss58Format) or 42
         }
     else
-        self.chain_properties = {decimals = 12, symbol = "UNIT", divisor = 10^12}
+        self.chain_properties = {decimals = 12, symbol = "UNIT", divisor = 10^12, ss58_prefix = 42}
     end
     return self.chain_properties
 end
 
+-- Render addresses in results in one SS58 format, whatever the chain's own
+-- (decoded events, multisig and address book output; decoding stays hex underneath)
+-- prefix: number or network name (see Address.PREFIXES); nil goes back to the chain's
+function RPC:set_ss58_format(prefix)
+    if prefix ~= nil then
+        prefix = Address.PREFIXES[prefix] or prefix
+        Address.encode(string.rep("\0", 32), prefix)  -- rejects invalid prefixes
+    end
+    self.ss58_format = prefix
+end
+
+-- SS58 prefix results are rendered with: the one set, else the chain's
+function RPC:get_ss58_format()
+    return self.ss58_format or self:get_chain_properties().ss58_prefix
+end
+
+-- Address in this client's format; who: SS58 address (any network), "0x" hex or raw key
+function RPC:format_address(who)
+    return Address.convert(who, self:get_ss58_format())
+end
+
+-- Default signer for transactions built with this client: functions taking a signer
+-- use it when theirs is nil, so a secret is unlocked once instead of on every call
+-- signer: keyring pair, or an account name together with an open sublua.keystore;
+--   nil clears it
+-- Returns the keypair
+function RPC:set_signer(signer, keystore)
+    if type(signer) == "string" then
+        if not keystore then error("Account name '" .. signer .. "' needs a keystore") end
+        signer = keystore:keypair(signer)
+    elseif signer ~= nil and (type(signer) ~= "table" or not signer.pubkey) then
+        error("Signer must be a keypair or a keystore account name")
+    end
+    self.signer = signer
+    return signer
+end
+
+function RPC:get_signer()
+    return self.signer
+end
+
+-- Safety settings for transactions signed with this client (see sublua.safety):
+-- the largest native transfer in planck, the destinations transfers may go to, and
+-- two-step confirmation of every extrinsic; nil / false turns each off
+function RPC:set_max_transfer(amount) require("sublua.safety").set_max_transfer(self, amount) end
+function RPC:set_allowed_destinations(addresses) require("sublua.safety").set_allowed_destinations(self, addresses) end
+function RPC:set_confirmation(enabled) require("sublua.safety").set_confirmation(self, enabled) end
+
 -- Basic RPC wrappers
 function RPC:chain_getBlockHash(num) return self:request("chain_getBlockHash", num and {num} or {}) end
 function RPC:chain_getFinalizedHead() return self:request("chain_getFinalizedHead") end
+function RPC:chain_getBlock(hash) return self:request("chain_getBlock", hash and {hash} or {}) end
+function RPC:chain_getHeader(hash) return self:request("chain_getHeader", hash and {hash} or {}) end
 function RPC:state_getRuntimeVersion(at) return self:request("state_getRuntimeVersion", at and {at} or {}) end
 function RPC:state_getMetadata(at) return self:request("state_getMetadata", at and {at} or {}) end
 function RPC:payment_queryInfo(extrinsic, at) 
@@ -98,7 +511,61 @@ function RPC:state_getStorage(key, at)
     local p = {key}; if at then table.insert(p, at) end
     return self:request("state_getStorage", p)
 end
+function RPC:state_queryStorageAt(keys, at)
+    local p = {keys}; if at then table.insert(p, at) end
+    return self:request("state_queryStorageAt", p)
+end
+function RPC:state_getKeysPaged(prefix, count, start_key, at)
+    return self:request("state_getKeysPaged", {prefix, count or 1000, start_key or json.null, at})
+end
+function RPC:state_getReadProof(keys, at)
+    local p = {keys}; if at then table.insert(p, at) end
+    return self:request("state_getReadProof", p)
+end
+function RPC:state_call(method, data, at)
+    local p = {method, data}; if at then table.insert(p, at) end
+    return self:request("state_call", p)
+end
+function RPC:grandpa_proveFinality(block_number) return self:request("grandpa_proveFinality", {block_number}) end
+function RPC:beefy_getFinalizedHead() return self:request("beefy_getFinalizedHead", {}) end
+function RPC:mmr_root(at) return self:request("mmr_root", at and {at} or {}) end
+function RPC:mmr_generateProof(block_numbers, best_known_block_number, at)
+    return self:request("mmr_generateProof", {block_numbers, best_known_block_number or json.null, at or json.null})
+end
+function RPC:system_accountNextIndex(address) return self:request("system_accountNextIndex", {address}) end
 function RPC:author_submitExtrinsic(hex) return self:request("author_submitExtrinsic", {hex}) end
+function RPC:author_pendingExtrinsics() return self:request("author_pendingExtrinsics", {}) end
+
+-- Session keys
+-- author_rotateKeys is an unsafe RPC: it only works against the validator's own
+-- node (--rpc-methods unsafe) and must be enabled explicitly on the client.
+function RPC:author_rotateKeys()
+    if not self.allow_unsafe then
+        error("author_rotateKeys is an unsafe RPC; create the client with { allow_unsafe = true }")
+    end
+    return self:request("author_rotateKeys")
+end
+function RPC:author_hasSessionKeys(keys_hex) return self:request("author_hasSessionKeys", {keys_hex}) end
+function RPC:author_hasKey(pubkey_hex, key_type) return self:request("author_hasKey", {pubkey_hex, key_type}) end
+
+-- Offchain storage and the statement store
+-- Local storage and statement_dump/statement_remove are unsafe RPCs, like author_rotateKeys;
+-- the statement store only answers on nodes started with --enable-statement-store
+local function unsafe(self, method, params)
+    if not self.allow_unsafe then
+        error(method .. " is an unsafe RPC; create the client with { allow_unsafe = true }")
+    end
+    return self:request(method, params)
+end
+function RPC:offchain_localStorageGet(kind, key) return unsafe(self, "offchain_localStorageGet", {kind, key}) end
+function RPC:offchain_localStorageSet(kind, key, value)
+    return unsafe(self, "offchain_localStorageSet", {kind, key, value})
+end
+function RPC:statement_submit(encoded) return self:request("statement_submit", {encoded}) end
+function RPC:statement_broadcasts(topics) return self:request("statement_broadcasts", {topics or {}}) end
+function RPC:statement_posted(topics, dest) return self:request("statement_posted", {topics or {}, dest}) end
+function RPC:statement_dump() return unsafe(self, "statement_dump", {}) end
+function RPC:statement_remove(hash) return unsafe(self, "statement_remove", {hash}) end
 
 -- Metadata
 function RPC:state_getMetadata(at) 
@@ -106,19 +573,53 @@ function RPC:state_getMetadata(at)
     return self:request("state_getMetadata", p)
 end
 
--- Get and parse metadata (cached)
+-- Get and parse metadata (cached; re-checked against the runtime version
+-- at most every runtime_check_interval seconds)
 function RPC:get_metadata()
     if self.metadata then
-        return self.metadata
+        local interval = self.runtime_check_interval
+        if not interval or os.time() - (self.runtime_checked_at or 0) < interval then
+            return self.metadata
+        end
+        self:check_runtime_upgrade()
+        if self.metadata then
+            return self.metadata
+        end
     end
     
+    if not self.runtime_version then
+        self.runtime_version = self:state_getRuntimeVersion()
+        self.runtime_checked_at = os.time()
+    end
     print("Fetching runtime metadata...")
     local metadata_hex = self:state_getMetadata()
     self.metadata = Metadata.parse(metadata_hex)
+    if self.types then Types.register(self.metadata, self.types) end
     print("Metadata parsed: " .. self.metadata.version .. " pallets found")
     return self.metadata
 end
 
+-- Runtime upgrades
+-- state_subscribeRuntimeVersion needs a WebSocket, so upgrades are found by polling:
+-- get_metadata calls this on its own, and callers that already fetched the version can pass it in
+-- Drops the cached metadata and calls on_runtime_upgrade(old, new, api) when the
+-- spec or transaction version changed; returns true in that case
+function RPC:check_runtime_upgrade(version)
+    version = version or self:state_getRuntimeVersion()
+    self.runtime_checked_at = os.time()
+    local old = self.runtime_version
+    self.runtime_version = version
+    if not old or (old.specVersion == version.specVersion
+        and old.transactionVersion == version.transactionVersion) then
+        return false
+    end
+    self.metadata = nil
+    if self.on_runtime_upgrade then
+        self.on_runtime_upgrade(old, version, self)
+    end
+    return true
+end
+
 -- Get call index for a pallet and call name
 function RPC:get_call_index(pallet_name, call_name)
     local meta = self:get_metadata()
@@ -0,0 +1,161 @@
+-- sublua/runtime.lua
+-- Runtime code: download the wasm a chain runs (the ":code" storage value) and propose
+-- an upgrade with System.set_code, wrapped for the chain's governance
+-- Modes: "sudo" signs Sudo.sudo_unchecked_weight(set_code) in one extrinsic; "democracy"
+-- notes the set_code call as a preimage and proposes it by hash (two extrinsics)
+-- Runtimes are commonly zstd-compressed (sp-maybe-compressed-blob); they are kept as is
+
+local crypto = require("polkadot_crypto")
+local Call = require("sublua.call")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Metadata = require("sublua.metadata")
+local ChainSpec = require("sublua.chainspec")
+local Preimage = require("sublua.preimage")
+
+local Runtime = {}
+
+Runtime.CODE_KEY = "0x3a636f6465"  -- ":code"
+
+-- zstd-compressed runtime blobs (sp-maybe-compressed-blob)
+local ZSTD_PREFIX = "\82\188\83\118\70\219\142\5"
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+local function is_code(data)
+    return data:sub(1, 4) == "\0asm" or data:sub(1, 8) == ZSTD_PREFIX
+end
+
+-- Runtime code from wasm bytes, 0x hex, or a file path
+local function load_code(wasm)
+    assert(type(wasm) == "string" and wasm ~= "", "wasm must be code bytes, 0x hex or a file path")
+    if is_code(wasm) then return wasm end
+    if wasm:match("^0x%x+$") then
+        local code = from_hex(wasm)
+        if is_code(code) then return code end
+    end
+    local f, err = io.open(wasm, "rb")
+    if not f then error("Cannot read runtime: " .. tostring(err)) end
+    local code = f:read("*a")
+    f:close()
+    if not is_code(code) then error("Not a wasm runtime: " .. wasm) end
+    return code
+end
+
+-- ============================================================
+-- Runtime Code
+-- ============================================================
+
+-- Describe runtime code
+-- Returns { size, hash (blake2_256, as System.authorize_upgrade takes it), compressed,
+--   state_version (nil for compressed code) }
+function Runtime.inspect(code)
+    code = load_code(code)
+    return {
+        size = #code,
+        hash = "0x" .. to_hex(crypto.blake2b(code, 32)),
+        compressed = code:sub(1, 8) == ZSTD_PREFIX,
+        state_version = ChainSpec.runtime_state_version(code)
+    }
+end
+
+-- Download the runtime at a block
+-- block_hash: block to read at (default: best block)
+-- out_path: optional file to write the code to
+-- Returns the code bytes and Runtime.inspect's info (plus path when written)
+function Runtime.get_wasm(api, block_hash, out_path)
+    local hex = api:state_getStorage(Runtime.CODE_KEY, block_hash)
+    if type(hex) ~= "string" or hex == "0x" then error("No runtime code at " .. tostring(block_hash or "best block")) end
+    local code = from_hex(hex)
+    local info = Runtime.inspect(code)
+    if out_path then
+        local f, err = io.open(out_path, "wb")
+        if not f then error("Cannot write runtime: " .. tostring(err)) end
+        f:write(code)
+        f:close()
+        info.path = out_path
+    end
+    return code, info
+end
+
+-- ============================================================
+-- Upgrades
+-- ============================================================
+
+-- Build System.set_code call bytes
+-- wasm: code bytes, 0x hex, or a file path
+function Runtime.encode_set_code(meta_source, wasm)
+    return Call.encode_call(meta_source, "System", "set_code", { code = "0x" .. to_hex(load_code(wasm)) })
+end
+
+-- Wrap a call in Sudo.sudo_unchecked_weight with a zero weight (set_code fills a block,
+-- so the checked Sudo.sudo would refuse it)
+function Runtime.encode_sudo(meta_source, call_bytes)
+    local meta = Metadata.from_source(meta_source)
+    local sudo = assert(meta.pallets["Sudo"], "Sudo pallet not found in metadata")
+    local call_index = sudo.calls and sudo.calls["sudo_unchecked_weight"]
+    assert(call_index, "sudo_unchecked_weight not found in Sudo")
+    return Call.encode_index(sudo.index, call_index) .. call_bytes .. Scale.encode_compact(0) .. Scale.encode_compact(0)
+end
+
+-- Propose a runtime upgrade
+-- mode: "sudo" or "democracy"
+-- wasm: code bytes, 0x hex, or a file path
+-- opts: { value = nil, ... }
+--   value: democracy deposit (default Democracy.MinimumDeposit)
+--   other opts go to Transaction.create_signed_from_api
+-- Returns a list of extrinsic hex to submit in order (one for sudo; preimage note then
+--   proposal for democracy, with consecutive nonces) and details
+--   { code_hash, call_hash, len, transactions = { details, ... } }
+function Runtime.propose_upgrade(api, signer, mode, wasm, opts)
+    opts = opts or {}
+    signer = require("sublua.transaction").signer_of(api, signer)
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.value = nil
+    local code = load_code(wasm)
+    local set_code = Runtime.encode_set_code(api, code)
+    local details = {
+        code_hash = Runtime.inspect(code).hash,
+        call_hash = Call.call_hash(set_code),
+        len = #set_code,
+        transactions = {}
+    }
+
+    if mode == "sudo" then
+        local signed, d = sign(api, signer, Runtime.encode_sudo(api, set_code), o)
+        details.transactions[1] = d
+        return { signed }, details
+    elseif mode == "democracy" then
+        local meta = api:get_metadata()
+        local value = opts.value
+        if value == nil then
+            local c = Metadata.get_constant(meta, "Democracy", "MinimumDeposit")
+            if not c then error("Democracy.MinimumDeposit not found; pass opts.value") end
+            value = Codec.decode(meta, c.type_id, c.value)
+        end
+        o.nonce = o.nonce or tonumber(api:system_accountNextIndex(signer.address))
+        local note, d1 = Preimage.note(api, signer, set_code, o)
+        o.nonce = o.nonce + 1
+        local propose, d2 = sign(api, signer, Call.encode_call(meta, "Democracy", "propose", {
+            proposal = Preimage.lookup_of(set_code), value = value
+        }), o)
+        details.transactions[1], details.transactions[2] = d1, d2
+        return { note, propose }, details
+    end
+    error("Unknown upgrade mode: " .. tostring(mode) .. " (expected \"sudo\" or \"democracy\")")
+end
+
+return Runtime
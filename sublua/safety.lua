@@ -0,0 +1,180 @@
+-- sublua/safety.lua
+-- Safety net for scripts moving real funds, set per client: a cap on native transfers,
+-- an allow-list of destinations, and a confirmation mode where signing first fails with a
+-- digest of the decoded intent and only goes ahead once that digest is passed back
+-- Every extrinsic signed through the client is checked (Transaction.create_signed_from_api,
+-- so submit and the pallet helpers too), calls nested in batches, proxies and multisigs
+-- included; signing without a client (create_signed_raw) is not
+
+local crypto = require("polkadot_crypto")
+local json = require("cjson")
+local Bytes = require("sublua.bytes")
+local Codec = require("sublua.codec")
+local Validate = require("sublua.validate")
+
+local Safety = {}
+
+-- Argument names holding where a transfer goes and how much it moves
+local DEST_FIELDS = { "dest", "target", "destination", "beneficiary", "to" }
+local AMOUNT_FIELDS = { "value", "amount" }
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function settings(api)
+    api.safety = api.safety or {}
+    return api.safety
+end
+
+-- ============================================================
+-- Settings
+-- ============================================================
+
+-- Largest native (Balances) transfer the client signs, in planck (number or decimal string)
+-- nil removes the cap; transfer_all is refused while a cap is set, as it has no amount
+function Safety.set_max_transfer(api, amount)
+    if type(amount) == "number" and amount % 1 == 0 then amount = string.format("%.0f", amount) end
+    if amount ~= nil and (type(amount) ~= "string" or not amount:match("^%d+$")) then
+        Validate.fail("amount", "planck as an integer or decimal string", amount)
+    end
+    settings(api).max_transfer = amount
+end
+
+-- Destinations transfers (any pallet's transfer calls) may go to: SS58 addresses or 0x
+-- public keys; nil allows any. A transfer whose destination is not an account (e.g. an XCM
+-- location) is refused while a list is set
+function Safety.set_allowed_destinations(api, addresses)
+    if addresses == nil then
+        settings(api).allowed = nil
+        return
+    end
+    Validate.list(addresses, "addresses", { what = "an array of addresses" })
+    local allowed = {}
+    for i, who in ipairs(addresses) do allowed[Validate.account(who, "addresses." .. i)] = true end
+    settings(api).allowed = allowed
+end
+
+-- Two-step mode: signing fails with "Confirmation required: <digest> ..." until it is called
+-- again with opts.confirm = digest (see Safety.confirmation)
+function Safety.set_confirmation(api, enabled)
+    settings(api).confirm = enabled and true or nil
+end
+
+-- ============================================================
+-- Intent
+-- ============================================================
+
+local function account_of(value)
+    if type(value) == "table" and (value.variant == "Id" or value.variant == "Address32") then value = value.value end
+    if type(value) == "string" and value:match("^0x%x+$") and #value == 66 then return value:lower() end
+    return nil
+end
+
+local function first_field(args, names)
+    for _, name in ipairs(names) do
+        if args[name] ~= nil then return name, args[name] end
+    end
+    return nil
+end
+
+-- Calls in a decoded call tree: the call itself, then any RuntimeCall in its arguments
+local function walk(meta, pallet, name, args, out)
+    table.insert(out, { pallet = pallet, call = name, args = args })
+    local function visit(value)
+        if type(value) ~= "table" then return end
+        local p = type(value.variant) == "string" and meta.pallets[value.variant]
+        if p and p.calls_type_id and type(value.value) == "table" and type(value.value.variant) == "string" then
+            return walk(meta, value.variant, value.value.variant, value.value.value or {}, out)
+        end
+        for _, item in pairs(value) do visit(item) end
+    end
+    for _, value in pairs(args) do visit(value) end
+    return out
+end
+
+-- What signing call_bytes with signer does, decoded
+-- Returns { digest, text, transfers = { { pallet, call, dest, amount }, ... } }
+--   digest: "0x" blake2_256 of genesis hash, signer and call, to pass back as opts.confirm
+--   text: "<signer> signs <Pallet.call> <arguments as JSON>"
+--   transfers: calls moving funds (named *transfer*), nested ones included; dest is the
+--     "0x" account, false when it is not an account and nil without one; amount is a
+--     decimal string or nil
+function Safety.intent(api, signer, call_bytes)
+    local meta = api:get_metadata()
+    local Transaction = require("sublua.transaction")
+    local call = Transaction.decode_call(meta, call_bytes, 1)
+    local pallet = meta.pallets[call.pallet]
+    local args_json = json.encode(Codec.to_json(meta, pallet.calls_type_id, { variant = call.name, value = call.args }))
+    local transfers = {}
+    for _, c in ipairs(walk(meta, call.pallet, call.name, call.args, {})) do
+        if c.call:find("transfer", 1, true) and type(c.args) == "table" then
+            local dest_field, dest = first_field(c.args, DEST_FIELDS)
+            local _, amount = first_field(c.args, AMOUNT_FIELDS)
+            table.insert(transfers, {
+                pallet = c.pallet, call = c.call,
+                dest = dest_field and (account_of(dest) or false) or nil,
+                amount = amount ~= nil and tostring(amount) or nil
+            })
+        end
+    end
+    local genesis = from_hex(api:chain_getBlockHash(0))
+    return {
+        digest = "0x" .. to_hex(crypto.blake2b(genesis .. signer.pubkey .. call_bytes, 32)),
+        text = signer.address .. " signs " .. call.pallet .. "." .. call.name .. " " .. args_json,
+        transfers = transfers
+    }
+end
+
+-- ============================================================
+-- Checks
+-- ============================================================
+
+local function name_of(t)
+    return t.pallet .. "." .. t.call
+end
+
+-- Refuse to sign what the client's settings do not allow
+-- confirm: digest from an earlier "Confirmation required" error (opts.confirm)
+-- Raises "Safety: ..." or "Confirmation required: <digest> ..."; returns the intent otherwise
+-- (nil when the client has no settings)
+function Safety.check(api, signer, call_bytes, confirm)
+    local s = api.safety
+    if not s or (s.max_transfer == nil and s.allowed == nil and not s.confirm) then return nil end
+    local intent = Safety.intent(api, signer, call_bytes)
+    for _, t in ipairs(intent.transfers) do
+        if s.max_transfer and t.pallet == "Balances" then
+            if t.amount == nil or not t.amount:match("^%d+$") then
+                error("Safety: " .. name_of(t) .. " has no amount to check against the transfer limit of "
+                    .. s.max_transfer, 0)
+            end
+            if Bytes.compare(Bytes.decimal_to_bytes(t.amount), Bytes.decimal_to_bytes(s.max_transfer)) > 0 then
+                error("Safety: " .. name_of(t) .. " of " .. t.amount .. " exceeds the transfer limit of "
+                    .. s.max_transfer, 0)
+            end
+        end
+        if s.allowed and t.dest ~= nil and not s.allowed[t.dest] then
+            local who = t.dest and require("sublua.address").encode(from_hex(t.dest), api.ss58_format or 42)
+            error("Safety: " .. name_of(t) .. " to " .. (who or "a destination that is not an account")
+                .. " is not in the allowed destinations", 0)
+        end
+    end
+    if s.confirm and confirm ~= intent.digest then
+        error("Confirmation required: " .. intent.digest .. " " .. intent.text, 0)
+    end
+    return intent
+end
+
+-- Digest and intent text of a "Confirmation required" error (e.g. report.error of
+-- Transaction.submit), or nil for other errors
+function Safety.confirmation(err)
+    local digest, text = tostring(err):match("Confirmation required: (0x%x+) (.*)$")
+    return digest, text
+end
+
+return Safety
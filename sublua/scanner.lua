@@ -0,0 +1,212 @@
+-- sublua/scanner.lua
+-- Historical block range scanner
+-- Walks a range of blocks, decodes extrinsics and events, and hands only the matching
+-- ones to a callback. Progress is reported through a cursor that can be saved and passed
+-- back in to resume an interrupted scan where it stopped
+-- Blocks are decoded with the api's current metadata, so a range that spans a runtime
+-- upgrade should be split at the upgrade block
+
+local Cancel = require("sublua.cancel")
+local Events = require("sublua.events")
+local Transaction = require("sublua.transaction")
+
+local Scanner = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- Normalise an account (SS58 address, 0x public key or 32 raw bytes) to lowercase 0x hex
+local function account_hex(account)
+    if account:sub(1, 2) == "0x" then
+        return account:lower()
+    end
+    if #account == 32 then
+        return "0x" .. to_hex(account)
+    end
+    local pubkey = require("sublua.address").decode(account)
+    return "0x" .. to_hex(pubkey)
+end
+
+-- True if a decoded value contains the account anywhere (AccountId32 decodes to 0x hex,
+-- or to SS58 in events of a client with an ss58_format)
+local function mentions(value, account)
+    if type(value) == "string" then
+        if value:lower() == account then return true end
+        if #value < 46 or #value > 50 or value:sub(1, 2) == "0x" then return false end
+        local info = require("sublua.address").inspect(value)
+        return info.valid and info.format == "ss58" and info.pubkey:lower() == account
+    elseif type(value) == "table" then
+        for _, v in pairs(value) do
+            if mentions(v, account) then return true end
+        end
+    end
+    return false
+end
+
+-- ============================================================
+-- Filters
+-- ============================================================
+
+-- Normalise a scan filter
+-- filter: a table or the same table as a JSON string: {
+--   events = { { pallet, name }, ... } | preset name (see Events.PRESETS),
+--   calls = { { pallet, name }, ... },   -- name optional = any call of the pallet
+--   address = SS58 address or 0x public key -- only items that mention this account
+-- }
+-- A filter with only an address matches every event and every signed extrinsic touching it
+-- Returns the normalised filter
+function Scanner.compile(filter)
+    if type(filter) == "string" then
+        filter = require("cjson").decode(filter)
+    end
+    filter = filter or {}
+    local compiled = { events = filter.events, calls = filter.calls }
+    if type(compiled.events) == "string" then
+        compiled.events = assert(Events.PRESETS[compiled.events], "Unknown filter preset: " .. compiled.events)
+    end
+    if filter.address then
+        compiled.account = account_hex(filter.address)
+    end
+    if not compiled.events and not compiled.calls then
+        assert(compiled.account, "Scan filter needs events, calls or an address")
+        compiled.events, compiled.calls = true, true
+    end
+    return compiled
+end
+
+local function call_matches(calls, call)
+    if calls == true then return true end
+    for _, f in ipairs(calls) do
+        if f.pallet == call.pallet and (f.name == nil or f.name == call.name) then
+            return true
+        end
+    end
+    return false
+end
+
+-- Check a decoded event (see Events.decode) against a compiled filter
+function Scanner.match_event(filter, event)
+    if not filter.events then return false end
+    return (filter.events == true or Events.matches(filter.events, event))
+        and (not filter.account or mentions(event.fields, filter.account))
+end
+
+-- Matching items of one block, in extrinsic order followed by events in emission order
+-- filter: a compiled filter (see Scanner.compile); hash: the block hash if already known
+-- Returns a list of { kind = "extrinsic" | "event", block_number, block_hash, extrinsic_index,
+--   event_index (events only), extrinsic (Transaction.decode result) | event (Events.decode entry) }
+function Scanner.scan_block(api, number, filter, hash)
+    hash = hash or api:chain_getBlockHash(number)
+    local items = {}
+
+    if filter.calls then
+        local block = api:chain_getBlock(hash).block
+        local meta = api:get_metadata()
+        for i, ext_hex in ipairs(block.extrinsics) do
+            local ok, ext = pcall(Transaction.decode, meta, ext_hex)
+            if ok and call_matches(filter.calls, ext.call)
+                and (not filter.account or (ext.signer and mentions(ext.signer, filter.account))
+                    or mentions(ext.call.args, filter.account)) then
+                table.insert(items, {
+                    kind = "extrinsic",
+                    block_number = number,
+                    block_hash = hash,
+                    extrinsic_index = i - 1,
+                    extrinsic = ext
+                })
+            end
+        end
+    end
+
+    if filter.events then
+        for _, event in ipairs(Events.fetch(api, hash)) do
+            if Scanner.match_event(filter, event) then
+                table.insert(items, {
+                    kind = "event",
+                    block_number = number,
+                    block_hash = hash,
+                    extrinsic_index = event.extrinsic_index,
+                    event_index = event.index,
+                    event = event
+                })
+            end
+        end
+    end
+    return items
+end
+
+-- ============================================================
+-- Range Scan
+-- ============================================================
+
+-- Scan blocks from_block..to_block (inclusive)
+-- filter: see Scanner.compile
+-- callback(item): called for every match (see Scanner.scan_block); return false to stop
+-- opts: { cursor = nil, on_progress = nil, progress_every = 100, cancel = nil }
+--   cursor: a cursor returned by an earlier scan; scanning resumes at cursor.next_block,
+--     skipping the first next_item matches of that block that were already delivered
+--   on_progress(cursor): called every progress_every blocks and once at the end
+--   cancel: token (see sublua.cancel), checked before each block
+-- Returns the cursor { from_block, to_block, next_block, next_item, scanned, matched, done }
+--   done is false when the callback stopped the scan, it was cancelled or a block failed to
+--   load; in the latter cases the error is returned as a second value and next_block is the
+--   block not scanned yet
+function Scanner.scan(api, from_block, to_block, filter, callback, opts)
+    opts = opts or {}
+    assert(from_block <= to_block, "from_block must not exceed to_block")
+    local compiled = Scanner.compile(filter)
+    local every = opts.progress_every or 100
+
+    local cursor = opts.cursor
+    if cursor then
+        assert(cursor.from_block == from_block and cursor.to_block == to_block,
+            "Cursor belongs to a different block range")
+        cursor.done = false
+    else
+        cursor = { from_block = from_block, to_block = to_block, next_block = from_block,
+                   next_item = 0, scanned = 0, matched = 0, done = false }
+    end
+
+    local function progress()
+        if opts.on_progress then opts.on_progress(cursor) end
+    end
+
+    while cursor.next_block <= to_block do
+        local cancelled = Cancel.check(opts.cancel, "scan stopped before block " .. cursor.next_block)
+        if cancelled then
+            progress()
+            return cursor, cancelled
+        end
+        local ok, items = pcall(Scanner.scan_block, api, cursor.next_block, compiled)
+        if not ok then
+            progress()
+            return cursor, "Block " .. cursor.next_block .. ": " .. tostring(items)
+        end
+        local stopped = false
+        for i = cursor.next_item + 1, #items do
+            cursor.matched = cursor.matched + 1
+            cursor.next_item = i
+            if callback(items[i]) == false then
+                stopped = true
+                break
+            end
+        end
+        if not stopped or cursor.next_item == #items then
+            cursor.next_block = cursor.next_block + 1
+            cursor.next_item = 0
+            cursor.scanned = cursor.scanned + 1
+        end
+        if stopped then
+            progress()
+            return cursor
+        end
+        if cursor.scanned % every == 0 then progress() end
+    end
+
+    cursor.done = true
+    progress()
+    return cursor
+end
+
+return Scanner
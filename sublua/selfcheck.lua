@@ -0,0 +1,231 @@
+-- sublua/selfcheck.lua
+-- Encoding self-check: a canonical set of values, calls and signing payloads encoded by
+-- the low-level encoders (sublua.scale, Call.encode_transfer, the built-in signed
+-- extension handlers) and by the metadata-driven ones (sublua.codec, Call.encode_call,
+-- make_signing_payload), compared byte for byte with each other and with reference
+-- encodings as parity-scale-codec / sp-runtime (and so subxt) produce them
+-- Meant for CI and for hosts embedding the SDK: a regression in either path (an era
+-- packed wrong, a compact length off by one) shows up as a named discrepancy
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Codec = require("sublua.codec")
+local Call = require("sublua.call")
+local Metadata = require("sublua.metadata")
+
+local Selfcheck = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- ============================================================
+-- Reference Vectors
+-- ============================================================
+
+-- Compact<u128> encodings (single byte, two byte, four byte and big-integer modes)
+Selfcheck.COMPACTS = {
+    { value = 0, hex = "00" },
+    { value = 1, hex = "04" },
+    { value = 63, hex = "fc" },
+    { value = 64, hex = "0101" },
+    { value = 16383, hex = "fdff" },
+    { value = 16384, hex = "02000100" },
+    { value = 1073741823, hex = "feffffff" },
+    { value = 1073741824, hex = "0300000040" },
+    { value = 4294967296, hex = "070000000001" },
+    { value = 1000000000000, hex = "070010a5d4e8" },
+    { value = "18446744073709551615", hex = "13ffffffffffffffff" },
+    { value = "340282366920938463463374607431768211455", hex = "33" .. string.rep("ff", 16) }
+}
+
+-- Eras as sp_runtime::generic::Era encodes them (its mortal_codec_works and
+-- long_period_mortal_codec_works tests)
+Selfcheck.ERAS = {
+    { name = "immortal", era = { immortal = true }, hex = "00" },
+    { name = "mortal(64, 42)", current = 42, period = 64, hex = "a502" },
+    { name = "mortal(32768, 20000)", current = 20000, period = 32768, hex = "4e9c" }
+}
+
+-- Balances.transfer_keep_alive(Id(dev Alice), 10^12) at Westend's indices (4, 3)
+local ALICE = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+local TRANSFER_HEX = "0403" .. "00" .. ALICE .. "070010a5d4e8"
+local WESTEND_GENESIS = "e143f23803ac50e8f6f8e62695d1ce9e4e1d68aa36c1cd2cfd15340213f3423e"
+
+-- Signing payload of that transfer with the default (Westend) signed extensions:
+-- call | era | nonce | tip | metadata hash mode || spec | tx version | genesis | era block | metadata hash
+Selfcheck.PAYLOAD = {
+    props = { specVersion = 1000, txVersion = 25, genesisHash = "0x" .. WESTEND_GENESIS, nonce = 5, tip = 0 },
+    call = TRANSFER_HEX,
+    extra = "00" .. "14" .. "00" .. "00",
+    additional = "e8030000" .. "19000000" .. WESTEND_GENESIS .. WESTEND_GENESIS .. "00"
+}
+
+-- ============================================================
+-- Checks
+-- ============================================================
+
+local function record(report, name, expected, got, note)
+    local ok = expected == got
+    table.insert(report.checks, { name = name, ok = ok, expected = expected, got = got, note = note })
+    if not ok then report.failures = report.failures + 1 end
+end
+
+-- Run fn, turning an error into a "error: ..." result so one broken encoder does not
+-- hide the rest
+local function attempt(fn, ...)
+    local ok, result = pcall(fn, ...)
+    if ok then return result end
+    return "error: " .. tostring(result)
+end
+
+local function compact_type(meta)
+    for id, t in pairs(meta.types) do
+        local def = t.type_def
+        if def.type == "Compact" and Codec.primitive_name(meta, def.type_id) == "u128" then return id end
+    end
+    return nil
+end
+
+local function check_compacts(report, meta)
+    local compact_id = meta and compact_type(meta)
+    for _, v in ipairs(Selfcheck.COMPACTS) do
+        local name = "compact " .. tostring(v.value)
+        if type(v.value) == "number" then
+            record(report, name .. " (scale)", v.hex, attempt(function() return to_hex(Scale.encode_compact(v.value)) end))
+        end
+        if compact_id then
+            record(report, name .. " (codec)", v.hex, attempt(function() return to_hex(Codec.encode(meta, compact_id, v.value)) end))
+            record(report, name .. " (codec decode)", tostring(v.value), attempt(function()
+                return tostring((Codec.decode(meta, compact_id, from_hex(v.hex), 1)))
+            end))
+        end
+    end
+end
+
+local function check_eras(report)
+    local Transaction = require("sublua.transaction")
+    for _, e in ipairs(Selfcheck.ERAS) do
+        local era = e.era or attempt(Transaction.mortal_era, e.current, e.period)
+        local hex = attempt(function() return to_hex(Transaction.encode_era(era)) end)
+        record(report, "era " .. e.name, e.hex, hex)
+        if not e.era then
+            record(report, "era " .. e.name .. " (decode)", e.period .. "/" .. e.current, attempt(function()
+                local bytes = from_hex(e.hex)
+                local decoded = Transaction.decode_era({ variant = "Mortal" .. string.byte(bytes, 1), value = string.byte(bytes, 2) })
+                return string.format("%d/%d", decoded.period, decoded.phase)
+            end))
+        end
+    end
+end
+
+local function check_calls(report, meta)
+    record(report, "transfer_keep_alive (low-level)", TRANSFER_HEX, attempt(function()
+        return to_hex(Call.encode_transfer(4, 3, from_hex(ALICE), 1000000000000))
+    end))
+    local balances = meta and meta.pallets["Balances"]
+    local index = balances and balances.calls and balances.calls["transfer_keep_alive"]
+    if not index then return end
+    local note = "pallet " .. balances.index .. ", call " .. index .. " from metadata"
+    record(report, "transfer_keep_alive (low-level vs metadata)",
+        attempt(function() return to_hex(Call.encode_transfer(balances.index, index, from_hex(ALICE), 1000000000000)) end),
+        attempt(function()
+            return to_hex(Call.encode_call(meta, "Balances", "transfer_keep_alive",
+                { dest = { variant = "Id", value = "0x" .. ALICE }, value = "1000000000000" }))
+        end), note)
+    record(report, "transfer_keep_alive (JSON args vs metadata)",
+        attempt(function()
+            return to_hex(Call.encode_call(meta, "Balances", "transfer_keep_alive",
+                { dest = { variant = "Id", value = "0x" .. ALICE }, value = "1000000000000" }))
+        end),
+        attempt(function()
+            return to_hex(Call.encode_call_json(meta, "Balances", "transfer_keep_alive",
+                { dest = "0x" .. ALICE, value = 1000000000000 }))
+        end), note)
+end
+
+local function props()
+    local p = {}
+    for k, v in pairs(Selfcheck.PAYLOAD.props) do p[k] = v end
+    return p
+end
+
+local DEFAULT_IDENTIFIERS = "CheckNonZeroSender,CheckSpecVersion,CheckTxVersion,CheckGenesis,CheckMortality,"
+    .. "CheckNonce,CheckWeight,ChargeTransactionPayment,CheckMetadataHash"
+
+local function check_signing(report, meta)
+    local Transaction = require("sublua.transaction")
+    local P = Selfcheck.PAYLOAD
+    local call = from_hex(P.call)
+    local signer = require("sublua.keyring").from_seed(string.rep("s", 32))
+
+    -- The low-level path: default extension list, no metadata
+    local extrinsic = attempt(Transaction.create_signed_raw, call, signer, P.props.nonce, props())
+    local parsed = attempt(function()
+        local len, offset = Scale.decode_compact(extrinsic, 1)
+        assert(offset + len - 1 == #extrinsic, "length prefix does not match")
+        local body = extrinsic:sub(offset)
+        assert(body:sub(1, 2) == "\132\0" and body:sub(3, 34) == signer.pubkey, "version or signer wrong")
+        return { signature = body:sub(36, 99), rest = to_hex(body:sub(100)) }
+    end)
+    if type(parsed) == "string" then
+        record(report, "signed extrinsic (low-level)", "a V4 signed extrinsic", parsed)
+        return
+    end
+    record(report, "signed extrinsic extra and call (low-level)", P.extra .. P.call, parsed.rest)
+    local payload = from_hex(P.call .. P.extra .. P.additional)
+    record(report, "signature over the reference payload (low-level)", true,
+        crypto.ed25519_verify(signer.pubkey, payload, parsed.signature) and true or false)
+
+    if not meta then return end
+    local identifiers = {}
+    for i, ext in ipairs(meta.extrinsic.signed_extensions) do identifiers[i] = ext.identifier end
+    if table.concat(identifiers, ",") ~= DEFAULT_IDENTIFIERS then
+        table.insert(report.checks, { name = "signing payload (metadata)", ok = true, skipped = true,
+            note = "the chain's signed extensions differ from the default set" })
+        return
+    end
+    record(report, "signing payload (metadata)", to_hex(payload), attempt(function()
+        return to_hex((Transaction.make_signing_payload(meta, call, props())))
+    end))
+    record(report, "signed extrinsic (low-level vs metadata)", to_hex(extrinsic), attempt(function()
+        return to_hex(Transaction.create_signed_raw(call, signer, P.props.nonce, props(), meta))
+    end))
+end
+
+-- Encode the canonical set with every encoder and compare
+-- meta_source: parsed metadata, RPC client or metadata hex to also check the
+--   metadata-driven encoders against the low-level ones (nil checks the low-level
+--   encoders against the reference vectors only)
+-- Returns { ok, failures, checks = { { name, ok, expected, got, note, skipped }, ... } }
+--   expected/got are hex (or "error: ..." when an encoder raised)
+function Selfcheck.encoding(meta_source)
+    local meta = meta_source and Metadata.from_source(meta_source)
+    local report = { failures = 0, checks = {} }
+    check_compacts(report, meta)
+    check_eras(report)
+    check_calls(report, meta)
+    check_signing(report, meta)
+    report.ok = report.failures == 0
+    return report
+end
+
+-- Report as text: one line per discrepancy, then a summary line
+function Selfcheck.format(report)
+    local lines = {}
+    for _, c in ipairs(report.checks) do
+        if not c.ok then
+            table.insert(lines, string.format("MISMATCH %s: expected %s, got %s%s", c.name, tostring(c.expected),
+                tostring(c.got), c.note and (" (" .. c.note .. ")") or ""))
+        end
+    end
+    table.insert(lines, string.format("%d checks, %d mismatches", #report.checks, report.failures))
+    return table.concat(lines, "\n")
+end
+
+return Selfcheck
@@ -0,0 +1,205 @@
+-- sublua/session.lua
+-- Session key management for validator operators
+-- Wraps author_rotateKeys / author_hasSessionKeys and builds Session.set_keys calls
+-- Key layouts cover every sp_core key type a runtime can put in its SessionKeys,
+-- including bandersnatch (Sassafras) and BLS (BEEFY). For those two only the encoding
+-- is supported: the SDK splits, checks and registers their public keys, but has no
+-- bandersnatch or BLS12 curve code to derive, sign or verify with them; the node's
+-- keystore generates and uses them
+
+local Scale = require("sublua.scale")
+local Call = require("sublua.call")
+
+local Session = {}
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- ============================================================
+-- Key Types
+-- ============================================================
+
+-- Public key sizes of the sp_core crypto types
+Session.KEY_SIZES = {
+    ed25519 = 32,
+    sr25519 = 32,
+    ecdsa = 33,
+    bandersnatch = 32,
+    bls377 = 144,
+    bls381 = 144,
+    ecdsa_bls377 = 177,
+    ecdsa_bls381 = 177
+}
+
+-- Key types the SDK can derive and sign with itself (bandersnatch and BLS are not among them)
+Session.LOCAL_SIGNING = { ed25519 = true }
+
+-- SessionKeys layouts: { { name, crypto, key_type }, ... } in runtime field order
+-- key_type is the 4-character keystore id used by author_hasKey
+Session.LAYOUTS = {
+    polkadot = {
+        { "grandpa", "ed25519", "gran" },
+        { "babe", "sr25519", "babe" },
+        { "para_validator", "sr25519", "para" },
+        { "para_assignment", "sr25519", "asgn" },
+        { "authority_discovery", "sr25519", "audi" },
+        { "beefy", "ecdsa", "beef" }
+    },
+    sassafras = {
+        { "grandpa", "ed25519", "gran" },
+        { "sassafras", "bandersnatch", "sass" }
+    },
+    beefy_bls = {
+        { "grandpa", "ed25519", "gran" },
+        { "babe", "sr25519", "babe" },
+        { "beefy", "ecdsa_bls377", "beef" }
+    }
+}
+
+local function layout_of(layout)
+    if type(layout) == "string" then
+        return Session.LAYOUTS[layout] or error("Unknown session key layout '" .. layout .. "'")
+    end
+    assert(type(layout) == "table", "layout must be a table or a name in Session.LAYOUTS")
+    return layout
+end
+
+-- Total size in bytes of the keys of a layout
+function Session.layout_size(layout)
+    local size = 0
+    for _, field in ipairs(layout_of(layout)) do
+        size = size + (Session.KEY_SIZES[field[2]] or error("Unknown key type '" .. tostring(field[2]) .. "'"))
+    end
+    return size
+end
+
+-- Split the output of rotate_keys into its public keys
+-- layout: name in Session.LAYOUTS or a layout table
+-- Returns { { name, crypto, key_type, public = "0x..", local_signing }, ... }, or nil, err
+function Session.split_keys(keys_hex, layout)
+    layout = layout_of(layout)
+    local keys = from_hex(keys_hex)
+    local expected = Session.layout_size(layout)
+    if #keys ~= expected then
+        return nil, "session keys are " .. #keys .. " bytes, layout expects " .. expected
+    end
+    local out, at = {}, 1
+    for _, field in ipairs(layout) do
+        local size = Session.KEY_SIZES[field[2]]
+        table.insert(out, {
+            name = field[1],
+            crypto = field[2],
+            key_type = field[3],
+            public = "0x" .. to_hex(keys:sub(at, at + size - 1)),
+            local_signing = Session.LOCAL_SIGNING[field[2]] == true
+        })
+        at = at + size
+    end
+    return out
+end
+
+-- ============================================================
+-- Call Encoding
+-- Session.set_keys(keys: T::Keys, proof: Vec<u8>)
+-- T::Keys is a composite of fixed-size public keys, so the SCALE
+-- encoding is simply the concatenation returned by author_rotateKeys.
+-- Sizes differ by key type (ecdsa 33, BLS 144), see Session.KEY_SIZES.
+-- ============================================================
+
+-- Build Session.set_keys call bytes
+-- pallet_index: Session pallet index (9 on Polkadot)
+-- call_index: set_keys index (0)
+-- keys_hex: SCALE-encoded session keys as returned by author_rotateKeys
+-- proof: raw ownership proof bytes (default empty)
+-- layout: optional name in Session.LAYOUTS or layout table to check the keys' size against
+function Session.encode_set_keys(pallet_index, call_index, keys_hex, proof, layout)
+    assert(type(keys_hex) == "string" and keys_hex:gsub("^0x", ""):match("^%x+$") and #keys_hex % 2 == 0,
+        "keys_hex must be non-empty hex")
+    local keys = from_hex(keys_hex)
+    if layout then
+        local ok, err = Session.split_keys(keys_hex, layout)
+        if not ok then error(err) end
+    end
+    proof = proof or ""
+
+    local call_idx = Call.encode_index(pallet_index, call_index)
+    return call_idx .. keys .. Scale.encode_compact(#proof) .. proof
+end
+
+-- Build Session.purge_keys call bytes (no arguments)
+function Session.encode_purge_keys(pallet_index, call_index)
+    return Call.encode_index(pallet_index, call_index)
+end
+
+-- ============================================================
+-- Node Operations
+-- ============================================================
+
+-- Generate fresh session keys in the node's keystore
+-- Requires a client created with { allow_unsafe = true } pointed at the validator node
+function Session.rotate_keys(api)
+    return api:author_rotateKeys()
+end
+
+-- Check whether the node's keystore holds the private keys for keys_hex
+function Session.has_session_keys(api, keys_hex)
+    return api:author_hasSessionKeys(keys_hex) == true
+end
+
+-- Check each key of a layout in the node's keystore (bandersnatch and BLS keys included)
+-- Returns the split_keys entries with .present set, or nil, err
+function Session.check_keys(api, keys_hex, layout)
+    local keys, err = Session.split_keys(keys_hex, layout)
+    if not keys then return nil, err end
+    for _, key in ipairs(keys) do
+        key.present = api:author_hasKey(key.public, key.key_type) == true
+    end
+    return keys
+end
+
+-- ============================================================
+-- Convenience: Build and sign using live chain state
+-- ============================================================
+
+-- Register session keys for the signer's (controller/stash) account
+-- api: RPC client
+-- signer: keyring pair
+-- keys_hex: output of rotate_keys
+-- proof: raw proof bytes (default empty)
+-- opts: { tip = 0, layout = nil } (layout: checked against the keys before signing)
+function Session.set_keys(api, signer, keys_hex, proof, opts)
+    local meta = api:get_metadata()
+    local session = meta.pallets["Session"]
+    assert(session, "Session pallet not found in metadata")
+
+    local call_index = session.calls and session.calls["set_keys"]
+    assert(call_index, "set_keys not found in Session")
+
+    local call_bytes = Session.encode_set_keys(session.index, call_index, keys_hex, proof, opts and opts.layout)
+
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Remove the signer's session keys
+function Session.purge_keys(api, signer, opts)
+    local meta = api:get_metadata()
+    local session = meta.pallets["Session"]
+    assert(session, "Session pallet not found in metadata")
+
+    local call_index = session.calls and session.calls["purge_keys"]
+    assert(call_index, "purge_keys not found in Session")
+
+    local call_bytes = Session.encode_purge_keys(session.index, call_index)
+
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+return Session
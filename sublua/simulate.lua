@@ -0,0 +1,206 @@
+-- sublua/simulate.lua
+-- Preview what a call would do before signing it for real
+-- Dry-run mode executes the call from any origin (a signed account, Root, a collective)
+-- with the runtime's DryRunApi on the node's current state: outcome, weight and emitted
+-- events, nothing persisted
+-- Fork mode is for a local fork of the chain (a chopsticks endpoint): the call is signed,
+-- submitted and built into a block there with dev_newBlock, so the fork's state really
+-- changes and watched storage can be compared before and after
+
+local Scale = require("sublua.scale")
+local Codec = require("sublua.codec")
+local Call = require("sublua.call")
+local Metadata = require("sublua.metadata")
+local Storage = require("sublua.storage")
+
+local Simulate = {}
+
+-- XCM version DryRunApi reports forwarded messages in
+Simulate.XCM_VERSION = 4
+
+local DRY_RUN_ERRORS = { [0] = "Unimplemented", "VersionedConversionFailed" }
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function client(node)
+    if type(node) == "string" then return require("sublua.rpc").new(node) end
+    return node
+end
+
+-- ============================================================
+-- Origins
+-- ============================================================
+
+local function origin_type(meta)
+    for id, t in pairs(meta.types) do
+        if t.path and t.path[#t.path] == "OriginCaller" then return id end
+    end
+    error("OriginCaller type not in metadata")
+end
+
+-- OriginCaller value for an origin given as
+--   "Root" or "None"; an SS58 address, 0x public key or keypair (a signed origin);
+--   or the OriginCaller shape itself, e.g. { Council = { Members = { 3, 5 } } }
+function Simulate.origin(origin)
+    if origin == "Root" or origin == "None" then return { system = origin } end
+    if type(origin) == "table" and origin.pubkey and origin.address then origin = origin.address end
+    if type(origin) == "string" then return { system = { Signed = origin } } end
+    if type(origin) ~= "table" then error("Origin must be Root, None, an account or an OriginCaller value") end
+    return origin
+end
+
+-- ============================================================
+-- Dry Run
+-- ============================================================
+
+local function event_type(meta)
+    local entry = Metadata.get_storage_entry(meta, "System", "Events")
+    local record = Codec.resolve(meta, Codec.resolve(meta, entry.storage_type.value).type_def.type_id).type_def
+    for _, field in ipairs(record.fields) do
+        if field.name == "event" then return field.type_id end
+    end
+    error("System.Events records have no event field")
+end
+
+-- PostDispatchInfo { actual_weight: Option<Weight>, pays_fee: Pays }
+local function decode_post_info(data, offset)
+    local weight
+    if string.byte(data, offset) == 1 then
+        local ref_time, proof_size
+        ref_time, offset = Scale.decode_compact(data, offset + 1)
+        proof_size, offset = Scale.decode_compact(data, offset)
+        weight = { ref_time = ref_time, proof_size = proof_size }
+    else
+        offset = offset + 1
+    end
+    return weight, string.byte(data, offset) == 0, offset + 1
+end
+
+-- Outcome of dispatching call_bytes from origin on the state at `at`
+-- (DryRunApi_dry_run_call: nothing is persisted)
+-- Returns { mode = "dry_run", ok, error, module, weight, pays_fee, events, diffs = {} }
+--   error/module: the DispatchError and its pallet error ({ pallet, name, docs }) when it failed
+--   weight: { ref_time, proof_size } the dispatch reported, nil when it did not
+--   events: { index, pallet, name, fields } emitted while dispatching (accounts as SS58 in
+--     the client's format when one is set)
+function Simulate.dry_run(api, call_bytes, origin, at)
+    local meta = api:get_metadata()
+    local input = Codec.encode(meta, origin_type(meta), Codec.from_json(meta, origin_type(meta), Simulate.origin(origin)))
+        .. call_bytes .. Scale.encode_u32(Simulate.XCM_VERSION)
+    local data = from_hex(api:state_call("DryRunApi_dry_run_call", "0x" .. to_hex(input), at) or "0x")
+    if #data == 0 then error("DryRunApi_dry_run_call returned nothing") end
+    if string.byte(data, 1) ~= 0 then
+        error("Dry run failed: " .. (DRY_RUN_ERRORS[string.byte(data, 2)] or "unknown error"))
+    end
+
+    local result = { mode = "dry_run", diffs = {} }
+    local offset = 2
+    local failed = string.byte(data, offset) ~= 0
+    result.weight, result.pays_fee, offset = decode_post_info(data, offset + 1)
+    result.ok = not failed
+    if failed then
+        local type_id = assert(Codec.find_type(meta, "sp_runtime::DispatchError"), "DispatchError type not in metadata")
+        result.error, offset = Codec.decode(meta, type_id, data, offset)
+        if result.error.variant == "Module" then
+            result.module = Metadata.get_error(meta, result.error.value.index, string.byte(from_hex(result.error.value.error), 1))
+        end
+    end
+
+    local ev_type = event_type(meta)
+    local count
+    count, offset = Scale.decode_compact(data, offset)
+    result.events = {}
+    for i = 1, count do
+        local event
+        event, offset = Codec.decode(meta, ev_type, data, offset)
+        if api.ss58_format ~= nil then event = Codec.format_accounts(meta, ev_type, event, api.ss58_format) end
+        result.events[i] = {
+            index = i - 1,
+            pallet = event.variant,
+            name = event.value and event.value.variant,
+            fields = event.value and event.value.value
+        }
+    end
+    return result
+end
+
+-- ============================================================
+-- Fork
+-- ============================================================
+
+local function read(api, meta, watch, at)
+    local raw = api:state_getStorage(watch.key, at)
+    if type(raw) ~= "string" or raw == "0x" then return nil, nil end
+    local entry = Metadata.get_storage_entry(meta, watch.pallet, watch.entry)
+    return raw, Codec.decode_hex(meta, entry.storage_type.value, raw)
+end
+
+-- Sign call_bytes, build it into a new block of the fork and compare watched storage
+-- opts: { signer, watch = {}, ... }; others go to Transaction.create_signed_from_api
+-- Returns { mode = "fork", ok, error, module, events, diffs, block_hash, fee_paid }
+--   diffs: { { pallet, entry, keys, before, after } } for watched values that changed
+function Simulate.on_fork(api, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    local signer = Transaction.signer_of(api, opts.signer)
+    local meta = api:get_metadata()
+    local watches = {}
+    for i, w in ipairs(opts.watch or {}) do
+        local pallet, entry, keys = w[1] or w.pallet, w[2] or w.entry, w[3] or w.keys or {}
+        watches[i] = { pallet = pallet, entry = entry, keys = keys, key = Storage.build_key(meta, pallet, entry, keys) }
+    end
+
+    local head = api:chain_getBlockHash()
+    for _, watch in ipairs(watches) do watch.raw, watch.before = read(api, meta, watch, head) end
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.signer, o.watch, o.fork, o.at = nil, nil, nil, nil
+    local signed = Transaction.create_signed_from_api(api, signer, call_bytes, o)
+    api:author_submitExtrinsic(signed)
+    local block_hash = api:request("dev_newBlock", { {} })
+    local outcome = Transaction.get_result(api, signed, block_hash)
+    if not outcome then error("The fork built block " .. tostring(block_hash) .. " without the extrinsic") end
+
+    local diffs = {}
+    for _, watch in ipairs(watches) do
+        local raw, after = read(api, meta, watch, block_hash)
+        if raw ~= watch.raw then
+            table.insert(diffs, { pallet = watch.pallet, entry = watch.entry, keys = watch.keys, before = watch.before, after = after })
+        end
+    end
+    return {
+        mode = "fork",
+        ok = outcome.success,
+        error = outcome.error and outcome.error.error,
+        module = outcome.error and outcome.error.module,
+        events = outcome.events,
+        diffs = diffs,
+        block_hash = block_hash,
+        fee_paid = outcome.fee_paid
+    }
+end
+
+-- Preview a call: pallet, call and args as for Call.encode_call_json (args as a table or JSON)
+-- node: RPC client or URL
+-- origin: see Simulate.origin (dry-run mode; fork mode signs with opts.signer instead)
+-- opts: { at = nil, fork = false, signer = nil, watch = nil, ... }
+--   at: block hash to dry-run on (default: best block)
+--   fork: node is a local fork (chopsticks): sign and build the call into a block there
+--   signer: keypair signing on the fork (default: the client's signer)
+--   watch: storage to compare on the fork, { { pallet, entry, keys }, ... }
+-- Returns the result of Simulate.dry_run or Simulate.on_fork
+function Simulate.simulate_call(node, pallet, call, args, origin, opts)
+    opts = opts or {}
+    local api = client(node)
+    local call_bytes = Call.encode_call_json(api, pallet, call, args)
+    if opts.fork then return Simulate.on_fork(api, call_bytes, opts) end
+    return Simulate.dry_run(api, call_bytes, origin, opts.at)
+end
+
+return Simulate
@@ -0,0 +1,146 @@
+-- sublua/snapshot.lua
+-- Storage snapshots: every key under selected pallets, downloaded at one block into a
+-- JSON file, then read offline through a client-shaped object
+-- The file also keeps the metadata, so a snapshot is enough to decode its own values:
+-- analytics scripts and tests run sublua.storage against it without a node
+-- File: { format = 1, block_hash, spec_version, metadata = "0x..", pallets = { ... },
+--   storage = { ["0x key"] = "0x value", ... } }
+
+local crypto = require("polkadot_crypto")
+local json = require("cjson")
+local Metadata = require("sublua.metadata")
+local Storage = require("sublua.storage")
+
+local Snapshot = {}
+Snapshot.__index = Snapshot
+
+Snapshot.FORMAT = 1
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- ============================================================
+-- Download
+-- ============================================================
+
+-- Download all storage of the given pallets
+-- pallets: list of pallet names, or the same as a JSON array string
+-- block_hash: block to read at (default: the finalized head)
+-- out_path: file to write (replaced atomically)
+-- opts: { page_size = 1000 }
+-- Returns { path, block_hash, keys = number of entries written }
+function Snapshot.dump(api, pallets, block_hash, out_path, opts)
+    opts = opts or {}
+    if type(pallets) == "string" then pallets = json.decode(pallets) end
+    assert(type(pallets) == "table" and #pallets > 0, "pallets must be a non-empty list of pallet names")
+    assert(type(out_path) == "string" and out_path ~= "", "Snapshot needs an output path")
+    local page_size = opts.page_size or 1000
+    block_hash = block_hash or api:chain_getFinalizedHead()
+
+    local metadata_hex = api:state_getMetadata(block_hash)
+    local meta = Metadata.parse(metadata_hex)
+    local storage, count = {}, 0
+    for _, name in ipairs(pallets) do
+        local pallet = meta.pallets[name]
+        if not pallet then error("Pallet not found: " .. tostring(name)) end
+        if not pallet.storage then error("Pallet has no storage: " .. name) end
+        local prefix = "0x" .. to_hex(crypto.twox128(pallet.storage.prefix))
+
+        local start
+        repeat
+            local page = api:state_getKeysPaged(prefix, page_size, start, block_hash) or {}
+            if #page > 0 then
+                for _, set in ipairs(api:state_queryStorageAt(page, block_hash) or {}) do
+                    for _, change in ipairs(set.changes or {}) do
+                        if type(change[2]) == "string" and storage[change[1]] == nil then
+                            storage[change[1]] = change[2]
+                            count = count + 1
+                        end
+                    end
+                end
+            end
+            start = page[#page]
+        until #page < page_size
+    end
+
+    local runtime = api:state_getRuntimeVersion(block_hash)
+    local state = {
+        format = Snapshot.FORMAT,
+        block_hash = block_hash,
+        spec_version = runtime and runtime.specVersion,
+        metadata = metadata_hex,
+        pallets = pallets,
+        storage = storage
+    }
+    local tmp = out_path .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write snapshot: " .. tostring(err)) end
+    f:write(json.encode(state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, out_path)
+    if not ok then error("Cannot write snapshot: " .. tostring(rename_err)) end
+    return { path = out_path, block_hash = block_hash, keys = count }
+end
+
+-- ============================================================
+-- Offline Reads
+-- A loaded snapshot answers the storage RPCs sublua.storage uses, so it can stand in
+-- for an api client: Storage.fetch(snap, "System", "Account", { who })
+-- ============================================================
+
+-- Load a snapshot file
+function Snapshot.load(path)
+    local f, err = io.open(path, "rb")
+    if not f then error("Cannot open snapshot: " .. tostring(err)) end
+    local text = f:read("*a")
+    f:close()
+    local ok, state = pcall(json.decode, text)
+    if not ok or type(state) ~= "table" or type(state.storage) ~= "table" then
+        error("Unreadable snapshot file " .. path)
+    end
+    if state.format ~= Snapshot.FORMAT then
+        error("Unsupported snapshot format " .. tostring(state.format))
+    end
+    local self = setmetatable({ path = path, state = state, block_hash = state.block_hash }, Snapshot)
+    self.keys = {}
+    for key in pairs(state.storage) do table.insert(self.keys, key) end
+    table.sort(self.keys)
+    return self
+end
+
+function Snapshot:get_metadata()
+    if not self.metadata then self.metadata = Metadata.parse(self.state.metadata) end
+    return self.metadata
+end
+
+-- Values outside the snapshot read as absent; the block argument is ignored
+function Snapshot:state_getStorage(key)
+    return self.state.storage[key]
+end
+
+function Snapshot:state_queryStorageAt(keys)
+    local changes = {}
+    for i, key in ipairs(keys) do changes[i] = { key, self.state.storage[key] } end
+    return { { block = self.block_hash, changes = changes } }
+end
+
+function Snapshot:state_getKeysPaged(prefix, count, start_key)
+    local out = {}
+    for _, key in ipairs(self.keys) do
+        if #out >= (count or 1000) then break end
+        if key:sub(1, #prefix) == prefix and (not start_key or start_key == json.null or key > start_key) then
+            table.insert(out, key)
+        end
+    end
+    return out
+end
+
+-- Read and decode one value from a snapshot (a loaded one or a file path)
+-- keys: map keys as for Storage.fetch
+function Snapshot.query(snapshot, pallet, entry_name, keys)
+    if type(snapshot) == "string" then snapshot = Snapshot.load(snapshot) end
+    return Storage.fetch(snapshot, pallet, entry_name, keys)
+end
+
+return Snapshot
@@ -0,0 +1,218 @@
+-- sublua/society.lua
+-- Society (Kusama) and Alliance pallets: membership actions and member queries
+-- Calls are encoded from metadata, so argument names follow the runtime; functions
+-- that sign return the extrinsic hex, like Session.set_keys
+-- Society queries read the current (v2) storage layout and fall back to the older
+-- one where Members and Candidates were plain lists
+
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Metadata = require("sublua.metadata")
+local Address = require("sublua.address")
+
+local Society = {}
+
+local function sign(api, signer, pallet, call_name, args, opts)
+    local call_bytes = Call.encode_call(api, pallet, call_name, args)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+local function account(api, pallet, call_name, field, who)
+    return Call.account_arg(api, pallet, call_name, field, who)
+end
+
+local function pubkey_hex(who)
+    return Address.convert(who, "hex")
+end
+
+local function is_plain(api, pallet, entry_name)
+    local entry, _, err = Metadata.get_storage_entry(api:get_metadata(), pallet, entry_name)
+    if err then error(pallet .. "." .. entry_name .. ": " .. err) end
+    return entry.storage_type.type == "Plain"
+end
+
+-- ============================================================
+-- Society Calls
+-- ============================================================
+
+-- Bid to join with value (the amount asked from the pot on admission)
+-- opts: passed to Transaction.create_signed_from_api (tip, nonce, mortality, ...)
+function Society.bid(api, signer, value, opts)
+    return sign(api, signer, "Society", "bid", { value = value }, opts)
+end
+
+-- Withdraw the signer's bid
+function Society.unbid(api, signer, opts)
+    return sign(api, signer, "Society", "unbid", {}, opts)
+end
+
+-- Vouch for a non-member: they are bid in with value; tip is kept by the voucher
+function Society.vouch(api, signer, who, value, tip, opts)
+    return sign(api, signer, "Society", "vouch",
+        { who = account(api, "Society", "vouch", "who", who), value = value, tip = tip or 0 }, opts)
+end
+
+-- Withdraw the signer's vouch
+function Society.unvouch(api, signer, opts)
+    return sign(api, signer, "Society", "unvouch", {}, opts)
+end
+
+-- Approve or reject a candidate of the current round
+function Society.vote(api, signer, candidate, approve, opts)
+    return sign(api, signer, "Society", "vote",
+        { candidate = account(api, "Society", "vote", "candidate", candidate), approve = approve == true }, opts)
+end
+
+-- Vote on the member being challenged (the defender)
+function Society.defender_vote(api, signer, approve, opts)
+    return sign(api, signer, "Society", "defender_vote", { approve = approve == true }, opts)
+end
+
+-- Collect the signer's matured payout
+function Society.payout(api, signer, opts)
+    return sign(api, signer, "Society", "payout", {}, opts)
+end
+
+-- Become a member after being approved as a candidate
+function Society.claim_membership(api, signer, opts)
+    return sign(api, signer, "Society", "claim_membership", {}, opts)
+end
+
+-- ============================================================
+-- Society Queries
+-- ============================================================
+
+-- All members: { { account = "0x..", record = MemberRecord }, ... }
+-- record is nil on runtimes that store members as a plain list
+function Society.query_members(api)
+    local out = {}
+    if is_plain(api, "Society", "Members") then
+        for _, who in ipairs(Storage.fetch(api, "Society", "Members") or {}) do
+            table.insert(out, { account = who })
+        end
+        return out
+    end
+    for _, item in ipairs(Storage.entries(api, "Society", "Members")) do
+        table.insert(out, { account = item.keys[1], record = item.value })
+    end
+    return out
+end
+
+-- Membership record of who (rank, strikes, vouching, index), true on list-layout
+-- runtimes, or nil for non-members
+function Society.query_member(api, who)
+    local pubkey = pubkey_hex(who)
+    if is_plain(api, "Society", "Members") then
+        for _, member in ipairs(Storage.fetch(api, "Society", "Members") or {}) do
+            if member == pubkey then return true end
+        end
+        return nil
+    end
+    return Storage.fetch(api, "Society", "Members", { pubkey })
+end
+
+function Society.is_member(api, who)
+    return Society.query_member(api, who) ~= nil
+end
+
+-- Candidates of the current round: { { account, candidacy }, ... }
+-- On list-layout runtimes candidacy is the Bid { who, kind, value }
+function Society.query_candidates(api)
+    local out = {}
+    if is_plain(api, "Society", "Candidates") then
+        for _, bid in ipairs(Storage.fetch(api, "Society", "Candidates") or {}) do
+            table.insert(out, { account = bid.who, candidacy = bid })
+        end
+        return out
+    end
+    for _, item in ipairs(Storage.entries(api, "Society", "Candidates")) do
+        table.insert(out, { account = item.keys[1], candidacy = item.value })
+    end
+    return out
+end
+
+-- Outstanding bids: list of { who, kind, value }
+function Society.query_bids(api)
+    return Storage.fetch(api, "Society", "Bids") or {}
+end
+
+-- Head and founder accounts ("0x.." public keys), nil when unset
+function Society.query_head(api)
+    return Storage.fetch(api, "Society", "Head")
+end
+
+function Society.query_founder(api)
+    return Storage.fetch(api, "Society", "Founder")
+end
+
+-- Pending payouts of who as stored (PayoutRecord, or a list of (block, amount))
+function Society.query_payouts(api, who)
+    return Storage.fetch(api, "Society", "Payouts", { pubkey_hex(who) })
+end
+
+-- ============================================================
+-- Alliance Calls
+-- Motions of the Alliance are proposed and closed on its collective
+-- (AllianceMotion, see sublua.collective); alliance_vote votes on them through
+-- the Alliance pallet
+-- ============================================================
+
+-- Join as an ally (reserves the deposit)
+function Society.alliance_join(api, signer, opts)
+    return sign(api, signer, "Alliance", "join_alliance", {}, opts)
+end
+
+-- Fellows nominate a non-member as an ally without a deposit
+function Society.alliance_nominate_ally(api, signer, who, opts)
+    return sign(api, signer, "Alliance", "nominate_ally",
+        { who = account(api, "Alliance", "nominate_ally", "who", who) }, opts)
+end
+
+-- Elevate an ally to fellow (dispatched by a motion)
+function Society.alliance_elevate_ally(api, signer, ally, opts)
+    return sign(api, signer, "Alliance", "elevate_ally",
+        { ally = account(api, "Alliance", "elevate_ally", "ally", ally) }, opts)
+end
+
+-- Start the retirement period, then retire once it has passed
+function Society.alliance_give_retirement_notice(api, signer, opts)
+    return sign(api, signer, "Alliance", "give_retirement_notice", {}, opts)
+end
+
+function Society.alliance_retire(api, signer, opts)
+    return sign(api, signer, "Alliance", "retire", {}, opts)
+end
+
+-- Vote on an Alliance motion
+-- proposal_hash: "0x" hash of the proposed call; index: proposal index
+function Society.alliance_vote(api, signer, proposal_hash, index, approve, opts)
+    return sign(api, signer, "Alliance", "vote",
+        { proposal = proposal_hash, index = index, approve = approve == true }, opts)
+end
+
+-- ============================================================
+-- Alliance Queries
+-- ============================================================
+
+-- Members by role: { Fellow = { "0x..", ... }, Ally = { ... }, Retiring = { ... } }
+function Society.alliance_members(api)
+    local out = {}
+    for _, item in ipairs(Storage.entries(api, "Alliance", "Members")) do
+        out[item.keys[1].variant] = item.value
+    end
+    return out
+end
+
+-- Role of who ("Fellow", "Ally" or "Retiring"), or nil for non-members
+function Society.alliance_role(api, who)
+    local pubkey = pubkey_hex(who)
+    for role, members in pairs(Society.alliance_members(api)) do
+        for _, member in ipairs(members) do
+            if member == pubkey then return role end
+        end
+    end
+    return nil
+end
+
+return Society
@@ -0,0 +1,363 @@
+-- sublua/staking.lua
+-- Staking queries for validator monitoring (slashes, offline reports), reward payouts,
+-- validator preferences and controller migration
+
+local Codec = require("sublua.codec")
+local Events = require("sublua.events")
+local Metadata = require("sublua.metadata")
+local Storage = require("sublua.storage")
+local Call = require("sublua.call")
+local Validate = require("sublua.validate")
+
+local Staking = {}
+
+-- Eras a runtime keeps exposures and rewards for, when it has no HistoryDepth constant
+local DEFAULT_HISTORY_DEPTH = 84
+
+-- ============================================================
+-- Offline Reports
+-- ============================================================
+
+-- Scan a block range for ImOnline.SomeOffline events
+-- api: RPC client
+-- from_block, to_block: inclusive block numbers
+-- Returns a list of { block_number, block_hash, offline, event }
+function Staking.query_offline_events(api, from_block, to_block)
+    assert(from_block <= to_block, "from_block must not exceed to_block")
+    local filter = { { pallet = "ImOnline", name = "SomeOffline" } }
+
+    local found = {}
+    for n = from_block, to_block do
+        local hash = api:chain_getBlockHash(n)
+        for _, event in ipairs(Events.filter(Events.fetch(api, hash), filter)) do
+            table.insert(found, {
+                block_number = n,
+                block_hash = hash,
+                offline = event.fields and event.fields.offline,
+                event = event
+            })
+        end
+    end
+    return found
+end
+
+-- ============================================================
+-- Slashes
+-- ============================================================
+
+-- Query pending (unapplied) slashes for an era
+-- Staking.UnappliedSlashes is keyed by Twox64Concat(EraIndex). Older runtimes
+-- store Vec<UnappliedSlash> per era; newer ones use a double map with one
+-- entry per slash, which is iterated under the era prefix page by page.
+-- opts: { at = best block, page_size = 1000, cancel = nil }
+--   at: block hash; the best block is taken once so every page reads the same state
+--   cancel: token (see sublua.cancel), checked before each page
+-- Returns a list of decoded UnappliedSlash values
+function Staking.query_slashes(api, era, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local entry, _, err = Metadata.get_storage_entry(meta, "Staking", "UnappliedSlashes")
+    if err then error("Staking.UnappliedSlashes: " .. err) end
+    local at = opts.at or api:chain_getBlockHash()
+
+    if #entry.storage_type.hashers == 1 then
+        return Storage.fetch(api, "Staking", "UnappliedSlashes", { era }, at) or {}
+    end
+
+    local slashes = {}
+    local entries = Storage.entries(api, "Staking", "UnappliedSlashes", { era },
+        { at = at, page_size = opts.page_size, cancel = opts.cancel })
+    for _, e in ipairs(entries) do table.insert(slashes, e.value) end
+    return slashes
+end
+
+-- ============================================================
+-- Controllers
+-- Runtimes since the controller deprecation take set_controller() with no arguments: it
+-- only makes the stash its own controller. Older ones take set_controller(controller)
+-- ============================================================
+
+local function sign(api, signer, call_bytes, opts)
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- Argument names of a Staking call, or nil when the runtime has no such call
+local function call_fields(meta, name)
+    local pallet = meta.pallets["Staking"]
+    if not pallet or not pallet.calls_type_id then return nil end
+    for _, variant in ipairs(Codec.resolve(meta, pallet.calls_type_id).type_def.variants) do
+        if variant.name == name then
+            local names = {}
+            for i, field in ipairs(variant.fields) do names[i] = field.name end
+            return names
+        end
+    end
+    return nil
+end
+
+-- Whether the runtime still lets a stash name a separate controller account
+-- meta_source: parsed metadata, RPC client, or metadata hex
+function Staking.uses_controllers(meta_source)
+    local fields = call_fields(Metadata.from_source(meta_source), "set_controller")
+    return fields ~= nil and #fields > 0
+end
+
+-- Controller of a bonded stash as "0x" public key, or nil when the stash is not bonded
+-- stash: SS58 address or 0x public key
+function Staking.controller_of(api, stash, at)
+    return Storage.fetch(api, "Staking", "Bonded", { Validate.account(stash, "stash") }, at)
+end
+
+-- Whether a bonded stash still has a separate controller to migrate away from
+-- Returns needed, controller ("0x" public key; nil when the stash is not bonded)
+function Staking.needs_controller_migration(api, stash, at)
+    local controller = Staking.controller_of(api, stash, at)
+    return controller ~= nil and controller ~= Validate.account(stash, "stash"), controller
+end
+
+-- Sign Staking.set_controller from the stash (signer; nil uses the client's signer)
+-- controller: SS58 address or 0x public key of the new controller; nil, or the stash
+--   itself, makes the stash its own controller, the only change runtimes since the
+--   deprecation allow
+-- opts go to Transaction.create_signed_from_api
+-- Returns the signed extrinsic hex (and details, as Transaction.create_signed_from_api)
+function Staking.set_controller(api, signer, controller, opts)
+    local meta = api:get_metadata()
+    local fields = call_fields(meta, "set_controller")
+    if not fields then error("Staking.set_controller is not available on this runtime") end
+    signer = require("sublua.transaction").signer_of(api, signer)
+    local stash = "0x" .. signer.pubkey:gsub(".", function(c) return string.format("%02x", c:byte()) end)
+    local target = controller ~= nil and Validate.account(controller, "controller") or stash
+
+    if #fields == 0 then
+        if target ~= stash then
+            error("Staking.set_controller(controller) is not available on this runtime: controllers are "
+                .. "deprecated and the stash can only become its own controller (pass no controller)")
+        end
+        return sign(api, signer, Call.encode_call(meta, "Staking", "set_controller", {}), opts)
+    end
+    local arg = Call.account_arg(meta, "Staking", "set_controller", fields[1], target)
+    return sign(api, signer, Call.encode_call(meta, "Staking", "set_controller", { [fields[1]] = arg }), opts)
+end
+
+-- Make the signer's stash its own controller, on old and new runtimes alike
+-- Returns what Staking.set_controller does, or nil when there is nothing to migrate
+-- (the stash is not bonded or already controls itself)
+function Staking.migrate_controller(api, signer, opts)
+    signer = require("sublua.transaction").signer_of(api, signer)
+    if not Staking.needs_controller_migration(api, signer.address) then return nil end
+    return Staking.set_controller(api, signer, nil, opts)
+end
+
+-- ============================================================
+-- Validator Preferences
+-- Staking.validate(prefs: ValidatorPrefs { commission: Compact<Perbill>, blocked: bool })
+-- and Staking.kick(who: Vec<MultiAddress>), both signed by the controller (the stash on
+-- current runtimes)
+-- ============================================================
+
+local PERBILL_PER_PERCENT = 10000000
+
+-- Sign Staking.validate: declare the intent to validate with these preferences
+-- commission_percent: 0 to 100, fractions allowed down to Perbill precision (e.g. 2.5)
+-- blocked: refuse new nominations (existing nominators can be removed with Staking.kick)
+-- opts go to Transaction.create_signed_from_api
+-- Returns the signed extrinsic hex (and details, as Transaction.create_signed_from_api)
+function Staking.validate(api, signer, commission_percent, blocked, opts)
+    if type(commission_percent) == "string" then commission_percent = tonumber(commission_percent) or commission_percent end
+    if type(commission_percent) ~= "number" or commission_percent < 0 or commission_percent > 100 then
+        Validate.fail("commission_percent", "a number from 0 to 100", commission_percent)
+    end
+    local parts = math.floor(commission_percent * PERBILL_PER_PERCENT + 0.5)
+    local meta = api:get_metadata()
+    if not call_fields(meta, "validate") then error("Staking.validate is not available on this runtime") end
+    local call = Call.encode_call(meta, "Staking", "validate",
+        { prefs = { commission = parts, blocked = blocked and true or false } })
+    return sign(api, signer, call, opts)
+end
+
+-- Sign Staking.kick: remove nominators from the signer's validator
+-- nominators: SS58 addresses or 0x public keys
+-- opts go to Transaction.create_signed_from_api
+function Staking.kick(api, signer, nominators, opts)
+    Validate.list(nominators, "nominators", { min = 1, what = "an array of nominators" })
+    local who = {}
+    for i, n in ipairs(nominators) do who[i] = Validate.account(n, "nominators." .. i) end
+    local meta = api:get_metadata()
+    if not call_fields(meta, "kick") then error("Staking.kick is not available on this runtime") end
+    return sign(api, signer, Call.encode_call_json(meta, "Staking", "kick", { who = who }), opts)
+end
+
+-- Staking.Nominators has no index by validator: finding a validator's nominators reads
+-- the whole map (every nominator on the chain, page_size keys per RPC round trip). Build
+-- the reverse index once with Staking.nominators_index when asking about several validators
+
+-- Calls fn(target, nominator) for every target of every nomination at one block
+-- Returns the block hash read
+local function each_nomination(api, opts, fn)
+    opts = opts or {}
+    local at = opts.at or api:chain_getBlockHash()
+    local entries = Storage.entries(api, "Staking", "Nominators", nil,
+        { at = at, page_size = opts.page_size, cancel = opts.cancel })
+    for _, e in ipairs(entries) do
+        local nominator = {
+            who = e.keys[1],
+            targets = #e.value.targets,
+            submitted_in = e.value.submitted_in,
+            suppressed = e.value.suppressed
+        }
+        local seen = {}
+        for _, target in ipairs(e.value.targets) do
+            if not seen[target] then
+                seen[target] = true
+                fn(target, nominator)
+            end
+        end
+    end
+    return at
+end
+
+-- Reverse index of Staking.Nominators, read in full at one block
+-- opts: { at = best block, page_size = 1000, cancel = nil } as for Storage.entries
+--   at: block hash; the best block is taken once so every page reads the same state
+-- Returns { at = block hash, by_target = { [validator "0x" key] = nominators } } with
+-- nominators as returned by Staking.query_nominators_of
+function Staking.nominators_index(api, opts)
+    local by_target = {}
+    local at = each_nomination(api, opts, function(target, nominator)
+        local list = by_target[target]
+        if not list then
+            list = {}
+            by_target[target] = list
+        end
+        table.insert(list, nominator)
+    end)
+    return { at = at, by_target = by_target }
+end
+
+-- Accounts currently nominating a validator (from Staking.Nominators)
+-- validator: stash as SS58 address or 0x public key
+-- opts: { at = best block, page_size = 1000, cancel = nil, index = nil }
+--   at, page_size, cancel: as for Staking.nominators_index; the whole map is read
+--   index: a Staking.nominators_index result to look the validator up in instead
+-- Returns { { who, targets, submitted_in, suppressed }, ... } in storage order; who is the
+-- nominator's "0x" public key, targets the number of validators it nominates
+function Staking.query_nominators_of(api, validator, opts)
+    opts = opts or {}
+    local stash = Validate.account(validator, "validator")
+    if opts.index then return opts.index.by_target[stash] or {} end
+    local found = {}
+    each_nomination(api, opts, function(target, nominator)
+        if target == stash then table.insert(found, nominator) end
+    end)
+    return found
+end
+
+-- ============================================================
+-- Payouts
+-- A validator's reward for an era is paid page by page: ErasStakersOverview holds the
+-- page count of its exposure and ClaimedRewards the pages already paid
+-- ============================================================
+
+local function client(node)
+    if type(node) == "string" then return require("sublua.rpc").new(node) end
+    return node
+end
+
+-- Keypair from a keypair, a secret URI ("//Alice"), a 0x seed or a BIP-39 phrase;
+-- password is the phrase's BIP-39 passphrase (ignored for 0x seeds)
+local function keypair(api, signer, password)
+    if type(signer) ~= "string" then return require("sublua.transaction").signer_of(api, signer) end
+    local Keyring = require("sublua.keyring")
+    if signer:match("^//") then return Keyring.from_uri(signer, { password = password }) end
+    if signer:match("^0x" .. string.rep("%x", 64) .. "$") then return Keyring.from_seed(signer) end
+    return Keyring.from_mnemonic(signer, { password = password })
+end
+
+local function history_depth(meta)
+    local c = Metadata.get_constant(meta, "Staking", "HistoryDepth")
+    if not c then return DEFAULT_HISTORY_DEPTH end
+    return Codec.decode(meta, c.type_id, c.value)
+end
+
+-- Era pages of a validator's rewards that nobody has paid out yet
+-- api: RPC client; validator: stash as SS58 address or 0x public key
+-- opts: { at = best block, history_depth = Staking.HistoryDepth }
+-- Only eras still in history (the last history_depth ones before the active era) that
+-- have a reward (ErasValidatorReward) and an exposure for the validator are looked at
+-- Returns { { era, page }, ... } oldest first
+function Staking.pending_payouts(api, validator, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local stash = Validate.account(validator, "validator")
+    local _, _, err = Metadata.get_storage_entry(meta, "Staking", "ErasStakersOverview")
+    if err then error("Staking.ErasStakersOverview: " .. err .. " (the runtime predates paged rewards)") end
+
+    local active = Storage.fetch(api, "Staking", "ActiveEra", {}, opts.at)
+    if not active then return {} end
+    local depth = opts.history_depth or history_depth(meta)
+    local pending = {}
+    for era = math.max(0, active.index - depth), active.index - 1 do
+        local overview = Storage.fetch(api, "Staking", "ErasValidatorReward", { era }, opts.at)
+            and Storage.fetch(api, "Staking", "ErasStakersOverview", { era, stash }, opts.at)
+        if overview then
+            local claimed = {}
+            for _, page in ipairs(Storage.fetch(api, "Staking", "ClaimedRewards", { era, stash }, opts.at) or {}) do
+                claimed[page] = true
+            end
+            for page = 0, overview.page_count - 1 do
+                if not claimed[page] then table.insert(pending, { era = era, page = page }) end
+            end
+        end
+    end
+    return pending
+end
+
+-- Pay out every pending era page of a validator's rewards (see Staking.pending_payouts)
+-- node: RPC client or URL
+-- signer: keypair, secret URI, 0x seed or BIP-39 phrase; anyone may pay out, the rewards
+--   go to the stakers either way (nil uses the client's signer)
+-- validator: stash as SS58 address or 0x public key
+-- opts: { at = nil, history_depth = nil, password = nil, kind = "batch_all", auto_split = false, ... }
+--   password is the BIP-39 passphrase of a phrase or URI signer; the calls are batched with Utility.submit_batch (kind, auto_split, ratio and the
+--   Transaction.submit options apply); a single payout is submitted on its own
+-- Uses payout_stakers_by_page, or payout_stakers (one call per era) where the runtime
+-- has no paged call
+-- Returns hashes, reports, pending ({ { era, page }, ... }); all empty when nothing is owed,
+-- hashes nil when a submission was rejected
+function Staking.payout_pending_rewards(node, signer, validator, opts)
+    opts = opts or {}
+    local api = client(node)
+    signer = keypair(api, signer, opts.password)
+    local meta = api:get_metadata()
+    local pending = Staking.pending_payouts(api, validator, opts)
+    if #pending == 0 then return {}, {}, pending end
+
+    local stash = Validate.account(validator, "validator")
+    local staking = meta.pallets["Staking"]
+    local paged = staking.calls and staking.calls["payout_stakers_by_page"]
+    local calls, seen = {}, {}
+    for _, p in ipairs(pending) do
+        if paged then
+            table.insert(calls, Call.encode_call(meta, "Staking", "payout_stakers_by_page",
+                { validator_stash = stash, era = p.era, page = p.page }))
+        elseif not seen[p.era] then
+            seen[p.era] = true
+            table.insert(calls, Call.encode_call(meta, "Staking", "payout_stakers", { validator_stash = stash, era = p.era }))
+        end
+    end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.at, o.history_depth, o.password = nil, nil, nil
+    if #calls == 1 then
+        o.kind, o.auto_split, o.ratio = nil, nil, nil
+        local hash, report = require("sublua.transaction").submit(api, signer, calls[1], o)
+        return hash and { hash } or nil, { report }, pending
+    end
+    local hashes, reports = require("sublua.utility").submit_batch(api, signer, calls, o)
+    return hashes, reports, pending
+end
+
+return Staking
@@ -0,0 +1,452 @@
+-- sublua/storage.lua
+-- Storage key analysis against runtime metadata
+-- Keys are Twox128(prefix) + Twox128(entry) + hasher(key1) + hasher(key2) ...
+
+local crypto = require("polkadot_crypto")
+local Cancel = require("sublua.cancel")
+local Codec = require("sublua.codec")
+local Introspect = require("sublua.introspect")
+local Metadata = require("sublua.metadata")
+local Trie = require("sublua.trie")
+
+local Storage = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- StorageHasher indices from metadata
+-- hash_len: bytes of hash output; concat: raw key appended after the hash
+-- alg: algorithm name for crypto.hash
+Storage.HASHERS = {
+    [0] = { name = "Blake2_128", hash_len = 16, concat = false, alg = "blake2_128" },
+    [1] = { name = "Blake2_256", hash_len = 32, concat = false, alg = "blake2_256" },
+    [2] = { name = "Blake2_128Concat", hash_len = 16, concat = true, alg = "blake2_128_concat" },
+    [3] = { name = "Twox128", hash_len = 16, concat = false, alg = "twox128" },
+    [4] = { name = "Twox256", hash_len = 32, concat = false, alg = "twox256" },
+    [5] = { name = "Twox64Concat", hash_len = 8, concat = true, alg = "twox64_concat" },
+    [6] = { name = "Identity", hash_len = 0, concat = true, alg = "identity" }
+}
+
+-- Key type ids per hasher: a single key type, or the members of a tuple key
+local function key_types(meta, storage_type)
+    local n = #storage_type.hashers
+    if n == 1 then
+        return { storage_type.key }
+    end
+    local def = Codec.resolve(meta, storage_type.key).type_def
+    assert(def.type == "Tuple" and #def.types == n, "Storage key tuple does not match hashers")
+    return def.types
+end
+
+-- Twox128(prefix) .. Twox128(entry) -> { pallet, entry } lookup, cached per metadata table
+local prefix_cache = setmetatable({}, { __mode = "k" })
+
+local function prefix_table(meta)
+    local cached = prefix_cache[meta]
+    if cached then return cached end
+    cached = {}
+    for pallet_name, pallet in pairs(meta.pallets) do
+        if pallet.storage then
+            local p = crypto.twox128(pallet.storage.prefix)
+            for entry_name, entry in pairs(pallet.storage.entries) do
+                cached[p .. crypto.twox128(entry_name)] = { pallet = pallet_name, entry = entry }
+            end
+        end
+    end
+    prefix_cache[meta] = cached
+    return cached
+end
+
+-- Build the raw storage key for an entry, ready for state_getStorage
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- keys: list of map keys as codec values (see sublua.codec); may be shorter
+--   than the number of hashers to build a prefix for state_getKeysPaged
+-- Returns "0x" key hex
+function Storage.build_key(meta_source, pallet, entry_name, keys)
+    local meta = Metadata.from_source(meta_source)
+    local entry, prefix, err = Metadata.get_storage_entry(meta, pallet, entry_name)
+    if err then error(pallet .. "." .. entry_name .. ": " .. err) end
+    keys = keys or {}
+
+    local key = crypto.twox128(prefix) .. crypto.twox128(entry.name)
+    if entry.storage_type.type == "Plain" then
+        assert(#keys == 0, pallet .. "." .. entry_name .. " is a plain value and takes no keys")
+        return "0x" .. to_hex(key)
+    end
+
+    local hashers = entry.storage_type.hashers
+    assert(#keys <= #hashers, pallet .. "." .. entry_name .. " takes at most " .. #hashers .. " keys")
+    local types = key_types(meta, entry.storage_type)
+    for i, value in ipairs(keys) do
+        local hasher = assert(Storage.HASHERS[hashers[i]], "Unknown hasher index " .. tostring(hashers[i]))
+        key = key .. crypto.hash(hasher.alg, Codec.encode(meta, types[i], value))
+    end
+    return "0x" .. to_hex(key)
+end
+
+-- Read and decode a storage value
+-- api: RPC client; keys: full list of map keys (none for plain values)
+-- at: block hash (default: best block)
+-- Returns the decoded value; for absent values, the metadata default of "Default" entries
+--   and nil for "Optional" ones
+function Storage.fetch(api, pallet, entry_name, keys, at)
+    local meta = api:get_metadata()
+    local entry = Metadata.get_storage_entry(meta, pallet, entry_name)
+    local raw = api:state_getStorage(Storage.build_key(meta, pallet, entry_name, keys), at)
+    if type(raw) == "string" and raw ~= "0x" then
+        return Codec.decode_hex(meta, entry.storage_type.value, raw)
+    end
+    if entry.modifier == 1 and entry.default and #entry.default > 0 then
+        return (Codec.decode(meta, entry.storage_type.value, entry.default, 1))
+    end
+    return nil
+end
+
+-- List every entry of a map (or the entries under a partial key)
+-- api: RPC client; keys: leading map keys to iterate under (default: the whole map)
+-- opts: { at = best block, page_size = 1000, cancel = nil }
+--   cancel: token (see sublua.cancel), checked before each page; raises a "Cancelled:" error
+-- Returns { { keys = { decoded key, ... }, value = decoded value }, ... } in key order
+--   keys hold nil for opaque hashers (Blake2_128, Blake2_256, Twox128, Twox256)
+function Storage.entries(api, pallet, entry_name, keys, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local entry = Metadata.get_storage_entry(meta, pallet, entry_name)
+    assert(entry and entry.storage_type.type ~= "Plain", pallet .. "." .. entry_name .. " is not a map")
+    local prefix = Storage.build_key(meta, pallet, entry_name, keys)
+    local page_size = opts.page_size or 1000
+
+    local all, start = {}, nil
+    local what = "listing " .. pallet .. "." .. entry_name
+    repeat
+        Cancel.raise(opts.cancel, what)
+        local page = api:state_getKeysPaged(prefix, page_size, start, opts.at) or {}
+        for _, k in ipairs(page) do table.insert(all, k) end
+        start = page[#page]
+    until #page < page_size
+
+    local values = {}
+    for i = 1, #all, page_size do
+        Cancel.raise(opts.cancel, what)
+        local chunk = {}
+        for j = i, math.min(i + page_size - 1, #all) do chunk[#chunk + 1] = all[j] end
+        for _, set in ipairs(api:state_queryStorageAt(chunk, opts.at) or {}) do
+            for _, change in ipairs(set.changes or {}) do
+                if type(change[2]) == "string" and change[2] ~= "0x" then values[change[1]] = change[2] end
+            end
+        end
+    end
+
+    local out = {}
+    for _, k in ipairs(all) do
+        if values[k] then
+            local decoded = assert(Storage.decode_key(meta, k))
+            local key_values = {}
+            for i, item in ipairs(decoded.keys) do key_values[i] = item.value end
+            table.insert(out, { keys = key_values, value = Codec.decode_hex(meta, entry.storage_type.value, values[k]) })
+        end
+    end
+    return out
+end
+
+-- ============================================================
+-- Batch Reader
+-- Repeated reads of one entry (e.g. thousands of balance checks per second):
+-- storage keys are hashed once per account and results land in a caller-owned table
+-- ============================================================
+
+local Reader = {}
+Reader.__index = Reader
+
+-- api: RPC client; pallet/entry_name: a map entry or plain value
+-- opts: { raw = false, max_cached_keys = 10000 }
+--   raw: return value hex instead of decoded values (skips decoding entirely)
+--   max_cached_keys: hashed keys kept for string/number key values; the cache is
+--     dropped when full
+function Storage.batch_reader(api, pallet, entry_name, opts)
+    opts = opts or {}
+    local meta = api:get_metadata()
+    local entry, _, err = Metadata.get_storage_entry(meta, pallet, entry_name)
+    if err then error(pallet .. "." .. entry_name .. ": " .. err) end
+    local default
+    if entry.modifier == 1 and entry.default and #entry.default > 0 then default = entry.default end
+    return Introspect.track("storage_readers", setmetatable({
+        api = api, meta = meta, pallet = pallet, entry_name = entry_name,
+        value_type = entry.storage_type.value, default = default,
+        raw = opts.raw or false,
+        max_cached = opts.max_cached_keys or 10000,
+        keys = {}, cached = 0,
+        batch = {}
+    }, Reader), function(reader, stats)
+        stats.cached_storage_keys = stats.cached_storage_keys + reader.cached
+    end)
+end
+
+function Reader:key(key)
+    local simple = type(key) == "string" or type(key) == "number"
+    local hit = simple and self.keys[key]
+    if hit then return hit end
+    local storage_key = Storage.build_key(self.meta, self.pallet, self.entry_name, key ~= nil and { key } or {})
+    if simple then
+        if self.cached >= self.max_cached then
+            self.keys, self.cached = {}, 0
+        end
+        self.keys[key] = storage_key
+        self.cached = self.cached + 1
+    end
+    return storage_key
+end
+
+-- Read many values in one state_queryStorageAt call
+-- keys: list of map keys (one per read; a table of keys for multi-key maps is not
+--   cached), or a count of reads for plain values
+-- out: table to fill (cleared first); reuse it across calls to avoid reallocating
+-- at: block hash (default: best block)
+-- Returns out, with out[i] the value for keys[i] (nil when absent with no default)
+function Reader:read(keys, out, at)
+    out = out or {}
+    for k in pairs(out) do out[k] = nil end
+    local batch = self.batch
+    for i = #batch, 1, -1 do batch[i] = nil end
+
+    local count = type(keys) == "number" and keys or #keys
+    for i = 1, count do
+        local key = type(keys) == "table" and keys[i] or nil
+        local storage_key = type(key) == "table" and Storage.build_key(self.meta, self.pallet, self.entry_name, key)
+            or self:key(key)
+        batch[i] = storage_key
+    end
+    if count == 0 then return out end
+
+    local values = {}
+    for _, set in ipairs(self.api:state_queryStorageAt(batch, at) or {}) do
+        for _, change in ipairs(set.changes or {}) do
+            if type(change[2]) == "string" and change[2] ~= "0x" then values[change[1]] = change[2] end
+        end
+    end
+    for i = 1, count do
+        local raw = values[batch[i]]
+        if self.raw then
+            out[i] = raw or (self.default and ("0x" .. to_hex(self.default))) or nil
+        elseif raw then
+            out[i] = Codec.decode_hex(self.meta, self.value_type, raw)
+        elseif self.default then
+            out[i] = (Codec.decode(self.meta, self.value_type, self.default, 1))
+        end
+    end
+    return out
+end
+
+-- Identify which pallet/entry a raw storage key belongs to and recover its keys
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- Returns { pallet, entry, keys = { { hasher, hash, value } }, partial } or nil, err
+--   value is nil for opaque hashers (Blake2_128, Blake2_256, Twox128, Twox256)
+--   partial is true when the key is a prefix covering only some of the map keys
+function Storage.decode_key(meta_source, key_hex)
+    local meta = Metadata.from_source(meta_source)
+    local key = from_hex(key_hex)
+    if #key < 32 then
+        return nil, "Storage key shorter than pallet/entry prefix"
+    end
+
+    local found = prefix_table(meta)[key:sub(1, 32)]
+    if not found then
+        return nil, "Unknown storage prefix"
+    end
+
+    local entry = found.entry
+    local result = { pallet = found.pallet, entry = entry.name, keys = {}, partial = false }
+    local offset = 33
+
+    if entry.storage_type.type == "Plain" then
+        if offset <= #key then return nil, "Trailing bytes after plain storage key" end
+        return result
+    end
+
+    local types = key_types(meta, entry.storage_type)
+    for i, hasher_index in ipairs(entry.storage_type.hashers) do
+        if offset > #key then
+            result.partial = true
+            break
+        end
+        local hasher = Storage.HASHERS[hasher_index]
+        if not hasher then return nil, "Unknown hasher index " .. tostring(hasher_index) end
+        if offset + hasher.hash_len - 1 > #key then
+            return nil, "Storage key truncated inside " .. hasher.name .. " hash"
+        end
+
+        local item = { hasher = hasher.name }
+        if hasher.hash_len > 0 then
+            item.hash = "0x" .. to_hex(key:sub(offset, offset + hasher.hash_len - 1))
+        end
+        offset = offset + hasher.hash_len
+
+        if hasher.concat then
+            local ok, value, next_offset = pcall(Codec.decode, meta, types[i], key, offset)
+            if not ok then return nil, "Failed to decode key " .. i .. ": " .. tostring(value) end
+            item.value = value
+            offset = next_offset
+        end
+        result.keys[i] = item
+    end
+
+    if offset <= #key then
+        return nil, "Trailing bytes after storage key"
+    end
+    return result
+end
+
+-- ============================================================
+-- Diffs
+-- What changed in storage between two blocks, for audits and for seeing what a runtime
+-- upgrade or an extrinsic actually touched
+-- ============================================================
+
+-- Raw values of keys at a block, chunked through state_queryStorageAt
+local function raw_values(api, keys, at, chunk_size)
+    local values = {}
+    for i = 1, #keys, chunk_size do
+        local chunk = {}
+        for j = i, math.min(i + chunk_size - 1, #keys) do chunk[#chunk + 1] = keys[j] end
+        for _, set in ipairs(api:state_queryStorageAt(chunk, at) or {}) do
+            for _, change in ipairs(set.changes or {}) do
+                if type(change[2]) == "string" and change[2] ~= "0x" then values[change[1]] = change[2] end
+            end
+        end
+    end
+    return values
+end
+
+-- Key hex and whether it is a prefix, for one diff target
+local function diff_target(meta, target, path)
+    if type(target) == "string" then
+        if not target:match("^0x%x*$") or #target % 2 ~= 0 then error(path .. ": expected 0x key hex, got '" .. target .. "'", 0) end
+        local decoded = Storage.decode_key(meta, target)
+        return target:lower(), decoded ~= nil and decoded.partial
+    elseif type(target) == "table" and target.prefix then
+        return target.prefix:lower(), true
+    elseif type(target) == "table" and (target.pallet or target[1]) then
+        local pallet, entry_name, keys = target.pallet or target[1], target.entry or target[2], target.keys or target[3] or {}
+        local entry = Metadata.get_storage_entry(meta, pallet, entry_name)
+        if not entry then error(path .. ": no storage entry " .. tostring(pallet) .. "." .. tostring(entry_name), 0) end
+        local key = Storage.build_key(meta, pallet, entry_name, keys)
+        return key, entry.storage_type.type ~= "Plain" and #keys < #entry.storage_type.hashers
+    end
+    error(path .. ": expected 0x key hex, { prefix } or { pallet, entry, keys }", 0)
+end
+
+-- Storage that differs between two blocks
+-- api: RPC client or node URL
+-- targets: list (or JSON text of one) of exact keys as "0x" hex, { prefix = "0x.." },
+--   or { pallet, entry, keys } (a map with fewer keys than it takes is a prefix);
+--   "0x" hex that is the start of a map's keys counts as a prefix
+-- opts: { page_size = 1000, max_keys = 10000 }: keys under prefixes are listed at both
+--   blocks; more than max_keys is an error instead of a diff of part of the state
+-- Returns a list, in key order, of { key, kind = "added" | "removed" | "changed", pallet,
+--   entry, keys, before, after, before_raw, after_raw }; pallet/entry/keys/before/after are
+--   decoded when the key belongs to a metadata entry (keys as in Storage.entries), else
+--   before/after are the raw hex
+function Storage.diff(api, targets, block_a, block_b, opts)
+    opts = opts or {}
+    if type(api) == "string" then api = require("sublua.rpc").new(api) end
+    local meta = api:get_metadata()
+    if type(targets) == "string" then
+        local ok, decoded = pcall(require("cjson").decode, targets)
+        if not ok or type(decoded) ~= "table" then error("targets must be a JSON array of keys or prefixes") end
+        targets = decoded
+    end
+    local page_size = opts.page_size or 1000
+    local max_keys = opts.max_keys or 10000
+
+    local keys, seen = {}, {}
+    local function add(key)
+        if seen[key] then return end
+        seen[key] = true
+        table.insert(keys, key)
+        if #keys > max_keys then error("More than " .. max_keys .. " keys to compare; narrow the prefixes or raise max_keys") end
+    end
+    for i, target in ipairs(targets) do
+        local key, is_prefix = diff_target(meta, target, "targets." .. i)
+        if is_prefix then
+            for _, at in ipairs({ block_a, block_b }) do
+                local start
+                repeat
+                    local page = api:state_getKeysPaged(key, page_size, start, at) or {}
+                    for _, k in ipairs(page) do add(k:lower()) end
+                    start = page[#page]
+                until #page < page_size
+            end
+        else
+            add(key)
+        end
+    end
+    table.sort(keys)
+
+    local before = raw_values(api, keys, block_a, page_size)
+    local after = raw_values(api, keys, block_b, page_size)
+    local out = {}
+    for _, key in ipairs(keys) do
+        local a, b = before[key], after[key]
+        if a ~= b then
+            local change = {
+                key = key,
+                kind = (a == nil and "added") or (b == nil and "removed") or "changed",
+                before_raw = a, after_raw = b, before = a, after = b
+            }
+            local decoded = Storage.decode_key(meta, key)
+            if decoded and not decoded.partial then
+                local entry = Metadata.get_storage_entry(meta, decoded.pallet, decoded.entry)
+                change.pallet, change.entry, change.keys = decoded.pallet, decoded.entry, {}
+                for i, item in ipairs(decoded.keys) do change.keys[i] = item.value end
+                change.before = a and Codec.decode_hex(meta, entry.storage_type.value, a)
+                change.after = b and Codec.decode_hex(meta, entry.storage_type.value, b)
+            end
+            table.insert(out, change)
+        end
+    end
+    return out
+end
+
+-- ============================================================
+-- Read Proofs
+-- ============================================================
+
+-- Fetch a Merkle proof for storage keys from a node
+-- api: RPC client (from rpc.new(url)); keys: list of "0x" key hex
+-- block_hash: block to prove against (default: best block)
+-- Returns { at = block hash, proof = { "0x" node hex, ... } }
+function Storage.get_read_proof(api, keys, block_hash)
+    return api:state_getReadProof(keys, block_hash)
+end
+
+-- Check a storage value against a proof, without trusting the node that served it
+-- state_root: "0x" hex from a header you trust (not from the same provider's say-so)
+-- proof: result of get_read_proof, or its list of node hex
+-- expected_value: "0x" value hex, or nil to prove the key is absent
+-- Returns true, or false, err
+function Storage.verify_read_proof(state_root, proof, key_hex, expected_value)
+    local nodes = proof.proof or proof
+    local ok, value = pcall(Trie.read_proof, from_hex(state_root), nodes, from_hex(key_hex))
+    if not ok then
+        return false, tostring(value)
+    end
+    if expected_value == nil then
+        if value ~= nil then return false, "Key is present in the proof" end
+        return true
+    end
+    if value == nil then
+        return false, "Key is absent in the proof"
+    end
+    if value ~= from_hex(expected_value) then
+        return false, "Value mismatch: proof has 0x" .. to_hex(value)
+    end
+    return true
+end
+
+return Storage
@@ -0,0 +1,289 @@
+-- sublua/subscription.lua
+-- Finalized block subscriptions over the polling HTTP client: each new block, its events
+-- and the watched storage entries that changed in it, delivered once and in order
+-- There is no server-side subscription to lose when the connection drops: the subscription
+-- keeps the last block it delivered, and once the node answers again it reports
+-- on_resubscribed and replays every finalized block it missed in the meantime
+
+local json = require("cjson")
+local Codec = require("sublua.codec")
+local Events = require("sublua.events")
+local Introspect = require("sublua.introspect")
+local Metadata = require("sublua.metadata")
+local Storage = require("sublua.storage")
+
+local Subscription = {}
+Subscription.__index = Subscription
+
+local Queue = {}
+Queue.__index = Queue
+
+local function finalized_number(api)
+    local header = api:chain_getHeader(api:chain_getFinalizedHead())
+    return tonumber((header.number:gsub("^0x", "")), 16)
+end
+
+-- Subscribe to finalized blocks
+-- handlers: {
+--   on_block(block),             -- block = { number, hash }
+--   on_events(events, block),    -- decoded events of the block (see Events.fetch)
+--   on_storage(changes, block),  -- watched entries that changed in the block, only when some did:
+--                                --   { { pallet, entry, keys, value, previous }, ... }
+--   on_resubscribed(info)        -- the node answers again after failed polls, before the missed
+--                                --   blocks replay: { last_block, head, missed, error }
+-- }
+-- opts: { last_block = nil, storage = {}, batch_blocks = 100 }
+--   last_block: last block already handled, e.g. a saved cursor (see Subscription:cursor);
+--     default: blocks finalized after the first poll
+--   storage: entries to watch, { { pallet, entry, keys }, ... } (keys as Storage.fetch)
+--   batch_blocks: blocks delivered per poll at most
+function Subscription.new(api, handlers, opts)
+    opts = opts or {}
+    local watches = {}
+    for i, w in ipairs(opts.storage or {}) do
+        watches[i] = {
+            pallet = w[1] or w.pallet,
+            entry = w[2] or w.entry,
+            keys = w[3] or w.keys or {},
+            key = Storage.build_key(api, w[1] or w.pallet, w[2] or w.entry, w[3] or w.keys)
+        }
+    end
+    return Introspect.track("subscriptions", setmetatable({
+        api = api,
+        handlers = handlers or {},
+        last_block = opts.last_block,
+        watches = watches,
+        batch_blocks = opts.batch_blocks or 100,
+        down = nil  -- Error of the poll that failed, until the node answers again
+    }, Subscription))
+end
+
+-- Last block delivered, to save and pass back as opts.last_block after a restart
+function Subscription:cursor()
+    return self.last_block
+end
+
+local function decode_value(meta, watch, raw)
+    local entry = Metadata.get_storage_entry(meta, watch.pallet, watch.entry)
+    if type(raw) == "string" and raw ~= "0x" then
+        return Codec.decode_hex(meta, entry.storage_type.value, raw)
+    end
+    if entry.modifier == 1 and entry.default and #entry.default > 0 then
+        return (Codec.decode(meta, entry.storage_type.value, entry.default, 1))
+    end
+    return nil
+end
+
+local function raw_value(raw)
+    return type(raw) == "string" and raw ~= "0x" and raw or nil
+end
+
+-- Everything delivered for block n, fetched before any handler runs
+local function load(self, n)
+    local api = self.api
+    local block = { number = n, hash = api:chain_getBlockHash(n) }
+    local events = self.handlers.on_events and Events.fetch(api, block.hash)
+    local changes, raws = {}, {}
+    if #self.watches > 0 then
+        local meta = api:get_metadata()
+        for i, watch in ipairs(self.watches) do
+            local before = watch.raw
+            if not watch.known then
+                before = n > 0 and raw_value(api:state_getStorage(watch.key, api:chain_getBlockHash(n - 1))) or nil
+            end
+            raws[i] = raw_value(api:state_getStorage(watch.key, block.hash))
+            if raws[i] ~= before then
+                table.insert(changes, {
+                    pallet = watch.pallet,
+                    entry = watch.entry,
+                    keys = watch.keys,
+                    value = decode_value(meta, watch, raws[i]),
+                    previous = decode_value(meta, watch, before)
+                })
+            end
+        end
+    end
+    return block, events, changes, raws
+end
+
+local function fail(self, err)
+    self.down = err
+    return nil, err
+end
+
+-- Deliver the blocks finalized since the last poll, up to batch_blocks of them
+-- A block counts as delivered once its handlers return; an error from a handler is raised
+-- and the block is delivered again by the next poll
+-- Returns the number of blocks delivered, or nil, err when the node could not be reached
+--   (blocks before the failing one stay delivered; the next poll resumes after them)
+function Subscription:poll()
+    local ok, head = pcall(finalized_number, self.api)
+    if not ok then return fail(self, head) end
+    if not self.last_block then self.last_block = head end
+    local handlers = self.handlers
+    if self.down then
+        local info = {
+            last_block = self.last_block,
+            head = head,
+            missed = math.max(head - self.last_block, 0),
+            error = self.down
+        }
+        self.down = nil
+        if handlers.on_resubscribed then handlers.on_resubscribed(info) end
+    end
+    local count = 0
+    for n = self.last_block + 1, math.min(head, self.last_block + self.batch_blocks) do
+        local loaded, block, events, changes, raws = pcall(load, self, n)
+        if not loaded then return fail(self, "Block " .. n .. ": " .. tostring(block)) end
+        if handlers.on_block then handlers.on_block(block) end
+        if handlers.on_events then handlers.on_events(events, block) end
+        if #changes > 0 and handlers.on_storage then handlers.on_storage(changes, block) end
+        for i, watch in ipairs(self.watches) do
+            watch.raw, watch.known = raws[i], true
+        end
+        self.last_block = n
+        count = count + 1
+    end
+    return count
+end
+
+-- Keep polling as blocks are finalized
+-- opts: { poll_interval = 6, sleep = nil, stop = nil, on_error = nil }
+--   sleep: function(seconds), defaults to socket.sleep
+--   stop(): checked after every poll; return true to leave the loop
+--   on_error(err): called when a poll fails; the loop retries after poll_interval
+function Subscription:run(opts)
+    opts = opts or {}
+    local sleep = opts.sleep or require("socket").sleep
+    local interval = opts.poll_interval or 6
+    while true do
+        local count, err = self:poll()
+        if not count and opts.on_error then opts.on_error(err) end
+        if opts.stop and opts.stop() then return end
+        -- Still catching up: go again straight away
+        if count ~= self.batch_blocks then sleep(interval) end
+    end
+end
+
+-- ============================================================
+-- Event Queue
+-- A pull alternative to handlers: events of finalized blocks wait in a queue (optionally
+-- a JSON file) until the consumer acknowledges them, so a host that crashes between
+-- events_poll and events_ack is handed the same events again (at-least-once delivery)
+-- ============================================================
+
+-- Open an event queue
+-- opts: {
+--   state_file = nil,    -- JSON file for the queue and its block cursor (nil keeps it in memory)
+--   last_block = nil,    -- last block already handled when there is no state yet
+--                        --   (default: events of blocks finalized after the first poll)
+--   filter = nil,        -- only queue matching events (Events.filter list or preset name)
+--   batch_blocks = 100,  -- blocks fetched per events_poll at most
+--   max_queued = 10000   -- stop fetching blocks while this many events wait for an ack
+-- }
+function Subscription.queue(api, opts)
+    opts = opts or {}
+    local self = setmetatable({
+        api = api,
+        state_file = opts.state_file,
+        filter = opts.filter,
+        batch_blocks = opts.batch_blocks or 100,
+        max_queued = opts.max_queued or 10000,
+        state = { last_block = opts.last_block, next_seq = 1, events = {} }
+    }, Queue)
+    if self.state_file then
+        local f = io.open(self.state_file, "rb")
+        if f then
+            local text = f:read("*a")
+            f:close()
+            local ok, state = pcall(json.decode, text)
+            if not ok or type(state) ~= "table" then error("Unreadable event queue file " .. self.state_file) end
+            self.state = state
+            state.events = state.events or {}
+        end
+    end
+    return Introspect.track("event_queues", self, function(queue, stats)
+        stats.queued_events = stats.queued_events + #queue.state.events
+    end)
+end
+
+function Queue:save()
+    if not self.state_file then return end
+    local tmp = self.state_file .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write event queue: " .. tostring(err)) end
+    f:write(json.encode(self.state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, self.state_file)
+    if not ok then error("Cannot write event queue: " .. tostring(rename_err)) end
+end
+
+-- Fetch newly finalized blocks into the queue (events_poll does this first)
+-- The events and the block cursor are saved together, so a crash never loses a block's events
+-- Returns the number of events queued, or nil, err when the node could not be reached
+function Queue:fill()
+    local state = self.state
+    if #state.events >= self.max_queued then return 0 end
+    local added = 0
+    local sub = Subscription.new(self.api, {
+        on_events = function(events, block)
+            if self.filter then events = Events.filter(events, self.filter) end
+            for _, event in ipairs(events) do
+                table.insert(state.events, {
+                    seq = state.next_seq,
+                    block_number = block.number,
+                    block_hash = block.hash,
+                    event_index = event.index,
+                    extrinsic_index = event.extrinsic_index,
+                    pallet = event.pallet,
+                    name = event.name,
+                    fields = event.fields
+                })
+                state.next_seq = state.next_seq + 1
+                added = added + 1
+            end
+        end
+    }, { last_block = state.last_block, batch_blocks = self.batch_blocks })
+    local count, err = sub:poll()
+    if added > 0 or sub:cursor() ~= state.last_block then
+        state.last_block = sub:cursor()
+        self:save()
+    end
+    if not count then return nil, err end
+    return added
+end
+
+-- Oldest unacknowledged events, after fetching newly finalized blocks
+-- max: events returned at most (default 100); they stay queued until acknowledged
+-- Returns events, cursor, err
+--   events: { { seq, block_number, block_hash, event_index, extrinsic_index, pallet, name, fields }, ... }
+--   cursor: seq of the last event returned (pass it to events_ack), nil when there are none
+--   err: why new blocks could not be fetched; queued events are still returned
+function Queue:events_poll(max)
+    local _, err = self:fill()
+    local out = {}
+    for i = 1, math.min(max or 100, #self.state.events) do out[i] = self.state.events[i] end
+    return out, out[#out] and out[#out].seq, err
+end
+
+-- Acknowledge every event up to and including cursor, removing them from the queue
+-- Returns the number of events still queued
+function Queue:events_ack(cursor)
+    assert(type(cursor) == "number", "events_ack needs the cursor returned by events_poll")
+    local kept = {}
+    for _, event in ipairs(self.state.events) do
+        if event.seq > cursor then table.insert(kept, event) end
+    end
+    if #kept ~= #self.state.events then
+        self.state.events = kept
+        self:save()
+    end
+    return #kept
+end
+
+-- Events waiting for an acknowledgement
+function Queue:pending()
+    return #self.state.events
+end
+
+return Subscription
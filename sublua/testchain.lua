@@ -0,0 +1,194 @@
+-- sublua/testchain.lua
+-- Local test chains for end-to-end tests: start a Substrate dev node (or attach to one
+-- already running, e.g. a zombienet network), wait until it produces blocks, stop it
+-- The dev accounts of the node are sr25519; the keyring's //Alice, //Bob and //Charlie are
+-- not, so the node is started from its dev chain spec with those accounts endowed
+-- Needs a POSIX shell (the node runs in the background) and the node binary on PATH,
+-- or its path in opts.command / SUBLUA_TEST_NODE
+
+local json = require("cjson")
+
+local TestChain = {}
+TestChain.__index = TestChain
+
+TestChain.DEFAULT_COMMAND = "substrate-node"
+TestChain.DEFAULT_PORT = 9944
+-- Planck given to each endowed account (10^6 units at 12 decimals)
+TestChain.DEFAULT_ENDOWMENT = "1000000000000000000"
+
+local AMOUNT_MARK = "__sublua_amount:"
+
+local function quote(s)
+    return "'" .. tostring(s):gsub("'", "'\\''") .. "'"
+end
+
+local function read_file(path)
+    local f = io.open(path, "r")
+    if not f then return nil end
+    local data = f:read("*a")
+    f:close()
+    return data
+end
+
+local function write_file(path, data)
+    local f = assert(io.open(path, "w"))
+    f:write(data)
+    f:close()
+end
+
+-- ============================================================
+-- Chain Spec
+-- ============================================================
+
+-- Genesis balances list of a plain chain spec, whichever layout it uses
+local function balances_of(spec)
+    local genesis = spec.genesis or {}
+    local runtime_genesis = genesis.runtimeGenesis or {}
+    for _, config in ipairs({ runtime_genesis.patch or false, runtime_genesis.config or false, genesis.runtime or false }) do
+        if type(config) == "table" then
+            config.balances = config.balances or {}
+            config.balances.balances = config.balances.balances or {}
+            return config.balances.balances
+        end
+    end
+    error("Chain spec has no runtime genesis to endow accounts in (a raw spec?)")
+end
+
+-- Endow accounts in a plain chain spec
+-- spec: chain spec JSON text
+-- accounts: SS58 addresses or keypairs
+-- amount: planck each, decimal string (default TestChain.DEFAULT_ENDOWMENT)
+-- Returns the patched chain spec JSON text; amounts are written as exact JSON integers
+function TestChain.endow(spec, accounts, amount)
+    amount = tostring(amount or TestChain.DEFAULT_ENDOWMENT)
+    assert(amount:match("^%d+$"), "amount must be a decimal string")
+    local decoded = json.decode(spec)
+    local balances = balances_of(decoded)
+    for _, who in ipairs(accounts) do
+        local address = type(who) == "table" and who.address or who
+        local replaced = false
+        for _, pair in ipairs(balances) do
+            if pair[1] == address then
+                pair[2], replaced = AMOUNT_MARK .. amount, true
+            end
+        end
+        if not replaced then table.insert(balances, { address, AMOUNT_MARK .. amount }) end
+    end
+    -- cjson writes big numbers in exponent form, which the node's genesis parser rejects
+    return (json.encode(decoded):gsub('"' .. AMOUNT_MARK .. '(%d+)"', "%1"))
+end
+
+-- ============================================================
+-- Lifecycle
+-- ============================================================
+
+-- Shell command starting the node with spec_path as its chain
+function TestChain.command(opts, spec_path)
+    local parts = { quote(opts.command), "--dev", "--rpc-port", tostring(opts.port) }
+    if spec_path then
+        table.insert(parts, "--chain")
+        table.insert(parts, quote(spec_path))
+    end
+    for _, arg in ipairs(opts.args or {}) do table.insert(parts, quote(arg)) end
+    return table.concat(parts, " ")
+end
+
+local function best_number(api)
+    local header = api:chain_getHeader()
+    return header and tonumber((header.number or "0x0"):gsub("^0x", ""), 16) or 0
+end
+
+-- Wait until the node answers and has built a block; false on timeout
+local function wait_ready(chain, timeout, sleep)
+    local socket = require("socket")
+    local deadline = socket.gettime() + timeout
+    repeat
+        local ok, number = pcall(best_number, chain.api)
+        if ok and number >= 1 then return true end
+        sleep(0.5)
+    until socket.gettime() > deadline
+    return false
+end
+
+-- Start a dev node, or attach to a running one
+-- opts: { command, port = 9944, args = {}, accounts, amount, url, timeout = 60, sleep = nil }
+--   command: node binary (default: $SUBLUA_TEST_NODE, else "substrate-node")
+--   args: extra node arguments
+--   accounts: addresses or keypairs to endow (default: keyring //Alice, //Bob, //Charlie);
+--     false starts the plain dev chain; an attached chain is used as it is
+--   amount: planck each account gets (default TestChain.DEFAULT_ENDOWMENT)
+--   url: RPC endpoint of a node that is already running (zombienet, a CI service):
+--     nothing is started, and stop leaves it running
+--   timeout: seconds to wait for the first block
+--   sleep: function(seconds), defaults to socket.sleep
+-- Returns a chain { url, api, pid, log, accounts }; raises with the end of the node's
+-- log when it does not come up
+function TestChain.start(opts)
+    opts = opts or {}
+    local sleep = opts.sleep or require("socket").sleep
+    local RPC = require("sublua.rpc")
+    local chain = setmetatable({ accounts = {} }, TestChain)
+
+    local accounts = opts.accounts
+    if accounts == nil then
+        local Keyring = require("sublua.keyring")
+        accounts = { Keyring.from_uri("//Alice"), Keyring.from_uri("//Bob"), Keyring.from_uri("//Charlie") }
+    end
+    chain.accounts = accounts or {}
+
+    if opts.url then
+        chain.url = opts.url
+        chain.api = RPC.new(opts.url)
+        if not wait_ready(chain, opts.timeout or 60, sleep) then
+            error("Test chain at " .. opts.url .. " produced no block in time")
+        end
+        return chain
+    end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.command = o.command or os.getenv("SUBLUA_TEST_NODE") or TestChain.DEFAULT_COMMAND
+    o.port = o.port or TestChain.DEFAULT_PORT
+
+    local spec_path
+    if accounts then
+        local pipe = assert(io.popen(quote(o.command) .. " build-spec --chain dev --disable-default-bootnode 2>/dev/null"))
+        local spec = pipe:read("*a")
+        pipe:close()
+        if spec == nil or spec == "" then error("Could not build the dev chain spec with " .. o.command) end
+        spec_path = os.tmpname()
+        write_file(spec_path, TestChain.endow(spec, accounts, o.amount))
+        chain.spec = spec_path
+    end
+
+    chain.log = os.tmpname()
+    local pid_path = os.tmpname()
+    os.execute(TestChain.command(o, spec_path) .. " > " .. quote(chain.log) .. " 2>&1 & echo $! > " .. quote(pid_path))
+    chain.pid = tonumber((read_file(pid_path) or ""):match("%d+"))
+    os.remove(pid_path)
+    if not chain.pid then error("Could not start " .. o.command) end
+
+    chain.url = "http://127.0.0.1:" .. o.port
+    chain.api = RPC.new(chain.url)
+    if not wait_ready(chain, o.timeout or 60, sleep) then
+        local log = read_file(chain.log) or ""
+        chain:stop()
+        error("Test chain did not produce a block in time; node log ends with:\n" .. log:sub(-2000))
+    end
+    return chain
+end
+
+-- Stop the node (a no-op for attached chains) and remove its chain spec and log
+-- chain:stop() or TestChain.stop(chain)
+-- keep_log: leave the log file in place (its path is chain.log)
+function TestChain:stop(keep_log)
+    if self.pid then
+        os.execute("kill " .. self.pid .. " 2>/dev/null")
+        self.pid = nil
+    end
+    if self.spec then os.remove(self.spec) end
+    if self.log and not keep_log then os.remove(self.log) end
+    self.spec = nil
+end
+
+return TestChain
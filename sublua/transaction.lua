@@ -3,7 +3,12 @@
 
 local Scale = require("sublua.scale")
 local crypto = require("polkadot_crypto")
--- local Metadata = require("sublua.metadata") -- Not strictly needed if we don't access meta directly
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+local Events = require("sublua.events")
+local Call = require("sublua.call")
+local Cancel = require("sublua.cancel")
+local json = require("cjson")
 
 local Transaction = {}
 
@@ -19,7 +24,7 @@ end
 
 -- Extension Handlers
 -- Each handler returns { extra = "...", additional = "..." }
--- p: props (specVersion, txVersion, genesisHash, blockHash, nonce, tip, era, assetId, metadata_hash)
+-- p: props (specVersion, txVersion, genesisHash, finalizedHash, nonce, tip, era, eraBlockHash, assetId, metadata_hash)
 local handlers = {}
 
 handlers["CheckNonZeroSender"] = function(p) return { extra = "", additional = "" } end
@@ -37,10 +42,10 @@ handlers["CheckGenesis"] = function(p)
 end
 
 handlers["CheckMortality"] = function(p)
-    -- Era (Immortal = 0x00)
-    -- Additional: BlockHash (Genesis for immortal)
-    local era = "\0" -- TODO: Support mortal eras
-    local checkpoint = from_hex(p.finalizedHash or p.genesisHash)
+    -- Era: Immortal = 0x00, or packed MortalEra (see Transaction.encode_era)
+    -- Additional: hash of the era's birth block (p.eraBlockHash)
+    local era = Transaction.encode_era(p.era)
+    local checkpoint = from_hex(p.eraBlockHash or p.finalizedHash or p.genesisHash)
     return { extra = era, additional = checkpoint }
 end
 
@@ -87,6 +92,13 @@ handlers["WeightReclaim"] = function(p)
     return { extra = "", additional = "" }
 end
 
+-- Handlers added by applications (Transaction.register_extension); same shape as the
+-- built-in ones, and they take precedence over them
+local registered = {}
+
+-- MultiSignature variant byte per signature scheme
+local MULTI_SIGNATURE = { ed25519 = "\0", sr25519 = "\1" }
+
 -- Default signed extensions for Westend (order matters)
 local DEFAULT_EXTENSIONS = {
     "CheckNonZeroSender",
@@ -100,73 +112,280 @@ local DEFAULT_EXTENSIONS = {
     "CheckMetadataHash"
 }
 
--- Construct a signed extrinsic (V4)
-function Transaction.create_signed(call_hex, signer, nonce, props, extensions)
+-- ============================================================
+-- Mortality
+-- ============================================================
+
+-- Mortal era valid for `period` blocks starting at current_block
+-- period is rounded up to a power of two in [4, 65536] (default 64);
+-- phase is quantized as Substrate does for periods above 4096
+-- Returns { immortal = false, period, phase }
+function Transaction.mortal_era(current_block, period)
+    period = period or 64
+    local p = 4
+    while p < period and p < 65536 do p = p * 2 end
+    local quantize_factor = math.max(math.floor(p / 4096), 1)
+    local phase = math.floor((current_block % p) / quantize_factor) * quantize_factor
+    return { immortal = false, period = p, phase = phase }
+end
+
+-- First block of the era containing current_block (0 for immortal)
+-- Its hash is what CheckMortality signs over (props.eraBlockHash)
+function Transaction.era_birth(era, current_block)
+    if not era or era.immortal then return 0 end
+    return math.floor((math.max(current_block, era.phase) - era.phase) / era.period) * era.period + era.phase
+end
+
+-- SCALE-encode an era: nil / { immortal = true } -> 0x00,
+-- { period, phase } -> 2-byte packed MortalEra (inverse of Transaction.decode_era)
+function Transaction.encode_era(era)
+    if not era or era.immortal then return "\0" end
+    local period, phase = era.period, era.phase
+    local trailing_zeros = math.floor(math.log(period) / math.log(2) + 0.5)
+    assert(period >= 4 and period <= 65536 and 2 ^ trailing_zeros == period,
+        "Era period must be a power of two between 4 and 65536")
+    assert(phase >= 0 and phase < period, "Era phase must be less than period")
+    local quantize_factor = math.max(math.floor(period / 4096), 1)
+    local low = math.min(math.max(trailing_zeros - 1, 1), 15)
+    local encoded = low + math.floor(phase / quantize_factor) * 16
+    return string.char(encoded % 256, math.floor(encoded / 256))
+end
+
+-- ============================================================
+-- Signed Extension Introspection
+-- ============================================================
+
+-- True when a type encodes to zero bytes (unit tuples, marker structs)
+local function is_zero_sized(meta, type_id)
+    local def = Codec.resolve(meta, type_id).type_def
+    if def.type == "Tuple" then
+        for _, id in ipairs(def.types) do
+            if not is_zero_sized(meta, id) then return false end
+        end
+        return true
+    elseif def.type == "Composite" then
+        for _, field in ipairs(def.fields) do
+            if not is_zero_sized(meta, field.type_id) then return false end
+        end
+        return true
+    elseif def.type == "Array" then
+        return def.len == 0 or is_zero_sized(meta, def.type_id)
+    end
+    return false
+end
+
+-- Readable name for a type id: primitive, last path segment, or "()"
+local function describe_type(meta, type_id)
+    local name = Codec.primitive_name(meta, type_id) or Codec.type_name(meta, type_id)
+    if name then return name end
+    local def = Codec.resolve(meta, type_id).type_def
+    if def.type == "Tuple" and #def.types == 0 then return "()" end
+    return def.type
+end
+
+-- List the chain's signed extensions in signing order
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- Returns a list of { identifier, extra_type_id, extra_type, extra_empty,
+--   additional_type_id, additional_type, additional_empty, handled }
+--   handled: SubLua knows how to build this extension's extra/additional data
+function Transaction.get_signed_extensions(meta_source)
+    local meta = Metadata.from_source(meta_source)
+    local list = {}
+    for i, ext in ipairs(meta.extrinsic.signed_extensions) do
+        list[i] = {
+            identifier = ext.identifier,
+            extra_type_id = ext.type_id,
+            extra_type = describe_type(meta, ext.type_id),
+            extra_empty = is_zero_sized(meta, ext.type_id),
+            additional_type_id = ext.additional_signed,
+            additional_type = describe_type(meta, ext.additional_signed),
+            additional_empty = is_zero_sized(meta, ext.additional_signed),
+            handled = handlers[ext.identifier] ~= nil or registered[ext.identifier] ~= nil
+        }
+    end
+    return list
+end
+
+-- Supply the extra / additional-signed bytes of a signed extension, e.g. a chain's own
+-- CheckNonZeroSender-style extension that signs over data
+-- handler: function(props) returning { extra = bytes, additional = bytes } (missing parts are
+--   empty); props are the signing props, so custom fields can be passed through them; nil removes it
+-- With metadata the bytes are checked against the extension's types before anything is signed
+function Transaction.register_extension(identifier, handler)
+    assert(type(identifier) == "string", "Extension identifier must be a string")
+    assert(handler == nil or type(handler) == "function", "Extension handler must be a function")
+    registered[identifier] = handler
+end
+
+-- ============================================================
+-- Signing Payload
+-- ============================================================
+
+-- Error unless bytes are exactly one encoded value of type_id
+local function check_extension_bytes(meta, name, part, type_id, bytes)
+    if type(bytes) ~= "string" then
+        error("Signed extension " .. name .. " handler returned " .. type(bytes) .. " for " .. part)
+    end
+    local ok, _, offset = pcall(Codec.decode, meta, type_id, bytes, 1)
+    if not ok or offset ~= #bytes + 1 then
+        error("Signed extension " .. name .. ": " .. part .. " bytes (" .. #bytes
+            .. ") do not match the metadata type " .. describe_type(meta, type_id))
+    end
+end
+
+-- Build the concatenated extra / additional bytes for a list of extensions
+-- ext_list: identifiers (handlers only, unknown ones skipped) when meta is nil,
+--   otherwise the metadata's signed extension entries
+-- props.extensions[identifier] = { extra = value, additional = value } supplies
+--   codec values for extensions SubLua has no handler for (or overrides one);
+--   a function there is a one-off handler returning bytes, as register_extension
+-- Bytes from handlers are checked against the metadata types when meta is given
+local function build_extensions(meta, ext_list, props)
+    local extra, additional = {}, {}
+    local custom = props.extensions or {}
+
+    for _, ext in ipairs(ext_list) do
+        local name = type(ext) == "table" and ext.identifier or ext
+        local hook = type(custom[name]) == "function" and custom[name] or (not custom[name] and registered[name])
+        local res
+        if hook then
+            local out = hook(props) or {}
+            res = { extra = out.extra or "", additional = out.additional or "" }
+            if meta then
+                check_extension_bytes(meta, name, "extra", ext.type_id, res.extra)
+                check_extension_bytes(meta, name, "additional", ext.additional_signed, res.additional)
+            end
+        elseif custom[name] then
+            assert(meta, "Custom data for signed extension " .. name .. " requires metadata")
+            res = {
+                extra = Codec.encode(meta, ext.type_id, custom[name].extra),
+                additional = Codec.encode(meta, ext.additional_signed, custom[name].additional)
+            }
+        elseif handlers[name] then
+            res = handlers[name](props)
+        elseif meta then
+            if not (is_zero_sized(meta, ext.type_id) and is_zero_sized(meta, ext.additional_signed)) then
+                error("Signed extension " .. name .. " carries data (extra: "
+                    .. describe_type(meta, ext.type_id) .. ", additional: "
+                    .. describe_type(meta, ext.additional_signed)
+                    .. "); pass props.extensions." .. name .. " = { extra = ..., additional = ... }")
+            end
+            res = { extra = "", additional = "" }
+        end
+        -- Without metadata, unknown extensions are assumed empty and skipped
+        if res then
+            table.insert(extra, res.extra)
+            table.insert(additional, res.additional)
+        end
+    end
+    return table.concat(extra), table.concat(additional)
+end
+
+-- Build the payload a signer signs: call | extra | additional, following the
+-- chain's signed extensions from metadata (blake2_256 hashed if over 256 bytes)
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- props: specVersion, txVersion, genesisHash, finalizedHash, nonce, tip, ...
+--   plus optional extensions = { [identifier] = { extra, additional } }
+-- Returns payload, extra (the bytes that also go into the extrinsic), additional
+function Transaction.make_signing_payload(meta_source, call_bytes, props)
+    local meta = Metadata.from_source(meta_source)
+    local extra, additional = build_extensions(meta, meta.extrinsic.signed_extensions, props)
+    local payload = call_bytes .. extra .. additional
+    if #payload > 256 then
+        payload = crypto.blake2b(payload, 32)
+    end
+    return payload, extra, additional
+end
+
+-- Assemble a signed V4 extrinsic from a signature produced elsewhere
+-- (offline / air-gapped signing): compact_length( 0x84 | MultiAddress | MultiSignature | Extra | Call )
+-- scheme: "ed25519" (default) or "sr25519"; extra: as returned by make_signing_payload
+-- Returns "0x" hex; attach_signature_raw returns the bytes
+function Transaction.attach_signature_raw(call_bytes, pubkey, signature, extra, scheme)
+    assert(#pubkey == 32, "pubkey must be 32 bytes")
+    assert(#signature == 64, "signature must be 64 bytes")
+    local variant = assert(MULTI_SIGNATURE[scheme or "ed25519"], "Unsupported signature scheme: " .. tostring(scheme))
+    local inner = string.char(0x84) .. "\0" .. pubkey .. variant .. signature .. extra .. call_bytes
+    return Scale.encode_compact(#inner) .. inner
+end
+
+function Transaction.attach_signature(call_bytes, pubkey, signature, extra, scheme)
+    return "0x" .. to_hex(Transaction.attach_signature_raw(call_bytes, pubkey, signature, extra, scheme))
+end
+
+-- Construct a signed extrinsic (V4) from raw call bytes; returns the extrinsic bytes
+-- For hosts that already hold binary payloads: no hex encoding on the way in or out
+-- extensions: list of extension identifiers (default: DEFAULT_EXTENSIONS), or
+--   parsed metadata to follow the chain's own signed extensions
+function Transaction.create_signed_raw(call_bytes, signer, nonce, props, extensions)
     -- Validate inputs
     assert(signer.pubkey and #signer.pubkey == 32, "signer.pubkey must be 32 bytes")
-    assert(signer.seed and #signer.seed == 32, "signer.seed must be 32 bytes")
-    assert(type(call_hex) == "string", "call_hex must be a string")
-    
-    local call_bytes = from_hex(call_hex)
+    assert(signer.sign or (signer.seed and #signer.seed == 32), "signer needs a sign function or a 32-byte seed")
+    assert(type(call_bytes) == "string", "call_bytes must be a string")
     
     -- Default Props
     props.nonce = nonce
     props.tip = props.tip or 0
     
-    -- Resolve Extensions list
-    local ext_list = extensions or DEFAULT_EXTENSIONS
-    
-    local extra = ""
-    local additional = ""
-    
-    for _, name in ipairs(ext_list) do
-        local h = handlers[name]
-        if h then
-            local res = h(props)
-            extra = extra .. res.extra
-            additional = additional .. res.additional
-        else
-            -- Unknown extensions with empty extra/additional are safe to skip
+    -- Payload = Call | Extra | Additional (hashed first if > 256 bytes)
+    local payload, extra
+    if type(extensions) == "table" and extensions.extrinsic then
+        payload, extra = Transaction.make_signing_payload(extensions, call_bytes, props)
+    else
+        local additional
+        extra, additional = build_extensions(nil, extensions or DEFAULT_EXTENSIONS, props)
+        payload = call_bytes .. extra .. additional
+        if #payload > 256 then
+            payload = crypto.blake2b(payload, 32)
         end
     end
     
-    -- Construct Payload
-    -- Payload = Call | Extra | Additional
-    local payload = call_bytes .. extra .. additional
-    
-    -- Sign Payload
-    -- If > 256 bytes, hash payload first
-    if #payload > 256 then
-        payload = crypto.blake2b(payload, 32)
+    -- Keyring pairs sign locally; external signers (Keyring.from_external) call out
+    local sig
+    if signer.sign then
+        sig = signer:sign(payload)
+    else
+        sig = crypto.ed25519_sign(signer.seed, payload)
     end
     
-    local sig = crypto.ed25519_sign(signer.seed, payload)
-    
-    -- Construct Final Extrinsic
-    -- Format: compact_length( 0x84 | MultiAddress | MultiSignature | Extra | Call )
-    local version = string.char(0x84)
-    local address = "\0" .. signer.pubkey
-    local multi_sig = "\0" .. sig -- Ed25519
-    
-    local inner = version .. address .. multi_sig .. extra .. call_bytes
-    local len = Scale.encode_compact(#inner)
-    
-    return "0x" .. to_hex(len .. inner)
+    return Transaction.attach_signature_raw(call_bytes, signer.pubkey, sig, extra, signer.scheme)
+end
+
+-- Same as create_signed_raw for a "0x" hex call; returns "0x" hex
+function Transaction.create_signed(call_hex, signer, nonce, props, extensions)
+    assert(type(call_hex) == "string", "call_hex must be a string")
+    return "0x" .. to_hex(Transaction.create_signed_raw(from_hex(call_hex), signer, nonce, props, extensions))
+end
+
+-- Signer of a transaction: the one given, else the client's default (RPC:set_signer)
+-- Functions that take api, signer accept nil for signer through this
+function Transaction.signer_of(api, signer)
+    signer = signer or api.signer
+    if not signer then error("No signer: pass one or set a default with api:set_signer") end
+    return signer
 end
 
 -- Production-grade: build signed extrinsic using live chain state
 -- api: RPC client (from rpc.new(url))
--- signer: keyring pair (from keyring.from_seed)
+-- signer: keyring pair (from keyring.from_seed); nil uses the client's default (RPC:set_signer)
 -- call_bytes: raw call bytes (NOT hex)
--- opts: { tip = 0 } optional overrides
+-- opts: { tip = 0, extensions = nil, mortality = nil, nonce = nil } optional overrides
+--   extensions: { [identifier] = { extra, additional } } for chain-specific extensions
+--   mortality: era period in blocks (e.g. 64) for a mortal transaction; immortal if nil
+--   nonce: use this nonce instead of the account's on-chain nonce
+--   raw: return the extrinsic as bytes instead of "0x" hex
+--   confirm: digest of the intent, when the client asks for confirmation (see sublua.safety)
+-- The client's safety settings (transfer limit, allowed destinations) are checked first
 function Transaction.create_signed_from_api(api, signer, call_bytes, opts)
     opts = opts or {}
+    signer = Transaction.signer_of(api, signer)
+    if api.safety then require("sublua.safety").check(api, signer, call_bytes, opts.confirm) end
     
     -- Fetch chain state in parallel-safe order
     local genesis = api:chain_getBlockHash(0)
     local finalized = api:chain_getFinalizedHead()
     local runtime = api:state_getRuntimeVersion()
-    local account = api:system_account(signer.address)
+    api:check_runtime_upgrade(runtime)
     
     -- Get signed extensions from metadata
     local meta = api:get_metadata()
@@ -181,20 +400,697 @@ function Transaction.create_signed_from_api(api, signer, call_bytes, opts)
         txVersion = runtime.transactionVersion,
         genesisHash = genesis,
         finalizedHash = finalized,
-        tip = opts.tip or 0
+        tip = opts.tip or 0,
+        extensions = opts.extensions
     }
     
-    local call_hex = "0x" .. to_hex(call_bytes)
-    local nonce = account.nonce
+    -- Mortal era anchored at the finalized block
+    if opts.mortality then
+        local header = api:chain_getHeader(finalized)
+        local current = tonumber(header.number:gsub("^0x", ""), 16)
+        props.era = Transaction.mortal_era(current, opts.mortality)
+        props.eraBlockHash = api:chain_getBlockHash(Transaction.era_birth(props.era, current))
+    end
+    
+    local nonce = opts.nonce or api:system_account(signer.address).nonce
+    local signed = Transaction.create_signed_raw(call_bytes, signer, nonce, props, meta)
     
-    return Transaction.create_signed(call_hex, signer, nonce, props, ext_list), {
+    return opts.raw and signed or ("0x" .. to_hex(signed)), {
         nonce = nonce,
         specVersion = props.specVersion,
         txVersion = props.txVersion,
         genesisHash = genesis,
         finalizedHash = finalized,
+        era = props.era,
         extensions = ext_list
     }
 end
 
+-- ============================================================
+-- Submission
+-- ============================================================
+
+-- Pool rejections worth rebuilding for: the nonce or era went stale, or the runtime
+-- changed under the signature
+-- "Already Imported" and "Priority is too low" are not among them: the pool already holds
+-- a transaction at that nonce, usually an earlier attempt of this one, and rebuilding on
+-- the next nonce would send the call twice
+Transaction.RETRYABLE_ERRORS = {
+    { reason = "Stale", pattern = "outdated" },
+    { reason = "AncientBirthBlock", pattern = "ancient birth block" },
+    { reason = "BadProof", pattern = "bad signature" },
+    { reason = "TemporarilyBanned", pattern = "temporarily banned" }
+}
+
+local function retryable_reason(err)
+    for _, r in ipairs(Transaction.RETRYABLE_ERRORS) do
+        if err:find(r.pattern, 1, true) then return r.reason end
+    end
+    return nil
+end
+
+-- Build, sign and submit a call, rebuilding with a fresh nonce and era when the pool rejects it
+-- api: RPC client; signer: keyring pair; call_bytes: raw call bytes
+-- opts: create_signed_from_api opts, plus
+--   retry = { attempts = 3, backoff = 1, sleep = nil }: submit once when nil
+--     backoff: seconds before the first retry, doubled after each one
+--     sleep: function(seconds), defaults to socket.sleep
+--   timeout_ms: deadline for the whole call, retries and backoff included; a timeout
+--     ends it with a report.error starting with "Timeout:" (see rpc.is_timeout)
+--   wait: "inclusion" or "finalized" to also wait for the block (see wait_for_inclusion);
+--     the outcome lands in report.result, or report.error if it never showed up
+-- The nonce comes from system_accountNextIndex so pooled transactions are counted;
+-- opts.nonce overrides it for the first attempt only.
+-- Over HTTP only the submission response is seen; a transaction dropped later is not retried.
+-- Returns hash, report; or nil, report when no attempt was accepted
+--   report = { hash, error, extrinsic, from_block, attempts = { { attempt, nonce, era, hash | error, reason } } }
+--   extrinsic/from_block: the accepted extrinsic and the best block number when it was submitted,
+--     so the report can be handed to check_in_block_result
+function Transaction.submit(api, signer, call_bytes, opts)
+    opts = opts or {}
+    signer = Transaction.signer_of(api, signer)
+    if opts.timeout_ms then
+        local o = {}
+        for k, v in pairs(opts) do o[k] = v end
+        o.timeout_ms = nil
+        return api:with_deadline(opts.timeout_ms, function()
+            return Transaction.submit(api, signer, call_bytes, o)
+        end)
+    end
+    local retry = opts.retry or { attempts = 1 }
+    local attempts = retry.attempts or 3
+    local delay = retry.backoff or 1
+    local report = { attempts = {} }
+
+    for attempt = 1, attempts do
+        local entry = { attempt = attempt }
+        table.insert(report.attempts, entry)
+
+        local o = {}
+        for k, v in pairs(opts) do o[k] = v end
+        o.retry, o.raw = nil, nil
+        o.nonce = (attempt == 1 and opts.nonce) or tonumber(api:system_accountNextIndex(signer.address))
+        local built, signed, info = pcall(Transaction.create_signed_from_api, api, signer, call_bytes, o)
+        if not built then
+            entry.error = tostring(signed)
+            report.error = entry.error
+            return nil, report
+        end
+        entry.nonce = info.nonce
+        entry.era = info.era
+
+        local from_block = tonumber((api:chain_getHeader().number:gsub("^0x", "")), 16)
+        local ok, hash = pcall(api.author_submitExtrinsic, api, signed)
+        if ok then
+            entry.hash = hash
+            report.hash = hash
+            report.extrinsic, report.from_block = signed, from_block
+            if opts.wait then
+                report.result, report.error = Transaction.wait_for_inclusion(api, signed, {
+                    finalized = opts.wait == "finalized",
+                    from_block = from_block,
+                    max_blocks = info.era and info.era.period,
+                    sleep = retry.sleep
+                })
+            end
+            return hash, report
+        end
+        entry.error = tostring(hash)
+        entry.reason = retryable_reason(entry.error)
+        report.error = entry.error
+        if not entry.reason or attempt == attempts then
+            return nil, report
+        end
+        local left = api:time_left()
+        if left and left < delay then
+            report.error = "Timeout: no time left to retry after: " .. entry.error
+            return nil, report
+        end
+        local sleep = retry.sleep or require("socket").sleep
+        sleep(delay)
+        delay = delay * 2
+    end
+end
+
+-- ============================================================
+-- Dry Run
+-- ============================================================
+
+-- sp_runtime::transaction_validity error reasons, by variant index
+local INVALID_REASONS = {
+    [0] = "Call", "Payment", "Future", "Stale", "BadProof", "AncientBirthBlock",
+    "ExhaustsResources", "Custom", "BadMandatory", "MandatoryValidation", "BadSigner",
+    "IndeterminateImplicit", "UnknownOrigin"
+}
+local UNKNOWN_REASONS = { [0] = "CannotLookup", "NoUnsignedValidator", "Custom" }
+
+-- TransactionValidityError -> { kind = "Invalid"|"Unknown", reason, code }
+local function decode_validity_error(data, offset)
+    local kind = string.byte(data, offset)
+    local reasons = kind == 0 and INVALID_REASONS or UNKNOWN_REASONS
+    local index = string.byte(data, offset + 1)
+    local err = { kind = kind == 0 and "Invalid" or "Unknown", reason = reasons[index] or ("Unknown(" .. tostring(index) .. ")") }
+    if err.reason == "Custom" then
+        err.code = string.byte(data, offset + 2)
+    end
+    return err
+end
+
+-- Pallet error details for a DispatchError::Module, nil for other variants
+local function module_error(meta, err)
+    if type(err) ~= "table" or err.variant ~= "Module" then return nil end
+    return Metadata.get_error(meta, err.value.index, string.byte(from_hex(err.value.error), 1))
+end
+
+local function decode_bytes_list(data, offset)
+    local count
+    count, offset = Scale.decode_compact(data, offset)
+    local list = {}
+    for i = 1, count do
+        local len
+        len, offset = Scale.decode_compact(data, offset)
+        list[i] = "0x" .. to_hex(data:sub(offset, offset + len - 1))
+        offset = offset + len
+    end
+    return list, offset
+end
+
+-- Check an extrinsic against the transaction pool rules without submitting it
+-- (TaggedTransactionQueue_validate_transaction, v3: source, tx, block hash)
+-- at: block hash to validate against (default: finalized head)
+-- Returns { valid, priority, requires, provides, longevity, propagate } or
+--   { valid = false, error = { kind, reason, code } }
+function Transaction.validate(api, extrinsic_hex, at)
+    at = at or api:chain_getFinalizedHead()
+    local input = "\2" .. from_hex(extrinsic_hex) .. from_hex(at)  -- TransactionSource::External
+    local data = from_hex(api:state_call("TaggedTransactionQueue_validate_transaction", "0x" .. to_hex(input), at))
+
+    if string.byte(data, 1) ~= 0 then
+        return { valid = false, error = decode_validity_error(data, 2) }
+    end
+    local result = { valid = true }
+    result.priority = Codec.le_to_decimal(data:sub(2, 9))
+    local offset = 10
+    result.requires, offset = decode_bytes_list(data, offset)
+    result.provides, offset = decode_bytes_list(data, offset)
+    result.longevity = Codec.le_to_decimal(data:sub(offset, offset + 7))
+    result.propagate = string.byte(data, offset + 8) == 1
+    return result
+end
+
+-- Validate and dry-run an extrinsic: validity as in Transaction.validate, plus
+-- dispatch = { ok = true } or { ok = false, error = DispatchError, module = { pallet, name, docs } }
+-- from applying it to the state at `at` (BlockBuilder_apply_extrinsic; nothing is persisted)
+function Transaction.dry_run(api, extrinsic_hex, at)
+    at = at or api:chain_getFinalizedHead()
+    local result = Transaction.validate(api, extrinsic_hex, at)
+    if not result.valid then return result end
+
+    local data = from_hex(api:state_call("BlockBuilder_apply_extrinsic", extrinsic_hex, at))
+    if string.byte(data, 1) ~= 0 then
+        -- Passed pool validation but rejected at inclusion
+        result.valid = false
+        result.error = decode_validity_error(data, 2)
+        return result
+    end
+    if string.byte(data, 2) == 0 then
+        result.dispatch = { ok = true }
+        return result
+    end
+
+    local meta = api:get_metadata()
+    local type_id = assert(Codec.find_type(meta, "sp_runtime::DispatchError"), "DispatchError type not in metadata")
+    local err = Codec.decode(meta, type_id, data, 3)
+    result.dispatch = { ok = false, error = err, module = module_error(meta, err) }
+    return result
+end
+
+-- ============================================================
+-- Inclusion
+-- ============================================================
+
+-- Outcome of an extrinsic from the block that includes it and that block's events
+-- extrinsic_hex: the submitted extrinsic (as returned by create_signed*)
+-- Returns nil when the block does not contain it, otherwise
+--   { block_hash, block_number, extrinsic_index, extrinsic_hash, success,
+--     error = { error = DispatchError, module = { pallet, name, docs } } | nil,
+--     fee_paid, tip, events }
+--   fee_paid/tip: decimal strings from TransactionPayment.TransactionFeePaid (nil if not emitted)
+--   events: decoded events of this extrinsic (see sublua.events), in order
+function Transaction.get_result(api, extrinsic_hex, block_hash)
+    local block = api:chain_getBlock(block_hash).block
+    local target, index = extrinsic_hex:lower(), nil
+    for i, ext in ipairs(block.extrinsics) do
+        if ext:lower() == target then
+            index = i - 1
+            break
+        end
+    end
+    if not index then return nil end
+
+    local result = {
+        block_hash = block_hash,
+        block_number = tonumber((block.header.number:gsub("^0x", "")), 16),
+        extrinsic_index = index,
+        extrinsic_hash = "0x" .. to_hex(crypto.blake2b(from_hex(extrinsic_hex), 32)),
+        events = {}
+    }
+    for _, event in ipairs(Events.fetch(api, block_hash)) do
+        if event.phase.variant == "ApplyExtrinsic" and event.phase.value == index then
+            table.insert(result.events, event)
+            if event.pallet == "System" and event.name == "ExtrinsicSuccess" then
+                result.success = true
+            elseif event.pallet == "System" and event.name == "ExtrinsicFailed" then
+                local err = event.fields.dispatch_error
+                result.success = false
+                result.error = { error = err, module = module_error(api:get_metadata(), err) }
+            elseif event.pallet == "TransactionPayment" and event.name == "TransactionFeePaid" then
+                result.fee_paid = event.fields.actual_fee
+                result.tip = event.fields.tip
+            end
+        end
+    end
+    return result
+end
+
+-- Poll new blocks until one includes the extrinsic
+-- opts: { finalized = false, from_block = nil, max_blocks = 64, poll_interval = 2, sleep = nil,
+--         timeout_ms = nil, cancel = nil }
+--   finalized: only look at finalized blocks, so the result is final
+--   from_block: first block number to scan (default: the current head)
+--   max_blocks: give up after scanning this many blocks (use the era period for mortal transactions)
+--   poll_interval: seconds between head checks; sleep: function(seconds), defaults to socket.sleep
+--   timeout_ms: overall deadline; the api's own deadline (rpc:with_deadline) also applies
+--   cancel: token (see sublua.cancel), checked before each block and after each sleep
+-- Returns the get_result table, or nil, err
+function Transaction.wait_for_inclusion(api, extrinsic_hex, opts)
+    opts = opts or {}
+    if opts.timeout_ms then
+        local o = {}
+        for k, v in pairs(opts) do o[k] = v end
+        o.timeout_ms = nil
+        return api:with_deadline(opts.timeout_ms, function()
+            return Transaction.wait_for_inclusion(api, extrinsic_hex, o)
+        end)
+    end
+    local interval = opts.poll_interval or 2
+    local max_blocks = opts.max_blocks or 64
+    local sleep = opts.sleep or require("socket").sleep
+
+    local function head()
+        local header = api:chain_getHeader(opts.finalized and api:chain_getFinalizedHead() or nil)
+        return tonumber((header.number:gsub("^0x", "")), 16)
+    end
+
+    local what = "waiting for " .. (opts.finalized and "finality" or "inclusion")
+    local next_block = opts.from_block or head()
+    local last_block = next_block + max_blocks - 1
+    while true do
+        local cancelled = Cancel.check(opts.cancel, what)
+        if cancelled then return nil, cancelled end
+        local current = head()
+        while next_block <= math.min(current, last_block) do
+            cancelled = Cancel.check(opts.cancel, what)
+            if cancelled then return nil, cancelled end
+            local result = Transaction.get_result(api, extrinsic_hex, api:chain_getBlockHash(next_block))
+            if result then return result end
+            next_block = next_block + 1
+        end
+        if next_block > last_block then
+            return nil, "Extrinsic not included within " .. max_blocks .. " blocks"
+        end
+        local left = api:time_left()
+        if left and left < interval then
+            return nil, "Timeout: extrinsic not " .. (opts.finalized and "finalized" or "included") .. " before the deadline"
+        end
+        sleep(interval)
+    end
+end
+
+-- Outcome of an extrinsic as soon as a best (not yet finalized) block includes it, without waiting
+-- handle: the report of Transaction.submit (uses .extrinsic and .from_block), or the extrinsic hex
+-- opts: { from_block = nil, max_blocks = 64, finalized = false, poll_interval, sleep, timeout_ms, cancel }
+--   from_block: first block number to scan (default: handle.from_block, else the last max_blocks blocks)
+--   finalized: once it is in a best block, also wait for finality (wait_for_inclusion options apply)
+--   cancel: token (see sublua.cancel), checked before each block
+-- Returns the get_result table plus finalized (boolean), nil while no best block includes it yet,
+--   or nil, err when waiting for finality fails or is cancelled
+--   A failed extrinsic has success = false and error = { error = DispatchError, module };
+--   a best block can still be retracted until finalized is true
+function Transaction.check_in_block_result(api, handle, opts)
+    opts = opts or {}
+    local extrinsic_hex = type(handle) == "table" and handle.extrinsic or handle
+    assert(type(extrinsic_hex) == "string", "Expected a submit report or extrinsic hex")
+    local max_blocks = opts.max_blocks or 64
+    local best = tonumber((api:chain_getHeader().number:gsub("^0x", "")), 16)
+    local first = opts.from_block or (type(handle) == "table" and handle.from_block) or (best - max_blocks + 1)
+    first = math.max(first, best - max_blocks + 1, 0)
+
+    local result
+    for n = best, first, -1 do
+        local cancelled = Cancel.check(opts.cancel, "checking block " .. n)
+        if cancelled then return nil, cancelled end
+        result = Transaction.get_result(api, extrinsic_hex, api:chain_getBlockHash(n))
+        if result then break end
+    end
+    if not result then return nil end
+
+    local finalized = api:chain_getHeader(api:chain_getFinalizedHead())
+    result.finalized = tonumber((finalized.number:gsub("^0x", "")), 16) >= result.block_number
+    if result.finalized or not opts.finalized then return result end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.from_block, o.finalized = result.block_number, true
+    local final, err = Transaction.wait_for_inclusion(api, extrinsic_hex, o)
+    if not final then return nil, err end
+    final.finalized = true
+    return final
+end
+
+-- ============================================================
+-- Pool
+-- ============================================================
+
+-- Transactions waiting in the node's pool (author_pendingExtrinsics), decoded
+-- address_filter: SS58 address (any network) or 0x public key; only that signer's
+--   transactions are returned (nil = all)
+-- Returns a list of Transaction.decode results in pool order, each with .extrinsic (the hex);
+--   entries that do not decode with the current metadata have only .extrinsic and .error
+function Transaction.get_pending_extrinsics(api, address_filter)
+    local signer
+    if address_filter then
+        local info = require("sublua.address").inspect(address_filter)
+        if not info.valid then error("Invalid address filter: " .. tostring(info.error)) end
+        signer = info.pubkey:lower()
+    end
+    local meta = api:get_metadata()
+    local pending = {}
+    for _, hex in ipairs(api:author_pendingExtrinsics() or {}) do
+        local ok, decoded = pcall(Transaction.decode, meta, hex)
+        if not ok then
+            if not signer then table.insert(pending, { extrinsic = hex, error = tostring(decoded) }) end
+        elseif not signer or (type(decoded.signer) == "table" and decoded.signer.variant == "Id"
+                and decoded.signer.value:lower() == signer) then
+            decoded.extrinsic = hex
+            table.insert(pending, decoded)
+        end
+    end
+    return pending
+end
+
+-- ============================================================
+-- Replacement (fee bump)
+-- ============================================================
+
+local function head_number(api)
+    return tonumber((api:chain_getHeader().number:gsub("^0x", "")), 16)
+end
+
+-- Heuristic for a transaction languishing in the pool: the signer's on-chain nonce has
+-- not moved past the transaction's nonce although `blocks` blocks went by since it was signed
+-- opts: { since_block = nil } block it was submitted at; required for immortal transactions,
+--   mortal ones default to their era's birth block
+-- Returns stuck, { nonce, account_nonce, since_block, head, waited, included }
+--   included: the nonce was used (by this transaction or one that replaced it)
+function Transaction.is_stuck(api, extrinsic_hex, blocks, opts)
+    opts = opts or {}
+    local decoded = Transaction.decode(api:get_metadata(), extrinsic_hex)
+    assert(decoded.signed and decoded.address, "Only signed transactions can be stuck")
+    local head = head_number(api)
+    local info = {
+        nonce = tonumber(decoded.nonce),
+        account_nonce = tonumber(api:system_account(decoded.address).nonce),
+        head = head
+    }
+    if info.account_nonce > info.nonce then
+        info.included = true
+        return false, info
+    end
+    info.since_block = opts.since_block
+    if not info.since_block then
+        if not decoded.era or decoded.era.immortal then
+            error("Immortal transaction: pass opts.since_block")
+        end
+        info.since_block = Transaction.era_birth(decoded.era, head)
+    end
+    info.waited = head - info.since_block
+    return info.waited >= blocks, info
+end
+
+-- Re-sign a pending transaction's call with the same nonce and a higher tip and submit it;
+-- the pool swaps it for the original because it has a higher priority
+-- signer: the original signer; extrinsic_hex: the original transaction
+-- new_tip: planck, higher than the original tip
+-- opts: create_signed_from_api options; mortality defaults to the original era period
+-- Returns hash, { extrinsic, nonce, tip, replaced = original hash }, or nil, err
+function Transaction.replace_transaction(api, signer, extrinsic_hex, new_tip, opts)
+    opts = opts or {}
+    signer = Transaction.signer_of(api, signer)
+    local decoded = Transaction.decode(api:get_metadata(), extrinsic_hex)
+    if not decoded.signed then return nil, "Only signed transactions can be replaced" end
+    if type(decoded.signer) ~= "table" or decoded.signer.value ~= "0x" .. to_hex(signer.pubkey) then
+        return nil, "Signer does not match the original transaction"
+    end
+    local old_tip = tonumber(decoded.tip) or 0
+    if tonumber(new_tip) <= old_tip then
+        return nil, "New tip must be higher than the original tip (" .. tostring(decoded.tip or 0) .. ")"
+    end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.nonce, o.tip, o.raw = tonumber(decoded.nonce), new_tip, nil
+    if o.mortality == nil and decoded.era and not decoded.era.immortal then
+        o.mortality = decoded.era.period
+    end
+    local signed = Transaction.create_signed_from_api(api, signer, from_hex(decoded.call_hex), o)
+    local ok, hash = pcall(api.author_submitExtrinsic, api, signed)
+    if not ok then return nil, tostring(hash) end
+    return hash, { extrinsic = signed, nonce = o.nonce, tip = new_tip, replaced = decoded.hash }
+end
+
+-- ============================================================
+-- Decoding
+-- ============================================================
+
+-- Interpret a decoded Era enum value
+-- Immortal -> { immortal = true }
+-- MortalN(b) -> { immortal = false, period, phase } (Substrate packed u16 encoding)
+function Transaction.decode_era(era)
+    if era.variant == "Immortal" then
+        return { immortal = true }
+    end
+    local first = tonumber(era.variant:match("^Mortal(%d+)$"))
+    assert(first, "Unknown era variant: " .. tostring(era.variant))
+    local encoded = first + (era.value or 0) * 256
+    local period = 2 ^ ((encoded % 16) + 1)
+    local quantize_factor = math.max(math.floor(period / 4096), 1)
+    local phase = math.floor(encoded / 16) * quantize_factor
+    return { immortal = false, period = period, phase = phase }
+end
+
+-- Find a named type parameter of the UncheckedExtrinsic type (Address, Call, Signature)
+local function extrinsic_param(meta, name)
+    local t = Codec.resolve(meta, meta.extrinsic.type_id)
+    for _, param in ipairs(t.type_params or {}) do
+        if param.name == name and param.type_id then
+            return param.type_id
+        end
+    end
+    error("UncheckedExtrinsic type has no " .. name .. " parameter")
+end
+
+-- Decode call bytes into { pallet, name, args }
+-- data: raw bytes; offset: start position (default 1). Returns call, next_offset
+function Transaction.decode_call(meta, data, offset)
+    offset = offset or 1
+    local pallet_index = string.byte(data, offset)
+    local pallet = meta.pallets_by_index[pallet_index]
+    if not pallet or not pallet.calls_type_id then
+        error("No callable pallet at index " .. tostring(pallet_index))
+    end
+    local call, off = Codec.decode(meta, pallet.calls_type_id, data, offset + 1)
+    return { pallet = pallet.name, name = call.variant, args = call.value or {} }, off
+end
+
+-- Decode a hex-encoded extrinsic (signed or unsigned V4) into a readable table:
+-- { version, signed, signer, address, signature, extensions, era, nonce, tip,
+--   call = { pallet, name, args }, call_hex, hash }
+-- meta_source: parsed metadata, RPC client, or metadata hex
+function Transaction.decode(meta_source, extrinsic_hex)
+    local meta = Metadata.from_source(meta_source)
+    local data = from_hex(extrinsic_hex)
+
+    local len, offset = Scale.decode_compact(data, 1)
+    assert(offset + len - 1 == #data, "Extrinsic length prefix does not match data")
+
+    local version_byte = string.byte(data, offset)
+    offset = offset + 1
+    local result = {
+        version = version_byte % 128,
+        signed = version_byte >= 128,
+        hash = "0x" .. to_hex(crypto.blake2b(data, 32))
+    }
+    if result.version ~= 4 then
+        error("Unsupported extrinsic version: " .. result.version)
+    end
+
+    if result.signed then
+        result.signer, offset = Codec.decode(meta, extrinsic_param(meta, "Address"), data, offset)
+        result.signature, offset = Codec.decode(meta, extrinsic_param(meta, "Signature"), data, offset)
+
+        result.extensions = {}
+        for _, ext in ipairs(meta.extrinsic.signed_extensions) do
+            local value
+            value, offset = Codec.decode(meta, ext.type_id, data, offset)
+            result.extensions[ext.identifier] = value
+        end
+
+        local ext = result.extensions
+        if ext.CheckMortality then
+            result.era = Transaction.decode_era(ext.CheckMortality)
+        end
+        result.nonce = ext.CheckNonce
+        local payment = ext.ChargeTransactionPayment or ext.ChargeAssetTxPayment
+        if type(payment) == "table" then payment = payment.tip end
+        result.tip = payment
+
+        -- MultiAddress::Id -> SS58 using the chain's prefix
+        if type(result.signer) == "table" and result.signer.variant == "Id" then
+            local prefix = 42
+            local c = Metadata.get_constant(meta, "System", "SS58Prefix")
+            if c then prefix = Codec.decode(meta, c.type_id, c.value) end
+            result.address = crypto.ss58_encode(from_hex(result.signer.value), prefix)
+        end
+    end
+
+    local call_start = offset
+    result.call, offset = Transaction.decode_call(meta, data, offset)
+    assert(offset == #data + 1, "Trailing bytes after call")
+    result.call_hex = "0x" .. to_hex(data:sub(call_start))
+
+    return result
+end
+
+-- ============================================================
+-- Signing Payload Verification
+-- ============================================================
+
+-- Decode a signing payload (call | extra | additional) back into its parts, so an
+-- offline signer can see what it is about to sign
+-- payload: raw bytes or "0x" hex, as produced by make_signing_payload
+-- Returns { call = { pallet, name, args }, call_hex, extensions = { [identifier] = { extra, additional } },
+--   era, nonce, tip, spec_version, tx_version, genesis_hash, block_hash, metadata_hash_mode }
+--   block_hash: the era checkpoint (genesis hash for immortal transactions)
+function Transaction.decode_signing_payload(meta_source, payload)
+    local meta = Metadata.from_source(meta_source)
+    local data = payload
+    if data:match("^0x%x*$") then data = from_hex(data) end
+
+    local ok, result = pcall(function()
+        local r = { extensions = {} }
+        local call, offset = Transaction.decode_call(meta, data, 1)
+        r.call, r.call_hex = call, "0x" .. to_hex(data:sub(1, offset - 1))
+        local exts = meta.extrinsic.signed_extensions
+        for _, ext in ipairs(exts) do
+            r.extensions[ext.identifier] = {}
+            r.extensions[ext.identifier].extra, offset = Codec.decode(meta, ext.type_id, data, offset)
+        end
+        for _, ext in ipairs(exts) do
+            r.extensions[ext.identifier].additional, offset = Codec.decode(meta, ext.additional_signed, data, offset)
+        end
+        assert(offset == #data + 1, "Trailing bytes after signing payload")
+        return r
+    end)
+    if not ok then
+        if #data == 32 then
+            error("Payload is a blake2_256 hash (payloads over 256 bytes are hashed) and cannot be decoded")
+        end
+        error("Cannot decode signing payload: " .. tostring(result), 0)
+    end
+
+    local ext = result.extensions
+    local function part(id, key) return ext[id] and ext[id][key] end
+    if ext.CheckMortality then
+        result.era = Transaction.decode_era(ext.CheckMortality.extra)
+        result.block_hash = ext.CheckMortality.additional
+    end
+    result.nonce = part("CheckNonce", "extra")
+    local payment = part("ChargeTransactionPayment", "extra") or part("ChargeAssetTxPayment", "extra")
+    if type(payment) == "table" then payment = payment.tip end
+    result.tip = payment
+    result.spec_version = part("CheckSpecVersion", "additional")
+    result.tx_version = part("CheckTxVersion", "additional")
+    result.genesis_hash = part("CheckGenesis", "additional")
+    local mode = part("CheckMetadataHash", "extra")
+    if type(mode) == "table" then mode = mode.mode end
+    result.metadata_hash_mode = type(mode) == "table" and mode.variant or mode
+    return result
+end
+
+-- Decoded values compare equal (numbers and decimal strings alike)
+local function same_value(a, b)
+    if type(a) == "number" then a = string.format("%.0f", a) end
+    if type(b) == "number" then b = string.format("%.0f", b) end
+    if type(a) == "string" and type(b) == "string" then
+        return a:lower() == b:lower()
+    end
+    if type(a) ~= "table" or type(b) ~= "table" then return a == b end
+    for k, v in pairs(a) do
+        if not same_value(v, b[k]) then return false end
+    end
+    for k in pairs(b) do
+        if a[k] == nil then return false end
+    end
+    return true
+end
+
+local function show(value)
+    if type(value) == "table" then return json.encode(value) end
+    return tostring(value)
+end
+
+-- Check a signing payload against what the user meant to sign, before signing it
+-- expected: table or JSON text { pallet, name, args = {...} } plus optional
+--   nonce, tip, spec_version, tx_version, genesis_hash, block_hash, mortal (true/false)
+--   args follow sublua.codec conventions, as for call.encode_call
+-- meta_source: parsed metadata, RPC client, or metadata hex (from a trusted source)
+-- Returns true, decoded; or false, err, decoded
+function Transaction.verify_signing_payload(payload, expected, meta_source)
+    local meta = Metadata.from_source(meta_source)
+    if type(expected) == "string" then expected = json.decode(expected) end
+    assert(type(expected) == "table" and expected.pallet and expected.name, "expected needs pallet and name")
+
+    local ok, decoded = pcall(Transaction.decode_signing_payload, meta, payload)
+    if not ok then return false, tostring(decoded) end
+
+    local call = decoded.call
+    if call.pallet ~= expected.pallet or call.name ~= expected.name then
+        return false, "Call mismatch: payload calls " .. call.pallet .. "." .. call.name
+            .. ", expected " .. expected.pallet .. "." .. expected.name, decoded
+    end
+    local encoded = Call.encode_call(meta, expected.pallet, expected.name, expected.args)
+    if "0x" .. to_hex(encoded) ~= decoded.call_hex then
+        local intended = Transaction.decode_call(meta, encoded)
+        for name, value in pairs(intended.args) do
+            if not same_value(value, call.args[name]) then
+                return false, "Argument '" .. name .. "' mismatch: payload has " .. show(call.args[name])
+                    .. ", expected " .. show(value), decoded
+            end
+        end
+        return false, "Call mismatch: payload call " .. decoded.call_hex .. ", expected 0x" .. to_hex(encoded), decoded
+    end
+
+    for _, field in ipairs({ "nonce", "tip", "spec_version", "tx_version", "genesis_hash", "block_hash" }) do
+        if expected[field] ~= nil and not same_value(expected[field], decoded[field]) then
+            return false, "Field '" .. field .. "' mismatch: payload has " .. show(decoded[field])
+                .. ", expected " .. show(expected[field]), decoded
+        end
+    end
+    if expected.mortal ~= nil and decoded.era and expected.mortal == decoded.era.immortal then
+        return false, "Era mismatch: payload is " .. (decoded.era.immortal and "immortal" or "mortal"), decoded
+    end
+    return true, decoded
+end
+
 return Transaction
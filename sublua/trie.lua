@@ -0,0 +1,269 @@
+-- sublua/trie.lua
+-- Substrate base-16 Patricia-Merkle trie (sp-trie layout, blake2_256)
+-- Keys are handled as nibble strings (lowercase hex, one char per nibble)
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+
+local Trie = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function blake2_256(data)
+    return crypto.blake2b(data, 32)
+end
+
+-- Root of a trie with no entries: blake2_256(0x00)
+Trie.EMPTY_ROOT = blake2_256("\0")
+
+-- Node header prefixes and the number of bits they occupy
+local LEAF = { prefix = 0x40, bits = 2 }
+local BRANCH = { prefix = 0x80, bits = 2 }
+local BRANCH_WITH_VALUE = { prefix = 0xC0, bits = 2 }
+local HASHED_LEAF = { prefix = 0x20, bits = 3 }
+local HASHED_BRANCH = { prefix = 0x10, bits = 4 }
+
+-- Values at least this long are stored as a hash in state version 1
+local MAX_INLINE_VALUE = 32
+
+-- Header byte(s): prefix | nibble count, extended with 255-continuation bytes
+local function encode_header(kind, nibble_count)
+    local max = math.floor(255 / 2 ^ kind.bits)
+    if nibble_count < max then
+        return string.char(kind.prefix + nibble_count)
+    end
+    local out = { string.char(kind.prefix + max) }
+    local rem = nibble_count - max
+    while rem >= 255 do
+        table.insert(out, "\255")
+        rem = rem - 255
+    end
+    table.insert(out, string.char(rem))
+    return table.concat(out)
+end
+
+-- Nibble string -> bytes; an odd leading nibble takes a byte of its own
+local function encode_partial(nibbles)
+    local out = {}
+    local start = 1
+    if #nibbles % 2 == 1 then
+        out[1] = string.char(tonumber(nibbles:sub(1, 1), 16))
+        start = 2
+    end
+    for i = start, #nibbles, 2 do
+        table.insert(out, string.char(tonumber(nibbles:sub(i, i + 1), 16)))
+    end
+    return table.concat(out)
+end
+
+Trie.encode_partial = encode_partial
+
+local function value_hashed(value, state_version)
+    return state_version == 1 and #value > MAX_INLINE_VALUE
+end
+
+local function encode_value(value, state_version)
+    if value_hashed(value, state_version) then
+        return blake2_256(value)
+    end
+    return Scale.encode_compact(#value) .. value
+end
+
+-- Encode the node covering items[lo..hi] (sorted), whose key prefix has depth nibbles
+local function encode_node(items, lo, hi, depth, state_version)
+    if lo == hi then
+        local item = items[lo]
+        local kind = value_hashed(item.value, state_version) and HASHED_LEAF or LEAF
+        local partial = item.key:sub(depth + 1)
+        return encode_header(kind, #partial) .. encode_partial(partial) .. encode_value(item.value, state_version)
+    end
+
+    -- Common prefix of a sorted range = common prefix of its first and last keys
+    local first, last = items[lo].key, items[hi].key
+    local p = depth
+    while p < #first and p < #last and first:byte(p + 1) == last:byte(p + 1) do
+        p = p + 1
+    end
+
+    local value
+    if #items[lo].key == p then
+        value = items[lo].value
+        lo = lo + 1
+    end
+
+    local children, bitmap = {}, 0
+    local i = lo
+    while i <= hi do
+        local nibble = items[i].key:sub(p + 1, p + 1)
+        local j = i
+        while j < hi and items[j + 1].key:sub(p + 1, p + 1) == nibble do j = j + 1 end
+        local child = encode_node(items, i, j, p + 1, state_version)
+        if #child >= 32 then child = blake2_256(child) end
+        local index = tonumber(nibble, 16)
+        bitmap = bitmap + 2 ^ index
+        children[index] = Scale.encode_compact(#child) .. child
+        i = j + 1
+    end
+
+    local kind = BRANCH
+    if value then
+        kind = value_hashed(value, state_version) and HASHED_BRANCH or BRANCH_WITH_VALUE
+    end
+    local partial = first:sub(depth + 1, p)
+    local out = {
+        encode_header(kind, #partial), encode_partial(partial),
+        string.char(bitmap % 256, math.floor(bitmap / 256))
+    }
+    if value then table.insert(out, encode_value(value, state_version)) end
+    for n = 0, 15 do
+        if children[n] then table.insert(out, children[n]) end
+    end
+    return table.concat(out)
+end
+
+-- Merkle root of a key/value set
+-- entries: { [key] = value } as raw bytes, or "0x" hex when opts.hex is set
+-- state_version: 0 (values inline) or 1 (values over 32 bytes hashed), default 1
+-- Returns the raw 32-byte root
+function Trie.root(entries, state_version, opts)
+    state_version = state_version or 1
+    opts = opts or {}
+    local items = {}
+    for k, v in pairs(entries) do
+        if opts.hex then
+            table.insert(items, { key = k:gsub("^0x", ""):lower(), value = from_hex(v) })
+        else
+            table.insert(items, { key = to_hex(k), value = v })
+        end
+    end
+    if #items == 0 then
+        return Trie.EMPTY_ROOT
+    end
+    table.sort(items, function(a, b) return a.key < b.key end)
+    return blake2_256(encode_node(items, 1, #items, 0, state_version))
+end
+
+-- ============================================================
+-- Node Decoding and Proofs
+-- ============================================================
+
+-- Decode an encoded node
+-- Returns { partial = nibbles, value = bytes|nil, value_hash = 32 bytes|nil,
+--   children = { [nibble] = 32-byte hash or inline node } }
+function Trie.decode_node(data)
+    local first = string.byte(data, 1)
+    assert(first, "Empty trie node")
+    local kind
+    if first == 0 then
+        assert(#data == 1, "Trailing bytes after empty node")
+        return { partial = "", children = {} }
+    elseif first >= 0xC0 then kind = BRANCH_WITH_VALUE
+    elseif first >= 0x80 then kind = BRANCH
+    elseif first >= 0x40 then kind = LEAF
+    elseif first >= 0x20 then kind = HASHED_LEAF
+    elseif first >= 0x10 then kind = HASHED_BRANCH
+    else error(string.format("Unknown trie node header 0x%02x", first)) end
+
+    local max = math.floor(255 / 2 ^ kind.bits)
+    local nibble_count = first - kind.prefix
+    local offset = 2
+    if nibble_count == max then
+        repeat
+            local b = string.byte(data, offset)
+            assert(b, "Truncated trie node header")
+            nibble_count = nibble_count + b
+            offset = offset + 1
+        until b < 255
+    end
+
+    local partial_len = math.ceil(nibble_count / 2)
+    local partial = to_hex(data:sub(offset, offset + partial_len - 1))
+    assert(#partial == partial_len * 2, "Truncated trie node key")
+    if nibble_count % 2 == 1 then
+        assert(partial:sub(1, 1) == "0", "Invalid padding in trie node key")
+        partial = partial:sub(2)
+    end
+    offset = offset + partial_len
+
+    local node = { partial = partial, children = {} }
+    local is_branch = kind ~= LEAF and kind ~= HASHED_LEAF
+    local bitmap = 0
+    if is_branch then
+        local lo, hi = string.byte(data, offset, offset + 1)
+        assert(hi, "Truncated trie node bitmap")
+        bitmap = lo + hi * 256
+        offset = offset + 2
+    end
+
+    if kind == HASHED_LEAF or kind == HASHED_BRANCH then
+        node.value_hash = data:sub(offset, offset + 31)
+        assert(#node.value_hash == 32, "Truncated trie node value hash")
+        offset = offset + 32
+    elseif kind ~= BRANCH then
+        local len
+        len, offset = Scale.decode_compact(data, offset)
+        node.value = data:sub(offset, offset + len - 1)
+        assert(#node.value == len, "Truncated trie node value")
+        offset = offset + len
+    end
+
+    for n = 0, 15 do
+        if math.floor(bitmap / 2 ^ n) % 2 == 1 then
+            local len
+            len, offset = Scale.decode_compact(data, offset)
+            local child = data:sub(offset, offset + len - 1)
+            assert(#child == len and len <= 32, "Invalid trie node child reference")
+            node.children[n] = child
+            offset = offset + len
+        end
+    end
+    assert(offset == #data + 1, "Trailing bytes after trie node")
+    return node
+end
+
+-- Look a key up through a set of proof nodes
+-- root: 32-byte state root; nodes: list of encoded nodes (raw bytes or "0x" hex)
+-- key: raw key bytes
+-- Returns the value, or nil when the proof shows the key is absent
+-- Errors when the proof does not contain the nodes needed to decide
+function Trie.read_proof(root, nodes, key)
+    local db = {}
+    for _, node in ipairs(nodes) do
+        if node:match("^0x%x*$") then node = from_hex(node) end
+        db[blake2_256(node)] = node
+    end
+
+    local function lookup(ref)
+        if #ref < 32 then return ref end
+        local node = db[ref]
+        if not node then error("Incomplete proof: missing node " .. to_hex(ref)) end
+        return node
+    end
+
+    local nibbles = to_hex(key)
+    local encoded = lookup(root)
+    while true do
+        local node = Trie.decode_node(encoded)
+        local partial = node.partial
+        if nibbles:sub(1, #partial) ~= partial then return nil end
+        nibbles = nibbles:sub(#partial + 1)
+
+        if nibbles == "" then
+            if node.value_hash then return lookup(node.value_hash) end
+            return node.value
+        end
+        local child = node.children[tonumber(nibbles:sub(1, 1), 16)]
+        if not child then return nil end
+        nibbles = nibbles:sub(2)
+        encoded = lookup(child)
+    end
+end
+
+return Trie
@@ -0,0 +1,199 @@
+-- sublua/txbuilder.lua
+-- Stepwise transaction builder: set a call's arguments one at a time, each checked
+-- against metadata as it is set, then sign or submit
+-- An argument that does not fit its type fails at the setter that supplied it, naming
+-- the argument, rather than when the whole call is encoded
+
+local Call = require("sublua.call")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+
+local TxBuilder = {}
+TxBuilder.__index = TxBuilder
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- Start a call
+-- api: RPC client (metadata, signing and submission); pallet, call: names from metadata
+function TxBuilder.new(api, pallet_name, call_name)
+    local meta = api:get_metadata()
+    local pallet = meta.pallets[pallet_name]
+    if not pallet then error("Pallet not found: " .. tostring(pallet_name)) end
+    if not pallet.calls_type_id then error("Pallet has no calls: " .. pallet_name) end
+    for _, variant in ipairs(Codec.resolve(meta, pallet.calls_type_id).type_def.variants) do
+        if variant.name == call_name then
+            local fields = {}
+            for i, field in ipairs(variant.fields) do fields[i] = field end
+            return setmetatable({
+                api = api,
+                meta = meta,
+                pallet = pallet_name,
+                call = call_name,
+                fields = fields,
+                args = {},
+                opts = {}
+            }, TxBuilder)
+        end
+    end
+    error("Call not found: " .. pallet_name .. "." .. tostring(call_name))
+end
+
+-- ============================================================
+-- Arguments
+-- Setters return the builder, so they chain
+-- ============================================================
+
+local function field_of(self, name)
+    for _, field in ipairs(self.fields) do
+        if field.name == name then return field end
+    end
+    local names = {}
+    for i, field in ipairs(self.fields) do names[i] = tostring(field.name) end
+    error(self.pallet .. "." .. self.call .. " has no argument " .. tostring(name)
+        .. " (arguments: " .. (#names > 0 and table.concat(names, ", ") or "none") .. ")")
+end
+
+local function fail(self, name, err)
+    err = tostring(err):gsub("^[^:]*:%d+: ", "")
+    error(self.pallet .. "." .. self.call .. "." .. name .. ": " .. err, 0)
+end
+
+-- Any argument as a codec value (nested tables for composites and enums)
+function TxBuilder:arg(name, value)
+    local field = field_of(self, name)
+    local ok, err = pcall(Codec.encode, self.meta, field.type_id, value)
+    if not ok then fail(self, name, err) end
+    self.args[name] = value
+    return self
+end
+
+-- Largest value of each unsigned integer type, as a decimal string
+local UINT_MAX = {
+    u8 = "255",
+    u16 = "65535",
+    u32 = "4294967295",
+    u64 = "18446744073709551615",
+    u128 = "340282366920938463463374607431768211455",
+    u256 = "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+}
+
+-- Unsigned integer argument (u8..u256, or Compact of one) from a number or a decimal string,
+-- so u128 amounts need not fit a Lua number; the value is range-checked for the type
+function TxBuilder:arg_u128(name, value)
+    local field = field_of(self, name)
+    local def = Codec.resolve(self.meta, field.type_id).type_def
+    local prim = Codec.primitive_name(self.meta, def.type == "Compact" and def.type_id or field.type_id)
+    if not UINT_MAX[prim] then
+        fail(self, name, "not an unsigned integer (" .. tostring(prim or Codec.type_name(self.meta, field.type_id) or def.type) .. ")")
+    end
+    local str = value
+    if type(value) == "number" and value % 1 == 0 then str = string.format("%.0f", value) end
+    if type(str) ~= "string" or not str:match("^%d+$") then
+        fail(self, name, "expected a non-negative integer or decimal string, got " .. tostring(value))
+    end
+    local digits, max = str:gsub("^0+(%d)", "%1"), UINT_MAX[prim]
+    if #digits > #max or (#digits == #max and digits > max) then
+        fail(self, name, str .. " exceeds " .. prim)
+    end
+    return self:arg(name, str)
+end
+
+-- Account argument in the shape its type expects (MultiAddress Id or AccountId)
+-- who: SS58 address (any network), "0x" hex or raw public key
+function TxBuilder:arg_address(name, who)
+    field_of(self, name)
+    local ok, value = pcall(Call.account_arg, self.meta, self.pallet, self.call, name, who)
+    if not ok then fail(self, name, value) end
+    return self:arg(name, value)
+end
+
+-- Byte argument (Vec<u8> or [u8; N]) from raw bytes or "0x" hex
+function TxBuilder:arg_bytes(name, data)
+    if type(data) ~= "string" then fail(self, name, "expected bytes, got " .. type(data)) end
+    if not data:match("^0x%x*$") or #data % 2 == 1 then data = "0x" .. to_hex(data) end
+    return self:arg(name, data)
+end
+
+-- Enum argument: variant name plus its value (nil for unit variants)
+function TxBuilder:arg_variant(name, variant, value)
+    local field = field_of(self, name)
+    local def = Codec.resolve(self.meta, field.type_id).type_def
+    if def.type ~= "Variant" then fail(self, name, "not an enum") end
+    local names = {}
+    for i, v in ipairs(def.variants) do
+        if v.name == variant then return self:arg(name, { variant = variant, value = value }) end
+        names[i] = v.name
+    end
+    fail(self, name, "unknown variant " .. tostring(variant) .. " (variants: " .. table.concat(names, ", ") .. ")")
+end
+
+-- ============================================================
+-- Transaction Options
+-- ============================================================
+
+-- Tip in planck (number or decimal string)
+function TxBuilder:set_tip(tip)
+    assert(tostring(tip):match("^%d+$"), "tip must be a non-negative integer")
+    self.opts.tip = tip
+    return self
+end
+
+-- Mortal era of period blocks; nil or 0 for an immortal transaction
+function TxBuilder:set_era(period)
+    assert(period == nil or (type(period) == "number" and period >= 0), "era period must be a number of blocks")
+    self.opts.mortality = period ~= 0 and period or nil
+    return self
+end
+
+-- Nonce to sign with instead of the account's next one
+function TxBuilder:set_nonce(nonce)
+    assert(type(nonce) == "number" and nonce >= 0 and nonce % 1 == 0, "nonce must be a non-negative integer")
+    self.opts.nonce = nonce
+    return self
+end
+
+-- ============================================================
+-- Build
+-- ============================================================
+
+-- Arguments not set yet, in call order
+function TxBuilder:missing()
+    local out = {}
+    for _, field in ipairs(self.fields) do
+        if self.args[field.name] == nil then table.insert(out, field.name) end
+    end
+    return out
+end
+
+-- Call bytes; errors naming every argument still missing
+function TxBuilder:encode()
+    local missing = self:missing()
+    if #missing > 0 then
+        error(self.pallet .. "." .. self.call .. " is missing arguments: " .. table.concat(missing, ", "), 0)
+    end
+    return Call.encode_call(self.meta, self.pallet, self.call, self.args)
+end
+
+local function with_opts(self, opts)
+    local o = {}
+    for k, v in pairs(self.opts) do o[k] = v end
+    for k, v in pairs(opts or {}) do o[k] = v end
+    return o
+end
+
+-- Signed extrinsic hex and details, as Transaction.create_signed_from_api
+-- signer: keyring pair (nil: the client's default, see RPC:set_signer)
+-- opts: further create_signed_from_api options, over the builder's tip / era / nonce
+function TxBuilder:sign(signer, opts)
+    return Transaction.create_signed_from_api(self.api, signer, self:encode(), with_opts(self, opts))
+end
+
+-- Sign and submit through Transaction.submit (retry, wait, timeout_ms opts apply)
+-- Returns hash, report as Transaction.submit
+function TxBuilder:submit(signer, opts)
+    return Transaction.submit(self.api, signer, self:encode(), with_opts(self, opts))
+end
+
+return TxBuilder
@@ -0,0 +1,373 @@
+-- sublua/types.lua
+-- Custom type bundles for chains with non-standard or opaque metadata types
+-- A bundle (polkadot-js "types" style) adds named types to a parsed metadata registry,
+-- replaces the definition of metadata types with the same name, and can retype storage
+-- entries, so storage and event decoding through sublua.codec use the custom shapes
+--
+-- Bundle: { types = { Name = definition, ... }, storage = { ["Pallet.Entry"] = "Type" } }
+-- Definitions:
+--   "Type"                          alias of a type expression (see below)
+--   { { "field", "Type" }, ... }    struct, fields in order (field types may be inline definitions)
+--   { _enum = { "A", "B" } }        unit enum
+--   { _enum = { { "A", "u32" }, { "B", "Null" } } }   enum with data
+-- JSON text keeps key order, so plain objects work there as in polkadot-js:
+--   { "types": { "Info": { "owner": "AccountId", "count": "u32" }, "Kind": { "_enum": { "A": "u32", "B": "Null" } } } }
+-- Type expressions: primitives (bool, str, u8..u256, i8..i256), Vec<T>, Option<T>, Compact<T>,
+--   Box<T>, [T; N], (A, B), BTreeMap<K, V>, BTreeSet<T>, common aliases (AccountId, Balance,
+--   Hash, Bytes, Text, Null, ...), bundle type names, and metadata types by path or last segment
+
+local json = require("cjson")
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+
+local Types = {}
+
+-- Si1TypeDefPrimitive index by name
+local PRIMITIVE_INDEX = {}
+for index, name in pairs(Codec.PRIMITIVES) do PRIMITIVE_INDEX[name] = index end
+
+-- Common polkadot-js names
+Types.ALIASES = {
+    Text = "str",
+    String = "str",
+    Bytes = "Vec<u8>",
+    Null = "()",
+    AccountId = "[u8; 32]",
+    AccountId32 = "[u8; 32]",
+    Hash = "[u8; 32]",
+    H160 = "[u8; 20]",
+    H256 = "[u8; 32]",
+    H512 = "[u8; 64]",
+    Balance = "u128",
+    BlockNumber = "u32",
+    Index = "u32",
+    Moment = "u64"
+}
+
+-- ============================================================
+-- Ordered JSON
+-- cjson loses object key order, which decides struct field and enum variant order
+-- ============================================================
+
+-- Objects decode to lists of { key, value } pairs
+local ORDERED = {}
+
+local function json_ordered(text)
+    local pos = 1
+    local value
+
+    local function skip()
+        pos = text:find("[^%s]", pos) or #text + 1
+    end
+    local function fail(what)
+        error("Invalid JSON at position " .. pos .. ": " .. what)
+    end
+
+    local function str()
+        local out = {}
+        pos = pos + 1
+        while true do
+            local c = text:sub(pos, pos)
+            if c == "" then fail("unterminated string") end
+            if c == '"' then pos = pos + 1 break end
+            if c == "\\" then
+                local e = text:sub(pos + 1, pos + 1)
+                local map = { b = "\b", f = "\f", n = "\n", r = "\r", t = "\t" }
+                if e == "u" then
+                    local code = tonumber(text:sub(pos + 2, pos + 5), 16) or fail("bad \\u escape")
+                    out[#out + 1] = code < 128 and string.char(code) or "?"
+                    pos = pos + 6
+                else
+                    out[#out + 1] = map[e] or e
+                    pos = pos + 2
+                end
+            else
+                out[#out + 1] = c
+                pos = pos + 1
+            end
+        end
+        return table.concat(out)
+    end
+
+    value = function()
+        skip()
+        local c = text:sub(pos, pos)
+        if c == "{" then
+            local obj = setmetatable({}, ORDERED)
+            pos = pos + 1
+            skip()
+            if text:sub(pos, pos) == "}" then pos = pos + 1 return obj end
+            while true do
+                skip()
+                if text:sub(pos, pos) ~= '"' then fail("object key expected") end
+                local key = str()
+                skip()
+                if text:sub(pos, pos) ~= ":" then fail("':' expected") end
+                pos = pos + 1
+                table.insert(obj, { key, value() })
+                skip()
+                local sep = text:sub(pos, pos)
+                pos = pos + 1
+                if sep == "}" then return obj end
+                if sep ~= "," then fail("',' or '}' expected") end
+            end
+        elseif c == "[" then
+            local arr = {}
+            pos = pos + 1
+            skip()
+            if text:sub(pos, pos) == "]" then pos = pos + 1 return arr end
+            while true do
+                table.insert(arr, value())
+                skip()
+                local sep = text:sub(pos, pos)
+                pos = pos + 1
+                if sep == "]" then return arr end
+                if sep ~= "," then fail("',' or ']' expected") end
+            end
+        elseif c == '"' then
+            return str()
+        end
+        local literal = text:match("^[%w%.%+%-]+", pos) or fail("unexpected character '" .. c .. "'")
+        pos = pos + #literal
+        if literal == "true" then return true end
+        if literal == "false" then return false end
+        if literal == "null" then return json.null end
+        return tonumber(literal) or fail("bad literal " .. literal)
+    end
+
+    local result = value()
+    skip()
+    if pos <= #text then fail("trailing characters") end
+    return result
+end
+
+-- Ordered { key, value } pairs of a definition table
+local function ordered_pairs(t, what)
+    if getmetatable(t) == ORDERED then return t end
+    if t[1] ~= nil then
+        for _, pair in ipairs(t) do
+            assert(type(pair) == "table" and type(pair[1]) == "string", what .. ": expected { name, type } pairs")
+        end
+        return t
+    end
+    local list = {}
+    for k, v in pairs(t) do table.insert(list, { k, v }) end
+    assert(#list <= 1, what .. ": field order is ambiguous in a Lua table; use { { name, type }, ... } or JSON text")
+    return list
+end
+
+-- Entries of a name-keyed map (bundle types or storage overrides), in any order
+local function entries(t)
+    if getmetatable(t) == ORDERED then return t end
+    local list = {}
+    for k, v in pairs(t) do table.insert(list, { k, v }) end
+    return list
+end
+
+local function field(t, key)
+    if getmetatable(t) == ORDERED then
+        for _, pair in ipairs(t) do
+            if pair[1] == key then return pair[2] end
+        end
+        return nil
+    end
+    return t[key]
+end
+
+-- ============================================================
+-- Registry
+-- ============================================================
+
+local function trim(s) return (s:gsub("^%s+", ""):gsub("%s+$", "")) end
+
+-- Split "A, B<C, D>, E" at top-level commas
+local function split_args(s)
+    local parts, depth, start = {}, 0, 1
+    for i = 1, #s do
+        local c = s:sub(i, i)
+        if c == "<" or c == "(" or c == "[" then depth = depth + 1
+        elseif c == ">" or c == ")" or c == "]" then depth = depth - 1
+        elseif c == "," and depth == 0 then
+            table.insert(parts, trim(s:sub(start, i - 1)))
+            start = i + 1
+        end
+    end
+    local last = trim(s:sub(start))
+    if last ~= "" then table.insert(parts, last) end
+    return parts
+end
+
+-- Per-metadata state: { defs = bundle definitions, ids = expression -> id, next_id }
+local function state(meta)
+    if not meta.custom_types then
+        local max = -1
+        for id in pairs(meta.types) do if id > max then max = id end end
+        meta.custom_types = { defs = {}, ids = {}, next_id = max + 1 }
+    end
+    return meta.custom_types
+end
+
+local function new_type(meta, path, def)
+    local st = state(meta)
+    local id = st.next_id
+    st.next_id = id + 1
+    meta.types[id] = { id = id, path = path or {}, type_params = {}, type_def = def, docs = {} }
+    return id
+end
+
+-- Metadata type by full path or unique last path segment
+local function find_metadata_type(meta, name)
+    if name:find("::") then return Codec.find_type(meta, name) end
+    local found
+    for id, t in pairs(meta.types) do
+        if t.path and t.path[#t.path] == name then
+            if found and found ~= id then
+                error("Type name '" .. name .. "' is ambiguous in metadata; use its full path")
+            end
+            found = id
+        end
+    end
+    return found
+end
+
+local resolve
+local build_def
+
+-- Field or variant type: an expression, or an inline struct/enum definition
+local function member_type(meta, name, definition)
+    if type(definition) == "table" then
+        return new_type(meta, nil, build_def(meta, name, definition))
+    end
+    return resolve(meta, definition)
+end
+
+build_def = function(meta, name, definition)
+    if type(definition) == "string" then
+        return Codec.resolve(meta, resolve(meta, definition)).type_def
+    end
+    assert(type(definition) == "table", "Type '" .. name .. "': definition must be a string or table")
+    local enum = field(definition, "_enum")
+    if enum then
+        local variants = {}
+        if enum[1] ~= nil and type(enum[1]) == "string" then
+            for i, v in ipairs(enum) do variants[i] = { name = v, index = i - 1, fields = {}, docs = {} } end
+        else
+            for i, pair in ipairs(ordered_pairs(enum, "Enum " .. name)) do
+                local fields = {}
+                if pair[2] ~= "Null" and pair[2] ~= "()" then
+                    fields[1] = { type_id = member_type(meta, name .. "." .. pair[1], pair[2]),
+                        type_name = type(pair[2]) == "string" and pair[2] or nil, docs = {} }
+                end
+                variants[i] = { name = pair[1], index = i - 1, fields = fields, docs = {} }
+            end
+        end
+        return { type = "Variant", variants = variants }
+    end
+    local fields = {}
+    for i, pair in ipairs(ordered_pairs(definition, "Struct " .. name)) do
+        fields[i] = { name = pair[1], type_id = member_type(meta, name .. "." .. pair[1], pair[2]),
+            type_name = type(pair[2]) == "string" and pair[2] or nil, docs = {} }
+    end
+    return { type = "Composite", fields = fields }
+end
+
+-- Type id for a type expression, creating anonymous types as needed
+resolve = function(meta, expr)
+    expr = trim(expr)
+    local st = state(meta)
+    if st.ids[expr] then return st.ids[expr] end
+
+    local id
+    if PRIMITIVE_INDEX[expr] then
+        id = new_type(meta, nil, { type = "Primitive", value = PRIMITIVE_INDEX[expr] })
+    elseif expr == "()" then
+        id = new_type(meta, nil, { type = "Tuple", types = {} })
+    elseif st.defs[expr] ~= nil then
+        -- Bundle type: reserve the id first so definitions can refer to each other
+        local existing = find_metadata_type(meta, expr)
+        id = existing or new_type(meta, { expr }, nil)
+        st.ids[expr] = id
+        meta.types[id].type_def = build_def(meta, expr, st.defs[expr])
+        return id
+    elseif Types.ALIASES[expr] then
+        id = resolve(meta, Types.ALIASES[expr])
+    else
+        local outer, args = expr:match("^([%w_:]+)%s*<(.*)>$")
+        if outer then
+            args = split_args(args)
+            if outer == "Vec" or outer == "BTreeSet" then
+                id = new_type(meta, nil, { type = "Sequence", type_id = resolve(meta, args[1]) })
+            elseif outer == "Compact" then
+                id = new_type(meta, nil, { type = "Compact", type_id = resolve(meta, args[1]) })
+            elseif outer == "Box" then
+                id = resolve(meta, args[1])
+            elseif outer == "Option" then
+                id = new_type(meta, { "Option" }, { type = "Variant", variants = {
+                    { name = "None", index = 0, fields = {}, docs = {} },
+                    { name = "Some", index = 1, fields = { { type_id = resolve(meta, args[1]), docs = {} } }, docs = {} }
+                } })
+            elseif outer == "BTreeMap" then
+                id = resolve(meta, "Vec<(" .. args[1] .. ", " .. args[2] .. ")>")
+            else
+                error("Unknown generic type '" .. outer .. "' in " .. expr)
+            end
+        elseif expr:match("^%[.*%]$") then
+            local elem, len = expr:match("^%[(.+);%s*(%d+)%s*%]$")
+            assert(elem, "Bad array type " .. expr)
+            id = new_type(meta, nil, { type = "Array", len = tonumber(len), type_id = resolve(meta, elem) })
+        elseif expr:match("^%(.*%)$") then
+            local types = {}
+            for i, part in ipairs(split_args(expr:sub(2, -2))) do types[i] = resolve(meta, part) end
+            id = new_type(meta, nil, { type = "Tuple", types = types })
+        else
+            id = find_metadata_type(meta, expr)
+            if not id then error("Unknown type '" .. expr .. "'") end
+        end
+    end
+    st.ids[expr] = id
+    return id
+end
+
+-- Add a bundle to parsed metadata (mutates and returns it)
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- bundle: table or JSON text; a table without `types` / `storage` keys is taken as the types map
+-- Metadata types with the same name (last path segment or full path) get the bundle's definition
+function Types.register(meta_source, bundle)
+    local meta = Metadata.from_source(meta_source)
+    if type(bundle) == "string" then bundle = json_ordered(bundle) end
+    assert(type(bundle) == "table", "bundle must be a table or JSON text")
+    local types = field(bundle, "types")
+    local storage = field(bundle, "storage")
+    if types == nil and storage == nil then types = bundle end
+
+    local st = state(meta)
+    local names = {}
+    for _, pair in ipairs(types and entries(types) or {}) do
+        st.defs[pair[1]] = pair[2]
+        st.ids[pair[1]] = nil
+        table.insert(names, pair[1])
+    end
+    for _, name in ipairs(names) do resolve(meta, name) end
+
+    for _, pair in ipairs(storage and entries(storage) or {}) do
+        local pallet, entry_name = pair[1]:match("^([%w_]+)%.([%w_]+)$")
+        assert(pallet, "Storage override key must be 'Pallet.Entry', got " .. tostring(pair[1]))
+        local entry, _, err = Metadata.get_storage_entry(meta, pallet, entry_name)
+        if err then error(pair[1] .. ": " .. err) end
+        entry.storage_type.value = resolve(meta, pair[2])
+    end
+    return meta
+end
+
+-- Type id for a type expression against metadata (after register, bundle names work too)
+function Types.id(meta_source, expr)
+    return resolve(Metadata.from_source(meta_source), expr)
+end
+
+-- Decode hex (e.g. a raw storage value) as a type expression
+function Types.decode(meta_source, expr, hex)
+    local meta = Metadata.from_source(meta_source)
+    return Codec.decode_hex(meta, resolve(meta, expr), hex)
+end
+
+return Types
@@ -0,0 +1,109 @@
+-- sublua/uos.lua
+-- Universal Offline Signatures (UOS): QR payloads for Polkadot Vault (Parity Signer)
+-- Rendering the QR image itself is left to the host (qrencode, a game engine widget, ...)
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+
+local UOS = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Substrate UOS header: 0x53 | crypto | action
+local SUBSTRATE_ID = "\83"  -- 'S'
+local CRYPTO = { ed25519 = "\0", sr25519 = "\1" }
+local CRYPTO_BY_BYTE = { [0] = "ed25519", [1] = "sr25519" }
+UOS.ACTION_SIGN_TX = "\2"
+UOS.ACTION_SIGN_MESSAGE = "\3"
+
+-- Multipart framing: 0x00 | u16 BE frame count | u16 BE frame index | data
+local FRAME_SIZE = 1024
+
+local function u16_be(n)
+    return string.char(math.floor(n / 256), n % 256)
+end
+
+local function public_key(address)
+    if #address == 32 then return address end
+    if address:match("^0x%x+$") then return from_hex(address) end
+    return (crypto.ss58_decode(address))
+end
+
+-- ============================================================
+-- Payloads
+-- ============================================================
+
+-- Build the UOS payload for a transaction
+-- call_bytes: raw call bytes
+-- extensions: extra .. additional (see Transaction.make_signing_payload)
+-- address: SS58 address, "0x" hex or raw 32-byte public key of the signer
+-- genesis_hash: "0x" hex
+-- opts: { scheme = "ed25519" | "sr25519" }
+-- Returns raw payload bytes (wrap with UOS.frames before encoding as QR)
+function UOS.make_signer_payload(call_bytes, extensions, address, genesis_hash, opts)
+    opts = opts or {}
+    local crypto_byte = assert(CRYPTO[opts.scheme or "ed25519"], "Unsupported signature scheme: " .. tostring(opts.scheme))
+    local genesis = from_hex(genesis_hash)
+    assert(#genesis == 32, "genesis_hash must be 32 bytes")
+    return SUBSTRATE_ID .. crypto_byte .. UOS.ACTION_SIGN_TX .. public_key(address)
+        .. Scale.encode_compact(#call_bytes) .. call_bytes .. extensions .. genesis
+end
+
+-- Build the UOS payload for signing an arbitrary message
+function UOS.make_message_payload(message, address, genesis_hash, opts)
+    opts = opts or {}
+    local crypto_byte = assert(CRYPTO[opts.scheme or "ed25519"], "Unsupported signature scheme: " .. tostring(opts.scheme))
+    local genesis = from_hex(genesis_hash)
+    assert(#genesis == 32, "genesis_hash must be 32 bytes")
+    return SUBSTRATE_ID .. crypto_byte .. UOS.ACTION_SIGN_MESSAGE .. public_key(address) .. message .. genesis
+end
+
+-- Split a payload into multipart frames, one QR code each (animated when > 1)
+-- frame_size: data bytes per frame (default 1024)
+function UOS.frames(payload, frame_size)
+    frame_size = frame_size or FRAME_SIZE
+    local count = math.max(math.ceil(#payload / frame_size), 1)
+    assert(count <= 65535, "Payload too large for multipart QR")
+    local frames = {}
+    for i = 0, count - 1 do
+        local chunk = payload:sub(i * frame_size + 1, (i + 1) * frame_size)
+        frames[i + 1] = "\0" .. u16_be(count) .. u16_be(i) .. chunk
+    end
+    return frames
+end
+
+-- ============================================================
+-- Signatures
+-- ============================================================
+
+-- Parse the signature QR shown by Vault after signing
+-- data: scanned content, "0x" hex or raw bytes; either a MultiSignature
+--   (scheme byte + 64 bytes) or a bare 64-byte signature
+-- Returns { scheme, signature (raw 64 bytes), hex }
+function UOS.parse_signature(data)
+    local bytes = data
+    if data:match("^0x%x*$") or (#data ~= 64 and #data ~= 65 and data:match("^%x+$")) then
+        bytes = from_hex(data)
+    end
+
+    local scheme
+    if #bytes == 65 then
+        scheme = CRYPTO_BY_BYTE[string.byte(bytes, 1)]
+        if not scheme then
+            error("Unsupported signature scheme byte " .. string.byte(bytes, 1))
+        end
+        bytes = bytes:sub(2)
+    elseif #bytes ~= 64 then
+        error("Signature QR must hold 64 or 65 bytes, got " .. #bytes)
+    end
+    return { scheme = scheme, signature = bytes, hex = "0x" .. to_hex(bytes) }
+end
+
+return UOS
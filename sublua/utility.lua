@@ -0,0 +1,162 @@
+-- sublua/utility.lua
+-- Utility pallet helpers: derivative sub-accounts, as_derivative dispatch and batches
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Call = require("sublua.call")
+local Address = require("sublua.address")
+local Validate = require("sublua.validate")
+
+local Utility = {}
+
+-- Domain separator used by pallet_utility::derivative_account_id
+local SUB_ACCOUNT_PREFIX = "modlpy/utilisuba"
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- ============================================================
+-- Derivative Accounts
+-- derivative_account_id(who, index) =
+--     blake2_256(SCALE("modlpy/utilisuba" as [u8; 16], who, index: u16))
+-- ============================================================
+
+-- Compute the deterministic sub-account of a parent account
+-- parent: SS58 address
+-- index: u16 derivative index
+-- prefix: SS58 prefix of the result (default: same as parent)
+-- Returns address, pubkey
+function Utility.derive_sub_account(parent, index, prefix)
+    local parent_pub, parent_prefix = Address.decode(parent)
+    local pubkey = crypto.blake2b(SUB_ACCOUNT_PREFIX .. parent_pub .. Scale.encode_u16(index), 32)
+    return Address.encode(pubkey, prefix or parent_prefix), pubkey
+end
+
+-- ============================================================
+-- Call Encoding
+-- Utility.as_derivative(index: u16, call: Box<RuntimeCall>)
+-- ============================================================
+
+-- Build Utility.as_derivative call bytes
+-- pallet_index: Utility pallet index
+-- call_index: as_derivative index (1)
+-- index: u16 derivative index
+-- inner_call: raw call bytes dispatched from the sub-account
+function Utility.encode_as_derivative(pallet_index, call_index, index, inner_call)
+    assert(type(inner_call) == "string" and #inner_call >= 2, "inner_call must be raw call bytes")
+    return Call.encode_index(pallet_index, call_index) .. Scale.encode_u16(index) .. inner_call
+end
+
+-- Dispatch a call from the signer's derivative sub-account
+-- api: RPC client
+-- signer: keyring pair (the parent account)
+-- index: derivative index
+-- inner_call: raw call bytes
+-- opts: { tip = 0 }
+function Utility.as_derivative(api, signer, index, inner_call, opts)
+    local meta = api:get_metadata()
+    local utility = meta.pallets["Utility"]
+    assert(utility, "Utility pallet not found in metadata")
+
+    local call_index = utility.calls and utility.calls["as_derivative"]
+    assert(call_index, "as_derivative not found in Utility")
+
+    local call_bytes = Utility.encode_as_derivative(utility.index, call_index, index, inner_call)
+
+    local Transaction = require("sublua.transaction")
+    return Transaction.create_signed_from_api(api, signer, call_bytes, opts)
+end
+
+-- ============================================================
+-- Batches
+-- Utility.batch / batch_all / force_batch(calls: Vec<RuntimeCall>)
+-- ============================================================
+
+-- Build batch call bytes
+-- calls: list of raw call bytes (a list with holes or other keys is an error, not cut short)
+-- kind: "batch_all" (default; all or nothing), "batch" (stops at the first failure)
+--   or "force_batch" (keeps going past failures)
+function Utility.encode_batch(meta_source, calls, kind)
+    kind = kind or "batch_all"
+    local meta = require("sublua.metadata").from_source(meta_source)
+    local utility = meta.pallets["Utility"]
+    assert(utility, "Utility pallet not found in metadata")
+    local call_index = utility.calls and utility.calls[kind]
+    assert(call_index, kind .. " not found in Utility")
+    local _, n = Validate.list(calls, "calls", { what = "an array of calls" })
+    local parts = { Call.encode_index(utility.index, call_index), Scale.encode_compact(n) }
+    for i, call in ipairs(calls) do
+        if type(call) ~= "string" or #call < 2 then Validate.fail("calls." .. i, "raw call bytes", call) end
+        parts[i + 2] = call
+    end
+    return table.concat(parts)
+end
+
+-- Build batch call bytes from JSON
+-- calls: JSON array (text or decoded) whose items are "0x" call hex or
+--   { pallet, call, args } objects, args as for Call.encode_call_json
+-- Errors name the offending item and field, e.g. "calls.2.args: ..." or
+--   "calls.3.transfer_keep_alive.value: u128 expects a number or decimal string, got 'ten'"
+function Utility.encode_batch_json(meta_source, calls, kind)
+    local meta = require("sublua.metadata").from_source(meta_source)
+    calls = Validate.decode(calls, "calls", "a JSON array of calls")
+    Validate.list(calls, "calls", { min = 1, what = "an array of calls" })
+    local list = {}
+    for i, item in ipairs(calls) do
+        local path = "calls." .. i
+        if type(item) == "string" then
+            if not item:match("^0x%x+$") or #item % 2 ~= 0 or #item < 6 then
+                Validate.fail(path, "0x call hex or a { pallet, call, args } object", item)
+            end
+            list[i] = from_hex(item)
+        else
+            Validate.object(item, path, { "pallet", "call", "args" }, "0x call hex or a { pallet, call, args } object")
+            local pallet = Validate.text(item.pallet, path .. ".pallet")
+            local name = Validate.text(item.call, path .. ".call")
+            local args = item.args
+            if args == nil or args == require("cjson").null then args = {} end
+            Validate.object(args, path .. ".args", nil, "an object of call arguments")
+            list[i] = Validate.within(path, Call.encode_call_json, meta, pallet, name, args)
+        end
+    end
+    return Utility.encode_batch(meta, list, kind)
+end
+
+-- Submit calls as one batch, or as several when auto_split is set
+-- api: RPC client; signer: keyring pair; calls: list of raw call bytes
+-- opts: { kind = "batch_all", auto_split = false, ratio = 0.9, ... }
+--   auto_split: measure every call's weight and split the list into as many batches as
+--     needed so each extrinsic stays within the Normal class limit (see Weights.pack)
+--   ratio: share of that limit to fill
+--   other options go to Transaction.submit (tip, mortality, retry, wait, ...)
+-- Batches are submitted in order; each takes the next nonce from system_accountNextIndex
+-- Returns the list of tx hashes and the list of submit reports, or nil, reports when a
+--   batch was rejected (batches before it are already submitted; their hashes are in the reports)
+function Utility.submit_batch(api, signer, calls, opts)
+    opts = opts or {}
+    assert(#calls > 0, "No calls to batch")
+    local Transaction = require("sublua.transaction")
+    local groups = { calls }
+    if opts.auto_split then
+        groups = require("sublua.weights").pack(api, calls, { ratio = opts.ratio })
+    end
+
+    local o = {}
+    for k, v in pairs(opts) do o[k] = v end
+    o.kind, o.auto_split, o.ratio = nil, nil, nil
+
+    local meta = api:get_metadata()
+    local hashes, reports = {}, {}
+    for i, group in ipairs(groups) do
+        local hash, report = Transaction.submit(api, signer, Utility.encode_batch(meta, group, opts.kind), o)
+        reports[i] = report
+        if not hash then return nil, reports end
+        hashes[i] = hash
+        o.nonce = nil  -- opts.nonce is for the first batch
+    end
+    return hashes, reports
+end
+
+return Utility
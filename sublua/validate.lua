@@ -0,0 +1,169 @@
+-- sublua/validate.lua
+-- Strict checks for caller-supplied arguments, typically decoded JSON from a host or a CLI:
+-- a value of the wrong shape fails naming the field and what was expected, instead of being
+-- coerced into something that still encodes (a list with a hole losing its tail, a number
+-- where text belongs, a misspelt field that is never read)
+-- Paths follow Codec.from_json: dot-separated keys and 1-based list positions ("calls.2.args.dest")
+-- Errors read "<path>: expected <what>, got <value>"; Validate.parse and Validate.error_json
+-- hand the path and the expectation back to a host as data
+
+local json = require("cjson")
+
+local Validate = {}
+
+local function is_null(value)
+    return value == nil or value == json.null
+end
+
+-- Number of items when value is a list (keys exactly 1..n), else nil
+function Validate.length(value)
+    if type(value) ~= "table" then return nil end
+    local n, count = #value, 0
+    for key in pairs(value) do
+        if type(key) ~= "number" or key < 1 or key > n or key % 1 ~= 0 then return nil end
+        count = count + 1
+    end
+    return count == n and n or nil
+end
+
+-- Short description of a value for messages: null, number 5, 'text', object, array of 3
+function Validate.describe(value)
+    if is_null(value) then return "null" end
+    local kind = type(value)
+    if kind == "string" then
+        if #value > 48 then value = value:sub(1, 45) .. "..." end
+        return "'" .. value .. "'"
+    elseif kind == "number" or kind == "boolean" then
+        return kind .. " " .. tostring(value)
+    elseif kind == "table" then
+        if next(value) == nil then return "empty table" end
+        local n = Validate.length(value)
+        return n and ("array of " .. n) or "object"
+    end
+    return kind
+end
+
+function Validate.join(path, key)
+    return (path == nil or path == "") and tostring(key) or path .. "." .. tostring(key)
+end
+
+-- Raise "<path>: expected <expected>, got <value>" (reason, when given, follows in parentheses)
+function Validate.fail(path, expected, value, reason)
+    local msg = "expected " .. expected .. ", got " .. Validate.describe(value)
+    if reason then msg = msg .. " (" .. tostring(reason) .. ")" end
+    error(((path and path ~= "") and path .. ": " or "") .. msg, 0)
+end
+
+-- Run fn(...) with its errors placed under path: an error that already names a field
+-- ("dest: ...") gets path prefixed to that field, any other error is reported at path
+function Validate.within(path, fn, ...)
+    local results = { pcall(fn, ...) }
+    if results[1] then return select(2, (table.unpack or unpack)(results)) end
+    local err = tostring(results[2]):gsub("^[^:%s]+%.lua:%d+: ", "")
+    if err:match("^[%l%d_][%w_.]*: ") then error(path .. "." .. err, 0) end
+    error(path .. ": " .. err, 0)
+end
+
+-- Decoded JSON: text is parsed, tables pass through
+-- what: expectation for the message, e.g. "a JSON array of calls"
+function Validate.decode(value, path, what)
+    if type(value) ~= "string" then return value end
+    local ok, decoded = pcall(json.decode, value)
+    if not ok then Validate.fail(path, what or "JSON", value, "not valid JSON") end
+    return decoded
+end
+
+-- A list without holes or other keys
+-- opts: { min, max, what = "an array" }
+-- Returns value, number of items
+function Validate.list(value, path, opts)
+    opts = opts or {}
+    local what = opts.what or "an array"
+    local n = Validate.length(value)
+    if not n then Validate.fail(path, what, value) end
+    if opts.min and n < opts.min then Validate.fail(path, what .. " of at least " .. opts.min, value) end
+    if opts.max and n > opts.max then Validate.fail(path, what .. " of at most " .. opts.max, value) end
+    return value, n
+end
+
+-- An object whose keys are all in fields (a list of names); unknown keys fail at their path
+function Validate.object(value, path, fields, what)
+    what = what or "an object"
+    if type(value) ~= "table" or (next(value) ~= nil and Validate.length(value)) then
+        Validate.fail(path, what, value)
+    end
+    if fields then
+        local known = {}
+        for _, name in ipairs(fields) do known[name] = true end
+        for key, item in pairs(value) do
+            if not known[key] then
+                Validate.fail(Validate.join(path, key), "one of " .. table.concat(fields, ", "), item, "unknown field")
+            end
+        end
+    end
+    return value
+end
+
+-- Text, optionally limited in bytes
+-- opts: { max_bytes, optional = false (null allowed, returned as nil) }
+function Validate.text(value, path, opts)
+    opts = opts or {}
+    if opts.optional and is_null(value) then return nil end
+    local what = opts.max_bytes and ("text of at most " .. opts.max_bytes .. " bytes") or "text"
+    if type(value) ~= "string" then Validate.fail(path, what, value) end
+    if opts.max_bytes and #value > opts.max_bytes then Validate.fail(path, what, value, #value .. " bytes") end
+    return value
+end
+
+-- Integer from a number or a decimal string
+-- opts: { min, max }
+function Validate.integer(value, path, opts)
+    opts = opts or {}
+    local what = "an integer"
+    if opts.min and opts.max then
+        what = "an integer from " .. opts.min .. " to " .. opts.max
+    elseif opts.min then
+        what = "an integer of at least " .. opts.min
+    end
+    local n = value
+    if type(value) == "string" and value:match("^%-?%d+$") then n = tonumber(value) end
+    if type(n) ~= "number" or n % 1 ~= 0 or math.abs(n) >= 2^53 then Validate.fail(path, what, value) end
+    if (opts.min and n < opts.min) or (opts.max and n > opts.max) then Validate.fail(path, what, value) end
+    return n
+end
+
+-- Account as a lowercase "0x" public key, from an SS58 address or 0x public key
+function Validate.account(value, path)
+    local what = "an SS58 address or 0x public key"
+    if type(value) ~= "string" then Validate.fail(path, what, value) end
+    local info = require("sublua.address").inspect(value)
+    if not info.valid then Validate.fail(path, what, value, info.error) end
+    return info.pubkey:lower()
+end
+
+-- Error as data: { message, path, expected, got } (path, expected and got when the
+-- message carries them; errors from Codec.from_json give path and message)
+function Validate.parse(err)
+    local message = tostring(err)
+    local out = { message = message }
+    local path, rest = message:match("^([%l%d_][%w_.]*): (.*)$")
+    if path then
+        out.path = path
+        local expected, got = rest:match("^expected (.-), got (.*)$")
+        out.expected, out.got = expected, got
+    end
+    return out
+end
+
+-- Validate.parse as JSON text: {"error": message, "path": ..., "expected": ..., "got": ...}
+function Validate.error_json(err)
+    local parsed = Validate.parse(err)
+    return json.encode({
+        error = parsed.message,
+        path = parsed.path or json.null,
+        expected = parsed.expected or json.null,
+        got = parsed.got or json.null
+    })
+end
+
+return Validate
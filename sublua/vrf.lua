@@ -0,0 +1,74 @@
+-- sublua/vrf.lua
+-- Verifiable random function on account keys (ECVRF-EDWARDS25519-SHA512-TAI, RFC 9381)
+-- A player signs a context and message (round id, seed from a block hash, ...) and gets a
+-- 64-byte output nobody could have predicted; anyone holding the public key checks the
+-- proof and derives the same output
+-- These are ed25519 VRFs, the key type SubLua signs with: they are not interchangeable
+-- with schnorrkel (sr25519) VRFs such as BABE's
+
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Address = require("sublua.address")
+
+local Vrf = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local function bytes(value)
+    if type(value) == "string" and value:match("^0x%x*$") and #value % 2 == 0 then return from_hex(value) end
+    return value
+end
+
+-- VRF input: compact(len(context)) .. context .. message, so contexts cannot run into messages
+local function input(context, message)
+    assert(type(context) == "string", "VRF context must be a string")
+    assert(type(message) == "string", "VRF message must be a string")
+    return Scale.encode_compact(#context) .. context .. bytes(message)
+end
+
+-- Prove
+-- seed: 32-byte seed (raw or 0x hex) or a keyring pair with .seed (secure pairs hide it)
+-- context: domain label, e.g. "my-game/dice"; message: raw bytes or 0x hex
+-- Returns { output, proof } as 0x hex (64 and 80 bytes)
+function Vrf.sign(seed, context, message)
+    if type(seed) == "table" then
+        seed = seed.seed or error("VRF signing needs the seed; secure and external pairs do not expose it")
+    end
+    seed = bytes(seed)
+    if #seed ~= 32 then error("Seed must be 32 bytes") end
+    local proof, output = crypto.vrf_prove(seed, input(context, message))
+    return { output = "0x" .. to_hex(output), proof = "0x" .. to_hex(proof) }
+end
+
+-- Check a proof
+-- public_key: SS58 address, 0x hex or 32 raw bytes
+-- Returns the 0x output when the proof is valid, or nil
+function Vrf.verify(public_key, context, message, proof)
+    local pubkey = public_key
+    if #pubkey ~= 32 then
+        local info = Address.inspect(public_key)
+        if not info.valid then error("Invalid public key: " .. tostring(info.error)) end
+        pubkey = from_hex(info.pubkey)
+    end
+    local output = crypto.vrf_verify(pubkey, input(context, message), bytes(proof))
+    return output and "0x" .. to_hex(output) or nil
+end
+
+-- Map an output to an integer in [1, n] (dice rolls, draws)
+-- Uses 48 bits of the output; the bias is below n / 2^48
+function Vrf.to_range(output, n)
+    assert(type(n) == "number" and n >= 1 and n % 1 == 0 and n <= 2 ^ 32, "n must be an integer from 1 to 2^32")
+    local raw = bytes(output)
+    local value = 0
+    for i = 1, 6 do value = value * 256 + raw:byte(i) end
+    return value % n + 1
+end
+
+return Vrf
@@ -0,0 +1,273 @@
+-- sublua/webhooks.lua
+-- Webhook dispatcher for finalized chain events
+-- Registered hooks and the next block to look at live in a JSON state file, so the
+-- dispatcher works from request/response servers (OpenResty, Lapis, CGI) with no
+-- long-lived process: call dispatch() from a timer, a cron job or any request handler,
+-- and it catches up on the finalized blocks it has not seen yet
+-- Every POST carries X-SubLua-Signature: sha256=<hex HMAC-SHA256(secret, body)>;
+-- receivers check it with Webhooks.verify
+
+local http = require("socket.http")
+local ltn12 = require("ltn12")
+local json = require("cjson")
+local crypto = require("polkadot_crypto")
+local Scanner = require("sublua.scanner")
+
+local Webhooks = {}
+Webhooks.__index = Webhooks
+
+Webhooks.SIGNATURE_HEADER = "X-SubLua-Signature"
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- ============================================================
+-- Signatures
+-- ============================================================
+
+-- XOR every byte of a string with a constant (no bit library on Lua 5.1)
+local function xor_bytes(str, pad)
+    return (str:gsub(".", function(c)
+        local a, b, r, bit = string.byte(c), pad, 0, 1
+        while a > 0 or b > 0 do
+            if a % 2 ~= b % 2 then r = r + bit end
+            a, b, bit = math.floor(a / 2), math.floor(b / 2), bit * 2
+        end
+        return string.char(r)
+    end))
+end
+
+-- HMAC-SHA256 (RFC 2104) of a body, as the header value "sha256=<hex>"
+function Webhooks.sign(secret, body)
+    local key = secret
+    if #key > 64 then key = crypto.sha256(key) end
+    key = key .. string.rep("\0", 64 - #key)
+    local inner = crypto.sha256(xor_bytes(key, 0x36) .. body)
+    return "sha256=" .. to_hex(crypto.sha256(xor_bytes(key, 0x5c) .. inner))
+end
+
+-- Check a received signature header against the body (constant time comparison)
+function Webhooks.verify(secret, body, signature)
+    local expected = Webhooks.sign(secret, body)
+    if type(signature) ~= "string" or #signature ~= #expected then return false end
+    local diff = 0
+    for i = 1, #expected do
+        if expected:byte(i) ~= signature:byte(i) then diff = diff + 1 end
+    end
+    return diff == 0
+end
+
+-- ============================================================
+-- Delivery
+-- ============================================================
+
+-- Default transport: POST with LuaSocket
+-- Returns the HTTP status, or nil, err
+function Webhooks.http_post(url, body, headers, timeout_ms)
+    local saved_timeout = http.TIMEOUT
+    if timeout_ms then http.TIMEOUT = timeout_ms / 1000 end
+    local ok, status = http.request{
+        url = url,
+        method = "POST",
+        headers = headers,
+        source = ltn12.source.string(body),
+        sink = ltn12.sink.null()
+    }
+    http.TIMEOUT = saved_timeout
+    if not ok then return nil, tostring(status) end
+    return status
+end
+
+-- ============================================================
+-- Dispatcher
+-- ============================================================
+
+-- Open a dispatcher
+-- opts: {
+--   state_file = nil,    -- JSON file for hooks, cursor and retries (nil keeps state in memory)
+--   start_block = nil,   -- first block when there is no state yet (default: the finalized head)
+--   max_blocks = 100,    -- blocks handled per dispatch() call at most
+--   max_attempts = 5,    -- deliveries are retried on later dispatch() calls, then dropped
+--   timeout_ms = 5000,   -- per POST
+--   post = nil           -- function(url, body, headers, timeout_ms) -> status | nil, err
+-- }
+-- The state file holds the hook secrets; keep it out of any public directory
+function Webhooks.new(api, opts)
+    opts = opts or {}
+    local self = setmetatable({
+        api = api,
+        state_file = opts.state_file,
+        start_block = opts.start_block,
+        max_blocks = opts.max_blocks or 100,
+        max_attempts = opts.max_attempts or 5,
+        timeout_ms = opts.timeout_ms or 5000,
+        post = opts.post or Webhooks.http_post,
+        state = { hooks = {}, next_id = 1, pending = {} }
+    }, Webhooks)
+    if self.state_file then
+        local f = io.open(self.state_file, "rb")
+        if f then
+            local text = f:read("*a")
+            f:close()
+            local ok, state = pcall(json.decode, text)
+            if not ok or type(state) ~= "table" then error("Unreadable webhook state file " .. self.state_file) end
+            self.state = state
+            state.pending = state.pending or {}
+        end
+    end
+    return self
+end
+
+function Webhooks:save()
+    if not self.state_file then return end
+    local tmp = self.state_file .. ".tmp"
+    local f, err = io.open(tmp, "wb")
+    if not f then error("Cannot write webhook state: " .. tostring(err)) end
+    f:write(json.encode(self.state))
+    f:close()
+    local ok, rename_err = os.rename(tmp, self.state_file)
+    if not ok then error("Cannot write webhook state: " .. tostring(rename_err)) end
+end
+
+-- Register a webhook
+-- url: endpoint that receives the POSTs
+-- filter: events to deliver, as a table or JSON string (see Scanner.compile; calls are not delivered)
+-- secret: HMAC key shared with the receiver
+-- Returns the hook id
+function Webhooks:register(url, filter, secret)
+    assert(type(url) == "string" and url:match("^https?://"), "Webhook url must be http(s)")
+    assert(type(secret) == "string" and secret ~= "", "Webhook secret must be a non-empty string")
+    if type(filter) == "string" then filter = json.decode(filter) end
+    local compiled = Scanner.compile(filter)
+    assert(compiled.events, "Webhook filter needs events or an address")
+    local id = self.state.next_id
+    self.state.next_id = id + 1
+    table.insert(self.state.hooks, { id = id, url = url, filter = filter, secret = secret })
+    self:save()
+    return id
+end
+
+-- Remove a webhook (and its undelivered payloads); returns true if it existed
+function Webhooks:unregister(id)
+    for i, hook in ipairs(self.state.hooks) do
+        if hook.id == id then
+            table.remove(self.state.hooks, i)
+            local kept = {}
+            for _, p in ipairs(self.state.pending) do
+                if p.hook ~= id then table.insert(kept, p) end
+            end
+            self.state.pending = kept
+            self:save()
+            return true
+        end
+    end
+    return false
+end
+
+function Webhooks:hooks()
+    return self.state.hooks
+end
+
+local function hook_by_id(self, id)
+    for _, hook in ipairs(self.state.hooks) do
+        if hook.id == id then return hook end
+    end
+end
+
+-- POST one payload; returns true on a 2xx answer, or false, err
+local function deliver(self, hook, body)
+    local headers = {
+        ["Content-Type"] = "application/json",
+        ["Content-Length"] = tostring(#body),
+        [Webhooks.SIGNATURE_HEADER] = Webhooks.sign(hook.secret, body)
+    }
+    local status, err = self.post(hook.url, body, headers, self.timeout_ms)
+    if not status then return false, err end
+    if type(status) ~= "number" or status < 200 or status > 299 then
+        return false, "HTTP " .. tostring(status)
+    end
+    return true
+end
+
+local function finalized_number(api)
+    local header = api:chain_getHeader(api:chain_getFinalizedHead())
+    return tonumber((header.number:gsub("^0x", "")), 16)
+end
+
+-- Deliver events from finalized blocks not handled yet, and retry earlier failures
+-- Returns { blocks, delivered, failed, dropped }, or nil, err when the chain could not be read
+--   (deliveries that fail are kept for the next call until max_attempts is reached)
+function Webhooks:dispatch()
+    local report = { blocks = 0, delivered = 0, failed = 0, dropped = 0 }
+
+    local retries = self.state.pending
+    self.state.pending = {}
+    local function attempt(p)
+        local hook = hook_by_id(self, p.hook)
+        if not hook then return end
+        local ok, err = deliver(self, hook, p.body)
+        if ok then
+            report.delivered = report.delivered + 1
+            return
+        end
+        p.attempts = (p.attempts or 0) + 1
+        p.error = err
+        if p.attempts >= self.max_attempts then
+            report.dropped = report.dropped + 1
+        else
+            report.failed = report.failed + 1
+            table.insert(self.state.pending, p)
+        end
+    end
+    for _, p in ipairs(retries) do attempt(p) end
+
+    local compiled = {}
+    for _, hook in ipairs(self.state.hooks) do compiled[hook.id] = Scanner.compile(hook.filter) end
+
+    local ok, head = pcall(finalized_number, self.api)
+    if not ok then
+        self:save()
+        return nil, "Cannot read the finalized head: " .. tostring(head)
+    end
+    local next_block = self.state.next_block or self.start_block or head
+    local last = math.min(head, next_block + self.max_blocks - 1)
+    for n = next_block, last do
+        local hash = self.api:chain_getBlockHash(n)
+        local got, items = pcall(Scanner.scan_block, self.api, n, { events = true }, hash)
+        if not got then
+            self:save()
+            return nil, "Block " .. n .. ": " .. tostring(items)
+        end
+        for _, item in ipairs(items) do
+            for _, hook in ipairs(self.state.hooks) do
+                if Scanner.match_event(compiled[hook.id], item.event) then
+                    attempt({ hook = hook.id, body = json.encode({
+                        hook = hook.id,
+                        block_number = n,
+                        block_hash = hash,
+                        event_index = item.event.index,
+                        phase = item.event.phase.variant,
+                        extrinsic_index = item.extrinsic_index,
+                        pallet = item.event.pallet,
+                        name = item.event.name,
+                        fields = item.event.fields,
+                        topics = item.event.topics
+                    }) })
+                end
+            end
+        end
+        self.state.next_block = n + 1
+        report.blocks = report.blocks + 1
+    end
+    self:save()
+    return report
+end
+
+-- One-shot helper for setup scripts: register a webhook in a state file
+-- Returns the hook id
+function Webhooks.register_webhook(state_file, url, filter, secret)
+    return Webhooks.new(nil, { state_file = state_file }):register(url, filter, secret)
+end
+
+return Webhooks
@@ -0,0 +1,131 @@
+-- sublua/weights.lua
+-- Call weight introspection and block weight limits
+-- Weights come from the TransactionPaymentCallApi runtime API (no signature needed) and
+-- limits from the System.BlockWeights constant, so bots can pack Utility.batch calls
+-- without exceeding what one extrinsic may use
+-- Weights are { ref_time, proof_size } tables of plain numbers (both fit well below 2^53)
+
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+local Call = require("sublua.call")
+
+local Weights = {}
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Decoded sp_weights Weight (decimal strings) -> numbers; Option<Weight> None -> nil
+local function weight(value)
+    if type(value) ~= "table" then return nil end
+    if value.variant then
+        if value.variant ~= "Some" then return nil end
+        value = value.value
+    end
+    return { ref_time = tonumber(value.ref_time), proof_size = tonumber(value.proof_size) }
+end
+
+-- ============================================================
+-- Limits
+-- ============================================================
+
+-- Block weight limits from System.BlockWeights
+-- meta_source: parsed metadata, RPC client, or metadata hex
+-- Returns { base_block, max_block, normal, operational, mandatory }
+--   each class: { base_extrinsic, max_extrinsic, max_total, reserved } (nil when unlimited)
+function Weights.block_limits(meta_source)
+    local meta = Metadata.from_source(meta_source)
+    local c = Metadata.get_constant(meta, "System", "BlockWeights")
+    if not c then error("System.BlockWeights constant not found in metadata") end
+    local value = Codec.decode(meta, c.type_id, c.value)
+    local limits = { base_block = weight(value.base_block), max_block = weight(value.max_block) }
+    for class, w in pairs(value.per_class) do
+        limits[class] = {
+            base_extrinsic = weight(w.base_extrinsic),
+            max_extrinsic = weight(w.max_extrinsic),
+            max_total = weight(w.max_total),
+            reserved = weight(w.reserved)
+        }
+    end
+    return limits
+end
+
+-- ============================================================
+-- Call Weights
+-- ============================================================
+
+local CLASSES = { [0] = "Normal", [1] = "Operational", [2] = "Mandatory" }
+
+-- Weight and fee of call bytes (TransactionPaymentCallApi_query_call_info)
+-- call_bytes: raw call (pallet index, call index, args)
+-- at: block hash (default: best block)
+-- Returns { ref_time, proof_size, class, partial_fee }; partial_fee is a decimal string
+function Weights.query_call(api, call_bytes, at)
+    local meta = api:get_metadata()
+    local len = #call_bytes
+    local input = call_bytes .. string.char(len % 256, math.floor(len / 256) % 256,
+        math.floor(len / 65536) % 256, math.floor(len / 16777216) % 256)
+    local data = from_hex(api:state_call("TransactionPaymentCallApi_query_call_info", "0x" .. to_hex(input), at))
+
+    local type_id = assert(Codec.find_type(meta, "sp_weights::weight_v2::Weight"), "Weight type not in metadata")
+    local w, offset = Codec.decode(meta, type_id, data, 1)
+    local result = weight(w)
+    result.class = CLASSES[string.byte(data, offset)]
+    result.partial_fee = Codec.le_to_decimal(data:sub(offset + 1, offset + 16))
+    return result
+end
+
+-- Weight of a call given by name, next to the limits it has to fit in
+-- args: table, or the same as a JSON string
+-- Returns { ref_time, proof_size, class, partial_fee, limit, fits, limits }
+--   limit: max_extrinsic of the call's dispatch class; fits: the call alone stays within it
+function Weights.query_call_weight(api, pallet, call_name, args, at)
+    if type(args) == "string" then args = require("cjson").decode(args) end
+    local result = Weights.query_call(api, Call.encode_call(api, pallet, call_name, args or {}), at)
+    result.limits = Weights.block_limits(api:get_metadata())
+    local class = result.limits[(result.class or "Normal"):lower()]
+    result.limit = class and (class.max_extrinsic or class.max_total) or result.limits.max_block
+    result.fits = result.limit == nil
+        or (result.ref_time <= result.limit.ref_time and result.proof_size <= result.limit.proof_size)
+    return result
+end
+
+-- Split calls into batches that each stay within a Normal extrinsic's weight
+-- calls: list of raw call bytes
+-- opts: { ratio = 0.9, at = nil }
+--   ratio: share of max_extrinsic to fill, leaving room for the batch call itself
+-- Returns a list of batches (lists of call bytes, in order) and the weight of each batch
+-- Errors if a single call is heavier than the budget
+function Weights.pack(api, calls, opts)
+    opts = opts or {}
+    local ratio = opts.ratio or 0.9
+    local limits = Weights.block_limits(api:get_metadata())
+    local max = limits.normal.max_extrinsic or limits.normal.max_total or limits.max_block
+    local budget = { ref_time = max.ref_time * ratio, proof_size = max.proof_size * ratio }
+
+    local batches, totals = {}, {}
+    local current, total
+    for i, call in ipairs(calls) do
+        local w = Weights.query_call(api, call, opts.at)
+        if w.ref_time > budget.ref_time or w.proof_size > budget.proof_size then
+            error("Call " .. i .. " alone exceeds the extrinsic weight budget")
+        end
+        if not current or total.ref_time + w.ref_time > budget.ref_time
+            or total.proof_size + w.proof_size > budget.proof_size then
+            current, total = {}, { ref_time = 0, proof_size = 0 }
+            table.insert(batches, current)
+            table.insert(totals, total)
+        end
+        table.insert(current, call)
+        total.ref_time = total.ref_time + w.ref_time
+        total.proof_size = total.proof_size + w.proof_size
+    end
+    return batches, totals
+end
+
+return Weights
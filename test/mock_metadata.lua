@@ -0,0 +1,487 @@
+-- test/mock_metadata.lua
+-- Builds a small SCALE-encoded RuntimeMetadataV14 blob for offline tests
+--
+-- Types are declared by name and resolved to sequential ids at encode time,
+-- so tests exercise the real Metadata.parse path instead of hand-built tables.
+
+local Scale = require("sublua.scale")
+
+local Mock = {}
+
+local PRIMITIVE_INDEX = {
+    bool = 0, char = 1, str = 2, u8 = 3, u16 = 4, u32 = 5, u64 = 6, u128 = 7, u256 = 8,
+    i8 = 9, i16 = 10, i32 = 11, i64 = 12, i128 = 13, i256 = 14
+}
+
+local HASHER_INDEX = {
+    Blake2_128 = 0, Blake2_256 = 1, Blake2_128Concat = 2, Twox128 = 3,
+    Twox256 = 4, Twox64Concat = 5, Identity = 6
+}
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+-- ============================================================
+-- Type registry (name -> definition). Definitions:
+--   { primitive = "u32" }
+--   { composite = { { name = "x", type = "u32" }, ... }, path = {...} }
+--   { variant = { { name = "A", index = 0, fields = {...} }, ... }, path = {...} }
+--   { sequence = "u8" } | { array = "u8", len = 32 } | { tuple = { "u8", "u32" } }
+--   { compact = "u128" } | { bitseq = { "u8", "Lsb0" } }
+-- ============================================================
+
+local TYPES = {
+    { "u8", { primitive = "u8" } },
+    { "u16", { primitive = "u16" } },
+    { "u32", { primitive = "u32" } },
+    { "u64", { primitive = "u64" } },
+    { "u128", { primitive = "u128" } },
+    { "i64", { primitive = "i64" } },
+    { "bool", { primitive = "bool" } },
+    { "str", { primitive = "str" } },
+    { "()", { tuple = {} } },
+    { "[u8;4]", { array = "u8", len = 4 } },
+    { "[u8;20]", { array = "u8", len = 20 } },
+    { "[u8;32]", { array = "u8", len = 32 } },
+    { "[u8;64]", { array = "u8", len = 64 } },
+    { "Vec<u8>", { sequence = "u8" } },
+    { "Compact<u32>", { compact = "u32" } },
+    { "Compact<u64>", { compact = "u64" } },
+    { "Compact<u128>", { compact = "u128" } },
+    { "AccountId32", { path = { "sp_core", "crypto", "AccountId32" }, composite = { { type = "[u8;32]", type_name = "[u8; 32]" } } } },
+    { "H256", { path = { "primitive_types", "H256" }, composite = { { type = "[u8;32]" } } } },
+    { "Vec<H256>", { sequence = "H256" } },
+    { "Vec<AccountId32>", { sequence = "AccountId32" } },
+    { "Perbill", { path = { "sp_arithmetic", "per_things", "Perbill" }, composite = { { type = "u32" } } } },
+    { "MultiAddress", { path = { "sp_runtime", "multiaddress", "MultiAddress" }, variant = {
+        { name = "Id", index = 0, fields = { { type = "AccountId32" } } },
+        { name = "Index", index = 1, fields = { { type = "Compact<()>" } } },
+        { name = "Raw", index = 2, fields = { { type = "Vec<u8>" } } },
+        { name = "Address32", index = 3, fields = { { type = "[u8;32]" } } },
+        { name = "Address20", index = 4, fields = { { type = "[u8;20]" } } }
+    } } },
+    { "Compact<()>", { compact = "()" } },
+    { "Option<u32>", { path = { "Option" }, variant = {
+        { name = "None", index = 0 },
+        { name = "Some", index = 1, fields = { { type = "u32" } } }
+    } } },
+    { "Weight", { path = { "sp_weights", "weight_v2", "Weight" }, composite = {
+        { name = "ref_time", type = "Compact<u64>" },
+        { name = "proof_size", type = "Compact<u64>" }
+    } } },
+    { "DispatchClass", { path = { "frame_support", "dispatch", "DispatchClass" }, variant = {
+        { name = "Normal", index = 0 }, { name = "Operational", index = 1 }, { name = "Mandatory", index = 2 }
+    } } },
+    { "Pays", { path = { "frame_support", "dispatch", "Pays" }, variant = {
+        { name = "Yes", index = 0 }, { name = "No", index = 1 }
+    } } },
+    { "DispatchInfo", { path = { "frame_support", "dispatch", "DispatchInfo" }, composite = {
+        { name = "weight", type = "Weight" },
+        { name = "class", type = "DispatchClass" },
+        { name = "pays_fee", type = "Pays" }
+    } } },
+    { "ModuleError", { path = { "sp_runtime", "ModuleError" }, composite = {
+        { name = "index", type = "u8" },
+        { name = "error", type = "[u8;4]" }
+    } } },
+    { "DispatchError", { path = { "sp_runtime", "DispatchError" }, variant = {
+        { name = "Other", index = 0 },
+        { name = "CannotLookup", index = 1 },
+        { name = "BadOrigin", index = 2 },
+        { name = "Module", index = 3, fields = { { type = "ModuleError" } } }
+    } } },
+    -- Calls
+    { "SystemCall", { path = { "frame_system", "pallet", "Call" }, variant = {
+        { name = "remark", index = 0, fields = { { name = "remark", type = "Vec<u8>" } } },
+        { name = "set_code", index = 2, fields = { { name = "code", type = "Vec<u8>" } } }
+    } } },
+    { "BalancesCall", { path = { "pallet_balances", "pallet", "Call" }, variant = {
+        { name = "transfer_allow_death", index = 0, fields = {
+            { name = "dest", type = "MultiAddress" }, { name = "value", type = "Compact<u128>" } } },
+        { name = "transfer_keep_alive", index = 3, fields = {
+            { name = "dest", type = "MultiAddress" }, { name = "value", type = "Compact<u128>" } } },
+        { name = "transfer_all", index = 4, fields = {
+            { name = "dest", type = "MultiAddress" }, { name = "keep_alive", type = "bool" } } }
+    } } },
+    { "RuntimeCall", { path = { "runtime", "RuntimeCall" }, variant = {
+        { name = "System", index = 0, fields = { { type = "SystemCall" } } },
+        { name = "Balances", index = 4, fields = { { type = "BalancesCall" } } }
+    } } },
+    -- Events
+    { "SystemEvent", { path = { "frame_system", "pallet", "Event" }, variant = {
+        { name = "ExtrinsicSuccess", index = 0, fields = { { name = "dispatch_info", type = "DispatchInfo" } } },
+        { name = "ExtrinsicFailed", index = 1, fields = {
+            { name = "dispatch_error", type = "DispatchError" }, { name = "dispatch_info", type = "DispatchInfo" } } }
+    } } },
+    { "BalancesEvent", { path = { "pallet_balances", "pallet", "Event" }, variant = {
+        { name = "Transfer", index = 2, fields = {
+            { name = "from", type = "AccountId32" }, { name = "to", type = "AccountId32" }, { name = "amount", type = "u128" } } }
+    } } },
+    { "StakingEvent", { path = { "pallet_staking", "pallet", "Event" }, variant = {
+        { name = "Slashed", index = 1, fields = {
+            { name = "staker", type = "AccountId32" }, { name = "amount", type = "u128" } } }
+    } } },
+    { "(AccountId32, ())", { tuple = { "AccountId32", "()" } } },
+    { "Vec<(AccountId32, ())>", { sequence = "(AccountId32, ())" } },
+    { "ImOnlineEvent", { path = { "pallet_im_online", "pallet", "Event" }, variant = {
+        { name = "AllGood", index = 1 },
+        { name = "SomeOffline", index = 2, fields = { { name = "offline", type = "Vec<(AccountId32, ())>" } } }
+    } } },
+    { "RuntimeEvent", { path = { "runtime", "RuntimeEvent" }, variant = {
+        { name = "System", index = 0, fields = { { type = "SystemEvent" } } },
+        { name = "Balances", index = 4, fields = { { type = "BalancesEvent" } } },
+        { name = "Staking", index = 7, fields = { { type = "StakingEvent" } } },
+        { name = "ImOnline", index = 12, fields = { { type = "ImOnlineEvent" } } },
+        { name = "TransactionPayment", index = 32, fields = { { type = "TransactionPaymentEvent" } } }
+    } } },
+    { "Phase", { path = { "frame_system", "Phase" }, variant = {
+        { name = "ApplyExtrinsic", index = 0, fields = { { type = "u32" } } },
+        { name = "Finalization", index = 1 },
+        { name = "Initialization", index = 2 }
+    } } },
+    { "EventRecord", { path = { "frame_system", "EventRecord" }, composite = {
+        { name = "phase", type = "Phase" },
+        { name = "event", type = "RuntimeEvent" },
+        { name = "topics", type = "Vec<H256>" }
+    } } },
+    { "Vec<EventRecord>", { sequence = "EventRecord" } },
+    -- Storage values
+    { "AccountData", { path = { "pallet_balances", "types", "AccountData" }, composite = {
+        { name = "free", type = "u128" }, { name = "reserved", type = "u128" },
+        { name = "frozen", type = "u128" }, { name = "flags", type = "u128" }
+    } } },
+    { "AccountInfo", { path = { "frame_system", "AccountInfo" }, composite = {
+        { name = "nonce", type = "u32" }, { name = "consumers", type = "u32" },
+        { name = "providers", type = "u32" }, { name = "sufficients", type = "u32" },
+        { name = "data", type = "AccountData" }
+    } } },
+    { "(AccountId32, u128)", { tuple = { "AccountId32", "u128" } } },
+    { "Vec<(AccountId32, u128)>", { sequence = "(AccountId32, u128)" } },
+    { "UnappliedSlash", { path = { "pallet_staking", "UnappliedSlash" }, composite = {
+        { name = "validator", type = "AccountId32" },
+        { name = "own", type = "u128" },
+        { name = "others", type = "Vec<(AccountId32, u128)>" },
+        { name = "reporters", type = "Vec<AccountId32>" },
+        { name = "payout", type = "u128" }
+    } } },
+    { "Vec<UnappliedSlash>", { sequence = "UnappliedSlash" } },
+    -- Extrinsic
+    { "MultiSignature", { path = { "sp_runtime", "MultiSignature" }, variant = {
+        { name = "Ed25519", index = 0, fields = { { type = "[u8;64]" } } },
+        { name = "Sr25519", index = 1, fields = { { type = "[u8;64]" } } }
+    } } },
+    { "UncheckedExtrinsic", {
+        path = { "sp_runtime", "generic", "unchecked_extrinsic", "UncheckedExtrinsic" },
+        params = { { name = "Address", type = "MultiAddress" }, { name = "Call", type = "RuntimeCall" },
+                   { name = "Signature", type = "MultiSignature" }, { name = "Extra", type = "()" } },
+        composite = { { type = "Vec<u8>" } }
+    } },
+    { "Era", { path = { "sp_runtime", "generic", "era", "Era" }, variant = (function()
+        -- Immortal = 0, MortalN = N carrying the second encoded byte
+        local variants = { { name = "Immortal", index = 0 } }
+        for i = 1, 255 do
+            variants[i + 1] = { name = "Mortal" .. i, index = i, fields = { { type = "u8" } } }
+        end
+        return variants
+    end)() } },
+    { "CheckMortality", { path = { "frame_system", "extensions", "check_mortality", "CheckMortality" }, composite = { { type = "Era" } } } },
+    { "CheckNonce", { path = { "frame_system", "extensions", "check_nonce", "CheckNonce" }, composite = { { type = "Compact<u32>" } } } },
+    { "ChargeTransactionPayment", { path = { "pallet_transaction_payment", "ChargeTransactionPayment" }, composite = { { type = "Compact<u128>" } } } },
+    { "Mode", { path = { "frame_metadata_hash_extension", "Mode" }, variant = {
+        { name = "Disabled", index = 0 }, { name = "Enabled", index = 1 }
+    } } },
+    { "CheckMetadataHash", { path = { "frame_metadata_hash_extension", "CheckMetadataHash" }, composite = { { name = "mode", type = "Mode" } } } },
+    { "Option<H256>", { path = { "Option" }, variant = {
+        { name = "None", index = 0 },
+        { name = "Some", index = 1, fields = { { type = "H256" } } }
+    } } },
+    -- Errors
+    { "BalancesError", { path = { "pallet_balances", "pallet", "Error" }, variant = {
+        { name = "VestingBalance", index = 0, docs = { "Vesting balance too high to send value." } },
+        { name = "LiquidityRestrictions", index = 1, docs = { "Account liquidity restrictions prevent withdrawal." } },
+        { name = "InsufficientBalance", index = 2, docs = { "Balance too low to send value." } }
+    } } },
+    { "TransactionPaymentEvent", { path = { "pallet_transaction_payment", "pallet", "Event" }, variant = {
+        { name = "TransactionFeePaid", index = 0, fields = {
+            { name = "who", type = "AccountId32" }, { name = "actual_fee", type = "u128" }, { name = "tip", type = "u128" } } }
+    } } },
+    -- Staking and nomination pools
+    { "UnlockChunk", { path = { "pallet_staking", "UnlockChunk" }, composite = {
+        { name = "value", type = "Compact<u128>" }, { name = "era", type = "Compact<u32>" }
+    } } },
+    { "Vec<UnlockChunk>", { sequence = "UnlockChunk" } },
+    { "StakingLedger", { path = { "pallet_staking", "StakingLedger" }, composite = {
+        { name = "stash", type = "AccountId32" }, { name = "total", type = "Compact<u128>" },
+        { name = "active", type = "Compact<u128>" }, { name = "unlocking", type = "Vec<UnlockChunk>" }
+    } } },
+    { "Nominations", { path = { "pallet_staking", "Nominations" }, composite = {
+        { name = "targets", type = "Vec<AccountId32>" }, { name = "submitted_in", type = "u32" },
+        { name = "suppressed", type = "bool" }
+    } } },
+    { "PoolMember", { path = { "pallet_nomination_pools", "PoolMember" }, composite = {
+        { name = "pool_id", type = "u32" }, { name = "points", type = "u128" },
+        { name = "last_recorded_reward_counter", type = "u128" }
+    } } },
+    -- Block weight limits
+    { "Option<Weight>", { path = { "Option" }, variant = {
+        { name = "None", index = 0 },
+        { name = "Some", index = 1, fields = { { type = "Weight" } } }
+    } } },
+    { "WeightsPerClass", { path = { "frame_system", "limits", "WeightsPerClass" }, composite = {
+        { name = "base_extrinsic", type = "Weight" }, { name = "max_extrinsic", type = "Option<Weight>" },
+        { name = "max_total", type = "Option<Weight>" }, { name = "reserved", type = "Option<Weight>" }
+    } } },
+    { "PerDispatchClass<WeightsPerClass>", { path = { "frame_support", "dispatch", "PerDispatchClass" }, composite = {
+        { name = "normal", type = "WeightsPerClass" }, { name = "operational", type = "WeightsPerClass" },
+        { name = "mandatory", type = "WeightsPerClass" }
+    } } },
+    { "BlockWeights", { path = { "frame_system", "limits", "BlockWeights" }, composite = {
+        { name = "base_block", type = "Weight" }, { name = "max_block", type = "Weight" },
+        { name = "per_class", type = "PerDispatchClass<WeightsPerClass>" }
+    } } },
+}
+
+-- ============================================================
+-- Pallets
+-- ============================================================
+
+-- System.BlockWeights: 2s of ref time and 5 MiB of proof per block, 75% for Normal
+local function weight(ref_time, proof_size)
+    return Scale.encode_compact(ref_time) .. Scale.encode_compact(proof_size)
+end
+local function some_weight(ref_time, proof_size) return "\1" .. weight(ref_time, proof_size) end
+local BLOCK_WEIGHTS = weight(5000000, 0) .. weight(2000000000000, 5242880)
+    .. weight(125000000, 0) .. some_weight(1479875000000, 3879731) .. some_weight(1500000000000, 3932160) .. some_weight(0, 0)
+    .. weight(125000000, 0) .. some_weight(1979875000000, 5190451) .. some_weight(2000000000000, 5242880)
+        .. some_weight(500000000000, 1310720)
+    .. weight(125000000, 0) .. "\0\0\0"
+
+local PALLETS = {
+    {
+        name = "System", index = 0, calls = "SystemCall", events = "SystemEvent",
+        storage = { prefix = "System", entries = {
+            { name = "Account", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "AccountInfo" } },
+            { name = "Number", plain = "u32" },
+            { name = "Events", plain = "Vec<EventRecord>" }
+        } },
+        constants = {
+            { name = "SS58Prefix", type = "u16", value = "\42\0" },
+            { name = "BlockWeights", type = "BlockWeights", value = BLOCK_WEIGHTS }
+        }
+    },
+    {
+        name = "Balances", index = 4, calls = "BalancesCall", events = "BalancesEvent", errors = "BalancesError",
+        storage = { prefix = "Balances", entries = {
+            { name = "TotalIssuance", plain = "u128" }
+        } },
+        constants = {
+            { name = "ExistentialDeposit", type = "u128", value = "\0\228\11\84\2" .. string.rep("\0", 11) }
+        }
+    },
+    {
+        name = "Staking", index = 7, events = "StakingEvent",
+        storage = { prefix = "Staking", entries = {
+            { name = "UnappliedSlashes", map = { hashers = { "Twox64Concat" }, key = "u32", value = "Vec<UnappliedSlash>" } },
+            { name = "Bonded", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "AccountId32" } },
+            { name = "Ledger", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "StakingLedger" } },
+            { name = "Nominators", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "Nominations" } }
+        } }
+    },
+    { name = "ImOnline", index = 12, events = "ImOnlineEvent" },
+    { name = "TransactionPayment", index = 32, events = "TransactionPaymentEvent" },
+    {
+        name = "NominationPools", index = 39,
+        storage = { prefix = "NominationPools", entries = {
+            { name = "PoolMembers", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "PoolMember" } }
+        } }
+    },
+}
+
+local EXTENSIONS = {
+    { "CheckNonZeroSender", "()", "()" },
+    { "CheckSpecVersion", "()", "u32" },
+    { "CheckTxVersion", "()", "u32" },
+    { "CheckGenesis", "()", "H256" },
+    { "CheckMortality", "CheckMortality", "H256" },
+    { "CheckNonce", "CheckNonce", "()" },
+    { "CheckWeight", "()", "()" },
+    { "ChargeTransactionPayment", "ChargeTransactionPayment", "()" },
+    { "CheckMetadataHash", "CheckMetadataHash", "Option<H256>" }
+}
+
+-- ============================================================
+-- Encoder
+-- ============================================================
+
+local function enc_str(s) return Scale.encode_compact(#s) .. s end
+
+local function enc_vec(list, f)
+    local out = { Scale.encode_compact(#list) }
+    for _, item in ipairs(list) do table.insert(out, f(item)) end
+    return table.concat(out)
+end
+
+local function enc_opt(v, f)
+    if v == nil then return "\0" end
+    return "\1" .. f(v)
+end
+
+local function build(types_list, pallets, extensions)
+    local ids = {}
+    for i, t in ipairs(types_list) do ids[t[1]] = i - 1 end
+    local function id(name)
+        local v = ids[name]
+        if not v then error("mock_metadata: unknown type " .. tostring(name)) end
+        return Scale.encode_compact(v)
+    end
+
+    local function enc_field(f)
+        return enc_opt(f.name, enc_str) .. id(f.type) .. enc_opt(f.type_name, enc_str) .. enc_vec({}, enc_str)
+    end
+
+    local function enc_def(d)
+        if d.composite then
+            return "\0" .. enc_vec(d.composite, enc_field)
+        elseif d.variant then
+            return "\1" .. enc_vec(d.variant, function(v)
+                return enc_str(v.name) .. enc_vec(v.fields or {}, enc_field) .. string.char(v.index) .. enc_vec(v.docs or {}, enc_str)
+            end)
+        elseif d.sequence then
+            return "\2" .. id(d.sequence)
+        elseif d.array then
+            return "\3" .. Scale.encode_u32(d.len) .. id(d.array)
+        elseif d.tuple then
+            return "\4" .. enc_vec(d.tuple, id)
+        elseif d.primitive then
+            return "\5" .. string.char(PRIMITIVE_INDEX[d.primitive])
+        elseif d.compact then
+            return "\6" .. id(d.compact)
+        elseif d.bitseq then
+            return "\7" .. id(d.bitseq[1]) .. id(d.bitseq[2])
+        end
+        error("mock_metadata: bad type definition")
+    end
+
+    local parts = { "meta", string.char(14) }
+
+    table.insert(parts, enc_vec(types_list, function(t)
+        local d = t[2]
+        return Scale.encode_compact(ids[t[1]])
+            .. enc_vec(d.path or {}, enc_str)
+            .. enc_vec(d.params or {}, function(p) return enc_str(p.name) .. enc_opt(p.type, id) end)
+            .. enc_def(d)
+            .. enc_vec({}, enc_str)
+    end))
+
+    table.insert(parts, enc_vec(pallets, function(p)
+        local storage = enc_opt(p.storage, function(s)
+            return enc_str(s.prefix) .. enc_vec(s.entries, function(e)
+                local ty
+                if e.plain then
+                    ty = "\0" .. id(e.plain)
+                else
+                    ty = "\1" .. enc_vec(e.map.hashers, function(h) return string.char(HASHER_INDEX[h]) end)
+                        .. id(e.map.key) .. id(e.map.value)
+                end
+                local default = e.default or ""
+                return enc_str(e.name) .. string.char(e.modifier or 0) .. ty
+                    .. Scale.encode_compact(#default) .. default .. enc_vec({}, enc_str)
+            end)
+        end)
+        return enc_str(p.name) .. storage
+            .. enc_opt(p.calls, id) .. enc_opt(p.events, id)
+            .. enc_vec(p.constants or {}, function(c)
+                return enc_str(c.name) .. id(c.type) .. Scale.encode_compact(#c.value) .. c.value .. enc_vec({}, enc_str)
+            end)
+            .. enc_opt(p.errors, id)
+            .. string.char(p.index)
+    end))
+
+    -- ExtrinsicMetadata { ty, version, signed_extensions }
+    table.insert(parts, id("UncheckedExtrinsic") .. string.char(4) .. enc_vec(extensions, function(e)
+        return enc_str(e[1]) .. id(e[2]) .. id(e[3])
+    end))
+
+    -- Runtime type
+    table.insert(parts, id("()"))
+
+    return "0x" .. to_hex(table.concat(parts))
+end
+
+-- Hex-encoded metadata. extra = { types = {...}, pallets = {...} } appends fixtures.
+function Mock.hex(extra)
+    extra = extra or {}
+    local types_list = {}
+    for _, t in ipairs(TYPES) do table.insert(types_list, t) end
+    for _, t in ipairs(extra.types or {}) do table.insert(types_list, t) end
+    local pallets = {}
+    for _, p in ipairs(PALLETS) do table.insert(pallets, p) end
+    for _, p in ipairs(extra.pallets or {}) do table.insert(pallets, p) end
+    return build(types_list, pallets, extra.extensions or EXTENSIONS)
+end
+
+-- Parsed metadata table
+function Mock.metadata(extra)
+    local Metadata = require("sublua.metadata")
+    return Metadata.parse(Mock.hex(extra))
+end
+
+-- Type id lookup by fixture name (ids follow declaration order)
+function Mock.type_id(name, extra)
+    local list = {}
+    for _, t in ipairs(TYPES) do table.insert(list, t) end
+    for _, t in ipairs((extra and extra.types) or {}) do table.insert(list, t) end
+    for i, t in ipairs(list) do
+        if t[1] == name then return i - 1 end
+    end
+    error("mock_metadata: unknown type " .. tostring(name))
+end
+
+-- Fake RPC client serving storage from a table { [key] = hex } and the mock metadata
+function Mock.api(storage, extra)
+    local meta = Mock.metadata(extra)
+    local api = { metadata = meta, storage = storage or {}, calls = {} }
+    function api:get_metadata() return self.metadata end
+    function api:state_getStorage(key, at)
+        table.insert(self.calls, { "state_getStorage", key, at })
+        return self.storage[key]
+    end
+    function api:state_queryStorageAt(keys, at)
+        table.insert(self.calls, { "state_queryStorageAt", keys, at })
+        local changes = {}
+        for i, key in ipairs(keys) do changes[i] = { key, self.storage[key] } end
+        return { { block = at, changes = changes } }
+    end
+    function api:state_getKeysPaged(prefix)
+        local keys = {}
+        for k in pairs(self.storage) do
+            if k:sub(1, #prefix) == prefix then table.insert(keys, k) end
+        end
+        table.sort(keys)
+        return keys
+    end
+    function api:chain_getBlockHash(n) return "0x" .. string.format("%064x", n or 0) end
+    function api:chain_getFinalizedHead() return "0x" .. string.rep("11", 32) end
+    function api:chain_getHeader() return { number = string.format("0x%x", self.best_number or 100) } end
+    function api:state_getRuntimeVersion() return { specVersion = 100, transactionVersion = 1 } end
+    function api:check_runtime_upgrade() return false end
+    function api:with_deadline(timeout_ms, fn)
+        self.deadline_ms = timeout_ms
+        local results = { fn(self) }
+        self.deadline_ms = nil
+        return results[1], results[2]
+    end
+    function api:time_left() return self.deadline_ms and self.deadline_ms / 1000 end
+    function api:get_chain_properties() return { decimals = 12, symbol = "UNIT", ss58_prefix = 42 } end
+    function api:system_accountNextIndex(address)
+        table.insert(self.calls, { "system_accountNextIndex", address })
+        return self.next_index or 0
+    end
+    function api:system_account(address)
+        return { nonce = self.next_index or 0 }
+    end
+    return api
+end
+
+return Mock
@@ -9,8 +9,62 @@ local tests = {
     {name = "SCALE Codec", file = "test_scale.lua"},
     {name = "Keyring", file = "test_keyring.lua"},
     {name = "Transaction Builder", file = "test_transaction.lua"},
+    {name = "Call Encoding", file = "test_call.lua"},
     {name = "RPC Client", file = "test_rpc.lua"},
     {name = "XCM", file = "test_xcm.lua"},
+    {name = "Session Keys", file = "test_session.lua"},
+    {name = "Metadata Codec", file = "test_codec.lua"},
+    {name = "Staking Monitoring", file = "test_staking.lua"},
+    {name = "Storage Keys", file = "test_storage.lua"},
+    {name = "Utility", file = "test_utility.lua"},
+    {name = "Offline Signing (UOS)", file = "test_uos.lua"},
+    {name = "Faucet", file = "test_faucet.lua"},
+    {name = "Chain Spec", file = "test_chainspec.lua"},
+    {name = "GRANDPA", file = "test_grandpa.lua"},
+    {name = "Registry", file = "test_registry.lua"},
+    {name = "Address", file = "test_address.lua"},
+    {name = "Portfolio", file = "test_portfolio.lua"},
+    {name = "Prices", file = "test_prices.lua"},
+    {name = "Custom Types", file = "test_types.lua"},
+    {name = "Metadata JSON", file = "test_metadata_json.lua"},
+    {name = "Codegen", file = "test_codegen.lua"},
+    {name = "CLI", file = "test_cli.lua"},
+    {name = "ABI", file = "test_abi.lua"},
+    {name = "Block Scanner", file = "test_scanner.lua"},
+    {name = "Indexer", file = "test_indexer.lua"},
+    {name = "Webhooks", file = "test_webhooks.lua"},
+    {name = "Merchant", file = "test_merchant.lua"},
+    {name = "Weights", file = "test_weights.lua"},
+    {name = "Keystore", file = "test_keystore.lua"},
+    {name = "Ledger Derivation", file = "test_ledger.lua"},
+    {name = "VRF", file = "test_vrf.lua"},
+    {name = "Random", file = "test_random.lua"},
+    {name = "Society", file = "test_society.lua"},
+    {name = "Collective", file = "test_collective.lua"},
+    {name = "Preimage", file = "test_preimage.lua"},
+    {name = "Fellowship", file = "test_fellowship.lua"},
+    {name = "Parachains", file = "test_parachains.lua"},
+    {name = "BEEFY", file = "test_beefy.lua"},
+    {name = "Snapshot", file = "test_snapshot.lua"},
+    {name = "Runtime", file = "test_runtime.lua"},
+    {name = "Offchain", file = "test_offchain.lua"},
+    {name = "Multisig", file = "test_multisig.lua"},
+    {name = "Identity", file = "test_identity.lua"},
+    {name = "Assets", file = "test_assets.lua"},
+    {name = "DEX", file = "test_dex.lua"},
+    {name = "TxBuilder", file = "test_txbuilder.lua"},
+    {name = "Prometheus", file = "test_prometheus.lua"},
+    {name = "Subscription", file = "test_subscription.lua"},
+    {name = "Pump", file = "test_pump.lua"},
+    {name = "Cancel", file = "test_cancel.lua"},
+    {name = "Introspect", file = "test_introspect.lua"},
+    {name = "Validate", file = "test_validate.lua"},
+    {name = "Safety", file = "test_safety.lua"},
+    {name = "Simulate", file = "test_simulate.lua"},
+    {name = "FastUnstake", file = "test_fast_unstake.lua"},
+    {name = "Locks", file = "test_locks.lua"},
+    {name = "TestChain", file = "test_testchain.lua"},
+    {name = "Selfcheck", file = "test_selfcheck.lua"},
     {name = "Integration", file = "test_integration.lua"},
 }
 
@@ -0,0 +1,66 @@
+-- test/test_abi.lua
+-- Unit tests for C module ABI versioning
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;" .. package.path
+
+local crypto = require("polkadot_crypto")
+local Abi = require("sublua.abi")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== ABI Tests ===\n")
+
+test("ABI: C module matches the Lua side", function()
+    assert(crypto.abi_version() == Abi.VERSION)
+    assert(crypto.ABI_VERSION == Abi.VERSION)
+    assert(Abi.check(crypto) == true)
+end)
+
+test("ABI: stale or mismatched modules are rejected", function()
+    local ok, err = Abi.check({ blake2b = crypto.blake2b })
+    assert(not ok and err:find("predates ABI versioning"))
+    ok, err = Abi.check({ abi_version = function() return Abi.VERSION + 1 end })
+    assert(not ok and err:find("ABI v" .. (Abi.VERSION + 1)) and err:find("rebuild"))
+end)
+
+test("ABI: header FFI section is plain declarations", function()
+    local decls, version = Abi.cdef("c_src/polkadot_crypto.h")
+    assert(version == Abi.VERSION)
+    assert(decls:find("unsigned int sublua_abi_version%(void%);"))
+    assert(decls:find("} sublua_keypair_t;"))
+    assert(not decls:find("#"), "preprocessor lines are not accepted by ffi.cdef")
+    local ok, err = pcall(Abi.cdef, "/nonexistent/polkadot_crypto.h")
+    assert(not ok and err:find("Cannot read C header"))
+end)
+
+test("ABI: key handles still work with the versioned layout", function()
+    local handle = crypto.keypair_create(string.rep("a", 32))
+    local sig = handle:sign("hello")
+    assert(crypto.ed25519_verify(handle:public_key(), "hello", sig))
+    handle:destroy()
+    assert(not pcall(handle.sign, handle, "hello"))
+end)
+
+print("\n=== ABI Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All ABI tests passed!")
+    os.exit(0)
+end
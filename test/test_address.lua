@@ -0,0 +1,132 @@
+-- test/test_address.lua
+-- Unit tests for SS58 address inspection, validation and conversion
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;" .. package.path
+
+local crypto = require("polkadot_crypto")
+local Address = require("sublua.address")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Address Tests ===\n")
+
+-- Alice's sr25519 public key in several network formats
+local ALICE = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+local FORMATS = {
+    [0] = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5",
+    [2] = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F",
+    [42] = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+    [64] = "cEaNSpz4PxFcZ7nT1VEKrKewH67rfx6MfcM6yKojyyPz7qaqp",
+    [1284] = "VdvKmYJfD4VXA9fzz1SbmCo2eYHSzUFbaDCZSuaNKJAe8YNg6",
+    [16383] = "yNa8JpqfFB3q8A29rCwSgxvdU94ufJw2yKKxDgznS5m1PoFvn"
+}
+
+test("Address: encode one- and two-byte prefixes", function()
+    for prefix, address in pairs(FORMATS) do
+        assert(Address.encode(ALICE, prefix) == address, "prefix " .. prefix)
+    end
+    assert(Address.encode(ALICE, "kusama") == FORMATS[2])
+    assert(Address.encode(ALICE, 42) == crypto.ss58_encode(Address.decode(FORMATS[0]), 42))
+    assert(not pcall(Address.encode, ALICE, 16384))
+    assert(not pcall(Address.encode, "0x1234", 0))
+end)
+
+test("Address: inspect", function()
+    for prefix, address in pairs(FORMATS) do
+        local info = Address.inspect(address)
+        assert(info.valid and info.format == "ss58" and info.checksum_valid)
+        assert(info.prefix == prefix and info.pubkey == ALICE)
+    end
+    assert(Address.inspect(FORMATS[0]).network == "polkadot")
+    local hex = Address.inspect(ALICE:upper():gsub("^0X", "0x"))
+    assert(hex.valid and hex.format == "hex" and hex.pubkey == ALICE and hex.prefix == nil)
+    local raw = Address.inspect(Address.decode(FORMATS[42]))
+    assert(raw.valid and raw.format == "raw" and raw.pubkey == ALICE)
+end)
+
+test("Address: inspect reports bad checksums and input", function()
+    local tampered = FORMATS[42]:sub(1, -2) .. "Z"
+    local info = Address.inspect(tampered)
+    assert(not info.valid and info.checksum_valid == false and info.prefix == 42)
+    assert(info.error:match("checksum"))
+    assert(Address.inspect("5Grwva0").error:match("base58"))
+    assert(not Address.inspect("5Grwva").valid)
+    assert(Address.inspect("0x1234").error:match("32 bytes"))
+    assert(not Address.inspect(nil).valid)
+end)
+
+test("Address: is_valid with expected network", function()
+    assert(Address.is_valid(FORMATS[0]))
+    assert(Address.is_valid(FORMATS[0], 0))
+    assert(Address.is_valid(FORMATS[0], "polkadot"))
+    local ok, err = Address.is_valid(FORMATS[2], "polkadot")
+    assert(not ok and err:match("prefix 2, expected 0"))
+    ok, err = Address.is_valid(ALICE)
+    assert(not ok and err:match("Not an SS58"))
+    assert(not Address.is_valid(FORMATS[42]:sub(1, -2) .. "Z"))
+end)
+
+test("Address: convert between formats", function()
+    assert(Address.convert(FORMATS[2], 0) == FORMATS[0])
+    assert(Address.convert(FORMATS[1284], "substrate") == FORMATS[42])
+    assert(Address.convert(FORMATS[0], "hex") == ALICE)
+    assert(Address.convert(ALICE, 2) == FORMATS[2])
+    assert(not pcall(Address.convert, "nope", 0))
+end)
+
+test("Address: decode", function()
+    local pubkey, prefix = Address.decode(FORMATS[1284])
+    assert(#pubkey == 32 and prefix == 1284)
+    assert(not pcall(Address.decode, ALICE))
+end)
+
+local ALITH = "0xf24FF3a9CF04c71Dbc94D0b566f7A27B94566cac"
+
+test("Address: EIP-55 checksummed H160", function()
+    assert(Address.to_checksum_h160("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed") == "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+    assert(Address.to_checksum_h160(ALITH:lower()) == ALITH)
+    assert(not pcall(Address.to_checksum_h160, "0x1234"))
+end)
+
+test("Address: EVM to Substrate, hashed and truncated", function()
+    local address, pubkey = Address.evm_to_substrate(ALITH)
+    assert(address == "5Fghzk1AJt88PeFEzuRfXzbPchiBbsVGTTXcdx599VdZzkTA")
+    assert(pubkey == "0x" .. Address.inspect(address).pubkey:sub(3))
+    assert(Address.evm_to_substrate(ALITH:lower(), "hashed") == address)
+    assert(Address.evm_to_substrate(ALITH, "truncated") == "5HYRCKHYJN9z5xUtfFkyMj4JUhsAv19D6ExiWtsJedTt9vJC")
+    assert(Address.inspect(Address.evm_to_substrate(ALITH, "hashed", 0)).prefix == 0)
+    -- Mixed case with a broken checksum is rejected
+    assert(not pcall(Address.evm_to_substrate, "0xF24ff3a9CF04c71Dbc94D0b566f7A27B94566cac"))
+    assert(not pcall(Address.evm_to_substrate, ALITH, "xor"))
+end)
+
+test("Address: Substrate to EVM", function()
+    local truncated = Address.evm_to_substrate(ALITH, "truncated")
+    assert(Address.substrate_to_evm(truncated) == ALITH)
+    assert(Address.substrate_to_evm(FORMATS[0]) == Address.to_checksum_h160(ALICE:sub(1, 42)))
+    assert(not pcall(Address.substrate_to_evm, FORMATS[0], "hashed"))
+end)
+
+print("\n=== Address Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All address tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,219 @@
+-- test/test_assets.lua
+-- Unit tests for Assets / ForeignAssets queries and approvals (mocked storage)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Assets = require("sublua.assets")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Address = require("sublua.address")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Assets Module Tests ===\n")
+
+local ALICE = "0x" .. string.rep("01", 32)
+local BOB = "0x" .. string.rep("02", 32)
+
+-- Storage entries shared by Assets (u32 ids) and ForeignAssets (Location ids)
+local function asset_storage(prefix, id)
+    local key = "(" .. id .. ", AccountId32)"
+    local approval_key = "(" .. id .. ", AccountId32, AccountId32)"
+    return { prefix = prefix, entries = {
+        { name = "Approvals", map = { hashers = { "Blake2_128Concat", "Blake2_128Concat", "Blake2_128Concat" },
+            key = approval_key, value = "Approval" } },
+        { name = "Asset", map = { hashers = { "Blake2_128Concat" }, key = id, value = "AssetDetails" } },
+        { name = "Account", map = { hashers = { "Blake2_128Concat", "Blake2_128Concat" }, key = key, value = "AssetAccount" } },
+        { name = "Metadata", modifier = 1, default = string.rep("\0", 20),
+            map = { hashers = { "Blake2_128Concat" }, key = id, value = "AssetMetadata" } }
+    } }
+end
+
+local FIXTURE = {
+    types = {
+        { "AssetStatus", { path = { "pallet_assets", "types", "AssetStatus" }, variant = {
+            { name = "Live", index = 0 }, { name = "Frozen", index = 1 }, { name = "Destroying", index = 2 } } } },
+        { "AssetDetails", { path = { "pallet_assets", "types", "AssetDetails" }, composite = {
+            { name = "owner", type = "AccountId32" }, { name = "supply", type = "u128" },
+            { name = "min_balance", type = "u128" }, { name = "is_sufficient", type = "bool" },
+            { name = "accounts", type = "u32" }, { name = "status", type = "AssetStatus" } } } },
+        { "AccountStatus", { path = { "pallet_assets", "types", "AccountStatus" }, variant = {
+            { name = "Liquid", index = 0 }, { name = "Frozen", index = 1 }, { name = "Blocked", index = 2 } } } },
+        { "AssetAccount", { path = { "pallet_assets", "types", "AssetAccount" }, composite = {
+            { name = "balance", type = "u128" }, { name = "status", type = "AccountStatus" } } } },
+        { "AssetMetadata", { path = { "pallet_assets", "types", "AssetMetadata" }, composite = {
+            { name = "deposit", type = "u128" }, { name = "name", type = "Vec<u8>" }, { name = "symbol", type = "Vec<u8>" },
+            { name = "decimals", type = "u8" }, { name = "is_frozen", type = "bool" } } } },
+        { "Junction", { path = { "staging_xcm", "v4", "junction", "Junction" }, variant = {
+            { name = "Parachain", index = 0, fields = { { type = "u32" } } },
+            { name = "GeneralIndex", index = 5, fields = { { type = "u128" } } } } } },
+        { "Junctions", { path = { "staging_xcm", "v4", "junctions", "Junctions" }, variant = {
+            { name = "Here", index = 0 }, { name = "X1", index = 1, fields = { { type = "[Junction;1]" } } } } } },
+        { "[Junction;1]", { array = "Junction", len = 1 } },
+        { "Location", { path = { "staging_xcm", "v4", "location", "Location" }, composite = {
+            { name = "parents", type = "u8" }, { name = "interior", type = "Junctions" } } } },
+        { "(u32, AccountId32)", { tuple = { "u32", "AccountId32" } } },
+        { "(Location, AccountId32)", { tuple = { "Location", "AccountId32" } } },
+        { "(u32, AccountId32, AccountId32)", { tuple = { "u32", "AccountId32", "AccountId32" } } },
+        { "(Location, AccountId32, AccountId32)", { tuple = { "Location", "AccountId32", "AccountId32" } } },
+        { "Approval", { path = { "pallet_assets", "types", "Approval" }, composite = {
+            { name = "amount", type = "u128" }, { name = "deposit", type = "u128" } } } },
+        { "AssetsCall", { path = { "pallet_assets", "pallet", "Call" }, variant = {
+            { name = "approve_transfer", index = 22, fields = { { name = "id", type = "Compact<u32>" },
+                { name = "delegate", type = "MultiAddress" }, { name = "amount", type = "Compact<u128>" } } },
+            { name = "cancel_approval", index = 23, fields = { { name = "id", type = "Compact<u32>" },
+                { name = "delegate", type = "MultiAddress" } } },
+            { name = "transfer_approved", index = 25, fields = { { name = "id", type = "Compact<u32>" },
+                { name = "owner", type = "MultiAddress" }, { name = "destination", type = "MultiAddress" },
+                { name = "amount", type = "Compact<u128>" } } }
+        } } }
+    },
+    pallets = {
+        { name = "Assets", index = 50, calls = "AssetsCall", storage = asset_storage("Assets", "u32") },
+        { name = "ForeignAssets", index = 53, storage = asset_storage("ForeignAssets", "Location") }
+    }
+}
+
+local DOT = { parents = 1, interior = { variant = "Here" } }
+local BILL = { parents = 1, interior = { variant = "X1", value = { { variant = "Parachain", value = 2000 } } } }
+
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function asset(api, pallet, id, symbol, decimals, min_balance)
+    put(api, pallet, "Asset", { id }, { owner = BOB, supply = "1000000", min_balance = min_balance or "1",
+        is_sufficient = pallet == "ForeignAssets", accounts = 2, status = { variant = "Live" } })
+    if symbol then
+        put(api, pallet, "Metadata", { id }, { deposit = "0", name = "0x" .. to_hex(symbol .. " Token"),
+            symbol = "0x" .. to_hex(symbol), decimals = decimals, is_frozen = false })
+    end
+end
+
+-- Alice: native UNIT, USDT (1984), a frozen balance of 7, nothing of 8 (zero), and
+-- DOT plus an asset without metadata from other chains
+local function hub()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "System", "Account", { ALICE }, { nonce = 1, consumers = 1, providers = 1, sufficients = 1,
+        data = { free = "1500000000000", reserved = "0", frozen = "20", flags = "0" } })
+    asset(api, "Assets", 1984, "USDT", 6, "700")
+    asset(api, "Assets", 7, "FRZ", 2)
+    asset(api, "Assets", 8, "NIL", 10)
+    asset(api, "Assets", 9, "BOB", 10)
+    asset(api, "ForeignAssets", DOT, "DOT", 10)
+    asset(api, "ForeignAssets", BILL)
+    put(api, "Assets", "Account", { 1984, ALICE }, { balance = "12500000", status = { variant = "Liquid" } })
+    put(api, "Assets", "Account", { 7, ALICE }, { balance = "5", status = { variant = "Frozen" } })
+    put(api, "Assets", "Account", { 8, ALICE }, { balance = "0", status = { variant = "Liquid" } })
+    put(api, "Assets", "Account", { 9, BOB }, { balance = "99", status = { variant = "Liquid" } })
+    put(api, "ForeignAssets", "Account", { DOT, ALICE }, { balance = "25000000000", status = { variant = "Liquid" } })
+    put(api, "ForeignAssets", "Account", { BILL, ALICE }, { balance = "42", status = { variant = "Liquid" } })
+    return api
+end
+
+test("Assets: list and metadata", function()
+    local api = hub()
+    local listed = Assets.list(api)
+    assert(#listed == 4 and listed[1].id == 7 and listed[4].id == 1984, "sorted by id")
+    assert(listed[4].details.min_balance == "700")
+    local foreign = Assets.list(api, "ForeignAssets")
+    assert(#foreign == 2 and foreign[1].id.parents == 1)
+    local m = Assets.query_metadata(api, "ForeignAssets", { DOT, BILL })
+    assert(m[1].symbol == "DOT" and m[1].decimals == 10 and m[1].name == "DOT Token")
+    assert(m[2] == nil, "no metadata")
+end)
+
+test("Assets: single balance", function()
+    local api = hub()
+    local b = Assets.query_balance(api, "Assets", 1984, Address.encode(ALICE, 0))
+    assert(b.balance == "12500000" and b.status == "Liquid")
+    assert(Assets.query_balance(api, "Assets", 1984, BOB) == nil)
+    assert(Assets.account_status({ balance = "1", is_frozen = true }) == "Frozen", "older layout")
+end)
+
+test("Assets: query_all_balances combines native, Assets and ForeignAssets", function()
+    local api = hub()
+    local all = Assets.query_all_balances(api, Address.encode(ALICE, 0))
+    assert(all.at == api:chain_getFinalizedHead())
+    assert(all.native.free == "1500000000000" and all.native.frozen == "20")
+    assert(all.native.formatted == "1.5 UNIT")
+    assert(#all.assets == 4, "zero and foreign-held balances are left out")
+    local a, b = all.assets[1], all.assets[2]
+    local c, d = all.assets[3], all.assets[4]
+    if c.symbol == nil then c, d = d, c end
+    assert(a.pallet == "Assets" and a.id == 7 and a.status == "Frozen" and a.formatted == "0.05 FRZ")
+    assert(b.id == 1984 and b.symbol == "USDT" and b.formatted == "12.5 USDT" and b.min_balance == "700")
+    assert(b.is_sufficient == false)
+    assert(c.pallet == "ForeignAssets" and c.id.interior.variant == "Here" and c.formatted == "2.5 DOT")
+    assert(c.is_sufficient == true)
+    assert(d.symbol == nil and d.decimals == 0 and d.formatted == "42")
+
+    local only = Assets.query_all_balances(api, ALICE, { pallets = { "ForeignAssets", "PoolAssets" } })
+    assert(#only.assets == 2 and only.assets[1].pallet == "ForeignAssets", "missing pallets skipped")
+    local empty = Assets.query_all_balances(api, "0x" .. string.rep("03", 32))
+    assert(empty.native.free == "0" and #empty.assets == 0)
+end)
+
+test("Assets: approve, cancel and spend an allowance", function()
+    local api = hub()
+    local alice, bob = Keyring.from_uri("//Alice"), Keyring.from_uri("//Bob")
+    local meta = api:get_metadata()
+    local call = Transaction.decode(meta, (Assets.approve_transfer(api, alice, nil, 1984, bob.address, "5000000"))).call
+    assert(call.pallet == "Assets" and call.name == "approve_transfer")
+    assert(call.args.id == 1984 and tonumber(call.args.amount) == 5000000)
+    assert(call.args.delegate.variant == "Id" and call.args.delegate.value == "0x" .. to_hex(bob.pubkey))
+    call = Transaction.decode(meta, (Assets.cancel_approval(api, alice, "Assets", 1984, bob.address))).call
+    assert(call.name == "cancel_approval" and call.args.delegate.value == "0x" .. to_hex(bob.pubkey))
+    call = Transaction.decode(meta, (Assets.transfer_approved(api, bob, nil, 1984, alice.address, BOB, 300))).call
+    assert(call.name == "transfer_approved" and call.args.owner.value == "0x" .. to_hex(alice.pubkey))
+    assert(call.args.destination.value == BOB and tonumber(call.args.amount) == 300)
+end)
+
+test("Assets: query approvals", function()
+    local api = hub()
+    put(api, "Assets", "Approvals", { 1984, ALICE, BOB }, { amount = "5000000", deposit = "100" })
+    put(api, "Assets", "Approvals", { 7, BOB, ALICE }, { amount = "1", deposit = "100" })
+    put(api, "ForeignAssets", "Approvals", { DOT, ALICE, BOB }, { amount = "20", deposit = "100" })
+    local a = Assets.query_approval(api, "Assets", 1984, Address.encode(ALICE, 0), BOB)
+    assert(a.amount == "5000000" and a.deposit == "100")
+    assert(Assets.query_approval(api, nil, 1984, BOB, ALICE) == nil)
+    local all = Assets.query_approvals(api, ALICE, BOB)
+    assert(#all == 2 and all[1].pallet == "Assets" and all[1].id == 1984)
+    assert(all[2].pallet == "ForeignAssets" and all[2].id.interior.variant == "Here" and all[2].amount == "20")
+    assert(#Assets.query_approvals(api, ALICE, BOB, { pallets = { "Assets" } }) == 1)
+    assert(#Assets.query_approvals(api, BOB, BOB) == 0)
+end)
+
+print("\n=== Assets Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All assets tests passed!")
+    os.exit(0)
+end
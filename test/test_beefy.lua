@@ -0,0 +1,150 @@
+-- test/test_beefy.lua
+-- Unit tests for BEEFY / MMR commitment queries and proof decoding
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Beefy = require("sublua.beefy")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== BEEFY Module Tests ===\n")
+
+local FIXTURE = {
+    types = {
+        { "[u8;33]", { array = "u8", len = 33 } },
+        { "BeefyId", { path = { "sp_consensus_beefy", "ecdsa_crypto", "Public" }, composite = { { type = "[u8;33]" } } } },
+        { "Vec<BeefyId>", { sequence = "BeefyId" } },
+        { "BeefyAuthoritySet", { path = { "sp_consensus_beefy", "mmr", "BeefyAuthoritySet" }, composite = {
+            { name = "id", type = "u64" }, { name = "len", type = "u32" }, { name = "keyset_commitment", type = "H256" }
+        } } }
+    },
+    pallets = {
+        { name = "Mmr", index = 201, storage = { prefix = "Mmr", entries = {
+            { name = "RootHash", plain = "H256", modifier = 1, default = string.rep("\0", 32) },
+            { name = "NumberOfLeaves", plain = "u64", modifier = 1, default = string.rep("\0", 8) }
+        } } },
+        { name = "Beefy", index = 200, storage = { prefix = "Beefy", entries = {
+            { name = "Authorities", plain = "Vec<BeefyId>", modifier = 1, default = "\0" },
+            { name = "ValidatorSetId", plain = "u64", modifier = 1, default = string.rep("\0", 8) }
+        } } },
+        { name = "BeefyMmrLeaf", index = 202, storage = { prefix = "BeefyMmr", entries = {
+            { name = "BeefyAuthorities", plain = "BeefyAuthoritySet" },
+            { name = "BeefyNextAuthorities", plain = "BeefyAuthoritySet" }
+        } } }
+    }
+}
+
+local BEEFY_HEAD = "0x" .. string.rep("be", 32)
+local ROOT = "0x" .. string.rep("7a", 32)
+local KEY = "0x02" .. string.rep("11", 32)
+
+local function put(api, pallet, entry_name, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name)] = "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function beefy_api()
+    local api = Mock.api({}, FIXTURE)
+    function api:beefy_getFinalizedHead() return BEEFY_HEAD end
+    put(api, "Mmr", "RootHash", ROOT)
+    put(api, "Mmr", "NumberOfLeaves", "20500000")
+    put(api, "Beefy", "ValidatorSetId", "3120")
+    put(api, "Beefy", "Authorities", { KEY })
+    put(api, "BeefyMmrLeaf", "BeefyAuthorities", { id = "3120", len = 297, keyset_commitment = "0x" .. string.rep("cc", 32) })
+    put(api, "BeefyMmrLeaf", "BeefyNextAuthorities", { id = "3121", len = 297, keyset_commitment = "0x" .. string.rep("dd", 32) })
+    return api
+end
+
+-- MmrLeaf with parent (number, hash) and the next authority set 3121
+local function leaf(number)
+    return "\0" .. Scale.encode_u32(number) .. string.rep("\1", 32)
+        .. Scale.encode_u64(3121) .. Scale.encode_u32(297) .. string.rep("\221", 32) .. string.rep("\2", 32)
+end
+
+test("Beefy: commitment read at the BEEFY-finalized head", function()
+    local api = beefy_api()
+    local c = Beefy.query_commitment(api)
+    assert(c.block_hash == BEEFY_HEAD and c.mmr_root == ROOT and c.leaf_count == 20500000)
+    assert(c.validator_set_id == 3120 and c.authorities[1] == KEY)
+    assert(c.authority_set.id == "3120" and c.next_authority_set.len == 297)
+    for _, call in ipairs(api.calls) do
+        if call[1] == "state_getStorage" then assert(call[3] == BEEFY_HEAD, "read at the wrong block") end
+    end
+end)
+
+test("Beefy: single values take an explicit block", function()
+    local api = beefy_api()
+    local at = "0x" .. string.rep("01", 32)
+    assert(Beefy.query_mmr_root(api, at) == ROOT)
+    assert(api.calls[#api.calls][3] == at)
+    assert(Beefy.query_validator_set_id(api) == 3120)
+end)
+
+test("Beefy: decode a leaf proof", function()
+    local proof = Scale.encode_compact(2) .. Scale.encode_u64(5) .. Scale.encode_u64(9)
+        .. Scale.encode_u64(16) .. Scale.encode_compact(1) .. string.rep("\3", 32)
+    local p = Beefy.decode_leaf_proof("0x" .. to_hex(proof))
+    assert(p.leaf_indices[1] == 5 and p.leaf_indices[2] == 9 and p.leaf_count == 16)
+    assert(#p.items == 1 and p.items[1] == "0x" .. string.rep("03", 32))
+    assert(not pcall(Beefy.decode_leaf_proof, proof .. "\0"), "trailing bytes rejected")
+end)
+
+test("Beefy: decode a leaf and its keccak hash", function()
+    local raw = leaf(100)
+    local l = Beefy.decode_leaf(raw)
+    assert(l.version == 0 and l.parent_number == 100 and l.parent_hash == "0x" .. string.rep("01", 32))
+    assert(l.beefy_next_authority_set.id == 3121 and l.beefy_next_authority_set.len == 297)
+    assert(l.leaf_extra == "0x" .. string.rep("02", 32))
+    assert(l.hash == "0x" .. to_hex(crypto.hash("keccak256", raw)))
+end)
+
+test("Beefy: generate_proof decodes the node's response", function()
+    local api = beefy_api()
+    local seen
+    function api:mmr_generateProof(numbers, best, at)
+        seen = { numbers, best, at }
+        local leaves = Scale.encode_compact(1) .. Scale.encode_compact(#leaf(100)) .. leaf(100)
+        local proof = Scale.encode_compact(1) .. Scale.encode_u64(100) .. Scale.encode_u64(200) .. Scale.encode_compact(0)
+        return { blockHash = BEEFY_HEAD, leaves = "0x" .. to_hex(leaves), proof = "0x" .. to_hex(proof) }
+    end
+    local result = Beefy.generate_proof(api, 101, 200)
+    assert(seen[1][1] == 101 and seen[2] == 200 and seen[3] == nil)
+    assert(result.block_hash == BEEFY_HEAD and #result.leaves == 1 and result.leaves[1].parent_number == 100)
+    assert(result.proof.leaf_indices[1] == 100 and result.proof.leaf_count == 200)
+    assert(result.raw.proof:match("^0x"))
+    assert(not pcall(Beefy.generate_proof, api, {}), "empty block list rejected")
+end)
+
+print("\n=== BEEFY Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All BEEFY tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,100 @@
+-- test/test_call.lua
+-- Unit tests for call encoding helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Call = require("sublua.call")
+local Scale = require("sublua.scale")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Call Encoding Tests ===\n")
+
+local meta = Mock.metadata()
+local DEST = string.rep("\5", 32)
+
+test("Call: encode_call matches hand-built transfer", function()
+    local generic = Call.encode_call(meta, "Balances", "transfer_allow_death", {
+        dest = { variant = "Id", value = "0x" .. to_hex(DEST) },
+        value = 1000
+    })
+    local manual = Call.encode_transfer(4, 0, DEST, 1000)
+    assert(generic == manual, to_hex(generic) .. " ~= " .. to_hex(manual))
+end)
+
+test("Call: encode_call uses metadata call index", function()
+    local bytes = Call.encode_call(meta, "Balances", "transfer_keep_alive", {
+        dest = { variant = "Id", value = "0x" .. to_hex(DEST) },
+        value = "1"
+    })
+    assert(to_hex(bytes:sub(1, 2)) == "0403")
+end)
+
+test("Call: encode_call accepts metadata hex source", function()
+    local bytes = Call.encode_call(Mock.hex(), "System", "remark", { remark = "0x6869" })
+    assert(to_hex(bytes) == "0000086869")
+end)
+
+test("Call: unknown pallet and call", function()
+    assert(not pcall(Call.encode_call, meta, "Nope", "x", {}))
+    assert(not pcall(Call.encode_call, meta, "Balances", "nope", {}))
+end)
+
+test("Call: missing argument errors", function()
+    local ok, err = pcall(Call.encode_call, meta, "Balances", "transfer_allow_death", { value = 1 })
+    assert(not ok and tostring(err):match("dest"))
+end)
+
+test("Call: call_hash is blake2_256", function()
+    local bytes = Call.encode_call(meta, "System", "remark", { remark = "0x6869" })
+    local hash = Call.call_hash(bytes)
+    assert(hash == "0x" .. to_hex(crypto.blake2b(bytes, 32)))
+    assert(#hash == 66)
+end)
+
+test("Call: encode_call_json coerces arguments", function()
+    local alice = require("sublua.keyring").from_uri("//Alice")
+    local expected = Call.encode_call(meta, "Balances", "transfer_keep_alive", {
+        dest = { variant = "Id", value = "0x" .. to_hex(alice.pubkey) }, value = "1000000000000"
+    })
+    local json = '{ "dest": "' .. alice.address .. '", "value": 1000000000000 }'
+    assert(Call.encode_call_json(meta, "Balances", "transfer_keep_alive", json) == expected)
+    assert(Call.encode_call_json(meta, "Balances", "transfer_keep_alive",
+        { dest = { Id = "0x" .. to_hex(alice.pubkey) }, value = "1000000000000" }) == expected)
+    assert(Call.encode_call_json(meta, "System", "remark", '{ "remark": "hi" }')
+        == Call.encode_call(meta, "System", "remark", { remark = "0x6869" }))
+    local ok, err = pcall(Call.encode_call_json, meta, "Balances", "transfer_keep_alive", '{ "dest": "nobody", "value": 1 }')
+    assert(not ok and err:match("^transfer_keep_alive.dest.Id: invalid account 'nobody'"))
+    ok, err = pcall(Call.encode_call_json, meta, "Balances", "transfer_keep_alive", "[1, 2")
+    assert(not ok and err:match("JSON object"))
+end)
+
+print("\n=== Call Encoding Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All call encoding tests passed!")
+    os.exit(0)
+end
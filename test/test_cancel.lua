@@ -0,0 +1,114 @@
+-- test/test_cancel.lua
+-- Unit tests for cancellation tokens and the operations that honour them (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Cancel = require("sublua.cancel")
+local Scanner = require("sublua.scanner")
+local Transaction = require("sublua.transaction")
+local Storage = require("sublua.storage")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Cancel Module Tests ===\n")
+
+-- Token cancelled by its poll function at the nth check
+local function cancel_at(n)
+    local checks = 0
+    return Cancel.token(function()
+        checks = checks + 1
+        return checks >= n and "user pressed Cancel"
+    end)
+end
+
+test("Cancel: Tokens cancel once and run cleanups", function()
+    local token = Cancel.token()
+    local closed = {}
+    token:on_cancel(function(reason) table.insert(closed, "file:" .. reason) end)
+    token:on_cancel(function() table.insert(closed, "socket") end)
+    assert(not token:is_cancelled() and Cancel.check(token, "work") == nil and Cancel.check(nil, "work") == nil)
+    token:cancel("quit")
+    token:cancel("again")
+    assert(token:is_cancelled() and token.reason == "quit")
+    assert(#closed == 2 and closed[1] == "socket" and closed[2] == "file:quit", "cleanups once, newest first")
+    token:on_cancel(function(reason) table.insert(closed, reason) end)
+    assert(closed[3] == "quit", "late cleanup runs straight away")
+    local err = Cancel.check(token, "scan")
+    assert(err == "Cancelled: scan (quit)" and Cancel.is_cancelled(err))
+    local ok, raised = pcall(Cancel.raise, token, "listing")
+    assert(not ok and raised == "Cancelled: listing (quit)")
+    assert(cancel_at(1):is_cancelled() and cancel_at(1).reason == nil)
+end)
+
+test("Cancel: Block scans stop with a resumable cursor", function()
+    local original = Scanner.scan_block
+    Scanner.scan_block = function(_, number) return { { kind = "event", block_number = number } } end
+    local seen = {}
+    local token = Cancel.token()
+    local cursor, err = Scanner.scan(Mock.api(), 1, 100, { address = "0x" .. string.rep("11", 32) }, function(item)
+        table.insert(seen, item.block_number)
+        if item.block_number == 3 then token:cancel() end
+    end, { cancel = token })
+    assert(#seen == 3 and Cancel.is_cancelled(err) and err:match("before block 4"))
+    assert(cursor.next_block == 4 and cursor.done == false)
+    local resumed = Scanner.scan(Mock.api(), 1, 100, { address = "0x" .. string.rep("11", 32) }, function() end,
+        { cursor = cursor })
+    Scanner.scan_block = original
+    assert(resumed.done and resumed.scanned == 100)
+end)
+
+test("Cancel: Inclusion waits return a cancelled error", function()
+    local original = Transaction.get_result
+    Transaction.get_result = function() return nil end
+    local api = Mock.api()
+    local token = Cancel.token()
+    local sleeps = 0
+    local result, err = Transaction.wait_for_inclusion(api, "0xabcd", {
+        from_block = 100, cancel = token,
+        sleep = function() sleeps = sleeps + 1 token:cancel("closed window") end
+    })
+    assert(result == nil and sleeps == 1 and err == "Cancelled: waiting for inclusion (closed window)")
+    result, err = Transaction.check_in_block_result(api, "0xabcd", { cancel = cancel_at(3) })
+    Transaction.get_result = original
+    assert(result == nil and Cancel.is_cancelled(err) and err:match("checking block 98"))
+end)
+
+test("Cancel: Vanity search and storage listing stop", function()
+    local token = cancel_at(2)
+    local pair, err = Keyring.vanity("zzzzzzzz", { cancel = token })
+    assert(pair == nil and err == "Cancelled: vanity search after 256 tries (user pressed Cancel)")
+
+    local api = Mock.api({
+        [Storage.build_key(Mock.metadata(), "System", "Account", { "0x" .. string.rep("11", 32) })] = "0x00",
+        [Storage.build_key(Mock.metadata(), "System", "Account", { "0x" .. string.rep("22", 32) })] = "0x00"
+    })
+    -- Pages of one key never run dry on the mock, so only the token ends the listing
+    local ok, listing_err = pcall(Storage.entries, api, "System", "Account", nil, { page_size = 1, cancel = cancel_at(5) })
+    assert(not ok and listing_err == "Cancelled: listing System.Account (user pressed Cancel)")
+end)
+
+print("\n=== Cancel Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All cancel tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,152 @@
+-- test/test_chainspec.lua
+-- Unit tests for trie roots and offline chain-spec parsing
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local crypto = require("polkadot_crypto")
+local json = require("cjson")
+local Trie = require("sublua.trie")
+local ChainSpec = require("sublua.chainspec")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+print("=== Chain Spec Tests ===\n")
+
+test("Trie: empty root", function()
+    assert(to_hex(Trie.root({})) == "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314")
+end)
+
+test("Trie: single leaf and branch layouts", function()
+    assert(Trie.root({ ["0xaa"] = "0xbb" }, 1, { hex = true }) == crypto.blake2b(from_hex("42aa04bb"), 32))
+    local root = Trie.root({ ["0x4819"] = "0xfe", ["0x1314"] = "0xff" }, 1, { hex = true })
+    assert(root == crypto.blake2b(from_hex("8012001443031404ff1443081904fe"), 32))
+end)
+
+test("Trie: known root for a text key", function()
+    local expected = "7ab18659d3b2789ef2400d107104c7d4fb93d06d9ab6a8b641a5de7c3fecc851"
+    assert(to_hex(Trie.root({ abcd = "hello world" }, 0)) == expected)
+    assert(to_hex(Trie.root({ abcd = "hello world" }, 1)) == expected)
+end)
+
+test("Trie: state version 1 hashes long values", function()
+    local long = string.rep("v", 33)
+    local v0 = Trie.root({ k = long }, 0)
+    local v1 = Trie.root({ k = long }, 1)
+    assert(v0 ~= v1)
+    -- Hashed leaf: 0x20 | nibbles, partial key, blake2_256(value)
+    assert(v1 == crypto.blake2b("\34" .. "k" .. crypto.blake2b(long, 32), 32))
+    assert(Trie.root({ k = string.rep("v", 32) }, 0) == Trie.root({ k = string.rep("v", 32) }, 1))
+end)
+
+test("Trie: decode nodes", function()
+    local node = Trie.decode_node(from_hex("8012001443031404ff1443081904fe"))
+    assert(node.partial == "" and node.value == nil)
+    assert(node.children[1] == from_hex("43031404ff") and node.children[4] == from_hex("43081904fe"))
+    local leaf = Trie.decode_node(node.children[4])
+    assert(leaf.partial == "819" and leaf.value == "\254")
+    assert(Trie.decode_node("\0").partial == "")
+    assert(not pcall(Trie.decode_node, from_hex("42aa04bbcc")))
+    assert(not pcall(Trie.decode_node, from_hex("05")))
+end)
+
+test("Trie: read proofs with inline and hashed values", function()
+    local entries = { ["0x4819"] = "0xfe", ["0x1314"] = "0xff" }
+    local root = Trie.root(entries, 1, { hex = true })
+    local proof = { "0x8012001443031404ff1443081904fe" }
+    assert(Trie.read_proof(root, proof, from_hex("4819")) == "\254")
+    assert(Trie.read_proof(root, proof, from_hex("1314")) == "\255")
+    assert(Trie.read_proof(root, proof, from_hex("1315")) == nil)
+    assert(Trie.read_proof(root, proof, from_hex("48")) == nil)
+
+    -- State version 1 leaf holds the value hash; the value travels in the proof
+    local long = string.rep("v", 40)
+    local leaf = "\34" .. "k" .. crypto.blake2b(long, 32)
+    root = Trie.root({ k = long }, 1)
+    assert(Trie.read_proof(root, { leaf, long }, "k") == long)
+    assert(not pcall(Trie.read_proof, root, { leaf }, "k"))
+end)
+
+test("ChainSpec: Polkadot genesis header hash", function()
+    local hash = ChainSpec.genesis_header_hash("0x29d0d972cd27cbc511e9589fcb7a4506d5eb6a9e8df205f00472e5ab354a4e17")
+    assert(hash == "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3")
+end)
+
+local function raw_spec(top)
+    return json.encode({
+        name = "Local Testnet",
+        id = "local_testnet",
+        chainType = "Local",
+        bootNodes = { "/ip4/127.0.0.1/tcp/30333/p2p/12D3KooW" },
+        protocolId = "dot",
+        properties = { ss58Format = 0, tokenDecimals = 10, tokenSymbol = "DOT" },
+        genesis = { raw = { top = top, childrenDefault = {} } }
+    })
+end
+
+test("ChainSpec: parse raw spec", function()
+    local spec = ChainSpec.parse(raw_spec({ ["0xaa"] = "0xbb" }))
+    assert(spec.name == "Local Testnet" and spec.id == "local_testnet" and spec.chain_type == "Local")
+    assert(spec.boot_nodes[1]:match("^/ip4/") and spec.protocol_id == "dot")
+    assert(spec.ss58_prefix == 0 and spec.token_symbol == "DOT" and spec.token_decimals == 10)
+    assert(spec.raw and spec.state_version == 1 and spec.state_version_detected == false)
+    assert(spec.state_root == "0x" .. to_hex(crypto.blake2b(from_hex("42aa04bb"), 32)))
+    assert(spec.genesis_hash == ChainSpec.genesis_header_hash(spec.state_root))
+end)
+
+test("ChainSpec: state version from runtime code", function()
+    -- Uncompressed wasm without a runtime_version section -> version 0
+    local bare = "\0asm\1\0\0\0"
+    assert(ChainSpec.runtime_state_version(bare) == 0)
+    -- runtime_version section: names, versions, no apis, tx version, state version 1
+    local version = "\8ab\8ab" .. string.rep("\1\0\0\0", 3) .. "\0" .. "\1\0\0\0" .. "\1"
+    local name = "runtime_version"
+    local section = string.char(#name) .. name .. version
+    local wasm = bare .. "\0" .. string.char(#section) .. section
+    assert(ChainSpec.runtime_state_version(wasm) == 1)
+    -- zstd-compressed code cannot be inspected
+    assert(ChainSpec.runtime_state_version("\82\188\83\118\70\219\142\5rest") == nil)
+
+    local long = "0x" .. string.rep("ee", 40)
+    local spec = ChainSpec.parse(raw_spec({ ["0x3a636f6465"] = "0x" .. to_hex(bare), ["0x01"] = long }))
+    assert(spec.state_version == 0 and spec.state_version_detected)
+    local forced = ChainSpec.parse(raw_spec({ ["0x3a636f6465"] = "0x" .. to_hex(bare), ["0x01"] = long }), { state_version = 1 })
+    assert(forced.state_version == 1 and forced.genesis_hash ~= spec.genesis_hash)
+end)
+
+test("ChainSpec: non-raw spec has no genesis hash", function()
+    local spec = ChainSpec.parse({ name = "Dev", id = "dev", genesis = { runtimeGenesis = {} } })
+    assert(spec.raw == false and spec.genesis_hash == nil and spec.ss58_prefix == 42)
+    assert(not pcall(ChainSpec.parse, { name = "x" }))
+end)
+
+print("\n=== Chain Spec Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All chain spec tests passed!")
+    os.exit(0)
+end
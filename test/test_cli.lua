@@ -0,0 +1,163 @@
+-- test/test_cli.lua
+-- Unit tests for the sublua-cli command dispatcher
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local json = require("cjson")
+local Cli = require("sublua.cli")
+local Keyring = require("sublua.keyring")
+local Storage = require("sublua.storage")
+local Transaction = require("sublua.transaction")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== CLI Tests ===\n")
+
+local BOB = Keyring.from_uri("//Bob")
+
+-- Run a command line and capture its output
+local function run(args, api)
+    local out, errs = {}, {}
+    local code = Cli.run(args, {
+        print = function(s) table.insert(out, s) end,
+        error = function(s) table.insert(errs, s) end,
+        connect = function(url)
+            assert(url == "http://node", "unexpected url " .. url)
+            return api
+        end
+    })
+    return code, table.concat(out, "\n"), table.concat(errs, "\n")
+end
+
+test("CLI: usage and unknown commands", function()
+    local code, out = run({})
+    assert(code == 2 and out:find("Usage: sublua%-cli"))
+    code, out = run({ "help" })
+    assert(code == 0 and out:find("transfer <url>"))
+    local err
+    code, out, err = run({ "frobnicate" })
+    assert(code == 2 and err:find("unknown command 'frobnicate'"))
+end)
+
+test("CLI: derive and sign with dev keys", function()
+    local alice = Keyring.from_uri("//Alice")
+    local code, out = run({ "derive", "//Alice" })
+    local info = json.decode(out)
+    assert(code == 0 and info.address == alice.address and info.scheme == "ed25519")
+    code, out = run({ "derive", "//Alice", "--prefix", "polkadot" })
+    assert(code == 0 and json.decode(out).address:sub(1, 1) == "1")
+
+    local sig
+    code, sig = run({ "sign", "//Alice", "hello" })
+    assert(code == 0 and #sig == 130)
+    code, out = run({ "verify", alice.address, "hello", sig })
+    assert(code == 0 and out == "valid")
+    code, out = run({ "verify", BOB.address, "hello", sig })
+    assert(code == 1 and out == "invalid")
+end)
+
+test("CLI: BIP-39 phrase keys with a passphrase", function()
+    local phrase = "bottom drive obey lake curtain smoke basket hold race lonely fit walk"
+    local code, out = run({ "derive", phrase })
+    assert(code == 0 and json.decode(out).address == Keyring.from_mnemonic(phrase).address)
+    code, out = run({ "derive", phrase, "--password", "25th word" })
+    assert(code == 0 and json.decode(out).address == Keyring.from_mnemonic(phrase, { password = "25th word" }).address)
+    local sig
+    code, sig = run({ "sign", phrase, "hello", "--password", "25th word" })
+    code, out = run({ "verify", json.decode(out).address, "hello", sig })
+    assert(code == 0 and out == "valid")
+    local err
+    code, _, err = run({ "derive", "//Alice", "--password", "x" })
+    assert(code == 1 and err:find("only applies to BIP%-39 phrase"))
+end)
+
+test("CLI: keys from the environment", function()
+    local code, _, err = run({ "derive", "env:SUBLUA_CLI_TEST_UNSET_VAR" })
+    assert(code == 1 and err:find("SUBLUA_CLI_TEST_UNSET_VAR is not set"))
+    code, _, err = run({ "derive", "alice" })
+    assert(code == 1 and err:find("Key must be"))
+    code, _, err = run({ "derive", "//Alice", "--bogus" })
+    assert(code == 1 and err:find("Unknown option %-%-bogus"))
+end)
+
+test("CLI: query balance and storage", function()
+    local api = Mock.api()
+    local pubkey = "0x" .. string.rep("bb", 32)
+    local key = Storage.build_key(api, "System", "Account", { "0x" .. (BOB.pubkey:gsub(".", function(c)
+        return string.format("%02x", string.byte(c)) end)) })
+    api.storage[key] = "0x" .. "07000000" .. string.rep("00", 12)
+        .. "e8030000000000000000000000000000" .. string.rep("00", 48)
+    local code, out = run({ "query", "http://node", "balance", BOB.address }, api)
+    assert(code == 0 and json.decode(out).free == "1000", out)
+
+    api.storage[Storage.build_key(api, "System", "Number")] = "0x2a000000"
+    code, out = run({ "query", "http://node", "storage", "System.Number" }, api)
+    assert(code == 0 and out == "42")
+    code, out = run({ "query", "http://node", "storage", "Staking.Bonded", pubkey }, api)
+    assert(code == 0 and out == "null")
+    local err
+    code, _, err = run({ "query", "http://node", "storage", "System.Nope" }, api)
+    assert(code == 1 and err:find("Storage entry not found"))
+end)
+
+test("CLI: transfer submits a signed Balances call", function()
+    local api = Mock.api()
+    api.submitted = {}
+    function api:author_submitExtrinsic(hex)
+        table.insert(self.submitted, hex)
+        return "0x" .. string.rep("ab", 32)
+    end
+    local code, out = run({ "transfer", "http://node", "//Alice", BOB.address, "500", "--keep-alive" }, api)
+    assert(code == 0 and json.decode(out).hash == "0x" .. string.rep("ab", 32), out)
+    local decoded = Transaction.decode(api, api.submitted[1])
+    assert(decoded.call.pallet == "Balances" and decoded.call.name == "transfer_keep_alive")
+    assert(tostring(decoded.call.args.value) == "500")
+
+    local err
+    code, _, err = run({ "transfer", "http://node", "//Alice", BOB.address, "1.5" }, api)
+    assert(code == 1 and err:find("integer"))
+end)
+
+test("CLI: decode addresses and extrinsics", function()
+    local code, out = run({ "decode", "address", BOB.address })
+    assert(code == 0 and json.decode(out).prefix == 42)
+    code = run({ "decode", "address", "not-an-address" })
+    assert(code == 1)
+
+    local path = os.tmpname()
+    local f = assert(io.open(path, "wb"))
+    f:write(Mock.hex())
+    f:close()
+    local api = Mock.api()
+    local signed = Transaction.create_signed_from_api(api, Keyring.from_uri("//Alice"),
+        require("sublua.call").encode_call(api, "System", "remark", { remark = "0x6869" }), { nonce = 3 })
+    code, out = run({ "decode", "extrinsic", path, signed })
+    os.remove(path)
+    local decoded = json.decode(out)
+    assert(code == 0 and decoded.call.pallet == "System" and decoded.call.name == "remark", out)
+end)
+
+print("\n=== CLI Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All CLI tests passed!")
+    os.exit(0)
+end
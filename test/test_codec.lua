@@ -0,0 +1,290 @@
+-- test/test_codec.lua
+-- Unit tests for metadata-driven SCALE decoding
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Codec = require("sublua.codec")
+local Metadata = require("sublua.metadata")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Codec Tests ===\n")
+
+local meta = Mock.metadata()
+local T = Mock.type_id
+
+-- Metadata retention
+test("Metadata: storage entries retained", function()
+    local entry, prefix = Metadata.get_storage_entry(meta, "System", "Account")
+    assert(entry and prefix == "System")
+    assert(entry.storage_type.type == "Map")
+    assert(entry.storage_type.hashers[1] == 2, "Blake2_128Concat")
+    assert(entry.storage_type.value == T("AccountInfo"))
+end)
+
+test("Metadata: missing storage entry reports error", function()
+    local entry, _, err = Metadata.get_storage_entry(meta, "System", "Nope")
+    assert(entry == nil and err == "Storage entry not found")
+end)
+
+test("Metadata: constants retained as raw bytes", function()
+    local c = Metadata.get_constant(meta, "System", "SS58Prefix")
+    assert(c.type_id == T("u16"))
+    assert(Codec.decode(meta, c.type_id, c.value) == 42)
+end)
+
+test("Metadata: pallets indexed by index", function()
+    assert(meta.pallets_by_index[4].name == "Balances")
+end)
+
+-- Primitives
+test("Codec: u32 decodes to number", function()
+    local v, off = Codec.decode(meta, T("u32"), Scale.encode_u32(123456))
+    assert(v == 123456 and off == 5)
+end)
+
+test("Codec: u128 decodes to decimal string", function()
+    local c = Metadata.get_constant(meta, "Balances", "ExistentialDeposit")
+    assert(Codec.decode(meta, c.type_id, c.value) == "10000000000")
+end)
+
+test("Codec: negative i64", function()
+    local v = Codec.decode(meta, T("i64"), string.rep("\255", 8))
+    assert(v == "-1", "got " .. tostring(v))
+end)
+
+test("Codec: bool and str", function()
+    assert(Codec.decode(meta, T("bool"), "\1") == true)
+    assert(Codec.decode(meta, T("str"), "\12abc") == "abc")
+end)
+
+test("Codec: Compact<u128> big-int mode keeps precision", function()
+    -- 2^64 as compact: header 0b11 with 9 bytes -> (9-4)*4+3 = 0x17
+    local data = "\23" .. string.rep("\0", 8) .. "\1"
+    local v = Codec.decode(meta, T("Compact<u128>"), data)
+    assert(v == "18446744073709551616", "got " .. tostring(v))
+end)
+
+test("Codec: Compact<u32> decodes to number", function()
+    assert(Codec.decode(meta, T("Compact<u32>"), Scale.encode_compact(1000)) == 1000)
+end)
+
+-- Composites and enums
+test("Codec: AccountId32 newtype unwraps to hex", function()
+    local v = Codec.decode(meta, T("AccountId32"), string.rep("\171", 32))
+    assert(v == "0x" .. string.rep("ab", 32))
+end)
+
+test("Codec: named composite", function()
+    local data = Scale.encode_compact(10) .. Scale.encode_compact(20)
+    local v = Codec.decode(meta, T("Weight"), data)
+    assert(v.ref_time == "10" and v.proof_size == "20")
+end)
+
+test("Codec: variant with payload", function()
+    local v = Codec.decode(meta, T("MultiAddress"), "\0" .. string.rep("\1", 32))
+    assert(v.variant == "Id")
+    assert(v.value == "0x" .. string.rep("01", 32))
+end)
+
+test("Codec: unit variant", function()
+    local v = Codec.decode(meta, T("Phase"), "\1")
+    assert(v.variant == "Finalization" and v.value == nil)
+end)
+
+test("Codec: unknown variant index errors", function()
+    assert(not pcall(Codec.decode, meta, T("Phase"), "\9"))
+end)
+
+test("Codec: tuple and sequence", function()
+    local data = Scale.encode_compact(1) .. string.rep("\2", 32) .. string.rep("\0", 15) .. "\1"
+    local v = Codec.decode(meta, T("Vec<(AccountId32, u128)>"), data)
+    assert(#v == 1)
+    assert(v[1][1] == "0x" .. string.rep("02", 32))
+    assert(v[1][2] == "1329227995784915872903807060280344576", "got " .. tostring(v[1][2]))
+end)
+
+test("Codec: decode_hex rejects trailing bytes", function()
+    assert(not pcall(Codec.decode_hex, meta, T("u8"), "0x0102"))
+    assert(Codec.decode_hex(meta, T("u8"), "0x01") == 1)
+end)
+
+test("Codec: nested call", function()
+    local data = "\4\0\0" .. string.rep("\5", 32) .. Scale.encode_compact(1000)
+    local v = Codec.decode(meta, T("RuntimeCall"), data)
+    assert(v.variant == "Balances")
+    assert(v.value.variant == "transfer_allow_death")
+    assert(v.value.value.dest.variant == "Id")
+    assert(v.value.value.value == "1000")
+end)
+
+-- Encoding
+test("Codec: encode/decode roundtrip for call", function()
+    local value = {
+        variant = "Balances",
+        value = { variant = "transfer_keep_alive", value = {
+            dest = { variant = "Id", value = "0x" .. string.rep("07", 32) },
+            value = "340282366920938463463374607431768211455"
+        } }
+    }
+    local bytes = Codec.encode(meta, T("RuntimeCall"), value)
+    local back = Codec.decode(meta, T("RuntimeCall"), bytes)
+    assert(back.value.value.value == value.value.value.value)
+    assert(back.value.value.dest.value == value.value.value.dest.value)
+end)
+
+test("Codec: encode u128 accepts numbers and strings", function()
+    assert(Codec.encode(meta, T("u128"), 1) == "\1" .. string.rep("\0", 15))
+    assert(Codec.encode(meta, T("u128"), "256") == "\0\1" .. string.rep("\0", 14))
+end)
+
+test("Codec: encode rejects overflow and negatives", function()
+    assert(not pcall(Codec.encode, meta, T("u8"), 256))
+    assert(not pcall(Codec.encode, meta, T("u32"), -1))
+end)
+
+test("Codec: encode signed integers", function()
+    assert(Codec.encode(meta, T("i64"), -1) == string.rep("\255", 8))
+    assert(Codec.decode(meta, T("i64"), Codec.encode(meta, T("i64"), "-12345")) == "-12345")
+end)
+
+test("Codec: encode compact big-int mode", function()
+    local bytes = Codec.encode(meta, T("Compact<u128>"), "18446744073709551616")
+    assert(to_hex(bytes) == "17" .. string.rep("00", 8) .. "01", "got " .. to_hex(bytes))
+    assert(Codec.encode(meta, T("Compact<u32>"), 1000) == Scale.encode_compact(1000))
+end)
+
+test("Codec: encode fixed array length checked", function()
+    assert(not pcall(Codec.encode, meta, T("AccountId32"), "0x1234"))
+end)
+
+test("Codec: encode missing field errors", function()
+    local ok, err = pcall(Codec.encode, meta, T("Weight"), { ref_time = 1 })
+    assert(not ok and tostring(err):match("proof_size"))
+end)
+
+test("Codec: Compact<()> is zero-width", function()
+    assert(Codec.encode(meta, T("Compact<()>"), {}) == "")
+    local v, off = Codec.decode(meta, T("Compact<()>"), "", 1)
+    assert(off == 1)
+end)
+
+-- JSON values
+test("Codec: JSON values are coerced by type", function()
+    local json = require("cjson")
+    local alice = require("sublua.keyring").from_uri("//Alice")
+    local alice_hex = "0x" .. to_hex(alice.pubkey)
+    local ledger = Codec.from_json(meta, T("StakingLedger"), json.decode([[{
+        "stash": "]] .. alice.address .. [[", "total": 1000000000000, "active": "340282366920938463463374607431768211455",
+        "unlocking": [ { "value": "5", "era": 12 } ] }]]))
+    assert(ledger.stash == alice_hex and ledger.total == "1000000000000")
+    assert(ledger.unlocking[1].value == "5" and ledger.unlocking[1].era == 12)
+    assert(Codec.encode(meta, T("StakingLedger"), ledger))
+
+    local dest = Codec.from_json(meta, T("MultiAddress"), alice.address)
+    assert(dest.variant == "Id" and dest.value == alice_hex)
+    assert(Codec.from_json(meta, T("MultiAddress"), { Raw = "hi" }).value == "0x6869")
+    assert(Codec.from_json(meta, T("MultiAddress"), { variant = "Address20", value = { 1, 2, 3 } }).value == "0x010203")
+    assert(Codec.from_json(meta, T("Option<u32>"), json.null).variant == "None")
+    local some = Codec.from_json(meta, T("Option<u32>"), "7")
+    assert(some.variant == "Some" and some.value == 7)
+    assert(Codec.from_json(meta, T("Perbill"), 500000000) == 500000000)
+    local weights = Codec.from_json(meta, T("WeightsPerClass"), {
+        base_extrinsic = { ref_time = 1, proof_size = 2 }, max_extrinsic = { Some = { ref_time = 3, proof_size = 4 } },
+        max_total = "None", reserved = json.null })
+    assert(weights.base_extrinsic.ref_time == "1" and weights.max_extrinsic.value.proof_size == "4")
+    assert(weights.max_total.variant == "None" and weights.reserved.variant == "None")
+end)
+
+test("Codec: JSON coercion errors name the path", function()
+    local function err_of(type_name, value)
+        local ok, err = pcall(Codec.from_json, meta, T(type_name), value)
+        assert(not ok, "expected an error")
+        return err
+    end
+    assert(err_of("StakingLedger", { stash = "nope" }) == "stash: invalid account 'nope': " .. require("sublua.address").inspect("nope").error)
+    local ledger = { stash = "0x" .. string.rep("01", 32), total = 1, active = 1, unlocking = { { value = 1.5, era = 1 } } }
+    assert(err_of("StakingLedger", ledger) == "unlocking.1.value: u128 expects an integer, got 1.5")
+    ledger.unlocking = { { value = 1, era = 1, extra = true } }
+    assert(err_of("StakingLedger", ledger) == "unlocking.1.extra: unknown field")
+    ledger.unlocking = {}
+    ledger.total = 2^60
+    assert(err_of("StakingLedger", ledger):match("^total: .* pass it as a decimal string$"))
+    assert(err_of("MultiAddress", { Key = 1 }):match("enum expects"))
+    assert(err_of("MultiAddress", { variant = "Key" }):match("unknown variant Key %(Id, Index"))
+    assert(err_of("Weight", { ref_time = 1 }) == "proof_size: missing value")
+    assert(err_of("MultiAddress", { Raw = "0x686" }):match("^Raw: invalid hex '0x686'"))
+    assert(err_of("MultiAddress", { Raw = "0xzz" }):match("^Raw: invalid hex '0xzz'"))
+    assert(Codec.from_json(meta, T("MultiAddress"), { Raw = "0xAB" }).value == "0xab")
+end)
+
+test("Codec: SCALE <-> canonical JSON round trip", function()
+    local json = require("cjson")
+    local extra = { types = {
+        { "Option<Option<u32>>", { path = { "Option" }, variant = {
+            { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = "Option<u32>" } } } } } },
+        { "Vec<Option<Option<u32>>>", { sequence = "Option<Option<u32>>" } },
+        { "(u64,bool,())", { tuple = { "u64", "bool", "()" } } }
+    } }
+    local m = Mock.metadata(extra)
+    local function id(name) return Mock.type_id(name, extra) end
+
+    local ledger = "0x" .. string.rep("01", 32) .. to_hex(Scale.encode_compact(1000) .. Scale.encode_compact(7))
+        .. to_hex(Scale.encode_compact(1) .. Scale.encode_compact(5) .. Scale.encode_compact(12))
+    local text = Codec.value_to_json(m, id("StakingLedger"), ledger)
+    local doc = json.decode(text)
+    assert(doc.stash == "0x" .. string.rep("01", 32) and doc.total == "1000" and doc.active == "7")
+    assert(doc.unlocking[1].value == "5" and doc.unlocking[1].era == 12)
+    assert(Codec.json_to_scale(m, id("StakingLedger"), text) == ledger)
+
+    local cases = {
+        { "Vec<Option<Option<u32>>>", '[null,{"Some":null},{"Some":3}]' },
+        { "MultiAddress", '{"Index":null}' },
+        { "MultiAddress", '{"Raw":"0x6869"}' },
+        { "(u64,bool,())", '["18446744073709551615",true,null]' }
+    }
+    for _, case in ipairs(cases) do
+        local scale = Codec.json_to_scale(m, id(case[1]), case[2])
+        local back = Codec.value_to_json(m, id(case[1]), scale)
+        assert(json.encode(json.decode(back)) == json.encode(json.decode(case[2])), case[1] .. ": " .. back)
+        assert(Codec.json_to_scale(m, id(case[1]), back) == scale)
+    end
+    assert(Codec.json_to_scale(m, id("Vec<Option<Option<u32>>>"), '[null,{"Some":null},3]')
+        == Codec.json_to_scale(m, id("Vec<Option<Option<u32>>>"), '[null,{"Some":null},{"Some":3}]'))
+    local weights = Codec.to_json(m, id("WeightsPerClass"), {
+        base_extrinsic = { ref_time = "1", proof_size = "2" },
+        max_extrinsic = { variant = "Some", value = { ref_time = "3", proof_size = "4" } },
+        max_total = { variant = "None" }, reserved = { variant = "None" } })
+    assert(weights.max_extrinsic.ref_time == "3" and weights.max_total == json.null)
+    assert(not pcall(Codec.json_to_scale, m, id("MultiAddress"), "[1, 2"), "invalid JSON")
+end)
+
+print("\n=== Codec Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All codec tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,108 @@
+-- test/test_codegen.lua
+-- Unit tests for Lua binding generation from metadata
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Codegen = require("sublua.codegen")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Codegen Tests ===\n")
+
+local ALICE = "0x" .. string.rep("d4", 32)
+
+local function load_source(source)
+    local chunk = assert((loadstring or load)(source))
+    return chunk()
+end
+
+test("Codegen: snake_case names", function()
+    assert(Codegen.snake_case("TotalIssuance") == "total_issuance")
+    assert(Codegen.snake_case("SS58Prefix") == "ss58_prefix")
+    assert(Codegen.snake_case("Account") == "account")
+    assert(Codegen.snake_case("transfer_keep_alive") == "transfer_keep_alive")
+end)
+
+test("Codegen: call functions take named arguments", function()
+    local meta = Mock.metadata()
+    local source = Codegen.render_pallet(meta, "Balances")
+    assert(source:find("function Balances.calls.transfer_keep_alive(api, dest, value)", 1, true))
+    assert(source:find("-- transfer_keep_alive(dest: MultiAddress, value: Compact<u128>)", 1, true))
+    local Balances = load_source(source)
+    local dest = { variant = "Id", value = ALICE }
+    assert(Balances.calls.transfer_keep_alive(meta, dest, 5)
+        == Call.encode_call(meta, "Balances", "transfer_keep_alive", { dest = dest, value = 5 }))
+end)
+
+test("Codegen: storage functions take typed keys", function()
+    local api = Mock.api()
+    local System = load_source(Codegen.render_pallet(api, "System"))
+    assert(type(System.storage.account) == "function" and type(System.storage.number) == "function")
+    api.storage[Storage.build_key(api, "System", "Number")] = "0x2a000000"
+    assert(System.storage.number(api) == 42)
+    assert(System.storage.account(api, ALICE) == nil)
+    local last = api.calls[#api.calls]
+    assert(last[2] == Storage.build_key(api, "System", "Account", { ALICE }))
+
+    local source = Codegen.render_pallet(api, "Staking")
+    assert(source:find("-- Ledger(AccountId32) -> StakingLedger | nil", 1, true))
+    assert(source:find("function Staking.storage.unapplied_slashes(api, key, at)", 1, true))
+end)
+
+test("Codegen: constants decode from metadata", function()
+    local meta = Mock.metadata()
+    local Balances = load_source(Codegen.render_pallet(meta, "Balances"))
+    assert(Balances.constants.existential_deposit(meta) == "10000000000")
+    local System = load_source(Codegen.render_pallet(meta, "System"))
+    assert(System.constants.ss58_prefix(meta) == 42)
+end)
+
+test("Codegen: generate writes pallet modules and an index", function()
+    local dir = os.tmpname()
+    os.remove(dir)
+    assert(os.execute("mkdir " .. dir))
+    local files = Codegen.generate(Mock.metadata(), dir, { pallets = { "Balances", "System" } })
+    assert(#files == 3 and files[1] == dir .. "/system.lua" and files[3] == dir .. "/init.lua")
+
+    local parent, name = dir:match("^(.*)/([^/]+)$")
+    local saved = package.path
+    package.path = parent .. "/?.lua;" .. parent .. "/?/init.lua;" .. package.path
+    local ok, runtime = pcall(require, name)
+    package.path = saved
+    for _, path in ipairs(files) do os.remove(path) end
+    os.remove(dir)
+    assert(ok, runtime)
+    assert(runtime.Balances.calls.transfer_allow_death and runtime.System.storage.events)
+
+    local failed_ok, err = pcall(Codegen.generate, Mock.metadata(), "/nonexistent/dir")
+    assert(not failed_ok and err:find("does the directory exist"))
+    failed_ok, err = pcall(Codegen.generate, Mock.metadata(), "/tmp", { pallets = { "Nope" } })
+    assert(not failed_ok and err:find("Pallet not found: Nope"))
+end)
+
+print("\n=== Codegen Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All codegen tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,168 @@
+-- test/test_collective.lua
+-- Unit tests for Collective (council / technical committee) motions
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Collective = require("sublua.collective")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Collective Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+
+local COUNCIL = {
+    types = {
+        { "CollectiveCall", { path = { "pallet_collective", "pallet", "Call" }, variant = {
+            { name = "propose", index = 2, fields = {
+                { name = "threshold", type = "Compact<u32>" }, { name = "proposal", type = "RuntimeCall" },
+                { name = "length_bound", type = "Compact<u32>" } } },
+            { name = "vote", index = 3, fields = {
+                { name = "proposal", type = "H256" }, { name = "index", type = "Compact<u32>" }, { name = "approve", type = "bool" } } },
+            { name = "close", index = 6, fields = {
+                { name = "proposal_hash", type = "H256" }, { name = "index", type = "Compact<u32>" },
+                { name = "proposal_weight_bound", type = "Weight" }, { name = "length_bound", type = "Compact<u32>" } } }
+        } } },
+        { "Votes", { path = { "pallet_collective", "Votes" }, composite = {
+            { name = "index", type = "u32" }, { name = "threshold", type = "u32" },
+            { name = "ayes", type = "Vec<AccountId32>" }, { name = "nays", type = "Vec<AccountId32>" },
+            { name = "end", type = "u32" }
+        } } }
+    },
+    pallets = {
+        { name = "Council", index = 14, calls = "CollectiveCall", storage = { prefix = "Council", entries = {
+            { name = "Proposals", plain = "Vec<H256>", modifier = 1, default = "\0" },
+            { name = "ProposalOf", map = { hashers = { "Identity" }, key = "H256", value = "RuntimeCall" } },
+            { name = "Voting", map = { hashers = { "Identity" }, key = "H256", value = "Votes" } },
+            { name = "ProposalCount", plain = "u32", modifier = 1, default = "\0\0\0\0" },
+            { name = "Members", plain = "Vec<AccountId32>", modifier = 1, default = "\0" },
+            { name = "Prime", plain = "AccountId32" }
+        } } },
+        { name = "TechnicalCommittee", index = 15, calls = "CollectiveCall" }
+    }
+}
+
+local function remark(text)
+    return Call.encode_call(Mock.metadata(), "System", "remark", { remark = "0x" .. to_hex(text) })
+end
+
+local function put(api, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets["Council"].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, "Council", entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+-- A council with one open motion (a System.remark) voted aye by Alice
+local function council_api()
+    local api = Mock.api({}, COUNCIL)
+    local proposal = remark("hi")
+    local hash = Call.call_hash(proposal)
+    put(api, "Proposals", {}, { hash })
+    api.storage[Storage.build_key(api, "Council", "ProposalOf", { hash })] = "0x" .. to_hex(proposal)
+    put(api, "Voting", { hash }, { index = 7, threshold = 2, ayes = { ALICE }, nays = {}, ["end"] = 500 })
+    put(api, "Members", {}, { ALICE })
+    put(api, "ProposalCount", {}, 8)
+    return api, proposal, hash
+end
+
+test("Collective: propose splices the raw proposal", function()
+    local proposal = remark("hi")
+    local call = Collective.encode_propose(Mock.metadata(COUNCIL), "TechnicalCommittee", 2, proposal)
+    assert(to_hex(call:sub(1, 3)) == "0f0208", "got " .. to_hex(call:sub(1, 3)))
+    assert(call:sub(4, 3 + #proposal) == proposal)
+    assert(call:sub(4 + #proposal) == Scale.encode_compact(#proposal))
+end)
+
+test("Collective: propose signs and reports the motion hash", function()
+    local api = Mock.api({}, COUNCIL)
+    local proposal = remark("hi")
+    local signed, details = Collective.propose(api, alice, nil, 3, "0x" .. to_hex(proposal), { nonce = 0 })
+    assert(details.proposal_hash == Call.call_hash(proposal))
+    local decoded = Transaction.decode(api:get_metadata(), signed)
+    assert(decoded.call.pallet == "Council" and decoded.call.name == "propose")
+    assert(decoded.call.args.threshold == 3 and decoded.call.args.proposal.variant == "System")
+    assert(decoded.call.args.length_bound == #proposal)
+end)
+
+test("Collective: vote encoding", function()
+    local api = Mock.api({}, COUNCIL)
+    local hash = "0x" .. string.rep("cd", 32)
+    local call = Transaction.decode(api:get_metadata(), Collective.vote(api, alice, "Council", hash, 7, false, { nonce = 0 })).call
+    assert(call.name == "vote" and call.args.proposal == hash and call.args.index == 7 and call.args.approve == false)
+end)
+
+test("Collective: close weighs the stored proposal", function()
+    local api, proposal, hash = council_api()
+    function api:state_call(method, data)
+        assert(method == "TransactionPaymentCallApi_query_call_info")
+        return "0x" .. to_hex(Scale.encode_compact(123456) .. Scale.encode_compact(789) .. "\0" .. string.rep("\0", 16))
+    end
+    local call = Transaction.decode(api:get_metadata(), Collective.close(api, alice, "Council", hash, 7, { nonce = 0 })).call
+    assert(call.name == "close" and call.args.index == 7)
+    assert(call.args.proposal_weight_bound.ref_time == "123456" and call.args.proposal_weight_bound.proof_size == "789")
+    assert(call.args.length_bound == #proposal)
+end)
+
+test("Collective: close with explicit bounds", function()
+    local api = Mock.api({}, COUNCIL)
+    local hash = "0x" .. string.rep("cd", 32)
+    local call = Transaction.decode(api:get_metadata(), Collective.close(api, alice, nil, hash, 1,
+        { weight = { ref_time = 10, proof_size = 20 }, length_bound = 40, nonce = 0 })).call
+    assert(call.args.proposal_weight_bound.ref_time == "10" and call.args.length_bound == 40)
+    assert(not pcall(Collective.close, api, alice, nil, hash, 1, { nonce = 0 }), "unknown proposal without bounds")
+end)
+
+test("Collective: proposal queries", function()
+    local api, proposal, hash = council_api()
+    local list = Collective.query_proposals(api, "Council")
+    assert(#list == 1)
+    local p = list[1]
+    assert(p.hash == hash and p.index == 7 and p.threshold == 2 and p.ayes[1] == ALICE and #p.nays == 0)
+    assert(p["end"] == 500 and p.length == #proposal)
+    assert(p.call.pallet == "System" and p.call.name == "remark" and p.call.args.remark == "0x6869")
+    assert(Collective.query_proposal(api, nil, "0x" .. string.rep("00", 32)) == nil)
+end)
+
+test("Collective: members, prime and proposal count", function()
+    local api = council_api()
+    assert(Collective.query_members(api)[1] == ALICE)
+    assert(Collective.query_prime(api) == nil)
+    assert(Collective.query_proposal_count(api) == 8)
+end)
+
+print("\n=== Collective Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All collective tests passed!")
+    os.exit(0)
+end
@@ -110,6 +110,120 @@ test("Twox64: Deterministic", function()
     assert(h1 == h2)
 end)
 
+-- Twox256 / SHA-256 / Keccak-256 Tests
+test("Twox256: Starts with Twox128", function()
+    local hash = crypto.twox256("abc")
+    assert(#hash == 32)
+    assert(hash:sub(1, 16) == crypto.twox128("abc"))
+end)
+
+test("SHA-256: FIPS 180-4 vectors", function()
+    assert(to_hex(crypto.sha256("")) == "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+    assert(to_hex(crypto.sha256("abc")) == "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    assert(to_hex(crypto.sha256(string.rep("a", 1000000))) == "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0")
+end)
+
+test("Keccak-256: Ethereum vectors", function()
+    assert(to_hex(crypto.keccak256("")) == "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    assert(to_hex(crypto.keccak256("abc")) == "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+    -- Multi-block input (rate is 136 bytes)
+    assert(#crypto.keccak256(string.rep("a", 300)) == 32)
+end)
+
+test("SHA-512: FIPS 180-4 vectors", function()
+    assert(to_hex(crypto.sha512("abc")) == "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a"
+        .. "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")
+    assert(#crypto.sha512(string.rep("a", 300)) == 64)
+end)
+
+test("Argon2id: Known key and cost checks", function()
+    local key = crypto.argon2id("password", "somesalt", 64, 2)
+    assert(to_hex(key) == "16a1a498734609dd01456da406de9f3d9da93e6c86c300a12fc1465214ce4922")
+    assert(crypto.argon2id("password", "othersalt", 64, 2) ~= key)
+    assert(not pcall(crypto.argon2id, "password", "short", 64, 2))
+    assert(not pcall(crypto.argon2id, "password", "somesalt", 4, 2))
+end)
+
+test("AEAD: XChaCha20-Poly1305 round trip and tamper detection", function()
+    local key, nonce = string.rep("k", 32), string.rep("n", 24)
+    local box = crypto.aead_lock(key, nonce, "secret words", "name")
+    assert(#box == 16 + 12)
+    assert(crypto.aead_unlock(key, nonce, box, "name") == "secret words")
+    assert(crypto.aead_unlock(key, nonce, box, "other") == nil)
+    assert(crypto.aead_unlock(string.rep("x", 32), nonce, box, "name") == nil)
+    assert(crypto.aead_unlock(key, nonce, box:sub(1, -2) .. "\0", "name") == nil)
+    assert(crypto.aead_unlock(key, nonce, "short") == nil)
+    assert(not pcall(crypto.aead_lock, key, "short nonce", "x"))
+end)
+
+test("VRF: RFC 9381 ECVRF-EDWARDS25519-SHA512-TAI example 16", function()
+    local seed = from_hex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+    local proof, output = crypto.vrf_prove(seed, "")
+    assert(to_hex(proof) == "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f"
+        .. "26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805")
+    assert(to_hex(output) == "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff"
+        .. "66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae")
+    local pubkey = crypto.ed25519_keypair_from_seed(seed)
+    assert(crypto.vrf_verify(pubkey, "", proof) == output)
+    assert(crypto.vrf_verify(pubkey, "x", proof) == nil)
+    assert(crypto.vrf_verify(pubkey, "", proof:sub(1, 79) .. "\0") == nil)
+    assert(crypto.vrf_verify(crypto.ed25519_keypair_from_seed(string.rep("b", 32)), "", proof) == nil)
+    -- Small-order public key (the identity) is rejected
+    assert(crypto.vrf_verify("\1" .. string.rep("\0", 31), "", proof) == nil)
+end)
+
+-- Key handle Tests
+test("Keypair handle: Matches seed-based signing", function()
+    local seed = string.rep("k", 32)
+    local kp = crypto.keypair_create(seed)
+    assert(kp:public_key() == crypto.ed25519_keypair_from_seed(seed))
+    assert(crypto.keypair_public(kp) == kp:public_key())
+    assert(kp:sign("msg") == crypto.ed25519_sign(seed, "msg"))
+    assert(crypto.keypair_sign(kp, "msg") == kp:sign("msg"))
+end)
+
+test("Keypair handle: Unusable after destroy", function()
+    local kp = crypto.keypair_create(string.rep("k", 32))
+    kp:destroy()
+    assert(tostring(kp) == "keypair (destroyed)")
+    assert(not pcall(kp.sign, kp, "msg"))
+    assert(not pcall(kp.public_key, kp))
+    kp:destroy()  -- idempotent
+end)
+
+test("Keypair handle: Wiped when a <close> variable goes out of scope", function()
+    local kp = crypto.keypair_create(string.rep("k", 32))
+    getmetatable(kp).__close(kp)  -- what Lua 5.4 runs for `local kp <close> = ...`
+    assert(tostring(kp) == "keypair (destroyed)")
+end)
+
+test("Keypair handle: Error on invalid seed", function()
+    assert(not pcall(crypto.keypair_create, "short"))
+end)
+
+-- Unified hash() Tests
+test("hash: Matches the dedicated functions", function()
+    assert(crypto.hash("blake2_128", "abc") == crypto.blake2b("abc", 16))
+    assert(crypto.hash("blake2_256", "abc") == crypto.blake2b("abc", 32))
+    assert(crypto.hash("blake2_512", "abc") == crypto.blake2b("abc", 64))
+    assert(crypto.hash("twox64", "abc") == crypto.twox64("abc"))
+    assert(crypto.hash("twox128", "abc") == crypto.twox128("abc"))
+    assert(crypto.hash("twox256", "abc") == crypto.twox256("abc"))
+    assert(crypto.hash("keccak256", "abc") == crypto.keccak256("abc"))
+    assert(crypto.hash("sha256", "abc") == crypto.sha256("abc"))
+end)
+
+test("hash: Concat hashers append the input", function()
+    assert(crypto.hash("blake2_128_concat", "ab") == crypto.blake2b("ab", 16) .. "ab")
+    assert(crypto.hash("twox64_concat", "ab") == crypto.twox64("ab") .. "ab")
+    assert(crypto.hash("identity", "ab") == "ab")
+end)
+
+test("hash: Error on unknown algorithm", function()
+    local ok = pcall(function() crypto.hash("md5", "abc") end)
+    assert(not ok)
+end)
+
 -- Ed25519 Tests
 test("Ed25519: Keypair from seed", function()
     local seed = string.rep("\0", 32)
@@ -0,0 +1,161 @@
+-- test/test_dex.lua
+-- Unit tests for AssetConversion quotes, swaps and liquidity (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Dex = require("sublua.dex")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== DEX Module Tests ===\n")
+
+local FIXTURE = {
+    types = {
+        { "Junction", { path = { "staging_xcm", "v4", "junction", "Junction" }, variant = {
+            { name = "Parachain", index = 0, fields = { { type = "Compact<u32>" } } },
+            { name = "PalletInstance", index = 4, fields = { { type = "u8" } } },
+            { name = "GeneralIndex", index = 5, fields = { { type = "Compact<u128>" } } } } } },
+        { "[Junction;1]", { array = "Junction", len = 1 } },
+        { "[Junction;2]", { array = "Junction", len = 2 } },
+        { "Junctions", { path = { "staging_xcm", "v4", "junctions", "Junctions" }, variant = {
+            { name = "Here", index = 0 },
+            { name = "X1", index = 1, fields = { { type = "[Junction;1]" } } },
+            { name = "X2", index = 2, fields = { { type = "[Junction;2]" } } } } } },
+        { "Location", { path = { "staging_xcm", "v4", "location", "Location" }, composite = {
+            { name = "parents", type = "u8" }, { name = "interior", type = "Junctions" } } } },
+        { "Vec<Location>", { sequence = "Location" } },
+        { "AssetConversionCall", { path = { "pallet_asset_conversion", "pallet", "Call" }, variant = {
+            { name = "create_pool", index = 0, fields = {
+                { name = "asset1", type = "Location" }, { name = "asset2", type = "Location" } } },
+            { name = "add_liquidity", index = 1, fields = {
+                { name = "asset1", type = "Location" }, { name = "asset2", type = "Location" },
+                { name = "amount1_desired", type = "u128" }, { name = "amount2_desired", type = "u128" },
+                { name = "amount1_min", type = "u128" }, { name = "amount2_min", type = "u128" },
+                { name = "mint_to", type = "AccountId32" } } },
+            { name = "remove_liquidity", index = 2, fields = {
+                { name = "asset1", type = "Location" }, { name = "asset2", type = "Location" },
+                { name = "lp_token_burn", type = "u128" },
+                { name = "amount1_min_receive", type = "u128" }, { name = "amount2_min_receive", type = "u128" },
+                { name = "withdraw_to", type = "AccountId32" } } },
+            { name = "swap_exact_tokens_for_tokens", index = 3, fields = {
+                { name = "path", type = "Vec<Location>" }, { name = "amount_in", type = "u128" },
+                { name = "amount_out_min", type = "u128" }, { name = "send_to", type = "AccountId32" },
+                { name = "keep_alive", type = "bool" } } },
+            { name = "swap_tokens_for_exact_tokens", index = 4, fields = {
+                { name = "path", type = "Vec<Location>" }, { name = "amount_out", type = "u128" },
+                { name = "amount_in_max", type = "u128" }, { name = "send_to", type = "AccountId32" },
+                { name = "keep_alive", type = "bool" } } }
+        } } }
+    },
+    pallets = {
+        { name = "Assets", index = 50 },
+        { name = "AssetConversion", index = 56, calls = "AssetConversionCall" }
+    }
+}
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+
+local function hub(result)
+    local api = Mock.api({}, FIXTURE)
+    function api:state_call(method, data, at)
+        table.insert(self.calls, { method, data, at })
+        return result
+    end
+    return api
+end
+
+local function decode(api, signed)
+    return Transaction.decode(api:get_metadata(), signed).call
+end
+
+test("Dex: local asset locations", function()
+    local usdt = Dex.local_asset(Mock.metadata(FIXTURE), 1984)
+    assert(usdt.parents == 0 and usdt.interior.variant == "X2")
+    assert(usdt.interior.value[1].value == 50 and usdt.interior.value[2].value == 1984)
+end)
+
+test("Dex: quotes through AssetConversionApi", function()
+    -- Some(2_500_000)
+    local api = hub("0x01" .. "a0252600" .. string.rep("00", 12))
+    local usdt = Dex.local_asset(api, 1984)
+    local out = Dex.quote_price_exact_tokens_for_tokens(api, Dex.NATIVE, usdt, "10000000000")
+    assert(out == "2500000")
+    local call = api.calls[#api.calls]
+    assert(call[1] == "AssetConversionApi_quote_price_exact_tokens_for_tokens")
+    local meta = api:get_metadata()
+    local location = Mock.type_id("Location", FIXTURE)
+    local expected = Codec.encode(meta, location, Dex.NATIVE) .. Codec.encode(meta, location, usdt)
+        .. Codec.encode(meta, Mock.type_id("u128", FIXTURE), "10000000000") .. "\1"
+    assert(call[2] == "0x" .. to_hex(expected), "asset kinds, amount, include_fee")
+
+    Dex.quote_price_tokens_for_exact_tokens(api, usdt, Dex.NATIVE, 5, false, "0xabcd")
+    call = api.calls[#api.calls]
+    assert(call[1] == "AssetConversionApi_quote_price_tokens_for_exact_tokens" and call[3] == "0xabcd")
+    assert(call[2]:sub(-2) == "00", "fee left out")
+    assert(Dex.quote_price_exact_tokens_for_tokens(hub("0x00"), Dex.NATIVE, usdt, 1) == nil, "no pool")
+end)
+
+test("Dex: swaps", function()
+    local api = hub()
+    local usdt = Dex.local_asset(api, 1984)
+    local call = decode(api, (Dex.swap_exact_tokens_for_tokens(api, alice, { usdt, Dex.NATIVE }, "1000000", "39000000000")))
+    assert(call.pallet == "AssetConversion" and call.name == "swap_exact_tokens_for_tokens")
+    assert(#call.args.path == 2 and tonumber(call.args.path[1].interior.value[2].value) == 1984)
+    assert(call.args.path[2].interior.variant == "Here")
+    assert(call.args.amount_in == "1000000" and call.args.amount_out_min == "39000000000")
+    assert(call.args.send_to == "0x" .. to_hex(alice.pubkey) and call.args.keep_alive == true)
+
+    call = decode(api, (Dex.swap_tokens_for_exact_tokens(api, alice, { Dex.NATIVE, usdt }, 500, 2000,
+        { send_to = bob.address, keep_alive = false })))
+    assert(call.name == "swap_tokens_for_exact_tokens" and call.args.amount_out == "500")
+    assert(call.args.send_to == "0x" .. to_hex(bob.pubkey) and call.args.keep_alive == false)
+end)
+
+test("Dex: pools and liquidity", function()
+    local api = hub()
+    local usdt = Dex.local_asset(api, 1984)
+    local call = decode(api, (Dex.create_pool(api, alice, Dex.NATIVE, usdt)))
+    assert(call.name == "create_pool" and call.args.asset2.interior.variant == "X2")
+
+    call = decode(api, (Dex.add_liquidity(api, alice, Dex.NATIVE, usdt, "10000000000", "2500000", { amount2_min = 2400000 })))
+    assert(call.name == "add_liquidity" and call.args.amount1_desired == "10000000000")
+    assert(call.args.amount1_min == "0" and call.args.amount2_min == "2400000")
+    assert(call.args.mint_to == "0x" .. to_hex(alice.pubkey))
+
+    call = decode(api, (Dex.remove_liquidity(api, alice, Dex.NATIVE, usdt, 777, { withdraw_to = bob.address })))
+    assert(call.name == "remove_liquidity" and call.args.lp_token_burn == "777")
+    assert(call.args.amount1_min_receive == "0" and call.args.withdraw_to == "0x" .. to_hex(bob.pubkey))
+end)
+
+print("\n=== DEX Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All dex tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,116 @@
+-- test/test_fast_unstake.lua
+-- Unit tests for FastUnstake pallet calls and queue queries
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local FastUnstake = require("sublua.fast_unstake")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== FastUnstake Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local charlie = Keyring.from_uri("//Charlie")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+local BOB = "0x" .. to_hex(bob.pubkey)
+local CHARLIE = "0x" .. to_hex(charlie.pubkey)
+
+local FAST_UNSTAKE = {
+    types = {
+        { "FastUnstakeCall", { path = { "pallet_fast_unstake", "pallet", "Call" }, variant = {
+            { name = "register_fast_unstake", index = 0 },
+            { name = "deregister", index = 1 },
+            { name = "control", index = 2, fields = { { name = "eras_to_check", type = "u32" } } }
+        } } },
+        { "(AccountId32, u128)", { tuple = { "AccountId32", "u128" } } },
+        { "Vec<(AccountId32, u128)>", { sequence = "(AccountId32, u128)" } },
+        { "Vec<u32>", { sequence = "u32" } },
+        { "UnstakeRequest", { path = { "pallet_fast_unstake", "types", "UnstakeRequest" }, composite = {
+            { name = "stashes", type = "Vec<(AccountId32, u128)>" }, { name = "checked", type = "Vec<u32>" }
+        } } }
+    },
+    pallets = {
+        { name = "FastUnstake", index = 40, calls = "FastUnstakeCall", storage = { prefix = "FastUnstake", entries = {
+            { name = "Head", plain = "UnstakeRequest" },
+            { name = "Queue", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "u128" } },
+            { name = "CounterForQueue", plain = "u32", modifier = 1, default = "\0\0\0\0" }
+        } } }
+    }
+}
+
+local function put(api, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets["FastUnstake"].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, "FastUnstake", entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function call_of(api, signed)
+    return Transaction.decode(api:get_metadata(), signed).call
+end
+
+test("FastUnstake: register and deregister encode from metadata", function()
+    local api = Mock.api({}, FAST_UNSTAKE)
+    local call = call_of(api, FastUnstake.register_fast_unstake(api, alice, { nonce = 0 }))
+    assert(call.pallet == "FastUnstake" and call.name == "register_fast_unstake")
+    assert(call_of(api, FastUnstake.deregister(api, alice, { nonce = 0 })).name == "deregister")
+    assert(not pcall(FastUnstake.register_fast_unstake, Mock.api(), alice), "runtime without the pallet")
+end)
+
+test("FastUnstake: queue, head and status", function()
+    local api = Mock.api({}, FAST_UNSTAKE)
+    assert(FastUnstake.query_head(api) == nil and #FastUnstake.query_queue(api) == 0)
+    assert(FastUnstake.queue_length(api) == 0 and FastUnstake.status(api, alice.address) == nil)
+
+    put(api, "Head", {}, { stashes = { { ALICE, "100" } }, checked = { 40, 39 } })
+    put(api, "Queue", { BOB }, "100")
+    put(api, "CounterForQueue", {}, 1)
+    local head = FastUnstake.query_head(api)
+    assert(#head.stashes == 1 and head.stashes[1].who == ALICE and head.stashes[1].deposit == "100")
+    assert(#head.checked == 2 and head.checked[1] == 40)
+    local queue = FastUnstake.query_queue(api)
+    assert(#queue == 1 and queue[1].who == BOB and queue[1].deposit == "100")
+    assert(FastUnstake.queue_length(api) == 1)
+
+    local status = FastUnstake.status(api, alice.address)
+    assert(status.state == "checking" and status.deposit == "100" and #status.checked == 2)
+    status = FastUnstake.status(api, BOB)
+    assert(status.state == "queued" and status.deposit == "100" and status.checked == nil)
+    assert(FastUnstake.status(api, CHARLIE) == nil)
+    local ok, err = pcall(FastUnstake.status, api, "nobody")
+    assert(not ok and err:match("^who: expected an SS58 address"))
+end)
+
+print("\n=== FastUnstake Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All fast unstake tests passed!")
+    os.exit(0)
+end
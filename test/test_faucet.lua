@@ -0,0 +1,100 @@
+-- test/test_faucet.lua
+-- Unit tests for the testnet faucet client (HTTP mocked)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local http = require("socket.http")
+local json = require("cjson")
+local Faucet = require("sublua.faucet")
+local Keyring = require("sublua.keyring")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+-- Replace http.request with a canned response; returns the captured request
+local function with_response(status, body, fn)
+    local original, captured = http.request, {}
+    http.request = function(req)
+        captured.req = req
+        local chunks = {}
+        while true do
+            local chunk = req.source()
+            if not chunk then break end
+            table.insert(chunks, chunk)
+        end
+        captured.body = json.decode(table.concat(chunks))
+        if status == nil then return nil, body end
+        req.sink(body)
+        return 1, status
+    end
+    local ok, err = pcall(fn)
+    http.request = original
+    assert(ok, err)
+    return captured
+end
+
+print("=== Faucet Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+
+test("Faucet: successful drip returns the hash", function()
+    local hash
+    local captured = with_response(200, '{"hash":"0xabc"}', function()
+        hash = Faucet.request_testnet_funds("http://faucet.local/drip/web", alice.address, { parachain_id = 1000 })
+    end)
+    assert(hash == "0xabc")
+    assert(captured.req.method == "POST" and captured.req.url == "http://faucet.local/drip/web")
+    assert(captured.body.address == alice.address and captured.body.parachain_id == "1000")
+end)
+
+test("Faucet: faucet error is reported", function()
+    local hash, err
+    with_response(400, '{"error":"Requester has reached their daily quota"}', function()
+        hash, err = Faucet.request_testnet_funds("http://faucet.local/drip/web", alice.address)
+    end)
+    assert(hash == nil and err:match("daily quota"))
+end)
+
+test("Faucet: transport and body failures", function()
+    local hash, err
+    with_response(nil, "connection refused", function()
+        hash, err = Faucet.request_testnet_funds("http://faucet.local/drip/web", alice.address)
+    end)
+    assert(hash == nil and err:match("connection refused"))
+    with_response(nil, "timeout", function()
+        hash, err = Faucet.request_testnet_funds("http://faucet.local/drip/web", alice.address, { timeout_ms = 5000 })
+    end)
+    assert(hash == nil and err:match("^Timeout: .*5000 ms"))
+    with_response(502, "<html>Bad Gateway</html>", function()
+        hash, err = Faucet.request_testnet_funds("http://faucet.local/drip/web", alice.address)
+    end)
+    assert(hash == nil and err:match("502"))
+end)
+
+test("Faucet: invalid address rejected before any request", function()
+    local hash, err = Faucet.request_testnet_funds("http://faucet.local/drip/web", "not-an-address")
+    assert(hash == nil and err:match("Invalid SS58"))
+end)
+
+print("\n=== Faucet Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All faucet tests passed!")
+    os.exit(0)
+end
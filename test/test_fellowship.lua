@@ -0,0 +1,224 @@
+-- test/test_fellowship.lua
+-- Unit tests for Fellowship referenda, ranked collective and Whitelist helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Fellowship = require("sublua.fellowship")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Fellowship Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+local BOB = "0x" .. to_hex(bob.pubkey)
+
+local FIXTURE = {
+    types = {
+        { "FellowshipOrigins", { path = { "collectives_runtime", "fellowship", "origins", "Origin" }, variant = {
+            { name = "Members", index = 0 }, { name = "Fellows", index = 3 }
+        } } },
+        { "OriginCaller", { path = { "runtime", "OriginCaller" }, variant = {
+            { name = "FellowshipOrigins", index = 62, fields = { { type = "FellowshipOrigins" } } }
+        } } },
+        { "Bounded", { path = { "frame_support", "traits", "preimages", "Bounded" }, variant = {
+            { name = "Legacy", index = 0, fields = { { name = "hash", type = "H256" } } },
+            { name = "Inline", index = 1, fields = { { type = "Vec<u8>" } } },
+            { name = "Lookup", index = 2, fields = { { name = "hash", type = "H256" }, { name = "len", type = "u32" } } }
+        } } },
+        { "DispatchTime", { path = { "frame_support", "traits", "schedule", "DispatchTime" }, variant = {
+            { name = "At", index = 0, fields = { { type = "u32" } } },
+            { name = "After", index = 1, fields = { { type = "u32" } } }
+        } } },
+        { "ReferendaCall", { path = { "pallet_referenda", "pallet", "Call" }, variant = {
+            { name = "submit", index = 0, fields = {
+                { name = "proposal_origin", type = "OriginCaller" }, { name = "proposal", type = "Bounded" },
+                { name = "enactment_moment", type = "DispatchTime" } } },
+            { name = "place_decision_deposit", index = 1, fields = { { name = "index", type = "u32" } } },
+            { name = "refund_decision_deposit", index = 2, fields = { { name = "index", type = "u32" } } }
+        } } },
+        { "ReferendumStatus", { path = { "pallet_referenda", "types", "ReferendumStatus" }, composite = {
+            { name = "track", type = "u16" }, { name = "submitted", type = "u32" }
+        } } },
+        { "ReferendumInfo", { path = { "pallet_referenda", "types", "ReferendumInfo" }, variant = {
+            { name = "Ongoing", index = 0, fields = { { type = "ReferendumStatus" } } },
+            { name = "Approved", index = 1, fields = { { type = "u32" }, { type = "Option<u32>" } } },
+            { name = "Killed", index = 5, fields = { { type = "u32" } } }
+        } } },
+        { "RankedCall", { path = { "pallet_ranked_collective", "pallet", "Call" }, variant = {
+            { name = "add_member", index = 0, fields = { { name = "who", type = "MultiAddress" } } },
+            { name = "promote_member", index = 1, fields = { { name = "who", type = "MultiAddress" } } },
+            { name = "demote_member", index = 2, fields = { { name = "who", type = "MultiAddress" } } },
+            { name = "remove_member", index = 3, fields = { { name = "who", type = "MultiAddress" }, { name = "min_rank", type = "u16" } } },
+            { name = "vote", index = 4, fields = { { name = "poll", type = "u32" }, { name = "aye", type = "bool" } } }
+        } } },
+        { "RankedMemberRecord", { path = { "pallet_ranked_collective", "MemberRecord" }, composite = {
+            { name = "rank", type = "u16" }
+        } } },
+        { "VoteRecord", { path = { "pallet_ranked_collective", "VoteRecord" }, variant = {
+            { name = "Aye", index = 0, fields = { { type = "u32" } } },
+            { name = "Nay", index = 1, fields = { { type = "u32" } } }
+        } } },
+        { "(u32, AccountId32)", { tuple = { "u32", "AccountId32" } } },
+        { "WhitelistCall", { path = { "pallet_whitelist", "pallet", "Call" }, variant = {
+            { name = "whitelist_call", index = 0, fields = { { name = "call_hash", type = "H256" } } },
+            { name = "remove_whitelisted_call", index = 1, fields = { { name = "call_hash", type = "H256" } } },
+            { name = "dispatch_whitelisted_call", index = 2, fields = {
+                { name = "call_hash", type = "H256" }, { name = "call_encoded_len", type = "u32" },
+                { name = "call_weight_witness", type = "Weight" } } },
+            { name = "dispatch_whitelisted_call_with_preimage", index = 3, fields = { { name = "call", type = "RuntimeCall" } } }
+        } } }
+    },
+    pallets = {
+        { name = "FellowshipCollective", index = 60, calls = "RankedCall", storage = { prefix = "FellowshipCollective", entries = {
+            { name = "Members", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "RankedMemberRecord" } },
+            { name = "Voting", map = { hashers = { "Blake2_128Concat", "Twox64Concat" }, key = "(u32, AccountId32)", value = "VoteRecord" } }
+        } } },
+        { name = "FellowshipReferenda", index = 61, calls = "ReferendaCall", storage = { prefix = "FellowshipReferenda", entries = {
+            { name = "ReferendumCount", plain = "u32", modifier = 1, default = "\0\0\0\0" },
+            { name = "ReferendumInfoFor", map = { hashers = { "Blake2_128Concat" }, key = "u32", value = "ReferendumInfo" } }
+        } } },
+        { name = "Whitelist", index = 64, calls = "WhitelistCall", storage = { prefix = "Whitelist", entries = {
+            { name = "WhitelistedCall", map = { hashers = { "Twox64Concat" }, key = "H256", value = "()" } }
+        } } }
+    }
+}
+
+local FELLOWS = { variant = "FellowshipOrigins", value = { variant = "Fellows" } }
+
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function call_of(api, signed)
+    return Transaction.decode(api:get_metadata(), signed).call
+end
+
+local function remark(n)
+    return Call.encode_call(Mock.metadata(), "System", "remark", { remark = "0x" .. string.rep("ab", n) })
+end
+
+test("Fellowship: bounded proposals inline or by lookup", function()
+    local small, large = remark(10), remark(200)
+    local b = Fellowship.bounded(small)
+    assert(b.variant == "Inline" and b.value == "0x" .. to_hex(small))
+    b = Fellowship.bounded("0x" .. to_hex(large))
+    assert(b.variant == "Lookup" and b.value.len == #large and b.value.hash == Call.call_hash(large))
+end)
+
+test("Fellowship: submit a referendum", function()
+    local api = Mock.api({}, FIXTURE)
+    assert(not pcall(Fellowship.submit_referendum, api, alice, remark(1), {}), "origin is required")
+    local call = call_of(api, Fellowship.submit_referendum(api, alice, remark(1), { origin = FELLOWS, nonce = 0 }))
+    assert(call.pallet == "FellowshipReferenda" and call.name == "submit")
+    assert(call.args.proposal_origin.variant == "FellowshipOrigins" and call.args.proposal_origin.value.variant == "Fellows")
+    assert(call.args.proposal.variant == "Inline")
+    assert(call.args.enactment_moment.variant == "After" and call.args.enactment_moment.value == 10)
+    call = call_of(api, Fellowship.place_decision_deposit(api, alice, 4, { nonce = 0 }))
+    assert(call.name == "place_decision_deposit" and call.args.index == 4)
+end)
+
+test("Fellowship: referendum queries", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "FellowshipReferenda", "ReferendumInfoFor", { 3 }, { variant = "Ongoing", value = { track = 1, submitted = 90 } })
+    put(api, "FellowshipReferenda", "ReferendumInfoFor", { 2 }, { variant = "Approved", value = { 120, { variant = "None" } } })
+    put(api, "FellowshipReferenda", "ReferendumInfoFor", { 1 }, { variant = "Killed", value = 80 })
+    put(api, "FellowshipReferenda", "ReferendumCount", {}, 4)
+    local r = Fellowship.query_referendum(api, 3)
+    assert(r.status == "Ongoing" and r.ongoing.track == 1 and r.since == nil)
+    r = Fellowship.query_referendum(api, 2)
+    assert(r.status == "Approved" and r.since == 120)
+    assert(Fellowship.query_referendum(api, 1).since == 80)
+    assert(Fellowship.query_referendum(api, 9) == nil)
+    assert(Fellowship.query_referendum_count(api) == 4)
+end)
+
+test("Fellowship: collective votes and rank changes", function()
+    local api = Mock.api({}, FIXTURE)
+    local call = call_of(api, Fellowship.vote(api, alice, 3, true, { nonce = 0 }))
+    assert(call.pallet == "FellowshipCollective" and call.name == "vote" and call.args.poll == 3 and call.args.aye == true)
+    call = call_of(api, Fellowship.promote_member(api, alice, bob.address, { nonce = 0 }))
+    assert(call.name == "promote_member" and call.args.who.value == BOB)
+    call = call_of(api, Fellowship.remove_member(api, alice, BOB, 2, { nonce = 0 }))
+    assert(call.name == "remove_member" and call.args.min_rank == 2)
+end)
+
+test("Fellowship: member and vote queries", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "FellowshipCollective", "Members", { ALICE }, { rank = 1 })
+    put(api, "FellowshipCollective", "Members", { BOB }, { rank = 3 })
+    put(api, "FellowshipCollective", "Voting", { 3, BOB }, { variant = "Aye", value = 6 })
+    assert(Fellowship.query_rank(api, alice.address) == 1)
+    assert(Fellowship.query_rank(api, "0x" .. string.rep("00", 32)) == nil)
+    local members = Fellowship.query_members(api)
+    assert(#members == 2 and members[1].account == BOB and members[1].rank == 3)
+    local vote = Fellowship.query_vote(api, 3, bob.address)
+    assert(vote.aye == true and vote.votes == 6)
+    assert(Fellowship.query_vote(api, 3, ALICE) == nil)
+end)
+
+test("Fellowship: whitelist call encoding", function()
+    local api = Mock.api({}, FIXTURE)
+    local target = remark(5)
+    local hash = Call.call_hash(target)
+    local call = Fellowship.encode_whitelist_call(api, target)
+    assert(call == Fellowship.encode_whitelist_call(api, hash, true))
+    assert(to_hex(call:sub(1, 2)) == "4000" and "0x" .. to_hex(call:sub(3)) == hash)
+    assert(to_hex(Fellowship.encode_remove_whitelisted_call(api, hash):sub(1, 2)) == "4001")
+
+    local inline = Fellowship.encode_dispatch_whitelisted(api, target, { inline = true })
+    assert(inline == "\64\3" .. target)
+    function api:state_call()
+        return "0x" .. to_hex(Scale.encode_compact(1000) .. Scale.encode_compact(64) .. "\0" .. string.rep("\0", 16))
+    end
+    local decoded = Transaction.decode_call(api:get_metadata(), Fellowship.encode_dispatch_whitelisted(api, target))
+    assert(decoded.name == "dispatch_whitelisted_call" and decoded.args.call_hash == hash)
+    assert(decoded.args.call_encoded_len == #target and decoded.args.call_weight_witness.ref_time == "1000")
+end)
+
+test("Fellowship: is_whitelisted reads the unit value", function()
+    local api = Mock.api({}, FIXTURE)
+    local hash = Call.call_hash(remark(5))
+    assert(Fellowship.is_whitelisted(api, hash) == false)
+    api.storage[Storage.build_key(api, "Whitelist", "WhitelistedCall", { hash })] = "0x"
+    assert(Fellowship.is_whitelisted(api, hash) == true)
+end)
+
+print("\n=== Fellowship Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All fellowship tests passed!")
+    os.exit(0)
+end
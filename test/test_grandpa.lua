@@ -0,0 +1,252 @@
+-- test/test_grandpa.lua
+-- Unit tests for header hashing and GRANDPA justification verification
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Header = require("sublua.header")
+local Grandpa = require("sublua.grandpa")
+local Keyring = require("sublua.keyring")
+local Scale = require("sublua.scale")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- Rococo block header (smoldot test vector): BABE pre-runtime, BEEF consensus and BABE seal items
+local ROCOCO_HEADER = "0x" ..
+    "052337da75d11d756782372737845f368ab9594f7ba17c334328477e00d2f04e39b16661afb77ccec34dd975530e8632" ..
+    "f6a38ac4c74e6c91bbf07b0512db9e2cae84294679b5a0bd68fdad87de0f2d44f8172e068cf7123425092026660cbe08" ..
+    "d4ed0c0642414245b5010100000000fd79121000000000b60e504d2e27d13c510e8dcea0326ae9237b04b942b6c19c13" ..
+    "2d899b7bba0b78fb7b5175716ca9738ed0f332660475fef7e2c77184198d5af713d3059860790628d95c002126c74924" ..
+    "81a19fb8d0d76e967fdd9e32667628921808620738900004424545468403270b21e038641112769fa7670a567dde14bd" ..
+    "78ec30ca59b4471f38b91721175705424142450101b4fde75ac4ced0b70e617cf32ba0855e13a27e13070fde49727168" ..
+    "4e1834712f279a6c941c92b4e8c71434aa5dd6006da8afa25beac3e48becaafbc8b27b1a82"
+
+-- Justification from a live set-0 GRANDPA round (smoldot test vector), five precommits
+local LIVE_JUSTIFICATION = "0x" ..
+    "07b506000000000029f1abec90ac199df06dee3ba0734c08c3fd6df06caa3f78952f8f95164058d2009e04001429f1ab" ..
+    "ec90ac199df06dee3ba0734c08c3fd6df06caa3f78952f8f95164058d2009e04000df78178ccaa78ad29f1d5ea796f14" ..
+    "26c15e638b391e47d1ecdea57b468b4741248e270d5ef02cae965595dfa652d2672881661ad474e7d1a36b3152e5c552" ..
+    "081c151c11cb72334d26d70769e3af7bbff3801a4e2dca2b09b7cce0af8dd8130729f1abec90ac199df06dee3ba0734c" ..
+    "08c3fd6df06caa3f78952f8f95164058d2009e04003e25912c15c078e5ec717a38c1f72dd2b80c3edcfd93468555125a" ..
+    "a7c976176bb8bb0368aa841112594d9c91f208b9584a571534f765399aa30582140fe60803680d278213f908658a49a1" ..
+    "025a7f466c197e8fb6fabb5e62220a7bd75f860cab29f1abec90ac199df06dee3ba0734c08c3fd6df06caa3f78952f8f" ..
+    "95164058d2009e04007dac4f4701268980e85f4668d95f073a1c72b6d8ab38e7dac7f4dc7a06e105afac2fc63d542a4b" ..
+    "423e5af3123a246ceb84678826a4a4eda429e1989d92ed180b8e59368700ea89e2bf8922cc9e4b86d6651d1c689a0d57" ..
+    "813f9768dbaadecf7129f1abec90ac199df06dee3ba0734c08c3fd6df06caa3f78952f8f95164058d2009e040044c0d3" ..
+    "8eef2137dea57fcb9bd9aa3d5fce4a4a137b3c438e5012af28889c97e0bf9d5bbb27b9f9d49e49c55a36de0d4cb58645" ..
+    "03a5f85ec444ba50da57a2110bdea6f4a727d3b2399275d6ee8817881f10597471dc1d27f144295ad6fb933c7a29f1ab" ..
+    "ec90ac199df06dee3ba0734c08c3fd6df06caa3f78952f8f95164058d2009e04003abb7b87029d51c528c8da34fdc177" ..
+    "68bef6dde1afc3b1dad1af537762afc430434c3bdf0dca30010a63c8c97b1d59837846a2eb0bbf60395333d9c72332ae" ..
+    "02f72daf2e560e4f0f22fb5cbb04ad1d7fee850aab238fd014c178769e7e3a9b8400"
+
+print("=== GRANDPA Tests ===\n")
+
+test("Header: decode and re-encode", function()
+    local header = Header.decode(ROCOCO_HEADER)
+    assert(header.number == 11342 and header.parent_hash:sub(1, 10) == "0x052337da")
+    assert(#header.digest == 3)
+    assert(header.digest[1].type == "PreRuntime" and header.digest[1].engine == "BABE")
+    assert(header.digest[2].type == "Consensus" and header.digest[2].engine == "BEEF")
+    assert(header.digest[3].type == "Seal" and #from_hex(header.digest[3].data) == 64)
+    assert("0x" .. to_hex(Header.encode(header)) == ROCOCO_HEADER)
+    assert(Header.hash(header) == Header.hash(ROCOCO_HEADER))
+end)
+
+test("Header: chain_getHeader JSON", function()
+    local header = Header.decode(ROCOCO_HEADER)
+    local logs = {}
+    local encoded = Header.encode(header)
+    -- Digest items as the node serves them: each item SCALE-encoded on its own
+    for _, item in ipairs(header.digest) do
+        local single = Header.encode({
+            parent_hash = header.parent_hash, number = 0, state_root = header.state_root,
+            extrinsics_root = header.extrinsics_root, digest = { item }
+        })
+        table.insert(logs, "0x" .. to_hex(single:sub(1 + 32 + 1 + 64 + 1)))
+    end
+    local json_header = {
+        parentHash = header.parent_hash, number = string.format("0x%x", header.number),
+        stateRoot = header.state_root, extrinsicsRoot = header.extrinsics_root,
+        digest = { logs = logs }
+    }
+    assert(Header.encode(json_header) == encoded)
+    assert(Header.hash(json_header) == Header.hash(encoded))
+end)
+
+test("Header: genesis hash", function()
+    -- Polkadot genesis: zero parent, number 0, empty extrinsics trie, no digest
+    local genesis = {
+        parent_hash = "0x" .. string.rep("00", 32), number = 0,
+        state_root = "0x29d0d972cd27cbc511e9589fcb7a4506d5eb6a9e8df205f00472e5ab354a4e17",
+        extrinsics_root = "0x03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314",
+        digest = {}
+    }
+    assert(Header.hash(genesis) == "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3")
+end)
+
+test("GRANDPA: live justification", function()
+    local j = Grandpa.decode_justification(LIVE_JUSTIFICATION)
+    assert(j.round == 439559 and j.target_number == 302592 and #j.precommits == 5)
+    local authorities = {}
+    for _, p in ipairs(j.precommits) do table.insert(authorities, p.id) end
+    local set = { set_id = 0, authorities = authorities }
+    assert(Grandpa.verify_justification(LIVE_JUSTIFICATION, set, j.target_hash))
+    local ok, err = Grandpa.verify_justification(LIVE_JUSTIFICATION, { set_id = 1, authorities = authorities }, j.target_hash)
+    assert(not ok and err:match("Bad precommit signature"))
+    ok, err = Grandpa.verify_justification(LIVE_JUSTIFICATION, set, "0x" .. string.rep("11", 32))
+    assert(not ok and err:match("targets block #302592"))
+end)
+
+-- Synthetic chain: target <- child, voters sign either the target or the child
+local voters = {}
+for i = 1, 4 do voters[i] = Keyring.from_seed(string.rep(string.char(64 + i), 32)) end
+
+local function make_header(parent_hash, number)
+    return {
+        parent_hash = parent_hash, number = number,
+        state_root = "0x" .. string.rep("22", 32), extrinsics_root = "0x" .. string.rep("33", 32),
+        digest = {}
+    }
+end
+
+local target = make_header("0x" .. string.rep("00", 32), 10)
+local target_hash = Header.hash(target)
+local child = make_header(target_hash, 11)
+local child_hash = Header.hash(child)
+
+-- votes: list of { signer, hash, number }; ancestries: list of headers
+local function make_justification(votes, ancestries, set_id)
+    local round = Scale.encode_u64(7)
+    local out = { round, from_hex(target_hash), Scale.encode_u32(10), Scale.encode_compact(#votes) }
+    for _, v in ipairs(votes) do
+        local payload = "\1" .. from_hex(v[2]) .. Scale.encode_u32(v[3]) .. round .. Scale.encode_u64(set_id or 3)
+        table.insert(out, from_hex(v[2]) .. Scale.encode_u32(v[3]) .. v[1]:sign(payload) .. v[1].pubkey)
+    end
+    table.insert(out, Scale.encode_compact(#ancestries))
+    for _, h in ipairs(ancestries) do table.insert(out, Header.encode(h)) end
+    return "0x" .. to_hex(table.concat(out))
+end
+
+local SET = { set_id = 3, authorities = {} }
+for _, v in ipairs(voters) do table.insert(SET.authorities, { id = v.address, weight = 1 }) end
+
+test("GRANDPA: supermajority with descendant votes", function()
+    local j = make_justification({
+        { voters[1], target_hash, 10 }, { voters[2], child_hash, 11 }, { voters[3], target_hash, 10 }
+    }, { child })
+    assert(Grandpa.verify_justification(j, SET, target))
+    assert(Grandpa.verify_justification(j, SET, "0x" .. to_hex(Header.encode(target))))
+end)
+
+test("GRANDPA: threshold and weights", function()
+    local j = make_justification({ { voters[1], target_hash, 10 }, { voters[2], target_hash, 10 } }, {})
+    local ok, err = Grandpa.verify_justification(j, SET, target)
+    assert(not ok and err:match("weight 2 of 4, need 3"))
+    local weighted = { set_id = 3, authorities = {
+        { voters[1].pubkey, 5 }, { voters[2].pubkey, 5 }, { voters[3].pubkey, 1 }, { voters[4].pubkey, 1 }
+    } }
+    assert(Grandpa.verify_justification(j, weighted, target))
+end)
+
+test("GRANDPA: rejects bad precommits", function()
+    local outsider = Keyring.from_seed(string.rep("z", 32))
+    local base = { { voters[1], target_hash, 10 }, { voters[2], target_hash, 10 } }
+    local function check(extra, ancestries, pattern, set_id)
+        local votes = { base[1], base[2], extra }
+        local ok, err = Grandpa.verify_justification(make_justification(votes, ancestries or {}, set_id), SET, target)
+        assert(not ok and err:match(pattern), tostring(err))
+    end
+    check({ outsider, target_hash, 10 }, nil, "non%-authority")
+    check({ voters[3], child_hash, 11 }, nil, "does not descend")
+    check({ voters[3], target_hash, 10 }, { child }, "Unused header")
+    check({ voters[3], target_hash, 10 }, nil, "Bad precommit signature", 4)
+    local ok, err = Grandpa.verify_justification("0x1234", SET, target)
+    assert(not ok and err:match("Invalid justification"))
+end)
+
+test("GRANDPA: equivocating voters count once", function()
+    -- voters[1] precommits both the target and its child; 2 distinct voters of 4 is short
+    local j = make_justification({
+        { voters[1], target_hash, 10 }, { voters[1], child_hash, 11 }, { voters[2], target_hash, 10 }
+    }, { child })
+    local ok, err = Grandpa.verify_justification(j, SET, target)
+    assert(not ok and err:match("weight 2 of 4, need 3"), tostring(err))
+    j = make_justification({
+        { voters[1], target_hash, 10 }, { voters[1], child_hash, 11 },
+        { voters[2], target_hash, 10 }, { voters[3], target_hash, 10 }
+    }, { child })
+    assert(Grandpa.verify_justification(j, SET, target))
+end)
+
+test("GRANDPA: verify with the authority set as JSON", function()
+    local j = make_justification({
+        { voters[1], target_hash, 10 }, { voters[2], target_hash, 10 }, { voters[3], target_hash, 10 }
+    }, {})
+    local header_hex = "0x" .. to_hex(Header.encode(target))
+    local authorities = {}
+    for i, v in ipairs(voters) do
+        authorities[i] = i % 2 == 0 and string.format('["%s", 1]', v.address) or string.format('"0x%s"', to_hex(v.pubkey))
+    end
+    local json = '{"set_id": 3, "authorities": [' .. table.concat(authorities, ", ") .. ']}'
+    assert(Grandpa.verify_grandpa_justification(j, json, header_hex))
+    local ok, err = Grandpa.verify_grandpa_justification(j, json:gsub('"set_id": 3', '"set_id": 4'), header_hex)
+    assert(not ok and err:match("Bad precommit signature"))
+    ok, err = Grandpa.verify_grandpa_justification(j, "{nope", header_hex)
+    assert(not ok and err:match("Invalid authorities JSON"))
+    ok, err = Grandpa.verify_grandpa_justification(j, '{"authorities": []}', header_hex)
+    assert(not ok and err:match("set_id"))
+end)
+
+test("GRANDPA: fetch authority set and justification", function()
+    local api = {}
+    function api:state_call(method)
+        assert(method == "GrandpaApi_grandpa_authorities")
+        local out = Scale.encode_compact(#voters)
+        for _, v in ipairs(voters) do out = out .. v.pubkey .. Scale.encode_u64(1) end
+        return "0x" .. to_hex(out)
+    end
+    function api:state_getStorage() return "0x0300000000000000" end
+    function api:grandpa_proveFinality()
+        local j = from_hex(make_justification({
+            { voters[1], target_hash, 10 }, { voters[2], target_hash, 10 }, { voters[3], target_hash, 10 }
+        }, {}))
+        return "0x" .. to_hex(from_hex(target_hash) .. Scale.encode_compact(#j) .. j .. Scale.encode_compact(0))
+    end
+    local set = Grandpa.get_authority_set(api)
+    assert(set.set_id == 3 and #set.authorities == 4 and set.authorities[1].id == "0x" .. to_hex(voters[1].pubkey))
+    local j, block = Grandpa.get_justification(api, 10)
+    assert(block == target_hash)
+    assert(Grandpa.verify_justification(j, set, target))
+end)
+
+print("\n=== GRANDPA Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All GRANDPA tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,284 @@
+-- test/test_identity.lua
+-- Unit tests for identity queries and the address book
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Identity = require("sublua.identity")
+local AddressBook = require("sublua.addressbook")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Address = require("sublua.address")
+local Registry = require("sublua.registry")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Identity Module Tests ===\n")
+
+local ALICE = "0x" .. string.rep("a1", 32)
+local BOB = "0x" .. string.rep("b2", 32)
+local STASH = "0x" .. string.rep("c3", 32)
+local NOBODY = "0x" .. string.rep("d4", 32)
+
+-- value: "Registration" (older runtimes) or "(Registration, Option<Username>)"
+local function fixture(value)
+    return {
+        types = {
+            { "[u8;3]", { array = "u8", len = 3 } },
+            { "[u8;4]", { array = "u8", len = 4 } },
+            { "[u8;5]", { array = "u8", len = 5 } },
+            { "[u8;7]", { array = "u8", len = 7 } },
+            { "Data", { path = { "pallet_identity", "types", "Data" }, variant = {
+                { name = "None", index = 0 },
+                { name = "Raw3", index = 4, fields = { { type = "[u8;3]" } } },
+                { name = "Raw4", index = 5, fields = { { type = "[u8;4]" } } },
+                { name = "Raw5", index = 6, fields = { { type = "[u8;5]" } } },
+                { name = "Raw7", index = 8, fields = { { type = "[u8;7]" } } },
+                { name = "BlakeTwo256", index = 34, fields = { { type = "[u8;32]" } } }
+            } } },
+            { "IdentityInfo", { path = { "pallet_identity", "legacy", "IdentityInfo" }, composite = {
+                { name = "display", type = "Data" }, { name = "legal", type = "Data" },
+                { name = "web", type = "Data" }, { name = "email", type = "Data" },
+                { name = "riot", type = "Data" }, { name = "image", type = "Data" },
+                { name = "twitter", type = "Data" }
+            } } },
+            { "Judgement", { path = { "pallet_identity", "types", "Judgement" }, variant = {
+                { name = "Unknown", index = 0 }, { name = "FeePaid", index = 1, fields = { { type = "u128" } } },
+                { name = "Reasonable", index = 2 }, { name = "KnownGood", index = 3 },
+                { name = "OutOfDate", index = 4 }, { name = "LowQuality", index = 5 }, { name = "Erroneous", index = 6 }
+            } } },
+            { "(u32, Judgement)", { tuple = { "u32", "Judgement" } } },
+            { "Vec<(u32, Judgement)>", { sequence = "(u32, Judgement)" } },
+            { "Registration", { path = { "pallet_identity", "types", "Registration" }, composite = {
+                { name = "judgements", type = "Vec<(u32, Judgement)>" }, { name = "deposit", type = "u128" },
+                { name = "info", type = "IdentityInfo" }
+            } } },
+            { "Option<Vec<u8>>", { path = { "Option" }, variant = {
+                { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = "Vec<u8>" } } }
+            } } },
+            { "(Registration, Option<Vec<u8>>)", { tuple = { "Registration", "Option<Vec<u8>>" } } },
+            { "(AccountId32, Data)", { tuple = { "AccountId32", "Data" } } },
+            { "(u128, Vec<AccountId32>)", { tuple = { "u128", "Vec<AccountId32>" } } },
+            { "IdentityCall", { path = { "pallet_identity", "pallet", "Call" }, variant = {
+                { name = "set_identity", index = 1, fields = { { name = "info", type = "IdentityInfo" } } },
+                { name = "clear_identity", index = 3 }
+            } } }
+        },
+        pallets = {
+            { name = "Identity", index = 50, calls = "IdentityCall", storage = { prefix = "Identity", entries = {
+                { name = "IdentityOf", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = value } },
+                { name = "SuperOf", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "(AccountId32, Data)" } },
+                { name = "SubsOf", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "(u128, Vec<AccountId32>)" } }
+            } } }
+        }
+    }
+end
+
+local function put(api, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets["Identity"].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, "Identity", entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local NONE = { variant = "None" }
+local function raw(text) return { variant = "Raw" .. #text, value = "0x" .. to_hex(text) } end
+
+local function registration(display, judgement)
+    return {
+        judgements = judgement and { { 0, type(judgement) == "table" and judgement or { variant = judgement } } } or {},
+        deposit = "1000",
+        info = { display = raw(display), legal = NONE, web = raw("a.org"), email = NONE,
+            riot = NONE, image = { variant = "BlakeTwo256", value = "0x" .. string.rep("ee", 32) }, twitter = NONE }
+    }
+end
+
+-- Alice: verified identity with sub-account Stash ("Cold"); Bob: unverified, older layout
+local function identity_api(legacy)
+    local api = Mock.api({}, fixture(legacy and "Registration" or "(Registration, Option<Vec<u8>>)"))
+    if legacy then
+        put(api, "IdentityOf", { BOB }, registration("Bob", nil))
+    else
+        put(api, "IdentityOf", { ALICE }, { registration("Alice", "KnownGood"), { variant = "Some", value = "0x" .. to_hex("alice.dot") } })
+        put(api, "IdentityOf", { BOB }, { registration("Bob", { variant = "FeePaid", value = "10" }), NONE })
+        put(api, "SuperOf", { STASH }, { ALICE, raw("Cold") })
+        put(api, "SubsOf", { ALICE }, { "500", { STASH } })
+    end
+    return api
+end
+
+test("Identity: registration with judgements and username", function()
+    local api = identity_api()
+    local id = Identity.query_identity(api, ALICE)
+    assert(id.display == "Alice" and id.web == "a.org" and id.email == nil and id.verified == true)
+    assert(id.judgements[1].registrar == 0 and id.judgements[1].judgement == "KnownGood")
+    assert(id.username == "alice.dot" and id.deposit == "1000")
+    assert(id.image.BlakeTwo256 == "0x" .. string.rep("ee", 32))
+    assert(Identity.query_identity(api, BOB).verified == false, "FeePaid is not a verification")
+    assert(Identity.query_identity(api, NOBODY) == nil)
+end)
+
+test("Identity: older Registration layout", function()
+    local id = Identity.query_identity(identity_api(true), BOB)
+    assert(id.display == "Bob" and id.username == nil and #id.judgements == 0)
+end)
+
+test("Identity: sub-identities and display names", function()
+    local api = identity_api()
+    local super = Identity.query_super(api, STASH)
+    assert(super.parent == ALICE and super.name == "Cold")
+    local subs = Identity.query_subs(api, Address.encode(ALICE, 0))
+    assert(subs.deposit == "500" and subs.subs[1] == STASH)
+    assert(#Identity.query_subs(api, BOB).subs == 0)
+    local name, verified = Identity.display_name(api, STASH)
+    assert(name == "Alice/Cold" and verified == true)
+    assert(Identity.display_name(api, NOBODY) == nil)
+end)
+
+test("AddressBook: display names with verification and labels", function()
+    local api = identity_api()
+    local book = AddressBook.new(api)
+    assert(book:resolve_display_name(ALICE) == "Alice (verified)")
+    assert(book:resolve_display_name(BOB) == "Bob")
+    assert(book:resolve_display_name(STASH) == "Alice/Cold (verified)")
+    assert(book:resolve_display_name(NOBODY) == Address.encode(NOBODY, 42))
+    book:set_label(NOBODY, "Exchange hot wallet")
+    local name, entry = book:resolve_display_name(NOBODY)
+    assert(name == "Exchange hot wallet" and entry.label == name)
+end)
+
+test("AddressBook: cached until the ttl runs out", function()
+    local api = identity_api()
+    local clock = 1000
+    local book = AddressBook.new(api, { ttl = 60, now = function() return clock end })
+    book:resolve_display_name(BOB)
+    local reads = #api.calls
+    book:resolve_display_name(BOB)
+    assert(#api.calls == reads, "second lookup served from cache")
+    clock = clock + 61
+    book:resolve_display_name(BOB)
+    assert(#api.calls > reads, "expired entries are read again")
+    reads = #api.calls
+    book:clear(BOB)
+    book:resolve_display_name(BOB)
+    assert(#api.calls > reads)
+end)
+
+test("AddressBook: search by identity name", function()
+    local api = identity_api()
+    local book = AddressBook.new(api, { prefix = 0 })
+    local found = book:resolve_address_by_identity("ali")
+    assert(#found == 2 and found[1].display == "Alice" and found[2].display == "Alice/Cold")
+    assert(found[1].address == Address.encode(ALICE, 0) and found[2].parent == found[1].address)
+    assert(found[2].verified == true)
+    local cold = book:resolve_address_by_identity("COLD")
+    assert(#cold == 1 and cold[1].address == Address.encode(STASH, 0))
+    book:set_label(NOBODY, "Treasury multisig")
+    assert(#book:resolve_address_by_identity("treasury") == 1)
+    assert(#book:resolve_address_by_identity("zzz") == 0)
+end)
+
+-- Relay chain without the Identity pallet
+local function relay_api(spec_name)
+    local api = Mock.api({})
+    function api:state_getRuntimeVersion() return { specName = spec_name, specVersion = 1000000 } end
+    return api
+end
+
+test("Identity: relay chains route to their people chain", function()
+    local relay = relay_api("polkadot")
+    local people = Identity.chain(relay)
+    assert(people.url == Identity.PEOPLE_CHAINS.polkadot and Identity.chain(relay) == people, "created once")
+    assert(not pcall(Identity.chain, relay_api("solochain")), "unknown relays need an explicit people chain")
+
+    local own = identity_api()
+    assert(Identity.chain(own) == own, "chains with the pallet answer themselves")
+    local relay2 = relay_api("kusama")
+    Identity.set_people_chain(relay2, own)
+    assert(Identity.query_identity(relay2, ALICE).display == "Alice")
+    assert(Identity.display_name(relay2, STASH) == "Alice/Cold")
+    assert(AddressBook.new(relay2):resolve_display_name(BOB) == "Bob")
+    assert(#AddressBook.new(relay2):resolve_address_by_identity("cold") == 1)
+end)
+
+test("Identity: set and clear identity on the people chain", function()
+    local people = identity_api()
+    local relay = relay_api("polkadot")
+    Identity.set_people_chain(relay, people)
+    local alice = Keyring.from_uri("//Alice")
+    local signed = Identity.set_identity(relay, alice, { display = "Alice", web = "a.org", matrix = "@a:m.io" })
+    local call = Transaction.decode(people:get_metadata(), signed).call
+    assert(call.pallet == "Identity" and call.name == "set_identity")
+    assert(Identity.data_text(call.args.info.display) == "Alice" and call.args.info.legal.variant == "None")
+    assert(Identity.data_text(call.args.info.riot) == "@a:m.io", "matrix goes to riot")
+    assert(not pcall(Identity.set_identity, relay, alice, { display = string.rep("x", 33) }), "too long")
+    local ok, err = pcall(Identity.encode_info, people, { telegram = "@a" })
+    assert(not ok and err:match("^info%.telegram: expected one of display, .*%(unknown field%)$"), err)
+    ok, err = pcall(Identity.encode_info, people, '{"display": "Alice", "web": 7}')
+    assert(not ok and err == "info.web: expected text of at most 7 bytes, got number 7", err)
+    call = Transaction.decode(people:get_metadata(), (Identity.clear_identity(relay, alice))).call
+    assert(call.name == "clear_identity")
+end)
+
+test("Identity: fields are checked before submission", function()
+    local people = identity_api()
+    local relay = relay_api("polkadot")
+    Identity.set_people_chain(relay, people)
+    local alice = Keyring.from_uri("//Alice")
+    assert(Identity.max_field_bytes(people) == 7, "largest Raw variant of the fixture")
+    assert(#Identity.check_info(people, { display = "Alice", email = "a@bc.io", twitter = "@al", matrix = "@a:m.io" }) == 0)
+    local problems = Identity.check_info(people, { display = "Alice ", email = "alice", twitter = "@a b" })
+    assert(#problems == 3, "every problem, in field order")
+    assert(problems[1] == "info.display: expected text without control characters or surrounding spaces, got 'Alice '")
+    assert(problems[2]:match("^info%.email: expected an address like") and problems[3]:match("^info%.twitter: "))
+    assert(Identity.check_info(people, { display = string.rep("x", 8) })[1]
+        == "info.display: expected text of at most 7 bytes, got 'xxxxxxxx' (8 bytes)")
+
+    local ok, err = pcall(Identity.set_identity, relay, alice, { email = "alice" })
+    assert(not ok and err == "info.email: expected an address like name@example.org, got 'alice'", err)
+    local signed = Identity.set_identity(relay, alice, { email = "alice" }, { allow_invalid = true })
+    assert(Identity.data_text(Transaction.decode(people:get_metadata(), signed).call.args.info.email) == "alice")
+    assert(not pcall(Identity.set_identity, relay, alice, { display = string.rep("x", 8) }, { allow_invalid = true }),
+        "lengths still apply")
+end)
+
+test("Identity: registry resolves the people chain", function()
+    local people = identity_api()
+    local chains = Registry.new()
+    chains:register("polkadot", { api = relay_api("polkadot"), people = "people" })
+    chains:register("people", { api = people, relay = "polkadot" })
+    chains:register("assethub", { api = Mock.api({}), relay = "polkadot" })
+    assert(chains:identity_api("polkadot") == people and chains:identity_api("assethub") == people)
+    assert(Identity.query_identity(chains:api("polkadot"), ALICE).verified == true, "relay client linked")
+    assert(chains:identity_api("people") == people)
+end)
+
+print("\n=== Identity Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All identity tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,171 @@
+-- test/test_indexer.lua
+-- Unit tests for the embedded transfer/event indexer
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Indexer = require("sublua.indexer")
+local Keyring = require("sublua.keyring")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Indexer Tests ===\n")
+
+local ALICE = Keyring.from_uri("//Alice")
+local BOB = Keyring.from_uri("//Bob")
+local CHARLIE = string.rep("\3", 32)
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function u128(n) return Scale.encode_u64(n) .. string.rep("\0", 8) end
+local function record(index, event)
+    return "\0" .. Scale.encode_u32(index) .. event .. Scale.encode_compact(0)
+end
+local function transfer(from, to, amount)
+    return "\4\2" .. from .. to .. u128(amount)
+end
+local function slashed(staker, amount)
+    return "\7\1" .. staker .. u128(amount)
+end
+
+-- Chain mock: events[n] = { record }, finalized head at api.finalized
+local function chain_api(events, finalized)
+    local api = Mock.api()
+    api.finalized = finalized
+    function api:chain_getHeader(hash)
+        return { number = string.format("0x%x", self.finalized) }
+    end
+    function api:state_getStorage(key, at)
+        local n = tonumber(at:sub(3), 16)
+        if events[n] == "broken" then error("node went away") end
+        local list = events[n] or {}
+        return "0x" .. to_hex(Scale.encode_compact(#list) .. table.concat(list))
+    end
+    return api
+end
+
+local EVENTS = {
+    [3] = { record(0, transfer(ALICE.pubkey, BOB.pubkey, 10)), record(1, transfer(CHARLIE, CHARLIE, 1)) },
+    [4] = { record(0, slashed(BOB.pubkey, 50)) },
+    [6] = { record(2, transfer(CHARLIE, ALICE.pubkey, 7)) }
+}
+
+test("Indexer: sync stores transfers and events of watched accounts", function()
+    local api = chain_api(EVENTS, 6)
+    local indexer = Indexer.new(api, { addresses = { ALICE.address, "0x" .. to_hex(BOB.pubkey) },
+        store = Indexer.memory_store(), start_block = 1 })
+    assert(indexer:sync() == 6 and indexer:next_block() == 7)
+    assert(indexer:sync() == 0)
+
+    local alice = indexer:query_transfers(ALICE.address)
+    assert(#alice == 2)
+    assert(alice[1].block_number == 3 and alice[1].amount == "10" and alice[1].to == "0x" .. to_hex(BOB.pubkey))
+    assert(alice[2].block_number == 6 and alice[2].from == "0x" .. to_hex(CHARLIE) and alice[2].extrinsic_index == 2)
+    assert(#indexer:query_transfers(ALICE.address, { from_block = 4 }) == 1)
+    assert(#indexer:query_transfers(BOB.address, { to_block = 3 }) == 1)
+
+    local bob = indexer:query_events(BOB.address)
+    assert(#bob == 2 and bob[2].pallet == "Staking" and bob[2].name == "Slashed" and bob[2].fields.amount == "50")
+    -- Charlie is not watched: only the transfer with Alice was kept, not Charlie -> Charlie
+    assert(#indexer:query_transfers("0x" .. to_hex(CHARLIE)) == 1)
+end)
+
+test("Indexer: rows stay hex when the client renders SS58", function()
+    local api = chain_api(EVENTS, 6)
+    api.ss58_format = 0
+    local indexer = Indexer.new(api, { addresses = { ALICE.address }, store = Indexer.memory_store(), start_block = 1 })
+    indexer:sync()
+    local alice = indexer:query_transfers(ALICE.address)
+    assert(#alice == 2 and alice[1].from == "0x" .. to_hex(ALICE.pubkey) and alice[2].from == "0x" .. to_hex(CHARLIE))
+end)
+
+test("Indexer: batches and resumes from the stored block", function()
+    local api = chain_api(EVENTS, 4)
+    local store = Indexer.memory_store()
+    local indexer = Indexer.new(api, { addresses = { ALICE.address }, store = store, start_block = 1, batch_blocks = 2 })
+    assert(indexer:sync() == 2 and indexer:sync() == 2 and indexer:sync() == 0)
+    api.finalized = 6
+    indexer = Indexer.new(api, { addresses = { ALICE.address }, store = store, start_block = 1 })
+    assert(indexer:sync() == 2 and #indexer:query_transfers(ALICE.address) == 2)
+    assert(Indexer.new(chain_api({}, 9), { addresses = { BOB.address }, store = Indexer.memory_store() }):sync() == 1)
+end)
+
+test("Indexer: failed block is retried on the next sync", function()
+    local events = { [2] = "broken", [3] = EVENTS[3] }
+    local api = chain_api(events, 3)
+    local indexer = Indexer.new(api, { addresses = { ALICE.address }, store = Indexer.memory_store(), start_block = 1 })
+    local count, err = indexer:sync()
+    assert(count == nil and err:match("^Block 2: .*node went away") and indexer:next_block() == 2)
+    events[2] = nil
+    assert(indexer:sync() == 2 and #indexer:query_transfers(ALICE.address) == 1)
+end)
+
+test("Indexer: follow polls until stopped", function()
+    local api = chain_api(EVENTS, 3)
+    local slept, rounds = 0, 0
+    local indexer = Indexer.start(api, { addresses = { ALICE.address }, store = Indexer.memory_store(), start_block = 1 }, {
+        sleep = function() slept = slept + 1; api.finalized = 6 end,
+        stop = function() rounds = rounds + 1; return rounds == 2 end
+    })
+    assert(slept == 1 and indexer:next_block() == 7 and #indexer:query_transfers(ALICE.address) == 2)
+end)
+
+test("Indexer: SQLite store binds rows inside a transaction", function()
+    local log = {}
+    local db = {}
+    function db:exec(sql) table.insert(log, sql:match("^%s*(%u+)")); return 0 end
+    function db:errmsg() return "boom" end
+    function db:prepare(sql)
+        local stmt = {}
+        function stmt:bind_values(...) table.insert(log, { sql = sql, n = select("#", ...), ... }) end
+        function stmt:nrows() return function() end end
+        function stmt:finalize() end
+        return stmt
+    end
+    local store = Indexer.sqlite_store(db)
+    local indexer = Indexer.new(chain_api(EVENTS, 3), { addresses = { ALICE.address }, store = store, start_block = 3 })
+    assert(indexer:sync() == 1)
+    assert(log[1] == "CREATE" and log[3] == "BEGIN" and log[#log] == "COMMIT")
+    local insert
+    for _, entry in ipairs(log) do
+        if type(entry) == "table" and entry.sql:match("^INSERT OR REPLACE INTO transfers") then insert = entry end
+    end
+    assert(insert and insert[1] == 3 and insert[3] == 0 and insert[4] == 0 and insert[7] == "10")
+    assert(insert[5] == "0x" .. to_hex(ALICE.pubkey))
+
+    local ok, err = pcall(Indexer.new, chain_api({}, 1), { addresses = { ALICE.address }, db = "/nonexistent/x.sqlite" })
+    assert(ok or err:match("lsqlite3") or err:match("Cannot open"))
+end)
+
+test("Indexer: config checks", function()
+    assert(not pcall(Indexer.new, chain_api({}, 1), { addresses = {} , store = Indexer.memory_store() }))
+    assert(not pcall(Indexer.new, chain_api({}, 1), { addresses = { ALICE.address } }))
+    assert(not pcall(Indexer.new, chain_api({}, 1), { addresses = { "nope" }, store = Indexer.memory_store() }))
+end)
+
+print("\n=== Indexer Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Indexer tests passed!")
+    os.exit(0)
+end
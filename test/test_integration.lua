@@ -36,6 +36,23 @@ local function test(name, fn, requires_network)
     end
 end
 
+-- Tests against a local dev node (sublua.testchain): started from SUBLUA_TEST_NODE, or
+-- attached at SUBLUA_TEST_CHAIN_URL (a node started with its accounts endowed, as
+-- TestChain.start does); skipped when neither is set
+local chain
+local function chain_test(name, fn)
+    local node, url = os.getenv("SUBLUA_TEST_NODE"), os.getenv("SUBLUA_TEST_CHAIN_URL")
+    if not node and not url then
+        tests_skipped = tests_skipped + 1
+        print("⏭️  " .. name .. " (skipped - set SUBLUA_TEST_NODE or SUBLUA_TEST_CHAIN_URL)")
+        return
+    end
+    test(name, function()
+        chain = chain or require("sublua.testchain").start({ url = url })
+        fn(chain.api, chain.accounts)
+    end)
+end
+
 print("=== Integration Tests ===\n")
 print("Note: Network tests can be skipped with SKIP_NETWORK_TESTS=1\n")
 
@@ -182,6 +199,52 @@ test("Integration: Full transaction flow", function()
     assert(signed:match("^0x"))
 end, true)
 
+-- Local Chain Tests (end to end: sign, submit, wait for the block, read the outcome)
+local Call = require("sublua.call")
+local Utility = require("sublua.utility")
+
+local function free_of(api, who)
+    return api:system_account(who.address).data.free
+end
+
+chain_test("Integration: Local chain transfer is included and moves funds", function(api, accounts)
+    local alice, bob = accounts[1], accounts[2]
+    local before = free_of(api, bob)
+    local call = Call.encode_call_json(api, "Balances", "transfer_keep_alive", { dest = bob.address, value = "1000000000000" })
+    local hash, report = Transaction.submit(api, alice, call, { wait = "inclusion" })
+    assert(hash, report.error)
+    assert(report.result and report.result.success, "transfer failed in its block")
+    assert(free_of(api, bob) ~= before)
+end)
+
+chain_test("Integration: Local chain batch and back-to-back nonces", function(api, accounts)
+    local alice, charlie = accounts[1], accounts[3]
+    local remark = Call.encode_call(api, "System", "remark", { remark = "0x7375626c7561" })
+    local transfer = Call.encode_call_json(api, "Balances", "transfer_keep_alive", { dest = charlie.address, value = "1000000000000" })
+    local hashes, reports = Utility.submit_batch(api, alice, { remark, transfer }, { wait = "inclusion" })
+    assert(hashes, reports[#reports].error)
+    assert(reports[1].result.success)
+
+    local first = Transaction.submit(api, alice, remark)
+    local second, report = Transaction.submit(api, alice, remark, { wait = "inclusion" })
+    assert(first and second and first ~= second, report.error)
+    assert(report.result.success)
+end)
+
+chain_test("Integration: Local chain reports dispatch errors", function(api, accounts)
+    local bob, charlie = accounts[2], accounts[3]
+    local too_much = Call.encode_call_json(api, "Balances", "transfer_keep_alive",
+        { dest = charlie.address, value = "1000000000000000000000" })
+    local hash, report = Transaction.submit(api, bob, too_much, { wait = "inclusion" })
+    if hash then
+        assert(report.result.success == false and report.result.error.module.pallet == "Balances")
+    else
+        assert(report.error, "rejected by the pool instead")
+    end
+end)
+
+if chain then chain:stop() end
+
 print("\n=== Integration Test Results ===")
 print("Passed: " .. tests_passed)
 print("Failed: " .. tests_failed)
@@ -0,0 +1,105 @@
+-- test/test_introspect.lua
+-- Unit tests for live object accounting
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Introspect = require("sublua.introspect")
+local RPC = require("sublua.rpc")
+local Subscription = require("sublua.subscription")
+local Pump = require("sublua.pump")
+local Storage = require("sublua.storage")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Introspect Module Tests ===\n")
+
+local function stats()
+    return Introspect.debug_stats({ collect = true })
+end
+
+test("Introspect: Counts live objects until they are dropped", function()
+    local before = stats()
+    local clients = { RPC.new("http://localhost"), RPC.new("http://localhost") }
+    local sub = Subscription.new(Mock.api(), {})
+    local now = stats()
+    assert(now.clients == before.clients + 2 and now.subscriptions == before.subscriptions + 1)
+    assert(now.lua_memory_kb > 0)
+    clients, sub = nil, nil
+    local after = stats()
+    assert(after.clients == before.clients and after.subscriptions == before.subscriptions,
+        "collected objects are not counted")
+end)
+
+test("Introspect: Reports what objects hold", function()
+    local before = stats()
+    local pump = Pump.new()
+    pump:spawn(function() end)
+    pump:deliver(function() end)()
+    pump:deliver(function() end)()
+    local queue = Subscription.queue(Mock.api())
+    queue.state.events = { { seq = 1 }, { seq = 2 }, { seq = 3 } }
+    local reader = Storage.batch_reader(Mock.api(), "System", "Account")
+    reader:key("0x" .. string.rep("11", 32))
+    local now = stats()
+    assert(now.pumps == before.pumps + 1 and now.pump_tasks == before.pump_tasks + 1)
+    assert(now.pump_callbacks == before.pump_callbacks + 2)
+    assert(now.event_queues == before.event_queues + 1 and now.queued_events == before.queued_events + 3)
+    assert(now.storage_readers == before.storage_readers + 1)
+    assert(now.cached_storage_keys == before.cached_storage_keys + 1)
+    pump:pump()
+    assert(stats().pump_tasks == before.pump_tasks and stats().pump_callbacks == before.pump_callbacks)
+end)
+
+test("Introspect: Open metrics servers stand out until closed", function()
+    local socket = require("socket")
+    local original_bind = socket.bind
+    socket.bind = function(host, port)
+        return {
+            settimeout = function() end,
+            getsockname = function() return host, port end,
+            close = function() end
+        }
+    end
+    local Prometheus = require("sublua.prometheus")
+    local server = Prometheus.start_metrics_server("127.0.0.1:9464", {})
+    socket.bind = original_bind
+    local before = stats()
+    assert(before.metrics_servers >= 1 and before.open_metrics_servers >= 1)
+    server:close()
+    local after = stats()
+    assert(after.open_metrics_servers == before.open_metrics_servers - 1)
+    assert(after.metrics_servers == before.metrics_servers, "still referenced, but closed")
+end)
+
+test("Introspect: Tracking keeps nothing alive", function()
+    local kind_before = stats().test_objects or 0
+    Introspect.track("test_objects", {})
+    assert(stats().test_objects == kind_before)
+    local kept = Introspect.track("test_objects", {})
+    assert(stats().test_objects == kind_before + 1 and kept)
+end)
+
+print("\n=== Introspect Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All introspect tests passed!")
+    os.exit(0)
+end
@@ -138,6 +138,14 @@ test("Keyring: from_uri error on unsupported URI", function()
     assert(not ok)
 end)
 
+-- from_mnemonic Tests
+test("Keyring: from_mnemonic matches the Substrate seed of the phrase", function()
+    local pair = Keyring.from_mnemonic("bottom drive obey lake curtain smoke basket hold race lonely fit walk")
+    assert(to_hex(pair.seed) == "fac7959dbfe72f052e5a0c3c8d6530f202b02fd8f9f5ca3580ec8deb7797479e")
+    local ok, err = pcall(Keyring.from_mnemonic, "bottom drive obey lake curtain smoke basket hold race lonely fit fit")
+    assert(not ok and err:find("checksum"))
+end)
+
 -- Address generation with different SS58 versions
 test("Keyring: Address generation consistency", function()
     local seed = string.rep("a", 32)
@@ -167,6 +175,56 @@ test("Keyring: Public key matches C module", function()
     assert(keypair.pubkey == c_pubkey)
 end)
 
+-- Secure (handle-backed) pairs
+test("Keyring: Secure pair hides the seed", function()
+    local pair = Keyring.from_seed(string.rep("a", 32), { secure = true })
+    local plain = Keyring.from_seed(string.rep("a", 32))
+    assert(pair.seed == nil)
+    assert(pair.pubkey == plain.pubkey and pair.address == plain.address)
+    assert(pair:sign("msg") == plain:sign("msg"))
+end)
+
+test("Keyring: Secure pair cannot sign after destroy", function()
+    local pair = Keyring.from_uri("//Bob", { secure = true })
+    pair:destroy()
+    assert(not pcall(pair.sign, pair, "msg"))
+end)
+
+-- External signers
+test("Keyring: from_external accepts raw, hex and SS58 keys", function()
+    local local_pair = Keyring.from_seed(string.rep("a", 32))
+    local cb = function() return string.rep("\0", 64) end
+    local hex = "0x" .. (local_pair.pubkey:gsub(".", function(c) return string.format("%02x", c:byte()) end))
+    for _, key in ipairs({ local_pair.pubkey, hex, local_pair.address }) do
+        local ext = Keyring.from_external(key, cb)
+        assert(ext.pubkey == local_pair.pubkey)
+        assert(ext.address == local_pair.address)
+        assert(ext.seed == nil and ext.external == true)
+    end
+end)
+
+test("Keyring: from_external delegates signing to the callback", function()
+    local device = Keyring.from_seed(string.rep("d", 32))
+    local seen_payload, seen_data
+    local ext = Keyring.from_external(device.pubkey, function(payload, user_data)
+        seen_payload, seen_data = payload, user_data
+        return device:sign(payload)
+    end, { slot = 3 })
+    local sig = ext:sign("hello")
+    assert(seen_payload == "hello" and seen_data.slot == 3)
+    assert(crypto.ed25519_verify(device.pubkey, "hello", sig))
+end)
+
+test("Keyring: from_external validates signatures and schemes", function()
+    local pub = string.rep("\1", 32)
+    local ext = Keyring.from_external(pub, function() return "0x" .. string.rep("00", 10) end)
+    assert(not pcall(ext.sign, ext, "msg"))
+    ext = Keyring.from_external(pub, function() return nil end)
+    assert(not pcall(ext.sign, ext, "msg"))
+    assert(not pcall(Keyring.from_external, pub, function() end, nil, { scheme = "ecdsa" }))
+    assert(Keyring.from_external(pub, function() end, nil, { scheme = "sr25519" }).scheme == "sr25519")
+end)
+
 print("\n=== Keyring Test Results ===")
 print("Passed: " .. tests_passed)
 print("Failed: " .. tests_failed)
@@ -0,0 +1,253 @@
+-- test/test_keystore.lua
+-- Unit tests for BIP-39 mnemonics and the password-protected keystore
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local json = require("cjson")
+local crypto = require("polkadot_crypto")
+local Bip39 = require("sublua.bip39")
+local Keystore = require("sublua.keystore")
+local Keychain = require("sublua.keychain")
+local Keyring = require("sublua.keyring")
+local Transaction = require("sublua.transaction")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Keystore Tests ===\n")
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local DEV_PHRASE = "bottom drive obey lake curtain smoke basket hold race lonely fit walk"
+local DEV_SEED = "0xfac7959dbfe72f052e5a0c3c8d6530f202b02fd8f9f5ca3580ec8deb7797479e"
+-- Cheap Argon2 cost so the tests stay fast
+local CHEAP = { kib = 64, passes = 1 }
+
+local function temp_path()
+    local path = os.tmpname()
+    os.remove(path)
+    return path
+end
+
+test("Bip39: entropy, checksum and seed", function()
+    assert(to_hex(Bip39.to_entropy(DEV_PHRASE)) == "1a486a5fbe53639984cb64b070755f7b")
+    assert(Bip39.from_entropy(string.rep("\0", 16)) == string.rep("abandon ", 11) .. "about")
+    assert(Bip39.from_entropy(Bip39.to_entropy(DEV_PHRASE)) == DEV_PHRASE)
+    assert(Bip39.is_valid("  Bottom DRIVE obey lake curtain smoke basket hold race lonely fit walk "))
+    assert(not Bip39.is_valid(string.rep("abandon ", 12)))
+    local _, err = Bip39.to_entropy("bottom drive obey")
+    assert(err:find("12, 15, 18, 21 or 24"))
+    _, err = Bip39.to_entropy(DEV_PHRASE:gsub("walk", "walks"))
+    assert(err:find("Word 12"))
+    assert(to_hex(Bip39.to_seed(DEV_PHRASE)):sub(1, 64) == DEV_SEED:sub(3))
+end)
+
+test("Bip39: passphrase (substrate-bip39 vector)", function()
+    local zero = string.rep("abandon ", 11) .. "about"
+    assert(to_hex(Bip39.to_seed(zero, "Substrate")) == "44e9d125f037ac1d51f0a7d3649689d422c2af8b1ec8e00d71db4d7bf6d127e3"
+        .. "3f50c3d5c84fa3e5399c72d6cbbbbc4a49bf76f76d952f479d74655a2ef2d453")
+    assert(Bip39.to_seed(zero, "") == Bip39.to_seed(zero))
+    local plain = Keyring.from_mnemonic(DEV_PHRASE)
+    local protected = Keyring.from_mnemonic(DEV_PHRASE, { password = "25th word" })
+    assert(protected.address ~= plain.address)
+    assert(protected.seed == Bip39.to_seed(DEV_PHRASE, "25th word"):sub(1, 32))
+end)
+
+test("Keystore: add, list and sign by name", function()
+    local path = temp_path()
+    local store = Keystore.init(path, "correct horse", CHEAP)
+    local address = store:add("treasury", DEV_PHRASE)
+    store:add("bot", "0x" .. string.rep("11", 32))
+    assert(address == Keyring.from_seed(DEV_SEED).address)
+    assert(not pcall(store.add, store, "bot", DEV_PHRASE))
+    assert(not pcall(store.add, store, "bad name", DEV_PHRASE))
+    assert(not pcall(store.add, store, "typo", "bottom drive obey lake"))
+
+    local list = store:list()
+    assert(#list == 2 and list[1].name == "bot" and list[2].name == "treasury" and list[2].address == address)
+
+    local sig = store:sign("treasury", "0x" .. to_hex("hello"))
+    assert(crypto.ed25519_verify(Keyring.from_seed(DEV_SEED).pubkey, "hello", sig))
+    assert(store:keypair("bot").seed == nil)
+    assert(store:keypair("bot", {}).seed == string.rep("\17", 32))
+    assert(not pcall(store.sign, store, "nobody", "x"))
+
+    local text = io.open(path, "rb"):read("*a")
+    assert(not text:find("bottom") and not text:find(string.rep("11", 32)))
+    assert(store:remove("bot") and not store:remove("bot") and #store:list() == 1)
+    os.remove(path)
+end)
+
+test("Keystore: raw seeds containing spaces", function()
+    local path = temp_path()
+    local store = Keystore.init(path, "pw", CHEAP)
+    local seed = "seed with spaces" .. string.rep(" ", 16)
+    local address = store:add("spacey", seed)
+    assert(address == Keyring.from_seed(seed).address)
+    assert(not pcall(store.add, store, "pass", seed, "extra"), "a passphrase only applies to phrases")
+    store = Keystore.init(path, "pw")
+    assert(store:keypair("spacey", {}).seed == seed)
+    os.remove(path)
+end)
+
+test("Keystore: phrases with a BIP-39 passphrase", function()
+    local path = temp_path()
+    local store = Keystore.init(path, "pw", CHEAP)
+    local address = store:add("vault", DEV_PHRASE, "25th word")
+    assert(address == Keyring.from_mnemonic(DEV_PHRASE, { password = "25th word" }).address)
+    assert(Keystore.init(path, "pw"):keypair("vault").address == address)
+    assert(store:add("plain", DEV_PHRASE, "") == Keyring.from_mnemonic(DEV_PHRASE).address)
+    assert(not pcall(store.add, store, "seed", DEV_SEED, "25th word"))
+    os.remove(path)
+end)
+
+test("Keystore: reopening needs the master password", function()
+    local path = temp_path()
+    Keystore.init(path, "correct horse", CHEAP):add("treasury", DEV_PHRASE)
+    local ok, err = pcall(Keystore.init, path, "wrong", CHEAP)
+    assert(not ok and err:find("Wrong master password"))
+    local store = Keystore.init(path, "correct horse")
+    assert(store.state.kdf.kib == 64 and store:list()[1].name == "treasury")
+
+    -- A secret moved to another name does not decrypt
+    local state = json.decode(io.open(path, "rb"):read("*a"))
+    state.accounts.copy = state.accounts.treasury
+    local f = io.open(path, "wb")
+    f:write(json.encode(state))
+    f:close()
+    store = Keystore.init(path, "correct horse")
+    ok, err = pcall(store.keypair, store, "copy")
+    assert(not ok and err:find("does not decrypt"))
+    os.remove(path)
+end)
+
+test("Keystore: submit by account name", function()
+    local path = temp_path()
+    local store = Keystore.init(path, "pw", CHEAP)
+    store:add("bot", DEV_SEED)
+    local api = Mock.api()
+    api.submitted = {}
+    function api:author_submitExtrinsic(hex)
+        table.insert(self.submitted, hex)
+        return "0x" .. string.rep("ab", 32)
+    end
+    local hash = store:submit(api, "bot", "\0\0\8hi")
+    assert(hash == "0x" .. string.rep("ab", 32))
+    local decoded = Transaction.decode(api:get_metadata(), api.submitted[1])
+    assert(decoded.address == Keyring.from_seed(DEV_SEED).address and decoded.call.name == "remark")
+    os.remove(path)
+end)
+
+-- Keychain whose exec records commands and answers lookups from a table
+local function fake_keychain(platform)
+    local calls, stored = {}, {}
+    local chain = Keychain.new({ platform = platform, exec = function(cmd, input)
+        table.insert(calls, { cmd = cmd, input = input })
+        local account = cmd:match("%-a '([^']+)'") or cmd:match("account '([^']+)'") or cmd:match("','([^']+)'")
+            or (input and input:match("%-a (%S+)"))
+        if cmd:find("add%-generic") or (input and input:find("add%-generic")) then
+            stored[account] = input:match("%-w (%x+)")
+        elseif cmd:find("store") or cmd:find("%.Add") then
+            stored[account] = input:match("%x+")
+        elseif cmd:find("delete") or cmd:find("clear") or cmd:find("Remove") then
+            stored[account] = nil
+        else
+            return (stored[account] or "") .. "\n"
+        end
+        return ""
+    end })
+    return chain, calls, stored
+end
+
+test("Keychain: commands per platform keep values off the command line", function()
+    for _, platform in ipairs({ "macos", "libsecret", "windows" }) do
+        local chain, calls, stored = fake_keychain(platform)
+        chain:set("ks/bot", "abcd01")
+        assert(stored["ks/bot"] == "abcd01", platform)
+        assert(not calls[1].cmd:find("abcd01") and calls[1].input:find("abcd01"), platform)
+        assert(chain:get("ks/bot") == "abcd01" and not calls[2].input, platform)
+        chain:delete("ks/bot")
+        assert(chain:get("ks/bot") == nil, platform)
+    end
+    local chain = fake_keychain("libsecret")
+    assert(not pcall(chain.set, chain, "it's", "00"))
+    assert(not pcall(chain.set, chain, "ok", "not hex"))
+    assert(not pcall(Keychain.new, { platform = "amiga" }))
+end)
+
+test("Keystore: secrets in the OS keychain", function()
+    local path = temp_path()
+    local chain, _, stored = fake_keychain("libsecret")
+    local store = Keystore.init(path, "pw", { kib = 64, passes = 1, keychain = chain })
+    store:add("bot", DEV_SEED)
+    local entry = store.state.id .. "/bot"
+    assert(stored[entry] and store.state.accounts.bot.secret == nil and store:list()[1].keychain)
+    assert(not io.open(path, "rb"):read("*a"):find(stored[entry]))
+
+    store = Keystore.init(path, "pw", { keychain = chain })
+    assert(store:keypair("bot", {}).seed == Keyring.from_seed(DEV_SEED).seed)
+    -- The keychain entry is still sealed: the master password is needed
+    assert(not pcall(Keystore.init, path, "other", { keychain = chain }))
+    assert(store:remove("bot") and stored[entry] == nil)
+
+    store:add("gone", DEV_SEED)
+    stored[store.state.id .. "/gone"] = nil
+    local ok, err = pcall(store.keypair, store, "gone")
+    assert(not ok and err:find("missing from the libsecret keychain"))
+    os.remove(path)
+end)
+
+test("Keychain: failing commands are reported", function()
+    assert(Keychain.exec("cat > /dev/null", "abcd") == "")
+    assert(Keychain.exec("echo 00ff") == "00ff\n")
+    local out, err = Keychain.exec("cat > /dev/null; exit 3", "abcd")
+    assert(out == nil and err:find("exited with 3"))
+    assert(Keychain.exec("exit 1") == nil)
+
+    -- secret-tool without a session bus: it reads the secret, then fails
+    local chain = Keychain.new({ platform = "libsecret", exec = function(_, input)
+        return Keychain.exec("cat > /dev/null; exit 1", input)
+    end })
+    local ok
+    ok, err = pcall(chain.set, chain, "ks/bot", "abcd01")
+    assert(not ok and err:find("Cannot store ks/bot in the libsecret keychain"))
+
+    local path = temp_path()
+    local store = Keystore.init(path, "pw", { kib = 64, passes = 1, keychain = chain })
+    assert(not pcall(store.add, store, "bot", DEV_SEED))
+    -- A store that accepts the write but keeps nothing
+    local forgetful = fake_keychain("libsecret")
+    local set = forgetful.set
+    forgetful.set = function(self, account) return set(self, account, "00") end
+    store = Keystore.init(path, "pw", { keychain = forgetful })
+    ok, err = pcall(store.add, store, "bot", DEV_SEED)
+    assert(not ok and err:find("did not keep account 'bot'"))
+    assert(store.state.accounts.bot == nil and Keystore.init(path, "pw").state.accounts.bot == nil)
+    os.remove(path)
+end)
+
+print("\n=== Keystore Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Keystore tests passed!")
+    os.exit(0)
+end
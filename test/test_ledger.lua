@@ -0,0 +1,83 @@
+-- test/test_ledger.lua
+-- Unit tests for Ledger-compatible (BIP32-Ed25519) account derivation
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local crypto = require("polkadot_crypto")
+local Ledger = require("sublua.ledger")
+local Bip39 = require("sublua.bip39")
+local Keyring = require("sublua.keyring")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Ledger Derivation Tests ===\n")
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local PHRASE = "bottom drive obey lake curtain smoke basket hold race lonely fit walk"
+
+test("Ledger: standard BIP-39 seed of the words", function()
+    assert(to_hex(Bip39.to_bip39_seed(PHRASE)) == "02d5cd1db85b4d1397d78978062a1160e76e94cc5aaad3089644846865bb18fc"
+        .. "68ddf383059d3fe82902a203d60790a8c8ab488de5013d10a8a8bded8d9174b9")
+    assert(Bip39.to_bip39_seed("bottom drive") == nil)
+end)
+
+-- Seeds computed with an independent implementation of the polkadot.js hdLedger algorithm
+test("Ledger: seeds at the app's paths", function()
+    assert(Ledger.path() == "m/44'/354'/0'/0'/0'" and Ledger.path(1, 2, 434) == "m/44'/434'/1'/0'/2'")
+    assert(to_hex(Ledger.derive_seed(PHRASE, "m/44'/354'/0'/0'/0'"))
+        == "b8ee5019cf9d34355cb10ca235ee142ceccd6d5182a499d4a7fb085e9f39705e")
+    assert(to_hex(Ledger.derive_seed(PHRASE, "m/44'/354'/0'/0'/1'"))
+        == "30cf9a0048fdebff3e21962770ee5b6548a58504bdbbedc27cbd92109c39705e")
+    assert(to_hex(Ledger.derive_seed(PHRASE, "m/44'/434'/0'/0'/0'"))
+        == "50cfb928ebf0c380856e97e0a63f25ece28805df755b2a8a58da95b39839705e")
+    assert(to_hex(Ledger.derive_seed(PHRASE, "m/44'/354'/0'/0'/0'", "pass"))
+        == "e0c944de8444b36f3cb7bb0fe5c6d455e09433fa6501c1ecc205ba258c43f957")
+    -- A 25th word is the passphrase
+    local long = Bip39.from_entropy(string.rep("\1", 32))
+    assert(Ledger.derive_seed(long .. " pass", "m/44'/354'/0'/0'/0'") == Ledger.derive_seed(long, "m/44'/354'/0'/0'/0'", "pass"))
+    assert(Ledger.derive_seed(long, "m/44'/354'/0'/0'/0'") ~= Ledger.derive_seed(long, "m/44'/354'/0'/0'/0'", "pass"))
+    assert(not pcall(Ledger.derive_seed, PHRASE, "m/44'/354'/0/0'/0'"))
+    assert(not pcall(Ledger.derive_seed, PHRASE, "44'/354'"))
+end)
+
+test("Ledger: chain addresses and watch-only lists", function()
+    local pair = Ledger.from_mnemonic(PHRASE)
+    local seed = Ledger.derive_seed(PHRASE, "m/44'/354'/0'/0'/0'")
+    assert(pair.pubkey == Keyring.from_seed(seed).pubkey and pair.path == "m/44'/354'/0'/0'/0'")
+    assert(pair.address == crypto.ss58_encode(pair.pubkey, 0) and pair.address:sub(1, 1) == "1")
+    local ksm = Ledger.from_mnemonic(PHRASE, { chain = "kusama", secure = true })
+    assert(ksm.seed == nil and ksm.address == crypto.ss58_encode(crypto.ed25519_keypair_from_seed(
+        Ledger.derive_seed(PHRASE, "m/44'/434'/0'/0'/0'")), 2))
+    assert(not pcall(Ledger.from_mnemonic, PHRASE, { chain = "nowhere" }))
+
+    local list = Ledger.addresses(PHRASE, 2)
+    assert(#list == 2 and list[1].address == pair.address and list[2].path == "m/44'/354'/0'/0'/1'")
+    assert(list[2].pubkey == crypto.ed25519_keypair_from_seed(Ledger.derive_seed(PHRASE, list[2].path)))
+end)
+
+print("\n=== Ledger Derivation Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Ledger Derivation tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,205 @@
+-- test/test_locks.lua
+-- Unit tests for balance locks, freezes and conviction vote unlock blocks (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Locks = require("sublua.locks")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Locks Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+local BOB = "0x" .. to_hex(bob.pubkey)
+
+local function option(name, inner)
+    return { name, { path = { "Option" }, variant = {
+        { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = inner } } }
+    } } }
+end
+
+local CHAIN = {
+    types = {
+        { "[u8;8]", { array = "u8", len = 8 } },
+        { "Reasons", { path = { "pallet_balances", "types", "Reasons" }, variant = {
+            { name = "Fee", index = 0 }, { name = "Misc", index = 1 }, { name = "All", index = 2 }
+        } } },
+        { "BalanceLock", { path = { "pallet_balances", "types", "BalanceLock" }, composite = {
+            { name = "id", type = "[u8;8]" }, { name = "amount", type = "u128" }, { name = "reasons", type = "Reasons" }
+        } } },
+        { "Vec<BalanceLock>", { sequence = "BalanceLock" } },
+        { "PoolsFreezeReason", { path = { "pallet_nomination_pools", "pallet", "FreezeReason" }, variant = {
+            { name = "PoolMinBalance", index = 0 }
+        } } },
+        { "RuntimeFreezeReason", { path = { "runtime", "RuntimeFreezeReason" }, variant = {
+            { name = "NominationPools", index = 39, fields = { { type = "PoolsFreezeReason" } } }
+        } } },
+        { "IdAmount", { path = { "frame_support", "traits", "tokens", "misc", "IdAmount" }, composite = {
+            { name = "id", type = "RuntimeFreezeReason" }, { name = "amount", type = "u128" }
+        } } },
+        { "Vec<IdAmount>", { sequence = "IdAmount" } },
+        { "Vote", { path = { "pallet_conviction_voting", "vote", "Vote" }, composite = { { type = "u8" } } } },
+        { "AccountVote", { path = { "pallet_conviction_voting", "vote", "AccountVote" }, variant = {
+            { name = "Standard", index = 0, fields = { { name = "vote", type = "Vote" }, { name = "balance", type = "u128" } } },
+            { name = "Split", index = 1, fields = { { name = "aye", type = "u128" }, { name = "nay", type = "u128" } } },
+            { name = "SplitAbstain", index = 2, fields = {
+                { name = "aye", type = "u128" }, { name = "nay", type = "u128" }, { name = "abstain", type = "u128" } } }
+        } } },
+        { "(u32, AccountVote)", { tuple = { "u32", "AccountVote" } } },
+        { "Vec<(u32, AccountVote)>", { sequence = "(u32, AccountVote)" } },
+        { "Delegations", { path = { "pallet_conviction_voting", "types", "Delegations" }, composite = {
+            { name = "votes", type = "u128" }, { name = "capital", type = "u128" }
+        } } },
+        { "PriorLock", { path = { "pallet_conviction_voting", "vote", "PriorLock" }, composite = {
+            { type = "u32" }, { type = "u128" }
+        } } },
+        { "Conviction", { path = { "pallet_conviction_voting", "conviction", "Conviction" }, variant = {
+            { name = "None", index = 0 }, { name = "Locked1x", index = 1 }, { name = "Locked2x", index = 2 },
+            { name = "Locked3x", index = 3 }
+        } } },
+        { "Voting", { path = { "pallet_conviction_voting", "vote", "Voting" }, variant = {
+            { name = "Casting", index = 0, fields = {
+                { name = "votes", type = "Vec<(u32, AccountVote)>" }, { name = "delegations", type = "Delegations" },
+                { name = "prior", type = "PriorLock" } } },
+            { name = "Delegating", index = 1, fields = {
+                { name = "balance", type = "u128" }, { name = "target", type = "AccountId32" },
+                { name = "conviction", type = "Conviction" }, { name = "delegations", type = "Delegations" },
+                { name = "prior", type = "PriorLock" } } }
+        } } },
+        { "(AccountId32, u16)", { tuple = { "AccountId32", "u16" } } },
+        { "(u16, u128)", { tuple = { "u16", "u128" } } },
+        { "Vec<(u16, u128)>", { sequence = "(u16, u128)" } },
+        option("Option<u128>", "u128"),
+        { "ReferendumInfo", { path = { "pallet_referenda", "types", "ReferendumInfo" }, variant = {
+            { name = "Ongoing", index = 0, fields = { { type = "u32" } } },
+            { name = "Approved", index = 1, fields = { { type = "u32" }, { type = "Option<u128>" }, { type = "Option<u128>" } } },
+            { name = "Rejected", index = 2, fields = { { type = "u32" }, { type = "Option<u128>" }, { type = "Option<u128>" } } },
+            { name = "Killed", index = 5, fields = { { type = "u32" } } }
+        } } }
+    },
+    pallets = {
+        { name = "Balances", index = 4, storage = { prefix = "Balances", entries = {
+            { name = "Locks", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "Vec<BalanceLock>" },
+                modifier = 1, default = "\0" },
+            { name = "Freezes", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "Vec<IdAmount>" },
+                modifier = 1, default = "\0" }
+        } } },
+        { name = "ConvictionVoting", index = 20, storage = { prefix = "ConvictionVoting", entries = {
+            { name = "VotingFor", map = { hashers = { "Twox64Concat", "Twox64Concat" }, key = "(AccountId32, u16)", value = "Voting" } },
+            { name = "ClassLocksFor", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "Vec<(u16, u128)>" },
+                modifier = 1, default = "\0" }
+        } }, constants = { { name = "VoteLockingPeriod", type = "u32", value = Scale.encode_u32(100) } } },
+        { name = "Referenda", index = 21, storage = { prefix = "Referenda", entries = {
+            { name = "ReferendumInfoFor", map = { hashers = { "Blake2_128Concat" }, key = "u32", value = "ReferendumInfo" } }
+        } } }
+    }
+}
+
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local NO_DELEGATIONS = { votes = "0", capital = "0" }
+
+local function standard(aye, conviction, balance)
+    return { variant = "Standard", value = { vote = (aye and 128 or 0) + conviction, balance = balance } }
+end
+
+local function casting(votes, prior)
+    return { variant = "Casting", value = { votes = votes, delegations = NO_DELEGATIONS, prior = prior or { 0, "0" } } }
+end
+
+-- Block 1000; track 0 votes on finished referenda, track 1 on an ongoing one,
+-- track 2 is delegated and track 3 only has a prior lock left
+local function chain()
+    local api = Mock.api({}, CHAIN)
+    api.best_number = 1000
+    put(api, "Balances", "Locks", { ALICE }, {
+        { id = "0x" .. to_hex("pyconvot"), amount = "50", reasons = { variant = "All" } },
+        { id = "0x" .. to_hex("staking "), amount = "100", reasons = { variant = "Misc" } },
+        { id = "0x0102030405060708", amount = "1", reasons = { variant = "Fee" } }
+    })
+    put(api, "Balances", "Freezes", { ALICE }, { { id = { variant = "NominationPools", value = { variant = "PoolMinBalance" } }, amount = "7" } })
+    put(api, "ConvictionVoting", "ClassLocksFor", { ALICE }, { { 0, "50" }, { 1, "15" }, { 2, "40" }, { 3, "20" } })
+    put(api, "ConvictionVoting", "VotingFor", { ALICE, 0 }, casting({ { 1, standard(true, 2, "50") }, { 2, standard(false, 1, "30") } }))
+    put(api, "ConvictionVoting", "VotingFor", { ALICE, 1 }, casting({ { 3, { variant = "Split", value = { aye = "10", nay = "5" } } } }))
+    put(api, "ConvictionVoting", "VotingFor", { ALICE, 2 }, { variant = "Delegating", value = {
+        balance = "40", target = BOB, conviction = { variant = "Locked1x" }, delegations = NO_DELEGATIONS, prior = { 0, "0" } } })
+    put(api, "ConvictionVoting", "VotingFor", { ALICE, 3 }, casting({}, { 800, "20" }))
+    put(api, "Referenda", "ReferendumInfoFor", { 1 }, { variant = "Approved", value = { 900, { variant = "None" }, { variant = "None" } } })
+    put(api, "Referenda", "ReferendumInfoFor", { 2 }, { variant = "Approved", value = { 950, { variant = "None" }, { variant = "None" } } })
+    put(api, "Referenda", "ReferendumInfoFor", { 3 }, { variant = "Ongoing", value = 0 })
+    return api
+end
+
+test("Locks: conviction vote unlock blocks per track", function()
+    local classes = Locks.conviction_unlocks(chain(), alice.address)
+    assert(#classes == 4)
+    local winning, losing = classes[1].votes[1], classes[1].votes[2]
+    assert(winning.aye and winning.conviction == "Locked2x" and winning.unlock_at == 1100, "900 + 2 periods of 100")
+    assert(not losing.aye and losing.conviction == "Locked1x" and losing.unlock_at == 950, "losing side: no conviction lock")
+    assert(classes[1].unlock_at == 1100 and classes[1].locked == "50")
+    assert(classes[2].unlock_at == nil and classes[2].votes[1].ongoing and classes[2].votes[1].amount == "15")
+    assert(classes[3].unlock_at == nil and classes[3].delegating.target == BOB and classes[3].delegating.conviction == "Locked1x")
+    assert(classes[4].unlock_at == 800 and classes[4].prior.amount == "20" and #classes[4].votes == 0)
+    assert(Locks.conviction_unlocks(Mock.api(), ALICE) == nil, "runtime without ConvictionVoting")
+end)
+
+test("Locks: query_locks names locks and freezes", function()
+    local result = Locks.query_locks(chain(), ALICE)
+    assert(result.block_number == 1000 and #result.locks == 3 and #result.freezes == 1)
+    local convot, staking, unknown = result.locks[1], result.locks[2], result.locks[3]
+    assert(convot.id == "pyconvot" and convot.name == "Conviction voting" and convot.amount == "50" and convot.reasons == "All")
+    assert(convot.unlock_at == nil and convot.unlockable == false, "a track is still locked by an ongoing vote")
+    assert(staking.name == "Staking" and staking.reasons == "Misc" and staking.unlock_at == nil)
+    assert(unknown.id == "0x0102030405060708")
+    assert(result.freezes[1].id == "NominationPools.PoolMinBalance" and result.freezes[1].amount == "7")
+    assert(result.conviction[4].unlockable and not result.conviction[1].unlockable)
+
+    local api = chain()
+    api.best_number = 1200
+    put(api, "Referenda", "ReferendumInfoFor", { 3 }, { variant = "Rejected", value = { 1050, { variant = "None" }, { variant = "None" } } })
+    put(api, "ConvictionVoting", "VotingFor", { ALICE, 2 }, casting({}))
+    convot = Locks.query_locks(api, ALICE).locks[1]
+    assert(convot.unlock_at == 1100 and convot.unlockable)
+    local ok, err = pcall(Locks.query_locks, api, "nobody")
+    assert(not ok and err:match("^who: expected"))
+end)
+
+print("\n=== Locks Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All locks tests passed!")
+    os.exit(0)
+end
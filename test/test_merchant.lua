@@ -0,0 +1,176 @@
+-- test/test_merchant.lua
+-- Unit tests for merchant deposit addresses, payment tracking and sweeps
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Merchant = require("sublua.merchant")
+local Keyring = require("sublua.keyring")
+local Storage = require("sublua.storage")
+local Transaction = require("sublua.transaction")
+local Utility = require("sublua.utility")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Merchant Tests ===\n")
+
+local WALLET = Keyring.from_uri("//Alice")
+local COLD = Keyring.from_uri("//Bob")
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+local UTILITY = {
+    types = {
+        { "Vec<RuntimeCall>", { sequence = "RuntimeCall" } },
+        { "UtilityCall", { path = { "pallet_utility", "pallet", "Call" }, variant = {
+            { name = "as_derivative", index = 1, fields = { { name = "index", type = "u16" }, { name = "call", type = "RuntimeCall" } } },
+            { name = "batch_all", index = 2, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } }
+        } } }
+    },
+    pallets = { { name = "Utility", index = 26, calls = "UtilityCall" } }
+}
+
+-- Chain mock with a finalized head at api.finalized and free balances per pubkey
+local function merchant_api()
+    local api = Mock.api({}, UTILITY)
+    api.finalized = 100
+    api.submitted = {}
+    function api:chain_getHeader() return { number = string.format("0x%x", self.finalized) } end
+    function api:author_submitExtrinsic(hex)
+        if self.reject then error("1010: Invalid Transaction") end
+        table.insert(self.submitted, hex)
+        return "0x" .. string.rep("cd", 32)
+    end
+    function api:fund(pubkey, free)
+        local key = Storage.build_key(self, "System", "Account", { "0x" .. to_hex(pubkey) })
+        self.storage[key] = "0x" .. string.rep("00", 16) .. to_hex(Scale.encode_u64(free) .. string.rep("\0", 8))
+            .. string.rep("00", 48)
+    end
+    return api
+end
+
+local function open(api, extra)
+    local opts = { wallet = WALLET.address, cold_address = COLD.address, confirmations = 3 }
+    for k, v in pairs(extra or {}) do opts[k] = v end
+    return Merchant.new(api, opts)
+end
+
+test("Merchant: orders get their own derivative addresses", function()
+    local merchant = open(merchant_api())
+    local a = merchant:create_order("A-1", 1000)
+    local b = merchant:create_order("A-2", "2000")
+    assert(a.index == 1 and b.index == 2 and a.status == "pending" and b.amount == "2000")
+    assert(a.address == Utility.derive_sub_account(WALLET.address, 1))
+    assert(b.address == Utility.derive_sub_account(WALLET.address, 2))
+    assert(not pcall(merchant.create_order, merchant, "A-1", 5))
+    assert(not pcall(merchant.create_order, merchant, "A-3", "1.5"))
+    assert(open(merchant_api(), { first_index = 500 }):create_order("x", 1).index == 500)
+end)
+
+test("Merchant: payment moves through partial, paid and confirmed", function()
+    local api = merchant_api()
+    local merchant = open(api)
+    local order = merchant:create_order("A-1", 1000)
+    local _, pubkey = Utility.derive_sub_account(WALLET.address, order.index)
+
+    assert(merchant:poll_order("A-1").status == "pending")
+    api:fund(pubkey, 400)
+    assert(merchant:poll_order("A-1").status == "partial" and order.received == "400")
+    api:fund(pubkey, 1000)
+    assert(merchant:poll_order("A-1").status == "paid" and order.paid_block == 100)
+    api.finalized = 101
+    assert(merchant:poll_order("A-1").status == "paid")
+    api.finalized = 102
+    assert(merchant:poll_order("A-1").status == "confirmed" and order.confirmed_block == 102)
+    local missing, err = merchant:poll_order("nope")
+    assert(missing == nil and err:match("Unknown order"))
+end)
+
+test("Merchant: unpaid orders expire", function()
+    local now = 1000
+    local api = merchant_api()
+    local merchant = open(api, { clock = function() return now end })
+    merchant:create_order("A-1", 1000, { expires_in = 60 })
+    assert(merchant:poll_order("A-1").status == "pending")
+    now = 1060
+    assert(merchant:poll_order("A-1").status == "expired")
+    local changed = merchant:poll_open()
+    assert(#changed == 0)
+end)
+
+test("Merchant: sweep batches confirmed orders to the cold address", function()
+    local api = merchant_api()
+    local merchant = open(api, { confirmations = 1 })
+    for i, id in ipairs({ "A-1", "A-2", "A-3" }) do
+        merchant:create_order(id, 100)
+        if i ~= 2 then api:fund(select(2, Utility.derive_sub_account(WALLET.address, i)), 100) end
+    end
+    local changed = merchant:poll_open()
+    assert(#changed == 2 and changed[1].id == "A-1" and changed[2].id == "A-3")
+    assert(not pcall(merchant.sweep, merchant, COLD))
+
+    local result = merchant:sweep(WALLET, { nonce = 7 })
+    assert(result.hash == "0x" .. string.rep("cd", 32) and #result.orders == 2 and result.orders[2] == "A-3")
+    assert(merchant:get_order("A-1").status == "swept" and merchant:get_order("A-2").status == "pending")
+
+    -- Utility.batch_all([as_derivative(1, transfer_all(cold, false)), as_derivative(3, ...)])
+    local transfer_all = "0404" .. "00" .. to_hex(COLD.pubkey) .. "00"
+    local call = "1a02" .. "08" .. "1a01" .. "0100" .. transfer_all .. "1a01" .. "0300" .. transfer_all
+    assert(api.submitted[1] == result.extrinsic and result.extrinsic:sub(-#call) == call)
+    assert(result.extrinsic == Transaction.create_signed_from_api(api, WALLET, from_hex(call), { nonce = 7 }))
+    assert(merchant:sweep(WALLET) == nil)
+end)
+
+test("Merchant: failed sweep keeps orders confirmed; state persists", function()
+    local path = os.tmpname()
+    os.remove(path)
+    local api = merchant_api()
+    local merchant = open(api, { confirmations = 1, state_file = path })
+    local order = merchant:create_order("A-1", 100)
+    api:fund(select(2, Utility.derive_sub_account(WALLET.address, order.index)), 150)
+    merchant:poll_order("A-1")
+    api.reject = true
+    local result, err = merchant:sweep(WALLET)
+    assert(result == nil and err:match("Invalid Transaction"))
+
+    local reopened = open(api, { state_file = path })
+    assert(reopened:get_order("A-1").status == "confirmed" and reopened:get_order("A-1").received == "150")
+    assert(reopened:create_order("A-2", 1).index == 2)
+    os.remove(path)
+end)
+
+test("Merchant: config checks", function()
+    assert(not pcall(Merchant.new, merchant_api(), { wallet = WALLET.address }))
+    assert(not pcall(Merchant.new, merchant_api(), { wallet = "nope", cold_address = COLD.address }))
+end)
+
+print("\n=== Merchant Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Merchant tests passed!")
+    os.exit(0)
+end
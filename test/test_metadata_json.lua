@@ -0,0 +1,128 @@
+-- test/test_metadata_json.lua
+-- Unit tests for the metadata JSON export
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local json = require("cjson")
+local MetadataJson = require("sublua.metadata_json")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Metadata JSON Tests ===\n")
+
+local function by_name(items, name)
+    for _, item in ipairs(items) do
+        if item.name == name then return item end
+    end
+end
+
+local function type_by_path(doc, path)
+    for _, t in ipairs(doc.types) do
+        if t.path == path then return t end
+    end
+end
+
+local function write(path, content)
+    local f = assert(io.open(path, "wb"))
+    f:write(content)
+    f:close()
+end
+
+test("MetadataJson: document covers pallets, types and extrinsic", function()
+    local doc = MetadataJson.document(Mock.metadata())
+    assert(doc.version == 14)
+    assert(doc.pallets[1].name == "System" and doc.pallets[2].name == "Balances")
+
+    local balances = doc.pallets[2]
+    local transfer = by_name(balances.calls.items, "transfer_keep_alive")
+    assert(transfer.index == 3 and transfer.args[1].name == "dest" and transfer.args[2].type_name == "Compact<u128>")
+    assert(by_name(balances.events.items, "Transfer").fields[3].name == "amount")
+    assert(by_name(balances.errors.items, "InsufficientBalance").docs[1] == "Balance too low to send value.")
+    local ed = by_name(balances.constants, "ExistentialDeposit")
+    assert(ed.type_name == "u128" and ed.decoded == "10000000000")
+
+    local account = by_name(doc.pallets[1].storage.entries, "Account")
+    assert(account.kind == "Map" and account.hashers[1] == "Blake2_128Concat")
+    assert(account.key_name == "AccountId32" and account.value_name == "AccountInfo")
+
+    local ext = doc.extrinsic.signed_extensions
+    assert(#ext == 9 and ext[5].identifier == "CheckMortality")
+    assert(#doc.types == #Mock.metadata().types + 1)
+    assert(type_by_path(doc, "sp_runtime::DispatchError").def.variant.variants[4].name == "Module")
+end)
+
+test("MetadataJson: readable names for anonymous and generic types", function()
+    local doc = MetadataJson.document(Mock.metadata())
+    local names = {}
+    for _, t in ipairs(doc.types) do names[t.name] = true end
+    assert(names["Vec<u8>"] and names["[u8; 32]"] and names["Compact<u128>"] and names["()"])
+    assert(names["UncheckedExtrinsic<MultiAddress, RuntimeCall, MultiSignature, ()>"])
+end)
+
+test("MetadataJson: pallet filter keeps reachable types only", function()
+    local doc = MetadataJson.document(Mock.metadata(), "Balances")
+    assert(#doc.pallets == 1 and doc.pallets[1].name == "Balances")
+    assert(type_by_path(doc, "sp_runtime::multiaddress::MultiAddress"))
+    assert(type_by_path(doc, "sp_core::crypto::AccountId32"))
+    assert(not type_by_path(doc, "pallet_staking::pallet::Event"))
+    assert(not type_by_path(doc, "frame_system::AccountInfo"))
+
+    doc = MetadataJson.document(Mock.metadata(), { "System", "Staking" })
+    assert(#doc.pallets == 2 and type_by_path(doc, "pallet_staking::StakingLedger"))
+    local ok, err = pcall(MetadataJson.document, Mock.metadata(), "Nope")
+    assert(not ok and err:find("Pallet not found: Nope"))
+end)
+
+test("MetadataJson: loads hex, JSON-RPC and binary files", function()
+    local path = os.tmpname()
+    local hex = Mock.hex()
+    write(path, hex .. "\n")
+    assert(MetadataJson.load(path).pallets.Balances)
+    write(path, json.encode({ jsonrpc = "2.0", id = 1, result = hex }))
+    assert(MetadataJson.load(path).pallets.Staking)
+    write(path, (hex:gsub("^0x", ""):gsub("..", function(cc) return string.char(tonumber(cc, 16)) end)))
+    assert(MetadataJson.load(path).pallets.System)
+    write(path, "not metadata")
+    local ok, err = pcall(MetadataJson.load, path)
+    assert(not ok and err:find("Unrecognised metadata file format"))
+    os.remove(path)
+    ok, err = pcall(MetadataJson.load, "/nonexistent/metadata.scale")
+    assert(not ok and err:find("Cannot open metadata file"))
+end)
+
+test("MetadataJson: export renders JSON and writes files", function()
+    local path = os.tmpname()
+    local text = MetadataJson.export(Mock.api(), "System", { file = path })
+    local decoded = json.decode(text)
+    assert(#decoded.pallets == 1 and decoded.pallets[1].name == "System")
+    assert(by_name(decoded.pallets[1].constants, "SS58Prefix").decoded == 42)
+    local f = assert(io.open(path, "rb"))
+    assert(f:read("*a") == text)
+    f:close()
+    os.remove(path)
+end)
+
+print("\n=== Metadata JSON Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All metadata JSON tests passed!")
+    os.exit(0)
+end
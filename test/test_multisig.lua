@@ -0,0 +1,228 @@
+-- test/test_multisig.lua
+-- Unit tests for multisig accounts, calls and the session coordinator
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Multisig = require("sublua.multisig")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Address = require("sublua.address")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    return (hex:gsub("^0x", ""):gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+print("=== Multisig Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local charlie = Keyring.from_uri("//Charlie")
+local dave = Keyring.from_seed(string.rep("d", 32))
+local function key(pair) return "0x" .. to_hex(pair.pubkey) end
+
+local FIXTURE = {
+    types = {
+        { "Timepoint", { path = { "pallet_multisig", "Timepoint" }, composite = {
+            { name = "height", type = "u32" }, { name = "index", type = "u32" } } } },
+        { "Option<Timepoint>", { path = { "Option" }, variant = {
+            { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = "Timepoint" } } }
+        } } },
+        { "MultisigEntry", { path = { "pallet_multisig", "Multisig" }, composite = {
+            { name = "when", type = "Timepoint" }, { name = "deposit", type = "u128" },
+            { name = "depositor", type = "AccountId32" }, { name = "approvals", type = "Vec<AccountId32>" } } } },
+        { "(AccountId32, [u8;32])", { tuple = { "AccountId32", "[u8;32]" } } },
+        { "MultisigCall", { path = { "pallet_multisig", "pallet", "Call" }, variant = {
+            { name = "as_multi_threshold_1", index = 0, fields = {
+                { name = "other_signatories", type = "Vec<AccountId32>" }, { name = "call", type = "RuntimeCall" } } },
+            { name = "as_multi", index = 1, fields = {
+                { name = "threshold", type = "u16" }, { name = "other_signatories", type = "Vec<AccountId32>" },
+                { name = "maybe_timepoint", type = "Option<Timepoint>" }, { name = "call", type = "RuntimeCall" },
+                { name = "max_weight", type = "Weight" } } },
+            { name = "approve_as_multi", index = 2, fields = {
+                { name = "threshold", type = "u16" }, { name = "other_signatories", type = "Vec<AccountId32>" },
+                { name = "maybe_timepoint", type = "Option<Timepoint>" }, { name = "call_hash", type = "[u8;32]" },
+                { name = "max_weight", type = "Weight" } } },
+            { name = "cancel_as_multi", index = 3, fields = {
+                { name = "threshold", type = "u16" }, { name = "other_signatories", type = "Vec<AccountId32>" },
+                { name = "timepoint", type = "Timepoint" }, { name = "call_hash", type = "[u8;32]" } } }
+        } } },
+        { "Vec<RuntimeCall>", { sequence = "RuntimeCall" } },
+        { "UtilityCall", { path = { "pallet_utility", "pallet", "Call" }, variant = {
+            { name = "batch", index = 0, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } },
+            { name = "batch_all", index = 2, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } }
+        } } }
+    },
+    pallets = {
+        { name = "Utility", index = 26, calls = "UtilityCall" },
+        { name = "Multisig", index = 31, calls = "MultisigCall", storage = { prefix = "Multisig", entries = {
+            { name = "Multisigs", map = { hashers = { "Twox64Concat", "Blake2_128Concat" },
+                key = "(AccountId32, [u8;32])", value = "MultisigEntry" } }
+        } } }
+    }
+}
+
+local CALL = Call.encode_call(Mock.metadata(), "System", "remark", { remark = "0x" .. to_hex("pay the team") })
+local WEIGHT = { ref_time = 1000000, proof_size = 2000 }
+
+local function open(api, session, approvals)
+    local meta = api:get_metadata()
+    local entry = meta.pallets["Multisig"].storage.entries["Multisigs"]
+    local s = session.state
+    api.storage[Storage.build_key(meta, "Multisig", "Multisigs", { Address.convert(s.multisig, "hex"), s.call_hash })] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, {
+            when = { height = 120, index = 2 }, deposit = "20000", depositor = approvals[1], approvals = approvals }))
+end
+
+local function decode(api, signed)
+    return Transaction.decode(api:get_metadata(), signed).call
+end
+
+test("Multisig: account id matches the pallet derivation", function()
+    local address, pubkey = Multisig.account_id({ bob.address, alice.address, key(charlie) }, 2)
+    local sorted = Multisig.sort_signatories({ key(charlie), alice.address, bob.address, alice.address })
+    assert(#sorted == 3 and sorted[1] < sorted[2] and sorted[2] < sorted[3], "sorted and de-duplicated")
+    local raw = "modlpy/utilisuba\12"
+    for _, k in ipairs(sorted) do raw = raw .. from_hex(k) end
+    assert(pubkey == crypto.blake2b(raw .. "\2\0", 32))
+    assert(address == Address.encode(pubkey, 42))
+    local ok, err = pcall(Multisig.account_id, { alice.address }, 2)
+    assert(not ok and err == "threshold: expected an integer from 1 to 1, got number 2", err)
+    ok, err = pcall(Multisig.sort_signatories, { alice.address, "5Typo" })
+    assert(not ok and err:match("^signatories%.2: expected an SS58 address or 0x public key, got '5Typo' %("), err)
+    ok, err = pcall(Multisig.account_id, { alice.address, nil, bob.address }, 1)
+    assert(not ok and err:match("^signatories: expected an array of signatories"), "a hole is not skipped")
+end)
+
+test("Multisig: session walks approve, approve, execute", function()
+    local api = Mock.api({}, FIXTURE)
+    local path = os.tmpname()
+    local session = Multisig.session_create(CALL, { alice.address, bob.address, charlie.address }, 3,
+        { path = path, max_weight = WEIGHT })
+    assert(session:status(api).status == "new")
+    assert(session:next_action(alice.address).action == "approve")
+    assert(session:next_action(dave.address).action == "none")
+
+    local signed, details = session:act(api, alice)
+    local call = decode(api, signed)
+    assert(details.action == "approve" and call.name == "approve_as_multi")
+    assert(call.args.maybe_timepoint.variant == "None" and call.args.call_hash == session.state.call_hash)
+    assert(#call.args.other_signatories == 2 and call.args.threshold == 3)
+
+    open(api, session, { key(alice) })
+    local st = session:status(api)
+    assert(st.status == "collecting" and st.remaining == 2 and st.timepoint.height == 120 and st.deposit == "20000")
+    assert(session:next_action(alice.address).action == "wait")
+    call = decode(api, (session:act(api, bob)))
+    assert(call.name == "approve_as_multi" and call.args.maybe_timepoint.value.index == 2)
+
+    open(api, session, { key(alice), key(bob) })
+    assert(session:status(api).status == "ready")
+    local saved = Multisig.session_load(path)
+    assert(saved:next_action(charlie.address).action == "execute", "state persisted")
+    assert(saved.state.timepoint.height == 120 and saved.state.depositor == key(alice))
+    signed, details = saved:act(api, charlie)
+    call = decode(api, signed)
+    assert(details.action == "execute" and call.name == "as_multi")
+    assert(call.args.call.variant == "System" and call.args.call.value.variant == "remark")
+    assert(tonumber(call.args.max_weight.ref_time) == WEIGHT.ref_time)
+    assert(call.args.maybe_timepoint.value.height == 120)
+
+    api.storage = {}
+    assert(saved:status(api).status == "closed" and saved:next_action(alice.address).action == "done")
+    assert(not pcall(saved.act, saved, api, alice), "nothing left to sign")
+    os.remove(path)
+end)
+
+test("Multisig: threshold 1 executes directly", function()
+    local api = Mock.api({}, FIXTURE)
+    local session = Multisig.session_create("0x" .. to_hex(CALL), { alice.address, bob.address }, 1)
+    assert(session:status(api).status == "ready")
+    local call = decode(api, (session:act(api, bob)))
+    assert(call.name == "as_multi_threshold_1" and #call.args.other_signatories == 1)
+    assert(call.args.other_signatories[1] == key(alice))
+end)
+
+test("Multisig: 1-of-N dispatch with batching", function()
+    local api = Mock.api({}, FIXTURE)
+    local signatories = { alice.address, bob.address, charlie.address }
+    local signed, details = Multisig.as_multi_threshold_1(api, bob, signatories, CALL)
+    local call = decode(api, signed)
+    assert(call.name == "as_multi_threshold_1" and #call.args.other_signatories == 2)
+    assert(call.args.call.value.variant == "remark")
+    assert(details.multisig == Multisig.account_id(signatories, 1))
+
+    local meta = api:get_metadata()
+    local second = Call.encode_call(meta, "System", "remark", { remark = "0x" .. to_hex("and the auditors") })
+    local batched = Multisig.encode_threshold_1(meta, bob.address, signatories, { CALL, "0x" .. to_hex(second) })
+    local expected = Multisig.encode_as_multi_threshold_1(meta, Multisig.sort_signatories({ alice.address, charlie.address }),
+        "\26\2\8" .. CALL .. second)
+    assert(batched == expected, "batch_all of both calls")
+    assert(Multisig.encode_threshold_1(meta, bob.address, signatories, { CALL }, "batch")
+        == Multisig.encode_threshold_1(meta, bob.address, signatories, CALL), "a single call is not batched")
+    assert(Multisig.encode_threshold_1(meta, bob.address, signatories, { CALL, second }, "batch"):find("\26\0\8", 1, true))
+    assert(not pcall(Multisig.as_multi_threshold_1, api, dave, signatories, CALL), "not a signatory")
+    assert(not pcall(Multisig.encode_threshold_1, meta, bob.address, signatories, {}))
+end)
+
+test("Multisig: depositor cancels", function()
+    local api = Mock.api({}, FIXTURE)
+    local session = Multisig.session_create(CALL, { alice.address, bob.address }, 2)
+    assert(not pcall(session.cancel, session, api, alice), "not open yet")
+    open(api, session, { key(alice) })
+    session:status(api)
+    local call = decode(api, (session:cancel(api, alice)))
+    assert(call.name == "cancel_as_multi" and call.args.timepoint.height == 120)
+    assert(call.args.call_hash == session.state.call_hash)
+end)
+
+test("Multisig: accounts in the client's SS58 format", function()
+    local api = Mock.api({}, FIXTURE)
+    local session = Multisig.session_create(CALL, { alice.address, bob.address }, 2)
+    open(api, session, { key(alice) })
+    assert(session:status(api).depositor == key(alice))
+    api.ss58_format = 0
+    local st = session:status(api)
+    assert(st.depositor == Address.encode(alice.pubkey, 0) and st.approvals[1] == st.depositor)
+    assert(st.multisig == Address.convert(session.state.multisig, 0))
+    assert(session.state.depositor == key(alice), "saved state stays hex")
+    assert(session:next_action(alice.address).action == "wait")
+    local open_entry = Multisig.query(api, session.state.multisig, session.state.call_hash)
+    assert(open_entry.approvals[1] == Address.encode(alice.pubkey, 0))
+    local _, details = Multisig.as_multi_threshold_1(api, alice, { alice.address, bob.address }, CALL)
+    assert(details.multisig == Multisig.account_id({ alice.address, bob.address }, 1, 0))
+end)
+
+print("\n=== Multisig Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All multisig tests passed!")
+    os.exit(0)
+end
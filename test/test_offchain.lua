@@ -0,0 +1,120 @@
+-- test/test_offchain.lua
+-- Unit tests for offchain local storage and statement store helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Offchain = require("sublua.offchain")
+local RPC = require("sublua.rpc")
+local Keyring = require("sublua.keyring")
+local crypto = require("polkadot_crypto")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Offchain Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local TOPIC = "0x" .. string.rep("0a", 32)
+local CHANNEL = "0x" .. string.rep("0c", 32)
+
+-- Node double: local storage in a table, statements in a list
+local function node()
+    local api = { kv = {}, statements = {}, calls = {} }
+    function api:offchain_localStorageGet(kind, key)
+        table.insert(self.calls, { "get", kind, key })
+        return self.kv[kind .. key]
+    end
+    function api:offchain_localStorageSet(kind, key, value)
+        table.insert(self.calls, { "set", kind, key, value })
+        self.kv[kind .. key] = value
+    end
+    function api:statement_submit(encoded)
+        table.insert(self.statements, encoded)
+        return { status = "new" }
+    end
+    function api:statement_broadcasts() return self.statements end
+    return api
+end
+
+test("Offchain: local storage keys and values as hex", function()
+    local api = node()
+    Offchain.set(api, "my-pallet::last-run", "0x2a000000")
+    assert(api.calls[1][2] == "PERSISTENT" and api.calls[1][3] == "0x" .. to_hex("my-pallet::last-run"))
+    assert(Offchain.get(api, "my-pallet::last-run") == "0x2a000000")
+    assert(Offchain.get(api, "my-pallet::last-run", Offchain.LOCAL) == nil, "kinds are separate")
+    Offchain.set(api, "0xabcd", "raw", Offchain.LOCAL)
+    assert(api.kv["LOCAL0xabcd"] == "0x" .. to_hex("raw"))
+end)
+
+test("Offchain: unsafe RPCs need allow_unsafe", function()
+    local api = RPC.new("http://localhost:9944")
+    local ok, err = pcall(api.offchain_localStorageGet, api, "PERSISTENT", "0x00")
+    assert(not ok and tostring(err):match("allow_unsafe"))
+    assert(not pcall(api.offchain_localStorageSet, api, "PERSISTENT", "0x00", "0x01"))
+    assert(not pcall(api.statement_dump, api))
+    assert(not pcall(api.statement_remove, api, "0x00"))
+end)
+
+test("Offchain: unsigned statement encoding", function()
+    local hex = Offchain.encode_statement({ priority = 5, topics = { TOPIC }, data = "hi" })
+    -- 3 fields: Priority(5), Topic1, Data("hi")
+    assert(hex == "0x0c" .. "0205000000" .. "04" .. string.rep("0a", 32) .. "0808" .. to_hex("hi"))
+    local s = Offchain.decode_statement(hex)
+    assert(s.priority == 5 and s.topics[1] == TOPIC and s.data == "0x" .. to_hex("hi") and s.proof == nil)
+    assert(s.hash == "0x" .. to_hex(crypto.blake2b(string.char(12, 2, 5, 0, 0, 0, 4) .. string.rep("\10", 32)
+        .. "\8\8hi", 32)))
+end)
+
+test("Offchain: signed statement round trip and proof check", function()
+    local hex = Offchain.encode_statement({ channel = CHANNEL, topics = { TOPIC, TOPIC }, data = "0x0102" }, alice)
+    local s = Offchain.decode_statement(hex)
+    assert(s.proof.type == "Ed25519" and s.proof.signer == "0x" .. to_hex(alice.pubkey))
+    assert(s.channel == CHANNEL and #s.topics == 2 and s.data == "0x0102")
+    assert(Offchain.verify_statement(hex))
+    local tampered = hex:sub(1, -3) .. "03"
+    assert(not Offchain.verify_statement(tampered))
+    assert(not Offchain.verify_statement(Offchain.encode_statement({ data = "x" })), "unsigned")
+end)
+
+test("Offchain: submit and query statements", function()
+    local api = node()
+    local result, hash = Offchain.submit_statement(api, { topics = { TOPIC }, data = "ping" }, alice)
+    assert(result.status == "new" and hash:match("^0x%x+$") and #hash == 66)
+    local list = Offchain.query_statements(api, { TOPIC })
+    assert(#list == 1 and list[1].hash == hash and list[1].data == "0x" .. to_hex("ping"))
+end)
+
+test("Offchain: malformed statements rejected", function()
+    assert(not pcall(Offchain.encode_statement, { topics = { TOPIC, TOPIC, TOPIC, TOPIC, TOPIC } }))
+    assert(not pcall(Offchain.encode_statement, { channel = "0x01" }))
+    assert(not pcall(Offchain.decode_statement, "0x0409"), "unknown field")
+    assert(not pcall(Offchain.decode_statement, "0x0403" .. string.rep("00", 31)), "truncated")
+    assert(not pcall(Offchain.decode_statement, "0x0002"), "trailing bytes")
+end)
+
+print("\n=== Offchain Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All offchain tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,153 @@
+-- test/test_parachains.lua
+-- Unit tests for relay-chain parachain queries
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Parachains = require("sublua.parachains")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Header = require("sublua.header")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Parachains Module Tests ===\n")
+
+local FIXTURE = {
+    types = {
+        { "ParaId", { path = { "polkadot_parachain_primitives", "primitives", "Id" }, composite = { { type = "u32" } } } },
+        { "Vec<ParaId>", { sequence = "ParaId" } },
+        { "HeadData", { path = { "polkadot_parachain_primitives", "primitives", "HeadData" }, composite = { { type = "Vec<u8>" } } } },
+        { "HrmpChannelId", { path = { "polkadot_parachain_primitives", "primitives", "HrmpChannelId" }, composite = {
+            { name = "sender", type = "ParaId" }, { name = "recipient", type = "ParaId" }
+        } } },
+        { "HrmpChannel", { path = { "polkadot_runtime_parachains", "hrmp", "HrmpChannel" }, composite = {
+            { name = "max_capacity", type = "u32" }, { name = "max_total_size", type = "u32" },
+            { name = "max_message_size", type = "u32" }, { name = "msg_count", type = "u32" },
+            { name = "total_size", type = "u32" }, { name = "mqc_head", type = "Option<H256>" },
+            { name = "sender_deposit", type = "u128" }, { name = "recipient_deposit", type = "u128" }
+        } } },
+        { "HrmpOpenChannelRequest", { path = { "polkadot_runtime_parachains", "hrmp", "HrmpOpenChannelRequest" }, composite = {
+            { name = "confirmed", type = "bool" }, { name = "sender_deposit", type = "u128" },
+            { name = "max_message_size", type = "u32" }, { name = "max_capacity", type = "u32" },
+            { name = "max_total_size", type = "u32" }
+        } } },
+        { "ParaLifecycle", { path = { "polkadot_runtime_parachains", "paras", "ParaLifecycle" }, variant = {
+            { name = "Onboarding", index = 0 }, { name = "Parathread", index = 1 }, { name = "Parachain", index = 2 },
+            { name = "UpgradingParathread", index = 3 }, { name = "DowngradingParachain", index = 4 },
+            { name = "OffboardingParathread", index = 5 }, { name = "OffboardingParachain", index = 6 }
+        } } }
+    },
+    pallets = {
+        { name = "Paras", index = 56, storage = { prefix = "Paras", entries = {
+            { name = "Parachains", plain = "Vec<ParaId>", modifier = 1, default = "\0" },
+            { name = "Heads", map = { hashers = { "Twox64Concat" }, key = "ParaId", value = "HeadData" } },
+            { name = "ParaLifecycles", map = { hashers = { "Twox64Concat" }, key = "ParaId", value = "ParaLifecycle" } }
+        } } },
+        { name = "Hrmp", index = 60, storage = { prefix = "Hrmp", entries = {
+            { name = "HrmpChannels", map = { hashers = { "Twox64Concat" }, key = "HrmpChannelId", value = "HrmpChannel" } },
+            { name = "HrmpOpenChannelRequests", map = { hashers = { "Twox64Concat" }, key = "HrmpChannelId", value = "HrmpOpenChannelRequest" } }
+        } } },
+        { name = "ParachainInfo", index = 2, storage = { prefix = "ParachainInfo", entries = {
+            { name = "ParachainId", plain = "ParaId", modifier = 1, default = "\100\0\0\0" }
+        } } }
+    }
+}
+
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function channel(msg_count)
+    return {
+        max_capacity = 1000, max_total_size = 102400, max_message_size = 102400, msg_count = msg_count,
+        total_size = msg_count * 10, mqc_head = { variant = "None" }, sender_deposit = "0", recipient_deposit = "0"
+    }
+end
+
+test("Parachains: HRMP channels, filtered by para", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "Hrmp", "HrmpChannels", { { sender = 2000, recipient = 1000 } }, channel(3))
+    put(api, "Hrmp", "HrmpChannels", { { sender = 1000, recipient = 2000 } }, channel(0))
+    put(api, "Hrmp", "HrmpChannels", { { sender = 2004, recipient = 2006 } }, channel(1))
+    local all = Parachains.query_hrmp_channels(api)
+    assert(#all == 3)
+    assert(all[1].sender == 1000 and all[1].recipient == 2000 and all[3].sender == 2004)
+    local mine = Parachains.query_hrmp_channels(api, { para_id = 2000 })
+    assert(#mine == 2 and mine[2].sender == 2000 and mine[2].msg_count == 3 and mine[2].max_capacity == 1000)
+end)
+
+test("Parachains: pending HRMP requests", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "Hrmp", "HrmpOpenChannelRequests", { { sender = 2004, recipient = 1000 } }, {
+        confirmed = false, sender_deposit = "100", max_message_size = 1024, max_capacity = 8, max_total_size = 8192 })
+    local requests = Parachains.query_hrmp_requests(api, { para_id = 1000 })
+    assert(#requests == 1 and requests[1].sender == 2004 and requests[1].confirmed == false)
+    assert(#Parachains.query_hrmp_requests(api, { para_id = 3000 }) == 0)
+end)
+
+test("Parachains: head data decoded as a header", function()
+    local api = Mock.api({}, FIXTURE)
+    local raw = Header.encode({
+        parent_hash = "0x" .. string.rep("01", 32), number = 4321,
+        state_root = "0x" .. string.rep("02", 32), extrinsics_root = "0x" .. string.rep("03", 32), digest = {}
+    })
+    put(api, "Paras", "Heads", { 1000 }, "0x" .. to_hex(raw))
+    local head = Parachains.query_parachain_head(api, 1000)
+    assert(head.para_id == 1000 and head.data == "0x" .. to_hex(raw))
+    assert(head.hash == "0x" .. to_hex(crypto.blake2b(raw, 32)) and head.hash == Header.hash(raw))
+    assert(head.number == 4321 and head.state_root == "0x" .. string.rep("02", 32))
+    assert(Parachains.query_parachain_head(api, 9999) == nil)
+end)
+
+test("Parachains: opaque head data", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "Paras", "Heads", { 2000 }, "0xdeadbeef")
+    local head = Parachains.query_parachain_head(api, 2000)
+    assert(head.data == "0xdeadbeef" and head.number == nil and head.hash:match("^0x%x+$"))
+end)
+
+test("Parachains: lifecycle, parachain list and para id", function()
+    local api = Mock.api({}, FIXTURE)
+    put(api, "Paras", "ParaLifecycles", { 1000 }, { variant = "Parachain" })
+    put(api, "Paras", "ParaLifecycles", { 2001 }, { variant = "Onboarding" })
+    put(api, "Paras", "Parachains", {}, { 1000, 2000 })
+    assert(Parachains.query_parachain_lifecycle(api, 1000) == "Parachain")
+    assert(Parachains.query_parachain_lifecycle(api, 2001) == "Onboarding")
+    assert(Parachains.query_parachain_lifecycle(api, 3000) == nil)
+    local list = Parachains.query_parachains(api)
+    assert(#list == 2 and list[2] == 2000)
+    assert(Parachains.query_para_id(api) == 100, "falls back to the metadata default")
+end)
+
+print("\n=== Parachains Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All parachains tests passed!")
+    os.exit(0)
+end
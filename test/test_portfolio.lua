@@ -0,0 +1,122 @@
+-- test/test_portfolio.lua
+-- Unit tests for the watch-only portfolio (mocked storage)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Portfolio = require("sublua.portfolio")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Address = require("sublua.address")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Portfolio Tests ===\n")
+
+local ALICE = "0x" .. string.rep("01", 32)
+local BOB = "0x" .. string.rep("02", 32)
+local VALIDATOR = "0x" .. string.rep("0a", 32)
+
+-- Mock api with Alice staking directly and Bob in a nomination pool
+local function chain()
+    local meta = Mock.metadata()
+    local storage = {}
+    local function put(pallet, entry, key, type_name, value)
+        storage[Storage.build_key(meta, pallet, entry, { key })] = "0x" .. (Codec.encode(meta, Mock.type_id(type_name), value)
+            :gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+    end
+    local function account(free, reserved)
+        return { nonce = 3, consumers = 0, providers = 1, sufficients = 0,
+            data = { free = free, reserved = reserved, frozen = "0", flags = "0" } }
+    end
+    put("System", "Account", ALICE, "AccountInfo", account("5000000000000000000000", "10"))
+    put("System", "Account", BOB, "AccountInfo", account("250", "0"))
+    put("Staking", "Bonded", ALICE, "AccountId32", ALICE)
+    put("Staking", "Ledger", ALICE, "StakingLedger", {
+        stash = ALICE, total = "1000", active = "800", unlocking = { { value = "200", era = 90 } }
+    })
+    put("Staking", "Nominators", ALICE, "Nominations", { targets = { VALIDATOR }, submitted_in = 88, suppressed = false })
+    put("NominationPools", "PoolMembers", BOB, "PoolMember", { pool_id = 12, points = "400", last_recorded_reward_counter = "0" })
+
+    local api = Mock.api(storage)
+    function api:state_call(method, data, at)
+        table.insert(self.calls, { method, data, at })
+        assert(method == "NominationPoolsApi_pending_rewards" and data == BOB)
+        return "0x" .. "2a" .. string.rep("00", 15)
+    end
+    return api
+end
+
+test("Portfolio: add, list and remove accounts", function()
+    local p = Portfolio.new()
+    p:add_account("cold", Address.encode(ALICE, "polkadot"))
+    p:add_account("pool", BOB)
+    assert(not pcall(p.add_account, p, "cold", BOB))
+    assert(not pcall(p.add_account, p, "bad", "not an address"))
+    assert(table.concat(p:labels(), ",") == "cold,pool")
+    p:remove("cold")
+    assert(table.concat(p:labels(), ",") == "pool")
+    assert(not pcall(p.remove, p, "cold"))
+end)
+
+test("Portfolio: snapshot aggregates balances, staking and pools", function()
+    local api = chain()
+    local p = Portfolio.new()
+    p:add_account("cold", Address.encode(ALICE, 0))
+    p:add_account("pool", BOB)
+    p:add_account("empty", "0x" .. string.rep("03", 32))
+    local snap = p:snapshot(api)
+
+    assert(snap.at == "0x" .. string.rep("11", 32) and #snap.accounts == 3)
+    local cold, pool, empty = snap.accounts[1], snap.accounts[2], snap.accounts[3]
+    assert(cold.label == "cold" and cold.free == "5000000000000000000000" and cold.nonce == 3)
+    assert(cold.staking.controller == ALICE and cold.staking.total == "1000" and cold.staking.active == "800")
+    assert(cold.staking.unlocking[1].value == "200" and cold.staking.unlocking[1].era == 90)
+    assert(cold.staking.nominations[1] == VALIDATOR and cold.pool == nil)
+    assert(pool.staking == nil and pool.pool.pool_id == 12 and pool.pool.points == "400")
+    assert(pool.pool.pending_rewards == "42")
+    assert(empty.free == "0" and empty.staking == nil and empty.pool == nil)
+
+    assert(snap.totals.free == "5000000000000000000250" and snap.totals.reserved == "10")
+    assert(snap.totals.bonded == "1000" and snap.totals.pending_rewards == "42")
+end)
+
+test("Portfolio: snapshot batches storage reads", function()
+    local api = chain()
+    local p = Portfolio.new()
+    p:add_account("cold", ALICE)
+    p:add_account("pool", BOB)
+    p:snapshot(api, { at = "0x" .. string.rep("22", 32), pending_rewards = false })
+    local batches = 0
+    for _, call in ipairs(api.calls) do
+        assert(call[1] == "state_queryStorageAt", "unexpected " .. call[1])
+        assert(call[3] == "0x" .. string.rep("22", 32))
+        batches = batches + 1
+    end
+    -- One batch for accounts, bonds, nominations and pools; one for ledgers
+    assert(batches == 2 and #api.calls[1][2] == 8 and #api.calls[2][2] == 1)
+end)
+
+print("\n=== Portfolio Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All portfolio tests passed!")
+    os.exit(0)
+end
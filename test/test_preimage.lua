@@ -0,0 +1,148 @@
+-- test/test_preimage.lua
+-- Unit tests for Preimage pallet helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Preimage = require("sublua.preimage")
+local Call = require("sublua.call")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Preimage Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+
+local function preimage_types(status)
+    return {
+        { "PreimageCall", { path = { "pallet_preimage", "pallet", "Call" }, variant = {
+            { name = "note_preimage", index = 0, fields = { { name = "bytes", type = "Vec<u8>" } } },
+            { name = "unnote_preimage", index = 1, fields = { { name = "hash", type = "H256" } } }
+        } } },
+        { "Option<(AccountId32, u128)>", { path = { "Option" }, variant = {
+            { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = "(AccountId32, u128)" } } }
+        } } },
+        { "RequestStatus", { path = { "pallet_preimage", status }, variant = status == "RequestStatus" and {
+            { name = "Unrequested", index = 0, fields = {
+                { name = "ticket", type = "(AccountId32, u128)" }, { name = "len", type = "u32" } } },
+            { name = "Requested", index = 1, fields = {
+                { name = "maybe_ticket", type = "Option<(AccountId32, u128)>" }, { name = "count", type = "u32" },
+                { name = "maybe_len", type = "Option<u32>" } } }
+        } or {
+            { name = "Unrequested", index = 0, fields = {
+                { name = "deposit", type = "(AccountId32, u128)" }, { name = "len", type = "u32" } } },
+            { name = "Requested", index = 1, fields = {
+                { name = "deposit", type = "Option<(AccountId32, u128)>" }, { name = "count", type = "u32" },
+                { name = "len", type = "Option<u32>" } } }
+        } } },
+        { "(H256, u32)", { tuple = { "H256", "u32" } } }
+    }
+end
+
+local function fixture(entry_name, status)
+    return {
+        types = preimage_types(status),
+        pallets = {
+            { name = "Preimage", index = 10, calls = "PreimageCall", storage = { prefix = "Preimage", entries = {
+                { name = entry_name, map = { hashers = { "Identity" }, key = "H256", value = "RequestStatus" } },
+                { name = "PreimageFor", map = { hashers = { "Identity" }, key = "(H256, u32)", value = "Vec<u8>" } }
+            } } }
+        }
+    }
+end
+
+local CURRENT = fixture("RequestStatusFor", "RequestStatus")
+local LEGACY = fixture("StatusFor", "OldRequestStatus")
+
+local function put(api, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets["Preimage"].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, "Preimage", entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local CALL = Call.encode_call(Mock.metadata(), "System", "remark", { remark = "0x" .. string.rep("ab", 100) })
+
+test("Preimage: hash and lookup_of", function()
+    local hash = Preimage.hash(CALL)
+    assert(hash == "0x" .. to_hex(crypto.blake2b(CALL, 32)))
+    assert(Preimage.hash("0x" .. to_hex(CALL)) == hash, "hex and raw bytes hash the same")
+    local lookup = Preimage.lookup_of(CALL)
+    assert(lookup.variant == "Lookup" and lookup.value.hash == hash and lookup.value.len == #CALL)
+end)
+
+test("Preimage: note and unnote encoding", function()
+    local api = Mock.api({}, CURRENT)
+    local signed, details = Preimage.note(api, alice, "0x" .. to_hex(CALL), { nonce = 0 })
+    assert(details.hash == Preimage.hash(CALL) and details.len == #CALL)
+    local call = Transaction.decode(api:get_metadata(), signed).call
+    assert(call.pallet == "Preimage" and call.name == "note_preimage" and call.args.bytes == "0x" .. to_hex(CALL))
+    call = Transaction.decode(api:get_metadata(), Preimage.unnote(api, alice, details.hash, { nonce = 0 })).call
+    assert(call.name == "unnote_preimage" and call.args.hash == details.hash)
+end)
+
+test("Preimage: query a noted preimage with its data", function()
+    local api = Mock.api({}, CURRENT)
+    local hash = Preimage.hash(CALL)
+    put(api, "RequestStatusFor", { hash }, { variant = "Unrequested", value = { ticket = { ALICE, "5" }, len = #CALL } })
+    put(api, "PreimageFor", { { hash, #CALL } }, "0x" .. to_hex(CALL))
+    local status = Preimage.query(api, hash, { data = true })
+    assert(status.status == "Unrequested" and status.len == #CALL and status.depositor == ALICE and status.count == 0)
+    assert(status.data == "0x" .. to_hex(CALL))
+    assert(Preimage.is_noted(api, hash))
+    assert(Preimage.query(api, "0x" .. string.rep("00", 32)) == nil)
+end)
+
+test("Preimage: requested but not yet noted", function()
+    local api = Mock.api({}, CURRENT)
+    local hash = Preimage.hash(CALL)
+    put(api, "RequestStatusFor", { hash }, { variant = "Requested", value = {
+        maybe_ticket = { variant = "None" }, count = 1, maybe_len = { variant = "None" } } })
+    local status = Preimage.query(api, hash, { data = true })
+    assert(status.status == "Requested" and status.count == 1 and status.len == nil and status.depositor == nil)
+    assert(status.data == nil)
+    assert(not Preimage.is_noted(api, hash))
+end)
+
+test("Preimage: older StatusFor layout", function()
+    local api = Mock.api({}, LEGACY)
+    local hash = Preimage.hash(CALL)
+    put(api, "StatusFor", { hash }, { variant = "Requested", value = {
+        deposit = { variant = "Some", value = { ALICE, "9" } }, count = 2, len = { variant = "Some", value = #CALL } } })
+    local status = Preimage.query(api, hash)
+    assert(status.status == "Requested" and status.depositor == ALICE and status.count == 2 and status.len == #CALL)
+end)
+
+print("\n=== Preimage Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All preimage tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,114 @@
+-- test/test_prices.lua
+-- Unit tests for the price client (HTTP mocked)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local http = require("socket.http")
+local Prices = require("sublua.prices")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+-- Replace http.request with canned responses; returns the captured requests
+local function with_responses(responses, fn)
+    local original, requests = http.request, {}
+    http.request = function(req)
+        table.insert(requests, req)
+        local r = table.remove(responses, 1)
+        if r.status == nil then return nil, r.body end
+        req.sink(r.body)
+        return 1, r.status
+    end
+    local ok, err = pcall(fn)
+    http.request = original
+    assert(ok, err)
+    return requests
+end
+
+print("=== Price Tests ===\n")
+
+test("Prices: CoinGecko-compatible lookup", function()
+    local client = Prices.new({ base_url = "http://prices.local/api/", api_key = "k" })
+    local price
+    local requests = with_responses({ { status = 200, body = '{"polkadot":{"eur":4.25}}' } }, function()
+        price = client:get_token_price("dot", "EUR")
+    end)
+    assert(price == 4.25)
+    assert(requests[1].url == "http://prices.local/api/simple/price?ids=polkadot&vs_currencies=eur")
+    assert(requests[1].method == "GET" and requests[1].headers["x-cg-demo-api-key"] == "k")
+end)
+
+test("Prices: cached until the TTL runs out", function()
+    local now = 1000
+    local client = Prices.new({ ttl = 30, clock = function() return now end })
+    local requests = with_responses({
+        { status = 200, body = '{"kusama":{"usd":20}}' },
+        { status = 200, body = '{"kusama":{"usd":21}}' }
+    }, function()
+        assert(client:get_token_price("KSM") == 20)
+        now = now + 29
+        assert(client:get_token_price("KSM") == 20)
+        now = now + 1
+        assert(client:get_token_price("KSM") == 21)
+    end)
+    assert(#requests == 2)
+end)
+
+test("Prices: errors are returned, not cached", function()
+    local client = Prices.new()
+    with_responses({
+        { status = 429, body = '{"status":"rate limited"}' },
+        { status = nil, body = "timeout" },
+        { status = 200, body = '{"polkadot":{}}' }
+    }, function()
+        local price, err = client:get_token_price("DOT")
+        assert(price == nil and err:match("HTTP 429"))
+        price, err = client:get_token_price("DOT")
+        assert(price == nil and err:match("^Timeout"))
+        price, err = client:get_token_price("DOT", "xyz")
+        assert(price == nil and err:match("No xyz price"))
+    end)
+    local price, err = client:get_token_price("NOPE")
+    assert(price == nil and err:match("No coin id known for NOPE"))
+end)
+
+test("Prices: pluggable provider and custom coin ids", function()
+    local asked = {}
+    local client = Prices.new({
+        coin_ids = { pha = "pha" },
+        provider = function(symbol, vs)
+            table.insert(asked, symbol .. "/" .. vs)
+            return 0.12
+        end
+    })
+    assert(client:get_token_price("PHA", "usd") == 0.12)
+    assert(client:get_token_price("pha") == 0.12)
+    assert(#asked == 1 and asked[1] == "PHA/usd" and client.coin_ids.PHA == "pha")
+    client:clear_cache()
+    client:get_token_price("PHA")
+    assert(#asked == 2)
+    assert(Prices.default() == Prices.default())
+end)
+
+print("\n=== Price Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All price tests passed!")
+    os.exit(0)
+end
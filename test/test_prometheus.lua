@@ -0,0 +1,131 @@
+-- test/test_prometheus.lua
+-- Unit tests for the Prometheus exporter (stub clients, in-memory connections)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Prometheus = require("sublua.prometheus")
+local socket = require("socket")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Prometheus Module Tests ===\n")
+
+local function client(m)
+    local snapshot = {
+        requests = 12, errors = 3, timeouts = 1, rpc_errors = 2, transport_errors = 0,
+        avg_latency_ms = 25, max_latency_ms = 180, bytes_out = 2048, bytes_in = 65536,
+        reconnects = 1, connected = true, methods = { state_call = 4, chain_getHeader = 8 }
+    }
+    for k, v in pairs(m or {}) do snapshot[k] = v end
+    return { metrics = function() return snapshot end }
+end
+
+local function has(text, line)
+    return text:find(line, 1, true) ~= nil
+end
+
+-- socket.bind stand-in: scrapes queue requests, accept() hands them to the server
+local pending = {}
+local original_bind = socket.bind
+socket.bind = function(host, port)
+    if host == "192.0.2.1" then return nil, "cannot assign requested address" end
+    local listener = { closed = false }
+    function listener:settimeout() end
+    function listener:getsockname() return host, port == 0 and 40000 or port end
+    function listener:accept() return table.remove(pending, 1) end
+    function listener:close() self.closed = true end
+    return listener
+end
+
+local function scrape(server, request)
+    local lines, sent = {}, {}
+    for line in request:gmatch("([^\n]*)\n") do table.insert(lines, (line:gsub("\r$", ""))) end
+    local conn = {}
+    function conn:settimeout() end
+    function conn:receive() return table.remove(lines, 1) end
+    function conn:send(data) table.insert(sent, data) end
+    function conn:close() end
+    table.insert(pending, conn)
+    local served = server:step(1)
+    return table.concat(sent), served
+end
+
+test("Prometheus: Renders client counters in text format", function()
+    local text = Prometheus.render(client())
+    assert(has(text, "# TYPE sublua_rpc_requests_total counter\nsublua_rpc_requests_total{client=\"default\"} 12\n"))
+    assert(has(text, 'sublua_rpc_errors_total{client="default",kind="timeout"} 1'))
+    assert(has(text, 'sublua_rpc_errors_total{client="default",kind="rpc"} 2'))
+    assert(has(text, 'sublua_rpc_latency_max_seconds{client="default"} 0.18'))
+    assert(has(text, 'sublua_rpc_received_bytes_total{client="default"} 65536'))
+    assert(has(text, 'sublua_rpc_up{client="default"} 1'))
+    assert(has(text, 'sublua_rpc_method_requests_total{client="default",method="chain_getHeader"} 8\n'
+        .. 'sublua_rpc_method_requests_total{client="default",method="state_call"} 4'))
+    assert(text:sub(-1) == "\n")
+end)
+
+test("Prometheus: Several clients are labelled by name", function()
+    local text = Prometheus.render({ relay = client(), hub = client({ requests = 3, connected = false }) })
+    assert(has(text, 'sublua_rpc_requests_total{client="hub"} 3\nsublua_rpc_requests_total{client="relay"} 12'))
+    assert(has(text, 'sublua_rpc_up{client="hub"} 0'))
+    local _, families = text:gsub("# TYPE ", "")
+    assert(families == 9, "one TYPE line per family")
+    text = Prometheus.render({ ['a"b\\c'] = client() })
+    assert(has(text, 'client="a\\"b\\\\c"'), "label values escaped")
+end)
+
+test("Prometheus: Serves GET /metrics over HTTP", function()
+    local server = Prometheus.start_metrics_server("127.0.0.1:0", client())
+    assert(server.port == 40000, "port 0 picks a free port")
+    assert(server:step() == 0, "nothing pending")
+    local response, served = scrape(server, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+    assert(served == 1 and response:match("^HTTP/1.1 200 OK\r\n"))
+    assert(has(response, "Content-Type: " .. Prometheus.CONTENT_TYPE))
+    local body = response:match("\r\n\r\n(.*)$")
+    assert(body == Prometheus.render(client()))
+    assert(tonumber(response:match("Content%-Length: (%d+)")) == #body)
+
+    response = scrape(server, "GET / HTTP/1.1\r\n\r\n")
+    assert(response:match("^HTTP/1.1 404"))
+    response = scrape(server, "POST /metrics HTTP/1.1\r\n\r\n")
+    assert(response:match("^HTTP/1.1 405"))
+    server:close()
+    assert(server.listener == nil and server:step() == 0)
+end)
+
+test("Prometheus: Failing clients answer 500 and bad addresses error", function()
+    local server = Prometheus.start_metrics_server(":0", { metrics = function() error("boom") end })
+    assert(server.host == "127.0.0.1")
+    local response = scrape(server, "GET /metrics HTTP/1.0\r\n\r\n")
+    assert(response:match("^HTTP/1.1 500") and has(response, "boom"))
+    server:close()
+    local ok, err = pcall(Prometheus.start_metrics_server, "localhost")
+    assert(not ok and err:match("bind_addr must be host:port"))
+    ok, err = pcall(Prometheus.start_metrics_server, "192.0.2.1:9464")
+    assert(not ok and err:match("Cannot listen on 192.0.2.1:9464: cannot assign"))
+end)
+
+socket.bind = original_bind
+
+print("\n=== Prometheus Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All prometheus tests passed!")
+    os.exit(0)
+end
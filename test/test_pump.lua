@@ -0,0 +1,116 @@
+-- test/test_pump.lua
+-- Unit tests for main-loop dispatch of spawned work and queued callbacks
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Pump = require("sublua.pump")
+local Subscription = require("sublua.subscription")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Pump Module Tests ===\n")
+
+-- Pump on a clock the test moves
+local function pump()
+    local clock = { now = 100 }
+    return Pump.new({ clock = function() return clock.now end }), clock
+end
+
+test("Pump: Callbacks run only from pump()", function()
+    local p = pump()
+    local seen = {}
+    local cb = p:deliver(function(a, b, c) table.insert(seen, { a, b, c }) end)
+    cb(1, nil, 3)
+    cb(4)
+    assert(#seen == 0 and p:pending() == 2, "queued, not called")
+    assert(p:pump() == 2 and #seen == 2)
+    assert(seen[1][1] == 1 and seen[1][2] == nil and seen[1][3] == 3 and seen[2][1] == 4)
+    assert(p:pump() == 0 and p:pending() == 0)
+end)
+
+test("Pump: Spawned work yields at waits and reports on the host loop", function()
+    local p, clock = pump()
+    local steps, done = {}, nil
+    local task = p:spawn(function()
+        table.insert(steps, "start")
+        p.wait(2)
+        table.insert(steps, "after wait")
+        return "hash", 7
+    end, function(ok, a, b) done = { ok, a, b } end)
+    assert(#steps == 0, "nothing runs before pump")
+    p:pump()
+    assert(#steps == 1 and not task.done)
+    clock.now = 101
+    p:pump()
+    assert(#steps == 1, "still waiting")
+    clock.now = 102
+    assert(p:pump() == 1 and #steps == 2 and task.done and task.ok)
+    assert(done[1] == true and done[2] == "hash" and done[3] == 7)
+    assert(task.results[1] == "hash" and p:pending() == 0)
+end)
+
+test("Pump: Errors go to on_done, or out of pump()", function()
+    local p = pump()
+    local got
+    p:spawn(function() error("node down", 0) end, function(ok, err) got = { ok, err } end)
+    p:pump()
+    assert(got[1] == false and got[2] == "node down")
+    p:spawn(function() error("unhandled", 0) end)
+    local ok, err = pcall(p.pump, p)
+    assert(not ok and err == "unhandled" and p:pending() == 0)
+end)
+
+test("Pump: Budget leaves callbacks for the next pump", function()
+    local p, clock = pump()
+    local n = 0
+    local cb = p:deliver(function() n = n + 1 clock.now = clock.now + 0.01 end)
+    for _ = 1, 5 do cb() end
+    assert(p:pump(25) == 3 and n == 3 and p:pending() == 2)
+    assert(p:pump() == 2 and n == 5)
+end)
+
+test("Pump: A subscription follows the chain from the host loop", function()
+    local p, clock = pump()
+    local api = Mock.api()
+    api.best_number = 10
+    local blocks, polls = {}, 0
+    local sub = Subscription.new(api, {
+        on_block = p:deliver(function(block) table.insert(blocks, block.number) end)
+    }, { last_block = 8 })
+    p:spawn(function()
+        sub:run({ sleep = p.wait, poll_interval = 6, stop = function() polls = polls + 1 return polls == 2 end })
+    end)
+    p:pump()
+    assert(#blocks == 2 and blocks[2] == 10, "delivered in the same pump")
+    api.best_number = 11
+    p:pump()
+    assert(#blocks == 2, "sleeping between polls")
+    clock.now = clock.now + 6
+    p:pump()
+    assert(#blocks == 3 and p:pending() == 0, "second poll delivered, loop stopped")
+end)
+
+print("\n=== Pump Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All pump tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,90 @@
+-- test/test_random.lua
+-- Unit tests for the random source, deterministic test sources and key generation
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Random = require("sublua.random")
+local Bip39 = require("sublua.bip39")
+local Keyring = require("sublua.keyring")
+local Keystore = require("sublua.keystore")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Random Tests ===\n")
+
+test("Random: OS source by default", function()
+    local a, b = Random.bytes(32), Random.bytes(32)
+    assert(#a == 32 and a ~= b and Random.bytes(0) == "")
+    assert(Random.source(nil) == Random.bytes)
+    assert(not pcall(Random.source, "fixture"))
+end)
+
+test("Random: deterministic sources repeat", function()
+    local source = Random.deterministic("fixture-1")
+    local first = source(10) .. source(100)
+    assert(Random.deterministic("fixture-1")(110) == first)
+    assert(Random.deterministic("fixture-2")(110) ~= first)
+    assert(source(10) ~= first:sub(1, 10), "a source continues its stream")
+    assert(not pcall(Random.deterministic, ""))
+end)
+
+test("Random: mnemonic generation and vanity search take an explicit source", function()
+    local pair, phrase = Keyring.generate({ random = Random.deterministic("golden") })
+    local pair2, phrase2 = Keyring.generate({ random = Random.deterministic("golden") })
+    assert(phrase == phrase2 and pair.address == pair2.address and Bip39.is_valid(phrase))
+    assert(select(2, phrase:gsub("%S+", "")) == 12)
+    assert(Bip39.generate(24, Random.deterministic("long")) == Bip39.generate(24, Random.deterministic("long")))
+    assert(Keyring.generate() ~= pair and select(2, Keyring.generate()) ~= phrase, "no source: OS entropy")
+    assert(not pcall(Bip39.generate, 13))
+
+    local v, seed, tries = Keyring.vanity("a", { random = Random.deterministic("vanity") })
+    assert(v.address:find("a", 1, true) and Keyring.from_seed(seed).pubkey == v.pubkey)
+    local again, seed2, tries2 = Keyring.vanity("a", { random = Random.deterministic("vanity") })
+    assert(seed2 == seed and tries2 == tries and again.address == v.address)
+    local start = Keyring.vanity("5", { prefix = 0, at_start = true, max_tries = 2000 })
+    assert(start == nil or start.address:sub(2, 2) == "5")
+    local none, err = Keyring.vanity("zzzzzzzz", { max_tries = 3 })
+    assert(none == nil and err:find("3 tries"))
+    assert(not pcall(Keyring.vanity, "0OIl"))
+end)
+
+test("Random: keystores always seal with OS randomness", function()
+    local getenv = os.getenv
+    os.getenv = function(name) return name == "SUBLUA_DETERMINISTIC_SEED" and "keystore" or getenv(name) end
+    local function build()
+        local path = os.tmpname()
+        os.remove(path)
+        Keystore.init(path, "pw", { kib = 64, passes = 1 }):add("bot", "0x" .. string.rep("11", 32))
+        local text = io.open(path, "rb"):read("*a")
+        os.remove(path)
+        return text
+    end
+    local ok, first, second = pcall(function() return build(), build() end)
+    os.getenv = getenv
+    assert(ok, first)
+    assert(first ~= second, "salts and nonces must differ between keystores")
+end)
+
+print("\n=== Random Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Random tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,109 @@
+-- test/test_registry.lua
+-- Unit tests for the multi-chain registry (mocked clients)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Registry = require("sublua.registry")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Registry Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+
+local function make_registry()
+    local registry = Registry.new()
+    registry:register("polkadot", { api = Mock.api(), ss58_prefix = 0 })
+    registry:register("assethub", { api = Mock.api(), para_id = 1000, relay = "polkadot" })
+    return registry
+end
+
+test("Registry: register and look up chains", function()
+    local registry = make_registry()
+    local names = registry:names()
+    assert(#names == 2 and names[1] == "assethub" and names[2] == "polkadot")
+    assert(registry:get("assethub").para_id == 1000 and registry:get("assethub").relay == "polkadot")
+    assert(registry:api("polkadot") ~= registry:api("assethub"))
+    assert(registry:metadata("polkadot") == registry:api("polkadot").metadata)
+
+    local chain = registry:register("westend", "wss://westend-rpc.polkadot.io")
+    assert(chain.api.url == "https://westend-rpc.polkadot.io")
+end)
+
+test("Registry: invalid registrations", function()
+    local registry = make_registry()
+    assert(not pcall(registry.register, registry, "polkadot", "http://x"))
+    assert(not pcall(registry.register, registry, "para", { url = "http://x", relay = "kusama" }))
+    assert(not pcall(registry.register, registry, "empty", {}))
+    local ok, err = pcall(registry.get, registry, "kusama")
+    assert(not ok and err:match("Unknown chain 'kusama'"))
+    assert(not pcall(registry.remove, registry, "polkadot"))
+    registry:remove("assethub")
+    registry:remove("polkadot")
+    assert(#registry:names() == 0)
+end)
+
+test("Registry: per-chain SS58 addresses", function()
+    local registry = make_registry()
+    assert(registry:ss58_prefix("polkadot") == 0 and registry:ss58_prefix("assethub") == 42)
+    local dot = registry:address("polkadot", alice)
+    assert(dot:sub(1, 1) == "1" and dot ~= alice.address)
+    assert(registry:address("assethub", dot) == alice.address)
+    assert(registry:address("polkadot", alice.pubkey) == dot)
+end)
+
+test("Registry: nonces are tracked per chain", function()
+    local registry = make_registry()
+    registry:api("polkadot").next_index = 5
+    assert(registry:next_nonce("polkadot", alice.address) == 5)
+    assert(registry:next_nonce("polkadot", alice.address) == 6)
+    assert(registry:next_nonce("assethub", alice.address) == 0)
+    -- Same account under another address format shares the tracker
+    assert(registry:next_nonce("polkadot", registry:address("polkadot", alice)) == 7)
+    assert(#registry:api("polkadot").calls == 1)
+    registry:reset_nonce("polkadot", alice.address)
+    assert(registry:next_nonce("polkadot", alice.address) == 5)
+end)
+
+test("Registry: signed extrinsics use the managed nonce", function()
+    local registry = make_registry()
+    local call = "\0\0\8hi"
+    local first, info = registry:create_signed("polkadot", alice, call)
+    local second, info2 = registry:create_signed("polkadot", alice, call)
+    assert(info.nonce == 0 and info2.nonce == 1 and first ~= second)
+    local decoded = Transaction.decode(registry:metadata("polkadot"), second)
+    assert(decoded.nonce == 1)
+
+    -- A failed build releases the nonce
+    local broken = Keyring.from_external(alice.pubkey, function() error("device unplugged") end)
+    local ok, err = pcall(registry.create_signed, registry, "polkadot", broken, call)
+    assert(not ok and err:match("device unplugged"))
+    assert(registry:next_nonce("polkadot", alice.address) == 0)
+end)
+
+print("\n=== Registry Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All registry tests passed!")
+    os.exit(0)
+end
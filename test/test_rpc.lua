@@ -3,7 +3,7 @@
 
 -- Fix paths to work from test directory or root
 package.cpath = "../sublua/?.so;./sublua/?.so;" .. package.cpath
-package.path = "../?.lua;../?/init.lua;./?.lua;./?/init.lua;" .. package.path
+package.path = "../?.lua;../?/init.lua;./?.lua;./?/init.lua;./test/?.lua;" .. package.path
 
 local RPC = require("sublua.rpc")
 local crypto = require("polkadot_crypto")
@@ -50,6 +50,57 @@ test("RPC: Convert WS to HTTP", function()
     assert(rpc.url == "http://localhost:9944")
 end)
 
+test("RPC: HTTP transport by default", function()
+    assert(RPC.new("wss://westend-rpc.polkadot.io").transport == "http")
+    assert(RPC.new("http://localhost", { transport = "http" }).transport == "http")
+end)
+
+test("RPC: Reject unsupported transport", function()
+    local ok, err = pcall(RPC.new, "ws://localhost:9944", { transport = "ws" })
+    assert(not ok and err:match("Unsupported RPC transport"))
+end)
+
+test("RPC: Subscription methods rejected on HTTP", function()
+    local rpc = RPC.new("http://localhost")
+    for _, method in ipairs({ "chain_subscribeNewHeads", "state_unsubscribeStorage",
+                              "author_submitAndWatchExtrinsic", "chainHead_v1_follow" }) do
+        assert(RPC.is_subscription(method))
+        local ok, err = pcall(rpc.request, rpc, method, {})
+        assert(not ok and err:match("subscription API"), method)
+    end
+    assert(not RPC.is_subscription("chain_getBlockHash"))
+    assert(not RPC.is_subscription("author_submitExtrinsic"))
+end)
+
+test("RPC: Proxy validation", function()
+    assert(RPC.new("http://localhost", { proxy = "http://proxy.local:3128" }).proxy == "http://proxy.local:3128")
+    local ok, err = pcall(RPC.new, "http://localhost", { proxy = "socks5://proxy.local:1080" })
+    assert(not ok and err:match("SOCKS"))
+    ok, err = pcall(RPC.new, "http://localhost", { proxy = "proxy.local:3128" })
+    assert(not ok and err:match("Invalid proxy"))
+end)
+
+test("RPC: TLS and proxy options reach the HTTP request", function()
+    local http = require("socket.http")
+    local original, captured = http.request, nil
+    http.request = function(t) captured = t; return nil, "connection refused" end
+    local rpc = RPC.new("https://node.local", {
+        proxy = "http://proxy.local:3128",
+        tls = { cafile = "/etc/ssl/dev-ca.pem" }
+    })
+    local ok, err = pcall(rpc.request, rpc, "system_health", {})
+    local verified_req = captured
+    local insecure = RPC.new("https://node.local", { tls = { verify = false } })
+    pcall(insecure.request, insecure, "system_health", {})
+    local insecure_req = captured
+    http.request = original
+    assert(not ok and err:match("via proxy http://proxy.local:3128"))
+    assert(err:match("connection refused"))
+    assert(verified_req.proxy == "http://proxy.local:3128")
+    assert(verified_req.cafile == "/etc/ssl/dev-ca.pem" and verified_req.verify == "peer")
+    assert(insecure_req.verify == "none")
+end)
+
 test("RPC: ID increments", function()
     local rpc = RPC.new("http://localhost")
     assert(rpc.id == 1)
@@ -70,6 +121,36 @@ test("RPC: Default chain properties", function()
     assert(props.divisor == 10^12)
 end)
 
+test("RPC: SS58 output format", function()
+    local rpc = RPC.new("http://localhost")
+    rpc.chain_properties = { decimals = 12, symbol = "UNIT", ss58_prefix = 42 }
+    assert(rpc.ss58_format == nil and rpc:get_ss58_format() == 42, "chain's format by default")
+    local pubkey = "0x" .. string.rep("01", 32)
+    rpc:set_ss58_format("kusama")
+    assert(rpc:get_ss58_format() == 2)
+    assert(rpc:format_address(pubkey) == require("sublua.address").encode(pubkey, 2))
+    assert(not pcall(rpc.set_ss58_format, rpc, 99999), "invalid prefix")
+    assert(rpc:get_ss58_format() == 2, "unchanged after a rejected prefix")
+    rpc:set_ss58_format(nil)
+    assert(rpc:get_ss58_format() == 42)
+    assert(RPC.new("http://localhost", { ss58_format = 0 }).ss58_format == 0)
+end)
+
+test("RPC: Session default signer", function()
+    local rpc = RPC.new("http://localhost")
+    local pair = require("sublua.keyring").from_uri("//Alice")
+    assert(rpc:get_signer() == nil)
+    assert(rpc:set_signer(pair) == pair and rpc:get_signer() == pair)
+    local keystore = { keypair = function(_, name) assert(name == "treasury"); return pair end }
+    rpc:set_signer(nil)
+    assert(rpc:get_signer() == nil)
+    assert(rpc:set_signer("treasury", keystore) == pair and rpc.signer == pair)
+    local ok, err = pcall(rpc.set_signer, rpc, "treasury")
+    assert(not ok and err:match("needs a keystore"))
+    assert(not pcall(rpc.set_signer, rpc, { address = "x" }), "not a keypair")
+    assert(rpc:get_signer() == pair, "unchanged after a rejected signer")
+end)
+
 -- Storage Key Construction
 test("RPC: System.Account storage key construction", function()
     local rpc = RPC.new("http://localhost")
@@ -194,6 +275,201 @@ test("RPC: Error handling structure", function()
     assert(type(rpc.request) == "function")
 end)
 
+-- Node stub: serves the mock metadata and a runtime version the test can bump
+local function runtime_stub(opts)
+    local Mock = require("mock_metadata")
+    local rpc = RPC.new("http://localhost", opts)
+    rpc.node = { spec = 100, metadata_fetches = 0 }
+    rpc.request = function(self, method)
+        if method == "state_getRuntimeVersion" then
+            return { specVersion = self.node.spec, transactionVersion = 1 }
+        elseif method == "state_getMetadata" then
+            self.node.metadata_fetches = self.node.metadata_fetches + 1
+            return Mock.hex()
+        end
+        error("unexpected " .. method)
+    end
+    return rpc
+end
+
+test("RPC: Runtime upgrade refreshes metadata", function()
+    local upgrades = {}
+    local rpc = runtime_stub({ on_runtime_upgrade = function(old, new, api)
+        table.insert(upgrades, { old.specVersion, new.specVersion, api })
+    end })
+    local meta = rpc:get_metadata()
+    assert(rpc.runtime_version.specVersion == 100)
+    assert(rpc:check_runtime_upgrade() == false and rpc:get_metadata() == meta)
+
+    rpc.node.spec = 101
+    assert(rpc:check_runtime_upgrade() == true)
+    assert(#upgrades == 1 and upgrades[1][1] == 100 and upgrades[1][2] == 101 and upgrades[1][3] == rpc)
+    assert(rpc.metadata == nil)
+    assert(rpc:get_metadata() ~= meta and rpc.node.metadata_fetches == 2)
+end)
+
+test("RPC: Cached metadata re-checks the runtime on an interval", function()
+    local rpc = runtime_stub({ runtime_check_interval = 0 })
+    local meta = rpc:get_metadata()
+    rpc.node.spec = 101
+    assert(rpc:get_metadata() ~= meta and rpc.runtime_version.specVersion == 101)
+
+    local never = runtime_stub({ runtime_check_interval = false })
+    meta = never:get_metadata()
+    never.node.spec = 101
+    assert(never:get_metadata() == meta and never.node.metadata_fetches == 1)
+    assert(RPC.new("http://localhost").runtime_check_interval == 60)
+end)
+
+test("RPC: Timeouts are applied and reported distinctly", function()
+    local http = require("socket.http")
+    local original, seen = http.request, nil
+    http.request = function() seen = http.TIMEOUT; return nil, "timeout" end
+    local rpc = RPC.new("http://localhost", { timeout_ms = 1500 })
+    local ok, err = pcall(rpc.request, rpc, "system_health", {})
+    local restored = http.TIMEOUT
+    http.request = original
+    assert(seen == 1.5 and restored ~= 1.5)
+    assert(not ok and RPC.is_timeout(err) and err:match("system_health") and err:match("1500 ms"))
+    assert(not RPC.is_timeout("HTTP error code 500"))
+end)
+
+test("RPC: Deadlines span several calls", function()
+    local socket = require("socket")
+    local http = require("socket.http")
+    local original_time, original_request, now = socket.gettime, http.request, 1000
+    local timeouts = {}
+    socket.gettime = function() return now end
+    http.request = function(req)
+        table.insert(timeouts, http.TIMEOUT)
+        now = now + 2
+        req.sink('{"jsonrpc":"2.0","id":1,"result":"0x00"}')
+        return 1, 200
+    end
+    local rpc = RPC.new("http://localhost", { timeout_ms = 60000 })
+    local nested
+    local ok, err = pcall(rpc.with_deadline, rpc, 3000, function(api)
+        api:chain_getFinalizedHead()
+        nested = api:with_deadline(10000, function(inner) return inner:time_left() end)
+        api:chain_getFinalizedHead()
+        api:chain_getFinalizedHead()
+    end)
+    socket.gettime, http.request = original_time, original_request
+    assert(timeouts[1] == 3 and timeouts[2] == 1 and #timeouts == 2)
+    assert(nested == 1)
+    assert(not ok and RPC.is_timeout(err) and err:match("chain_getFinalizedHead"))
+    assert(rpc.deadline == nil and rpc:time_left() == nil)
+end)
+
+test("RPC: Metrics count requests, errors and latency", function()
+    local socket = require("socket")
+    local http = require("socket.http")
+    local original_time, original_request, now = socket.gettime, http.request, 1000
+    local script = {
+        '{"jsonrpc":"2.0","id":1,"result":"0x00"}',
+        "refused",
+        "timeout",
+        '{"jsonrpc":"2.0","id":4,"error":{"code":1010,"message":"Invalid Transaction"}}',
+        '{"jsonrpc":"2.0","id":5,"result":"0x00"}'
+    }
+    socket.gettime = function() return now end
+    local rpc = RPC.new("http://localhost")
+    http.request = function(req)
+        now = now + 0.04
+        local reply = table.remove(script, 1)
+        if reply == "refused" or reply == "timeout" then return nil, reply end
+        req.sink(reply)
+        return 1, 200
+    end
+    for _ = 1, 5 do pcall(rpc.chain_getFinalizedHead, rpc) end
+    local m = rpc:metrics()
+    socket.gettime, http.request = original_time, original_request
+    assert(m.requests == 5 and m.errors == 3, "requests/errors")
+    assert(m.transport_errors == 1 and m.timeouts == 1 and m.rpc_errors == 1)
+    assert(m.reconnects == 1 and m.connected, "back up after the transport error")
+    assert(math.abs(m.avg_latency_ms - 40) < 1e-6 and math.abs(m.max_latency_ms - 40) < 1e-6)
+    assert(m.bytes_in > 0 and m.bytes_out > 0 and m.methods.chain_getFinalizedHead == 5)
+    assert(m.uptime_s > 0 and m.subscription_lag_ms == nil)
+    rpc:reset_metrics()
+    assert(rpc:metrics().requests == 0)
+end)
+
+test("RPC: Raw passthrough sends params and returns result text unchanged", function()
+    local http = require("socket.http")
+    local original_request = http.request
+    local replies = {
+        '{"jsonrpc":"2.0","result":{"n":340282366920938463463374607431768211455,"s":"a\\"}b","l":[]},"id":1}',
+        '{"id":2,"jsonrpc":"2.0","result":null}',
+        '{"jsonrpc":"2.0","id":3,"error":{"code":-32601,"message":"Method not found"}}'
+    }
+    local bodies = {}
+    http.request = function(req)
+        table.insert(bodies, req.source())
+        req.sink(table.remove(replies, 1))
+        return 1, 200
+    end
+    local rpc = RPC.new("http://localhost", { raw_methods = { "babe_*", "system_health" } })
+    local ok1, result = pcall(rpc.rpc_raw, rpc, "babe_epochAuthorship", '[]')
+    local ok2, none = pcall(rpc.rpc_raw, rpc, "system_health")
+    local ok3, err3 = pcall(rpc.rpc_raw, rpc, "babe_unknown", '{"x": [1, 2]}')
+    local ok4, err4 = pcall(rpc.rpc_raw, rpc, "author_rotateKeys")
+    local open = RPC.new("http://localhost")
+    local ok5, err5 = pcall(open.rpc_raw, open, "node_custom", "[1, 2")
+    http.request = original_request
+    assert(ok1 and result == '{"n":340282366920938463463374607431768211455,"s":"a\\"}b","l":[]}', tostring(result))
+    assert(ok2 and none == "null")
+    assert(bodies[3]:find('"params":{"x": [1, 2]}', 1, true), "params sent verbatim")
+    assert(not ok3 and err3:match("RPC error: Method not found"))
+    assert(not ok4 and err4:match("author_rotateKeys is not in this client's raw_methods"))
+    assert(not ok5 and err5:match("params_json must be a JSON array or object"))
+    assert(#bodies == 3 and rpc:metrics().methods.babe_epochAuthorship == 1)
+end)
+
+test("RPC: Batch returns results by position", function()
+    local http = require("socket.http")
+    local original_request = http.request
+    local bodies, reply = {}, nil
+    http.request = function(req)
+        local body = req.source()
+        table.insert(bodies, body)
+        local first = tonumber(body:match('"id":(%d+)'))
+        req.sink(reply(first))
+        return 1, 200
+    end
+    local rpc = RPC.new("http://localhost")
+    rpc.id = 7
+    reply = function(first)
+        -- Out of order, with a failed call and one left unanswered
+        return '[{"jsonrpc":"2.0","id":' .. (first + 1) .. ',"error":{"code":-32602,"message":"Invalid params"}},'
+            .. '{"jsonrpc":"2.0","id":' .. first .. ',"result":"0xaa"},'
+            .. '{"jsonrpc":"2.0","id":' .. (first + 3) .. ',"result":{"peers":3}}]'
+    end
+    local ok, results, errors = pcall(rpc.rpc_batch, rpc, {
+        { "chain_getFinalizedHead" },
+        { "chain_getBlockHash", { "x" } },
+        { "state_getStorage", '["0x26aa"]' },
+        { "system_health" }
+    })
+    assert(ok, tostring(results))
+    assert(results.n == 4 and results[1] == "0xaa" and results[4].peers == 3)
+    assert(results[2] == nil and errors[2]:match("^RPC error: Invalid params"))
+    assert(errors[3]:match("no response to state_getStorage") and errors[1] == nil)
+    assert(bodies[1]:sub(1, 1) == "[" and bodies[1]:find('"params":["0x26aa"]', 1, true))
+    local m = rpc:metrics()
+    assert(m.requests == 1 and m.methods.system_health == 1 and m.methods.chain_getBlockHash == 1)
+
+    reply = function(first) return '[{"jsonrpc":"2.0","id":' .. first .. ',"result":5}]' end
+    results = rpc:rpc_batch('[{"method":"system_peers","params":[]}]')
+    assert(results.n == 1 and results[1] == 5)
+    assert(#bodies == 2 and select(1, rpc:rpc_batch({})).n == 0, "empty batch sends nothing")
+    reply = function() return '{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"Batch too large"}}' end
+    local ok2, err2 = pcall(rpc.rpc_batch, rpc, { { "system_name" } })
+    local ok3, err3 = pcall(rpc.rpc_batch, rpc, { { "chain_subscribeNewHeads" } })
+    http.request = original_request
+    assert(not ok2 and err2:match("Batch too large"))
+    assert(not ok3 and err3:match("subscription API"))
+end)
+
 print("\n=== RPC Client Test Results ===")
 print("Passed: " .. tests_passed)
 print("Failed: " .. tests_failed)
@@ -0,0 +1,137 @@
+-- test/test_runtime.lua
+-- Unit tests for runtime code download and upgrade proposals
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Runtime = require("sublua.runtime")
+local Call = require("sublua.call")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Runtime Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+
+local FIXTURE = {
+    types = {
+        { "SudoCall", { path = { "pallet_sudo", "pallet", "Call" }, variant = {
+            { name = "sudo", index = 0, fields = { { name = "call", type = "RuntimeCall" } } },
+            { name = "sudo_unchecked_weight", index = 1, fields = {
+                { name = "call", type = "RuntimeCall" }, { name = "weight", type = "Weight" } } }
+        } } },
+        { "PreimageCall", { path = { "pallet_preimage", "pallet", "Call" }, variant = {
+            { name = "note_preimage", index = 0, fields = { { name = "bytes", type = "Vec<u8>" } } }
+        } } },
+        { "Bounded", { path = { "frame_support", "traits", "preimages", "Bounded" }, variant = {
+            { name = "Legacy", index = 0, fields = { { name = "hash", type = "H256" } } },
+            { name = "Inline", index = 1, fields = { { type = "Vec<u8>" } } },
+            { name = "Lookup", index = 2, fields = { { name = "hash", type = "H256" }, { name = "len", type = "u32" } } }
+        } } },
+        { "DemocracyCall", { path = { "pallet_democracy", "pallet", "Call" }, variant = {
+            { name = "propose", index = 0, fields = {
+                { name = "proposal", type = "Bounded" }, { name = "value", type = "Compact<u128>" } } }
+        } } }
+    },
+    pallets = {
+        { name = "Sudo", index = 255, calls = "SudoCall" },
+        { name = "Preimage", index = 10, calls = "PreimageCall" },
+        { name = "Democracy", index = 14, calls = "DemocracyCall", constants = {
+            { name = "MinimumDeposit", type = "u128", value = "\232\3" .. string.rep("\0", 14) }
+        } }
+    }
+}
+
+-- A minimal wasm module: magic, version, one empty custom section
+local WASM = "\0asm\1\0\0\0" .. "\0\4\3abc"
+local COMPRESSED = "\82\188\83\118\70\219\142\5" .. "zstd-frame"
+
+local function api_with_code(code)
+    local api = Mock.api({ [Runtime.CODE_KEY] = code and "0x" .. to_hex(code) }, FIXTURE)
+    return api
+end
+
+test("Runtime: inspect code", function()
+    local info = Runtime.inspect(WASM)
+    assert(info.size == #WASM and info.hash == "0x" .. to_hex(crypto.blake2b(WASM, 32)))
+    assert(info.compressed == false and info.state_version == 0)
+    local c = Runtime.inspect("0x" .. to_hex(COMPRESSED))
+    assert(c.compressed == true and c.state_version == nil)
+    assert(not pcall(Runtime.inspect, "/nonexistent/runtime.wasm"))
+end)
+
+test("Runtime: get_wasm reads :code and writes the file", function()
+    local api = api_with_code(COMPRESSED)
+    local at = "0x" .. string.rep("cd", 32)
+    local path = os.tmpname()
+    local code, info = Runtime.get_wasm(api, at, path)
+    assert(code == COMPRESSED and info.compressed and info.path == path)
+    assert(api.calls[1][2] == "0x3a636f6465" and api.calls[1][3] == at)
+    local f = io.open(path, "rb")
+    assert(f:read("*a") == COMPRESSED)
+    f:close()
+    assert(Runtime.inspect(path).hash == info.hash, "files load as code")
+    os.remove(path)
+    assert(not pcall(Runtime.get_wasm, api_with_code(nil)), "missing code is an error")
+end)
+
+test("Runtime: sudo upgrade wraps set_code", function()
+    local api = api_with_code(WASM)
+    local txs, details = Runtime.propose_upgrade(api, alice, "sudo", WASM)
+    assert(#txs == 1 and #details.transactions == 1)
+    local call = Transaction.decode(api:get_metadata(), txs[1]).call
+    assert(call.pallet == "Sudo" and call.name == "sudo_unchecked_weight")
+    assert(tonumber(call.args.weight.ref_time) == 0 and tonumber(call.args.weight.proof_size) == 0)
+    local inner = call.args.call
+    assert(inner.variant == "System" and inner.value.variant == "set_code")
+    assert(inner.value.value.code == "0x" .. to_hex(WASM))
+    assert(details.code_hash == Runtime.inspect(WASM).hash)
+    assert(details.call_hash == Call.call_hash(Runtime.encode_set_code(api, WASM)))
+end)
+
+test("Runtime: democracy upgrade notes a preimage and proposes it", function()
+    local api = api_with_code(WASM)
+    local txs, details = Runtime.propose_upgrade(api, alice, "democracy", "0x" .. to_hex(WASM))
+    assert(#txs == 2)
+    local meta = api:get_metadata()
+    local note, propose = Transaction.decode(meta, txs[1]), Transaction.decode(meta, txs[2])
+    assert(note.call.pallet == "Preimage" and propose.call.pallet == "Democracy")
+    assert(propose.nonce == note.nonce + 1, "consecutive nonces")
+    local proposal = propose.call.args.proposal
+    assert(proposal.variant == "Lookup" and proposal.value.hash == details.call_hash and proposal.value.len == details.len)
+    assert(tonumber(propose.call.args.value) == 1000, "defaults to MinimumDeposit")
+    local _, d = Runtime.propose_upgrade(api, alice, "democracy", WASM, { value = 5000, nonce = 7 })
+    assert(d.transactions[1].nonce == 7 and d.transactions[2].nonce == 8)
+    assert(not pcall(Runtime.propose_upgrade, api, alice, "council", WASM), "unknown mode")
+end)
+
+print("\n=== Runtime Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All runtime tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,123 @@
+-- test/test_safety.lua
+-- Unit tests for per-client transfer limits, allowed destinations and confirmation (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Safety = require("sublua.safety")
+local Transaction = require("sublua.transaction")
+local Call = require("sublua.call")
+local Utility = require("sublua.utility")
+local Keyring = require("sublua.keyring")
+local RPC = require("sublua.rpc")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Safety Module Tests ===\n")
+
+local UTILITY = {
+    types = {
+        { "Vec<RuntimeCall>", { sequence = "RuntimeCall" } },
+        { "UtilityCall", { path = { "pallet_utility", "pallet", "Call" }, variant = {
+            { name = "batch_all", index = 2, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } }
+        } } }
+    },
+    pallets = { { name = "Utility", index = 26, calls = "UtilityCall" } }
+}
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local charlie = Keyring.from_uri("//Charlie")
+
+local function transfer(api, to, value)
+    return Call.encode_call_json(api:get_metadata(), "Balances", "transfer_keep_alive", { dest = to.address, value = value })
+end
+
+local function refusal(api, call_bytes, opts)
+    local ok, err = pcall(Transaction.create_signed_from_api, api, alice, call_bytes, opts)
+    assert(not ok, "expected the transaction to be refused")
+    return err
+end
+
+test("Safety: Transfers above the limit are refused", function()
+    local api = Mock.api({}, UTILITY)
+    Safety.set_max_transfer(api, "1000000000000")
+    assert(Transaction.create_signed_from_api(api, alice, transfer(api, bob, "1000000000000")):match("^0x"))
+    assert(refusal(api, transfer(api, bob, "1000000000001"))
+        == "Safety: Balances.transfer_keep_alive of 1000000000001 exceeds the transfer limit of 1000000000000")
+    local batch = Utility.encode_batch(api, { transfer(api, bob, 1), transfer(api, charlie, "5000000000000") })
+    assert(refusal(api, batch):match("of 5000000000000 exceeds"), "nested calls are checked")
+    local all = Call.encode_call_json(api:get_metadata(), "Balances", "transfer_all", { dest = bob.address, keep_alive = false })
+    assert(refusal(api, all):match("^Safety: Balances%.transfer_all has no amount"))
+    local remark = Call.encode_call(api:get_metadata(), "System", "remark", { remark = "0x01" })
+    assert(Transaction.create_signed_from_api(api, alice, remark), "other calls are not limited")
+    Safety.set_max_transfer(api, nil)
+    assert(Transaction.create_signed_from_api(api, alice, all))
+    assert(not pcall(Safety.set_max_transfer, api, "ten"))
+end)
+
+test("Safety: Only allowed destinations receive transfers", function()
+    local api = Mock.api({}, UTILITY)
+    Safety.set_allowed_destinations(api, { bob.address })
+    assert(Transaction.create_signed_from_api(api, alice, transfer(api, bob, 10)))
+    assert(refusal(api, transfer(api, charlie, 10))
+        == "Safety: Balances.transfer_keep_alive to " .. charlie.address .. " is not in the allowed destinations")
+    assert(refusal(api, Utility.encode_batch(api, { transfer(api, bob, 1), transfer(api, charlie, 1) })):match(charlie.address))
+    local ok, err = pcall(Safety.set_allowed_destinations, api, { bob.address, "typo" })
+    assert(not ok and err:match("^addresses%.2: "))
+end)
+
+test("Safety: Confirmation needs the digest of the intent", function()
+    local api = Mock.api({}, UTILITY)
+    Safety.set_confirmation(api, true)
+    local call = transfer(api, bob, "250")
+    local digest, text = Safety.confirmation(refusal(api, call))
+    assert(digest and #digest == 66)
+    assert(text:match("^" .. alice.address .. " signs Balances%.transfer_keep_alive ") and text:find('"250"', 1, true))
+    assert(refusal(api, call, { confirm = "0x" .. string.rep("00", 32) }):match("^Confirmation required"))
+    assert(refusal(api, transfer(api, bob, "251"), { confirm = digest }):match("^Confirmation required"),
+        "a digest confirms one intent only")
+    assert(Transaction.create_signed_from_api(api, alice, call, { confirm = digest }))
+    local intent = Safety.intent(api, alice, call)
+    assert(intent.digest == digest and #intent.transfers == 1 and intent.transfers[1].amount == "250")
+
+    local hash, report = Transaction.submit(api, alice, call)
+    assert(hash == nil and Safety.confirmation(report.error) == digest, "submit reports the digest to confirm")
+    Safety.set_confirmation(api, false)
+    assert(Safety.check(api, alice, call) == nil)
+end)
+
+test("Safety: Clients carry their own settings", function()
+    local client = RPC.new("http://localhost")
+    client:set_max_transfer(5)
+    client:set_allowed_destinations({ bob.address })
+    client:set_confirmation(true)
+    assert(client.safety.max_transfer == "5" and client.safety.confirm)
+    assert(RPC.new("http://localhost").safety == nil)
+    client:set_allowed_destinations(nil)
+    assert(client.safety.allowed == nil)
+end)
+
+print("\n=== Safety Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All safety tests passed!")
+    os.exit(0)
+end
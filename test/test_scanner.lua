@@ -0,0 +1,164 @@
+-- test/test_scanner.lua
+-- Unit tests for the historical block range scanner
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Scanner = require("sublua.scanner")
+local Call = require("sublua.call")
+local Keyring = require("sublua.keyring")
+local Scale = require("sublua.scale")
+local Transaction = require("sublua.transaction")
+local Address = require("sublua.address")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Block Scanner Tests ===\n")
+
+local ALICE = Keyring.from_uri("//Alice")
+local BOB = Keyring.from_uri("//Bob")
+local CHARLIE = string.rep("\3", 32)
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function u128(n) return Scale.encode_u64(n) .. string.rep("\0", 8) end
+local function record(index, event)
+    return "\0" .. Scale.encode_u32(index) .. event .. Scale.encode_compact(0)
+end
+local function transfer(from, to, amount)
+    return "\4\2" .. from .. to .. u128(amount)
+end
+
+-- Chain mock: blocks[n] = { extrinsics = { hex }, events = { record } }
+local function chain_api(blocks)
+    local api = Mock.api()
+    api.fetched = {}
+    function api:chain_getBlock(hash)
+        local n = tonumber(hash:sub(3), 16)
+        if blocks[n] == "missing" then error("block not found") end
+        table.insert(self.fetched, n)
+        local block = blocks[n] or {}
+        return { block = { header = { number = string.format("0x%x", n) }, extrinsics = block.extrinsics or {} } }
+    end
+    function api:state_getStorage(key, at)
+        local block = blocks[tonumber(at:sub(3), 16)]
+        local events = type(block) == "table" and block.events or {}
+        return "0x" .. to_hex(Scale.encode_compact(#events) .. table.concat(events))
+    end
+    return api
+end
+
+local function signed_transfer(api, signer, dest, nonce)
+    local call = Call.encode_call(api, "Balances", "transfer_keep_alive",
+        { dest = { variant = "Id", value = "0x" .. to_hex(dest) }, value = 5 })
+    return Transaction.create_signed_from_api(api, signer, call, { nonce = nonce })
+end
+
+local function sample_chain()
+    local blocks = {}
+    local api = chain_api(blocks)
+    blocks[2] = {
+        extrinsics = { signed_transfer(api, ALICE, BOB.pubkey, 0) },
+        events = { record(0, transfer(ALICE.pubkey, BOB.pubkey, 5)) }
+    }
+    blocks[3] = { events = { record(0, transfer(CHARLIE, CHARLIE, 1)) } }
+    blocks[5] = {
+        extrinsics = { signed_transfer(api, BOB, CHARLIE, 0) },
+        events = { record(0, transfer(BOB.pubkey, CHARLIE, 5)), record(0, transfer(CHARLIE, ALICE.pubkey, 7)) }
+    }
+    return api
+end
+
+test("Scanner: compile filters", function()
+    local f = Scanner.compile('{"events":[{"pallet":"Balances","name":"Transfer"}]}')
+    assert(f.events[1].pallet == "Balances" and f.calls == nil)
+    f = Scanner.compile({ address = BOB.address })
+    assert(f.account == "0x" .. to_hex(BOB.pubkey) and f.events == true and f.calls == true)
+    assert(Scanner.compile({ events = "slashing" }).events[1].pallet == "Staking")
+    assert(not pcall(Scanner.compile, {}))
+    assert(not pcall(Scanner.compile, { events = "nope" }))
+end)
+
+test("Scanner: events only skip block bodies", function()
+    local api = sample_chain()
+    local seen = {}
+    local cursor = Scanner.scan(api, 1, 6, { events = { { pallet = "Balances", name = "Transfer" } } }, function(item)
+        table.insert(seen, item)
+    end)
+    assert(cursor.done and cursor.scanned == 6 and cursor.matched == 4 and cursor.next_block == 7)
+    assert(#api.fetched == 0)
+    assert(seen[1].kind == "event" and seen[1].block_number == 2 and seen[1].extrinsic_index == 0)
+    assert(seen[4].event.fields.amount == "7" and seen[4].event_index == 1 and seen[3].event_index == 0)
+end)
+
+test("Scanner: address filter across calls and events", function()
+    local api = sample_chain()
+    local seen = {}
+    Scanner.scan(api, 1, 6, { address = ALICE.address }, function(item) table.insert(seen, item) end)
+    assert(#seen == 3, #seen)
+    assert(seen[1].kind == "extrinsic" and seen[1].extrinsic.address == ALICE.address)
+    assert(seen[1].extrinsic.call.name == "transfer_keep_alive" and seen[1].extrinsic_index == 0)
+    assert(seen[2].kind == "event" and seen[2].block_number == 2)
+    assert(seen[3].block_number == 5 and seen[3].event.fields.to == "0x" .. to_hex(ALICE.pubkey))
+
+    seen = {}
+    Scanner.scan(api, 1, 6, { calls = { { pallet = "Balances" } }, address = "0x" .. to_hex(CHARLIE) },
+        function(item) table.insert(seen, item) end)
+    assert(#seen == 1 and seen[1].block_number == 5 and seen[1].extrinsic.address == BOB.address)
+
+    api.ss58_format = 0
+    seen = {}
+    Scanner.scan(api, 1, 6, { events = true, address = ALICE.address }, function(item) table.insert(seen, item) end)
+    assert(#seen == 2 and seen[2].event.fields.to == Address.encode(ALICE.pubkey, 0), "SS58 events still match")
+end)
+
+test("Scanner: progress and resumable cursor", function()
+    local api = sample_chain()
+    local filter = { events = { { pallet = "Balances" } } }
+    local progress = {}
+    local seen = {}
+    local cursor = Scanner.scan(api, 1, 6, filter, function(item)
+        table.insert(seen, item.block_number)
+        if #seen == 3 then return false end
+    end, { progress_every = 2, on_progress = function(c) table.insert(progress, c.next_block) end })
+    assert(not cursor.done and cursor.next_block == 5 and cursor.next_item == 1)
+    assert(#progress == 3 and progress[1] == 3 and progress[2] == 5 and progress[3] == 5)
+
+    local saved = require("cjson").decode(require("cjson").encode(cursor))
+    cursor = Scanner.scan(api, 1, 6, filter, function(item) table.insert(seen, item.block_number) end, { cursor = saved })
+    assert(cursor.done and cursor.matched == 4 and cursor.scanned == 6)
+    assert(#seen == 4 and seen[3] == 5 and seen[4] == 5)
+    assert(not pcall(Scanner.scan, api, 2, 6, filter, function() end, { cursor = saved }))
+end)
+
+test("Scanner: failed block leaves the cursor on it", function()
+    local api = chain_api({ [4] = "missing" })
+    local cursor, err = Scanner.scan(api, 1, 6, { calls = { { pallet = "System" } } }, function() end)
+    assert(not cursor.done and cursor.next_block == 4 and err:match("^Block 4: .*block not found"))
+end)
+
+print("\n=== Block Scanner Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Block Scanner tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,82 @@
+-- test/test_selfcheck.lua
+-- Unit tests for the encoding self-check against reference vectors
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Selfcheck = require("sublua.selfcheck")
+local Transaction = require("sublua.transaction")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Selfcheck Module Tests ===\n")
+
+local function check(name, report)
+    for _, c in ipairs(report.checks) do
+        if c.name == name then return c end
+    end
+    error("no check named " .. name)
+end
+
+test("Selfcheck: Low-level encoders match the reference vectors", function()
+    local report = Selfcheck.encoding()
+    assert(report.ok, Selfcheck.format(report))
+    assert(check("era mortal(32768, 20000)", report).got == "4e9c")
+    assert(check("signature over the reference payload (low-level)", report).ok)
+    assert(Selfcheck.format(report):match("^%d+ checks, 0 mismatches$"))
+end)
+
+test("Selfcheck: Metadata-driven encoders agree byte for byte", function()
+    local report = Selfcheck.encoding(Mock.metadata())
+    assert(report.ok, Selfcheck.format(report))
+    assert(check("compact 340282366920938463463374607431768211455 (codec)", report).ok)
+    assert(check("signed extrinsic (low-level vs metadata)", report).ok)
+    assert(#report.checks > #Selfcheck.encoding().checks)
+end)
+
+test("Selfcheck: Regressions are reported by name", function()
+    local encode_era = Transaction.encode_era
+    Transaction.encode_era = function(era)
+        if not era or era.immortal then return "\0" end
+        return string.char(era.phase % 256, math.floor(era.phase / 256))  -- phase only: wrong
+    end
+    local ok, report = pcall(Selfcheck.encoding)
+    Transaction.encode_era = encode_era
+    assert(ok, report)
+    assert(not report.ok and report.failures == 2)
+    local era = check("era mortal(64, 42)", report)
+    assert(not era.ok and era.expected == "a502" and era.got == "2a00")
+    assert(Selfcheck.format(report):find("MISMATCH era mortal(64, 42): expected a502, got 2a00", 1, true))
+
+    local encode_compact = Scale.encode_compact
+    Scale.encode_compact = function() error("boom") end
+    ok, report = pcall(Selfcheck.encoding)
+    Scale.encode_compact = encode_compact
+    assert(ok, report)
+    assert(check("compact 64 (scale)", report).got:match("^error: .*boom"), "a raising encoder does not stop the run")
+end)
+
+print("\n=== Selfcheck Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All selfcheck tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,124 @@
+-- test/test_session.lua
+-- Unit tests for session key management
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;" .. package.path
+
+local Session = require("sublua.session")
+local RPC = require("sublua.rpc")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Session Module Tests ===\n")
+
+-- Two 32-byte keys, as returned by author_rotateKeys
+local KEYS = "0x" .. string.rep("11", 32) .. string.rep("22", 32)
+
+test("Session: set_keys encoding with empty proof", function()
+    local call = Session.encode_set_keys(9, 0, KEYS)
+    -- call index (2) + keys (64) + Compact(0) proof (1)
+    assert(#call == 67, "expected 67 bytes, got " .. #call)
+    assert(to_hex(call:sub(1, 2)) == "0900")
+    assert(to_hex(call:sub(3, 66)) == KEYS:sub(3))
+    assert(string.byte(call, 67) == 0, "empty proof should encode as 0x00")
+end)
+
+test("Session: set_keys encoding with proof", function()
+    local call = Session.encode_set_keys(9, 0, KEYS, "\1\2\3")
+    assert(to_hex(call:sub(67)) == "0c010203", "got: " .. to_hex(call:sub(67)))
+end)
+
+test("Session: set_keys rejects malformed keys", function()
+    assert(not pcall(Session.encode_set_keys, 9, 0, "0x"), "should reject empty keys")
+    assert(not pcall(Session.encode_set_keys, 9, 0, "0x123"), "should reject odd-length hex")
+    local ok, err = pcall(Session.encode_set_keys, 9, 0, "0x1234", nil, "sassafras")
+    assert(not ok and tostring(err):match("layout expects 64"), "unexpected error: " .. tostring(err))
+end)
+
+test("Session: set_keys accepts Polkadot keys with a 33-byte BEEFY key", function()
+    local keys = "0x" .. string.rep("11", 5 * 32) .. "02" .. string.rep("33", 32)
+    assert(Session.layout_size("polkadot") == 193)
+    local call = Session.encode_set_keys(9, 0, keys, nil, "polkadot")
+    assert(#call == 2 + 193 + 1)
+    local split = assert(Session.split_keys(keys, "polkadot"))
+    assert(#split == 6 and split[6].name == "beefy" and split[6].crypto == "ecdsa")
+    assert(split[6].public == "0x02" .. string.rep("33", 32))
+end)
+
+test("Session: layout sizes cover ecdsa, bandersnatch and BLS keys", function()
+    assert(Session.layout_size("polkadot") == 5 * 32 + 33)
+    assert(Session.layout_size("sassafras") == 64)
+    assert(Session.layout_size("beefy_bls") == 64 + 177)
+    assert(not pcall(Session.layout_size, { { "x", "ed448", "xxxx" } }))
+end)
+
+test("Session: split_keys of a bandersnatch layout", function()
+    local keys = assert(Session.split_keys(KEYS, "sassafras"))
+    assert(#keys == 2)
+    assert(keys[1].crypto == "ed25519" and keys[1].local_signing == true)
+    assert(keys[2].name == "sassafras" and keys[2].key_type == "sass")
+    assert(keys[2].public == "0x" .. string.rep("22", 32))
+    assert(keys[2].local_signing == false, "bandersnatch keys are signed by the node")
+    local none, err = Session.split_keys(KEYS, "polkadot")
+    assert(none == nil and err:match("193"), "got: " .. tostring(err))
+end)
+
+test("Session: set_keys accepts BLS-sized keys", function()
+    local keys = "0x" .. string.rep("11", 64) .. string.rep("33", 177)
+    local call = Session.encode_set_keys(9, 0, keys, nil, "beefy_bls")
+    assert(#call == 2 + 241 + 1)
+end)
+
+test("Session: check_keys queries each key type", function()
+    local asked = {}
+    local api = { author_hasKey = function(_, pub, key_type)
+        table.insert(asked, key_type)
+        return key_type == "gran"
+    end }
+    local keys = assert(Session.check_keys(api, KEYS, "sassafras"))
+    assert(table.concat(asked, ",") == "gran,sass")
+    assert(keys[1].present == true and keys[2].present == false)
+end)
+
+test("Session: purge_keys encoding", function()
+    assert(to_hex(Session.encode_purge_keys(9, 1)) == "0901")
+end)
+
+test("Session: rotate_keys requires allow_unsafe", function()
+    local api = RPC.new("http://localhost:9944")
+    local ok, err = pcall(Session.rotate_keys, api)
+    assert(not ok)
+    assert(tostring(err):match("allow_unsafe"), "unexpected error: " .. tostring(err))
+end)
+
+test("Session: allow_unsafe flag stored on client", function()
+    local api = RPC.new("http://localhost:9944", { allow_unsafe = true })
+    assert(api.allow_unsafe == true)
+end)
+
+print("\n=== Session Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All session tests passed!")
+    os.exit(0)
+end
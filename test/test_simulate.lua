@@ -0,0 +1,143 @@
+-- test/test_simulate.lua
+-- Unit tests for call simulation: DryRunApi previews and runs on a fork (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Simulate = require("sublua.simulate")
+local Transaction = require("sublua.transaction")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Scale = require("sublua.scale")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Simulate Module Tests ===\n")
+
+local ORIGINS = {
+    types = {
+        { "RawOrigin", { path = { "frame_support", "dispatch", "RawOrigin" }, variant = {
+            { name = "Root", index = 0 },
+            { name = "Signed", index = 1, fields = { { type = "AccountId32" } } },
+            { name = "None", index = 2 }
+        } } },
+        { "OriginCaller", { path = { "runtime", "OriginCaller" }, variant = {
+            { name = "system", index = 0, fields = { { type = "RawOrigin" } } }
+        } } }
+    }
+}
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local function hex_of(pair) return "0x" .. to_hex(pair.pubkey) end
+
+-- Chain answering DryRunApi_dry_run_call with the given effects after the Ok byte
+local function dry_run_api(effects)
+    local api = Mock.api({}, ORIGINS)
+    function api:state_call(method, data, at)
+        self.dry_run = { method = method, data = data, at = at }
+        return "0x" .. to_hex(effects(self:get_metadata()))
+    end
+    return api
+end
+
+local function transfer_event(meta)
+    local event_type = Codec.find_type(meta, "runtime::RuntimeEvent")
+    return Codec.encode(meta, event_type, { variant = "Balances", value = { variant = "Transfer",
+        value = { from = hex_of(alice), to = hex_of(bob), amount = "10" } } })
+end
+
+test("Simulate: Dry run reports outcome, weight and events", function()
+    local api = dry_run_api(function(meta)
+        return "\0" .. "\0" .. "\1" .. Scale.encode_compact(1000) .. Scale.encode_compact(20) .. "\0"
+            .. Scale.encode_compact(1) .. transfer_event(meta) .. "\0" .. "\0"
+    end)
+    local result = Simulate.simulate_call(api, "Balances", "transfer_keep_alive",
+        { dest = bob.address, value = "10" }, alice.address, { at = "0xabc" })
+    assert(result.mode == "dry_run" and result.ok and result.pays_fee)
+    assert(result.weight.ref_time == 1000 and result.weight.proof_size == 20)
+    assert(#result.events == 1 and result.events[1].pallet == "Balances" and result.events[1].name == "Transfer")
+    assert(result.events[1].fields.amount == "10" and #result.diffs == 0)
+
+    local sent = api.dry_run
+    assert(sent.method == "DryRunApi_dry_run_call" and sent.at == "0xabc")
+    local expected_origin = "0x0001" .. to_hex(alice.pubkey)
+    assert(sent.data:sub(1, #expected_origin) == expected_origin, "system Signed origin first")
+    assert(sent.data:sub(-8) == "04000000", "XCM version last")
+end)
+
+test("Simulate: Failed dispatches name the pallet error", function()
+    local api = dry_run_api(function(meta)
+        local err = Codec.encode(meta, Codec.find_type(meta, "sp_runtime::DispatchError"),
+            { variant = "Module", value = { index = 4, error = "0x02000000" } })
+        return "\0" .. "\1" .. "\0" .. "\1" .. err .. Scale.encode_compact(0) .. "\0" .. "\0"
+    end)
+    local result = Simulate.simulate_call(api, "Balances", "transfer_keep_alive", '{"dest": "' .. bob.address
+        .. '", "value": "10"}', "Root")
+    assert(not result.ok and result.weight == nil and result.pays_fee == false)
+    assert(result.module.pallet == "Balances" and result.module.name == "InsufficientBalance")
+    assert(api.dry_run.data:sub(1, 6) == "0x0000", "Root origin")
+    assert(#result.events == 0)
+end)
+
+test("Simulate: Runtimes without the API fail plainly", function()
+    local api = dry_run_api(function() return "\1\0" end)
+    local ok, err = pcall(Simulate.simulate_call, api, "System", "remark", { remark = "0x01" }, alice)
+    assert(not ok and err:match("Dry run failed: Unimplemented"))
+    assert(not pcall(Simulate.origin, 42))
+    assert(Simulate.origin({ Council = { Members = { 3, 5 } } }).Council, "OriginCaller values pass through")
+end)
+
+test("Simulate: Fork runs compare watched storage", function()
+    local key = Storage.build_key(Mock.metadata(), "Balances", "TotalIssuance")
+    local api = Mock.api({ [key] = "0x" .. "0a" .. string.rep("00", 15) })
+    api.signer = alice
+    local submitted
+    function api:author_submitExtrinsic(hex) submitted = hex return "0x" .. string.rep("ee", 32) end
+    function api:request(method, params)
+        assert(method == "dev_newBlock" and type(params[1]) == "table")
+        self.storage[key] = "0x" .. "14" .. string.rep("00", 15)
+        return "0x" .. string.rep("bb", 32)
+    end
+    local original = Transaction.get_result
+    Transaction.get_result = function(_, extrinsic, block_hash)
+        assert(extrinsic == submitted and block_hash == "0x" .. string.rep("bb", 32))
+        return { success = true, events = { { pallet = "Balances", name = "Transfer" } }, fee_paid = "7" }
+    end
+    local ok, result = pcall(Simulate.simulate_call, api, "Balances", "transfer_keep_alive",
+        { dest = bob.address, value = "10" }, nil, { fork = true, watch = { { "Balances", "TotalIssuance" } } })
+    Transaction.get_result = original
+    assert(ok, result)
+    assert(result.mode == "fork" and result.ok and result.fee_paid == "7" and result.events[1].name == "Transfer")
+    assert(#result.diffs == 1 and result.diffs[1].entry == "TotalIssuance")
+    assert(result.diffs[1].before == "10" and result.diffs[1].after == "20")
+end)
+
+print("\n=== Simulate Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All simulate tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,131 @@
+-- test/test_snapshot.lua
+-- Unit tests for storage snapshots and offline reads
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Snapshot = require("sublua.snapshot")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Snapshot Module Tests ===\n")
+
+local FIXTURE = {
+    pallets = {
+        { name = "Counter", index = 90, storage = { prefix = "Counter", entries = {
+            { name = "Total", plain = "u32", modifier = 1, default = "\0\0\0\0" },
+            { name = "Scores", map = { hashers = { "Twox64Concat" }, key = "u32", value = "u64" } }
+        } } },
+        { name = "Other", index = 91, storage = { prefix = "Other", entries = {
+            { name = "Value", plain = "u32" }
+        } } }
+    }
+}
+
+local AT = "0x" .. string.rep("ab", 32)
+local PATH = os.tmpname()
+
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function live_api()
+    local api = Mock.api({}, FIXTURE)
+    function api:state_getMetadata() return Mock.hex(FIXTURE) end
+    put(api, "Counter", "Total", nil, 7)
+    for i = 1, 5 do put(api, "Counter", "Scores", { i }, tostring(i * 100)) end
+    put(api, "Other", "Value", nil, 1)
+    return api
+end
+
+test("Snapshot: dump selected pallets to a file", function()
+    local api = live_api()
+    local info = Snapshot.dump(api, '["Counter"]', AT, PATH)
+    assert(info.path == PATH and info.block_hash == AT and info.keys == 6)
+    for _, call in ipairs(api.calls) do
+        if call[1] == "state_queryStorageAt" then assert(call[3] == AT, "read at the wrong block") end
+    end
+    local snap = Snapshot.load(PATH)
+    assert(snap.block_hash == AT and snap.state.spec_version == 100)
+    assert(snap.state.pallets[1] == "Counter" and #snap.keys == 6)
+    assert(not io.open(PATH .. ".tmp", "rb"), "temporary file left behind")
+end)
+
+test("Snapshot: query values offline", function()
+    assert(Snapshot.query(PATH, "Counter", "Total") == 7)
+    local snap = Snapshot.load(PATH)
+    assert(Snapshot.query(snap, "Counter", "Scores", { 3 }) == "300")
+    assert(Snapshot.query(snap, "Counter", "Scores", { 9 }) == nil)
+    assert(Snapshot.query(snap, "Other", "Value") == nil, "pallets outside the snapshot read as absent")
+end)
+
+test("Snapshot: paged dump and map iteration", function()
+    local api = live_api()
+    local pages = 0
+    local keys_paged = api.state_getKeysPaged
+    function api:state_getKeysPaged(prefix, count, start)
+        pages = pages + 1
+        local out = {}
+        for _, k in ipairs(keys_paged(self, prefix)) do
+            if #out < count and (not start or k > start) then table.insert(out, k) end
+        end
+        return out
+    end
+    local info = Snapshot.dump(api, { "Counter", "Other" }, AT, PATH, { page_size = 2 })
+    assert(info.keys == 7 and pages >= 4)
+    local snap = Snapshot.load(PATH)
+    local prefix = Storage.build_key(snap:get_metadata(), "Counter", "Scores")
+    local first = snap:state_getKeysPaged(prefix, 2)
+    assert(#first == 2 and #snap:state_getKeysPaged(prefix, 10, first[2]) == 3)
+    local scores = Storage.entries(snap, "Counter", "Scores", nil, { page_size = 2 })
+    assert(#scores == 5)
+    local total = 0
+    for _, e in ipairs(scores) do total = total + tonumber(e.value) end
+    assert(total == 1500)
+end)
+
+test("Snapshot: bad input rejected", function()
+    local api = live_api()
+    assert(not pcall(Snapshot.dump, api, {}, AT, PATH), "empty pallet list")
+    assert(not pcall(Snapshot.dump, api, { "Nope" }, AT, PATH), "unknown pallet")
+    assert(not pcall(Snapshot.load, PATH .. ".missing"))
+    local f = io.open(PATH, "wb")
+    f:write("not json")
+    f:close()
+    assert(not pcall(Snapshot.load, PATH))
+end)
+
+os.remove(PATH)
+
+print("\n=== Snapshot Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All snapshot tests passed!")
+    os.exit(0)
+end
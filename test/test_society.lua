@@ -0,0 +1,205 @@
+-- test/test_society.lua
+-- Unit tests for Society and Alliance pallet helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Society = require("sublua.society")
+local Storage = require("sublua.storage")
+local Codec = require("sublua.codec")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Society Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local ALICE = "0x" .. to_hex(alice.pubkey)
+local BOB = "0x" .. to_hex(bob.pubkey)
+
+local TYPES = {
+    { "SocietyCall", { path = { "pallet_society", "pallet", "Call" }, variant = {
+        { name = "bid", index = 0, fields = { { name = "value", type = "u128" } } },
+        { name = "unbid", index = 1 },
+        { name = "vouch", index = 2, fields = {
+            { name = "who", type = "MultiAddress" }, { name = "value", type = "u128" }, { name = "tip", type = "u128" } } },
+        { name = "unvouch", index = 3 },
+        { name = "vote", index = 4, fields = { { name = "candidate", type = "MultiAddress" }, { name = "approve", type = "bool" } } },
+        { name = "defender_vote", index = 5, fields = { { name = "approve", type = "bool" } } },
+        { name = "payout", index = 6 },
+        { name = "claim_membership", index = 13 }
+    } } },
+    { "BidKind", { path = { "pallet_society", "BidKind" }, variant = {
+        { name = "Deposit", index = 0, fields = { { type = "u128" } } },
+        { name = "Vouch", index = 1, fields = { { type = "AccountId32" }, { type = "u128" } } }
+    } } },
+    { "Bid", { path = { "pallet_society", "Bid" }, composite = {
+        { name = "who", type = "AccountId32" }, { name = "kind", type = "BidKind" }, { name = "value", type = "u128" }
+    } } },
+    { "Vec<Bid>", { sequence = "Bid" } },
+    { "MemberRecord", { path = { "pallet_society", "MemberRecord" }, composite = {
+        { name = "rank", type = "u32" }, { name = "strikes", type = "u32" }, { name = "index", type = "u32" }
+    } } },
+    { "Candidacy", { path = { "pallet_society", "Candidacy" }, composite = {
+        { name = "round", type = "u32" }, { name = "bid", type = "u128" }
+    } } },
+    { "AllianceCall", { path = { "pallet_alliance", "pallet", "Call" }, variant = {
+        { name = "vote", index = 1, fields = {
+            { name = "proposal", type = "H256" }, { name = "index", type = "Compact<u32>" }, { name = "approve", type = "bool" } } },
+        { name = "join_alliance", index = 8 },
+        { name = "nominate_ally", index = 9, fields = { { name = "who", type = "MultiAddress" } } },
+        { name = "elevate_ally", index = 10, fields = { { name = "ally", type = "MultiAddress" } } },
+        { name = "give_retirement_notice", index = 11 },
+        { name = "retire", index = 12 }
+    } } },
+    { "MemberRole", { path = { "pallet_alliance", "MemberRole" }, variant = {
+        { name = "Fellow", index = 0 }, { name = "Ally", index = 1 }, { name = "Retiring", index = 2 }
+    } } }
+}
+
+local CURRENT = {
+    types = TYPES,
+    pallets = {
+        { name = "Society", index = 94, calls = "SocietyCall", storage = { prefix = "Society", entries = {
+            { name = "Members", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "MemberRecord" } },
+            { name = "Candidates", map = { hashers = { "Blake2_128Concat" }, key = "AccountId32", value = "Candidacy" } },
+            { name = "Bids", plain = "Vec<Bid>", modifier = 1, default = "\0" },
+            { name = "Head", plain = "AccountId32" },
+            { name = "Founder", plain = "AccountId32" }
+        } } },
+        { name = "Alliance", index = 50, calls = "AllianceCall", storage = { prefix = "Alliance", entries = {
+            { name = "Members", map = { hashers = { "Twox64Concat" }, key = "MemberRole", value = "Vec<AccountId32>" } }
+        } } }
+    }
+}
+
+local LEGACY = {
+    types = TYPES,
+    pallets = {
+        { name = "Society", index = 94, calls = "SocietyCall", storage = { prefix = "Society", entries = {
+            { name = "Members", plain = "Vec<AccountId32>" },
+            { name = "Candidates", plain = "Vec<Bid>" }
+        } } }
+    }
+}
+
+-- Store a value under its key, encoded with the entry's value type
+local function put(api, pallet, entry_name, keys, value)
+    local meta = api:get_metadata()
+    local entry = meta.pallets[pallet].storage.entries[entry_name]
+    api.storage[Storage.build_key(meta, pallet, entry_name, keys)] =
+        "0x" .. to_hex(Codec.encode(meta, entry.storage_type.value, value))
+end
+
+local function call_of(api, signed)
+    return Transaction.decode(api:get_metadata(), signed).call
+end
+
+test("Society: bid and vouch encode from metadata", function()
+    local api = Mock.api({}, CURRENT)
+    local call = call_of(api, Society.bid(api, alice, "1000000000000", { nonce = 0 }))
+    assert(call.pallet == "Society" and call.name == "bid" and call.args.value == "1000000000000")
+    call = call_of(api, Society.vouch(api, alice, bob.address, 500, 10, { nonce = 0 }))
+    assert(call.name == "vouch" and call.args.who.variant == "Id" and call.args.who.value == BOB)
+    assert(call.args.value == "500" and call.args.tip == "10")
+end)
+
+test("Society: vote, defender_vote and payout", function()
+    local api = Mock.api({}, CURRENT)
+    local call = call_of(api, Society.vote(api, alice, BOB, true, { nonce = 0 }))
+    assert(call.name == "vote" and call.args.candidate.value == BOB and call.args.approve == true)
+    call = call_of(api, Society.defender_vote(api, alice, false, { nonce = 0 }))
+    assert(call.name == "defender_vote" and call.args.approve == false)
+    assert(call_of(api, Society.payout(api, alice, { nonce = 0 })).name == "payout")
+    assert(call_of(api, Society.claim_membership(api, alice, { nonce = 0 })).name == "claim_membership")
+end)
+
+test("Society: member queries on the map layout", function()
+    local api = Mock.api({}, CURRENT)
+    put(api, "Society", "Members", { ALICE }, { rank = 1, strikes = 0, index = 0 })
+    put(api, "Society", "Candidates", { BOB }, { round = 4, bid = "25" })
+    put(api, "Society", "Head", {}, ALICE)
+    local members = Society.query_members(api)
+    assert(#members == 1 and members[1].account == ALICE and members[1].record.rank == 1)
+    assert(Society.is_member(api, alice.address) and not Society.is_member(api, bob.address))
+    local candidates = Society.query_candidates(api)
+    assert(#candidates == 1 and candidates[1].account == BOB and candidates[1].candidacy.round == 4)
+    assert(Society.query_head(api) == ALICE)
+    assert(Society.query_founder(api) == nil)
+    assert(#Society.query_bids(api) == 0, "Bids defaults to an empty list")
+end)
+
+test("Society: member queries on the list layout", function()
+    local api = Mock.api({}, LEGACY)
+    put(api, "Society", "Members", {}, { ALICE })
+    put(api, "Society", "Candidates", {}, { { who = BOB, kind = { variant = "Deposit", value = "5" }, value = "7" } })
+    local members = Society.query_members(api)
+    assert(#members == 1 and members[1].account == ALICE and members[1].record == nil)
+    assert(Society.query_member(api, ALICE) == true and Society.query_member(api, BOB) == nil)
+    local candidates = Society.query_candidates(api)
+    assert(candidates[1].account == BOB and candidates[1].candidacy.value == "7")
+end)
+
+test("Society: alliance calls", function()
+    local api = Mock.api({}, CURRENT)
+    assert(call_of(api, Society.alliance_join(api, alice, { nonce = 0 })).name == "join_alliance")
+    local call = call_of(api, Society.alliance_nominate_ally(api, alice, bob.address, { nonce = 0 }))
+    assert(call.pallet == "Alliance" and call.args.who.value == BOB)
+    local hash = "0x" .. string.rep("ab", 32)
+    call = call_of(api, Society.alliance_vote(api, alice, hash, 3, true, { nonce = 0 }))
+    assert(call.name == "vote" and call.args.proposal == hash and call.args.index == 3 and call.args.approve == true)
+    assert(call_of(api, Society.alliance_retire(api, alice, { nonce = 0 })).name == "retire")
+end)
+
+test("Society: alliance members by role", function()
+    local api = Mock.api({}, CURRENT)
+    put(api, "Alliance", "Members", { { variant = "Fellow" } }, { ALICE })
+    put(api, "Alliance", "Members", { { variant = "Ally" } }, { BOB })
+    local members = Society.alliance_members(api)
+    assert(members.Fellow[1] == ALICE and members.Ally[1] == BOB and members.Retiring == nil)
+    assert(Society.alliance_role(api, alice.address) == "Fellow")
+    assert(Society.alliance_role(api, bob.address) == "Ally")
+    assert(Society.alliance_role(api, "0x" .. string.rep("00", 32)) == nil)
+end)
+
+test("Storage: entries lists a map with decoded keys", function()
+    local api = Mock.api({}, CURRENT)
+    put(api, "Society", "Members", { ALICE }, { rank = 1, strikes = 0, index = 0 })
+    put(api, "Society", "Members", { BOB }, { rank = 0, strikes = 2, index = 1 })
+    local entries = Storage.entries(api, "Society", "Members")
+    assert(#entries == 2)
+    local by = {}
+    for _, e in ipairs(entries) do by[e.keys[1]] = e.value end
+    assert(by[ALICE].rank == 1 and by[BOB].strikes == 2)
+    assert(not pcall(Storage.entries, api, "Society", "Head"), "plain values are not maps")
+end)
+
+print("\n=== Society Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All society tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,472 @@
+-- test/test_staking.lua
+-- Unit tests for event filtering and staking monitoring queries
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Staking = require("sublua.staking")
+local Storage = require("sublua.storage")
+local Transaction = require("sublua.transaction")
+local Utility = require("sublua.utility")
+local Call = require("sublua.call")
+local Keyring = require("sublua.keyring")
+local Events = require("sublua.events")
+local Scale = require("sublua.scale")
+local Address = require("sublua.address")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function u128(n)
+    return Scale.encode_u64(n) .. string.rep("\0", 8)
+end
+
+print("=== Staking Monitoring Tests ===\n")
+
+local ALICE = string.rep("\1", 32)
+local BOB = string.rep("\2", 32)
+local CHARLIE_KEY = string.rep("\3", 32)
+
+-- EventRecord { phase, event, topics }
+local function record(phase, event)
+    return phase .. event .. Scale.encode_compact(0)
+end
+
+local SLASHED = "\7\1" .. ALICE .. u128(500)
+local OFFLINE = "\12\2" .. Scale.encode_compact(1) .. BOB
+local TRANSFER = "\4\2" .. ALICE .. BOB .. u128(10)
+
+local function events_hex(list)
+    return "0x" .. to_hex(Scale.encode_compact(#list) .. table.concat(list))
+end
+
+test("Events: storage key", function()
+    local key = Events.storage_key()
+    assert(key == "0x" .. to_hex(crypto.twox128("System")) .. to_hex(crypto.twox128("Events")))
+end)
+
+test("Events: decode records", function()
+    local meta = Mock.metadata()
+    local hex = events_hex({ record("\0" .. Scale.encode_u32(1), TRANSFER), record("\1", SLASHED) })
+    local events = Events.decode(meta, hex)
+    assert(#events == 2)
+    assert(events[1].pallet == "Balances" and events[1].name == "Transfer")
+    assert(events[1].fields.amount == "10")
+    assert(events[1].phase.variant == "ApplyExtrinsic" and events[1].phase.value == 1)
+    assert(events[2].pallet == "Staking" and events[2].name == "Slashed")
+    assert(events[2].fields.staker == "0x" .. to_hex(ALICE))
+    assert(events[1].index == 0 and events[2].index == 1)
+    assert(events[1].extrinsic_index == 1 and events[2].extrinsic_index == nil, "Finalization has no extrinsic")
+    assert(events[2].phase.variant == "Finalization" and #events[2].topics == 0)
+end)
+
+test("Events: accounts in an SS58 format", function()
+    local meta = Mock.metadata()
+    local hex = events_hex({ record("\1", TRANSFER), record("\1", SLASHED), record("\2", OFFLINE) })
+    local events = Events.decode(meta, hex, "polkadot")
+    assert(events[1].fields.from == Address.encode(ALICE, 0) and events[1].fields.to == Address.encode(BOB, 0))
+    assert(events[1].fields.amount == "10")
+    assert(events[2].fields.staker == Address.encode(ALICE, 0))
+    assert(events[3].fields.offline[1][1] == Address.encode(BOB, 0), "nested in tuples and sequences")
+
+    local api = Mock.api({ [Events.storage_key()] = hex })
+    assert(Events.fetch(api)[2].fields.staker == "0x" .. to_hex(ALICE), "hex unless the client sets a format")
+    api.ss58_format = 2
+    assert(Events.fetch(api)[2].fields.staker == Address.encode(ALICE, 2))
+end)
+
+test("Events: slashing preset filter", function()
+    local meta = Mock.metadata()
+    local hex = events_hex({ record("\1", TRANSFER), record("\1", SLASHED), record("\2", OFFLINE) })
+    local matched = Events.filter(Events.decode(meta, hex), "slashing")
+    assert(#matched == 2)
+    assert(matched[1].name == "Slashed" and matched[2].name == "SomeOffline")
+end)
+
+test("Events: pallet-wide filter", function()
+    local meta = Mock.metadata()
+    local hex = events_hex({ record("\1", TRANSFER), record("\1", SLASHED) })
+    local matched = Events.filter(Events.decode(meta, hex), { { pallet = "Balances" } })
+    assert(#matched == 1 and matched[1].name == "Transfer")
+end)
+
+test("Events: unknown preset errors", function()
+    assert(not pcall(Events.filter, {}, "nope"))
+end)
+
+test("Staking: query_offline_events scans range", function()
+    local api = Mock.api()
+    local key = Events.storage_key()
+    local hits = { [3] = true }
+    function api:state_getStorage(k, at)
+        local n = tonumber(at:sub(3), 16)
+        if hits[n] then return events_hex({ record("\1", OFFLINE), record("\1", TRANSFER) }) end
+        return events_hex({ record("\1", TRANSFER) })
+    end
+    local found = Staking.query_offline_events(api, 1, 5)
+    assert(#found == 1)
+    assert(found[1].block_number == 3)
+    assert(found[1].offline[1][1] == "0x" .. to_hex(BOB))
+end)
+
+test("Staking: query_slashes decodes UnappliedSlashes", function()
+    local era = Scale.encode_u32(42)
+    local key = "0x" .. to_hex(crypto.twox128("Staking")) .. to_hex(crypto.twox128("UnappliedSlashes"))
+        .. to_hex(crypto.twox64(era)) .. to_hex(era)
+    local slash = ALICE .. u128(100) .. Scale.encode_compact(1) .. BOB .. u128(5)
+        .. Scale.encode_compact(0) .. u128(0)
+    local api = Mock.api({ [key] = "0x" .. to_hex(Scale.encode_compact(1) .. slash) })
+    local slashes = Staking.query_slashes(api, 42)
+    assert(#slashes == 1)
+    assert(slashes[1].validator == "0x" .. to_hex(ALICE))
+    assert(slashes[1].own == "100")
+    assert(slashes[1].others[1][2] == "5")
+end)
+
+test("Staking: query_slashes empty era", function()
+    local api = Mock.api({})
+    assert(#Staking.query_slashes(api, 1) == 0)
+end)
+
+test("Staking: query_slashes pages a per-slash double map at one block", function()
+    local paged = {
+        types = { { "(u32,AccountId32)", { tuple = { "u32", "AccountId32" } } } },
+        pallets = { {
+            name = "Staking", index = 7,
+            storage = { prefix = "Staking", entries = {
+                { name = "UnappliedSlashes", map = { hashers = { "Twox64Concat", "Twox64Concat" },
+                    key = "(u32,AccountId32)", value = "UnappliedSlash" } }
+            } }
+        } }
+    }
+    local meta = Mock.metadata(paged)
+    local storage = {}
+    for i = 1, 5 do
+        local validator = string.rep(string.char(i), 32)
+        local slash = validator .. u128(i) .. Scale.encode_compact(0) .. Scale.encode_compact(0) .. u128(0)
+        storage[Storage.build_key(meta, "Staking", "UnappliedSlashes", { 42, "0x" .. to_hex(validator) })] = "0x" .. to_hex(slash)
+    end
+    local api = Mock.api(storage, paged)
+    local pinned = {}
+    local list_keys = api.state_getKeysPaged
+    function api:state_getKeysPaged(prefix, count, start, at)
+        table.insert(pinned, at)
+        local out = {}
+        for _, k in ipairs(list_keys(self, prefix)) do
+            if #out < count and (start == nil or k > start) then table.insert(out, k) end
+        end
+        return out
+    end
+    local slashes = Staking.query_slashes(api, 42, { page_size = 2 })
+    assert(#slashes == 5, "every page read, got " .. #slashes)
+    local own = {}
+    for _, slash in ipairs(slashes) do own[slash.own] = true end
+    assert(own["1"] and own["5"])
+    assert(#pinned == 3)
+    for _, at in ipairs(pinned) do assert(at == api:chain_getBlockHash(), "pages read at one block") end
+end)
+
+-- Paged rewards: active era 10, two eras of history kept
+local function payouts_fixture(with_paged_call)
+    local calls = {
+        { name = "payout_stakers", index = 18, fields = {
+            { name = "validator_stash", type = "AccountId32" }, { name = "era", type = "u32" } } }
+    }
+    if with_paged_call then
+        table.insert(calls, { name = "payout_stakers_by_page", index = 26, fields = {
+            { name = "validator_stash", type = "AccountId32" }, { name = "era", type = "u32" }, { name = "page", type = "u32" } } })
+    end
+    return {
+        types = {
+            { "Option<u64>", { path = { "Option" }, variant = {
+                { name = "None", index = 0 }, { name = "Some", index = 1, fields = { { type = "u64" } } }
+            } } },
+            { "ActiveEraInfo", { path = { "pallet_staking", "ActiveEraInfo" }, composite = {
+                { name = "index", type = "u32" }, { name = "start", type = "Option<u64>" }
+            } } },
+            { "PagedExposureMetadata", { path = { "sp_staking", "PagedExposureMetadata" }, composite = {
+                { name = "total", type = "Compact<u128>" }, { name = "own", type = "Compact<u128>" },
+                { name = "nominator_count", type = "u32" }, { name = "page_count", type = "u32" }
+            } } },
+            { "Vec<u32>", { sequence = "u32" } },
+            { "(u32, AccountId32)", { tuple = { "u32", "AccountId32" } } },
+            { "StakingCall", { path = { "pallet_staking", "pallet", "Call" }, variant = calls } },
+            { "Vec<RuntimeCall>", { sequence = "RuntimeCall" } },
+            { "UtilityCall", { path = { "pallet_utility", "pallet", "Call" }, variant = {
+                { name = "batch_all", index = 2, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } }
+            } } }
+        },
+        pallets = {
+            {
+                name = "Staking", index = 7, calls = "StakingCall",
+                storage = { prefix = "Staking", entries = {
+                    { name = "ActiveEra", plain = "ActiveEraInfo" },
+                    { name = "ErasValidatorReward", map = { hashers = { "Twox64Concat" }, key = "u32", value = "u128" } },
+                    { name = "ErasStakersOverview", map = { hashers = { "Twox64Concat", "Twox64Concat" },
+                        key = "(u32, AccountId32)", value = "PagedExposureMetadata" } },
+                    { name = "ClaimedRewards", map = { hashers = { "Twox64Concat", "Twox64Concat" },
+                        key = "(u32, AccountId32)", value = "Vec<u32>" }, modifier = 1, default = "\0" }
+                } },
+                constants = { { name = "HistoryDepth", type = "u32", value = Scale.encode_u32(2) } }
+            },
+            { name = "Utility", index = 26, calls = "UtilityCall" }
+        }
+    }
+end
+
+local function payouts_api(with_paged_call)
+    local fixture = payouts_fixture(with_paged_call)
+    local meta = Mock.metadata(fixture)
+    local stash = "0x" .. to_hex(ALICE)
+    local storage = {
+        [Storage.build_key(meta, "Staking", "ActiveEra")] = "0x" .. to_hex(Scale.encode_u32(10) .. "\0")
+    }
+    local function overview(pages) return "0x" .. to_hex("\0\0" .. Scale.encode_u32(3) .. Scale.encode_u32(pages)) end
+    -- Era 7 is out of history; 8 has two pages with page 0 claimed; 9 one unclaimed page
+    for _, era in ipairs({ 7, 8, 9 }) do
+        storage[Storage.build_key(meta, "Staking", "ErasValidatorReward", { era })] = "0x" .. to_hex(u128(1000))
+        storage[Storage.build_key(meta, "Staking", "ErasStakersOverview", { era, stash })] = overview(era == 8 and 2 or 1)
+    end
+    storage[Storage.build_key(meta, "Staking", "ClaimedRewards", { 8, stash })] = "0x" .. to_hex(Scale.encode_compact(1) .. Scale.encode_u32(0))
+    return Mock.api(storage, fixture), stash
+end
+
+test("Staking: pending_payouts finds unclaimed pages in history", function()
+    local api, stash = payouts_api(true)
+    local pending = Staking.pending_payouts(api, Address.encode(ALICE, 42))
+    assert(#pending == 2)
+    assert(pending[1].era == 8 and pending[1].page == 1 and pending[2].era == 9 and pending[2].page == 0)
+    assert(#Staking.pending_payouts(api, stash, { history_depth = 1 }) == 1)
+    assert(#Staking.pending_payouts(api, "0x" .. to_hex(BOB)) == 0, "no exposure, nothing owed")
+    assert(not pcall(Staking.pending_payouts, Mock.api(), stash), "runtimes without paged rewards")
+end)
+
+test("Staking: payout_pending_rewards batches payout_stakers_by_page", function()
+    local api, stash = payouts_api(true)
+    local alice = Keyring.from_uri("//Alice")
+    local sent = {}
+    local original = Transaction.submit
+    Transaction.submit = function(_, signer, call_bytes, opts)
+        table.insert(sent, { signer = signer, call = call_bytes, opts = opts })
+        return "0x" .. string.rep("ab", 32), { ok = true }
+    end
+    local ok, hashes, reports, pending = pcall(Staking.payout_pending_rewards, api, "//Alice", stash, { tip = 1 })
+    Transaction.submit = original
+    assert(ok, hashes)
+    assert(#hashes == 1 and #reports == 1 and #pending == 2)
+    assert(#sent == 1 and sent[1].signer.address == alice.address and sent[1].opts.tip == 1)
+    local meta = api:get_metadata()
+    local expected = Utility.encode_batch(meta, {
+        Call.encode_call(meta, "Staking", "payout_stakers_by_page", { validator_stash = stash, era = 8, page = 1 }),
+        Call.encode_call(meta, "Staking", "payout_stakers_by_page", { validator_stash = stash, era = 9, page = 0 })
+    })
+    assert(sent[1].call == expected)
+end)
+
+test("Staking: payout_pending_rewards without paged calls or pending eras", function()
+    local api, stash = payouts_api(false)
+    local sent = {}
+    local original = Transaction.submit
+    Transaction.submit = function(_, _, call_bytes)
+        table.insert(sent, call_bytes)
+        return "0x" .. string.rep("cd", 32), {}
+    end
+    local ok, hashes = pcall(Staking.payout_pending_rewards, api, Keyring.from_uri("//Bob"), stash, { history_depth = 1 })
+    local none_ok, none = pcall(Staking.payout_pending_rewards, api, Keyring.from_uri("//Bob"), "0x" .. to_hex(BOB))
+    Transaction.submit = original
+    assert(ok, hashes)
+    assert(#hashes == 1 and #sent == 1, "a single payout is not batched")
+    assert(sent[1] == Call.encode_call(api:get_metadata(), "Staking", "payout_stakers", { validator_stash = stash, era = 9 }))
+    assert(none_ok and #none == 0 and #sent == 1)
+end)
+
+test("Staking: payout_pending_rewards derives a phrase signer with its passphrase", function()
+    local api, stash = payouts_api(true)
+    local phrase = "bottom drive obey lake curtain smoke basket hold race lonely fit walk"
+    local sent = {}
+    local original = Transaction.submit
+    Transaction.submit = function(_, signer, _, opts)
+        table.insert(sent, { signer = signer, opts = opts })
+        return "0x" .. string.rep("ef", 32), {}
+    end
+    local ok, err = pcall(Staking.payout_pending_rewards, api, phrase, stash, { password = "secret" })
+    local plain_ok = pcall(Staking.payout_pending_rewards, api, phrase, stash)
+    Transaction.submit = original
+    assert(ok, err)
+    assert(plain_ok and #sent == 2)
+    assert(sent[1].signer.address == Keyring.from_mnemonic(phrase, { password = "secret" }).address)
+    assert(sent[2].signer.address == Keyring.from_mnemonic(phrase).address)
+    assert(sent[1].signer.address ~= sent[2].signer.address)
+    assert(sent[1].opts.password == nil, "the passphrase is not passed on to the submission")
+end)
+
+-- set_controller(controller: MultiAddress) before the deprecation, set_controller() after
+local function controller_api(legacy, bonded)
+    local fields = legacy and { { name = "controller", type = "MultiAddress" } } or {}
+    local fixture = {
+        types = { { "StakingCall", { path = { "pallet_staking", "pallet", "Call" }, variant = {
+            { name = "set_controller", index = 8, fields = fields } } } } },
+        pallets = { {
+            name = "Staking", index = 7, calls = "StakingCall",
+            storage = { prefix = "Staking", entries = {
+                { name = "Bonded", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "AccountId32" } }
+            } }
+        } }
+    }
+    local storage = {}
+    local meta = Mock.metadata(fixture)
+    for stash, controller in pairs(bonded or {}) do
+        storage[Storage.build_key(meta, "Staking", "Bonded", { "0x" .. to_hex(stash) })] = "0x" .. to_hex(controller)
+    end
+    return Mock.api(storage, fixture)
+end
+
+local function signed_call(api, extrinsic)
+    return Transaction.decode(api:get_metadata(), extrinsic).call
+end
+
+test("Staking: controller awareness follows the runtime", function()
+    local alice = Keyring.from_uri("//Alice")
+    local bob = Keyring.from_uri("//Bob")
+    local legacy = controller_api(true, { [alice.pubkey] = bob.pubkey })
+    local current = controller_api(false, { [alice.pubkey] = bob.pubkey, [bob.pubkey] = bob.pubkey })
+    assert(Staking.uses_controllers(legacy) and not Staking.uses_controllers(current))
+    assert(not Staking.uses_controllers(Mock.metadata()), "no set_controller at all")
+
+    assert(Staking.controller_of(current, alice.address) == "0x" .. to_hex(bob.pubkey))
+    assert(Staking.controller_of(current, "0x" .. to_hex(CHARLIE_KEY)) == nil)
+    assert(Staking.needs_controller_migration(current, alice.address))
+    assert(not Staking.needs_controller_migration(current, bob.address))
+
+    local call = signed_call(legacy, Staking.set_controller(legacy, alice, bob.address))
+    assert(call.name == "set_controller" and call.args.controller.value == "0x" .. to_hex(bob.pubkey))
+    call = signed_call(current, Staking.set_controller(current, alice))
+    assert(call.name == "set_controller" and next(call.args) == nil)
+    local ok, err = pcall(Staking.set_controller, current, alice, bob.address)
+    assert(not ok and err:match("not available on this runtime: controllers are deprecated"))
+    ok, err = pcall(Staking.set_controller, Mock.api(), alice)
+    assert(not ok and err:match("Staking%.set_controller is not available on this runtime"))
+end)
+
+test("Staking: migrate_controller makes the stash its own controller", function()
+    local alice = Keyring.from_uri("//Alice")
+    local bob = Keyring.from_uri("//Bob")
+    local legacy = controller_api(true, { [alice.pubkey] = bob.pubkey, [bob.pubkey] = bob.pubkey })
+    local call = signed_call(legacy, Staking.migrate_controller(legacy, alice))
+    assert(call.args.controller.value == "0x" .. to_hex(alice.pubkey))
+    assert(Staking.migrate_controller(legacy, bob) == nil, "already its own controller")
+    local current = controller_api(false, {})
+    assert(Staking.migrate_controller(current, alice) == nil, "not bonded")
+end)
+
+local PREFS = {
+    types = {
+        { "Compact<Perbill>", { compact = "Perbill" } },
+        { "ValidatorPrefs", { path = { "pallet_staking", "ValidatorPrefs" }, composite = {
+            { name = "commission", type = "Compact<Perbill>" }, { name = "blocked", type = "bool" }
+        } } },
+        { "Vec<MultiAddress>", { sequence = "MultiAddress" } },
+        { "StakingCall", { path = { "pallet_staking", "pallet", "Call" }, variant = {
+            { name = "validate", index = 4, fields = { { name = "prefs", type = "ValidatorPrefs" } } },
+            { name = "kick", index = 22, fields = { { name = "who", type = "Vec<MultiAddress>" } } }
+        } } }
+    },
+    pallets = { {
+        name = "Staking", index = 7, calls = "StakingCall",
+        storage = { prefix = "Staking", entries = {
+            { name = "Nominators", map = { hashers = { "Twox64Concat" }, key = "AccountId32", value = "Nominations" } }
+        } }
+    } }
+}
+
+test("Staking: validate and kick encode validator preferences", function()
+    local api = Mock.api({}, PREFS)
+    local alice = Keyring.from_uri("//Alice")
+    local call = signed_call(api, Staking.validate(api, alice, 2.5, true))
+    assert(call.name == "validate" and call.args.prefs.commission == 25000000 and call.args.prefs.blocked == true)
+    call = signed_call(api, Staking.validate(api, alice, "100", nil))
+    assert(call.args.prefs.commission == 1000000000 and call.args.prefs.blocked == false)
+    local ok, err = pcall(Staking.validate, api, alice, 101)
+    assert(not ok and err == "commission_percent: expected a number from 0 to 100, got number 101")
+
+    call = signed_call(api, Staking.kick(api, alice, { Address.encode(BOB, 42), "0x" .. to_hex(CHARLIE_KEY) }))
+    assert(call.name == "kick" and #call.args.who == 2)
+    assert(call.args.who[1].variant == "Id" and call.args.who[2].value == "0x" .. to_hex(CHARLIE_KEY))
+    ok, err = pcall(Staking.kick, api, alice, { "0x" .. to_hex(BOB), "nobody" })
+    assert(not ok and err:match("^nominators%.2: expected an SS58 address"))
+    assert(not pcall(Staking.validate, Mock.api(), alice, 1), "no Staking calls")
+end)
+
+test("Staking: query_nominators_of filters Nominators by target", function()
+    local meta = Mock.metadata(PREFS)
+    local function nominations(targets)
+        local list = {}
+        for i, t in ipairs(targets) do list[i] = t end
+        return "0x" .. to_hex(Scale.encode_compact(#list) .. table.concat(list) .. Scale.encode_u32(12) .. "\0")
+    end
+    local storage = {
+        [Storage.build_key(meta, "Staking", "Nominators", { "0x" .. to_hex(BOB) })] = nominations({ ALICE, CHARLIE_KEY }),
+        [Storage.build_key(meta, "Staking", "Nominators", { "0x" .. to_hex(CHARLIE_KEY) })] = nominations({ CHARLIE_KEY })
+    }
+    local api = Mock.api(storage, PREFS)
+    local found = Staking.query_nominators_of(api, Address.encode(ALICE, 42))
+    assert(#found == 1 and found[1].who == "0x" .. to_hex(BOB))
+    assert(found[1].targets == 2 and found[1].submitted_in == 12 and found[1].suppressed == false)
+    assert(#Staking.query_nominators_of(api, "0x" .. to_hex(CHARLIE_KEY)) == 2)
+
+    local pinned = {}
+    local list_keys = api.state_getKeysPaged
+    function api:state_getKeysPaged(prefix, count, start, at)
+        table.insert(pinned, at)
+        local out = {}
+        for _, k in ipairs(list_keys(self, prefix)) do
+            if #out < count and (start == nil or k > start) then table.insert(out, k) end
+        end
+        return out
+    end
+    assert(#Staking.query_nominators_of(api, "0x" .. to_hex(CHARLIE_KEY), { page_size = 1 }) == 2)
+    assert(#pinned == 3)
+    for _, at in ipairs(pinned) do assert(at == api:chain_getBlockHash(), "pages read at one block") end
+
+    local token = require("sublua.cancel").token()
+    token:cancel("stop")
+    local ok, err = pcall(Staking.query_nominators_of, api, "0x" .. to_hex(ALICE), { cancel = token })
+    assert(not ok and require("sublua.cancel").is_cancelled(err))
+
+    local index = Staking.nominators_index(api, { at = "0x" .. string.rep("01", 32) })
+    assert(index.at == "0x" .. string.rep("01", 32) and pinned[#pinned] == index.at)
+    local reads = #pinned
+    local of_alice = Staking.query_nominators_of(api, Address.encode(ALICE, 42), { index = index })
+    assert(#of_alice == 1 and of_alice[1].who == "0x" .. to_hex(BOB) and of_alice[1].targets == 2)
+    assert(#Staking.query_nominators_of(api, "0x" .. to_hex(CHARLIE_KEY), { index = index }) == 2)
+    assert(#Staking.query_nominators_of(api, "0x" .. to_hex(BOB), { index = index }) == 0)
+    assert(#pinned == reads, "lookups in the index read no storage")
+end)
+
+print("\n=== Staking Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All staking tests passed!")
+    os.exit(0)
+end
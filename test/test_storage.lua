@@ -0,0 +1,240 @@
+-- test/test_storage.lua
+-- Unit tests for storage key analysis
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Storage = require("sublua.storage")
+local Scale = require("sublua.scale")
+local crypto = require("polkadot_crypto")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function prefix(pallet, entry)
+    return to_hex(crypto.twox128(pallet)) .. to_hex(crypto.twox128(entry))
+end
+
+print("=== Storage Tests ===\n")
+
+local meta = Mock.metadata()
+local ALICE = string.rep("\1", 32)
+
+test("Storage: decode plain key", function()
+    local r = assert(Storage.decode_key(meta, "0x" .. prefix("System", "Number")))
+    assert(r.pallet == "System" and r.entry == "Number")
+    assert(#r.keys == 0 and r.partial == false)
+end)
+
+test("Storage: decode Blake2_128Concat map key", function()
+    local key = "0x" .. prefix("System", "Account") .. to_hex(crypto.blake2b(ALICE, 16)) .. to_hex(ALICE)
+    local r = assert(Storage.decode_key(meta, key))
+    assert(r.pallet == "System" and r.entry == "Account")
+    assert(r.keys[1].hasher == "Blake2_128Concat")
+    assert(r.keys[1].hash == "0x" .. to_hex(crypto.blake2b(ALICE, 16)))
+    assert(r.keys[1].value == "0x" .. to_hex(ALICE))
+end)
+
+test("Storage: decode Twox64Concat map key", function()
+    local era = Scale.encode_u32(42)
+    local key = "0x" .. prefix("Staking", "UnappliedSlashes") .. to_hex(crypto.twox64(era)) .. to_hex(era)
+    local r = assert(Storage.decode_key(meta, key))
+    assert(r.entry == "UnappliedSlashes")
+    assert(r.keys[1].value == 42)
+end)
+
+test("Storage: partial key (prefix only)", function()
+    local r = assert(Storage.decode_key(meta, "0x" .. prefix("System", "Account")))
+    assert(r.partial == true and #r.keys == 0)
+end)
+
+test("Storage: unknown prefix", function()
+    local r, err = Storage.decode_key(meta, "0x" .. string.rep("00", 32))
+    assert(r == nil and err == "Unknown storage prefix")
+end)
+
+test("Storage: trailing bytes rejected", function()
+    local r, err = Storage.decode_key(meta, "0x" .. prefix("System", "Number") .. "00")
+    assert(r == nil and err:match("Trailing"))
+end)
+
+test("Storage: short key rejected", function()
+    local r, err = Storage.decode_key(meta, "0x1234")
+    assert(r == nil and err:match("shorter"))
+end)
+
+test("Storage: build plain key", function()
+    assert(Storage.build_key(meta, "System", "Number") == "0x" .. prefix("System", "Number"))
+end)
+
+test("Storage: build map key", function()
+    local key = Storage.build_key(meta, "System", "Account", { "0x" .. to_hex(ALICE) })
+    assert(key == "0x" .. prefix("System", "Account") .. to_hex(crypto.blake2b(ALICE, 16)) .. to_hex(ALICE))
+    local era = Scale.encode_u32(42)
+    key = Storage.build_key(meta, "Staking", "UnappliedSlashes", { 42 })
+    assert(key == "0x" .. prefix("Staking", "UnappliedSlashes") .. to_hex(crypto.twox64(era)) .. to_hex(era))
+end)
+
+test("Storage: build/decode round trip", function()
+    local key = Storage.build_key(meta, "Staking", "UnappliedSlashes", { 7 })
+    local r = assert(Storage.decode_key(meta, key))
+    assert(r.entry == "UnappliedSlashes" and r.keys[1].value == 7)
+end)
+
+test("Storage: build rejects bad keys", function()
+    assert(not pcall(Storage.build_key, meta, "System", "Number", { 1 }))
+    assert(not pcall(Storage.build_key, meta, "System", "Account", { "0x01", "0x02" }))
+    assert(not pcall(Storage.build_key, meta, "System", "Missing"))
+end)
+
+-- Proof from a live chain (smoldot test vector): one key with an 8-byte value
+local PROOF_ROOT = "0x2b64c6ae01421a5f5d772bf205b09986c14a9fd7860ffc874381151014d361d9"
+local PROOF_KEY = "0xf0c365c3cf59d671eb72da0e7a4113c49f1f0515f462cdcf84e0f1d6045dfcbb"
+local PROOF = {
+    "0x9ec365c3cf59d671eb72da0e7a4113c40003585f078d434d6125b40443fe11fd292d13a41002000000685f0f1f0515f462cdcf84e0f1d6045dfcbb2050527f2977010000",
+    "0x80afbc800f7e8909bdcc1d75f47cc209b5d6776a5b3755926570252e1f2a854865263c42801cba764c6a6fe8cc6a5834da71024c7784accad782c6b8e6ce862cab1956f37980e90a91325f6411d5931c098e385f212838092703c14fa9cf73503dd9046aac98800cfff19df9db65218bb2ae79a521af00e8e6811759db1523173012997c605142801eaec2e3649561ed17ee72b26a9eee30a65213d2817a46a55eba1f1c501d49fc801038139ebcb2c0ea0cfbdd6b77f34a9b6f35246bb7ccaefdb7434dc72f79b9a28011d9e2c3f07190c981b8f0edcc4f44bfa51ddbaa9886a099f526b58353d1f5c28089d9035401e034c770d5962a33d667c2e1e0d25454351f9f52c9036876d46e0780f0fb51be7e503c8b58982799e7b21fb8382c851f2f62ea6b0ff8404e2459099580e94bee78d495df8730aed3dbdfd914acd4ac03ea368237e13f11ffd996fc5d0f805936fe63ca50321b5c3039ae08d32c3a6ccf81f58150aa398250a6fad6289cb5",
+    "0x80410080b6cc473d534c55a61316d4f2ece5335810bfe37dd936071f24b0d36f48dcb5f18095020c1a5f09c173cffd5ada00298c77bda665f44aab35f852714f6e19483e41"
+}
+
+test("Storage: verify read proof", function()
+    assert(Storage.verify_read_proof(PROOF_ROOT, PROOF, PROOF_KEY, "0x50527f2977010000"))
+    local ok, err = Storage.verify_read_proof(PROOF_ROOT, { at = "0x00", proof = PROOF }, PROOF_KEY, "0x50527f2977010001")
+    assert(not ok and err:match("mismatch"))
+    ok, err = Storage.verify_read_proof(PROOF_ROOT, PROOF, PROOF_KEY)
+    assert(not ok and err:match("present"))
+end)
+
+test("Storage: proof of absence and incomplete proofs", function()
+    -- Diverges inside the proven leaf's partial key
+    local absent = PROOF_KEY:sub(1, -3) .. "00"
+    assert(Storage.verify_read_proof(PROOF_ROOT, PROOF, absent))
+    local ok, err = Storage.verify_read_proof(PROOF_ROOT, PROOF, absent, "0x00")
+    assert(not ok and err:match("absent"))
+    -- A different root, or a proof missing the nodes on the path, cannot vouch for anything
+    ok, err = Storage.verify_read_proof("0x" .. string.rep("00", 32), PROOF, PROOF_KEY, "0x50527f2977010000")
+    assert(not ok and err:match("Incomplete proof"))
+    ok, err = Storage.verify_read_proof(PROOF_ROOT, { PROOF[1], PROOF[3] }, PROOF_KEY, "0x50527f2977010000")
+    assert(not ok and err:match("Incomplete proof"))
+end)
+
+test("Storage: get read proof over RPC", function()
+    local api = {}
+    function api:state_getReadProof(keys, at)
+        self.args = { keys, at }
+        return { at = at, proof = PROOF }
+    end
+    local result = Storage.get_read_proof(api, { PROOF_KEY }, "0x1234")
+    assert(api.args[1][1] == PROOF_KEY and api.args[2] == "0x1234")
+    assert(Storage.verify_read_proof(PROOF_ROOT, result, PROOF_KEY, "0x50527f2977010000"))
+end)
+
+test("Storage: batch reader reuses keys and the output table", function()
+    local api = Mock.api()
+    local a, b = "0x" .. string.rep("aa", 32), "0x" .. string.rep("bb", 32)
+    api.storage[Storage.build_key(api, "Staking", "Bonded", { a })] = b
+    local reader = Storage.batch_reader(api, "Staking", "Bonded")
+    local out = { "stale", extra = true }
+    local result = reader:read({ a, b, a }, out)
+    assert(result == out and out.extra == nil)
+    assert(out[1] == b and out[2] == nil and out[3] == b)
+    assert(#api.calls == 1 and api.calls[1][1] == "state_queryStorageAt" and #api.calls[1][2] == 3)
+    assert(reader.cached == 2)
+
+    -- Cached keys are reused; raw mode skips decoding
+    local raw = Storage.batch_reader(api, "Staking", "Bonded", { raw = true, max_cached_keys = 1 })
+    raw:read({ a, b }, out, "0x1234")
+    assert(out[1] == b and out[2] == nil and api.calls[2][3] == "0x1234")
+    assert(raw.cached == 1)
+    assert(#reader:read({}, out) == 0 and #api.calls == 2)
+end)
+
+test("Storage: batch reader falls back to defaults", function()
+    local api = Mock.api(nil, { pallets = { {
+        name = "Counters", index = 90,
+        storage = { prefix = "Counters", entries = {
+            { name = "Count", modifier = 1, default = "\7\0\0\0",
+              map = { hashers = { "Twox64Concat" }, key = "u32", value = "u32" } }
+        } }
+    } } })
+    api.storage[Storage.build_key(api, "Counters", "Count", { 1 })] = "0x09000000"
+    local out = Storage.batch_reader(api, "Counters", "Count"):read({ 1, 2 })
+    assert(out[1] == 9 and out[2] == 7)
+    out = Storage.batch_reader(api, "Counters", "Count", { raw = true }):read({ 2 })
+    assert(out[1] == "0x07000000")
+    assert(not pcall(Storage.batch_reader, api, "Counters", "Nope"))
+end)
+
+test("Storage: diff between two blocks", function()
+    local api = Mock.api()
+    local a, b, c = "0x" .. string.rep("aa", 32), "0x" .. string.rep("bb", 32), "0x" .. string.rep("cc", 32)
+    local bonded = function(who) return Storage.build_key(api, "Staking", "Bonded", { who }) end
+    local issuance = Storage.build_key(api, "Balances", "TotalIssuance")
+    local blocks = {
+        ["0x01"] = { [bonded(a)] = b, [bonded(b)] = c, [issuance] = "0x0a" .. string.rep("00", 15), ["0x3a636f6465"] = "0x00" },
+        ["0x02"] = { [bonded(a)] = c, [bonded(c)] = a, [issuance] = "0x0a" .. string.rep("00", 15), ["0x3a636f6465"] = "0x01" }
+    }
+    function api:state_getKeysPaged(prefix_hex, count, start, at)
+        local keys = {}
+        for k in pairs(blocks[at]) do
+            if k:sub(1, #prefix_hex) == prefix_hex and (not start or k > start) then table.insert(keys, k) end
+        end
+        table.sort(keys)
+        while #keys > count do table.remove(keys) end
+        return keys
+    end
+    function api:state_queryStorageAt(keys, at)
+        local changes = {}
+        for i, k in ipairs(keys) do changes[i] = { k, blocks[at][k] } end
+        return { { block = at, changes = changes } }
+    end
+
+    local diff = Storage.diff(api, { { "Staking", "Bonded" }, { "Balances", "TotalIssuance" }, "0x3a636f6465" },
+        "0x01", "0x02", { page_size = 1 })
+    assert(#diff == 4, "issuance did not change")
+    local by_kind = {}
+    for _, change in ipairs(diff) do by_kind[change.kind] = (by_kind[change.kind] or 0) + 1 end
+    assert(by_kind.added == 1 and by_kind.removed == 1 and by_kind.changed == 2)
+    for _, change in ipairs(diff) do
+        if change.key == bonded(a) then
+            assert(change.pallet == "Staking" and change.entry == "Bonded" and change.keys[1] == a)
+            assert(change.before == b and change.after == c and change.before_raw == b)
+        elseif change.key == bonded(c) then
+            assert(change.kind == "added" and change.before == nil and change.after == a)
+        elseif change.key == "0x3a636f6465" then
+            assert(change.pallet == nil and change.before == "0x00" and change.after == "0x01", "unknown keys stay raw")
+        end
+    end
+    local same = Storage.diff(api, '[{"prefix": "' .. bonded(a):sub(1, 66) .. '"}]', "0x01", "0x01")
+    assert(#same == 0)
+    assert(#Storage.diff(api, { bonded(b):sub(1, 66) }, "0x01", "0x02") == 3, "an entry's key prefix lists the map")
+    assert(not pcall(Storage.diff, api, { { "Staking", "Bonded" } }, "0x01", "0x02", { max_keys = 2 }))
+    local ok, err = pcall(Storage.diff, api, { "nope" }, "0x01", "0x02")
+    assert(not ok and err:match("^targets%.1: expected 0x key hex"))
+end)
+
+print("\n=== Storage Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All storage tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,211 @@
+-- test/test_subscription.lua
+-- Unit tests for finalized block subscriptions across outages (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Subscription = require("sublua.subscription")
+local Storage = require("sublua.storage")
+local Events = require("sublua.events")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Subscription Module Tests ===\n")
+
+-- u128 little-endian hex
+local function u128(n)
+    local hex = string.format("%016x", n)
+    local le = ""
+    for i = #hex - 1, 1, -2 do le = le .. hex:sub(i, i + 1) end
+    return "0x" .. le .. string.rep("00", 8)
+end
+
+-- Chain finalized up to api.best_number; TotalIssuance by block; api.offline fails every call
+local function chain(head, issuance)
+    local api = Mock.api()
+    api.best_number = head
+    local key = Storage.build_key(api, "Balances", "TotalIssuance")
+    local get_storage, get_hash = api.state_getStorage, api.chain_getBlockHash
+    function api:state_getStorage(k, at)
+        if self.offline then error("HTTP request to http://node failed: closed") end
+        local n = tonumber(at:sub(3), 16)
+        if k == key then return issuance[n] and u128(issuance[n]) end
+        return get_storage(self, k, at)
+    end
+    function api:chain_getBlockHash(n)
+        if self.offline then error("HTTP request to http://node failed: closed") end
+        return get_hash(self, n)
+    end
+    local get_header = api.chain_getHeader
+    function api:chain_getHeader(hash)
+        if self.offline then error("HTTP request to http://node failed: closed") end
+        return get_header(self, hash)
+    end
+    return api
+end
+
+test("Subscription: Delivers each finalized block once, in order", function()
+    local api = chain(10, {})
+    local seen, events = {}, 0
+    local sub = Subscription.new(api, {
+        on_block = function(block) table.insert(seen, block.number) end,
+        on_events = function(list) events = events + 1 assert(#list == 0) end
+    })
+    assert(sub:poll() == 0 and sub:cursor() == 10, "starts after the current head")
+    api.best_number = 13
+    assert(sub:poll() == 3 and sub:poll() == 0)
+    assert(table.concat(seen, ",") == "11,12,13" and events == 3)
+end)
+
+test("Subscription: Replays missed blocks after an outage", function()
+    local api = chain(20, {})
+    local seen, resubscribed = {}, {}
+    local sub = Subscription.new(api, {
+        on_block = function(block) table.insert(seen, block.number) end,
+        on_resubscribed = function(info) table.insert(resubscribed, info) end
+    }, { last_block = 18 })
+    assert(sub:poll() == 2)
+    api.offline = true
+    local count, err = sub:poll()
+    assert(count == nil and err:match("failed: closed"))
+    api.best_number = 25
+    assert(sub:poll() == nil and #resubscribed == 0)
+    api.offline = false
+    assert(sub:poll() == 5)
+    assert(#resubscribed == 1 and resubscribed[1].last_block == 20 and resubscribed[1].missed == 5)
+    assert(resubscribed[1].head == 25 and resubscribed[1].error:match("closed"))
+    assert(table.concat(seen, ",") == "19,20,21,22,23,24,25", "no block twice, none skipped")
+end)
+
+test("Subscription: A failing handler gets its block again", function()
+    local api = chain(5, {})
+    local seen, fail_at = {}, 7
+    local sub = Subscription.new(api, { on_block = function(block)
+        if block.number == fail_at then fail_at = nil error("handler crashed") end
+        table.insert(seen, block.number)
+    end }, { last_block = 5, batch_blocks = 2 })
+    api.best_number = 9
+    local ok, err = pcall(sub.poll, sub)
+    assert(not ok and err:match("handler crashed") and sub:cursor() == 6, "block 6 stays delivered")
+    assert(sub:poll() == 2 and sub:poll() == 1)
+    assert(table.concat(seen, ",") == "6,7,8,9")
+end)
+
+test("Subscription: Watched storage reports changes only", function()
+    local api = chain(3, { [3] = 1000, [4] = 1000, [5] = 1500, [7] = 1500 })
+    local changes = {}
+    local sub = Subscription.new(api, {
+        on_storage = function(list, block) changes[block.number] = list end
+    }, { last_block = 3, storage = { { "Balances", "TotalIssuance" } } })
+    api.best_number = 7
+    assert(sub:poll() == 4)
+    assert(changes[4] == nil, "baseline read at the parent block")
+    assert(changes[5][1].entry == "TotalIssuance" and tostring(changes[5][1].value) == "1500")
+    assert(tostring(changes[5][1].previous) == "1000")
+    assert(changes[6][1].value == nil and tostring(changes[6][1].previous) == "1500", "removed")
+    assert(tostring(changes[7][1].value) == "1500")
+end)
+
+test("Subscription: Run loop retries and stops", function()
+    local api = chain(1, {})
+    local sub = Subscription.new(api, {}, { last_block = 0 })
+    local polls, errors, sleeps = 0, 0, 0
+    api.offline = true
+    sub:run({
+        sleep = function() sleeps = sleeps + 1 api.offline = false end,
+        on_error = function() errors = errors + 1 end,
+        stop = function() polls = polls + 1 return polls == 2 end
+    })
+    assert(errors == 1 and sleeps == 1 and sub:cursor() == 1)
+end)
+
+-- Events.fetch stand-in: two events per block, a Transfer and an ExtrinsicSuccess
+local original_fetch = Events.fetch
+Events.fetch = function(api, hash)
+    if api.offline then error("HTTP request to http://node failed: closed") end
+    local n = tonumber(hash:sub(3), 16)
+    return {
+        { index = 0, pallet = "Balances", name = "Transfer", fields = { amount = tostring(n * 10) }, extrinsic_index = 1 },
+        { index = 1, pallet = "System", name = "ExtrinsicSuccess", fields = {}, extrinsic_index = 1 }
+    }
+end
+
+test("Subscription: Queue hands events out until they are acknowledged", function()
+    local api = chain(10, {})
+    local queue = Subscription.queue(api, { last_block = 10, filter = { { pallet = "Balances" } } })
+    api.best_number = 13
+    local events, cursor = queue:events_poll(2)
+    assert(#events == 2 and cursor == 2 and queue:pending() == 3)
+    assert(events[1].block_number == 11 and events[1].name == "Transfer" and events[1].fields.amount == "110")
+    local again = queue:events_poll(2)
+    assert(again[1].seq == 1 and again[2].seq == 2, "unacknowledged events come back")
+    assert(queue:events_ack(cursor) == 1)
+    events, cursor = queue:events_poll(10)
+    assert(#events == 1 and events[1].block_number == 13 and cursor == 3)
+    queue:events_ack(cursor)
+    events, cursor = queue:events_poll()
+    assert(#events == 0 and cursor == nil)
+    assert(not pcall(queue.events_ack, queue, nil))
+end)
+
+test("Subscription: Queue file survives a crash before the ack", function()
+    local path = os.tmpname()
+    os.remove(path)
+    local api = chain(5, {})
+    local queue = Subscription.queue(api, { state_file = path, last_block = 5 })
+    api.best_number = 6
+    local events, cursor = queue:events_poll()
+    assert(#events == 2 and cursor == 2)
+    -- Crash: a new process opens the same file
+    local reopened = Subscription.queue(api, { state_file = path, last_block = 0 })
+    events, cursor = reopened:events_poll()
+    assert(#events == 2 and events[1].block_number == 6 and cursor == 2, "same events, block not refetched")
+    reopened:events_ack(cursor)
+    api.offline = true
+    local err
+    events, cursor, err = Subscription.queue(api, { state_file = path }):events_poll()
+    assert(#events == 0 and err:match("failed: closed"))
+    api.offline = false
+    api.best_number = 7
+    events = Subscription.queue(api, { state_file = path }):events_poll()
+    assert(#events == 2 and events[1].seq == 3 and events[1].block_number == 7)
+    os.remove(path)
+end)
+
+test("Subscription: Queue stops fetching while full", function()
+    local api = chain(0, {})
+    local queue = Subscription.queue(api, { last_block = 0, max_queued = 3 })
+    api.best_number = 5
+    assert(queue:fill() == 10 and queue:pending() == 10, "one fill takes a whole batch")
+    assert(queue:fill() == 0)
+    local _, cursor = queue:events_poll(9)
+    queue:events_ack(cursor)
+    api.best_number = 6
+    assert(queue:fill() == 2 and queue:pending() == 3)
+end)
+
+Events.fetch = original_fetch
+
+print("\n=== Subscription Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All subscription tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,82 @@
+-- test/test_testchain.lua
+-- Unit tests for the local test chain helper (chain spec endowment, node command, attaching)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local TestChain = require("sublua.testchain")
+local RPC = require("sublua.rpc")
+local Keyring = require("sublua.keyring")
+local json = require("cjson")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== TestChain Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+
+test("TestChain: Endowing accounts in a dev chain spec", function()
+    local spec = '{"name": "Development", "genesis": {"runtimeGenesis": {"code": "0x00", "patch": '
+        .. '{"balances": {"balances": [["' .. bob.address .. '", 5]]}, "sudo": {"key": "5Grw"}}}}}'
+    local patched = TestChain.endow(spec, { alice, bob.address })
+    assert(patched:find('["' .. alice.address .. '",1000000000000000000]', 1, true), "exact integer, not 1e+18")
+    local balances = json.decode(patched).genesis.runtimeGenesis.patch.balances.balances
+    assert(#balances == 2 and balances[1][1] == bob.address, "existing entries are replaced")
+    assert(json.decode(patched).genesis.runtimeGenesis.patch.sudo.key == "5Grw")
+
+    local legacy = TestChain.endow('{"genesis": {"runtime": {"system": {}}}}', { alice }, "7")
+    assert(json.decode(legacy).genesis.runtime.balances.balances[1][2] == 7)
+    assert(not pcall(TestChain.endow, '{"genesis": {"raw": {"top": {}}}}', { alice }), "raw specs cannot be endowed")
+    assert(not pcall(TestChain.endow, spec, { alice }, "1e18"))
+end)
+
+test("TestChain: Node command", function()
+    local cmd = TestChain.command({ command = "/opt/my node", port = 9955, args = { "--pool-limit", "100" } }, "/tmp/spec.json")
+    assert(cmd == "'/opt/my node' --dev --rpc-port 9955 --chain '/tmp/spec.json' '--pool-limit' '100'")
+    assert(TestChain.command({ command = "substrate-node", port = 9944 }) == "'substrate-node' --dev --rpc-port 9944")
+end)
+
+test("TestChain: Attaching to a running node", function()
+    local original = RPC.new
+    local polls = 0
+    RPC.new = function(url)
+        return { url = url, chain_getHeader = function()
+            polls = polls + 1
+            if polls == 1 then error("connection refused") end
+            return { number = polls > 2 and "0x3" or "0x0" }
+        end }
+    end
+    local slept = 0
+    local ok, chain = pcall(TestChain.start, { url = "http://127.0.0.1:9988", sleep = function(s) slept = slept + s end })
+    RPC.new = original
+    assert(ok, chain)
+    assert(chain.url == "http://127.0.0.1:9988" and chain.api.url == chain.url and chain.pid == nil)
+    assert(polls == 3 and slept == 1, "waits for the first block")
+    assert(#chain.accounts == 3 and chain.accounts[1].address == alice.address)
+    chain:stop()
+    TestChain.stop(chain)
+end)
+
+print("\n=== TestChain Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All testchain tests passed!")
+    os.exit(0)
+end
@@ -3,7 +3,7 @@
 
 -- Fix paths to work from test directory or root
 package.cpath = "../sublua/?.so;./sublua/?.so;" .. package.cpath
-package.path = "../?.lua;../?/init.lua;./?.lua;./?/init.lua;" .. package.path
+package.path = "../?.lua;../?/init.lua;./?.lua;./?/init.lua;./test/?.lua;" .. package.path
 
 local Transaction = require("sublua.transaction")
 local Keyring = require("sublua.keyring")
@@ -248,6 +248,587 @@ test("Transaction: Different block hash changes signature", function()
     assert(sig1 ~= sig2)
 end)
 
+-- Decoding
+local Mock = require("mock_metadata")
+
+test("Transaction: Decode signed extrinsic", function()
+    local meta = Mock.metadata()
+    local signer = Keyring.from_seed(string.rep("a", 32))
+    local dest = string.rep("\5", 32)
+    local call_hex = "0x0400" .. "00" .. to_hex(dest) .. to_hex(Scale.encode_compact(1000))
+    local props = mock_props()
+    props.tip = 7
+    local signed = Transaction.create_signed(call_hex, signer, 3, props)
+
+    local decoded = Transaction.decode(meta, signed)
+    assert(decoded.version == 4 and decoded.signed == true)
+    assert(decoded.signer.variant == "Id")
+    assert(decoded.signer.value == "0x" .. to_hex(signer.pubkey))
+    assert(decoded.address == signer.address)
+    assert(decoded.signature.variant == "Ed25519")
+    assert(decoded.nonce == 3)
+    assert(decoded.tip == "7")
+    assert(decoded.era.immortal == true)
+    assert(decoded.call.pallet == "Balances")
+    assert(decoded.call.name == "transfer_allow_death")
+    assert(decoded.call.args.dest.value == "0x" .. to_hex(dest))
+    assert(decoded.call.args.value == "1000")
+    assert(decoded.call_hex == call_hex)
+    assert(decoded.hash:match("^0x%x+$") and #decoded.hash == 66)
+end)
+
+test("Transaction: Decode unsigned extrinsic", function()
+    local meta = Mock.metadata()
+    local inner = "\4" .. "\0\0" .. Scale.encode_compact(2) .. "hi"
+    local hex = "0x" .. to_hex(Scale.encode_compact(#inner) .. inner)
+    local decoded = Transaction.decode(meta, hex)
+    assert(decoded.signed == false)
+    assert(decoded.signer == nil)
+    assert(decoded.call.pallet == "System" and decoded.call.name == "remark")
+    assert(decoded.call.args.remark == "0x" .. to_hex("hi"))
+end)
+
+test("Transaction: Decode rejects bad length prefix", function()
+    local meta = Mock.metadata()
+    assert(not pcall(Transaction.decode, meta, "0x2004000000"))
+end)
+
+test("Transaction: Decode mortal era", function()
+    -- period 64, phase 42 encodes as 0xa502 -> bytes a5, 02
+    local era = Transaction.decode_era({ variant = "Mortal165", value = 2 })
+    assert(era.immortal == false)
+    assert(era.period == 64, "period " .. era.period)
+    assert(era.phase == 42, "phase " .. era.phase)
+end)
+
+-- Mortal eras (vectors from sp-runtime's generic::Era tests)
+test("Transaction: Mortal era construction", function()
+    local e = Transaction.mortal_era(42, 64)
+    assert(e.period == 64 and e.phase == 42)
+    e = Transaction.mortal_era(20000, 32768)
+    assert(e.period == 32768 and e.phase == 20000)
+    e = Transaction.mortal_era(513, 200)
+    assert(e.period == 256 and e.phase == 1)
+    e = Transaction.mortal_era(1, 2)
+    assert(e.period == 4 and e.phase == 1)
+    e = Transaction.mortal_era(1000001, 1000000)
+    assert(e.period == 65536 and e.phase == 1000001 % 65536 - 1000001 % 16)
+end)
+
+test("Transaction: Mortal era encoding", function()
+    assert(Transaction.encode_era(nil) == "\0")
+    assert(Transaction.encode_era({ immortal = true }) == "\0")
+    assert(to_hex(Transaction.encode_era(Transaction.mortal_era(42, 64))) == "a502")
+    assert(to_hex(Transaction.encode_era(Transaction.mortal_era(20000, 32768))) == "4e9c")
+    assert(not pcall(Transaction.encode_era, { period = 100, phase = 1 }))
+    assert(not pcall(Transaction.encode_era, { period = 64, phase = 64 }))
+end)
+
+test("Transaction: Mortal era round trip and birth", function()
+    for _, case in ipairs({ { 42, 64 }, { 20000, 32768 }, { 1000001, 65536 }, { 7, 4 } }) do
+        local era = Transaction.mortal_era(case[1], case[2])
+        local bytes = Transaction.encode_era(era)
+        local decoded = Transaction.decode_era({ variant = "Mortal" .. string.byte(bytes, 1), value = string.byte(bytes, 2) })
+        assert(decoded.period == era.period and decoded.phase == era.phase)
+    end
+    local era = Transaction.mortal_era(1000, 64)
+    assert(Transaction.era_birth(era, 1000) == 1000)
+    assert(Transaction.era_birth(era, 1010) == 1000)
+    assert(Transaction.era_birth(era, 1064) == 1064)
+    assert(Transaction.era_birth({ immortal = true }, 1000) == 0)
+end)
+
+test("Transaction: Mortal extrinsic signs over the birth block", function()
+    local meta = Mock.metadata()
+    local signer = Keyring.from_seed(string.rep("a", 32))
+    local props = mock_props()
+    props.era = Transaction.mortal_era(42, 64)
+    props.eraBlockHash = "0x" .. string.rep("33", 32)
+    local signed = Transaction.create_signed("0x0000" .. to_hex(Scale.encode_compact(0)), signer, 0, props, meta)
+    local decoded = Transaction.decode(meta, signed)
+    assert(decoded.era.immortal == false and decoded.era.period == 64 and decoded.era.phase == 42)
+
+    local other = mock_props()
+    other.era = props.era
+    other.eraBlockHash = "0x" .. string.rep("44", 32)
+    assert(Transaction.create_signed("0x0000" .. to_hex(Scale.encode_compact(0)), signer, 0, other, meta) ~= signed)
+end)
+
+test("Transaction: Raw variants skip hex on the way in and out", function()
+    local signer = Keyring.from_seed(string.rep("a", 32))
+    local signed = Transaction.create_signed("0x0400", signer, 0, mock_props())
+    local raw = Transaction.create_signed_raw("\4\0", signer, 0, mock_props())
+    assert(not raw:match("^0x") and "0x" .. to_hex(raw) == signed)
+
+    local api = Mock.api()
+    local call = "\0\0" .. Scale.encode_compact(0)
+    local hex_tx = Transaction.create_signed_from_api(api, signer, call, { nonce = 1 })
+    local raw_tx, info = Transaction.create_signed_from_api(api, signer, call, { nonce = 1, raw = true })
+    assert("0x" .. to_hex(raw_tx) == hex_tx and info.nonce == 1)
+end)
+
+-- Dry run
+local function dry_run_api(responses)
+    local api = Mock.api()
+    api.calls = {}
+    api.chain_getFinalizedHead = function() return "0x" .. string.rep("ab", 32) end
+    api.state_call = function(self, method, data, at)
+        table.insert(self.calls, { method = method, data = data, at = at })
+        return responses[method]
+    end
+    return api
+end
+
+local VALID = "0x00" .. "0a00000000000000" .. "00" .. "04" .. "08" .. "abcd" .. "4000000000000000" .. "01"
+
+test("Transaction: Validate reports ValidTransaction fields", function()
+    local api = dry_run_api({ TaggedTransactionQueue_validate_transaction = VALID })
+    local r = Transaction.validate(api, "0x1234")
+    assert(r.valid == true)
+    assert(r.priority == "10" and r.longevity == "64" and r.propagate == true)
+    assert(#r.requires == 0 and r.provides[1] == "0xabcd")
+    -- source (External) .. extrinsic .. block hash
+    assert(api.calls[1].data == "0x02" .. "1234" .. string.rep("ab", 32))
+end)
+
+test("Transaction: Validate reports invalid transactions", function()
+    local api = dry_run_api({ TaggedTransactionQueue_validate_transaction = "0x010002" })
+    local r = Transaction.validate(api, "0x1234")
+    assert(r.valid == false and r.error.kind == "Invalid" and r.error.reason == "Future")
+    api = dry_run_api({ TaggedTransactionQueue_validate_transaction = "0x01000709" })
+    r = Transaction.validate(api, "0x1234")
+    assert(r.error.reason == "Custom" and r.error.code == 9)
+end)
+
+test("Transaction: Dry run reports dispatch success and module errors", function()
+    local api = dry_run_api({ TaggedTransactionQueue_validate_transaction = VALID, BlockBuilder_apply_extrinsic = "0x0000" })
+    local r = Transaction.dry_run(api, "0x1234")
+    assert(r.valid and r.dispatch.ok == true)
+
+    -- Ok(Err(DispatchError::Module { index: 4, error: [2, 0, 0, 0] }))
+    api = dry_run_api({ TaggedTransactionQueue_validate_transaction = VALID, BlockBuilder_apply_extrinsic = "0x0001030402000000" })
+    r = Transaction.dry_run(api, "0x1234")
+    assert(r.valid and r.dispatch.ok == false)
+    assert(r.dispatch.error.variant == "Module")
+    assert(r.dispatch.module.pallet == "Balances" and r.dispatch.module.name == "InsufficientBalance")
+    assert(r.dispatch.module.docs == "Balance too low to send value.")
+end)
+
+-- External signers
+test("Transaction: External signer produces the same extrinsic", function()
+    local pair = Keyring.from_seed(string.rep("a", 32))
+    local ext = Keyring.from_external(pair.address, function(payload) return pair:sign(payload) end)
+    local call_hex = "0x0000" .. to_hex(Scale.encode_compact(2)) .. "6869"
+    assert(Transaction.create_signed(call_hex, ext, 4, mock_props()) == Transaction.create_signed(call_hex, pair, 4, mock_props()))
+end)
+
+test("Transaction: sr25519 external signer uses MultiSignature::Sr25519", function()
+    local ext = Keyring.from_external(string.rep("\7", 32), function() return string.rep("\9", 64) end, nil, { scheme = "sr25519" })
+    local signed = Transaction.create_signed("0x0000" .. to_hex(Scale.encode_compact(0)), ext, 0, mock_props())
+    local decoded = Transaction.decode(Mock.metadata(), signed)
+    assert(decoded.signature.variant == "Sr25519")
+    assert(decoded.signature.value == "0x" .. string.rep("09", 64))
+end)
+
+-- Signed extension introspection
+test("Transaction: List signed extensions from metadata", function()
+    local exts = Transaction.get_signed_extensions(Mock.metadata())
+    assert(#exts == 9)
+    assert(exts[1].identifier == "CheckNonZeroSender")
+    assert(exts[1].extra_empty and exts[1].additional_empty)
+    assert(exts[2].identifier == "CheckSpecVersion")
+    assert(exts[2].additional_type == "u32" and not exts[2].additional_empty)
+    assert(exts[5].identifier == "CheckMortality")
+    assert(exts[5].extra_type == "CheckMortality" and exts[5].additional_type == "H256")
+    for _, ext in ipairs(exts) do assert(ext.handled, ext.identifier) end
+end)
+
+test("Transaction: Unknown extensions are flagged", function()
+    local meta = Mock.metadata({ extensions = { { "CheckSpecVersion", "()", "u32" }, { "CustomExt", "u32", "()" } } })
+    local exts = Transaction.get_signed_extensions(meta)
+    assert(#exts == 2)
+    assert(exts[2].identifier == "CustomExt" and exts[2].handled == false)
+    assert(exts[2].extra_type == "u32" and exts[2].additional_type == "()")
+end)
+
+-- Metadata-driven signing payload
+test("Transaction: Metadata payload matches default extensions", function()
+    local meta = Mock.metadata()
+    local signer = Keyring.from_seed(string.rep("a", 32))
+    local call_hex = "0x0000" .. to_hex(Scale.encode_compact(2)) .. "6869"
+    local legacy = Transaction.create_signed(call_hex, signer, 5, mock_props())
+    local driven = Transaction.create_signed(call_hex, signer, 5, mock_props(), meta)
+    assert(legacy == driven)
+end)
+
+test("Transaction: Custom extension data is signed and encoded", function()
+    local extra = { extensions = {
+        { "CheckSpecVersion", "()", "u32" },
+        { "CheckNonce", "CheckNonce", "()" },
+        { "CustomExt", "u32", "H256" }
+    } }
+    local meta = Mock.metadata(extra)
+    local props = mock_props()
+    props.nonce = 1
+    props.extensions = { CustomExt = { extra = 77, additional = "0x" .. string.rep("22", 32) } }
+
+    local call = from_hex("0x0000" .. to_hex(Scale.encode_compact(0)))
+    local payload, ext_bytes = Transaction.make_signing_payload(meta, call, props)
+    assert(ext_bytes == Scale.encode_compact(1) .. Scale.encode_u32(77))
+    assert(payload == call .. ext_bytes .. Scale.encode_u32(100) .. string.rep("\34", 32))
+
+    local signer = Keyring.from_seed(string.rep("a", 32))
+    local signed = Transaction.create_signed("0x" .. to_hex(call), signer, 1, props, meta)
+    local decoded = Transaction.decode(meta, signed)
+    assert(decoded.extensions.CustomExt == 77)
+    assert(crypto.ed25519_verify(signer.pubkey, payload, from_hex(decoded.signature.value)))
+end)
+
+test("Transaction: Registered extension hooks are checked against metadata", function()
+    local meta = Mock.metadata({ extensions = {
+        { "CheckSpecVersion", "()", "u32" },
+        { "CheckAppId", "u32", "H256" }
+    } })
+    local app = string.rep("\7", 32)
+    Transaction.register_extension("CheckAppId", function(p)
+        return { extra = Scale.encode_u32(p.app_id), additional = app }
+    end)
+    local props = mock_props()
+    props.app_id = 9
+    local payload, ext_bytes, additional = Transaction.make_signing_payload(meta, "\0\0\0", props)
+    assert(ext_bytes == Scale.encode_u32(9) and additional == Scale.encode_u32(100) .. app)
+    assert(payload == "\0\0\0" .. ext_bytes .. additional)
+    local listed = Transaction.get_signed_extensions(meta)
+    assert(listed[2].identifier == "CheckAppId" and listed[2].handled)
+
+    -- A one-off hook in props.extensions wins over the registered one
+    props.extensions = { CheckAppId = function() return { extra = Scale.encode_u32(1), additional = app:sub(2) } end }
+    local ok, err = pcall(Transaction.make_signing_payload, meta, "\0\0\0", props)
+    assert(not ok and err:match("CheckAppId: additional bytes %(31%) do not match the metadata type H256"))
+
+    Transaction.register_extension("CheckAppId", nil)
+    props.extensions = nil
+    ok, err = pcall(Transaction.make_signing_payload, meta, "\0\0\0", props)
+    assert(not ok and err:match("CheckAppId carries data"))
+end)
+
+test("Transaction: Unhandled extension with data is an error", function()
+    local meta = Mock.metadata({ extensions = { { "CustomExt", "u32", "()" } } })
+    local ok, err = pcall(Transaction.make_signing_payload, meta, "\0\0\0", mock_props())
+    assert(not ok and err:match("CustomExt carries data"))
+end)
+
+-- Signing payload verification (offline signers)
+local BOB_HEX = "0x" .. string.rep("b0", 32)
+local function transfer_payload(meta, value, props)
+    local call = require("sublua.call").encode_call(meta, "Balances", "transfer_keep_alive", {
+        dest = { variant = "Id", value = BOB_HEX }, value = value
+    })
+    props = props or mock_props()
+    props.nonce, props.tip = props.nonce or 7, props.tip or 0
+    return "0x" .. to_hex((Transaction.make_signing_payload(meta, call, props)))
+end
+
+test("Transaction: Decode signing payload", function()
+    local meta = Mock.metadata()
+    local props = mock_props()
+    props.tip, props.era = 3, Transaction.mortal_era(100, 64)
+    props.eraBlockHash = "0x" .. string.rep("22", 32)
+    local decoded = Transaction.decode_signing_payload(meta, transfer_payload(meta, "1000", props))
+    assert(decoded.call.pallet == "Balances" and decoded.call.name == "transfer_keep_alive")
+    assert(decoded.call.args.value == "1000" and decoded.call.args.dest.value == BOB_HEX)
+    assert(decoded.nonce == 7 and decoded.tip == "3")
+    assert(decoded.spec_version == 100 and decoded.tx_version == 1)
+    assert(decoded.genesis_hash == "0x" .. string.rep("00", 32))
+    assert(decoded.era.period == 64 and decoded.block_hash == props.eraBlockHash)
+    assert(decoded.metadata_hash_mode == "Disabled")
+end)
+
+test("Transaction: Verify signing payload against the intended call", function()
+    local meta = Mock.metadata()
+    local expected = {
+        pallet = "Balances", name = "transfer_keep_alive",
+        args = { dest = { variant = "Id", value = BOB_HEX }, value = 1000 },
+        nonce = 7, genesis_hash = "0x" .. string.rep("00", 32), mortal = false
+    }
+    local ok, decoded = Transaction.verify_signing_payload(transfer_payload(meta, "1000"), expected, meta)
+    assert(ok and decoded.call.args.value == "1000")
+    -- The same expectation as JSON text
+    assert(Transaction.verify_signing_payload(transfer_payload(meta, "1000"), require("cjson").encode(expected), meta))
+
+    local err
+    ok, err = Transaction.verify_signing_payload(transfer_payload(meta, "1000000"), expected, meta)
+    assert(not ok and err:match("Argument 'value' mismatch: payload has 1000000, expected 1000"))
+    expected.name = "transfer_allow_death"
+    ok, err = Transaction.verify_signing_payload(transfer_payload(meta, "1000"), expected, meta)
+    assert(not ok and err:match("Call mismatch"))
+    expected.name, expected.nonce = "transfer_keep_alive", 8
+    ok, err = Transaction.verify_signing_payload(transfer_payload(meta, "1000"), expected, meta)
+    assert(not ok and err:match("Field 'nonce' mismatch"))
+    expected.nonce, expected.mortal = nil, true
+    ok, err = Transaction.verify_signing_payload(transfer_payload(meta, "1000"), expected, meta)
+    assert(not ok and err:match("immortal"))
+end)
+
+test("Transaction: Hashed or malformed payloads are rejected", function()
+    local meta = Mock.metadata()
+    local expected = { pallet = "Balances", name = "transfer_keep_alive", args = {} }
+    local ok, err = Transaction.verify_signing_payload("0x" .. string.rep("ab", 32), expected, meta)
+    assert(not ok and err:match("hash"))
+    ok, err = Transaction.verify_signing_payload(transfer_payload(meta, "1000") .. "00", expected, meta)
+    assert(not ok and err:match("Trailing"))
+end)
+
+-- Mock node whose pool answers submissions from a script of errors (nil = accepted)
+local function submit_api(script)
+    local api = Mock.api()
+    api.submitted, api.slept = {}, {}
+    function api:author_submitExtrinsic(hex)
+        table.insert(self.submitted, hex)
+        local err = table.remove(script, 1)
+        if err then error("RPC error: Invalid Transaction: " .. err) end
+        return "0x" .. string.rep("ab", 32)
+    end
+    return api
+end
+
+local submitter = Keyring.from_uri("//Alice")
+local retry = function(api) return { attempts = 3, backoff = 2, sleep = function(s) table.insert(api.slept, s) end } end
+
+test("Transaction: Default signer of the client", function()
+    local api = submit_api({})
+    local ok, err = pcall(Transaction.create_signed_from_api, api, nil, "\0\0\8hi")
+    assert(not ok and err:match("No signer"))
+    api.signer = submitter
+    local signed = Transaction.create_signed_from_api(api, nil, "\0\0\8hi", { nonce = 2 })
+    local decoded = Transaction.decode(api:get_metadata(), signed)
+    assert(decoded.signer.value == "0x" .. to_hex(submitter.pubkey) and tonumber(decoded.nonce) == 2)
+    api.next_index = 5
+    local hash, report = Transaction.submit(api, nil, "\0\0\8hi")
+    assert(hash and report.attempts[1].nonce == 5)
+    local bob = Keyring.from_uri("//Bob")
+    decoded = Transaction.decode(api:get_metadata(), (Transaction.create_signed_from_api(api, bob, "\0\0\8hi", { nonce = 0 })))
+    assert(decoded.signer.value == "0x" .. to_hex(bob.pubkey), "an explicit signer wins")
+end)
+
+test("Transaction: Submit without retry policy", function()
+    local api = submit_api({ "Transaction is outdated" })
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi")
+    assert(hash == nil and #report.attempts == 1 and report.error:match("outdated"))
+    assert(report.attempts[1].reason == "Stale")
+end)
+
+test("Transaction: Retry rebuilds with fresh nonce and era", function()
+    local api = submit_api({ "Transaction is outdated", "Transaction has an ancient birth block" })
+    api.next_index = 4
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { mortality = 64, retry = retry(api) })
+    assert(hash == "0x" .. string.rep("ab", 32) and report.hash == hash)
+    assert(#report.attempts == 3 and #api.submitted == 3)
+    assert(report.attempts[1].reason == "Stale" and report.attempts[2].reason == "AncientBirthBlock")
+    assert(report.attempts[3].hash == hash and report.attempts[3].nonce == 4)
+    assert(report.attempts[1].era.period == 64)
+    assert(api.slept[1] == 2 and api.slept[2] == 4)
+    assert(require("cjson").encode(report))
+end)
+
+test("Transaction: Retry stops on final errors and exhaustion", function()
+    local api = submit_api({ "Inability to pay some fees (e.g. account balance too low)" })
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { retry = retry(api) })
+    assert(hash == nil and #report.attempts == 1 and report.attempts[1].reason == nil)
+    assert(report.error:match("Inability to pay"))
+
+    api = submit_api({ "Transaction is outdated", "Transaction is outdated", "Transaction is outdated" })
+    hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { retry = retry(api) })
+    assert(hash == nil and #report.attempts == 3 and #api.slept == 2)
+end)
+
+test("Transaction: Pooled duplicates are not resubmitted", function()
+    for _, err in ipairs({ "Already Imported", "Priority is too low" }) do
+        local api = submit_api({ err })
+        api.next_index = 7
+        local hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { retry = retry(api) })
+        assert(hash == nil and report.error:match(err), err)
+        assert(#api.submitted == 1 and #report.attempts == 1 and #api.slept == 0, "no second nonce for " .. err)
+        assert(report.attempts[1].reason == nil)
+    end
+end)
+
+test("Transaction: Submit deadline cuts retries short", function()
+    local api = submit_api({ "Transaction is outdated", "Transaction is outdated" })
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { retry = retry(api), timeout_ms = 1000 })
+    assert(hash == nil and #report.attempts == 1 and #api.slept == 0)
+    assert(require("sublua.rpc").is_timeout(report.error) and report.error:match("outdated"))
+end)
+
+-- Chain mock for inclusion: blocks[n] = { extrinsics = { hex }, events = { record } }
+-- The head advances by one block per sleep
+local function chain_api(blocks, head)
+    local api = submit_api({})
+    api.best_number = head
+    function api:chain_getBlock(hash)
+        local n = tonumber(hash:sub(3), 16)
+        local block = blocks[n] or {}
+        return { block = { header = { number = string.format("0x%x", n) }, extrinsics = block.extrinsics or {} } }
+    end
+    function api:state_getStorage(key, at)
+        local block = blocks[tonumber(at:sub(3), 16)] or {}
+        local events = block.events or {}
+        return "0x" .. to_hex(Scale.encode_compact(#events) .. table.concat(events))
+    end
+    return api
+end
+
+local function u128(n) return Scale.encode_u64(n) .. string.rep("\0", 8) end
+local function record(index, event)
+    return "\0" .. Scale.encode_u32(index) .. event .. Scale.encode_compact(0)
+end
+local DISPATCH_INFO = "\0\0\0\0"
+local SUCCESS = "\0\0" .. DISPATCH_INFO
+local FAILED = "\0\1" .. "\3\4\2\0\0\0" .. DISPATCH_INFO
+local function fee_paid(fee, tip)
+    return "\32\0" .. submitter.pubkey .. u128(fee) .. u128(tip)
+end
+
+test("Transaction: Result of an included extrinsic", function()
+    local api = chain_api({
+        [7] = {
+            extrinsics = { "0x0102", "0xABCD" },
+            events = {
+                record(0, SUCCESS),
+                record(1, "\4\2" .. submitter.pubkey .. string.rep("\2", 32) .. u128(10)),
+                record(1, fee_paid(1500, 5)),
+                record(1, SUCCESS)
+            }
+        }
+    }, 7)
+    local result = Transaction.get_result(api, "0xabcd", api:chain_getBlockHash(7))
+    assert(result.block_number == 7 and result.extrinsic_index == 1)
+    assert(result.extrinsic_hash == "0x" .. to_hex(crypto.blake2b("\171\205", 32)))
+    assert(result.success == true and result.error == nil)
+    assert(result.fee_paid == "1500" and result.tip == "5")
+    assert(#result.events == 3 and result.events[1].name == "Transfer")
+    assert(Transaction.get_result(api, "0x0303", api:chain_getBlockHash(7)) == nil)
+end)
+
+test("Transaction: Result reports dispatch errors", function()
+    local api = chain_api({ [3] = { extrinsics = { "0xabcd" }, events = { record(0, fee_paid(9, 0)), record(0, FAILED) } } }, 3)
+    local result = Transaction.get_result(api, "0xabcd", api:chain_getBlockHash(3))
+    assert(result.success == false and result.fee_paid == "9")
+    assert(result.error.error.variant == "Module" and result.error.module.name == "InsufficientBalance")
+end)
+
+test("Transaction: Wait for inclusion polls new blocks", function()
+    local blocks = { [12] = { extrinsics = { "0xabcd" }, events = { record(0, SUCCESS) } } }
+    local api = chain_api(blocks, 10)
+    local slept = 0
+    local sleep = function() slept = slept + 1; api.best_number = api.best_number + 1 end
+    local result = Transaction.wait_for_inclusion(api, "0xabcd", { sleep = sleep })
+    assert(result and result.block_number == 12 and slept == 2)
+
+    api.best_number = 10
+    local missing, err = Transaction.wait_for_inclusion(api, "0xffff", { sleep = sleep, max_blocks = 3 })
+    assert(missing == nil and err:match("within 3 blocks"))
+end)
+
+test("Transaction: Wait for inclusion honours the deadline", function()
+    local api = chain_api({}, 10)
+    local result, err = Transaction.wait_for_inclusion(api, "0xabcd", { timeout_ms = 1000, sleep = function() end })
+    assert(result == nil and require("sublua.rpc").is_timeout(err))
+end)
+
+test("Transaction: Submit can wait for inclusion", function()
+    local blocks = {}
+    local api = chain_api(blocks, 20)
+    function api:author_submitExtrinsic(hex)
+        blocks[21] = { extrinsics = { hex }, events = { record(0, fee_paid(42, 0)), record(0, SUCCESS) } }
+        return "0x" .. string.rep("ab", 32)
+    end
+    local sleep = function() api.best_number = api.best_number + 1 end
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi", { mortality = 64, wait = "inclusion", retry = { sleep = sleep } })
+    assert(hash and report.error == nil)
+    assert(report.result.block_number == 21 and report.result.fee_paid == "42" and report.result.success)
+end)
+
+test("Transaction: In-block result before finality", function()
+    local blocks = {}
+    local api = chain_api(blocks, 30)
+    api.finalized_number = 28
+    local finalized_head = api:chain_getFinalizedHead()
+    function api:chain_getHeader(hash)
+        return { number = string.format("0x%x", hash == finalized_head and self.finalized_number or self.best_number) }
+    end
+    function api:author_submitExtrinsic(hex)
+        blocks[31] = { extrinsics = { hex }, events = { record(0, fee_paid(7, 0)), record(0, FAILED) } }
+        return "0x" .. string.rep("ab", 32)
+    end
+    local hash, report = Transaction.submit(api, submitter, "\0\0\8hi")
+    assert(hash and report.from_block == 30 and report.extrinsic)
+    assert(Transaction.check_in_block_result(api, report) == nil, "not in a block yet")
+
+    api.best_number = 32
+    local result = Transaction.check_in_block_result(api, report)
+    assert(result.block_number == 31 and result.finalized == false)
+    assert(result.success == false and result.error.module.name == "InsufficientBalance")
+
+    local sleep = function() api.finalized_number = api.finalized_number + 1 end
+    local final = Transaction.check_in_block_result(api, report.extrinsic, { finalized = true, sleep = sleep })
+    assert(final.finalized and final.block_number == 31 and api.finalized_number == 31)
+    assert(Transaction.check_in_block_result(api, report).finalized == true)
+end)
+
+test("Transaction: Stuck heuristic follows nonce and era birth", function()
+    local api = submit_api({})
+    api.next_index, api.best_number = 3, 100
+    local tx = Transaction.create_signed_from_api(api, submitter, "\0\0\8hi", { nonce = 3, mortality = 64 })
+    -- signed at block 100 with a 64 block era: born at block 64 + 36 = 100
+    api.best_number = 130
+    local stuck, info = Transaction.is_stuck(api, tx, 20)
+    assert(stuck and info.since_block == 100 and info.waited == 30 and info.nonce == 3)
+    assert(not Transaction.is_stuck(api, tx, 40))
+    api.next_index = 4
+    stuck, info = Transaction.is_stuck(api, tx, 20)
+    assert(not stuck and info.included)
+
+    local immortal = Transaction.create_signed_from_api(api, submitter, "\0\0\8hi", { nonce = 4 })
+    assert(not pcall(Transaction.is_stuck, api, immortal, 10))
+    assert(Transaction.is_stuck(api, immortal, 10, { since_block = 115 }))
+end)
+
+test("Transaction: Replacement keeps the call and nonce with a higher tip", function()
+    local api = submit_api({})
+    local call = require("sublua.call").encode_call(api, "System", "remark", { remark = "0x6869" })
+    local original = Transaction.create_signed_from_api(api, submitter, call, { nonce = 9, tip = 10, mortality = 128 })
+    local hash, info = Transaction.replace_transaction(api, submitter, original, 50)
+    assert(hash == "0x" .. string.rep("ab", 32) and api.submitted[1] == info.extrinsic)
+    local decoded = Transaction.decode(api, info.extrinsic)
+    assert(decoded.nonce == 9 and decoded.tip == "50" and decoded.era.period == 128)
+    assert(decoded.call_hex == "0x" .. to_hex(call))
+    assert(info.replaced == Transaction.decode(api, original).hash)
+
+    local none, err = Transaction.replace_transaction(api, submitter, original, 10)
+    assert(none == nil and err:match("higher than the original tip %(10%)"))
+    none, err = Transaction.replace_transaction(api, Keyring.from_uri("//Bob"), original, 50)
+    assert(none == nil and err:match("Signer does not match"))
+end)
+
+test("Transaction: Pending pool decoded and filtered by sender", function()
+    local api = submit_api({})
+    local bob = Keyring.from_uri("//Bob")
+    local mine = Transaction.create_signed_from_api(api, submitter, "\0\0\8hi", { nonce = 1 })
+    local theirs = Transaction.create_signed_from_api(api, bob, "\0\0\8yo", { nonce = 7 })
+    function api:author_pendingExtrinsics() return { mine, "0xdeadbeef", theirs } end
+
+    local all = Transaction.get_pending_extrinsics(api)
+    assert(#all == 3 and all[2].error and all[2].extrinsic == "0xdeadbeef")
+    assert(all[3].address == bob.address and all[3].nonce == 7 and all[3].extrinsic == theirs)
+
+    local filtered = Transaction.get_pending_extrinsics(api, submitter.address)
+    assert(#filtered == 1 and filtered[1].nonce == 1 and filtered[1].call.name == "remark")
+    local polkadot = require("polkadot_crypto").ss58_encode(bob.pubkey, 0)
+    assert(#Transaction.get_pending_extrinsics(api, polkadot) == 1)
+    assert(not pcall(Transaction.get_pending_extrinsics, api, "nope"))
+end)
+
 print("\n=== Transaction Builder Test Results ===")
 print("Passed: " .. tests_passed)
 print("Failed: " .. tests_failed)
@@ -0,0 +1,128 @@
+-- test/test_txbuilder.lua
+-- Unit tests for the stepwise transaction builder (mocked chain)
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local TxBuilder = require("sublua.txbuilder")
+local Transaction = require("sublua.transaction")
+local Call = require("sublua.call")
+local Keyring = require("sublua.keyring")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== TxBuilder Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+local bob = Keyring.from_uri("//Bob")
+local BOB_HEX = "0x" .. to_hex(bob.pubkey)
+
+local function chain()
+    local api = Mock.api()
+    api.submitted = {}
+    function api:author_submitExtrinsic(hex)
+        table.insert(self.submitted, hex)
+        return "0x" .. string.rep("ab", 32)
+    end
+    return api
+end
+
+test("TxBuilder: Arguments build the same call as encode_call", function()
+    local api = chain()
+    local call = TxBuilder.new(api, "Balances", "transfer_keep_alive")
+        :arg_address("dest", bob.address)
+        :arg_u128("value", "340282366920938463463374607431768211455")
+        :encode()
+    assert(call == Call.encode_call(api, "Balances", "transfer_keep_alive", {
+        dest = { variant = "Id", value = BOB_HEX }, value = "340282366920938463463374607431768211455"
+    }))
+    local same = TxBuilder.new(api, "Balances", "transfer_keep_alive")
+        :arg_variant("dest", "Id", BOB_HEX):arg_u128("value", 1000):encode()
+    assert(same == Call.encode_call(api, "Balances", "transfer_keep_alive", {
+        dest = { variant = "Id", value = BOB_HEX }, value = 1000
+    }))
+    local remark = TxBuilder.new(api, "System", "remark"):arg_bytes("remark", "hi"):encode()
+    assert(remark == TxBuilder.new(api, "System", "remark"):arg_bytes("remark", "0x6869"):encode())
+    assert(remark == Call.encode_call(api, "System", "remark", { remark = "0x6869" }))
+end)
+
+test("TxBuilder: Mistakes fail at the setter that made them", function()
+    local api = chain()
+    local ok, err = pcall(TxBuilder.new, api, "Balances", "transfer_everything")
+    assert(not ok and err:match("Call not found"))
+    local b = TxBuilder.new(api, "Balances", "transfer_keep_alive")
+    ok, err = pcall(b.arg, b, "amount", 1)
+    assert(not ok and err:match("has no argument amount %(arguments: dest, value%)"))
+    ok, err = pcall(b.arg_u128, b, "value", "12.5")
+    assert(not ok and err:match("^Balances.transfer_keep_alive.value: expected a non%-negative integer"))
+    ok, err = pcall(b.arg_u128, b, "value", "340282366920938463463374607431768211456")
+    assert(not ok and err:match("^Balances.transfer_keep_alive.value: 3%d+ exceeds u128$"))
+    ok, err = pcall(b.arg_u128, b, "dest", 1)
+    assert(not ok and err:match("dest: not an unsigned integer"))
+    ok, err = pcall(b.arg_variant, b, "dest", "Key", BOB_HEX)
+    assert(not ok and err:match("unknown variant Key %(variants: Id"))
+    ok, err = pcall(b.arg_address, b, "dest", "not an address")
+    assert(not ok and err:match("^Balances.transfer_keep_alive.dest: Invalid account"))
+    ok, err = pcall(b.arg, b, "dest", { variant = "Id", value = "0x1234" })
+    assert(not ok and err:match("^Balances.transfer_keep_alive.dest: expected 32 bytes"))
+
+    b:arg_address("dest", bob.address)
+    assert(#b:missing() == 1 and b:missing()[1] == "value")
+    ok, err = pcall(b.encode, b)
+    assert(not ok and err == "Balances.transfer_keep_alive is missing arguments: value")
+end)
+
+test("TxBuilder: Sign with tip, era and nonce", function()
+    local api = chain()
+    local signed, details = TxBuilder.new(api, "Balances", "transfer_keep_alive")
+        :arg_address("dest", bob.address):arg_u128("value", 5)
+        :set_tip(7):set_era(64):set_nonce(3)
+        :sign(alice)
+    local decoded = Transaction.decode(api:get_metadata(), signed)
+    assert(decoded.signer.value == "0x" .. to_hex(alice.pubkey))
+    assert(tonumber(decoded.nonce) == 3 and tonumber(decoded.tip) == 7)
+    assert(decoded.era.period == 64 and details.era.period == 64)
+    assert(decoded.call.pallet == "Balances" and decoded.call.args.dest.value == BOB_HEX)
+    assert(not pcall(TxBuilder.set_nonce, TxBuilder.new(api, "System", "remark"), -1))
+end)
+
+test("TxBuilder: Submit through the client", function()
+    local api = chain()
+    api.next_index = 9
+    api.signer = alice
+    local b = TxBuilder.new(api, "System", "remark"):arg_bytes("remark", "gm")
+    local hash, report = b:submit()
+    assert(hash and #api.submitted == 1 and report.attempts[1].nonce == 9)
+    hash, report = b:set_nonce(4):submit(bob)
+    local decoded = Transaction.decode(api:get_metadata(), api.submitted[2])
+    assert(hash and report.attempts[1].nonce == 4)
+    assert(decoded.signer.value == BOB_HEX and tonumber(decoded.nonce) == 4)
+end)
+
+print("\n=== TxBuilder Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All txbuilder tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,144 @@
+-- test/test_types.lua
+-- Unit tests for custom type bundles
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Types = require("sublua.types")
+local Codec = require("sublua.codec")
+local Events = require("sublua.events")
+local RPC = require("sublua.rpc")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Custom Types Tests ===\n")
+
+local ALICE = string.rep("d4", 32)
+
+test("Types: type expressions resolve to new or metadata types", function()
+    local meta = Mock.metadata()
+    assert(Codec.primitive_name(meta, Types.id(meta, "u64")) == "u64")
+    assert(Codec.primitive_name(meta, Types.id(meta, "Balance")) == "u128")
+    assert(Types.id(meta, "DispatchInfo") == Mock.type_id("DispatchInfo"))
+    assert(Types.id(meta, "sp_runtime::DispatchError") == Mock.type_id("DispatchError"))
+    assert(Types.decode(meta, "Vec<u16>", "0x0c010002000300")[3] == 3)
+    local pair = Types.decode(meta, "(u8, Compact<u32>, [u8; 2])", "0x070401ff")
+    assert(pair[1] == 7 and pair[2] == 1)
+    assert(Types.decode(meta, "Option<Text>", "0x010c616263").value == "abc")
+    assert(Types.decode(meta, "Bytes", "0x08beef") ~= nil)
+    local ok, err = pcall(Types.id, meta, "Nope")
+    assert(not ok and err:find("Unknown type 'Nope'"))
+    ok, err = pcall(Types.id, meta, "Call")
+    assert(not ok and err:find("ambiguous"))
+end)
+
+test("Types: structs and enums from a Lua bundle", function()
+    local meta = Types.register(Mock.metadata(), {
+        types = {
+            Kind = { _enum = { "Small", "Large" } },
+            Payload = { _enum = { { "Empty", "Null" }, { "Amount", "Balance" }, { "Owner", "AccountId" } } },
+            Record = { { "owner", "AccountId" }, { "kind", "Kind" }, { "payload", "Vec<Payload>" } }
+        }
+    })
+    local hex = "0x" .. ALICE .. "01" .. "08" .. "00" .. "01" .. "2a" .. string.rep("00", 15)
+    local record = Types.decode(meta, "Record", hex)
+    assert(record.kind.variant == "Large")
+    assert(#record.payload == 2 and record.payload[1].variant == "Empty")
+    assert(record.payload[2].variant == "Amount")
+    -- New types get their bundle name as path
+    assert(Codec.type_name(meta, Types.id(meta, "Record")) == "Record")
+end)
+
+test("Types: unordered Lua structs are rejected", function()
+    local ok, err = pcall(Types.register, Mock.metadata(), {
+        types = { Bad = { a = "u8", b = "u8" } }
+    })
+    assert(not ok and err:find("order is ambiguous"))
+end)
+
+test("Types: JSON bundles keep field and variant order", function()
+    local meta = Types.register(Mock.metadata(), [[{
+        "types": {
+            "Info": { "zeta": "u8", "alpha": "u16", "mode": "Mode", "flag": { "_enum": ["No", "Yes"] } },
+            "Mode": { "_enum": { "Off": "Null", "On": "u32" } }
+        }
+    }]])
+    local info = Types.decode(meta, "Info", "0x01020001" .. "05000000" .. "01")
+    assert(info.zeta == 1 and info.alpha == 2)
+    assert(info.mode.variant == "On" and info.flag.variant == "Yes")
+    local fields = Codec.resolve(meta, Types.id(meta, "Info")).type_def.fields
+    assert(fields[1].name == "zeta" and fields[3].name == "mode")
+    local ok, err = pcall(Types.register, Mock.metadata(), "{ \"types\": { \"A\": }")
+    assert(not ok and err:find("Invalid JSON"))
+end)
+
+test("Types: bundle overrides metadata types used by events", function()
+    local meta = Mock.metadata()
+    -- Pretend the chain emits a different Slashed layout: (staker, amount: u64, era: u32)
+    Types.register(meta, { types = {
+        ["pallet_staking::pallet::Event"] = { _enum = {
+            { "Unused", "Null" },
+            { "Slashed", "(AccountId, u64, u32)" }
+        } }
+    } })
+    local id = Mock.type_id("StakingEvent")
+    assert(Codec.resolve(meta, id).type_def.variants[2].name == "Slashed")
+    local records = "0x04" .. "00" .. "00000000" .. "07" .. "01" .. ALICE
+        .. "0a00000000000000" .. "05000000" .. "00"
+    local events = Events.decode(meta, records)
+    assert(events[1].pallet == "Staking" and events[1].name == "Slashed")
+end)
+
+test("Types: storage overrides retype entries", function()
+    local meta = Types.register(Mock.metadata(), {
+        types = { LegacyAccount = { { "nonce", "u32" }, { "free", "Balance" } } },
+        storage = { ["System.Account"] = "LegacyAccount" }
+    })
+    local entry = meta.pallets.System.storage.entries.Account
+    local value = Codec.decode_hex(meta, entry.storage_type.value, "0x03000000" .. "64" .. string.rep("00", 15))
+    assert(value.nonce == 3 and tostring(value.free) == "100")
+    local ok, err = pcall(Types.register, Mock.metadata(), { storage = { ["System.Nope"] = "u32" } })
+    assert(not ok and err:find("System.Nope"))
+end)
+
+test("Types: RPC re-applies the bundle after each metadata fetch", function()
+    local api = RPC.new("http://127.0.0.1:1", {
+        types = { types = { Tag = "[u8; 4]" } },
+        runtime_check_interval = false
+    })
+    local fetches = 0
+    function api:state_getRuntimeVersion() return { specVersion = 1, transactionVersion = 1 } end
+    function api:state_getMetadata()
+        fetches = fetches + 1
+        return Mock.hex()
+    end
+    local meta = api:get_metadata()
+    assert(Codec.type_name(meta, Types.id(meta, "Tag")) == "Tag")
+    api.metadata = nil
+    meta = api:get_metadata()
+    assert(fetches == 2 and meta.custom_types.defs.Tag == "[u8; 4]")
+end)
+
+print("\n=== Custom Types Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All custom types tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,102 @@
+-- test/test_uos.lua
+-- Unit tests for UOS offline signing payloads
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local UOS = require("sublua.uos")
+local Transaction = require("sublua.transaction")
+local Keyring = require("sublua.keyring")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== UOS Tests ===\n")
+
+local meta = Mock.metadata()
+local GENESIS = "0x" .. string.rep("00", 32)
+local signer = Keyring.from_seed(string.rep("a", 32))
+local call = "\0\0" .. Scale.encode_compact(2) .. "hi"
+
+local function props()
+    return {
+        specVersion = 100, txVersion = 1, nonce = 2, tip = 0,
+        genesisHash = GENESIS, finalizedHash = "0x" .. string.rep("11", 32)
+    }
+end
+
+test("UOS: transaction payload layout", function()
+    local _, extra, additional = Transaction.make_signing_payload(meta, call, props())
+    local payload = UOS.make_signer_payload(call, extra .. additional, signer.address, GENESIS)
+    assert(to_hex(payload:sub(1, 3)) == "530002")
+    assert(payload:sub(4, 35) == signer.pubkey)
+    assert(payload:sub(36) == Scale.encode_compact(#call) .. call .. extra .. additional .. string.rep("\0", 32))
+end)
+
+test("UOS: sr25519 and message payloads", function()
+    local payload = UOS.make_signer_payload(call, "", signer.pubkey, GENESIS, { scheme = "sr25519" })
+    assert(to_hex(payload:sub(1, 3)) == "530102")
+    local msg = UOS.make_message_payload("hello", signer.address, GENESIS)
+    assert(to_hex(msg:sub(1, 3)) == "530003")
+    assert(msg:sub(36, 40) == "hello")
+    assert(not pcall(UOS.make_signer_payload, call, "", signer.address, GENESIS, { scheme = "ecdsa" }))
+end)
+
+test("UOS: multipart frames", function()
+    local frames = UOS.frames(string.rep("x", 2500), 1024)
+    assert(#frames == 3)
+    assert(to_hex(frames[1]:sub(1, 5)) == "0000030000")
+    assert(to_hex(frames[3]:sub(1, 5)) == "0000030002")
+    assert(#frames[3] == 5 + 452)
+    assert(#UOS.frames("short") == 1)
+end)
+
+test("UOS: parse signature QR", function()
+    local sig = string.rep("\7", 64)
+    local parsed = UOS.parse_signature("0x00" .. to_hex(sig))
+    assert(parsed.scheme == "ed25519" and parsed.signature == sig)
+    parsed = UOS.parse_signature("01" .. to_hex(sig))
+    assert(parsed.scheme == "sr25519")
+    parsed = UOS.parse_signature(sig)
+    assert(parsed.scheme == nil and parsed.hex == "0x" .. to_hex(sig))
+    assert(not pcall(UOS.parse_signature, "0x1234"))
+    assert(not pcall(UOS.parse_signature, "0x09" .. to_hex(sig)))
+end)
+
+test("UOS: air-gapped round trip matches online signing", function()
+    local payload, extra = Transaction.make_signing_payload(meta, call, props())
+    -- The device signs the same payload and shows MultiSignature hex
+    local scanned = "0x00" .. to_hex(signer:sign(payload))
+    local sig = UOS.parse_signature(scanned)
+    local offline = Transaction.attach_signature(call, signer.pubkey, sig.signature, extra, sig.scheme)
+    local online = Transaction.create_signed("0x" .. to_hex(call), signer, 2, props(), meta)
+    assert(offline == online)
+end)
+
+print("\n=== UOS Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All UOS tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,165 @@
+-- test/test_utility.lua
+-- Unit tests for Utility pallet helpers
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Utility = require("sublua.utility")
+local Keyring = require("sublua.keyring")
+local crypto = require("polkadot_crypto")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+print("=== Utility Module Tests ===\n")
+
+local alice = Keyring.from_uri("//Alice")
+
+test("Utility: derive_sub_account preimage", function()
+    local addr, pub = Utility.derive_sub_account(alice.address, 1)
+    local expected = crypto.blake2b("modlpy/utilisuba" .. alice.pubkey .. "\1\0", 32)
+    assert(pub == expected)
+    assert(addr == crypto.ss58_encode(expected, 42))
+end)
+
+test("Utility: derive_sub_account is deterministic per index", function()
+    local a0 = Utility.derive_sub_account(alice.address, 0)
+    local a0b = Utility.derive_sub_account(alice.address, 0)
+    local a1 = Utility.derive_sub_account(alice.address, 1)
+    assert(a0 == a0b)
+    assert(a0 ~= a1)
+end)
+
+test("Utility: derive_sub_account keeps or overrides prefix", function()
+    local polkadot_parent = crypto.ss58_encode(alice.pubkey, 0)
+    local addr = Utility.derive_sub_account(polkadot_parent, 7)
+    local _, ver = crypto.ss58_decode(addr)
+    assert(ver == 0)
+    local _, ver2 = crypto.ss58_decode(Utility.derive_sub_account(polkadot_parent, 7, 2))
+    assert(ver2 == 2)
+end)
+
+test("Utility: as_derivative encoding", function()
+    local inner = "\4\0" .. "\0" .. string.rep("\1", 32) .. "\4"
+    local call = Utility.encode_as_derivative(26, 1, 258, inner)
+    assert(to_hex(call:sub(1, 4)) == "1a010201", "got " .. to_hex(call:sub(1, 4)))
+    assert(call:sub(5) == inner)
+end)
+
+test("Utility: as_derivative rejects empty call", function()
+    assert(not pcall(Utility.encode_as_derivative, 26, 1, 0, ""))
+end)
+
+local UTILITY = {
+    types = {
+        { "Vec<RuntimeCall>", { sequence = "RuntimeCall" } },
+        { "UtilityCall", { path = { "pallet_utility", "pallet", "Call" }, variant = {
+            { name = "batch", index = 0, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } },
+            { name = "as_derivative", index = 1, fields = { { name = "index", type = "u16" }, { name = "call", type = "RuntimeCall" } } },
+            { name = "batch_all", index = 2, fields = { { name = "calls", type = "Vec<RuntimeCall>" } } }
+        } } }
+    },
+    pallets = { { name = "Utility", index = 26, calls = "UtilityCall" } }
+}
+
+test("Utility: batch encoding", function()
+    local meta = Mock.metadata(UTILITY)
+    local calls = { "\0\0\4a", "\0\0\4b" }
+    assert(Utility.encode_batch(meta, calls) == "\26\2\8" .. calls[1] .. calls[2])
+    assert(Utility.encode_batch(meta, calls, "batch"):sub(1, 3) == "\26\0\8")
+    assert(not pcall(Utility.encode_batch, meta, calls, "force_batch"))
+    assert(not pcall(Utility.encode_batch, meta, { "" }))
+end)
+
+test("Utility: batch from JSON names the offending field", function()
+    local meta = Mock.metadata(UTILITY)
+    local Call = require("sublua.call")
+    local bob = Keyring.from_uri("//Bob")
+    local transfer = Call.encode_call_json(meta, "Balances", "transfer_keep_alive", { dest = bob.address, value = "10" })
+    local remark = "0x" .. to_hex(Call.encode_call(meta, "System", "remark", { remark = "0x01" }))
+    local json_text = '[{"pallet": "Balances", "call": "transfer_keep_alive", "args": {"dest": "' .. bob.address
+        .. '", "value": "10"}}, "' .. remark .. '"]'
+    local call = Utility.encode_batch_json(meta, json_text)
+    assert(call == Utility.encode_batch(meta, { transfer, Call.encode_call(meta, "System", "remark", { remark = "0x01" }) }))
+
+    local function err_of(...)
+        local ok, err = pcall(Utility.encode_batch_json, meta, ...)
+        assert(not ok)
+        return err
+    end
+    assert(err_of('[{"pallet": "Balances", "call": "transfer_keep_alive", "args": {"dest": "' .. bob.address
+        .. '", "value": "ten"}}]'):match("^calls%.1%.transfer_keep_alive%.value: "))
+    assert(err_of({ remark, { pallet = "Balances", call = "transfer_keep_alive", arg = {} } })
+        :match("^calls%.2%.arg: expected one of pallet, call, args"))
+    assert(err_of({ remark, "0x12" }) == "calls.2: expected 0x call hex or a { pallet, call, args } object, got '0x12'")
+    assert(err_of({ { pallet = "Balances", call = "transfer_everything" } }):match("^calls%.1: unknown variant"))
+    assert(err_of({ [1] = remark, [3] = remark }) == "calls: expected an array of calls, got object")
+    assert(err_of("[1, 2"):match("^calls: expected a JSON array of calls"))
+    local ok, err = pcall(Utility.encode_batch, meta, { "\0\0\4a", 7 })
+    assert(not ok and err == "calls.2: expected raw call bytes, got number 7")
+end)
+
+-- Every call weighs 40% of a Normal extrinsic's ref time; the nonce moves with each submission
+local function batch_api()
+    local api = Mock.api({}, UTILITY)
+    api.submitted, api.next_index = {}, 5
+    function api:state_call(method, data)
+        return "0x" .. to_hex(Scale.encode_compact(600000000000) .. Scale.encode_compact(0) .. string.rep("\0", 17))
+    end
+    function api:author_submitExtrinsic(hex)
+        if self.reject and #self.submitted == self.reject then error("1010: Invalid Transaction: Inability to pay some fees") end
+        table.insert(self.submitted, hex)
+        self.next_index = self.next_index + 1
+        return "0x" .. string.format("%064x", #self.submitted)
+    end
+    return api
+end
+
+test("Utility: submit_batch splits by weight when asked", function()
+    local calls = {}
+    for i = 1, 5 do calls[i] = "\0\0\4" .. string.char(96 + i) end
+    local api = batch_api()
+    local hashes, reports = Utility.submit_batch(api, alice, calls)
+    assert(#hashes == 1 and #api.submitted == 1 and reports[1].attempts[1].nonce == 5)
+
+    api = batch_api()
+    hashes, reports = Utility.submit_batch(api, alice, calls, { auto_split = true })
+    assert(#hashes == 3 and hashes[3] == "0x" .. string.format("%064x", 3))
+    assert(reports[1].attempts[1].nonce == 5 and reports[3].attempts[1].nonce == 7)
+    local first = require("sublua.transaction").create_signed_from_api(api, alice,
+        Utility.encode_batch(api, { calls[1], calls[2] }), { nonce = 5 })
+    assert(api.submitted[1] == first)
+
+    api = batch_api()
+    api.reject = 1
+    hashes, reports = Utility.submit_batch(api, alice, calls, { auto_split = true })
+    assert(hashes == nil and #reports == 2 and reports[1].hash and reports[2].error:match("Inability to pay"))
+end)
+
+print("\n=== Utility Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All utility tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,103 @@
+-- test/test_validate.lua
+-- Unit tests for strict argument validation and its error paths
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Validate = require("sublua.validate")
+local Keyring = require("sublua.keyring")
+local json = require("cjson")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Validate Module Tests ===\n")
+
+local function err_of(fn, ...)
+    local ok, err = pcall(fn, ...)
+    assert(not ok, "expected an error")
+    return err
+end
+
+test("Validate: Lists have no holes or other keys", function()
+    local list, n = Validate.list({ "a", "b" }, "calls")
+    assert(n == 2 and list[2] == "b")
+    assert(select(2, Validate.list({}, "calls")) == 0)
+    assert(err_of(Validate.list, { "a", nil, "c" }, "calls") == "calls: expected an array, got object")
+    assert(err_of(Validate.list, { "a", x = 1 }, "calls") == "calls: expected an array, got object")
+    assert(err_of(Validate.list, "a", "calls") == "calls: expected an array, got 'a'")
+    assert(err_of(Validate.list, {}, "calls", { min = 1, what = "an array of calls" })
+        == "calls: expected an array of calls of at least 1, got empty table")
+end)
+
+test("Validate: Text, integers and accounts", function()
+    assert(Validate.text("Alice", "info.display", { max_bytes = 32 }) == "Alice")
+    assert(Validate.text(json.null, "info.web", { optional = true }) == nil)
+    assert(err_of(Validate.text, string.rep("x", 33), "info.display", { max_bytes = 32 })
+        == "info.display: expected text of at most 32 bytes, got '" .. string.rep("x", 33) .. "' (33 bytes)")
+    assert(err_of(Validate.text, nil, "info.display") == "info.display: expected text, got null")
+    assert(Validate.integer("3", "threshold", { min = 1 }) == 3)
+    assert(err_of(Validate.integer, 1.5, "threshold") == "threshold: expected an integer, got number 1.5")
+    assert(err_of(Validate.integer, 0, "threshold", { min = 1, max = 3 })
+        == "threshold: expected an integer from 1 to 3, got number 0")
+    local alice = Keyring.from_uri("//Alice")
+    local hex = "0x" .. alice.pubkey:gsub(".", function(c) return string.format("%02x", c:byte()) end)
+    assert(Validate.account(alice.address, "who") == hex)
+    assert(Validate.account(hex:upper():gsub("^0X", "0x"), "who") == hex)
+    assert(err_of(Validate.account, 42, "signatories.1")
+        == "signatories.1: expected an SS58 address or 0x public key, got number 42")
+end)
+
+test("Validate: Objects reject unknown fields at their path", function()
+    assert(Validate.object({}, "info", { "display" }))
+    assert(err_of(Validate.object, { dispaly = "A" }, "info", { "display", "web" })
+        == "info.dispaly: expected one of display, web, got 'A' (unknown field)")
+    assert(err_of(Validate.object, { 1, 2 }, "info") == "info: expected an object, got array of 2")
+    assert(Validate.decode('{"display": "A"}', "info").display == "A")
+    assert(err_of(Validate.decode, "[1, 2", "info", "a JSON object")
+        == "info: expected a JSON object, got '[1, 2' (not valid JSON)")
+end)
+
+test("Validate: Nested errors keep their field path", function()
+    assert(Validate.within("calls.2", function(a, b) return a + b, "more" end, 1, 2) == 3)
+    assert(err_of(Validate.within, "calls.2", function() error("transfer_keep_alive.dest: invalid account", 0) end)
+        == "calls.2.transfer_keep_alive.dest: invalid account")
+    assert(err_of(Validate.within, "calls.2", function() error("Call not found: Balances.x") end)
+        == "calls.2: Call not found: Balances.x", "source position dropped")
+end)
+
+test("Validate: Errors as data and JSON", function()
+    local parsed = Validate.parse("signatories.2: expected an SS58 address or 0x public key, got '5Typo' (Invalid SS58)")
+    assert(parsed.path == "signatories.2" and parsed.expected == "an SS58 address or 0x public key")
+    assert(parsed.got == "'5Typo' (Invalid SS58)")
+    parsed = Validate.parse("transfer_keep_alive.value: u128 expects a number or decimal string, got 'ten'")
+    assert(parsed.path == "transfer_keep_alive.value" and parsed.expected == nil)
+    parsed = Validate.parse("Cancelled: scan (quit)")
+    assert(parsed.path == nil and parsed.message == "Cancelled: scan (quit)")
+    local decoded = json.decode(Validate.error_json("info.web: expected text, got number 7"))
+    assert(decoded.error == "info.web: expected text, got number 7" and decoded.path == "info.web")
+    assert(decoded.expected == "text" and decoded.got == "number 7")
+    assert(json.decode(Validate.error_json("boom")).path == json.null)
+end)
+
+print("\n=== Validate Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All validate tests passed!")
+    os.exit(0)
+end
@@ -0,0 +1,114 @@
+-- test/test_vrf.lua
+-- Unit tests for VRF proofs on account keys
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Vrf = require("sublua.vrf")
+local crypto = require("polkadot_crypto")
+local Keyring = require("sublua.keyring")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== VRF Tests ===\n")
+
+local PLAYER = Keyring.from_uri("//Alice")
+
+local function to_hex(s)
+    return (s:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- RFC 9381 ECVRF-EDWARDS25519-SHA512-TAI, examples 16 and 17
+local RFC_VECTORS = {
+    {
+        sk = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        pk = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        alpha = "",
+        pi = "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d97"
+            .. "27d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+        beta = "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af02679"
+            .. "8e8f81cd2e333de5cdf4f3e140fdd8ae"
+    },
+    {
+        sk = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        pk = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        alpha = "72",
+        pi = "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465"
+            .. "301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+        beta = "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848"
+            .. "a0bef4553a41befc57663b56373a5031"
+    }
+}
+
+test("VRF: RFC 9381 known-answer vectors", function()
+    for i, v in ipairs(RFC_VECTORS) do
+        local pi, beta = crypto.vrf_prove(from_hex(v.sk), from_hex(v.alpha))
+        assert(to_hex(pi) == v.pi, "example " .. (15 + i) .. " proof")
+        assert(to_hex(beta) == v.beta, "example " .. (15 + i) .. " output")
+        assert(crypto.vrf_verify(from_hex(v.pk), from_hex(v.alpha), from_hex(v.pi)) == from_hex(v.beta))
+        assert(crypto.vrf_verify(from_hex(v.pk), from_hex(v.alpha) .. "x", from_hex(v.pi)) == nil)
+    end
+end)
+
+test("VRF: sign and verify with a player key", function()
+    local result = Vrf.sign(PLAYER, "dice", "round-1")
+    assert(#result.output == 130 and #result.proof == 162)
+    assert(Vrf.sign(PLAYER.seed, "dice", "round-1").output == result.output)
+    assert(Vrf.verify(PLAYER.address, "dice", "round-1", result.proof) == result.output)
+    assert(Vrf.verify(PLAYER.pubkey, "dice", "round-1", result.proof) == result.output)
+    assert(Vrf.verify(PLAYER.address, "dice", "round-2", result.proof) == nil)
+    assert(Vrf.verify(Keyring.from_uri("//Bob").address, "dice", "round-1", result.proof) == nil)
+    assert(Vrf.sign(PLAYER, "dice", "round-2").output ~= result.output)
+end)
+
+test("VRF: contexts separate domains", function()
+    -- Same bytes split differently between context and message
+    assert(Vrf.sign(PLAYER, "ab", "c").output ~= Vrf.sign(PLAYER, "a", "bc").output)
+    local r = Vrf.sign(PLAYER, "cards", "0x0102")
+    assert(Vrf.verify(PLAYER.address, "cards", "\1\2", r.proof) == r.output)
+    assert(Vrf.verify(PLAYER.address, "dice", "0x0102", r.proof) == nil)
+end)
+
+test("VRF: outputs to ranges", function()
+    local seen = {}
+    for i = 1, 50 do
+        local roll = Vrf.to_range(Vrf.sign(PLAYER, "dice", "round-" .. i).output, 6)
+        assert(roll >= 1 and roll <= 6 and roll % 1 == 0)
+        seen[roll] = true
+    end
+    assert(seen[1] and seen[6])
+    assert(Vrf.to_range("0x" .. string.rep("00", 64), 6) == 1)
+    assert(not pcall(Vrf.to_range, "0x00", 0))
+end)
+
+test("VRF: secure pairs cannot prove", function()
+    assert(not pcall(Vrf.sign, Keyring.from_uri("//Alice", { secure = true }), "dice", "x"))
+    assert(not pcall(Vrf.sign, "0x1234", "dice", "x"))
+end)
+
+print("\n=== VRF Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All VRF tests passed!")
+    os.exit(0)
+end
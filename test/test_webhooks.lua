@@ -0,0 +1,151 @@
+-- test/test_webhooks.lua
+-- Unit tests for the chain event webhook dispatcher
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local json = require("cjson")
+local Webhooks = require("sublua.webhooks")
+local Keyring = require("sublua.keyring")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Webhook Tests ===\n")
+
+local ALICE = Keyring.from_uri("//Alice")
+local BOB = Keyring.from_uri("//Bob")
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function u128(n) return Scale.encode_u64(n) .. string.rep("\0", 8) end
+local function record(index, event)
+    return "\0" .. Scale.encode_u32(index) .. event .. Scale.encode_compact(0)
+end
+local TRANSFER = "\4\2" .. ALICE.pubkey .. BOB.pubkey .. u128(10)
+local SLASHED = "\7\1" .. BOB.pubkey .. u128(50)
+
+-- Chain mock: events[n] = { record }, finalized head at api.finalized
+local function chain_api(events, finalized)
+    local api = Mock.api()
+    api.finalized = finalized
+    function api:chain_getHeader() return { number = string.format("0x%x", self.finalized) } end
+    function api:state_getStorage(key, at)
+        local list = events[tonumber(at:sub(3), 16)] or {}
+        return "0x" .. to_hex(Scale.encode_compact(#list) .. table.concat(list))
+    end
+    return api
+end
+
+-- Transport that records POSTs and answers with the next queued status (200 by default)
+local function recorder(statuses)
+    local sent = {}
+    return sent, function(url, body, headers)
+        table.insert(sent, { url = url, body = body, headers = headers })
+        local status = table.remove(statuses or {}, 1)
+        if status == "down" then return nil, "connection refused" end
+        return status or 200
+    end
+end
+
+test("Webhooks: HMAC-SHA256 signatures", function()
+    -- RFC 4231 test case 2
+    assert(Webhooks.sign("Jefe", "what do ya want for nothing?")
+        == "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+    local sig = Webhooks.sign("secret", "{}")
+    assert(Webhooks.verify("secret", "{}", sig))
+    assert(not Webhooks.verify("other", "{}", sig))
+    assert(not Webhooks.verify("secret", "{} ", sig))
+    assert(not Webhooks.verify("secret", "{}", nil))
+end)
+
+test("Webhooks: matching events are posted once, signed", function()
+    local api = chain_api({ [3] = { record(0, TRANSFER), record(1, SLASHED) }, [4] = { record(0, TRANSFER) } }, 4)
+    local sent, post = recorder()
+    local hooks = Webhooks.new(api, { start_block = 2, post = post })
+    local id = hooks:register("https://app.example/hook", '{"events":[{"pallet":"Balances","name":"Transfer"}]}', "s3cret")
+    hooks:register("https://app.example/bob", { address = BOB.address }, "other")
+
+    local report = hooks:dispatch()
+    assert(report.blocks == 3 and report.delivered == 5 and report.failed == 0)
+    assert(#sent == 5 and sent[1].url == "https://app.example/hook" and sent[2].url == "https://app.example/bob")
+    local payload = json.decode(sent[1].body)
+    assert(payload.hook == id and payload.block_number == 3 and payload.event_index == 0 and payload.extrinsic_index == 0)
+    assert(payload.phase == "ApplyExtrinsic" and type(payload.topics) == "table")
+    assert(payload.pallet == "Balances" and payload.name == "Transfer" and payload.fields.amount == "10")
+    assert(Webhooks.verify("s3cret", sent[1].body, sent[1].headers[Webhooks.SIGNATURE_HEADER]))
+    assert(json.decode(sent[3].body).name == "Slashed")
+
+    assert(hooks:dispatch().blocks == 0 and #sent == 5)
+end)
+
+test("Webhooks: failed deliveries are retried then dropped", function()
+    local api = chain_api({ [1] = { record(0, TRANSFER) } }, 1)
+    local sent, post = recorder({ 500, "down", 200 })
+    local hooks = Webhooks.new(api, { start_block = 1, post = post, max_attempts = 3 })
+    hooks:register("https://app.example/hook", { events = { { pallet = "Balances" } } }, "s")
+    local report = hooks:dispatch()
+    assert(report.failed == 1 and report.delivered == 0)
+    report = hooks:dispatch()
+    assert(report.failed == 1 and hooks.state.pending[1].error == "connection refused")
+    report = hooks:dispatch()
+    assert(report.delivered == 1 and #hooks.state.pending == 0 and #sent == 3)
+
+    sent, post = recorder({ 500, 500 })
+    hooks = Webhooks.new(chain_api({ [1] = { record(0, TRANSFER) } }, 1), { start_block = 1, post = post, max_attempts = 2 })
+    hooks:register("https://app.example/hook", { events = { { pallet = "Balances" } } }, "s")
+    hooks:dispatch()
+    report = hooks:dispatch()
+    assert(report.dropped == 1 and #hooks.state.pending == 0)
+end)
+
+test("Webhooks: state file survives between requests", function()
+    local path = os.tmpname()
+    os.remove(path)
+    local id = Webhooks.register_webhook(path, "https://app.example/hook", { events = { { pallet = "Staking" } } }, "s")
+    local events = { [5] = { record(0, SLASHED) }, [6] = { record(0, SLASHED) } }
+    local api = chain_api(events, 5)
+
+    local sent, post = recorder()
+    local report = Webhooks.new(api, { state_file = path, start_block = 5, post = post }):dispatch()
+    assert(report.delivered == 1)
+    api.finalized = 6
+    local hooks = Webhooks.new(api, { state_file = path, post = post })
+    assert(hooks:dispatch().delivered == 1 and json.decode(sent[2].body).block_number == 6)
+    assert(hooks:unregister(id) and not hooks:unregister(id))
+    assert(#Webhooks.new(api, { state_file = path }):hooks() == 0)
+    os.remove(path)
+end)
+
+test("Webhooks: registration checks", function()
+    local hooks = Webhooks.new(chain_api({}, 1))
+    assert(not pcall(hooks.register, hooks, "ftp://x", { address = BOB.address }, "s"))
+    assert(not pcall(hooks.register, hooks, "https://x", { address = BOB.address }, ""))
+    assert(not pcall(hooks.register, hooks, "https://x", { calls = { { pallet = "Balances" } } }, "s"))
+end)
+
+print("\n=== Webhook Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Webhook tests passed!")
+    os.exit(0)
+end
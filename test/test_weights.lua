@@ -0,0 +1,102 @@
+-- test/test_weights.lua
+-- Unit tests for call weight queries and block weight limits
+
+package.cpath = "./sublua/?.so;" .. package.cpath
+package.path = "./?.lua;./?/init.lua;./test/?.lua;" .. package.path
+
+local Weights = require("sublua.weights")
+local Scale = require("sublua.scale")
+local Mock = require("mock_metadata")
+
+local passed = 0
+local failed = 0
+
+local function test(name, fn)
+    local ok, err = pcall(fn)
+    if ok then
+        passed = passed + 1
+        print("✅ " .. name)
+    else
+        failed = failed + 1
+        print("❌ " .. name .. ": " .. tostring(err))
+    end
+end
+
+print("=== Weight Tests ===\n")
+
+local function to_hex(str)
+    return (str:gsub(".", function(c) return string.format("%02x", string.byte(c)) end))
+end
+
+local function from_hex(hex)
+    hex = hex:gsub("^0x", "")
+    return (hex:gsub("..", function(cc) return string.char(tonumber(cc, 16)) end))
+end
+
+-- RuntimeDispatchInfo { weight, class, partial_fee } for every call, keyed by call bytes
+local function weight_api(weights)
+    local api = Mock.api()
+    api.queries = {}
+    function api:state_call(method, data, at)
+        assert(method == "TransactionPaymentCallApi_query_call_info", method)
+        local input = from_hex(data)
+        local call, len = input:sub(1, -5), input:sub(-4)
+        assert(len == Scale.encode_u32(#call), "length suffix")
+        table.insert(self.queries, call)
+        local w = weights[call] or weights.default
+        return "0x" .. to_hex(Scale.encode_compact(w[1]) .. Scale.encode_compact(w[2]) .. string.char(w.class or 0)
+            .. Scale.encode_u64(w.fee or 0) .. string.rep("\0", 8))
+    end
+    return api
+end
+
+test("Weights: block limits from System.BlockWeights", function()
+    local limits = Weights.block_limits(Mock.metadata())
+    assert(limits.max_block.ref_time == 2000000000000 and limits.max_block.proof_size == 5242880)
+    assert(limits.normal.max_extrinsic.ref_time == 1479875000000)
+    assert(limits.operational.reserved.ref_time == 500000000000)
+    assert(limits.mandatory.max_extrinsic == nil and limits.mandatory.base_extrinsic.ref_time == 125000000)
+end)
+
+test("Weights: query a call by name", function()
+    local api = weight_api({ default = { 190000000, 3593, fee = 15000000 } })
+    local info = Weights.query_call_weight(api, "Balances", "transfer_keep_alive",
+        '{"dest":{"variant":"Id","value":"0x' .. string.rep("22", 32) .. '"},"value":5}')
+    assert(info.ref_time == 190000000 and info.proof_size == 3593 and info.class == "Normal")
+    assert(info.partial_fee == "15000000" and info.fits and info.limit.ref_time == 1479875000000)
+    assert(api.queries[1]:sub(1, 2) == "\4\3")
+
+    api = weight_api({ default = { 1600000000000, 10, class = 1 } })
+    info = Weights.query_call_weight(api, "System", "remark", { remark = "0x00" })
+    assert(info.class == "Operational" and info.fits and info.limit.ref_time == 1979875000000)
+    api = weight_api({ default = { 1600000000000, 10 } })
+    assert(not Weights.query_call_weight(api, "System", "remark", { remark = "0x00" }).fits)
+end)
+
+test("Weights: pack calls into batches under the budget", function()
+    local heavy, light = "\0\0\4a", "\0\0\4b"
+    local api = weight_api({ [heavy] = { 600000000000, 1000 }, [light] = { 100000000000, 2000000 } })
+    local batches, totals = Weights.pack(api, { heavy, heavy, light, heavy, light, light })
+    -- budget: 0.9 * 1479875000000 ref time, 0.9 * 3879731 proof size
+    -- the third heavy call breaks the ref time budget, the third light one the proof size budget
+    assert(#batches == 3, #batches)
+    assert(#batches[1] == 3 and #batches[2] == 2 and #batches[3] == 1)
+    assert(batches[1][3] == light and batches[2][1] == heavy and batches[3][1] == light)
+    assert(totals[1].ref_time == 1300000000000 and totals[2].proof_size == 2001000)
+    assert(#Weights.pack(api, {}) == 0)
+
+    api = weight_api({ default = { 1400000000000, 0 } })
+    assert(not pcall(Weights.pack, api, { heavy }))
+    assert(#Weights.pack(api, { heavy }, { ratio = 1 }) == 1)
+end)
+
+print("\n=== Weight Test Results ===")
+print("Passed: " .. passed)
+print("Failed: " .. failed)
+
+if failed > 0 then
+    os.exit(1)
+else
+    print("🎉 All Weight tests passed!")
+    os.exit(0)
+end